@@ -0,0 +1,159 @@
+// Timing for the benchmarks in this directory. Every benchmark runs
+// repeatedly for a warm-up, then in SAMPLES batches sized to take about
+// SAMPLE_TIME each, and reports the median time per run. Arguments after
+// `cargo bench --`:
+//
+//   <filter>          only benchmarks whose name contains it
+//   --save-baseline   keep these times to compare the next runs with
+//   --check           fail when a benchmark is REGRESSION slower than the
+//                     baseline, e.g. in CI
+//
+// Under `cargo test --benches` every benchmark runs once, as a smoke test.
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const SAMPLE_TIME: Duration = Duration::from_millis(50);
+const SAMPLES: usize = 20;
+// Slowdown over the baseline counted as a regression
+const REGRESSION: f64 = 0.10;
+
+pub struct Bencher {
+    filter: Option<String>,
+    // Timing runs rather than running every benchmark once
+    timing: bool,
+    save: bool,
+    check: bool,
+    // Nanoseconds per run, by benchmark
+    baseline: BTreeMap<String, f64>,
+    results: BTreeMap<String, f64>,
+    regressions: Vec<String>,
+}
+
+impl Bencher {
+    pub fn from_args() -> Self {
+        let mut bencher = Bencher {
+            filter: None,
+            timing: false,
+            save: false,
+            check: false,
+            baseline: BTreeMap::new(),
+            results: BTreeMap::new(),
+            regressions: vec![],
+        };
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--bench" => bencher.timing = true,
+                "--save-baseline" => bencher.save = true,
+                "--check" => bencher.check = true,
+                flag if flag.starts_with('-') => {}
+                filter => bencher.filter = Some(filter.to_string()),
+            }
+        }
+        bencher.baseline = std::fs::read_to_string(baseline_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        bencher
+    }
+
+    pub fn bench<T>(&mut self, name: &str, mut routine: impl FnMut() -> T) {
+        if self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !name.contains(filter))
+        {
+            return;
+        }
+        if !self.timing {
+            black_box(routine());
+            println!("{} ... ok", name);
+            return;
+        }
+
+        let start = Instant::now();
+        let mut runs = 0_u32;
+        while start.elapsed() < WARM_UP {
+            black_box(routine());
+            runs += 1;
+        }
+        let per_run = start.elapsed() / runs;
+        let batch = (SAMPLE_TIME.as_nanos() / per_run.as_nanos().max(1)).max(1) as u32;
+
+        let mut samples: Vec<f64> = (0..SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..batch {
+                    black_box(routine());
+                }
+                start.elapsed().as_nanos() as f64 / batch as f64
+            })
+            .collect();
+        samples.sort_by(f64::total_cmp);
+        let median = samples[SAMPLES / 2];
+
+        let change = match self.baseline.get(name) {
+            Some(&baseline) => {
+                let change = median / baseline - 1.0;
+                if change > REGRESSION {
+                    self.regressions.push(name.to_string());
+                }
+                format!("{:+.1}%", change * 100.0)
+            }
+            None => String::new(),
+        };
+        println!(
+            "{:<40} {:>12} [{} .. {}] {}",
+            name,
+            format_time(median),
+            format_time(samples[0]),
+            format_time(samples[SAMPLES - 1]),
+            change
+        );
+        self.results.insert(name.to_string(), median);
+    }
+
+    // Saves the baseline if asked to, and exits with an error when checking
+    // found regressions
+    pub fn finish(self) {
+        if self.save && !self.results.is_empty() {
+            let mut baseline = self.baseline;
+            baseline.extend(self.results);
+            let path = baseline_path();
+            let saved = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| {
+                    let json = serde_json::to_string_pretty(&baseline).unwrap_or_default();
+                    std::fs::write(&path, json)
+                });
+            match saved {
+                Ok(()) => println!("Saved the baseline to {}", path.display()),
+                Err(err) => eprintln!("Could not save {}: {}", path.display(), err),
+            }
+        }
+        if self.check && !self.regressions.is_empty() {
+            eprintln!(
+                "Slower than the baseline by more than {}%: {}",
+                REGRESSION * 100.0,
+                self.regressions.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn baseline_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/bench/baseline.json")
+}
+
+fn format_time(nanos: f64) -> String {
+    match nanos {
+        n if n < 1e3 => format!("{:.1} ns", n),
+        n if n < 1e6 => format!("{:.2} µs", n / 1e3),
+        n if n < 1e9 => format!("{:.2} ms", n / 1e6),
+        n => format!("{:.2} s", n / 1e9),
+    }
+}
@@ -0,0 +1,96 @@
+// The fitting and parsing hot paths at several sizes, to check that a
+// refactor made for speed, e.g. the SIMD kernels or the flat Matrix, pays
+// off. Native only: `cargo bench --bench perf`, see harness for options.
+mod harness;
+
+use harness::Bencher;
+use moonlight::data::csv::CsvReader;
+use moonlight::data::generators::make_blobs;
+use moonlight::data::sparse::Rows;
+use moonlight::math::matrix::Matrix;
+use moonlight::math::simd;
+use moonlight::ml::clustering::kmeans::KMeans;
+use moonlight::ml::neighbors::knn::KNeighborsClassifier;
+use moonlight::ml::neighbors::Algorithm;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const N_FEATURES: usize = 8;
+const N_CENTERS: usize = 8;
+const N_QUERIES: usize = 1_000;
+const SEED: u64 = 42;
+
+fn main() {
+    let mut bencher = Bencher::from_args();
+    kmeans(&mut bencher);
+    knn(&mut bencher);
+    matrix(&mut bencher);
+    csv(&mut bencher);
+    bencher.finish();
+}
+
+fn kmeans(bencher: &mut Bencher) {
+    for n in SIZES {
+        let (points, _) = make_blobs(n, N_CENTERS, N_FEATURES, 1.0, Some(SEED));
+        let data = Matrix::from_rows(&points).expect("blobs are rectangular");
+        bencher.bench(&format!("kmeans/fit/{}", n), || {
+            let mut model = KMeans::new(N_CENTERS).with_random_state(SEED);
+            model.fit_matrix(&data);
+            model.inertia()
+        });
+    }
+}
+
+fn knn(bencher: &mut Bencher) {
+    let (queries, _) = make_blobs(N_QUERIES, N_CENTERS, 2, 1.0, Some(SEED + 1));
+    for n in SIZES {
+        let (points, labels) = make_blobs(n, N_CENTERS, 2, 1.0, Some(SEED));
+        for (name, algorithm) in [
+            ("kd_tree", Algorithm::KdTree),
+            ("brute", Algorithm::BruteForce),
+        ] {
+            let mut model = KNeighborsClassifier::new(5).with_algorithm(algorithm);
+            model.fit(&points, &labels);
+            bencher.bench(&format!("knn/predict_{}/{}", name, n), || {
+                model.predict(&queries)
+            });
+        }
+    }
+}
+
+fn matrix(bencher: &mut Bencher) {
+    for n in SIZES {
+        let (points, _) = make_blobs(2, 1, n, 1.0, Some(SEED));
+        let (a, b) = (&points[0], &points[1]);
+        bencher.bench(&format!("simd/dot/{}", n), || simd::dot(a, b));
+        bencher.bench(&format!("simd/squared_euclidean/{}", n), || {
+            simd::squared_euclidean(a, b)
+        });
+    }
+    for n in SIZES {
+        let (points, _) = make_blobs(n, N_CENTERS, N_FEATURES, 1.0, Some(SEED));
+        bencher.bench(&format!("matrix/from_rows/{}", n), || {
+            Matrix::from_rows(&points)
+        });
+        let data = Matrix::from_rows(&points).expect("blobs are rectangular");
+        let weights = vec![0.5; N_FEATURES];
+        bencher.bench(&format!("matrix/row_dot/{}", n), || {
+            (0..data.n_rows())
+                .map(|i| data.row_dot(i, &weights))
+                .sum::<f64>()
+        });
+        bencher.bench(&format!("matrix/to_points/{}", n), || data.to_points());
+    }
+}
+
+fn csv(bencher: &mut Bencher) {
+    for n in SIZES {
+        let (points, labels) = make_blobs(n, N_CENTERS, 4, 1.0, Some(SEED));
+        let mut text = String::from("a,b,c,d,label\n");
+        for (point, label) in points.iter().zip(&labels) {
+            let values: Vec<String> = point.iter().map(|v| format!("{:.4}", v)).collect();
+            text.push_str(&format!("{},c{}\n", values.join(","), label));
+        }
+        let reader = CsvReader::new();
+        bencher.bench(&format!("csv/parse/{}", n), || reader.parse(&text));
+    }
+}
@@ -0,0 +1,581 @@
+// moonlight's estimators for JavaScript and TypeScript apps that don't use
+// Dioxus. Data goes in and comes out as row-major Float64Arrays with the
+// number of features alongside, and labels as Uint32Arrays. `wasm-pack build
+// js --target web` writes moonlight_js.js and its moonlight_js.d.ts types to
+// js/pkg/, e.g.
+//
+//   const model = new JsKMeans(3);
+//   model.fit(new Float64Array([0, 0, 0, 1, 9, 9]), 2);
+//   model.predict(points, 2); // Uint32Array of clusters
+//
+// Fitted models can be saved with saveJson, see moonlight::ml::persist, and
+// those fitted by scikit-learn loaded with fromSklearn, see
+// moonlight::ml::sklearn.
+use moonlight::math::matrix::Matrix;
+use moonlight::ml::base::{Estimator, Predictor, Transformer};
+use moonlight::ml::classification::logistic::LogisticRegression;
+use moonlight::ml::classification::Classifier;
+use moonlight::ml::clustering::kmeans::KMeans;
+use moonlight::ml::decomposition::pca::Pca;
+use moonlight::ml::ensemble::random_forest::RandomForestClassifier;
+use moonlight::ml::params::{Configurable, ParamSpec, Params};
+use moonlight::ml::persist::Persist;
+use moonlight::ml::preprocessing::scalers::StandardScaler;
+use moonlight::ml::regression::linear::LinearRegression;
+use moonlight::ml::regression::LinearModel;
+use moonlight::ml::sklearn::FromSklearn;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::{Float64Array, Uint32Array};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[wasm_bindgen(typescript_custom_section)]
+const PARAMS: &str = r#"
+/** Hyperparameters by name, booleans as 0 or 1, see paramSpecs in the docs */
+export type Params = Record<string, number>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Params")]
+    pub type JsParams;
+}
+
+// data as n_features wide rows
+fn rows(data: &Float64Array, n_features: usize) -> Result<Matrix, JsError> {
+    let n_rows = (data.length() as usize)
+        .checked_div(n_features)
+        .unwrap_or(0);
+    Matrix::from_vec(data.to_vec(), n_rows, n_features).ok_or_else(|| {
+        JsError::new(&format!(
+            "{} values are not rows of {} features",
+            data.length(),
+            n_features
+        ))
+    })
+}
+
+fn flatten(points: &[Point]) -> Float64Array {
+    Float64Array::from(points.concat().as_slice())
+}
+
+fn labels(labels: &[usize]) -> Uint32Array {
+    let labels: Vec<u32> = labels.iter().map(|&label| label as u32).collect();
+    Uint32Array::from(labels.as_slice())
+}
+
+fn classes(y: &Uint32Array) -> Vec<usize> {
+    y.to_vec().into_iter().map(|label| label as usize).collect()
+}
+
+fn to_params(params: &JsParams) -> Result<Params, JsError> {
+    let values: BTreeMap<String, f64> = serde_wasm_bindgen::from_value(params.into())?;
+    Ok(values
+        .into_iter()
+        .fold(Params::default(), |params, (name, value)| {
+            params.with(&name, value)
+        }))
+}
+
+fn from_params(specs: &[ParamSpec], params: &Params) -> JsParams {
+    let values: BTreeMap<&str, f64> = specs
+        .iter()
+        .map(|spec| (spec.name, params.value(spec)))
+        .collect();
+    serde_wasm_bindgen::to_value(&values)
+        .unwrap_or(JsValue::UNDEFINED)
+        .unchecked_into()
+}
+
+fn js_error(err: impl std::error::Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+#[wasm_bindgen]
+pub struct JsKMeans {
+    model: KMeans,
+}
+
+#[wasm_bindgen]
+impl JsKMeans {
+    #[wasm_bindgen(constructor)]
+    pub fn new(k: usize) -> JsKMeans {
+        JsKMeans {
+            model: KMeans::new(k),
+        }
+    }
+
+    // k, max_iter, tolerance and the others, clamped to their ranges
+    #[wasm_bindgen(js_name = setParams)]
+    pub fn set_params(&mut self, params: &JsParams) -> Result<(), JsError> {
+        self.model.set_params(&to_params(params)?);
+        Ok(())
+    }
+
+    pub fn params(&self) -> JsParams {
+        from_params(&<KMeans>::param_specs(), &self.model.params())
+    }
+
+    #[wasm_bindgen(js_name = setRandomState)]
+    pub fn set_random_state(&mut self, seed: Option<u32>) {
+        self.model.random_state = seed.map(u64::from);
+    }
+
+    pub fn fit(&mut self, data: &Float64Array, n_features: usize) -> Result<(), JsError> {
+        self.model
+            .try_fit_matrix(&rows(data, n_features)?, &())
+            .map_err(js_error)
+    }
+
+    // Nearest centroid of every row
+    pub fn predict(&self, data: &Float64Array, n_features: usize) -> Result<Uint32Array, JsError> {
+        Ok(labels(
+            &self
+                .model
+                .try_predict_matrix(&rows(data, n_features)?)
+                .map_err(js_error)?,
+        ))
+    }
+
+    // Rows of as many values as the data has features
+    pub fn centroids(&self) -> Float64Array {
+        flatten(self.model.centroids())
+    }
+
+    // Cluster of every row of the last fit
+    pub fn labels(&self) -> Uint32Array {
+        labels(self.model.labels())
+    }
+
+    pub fn inertia(&self) -> f64 {
+        self.model.inertia()
+    }
+
+    #[wasm_bindgen(js_name = nIter)]
+    pub fn n_iter(&self) -> usize {
+        self.model.n_iter()
+    }
+
+    pub fn converged(&self) -> bool {
+        self.model.converged()
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsKMeans, JsError> {
+        let model = KMeans::load_json(json).map_err(js_error)?;
+        Ok(JsKMeans { model })
+    }
+
+    #[wasm_bindgen(js_name = fromSklearn)]
+    pub fn from_sklearn(json: &str) -> Result<JsKMeans, JsError> {
+        let model = KMeans::from_sklearn_json(json).map_err(js_error)?;
+        Ok(JsKMeans { model })
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsLinearRegression {
+    model: LinearRegression,
+}
+
+#[wasm_bindgen]
+impl JsLinearRegression {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsLinearRegression {
+        JsLinearRegression {
+            model: LinearRegression::new(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = setParams)]
+    pub fn set_params(&mut self, params: &JsParams) -> Result<(), JsError> {
+        self.model.set_params(&to_params(params)?);
+        Ok(())
+    }
+
+    pub fn params(&self) -> JsParams {
+        from_params(&<LinearRegression>::param_specs(), &self.model.params())
+    }
+
+    // y holds a target per row of x
+    pub fn fit(
+        &mut self,
+        x: &Float64Array,
+        n_features: usize,
+        y: &Float64Array,
+    ) -> Result<(), JsError> {
+        let x = rows(x, n_features)?;
+        self.model.try_fit_matrix(&x, &y.to_vec()).map_err(js_error)
+    }
+
+    pub fn predict(&self, x: &Float64Array, n_features: usize) -> Result<Float64Array, JsError> {
+        let predictions = self
+            .model
+            .try_predict_matrix(&rows(x, n_features)?)
+            .map_err(js_error)?;
+        Ok(Float64Array::from(predictions.as_slice()))
+    }
+
+    pub fn coefficients(&self) -> Float64Array {
+        Float64Array::from(self.model.coefficients())
+    }
+
+    pub fn intercept(&self) -> f64 {
+        self.model.intercept()
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsLinearRegression, JsError> {
+        let model = LinearRegression::load_json(json).map_err(js_error)?;
+        Ok(JsLinearRegression { model })
+    }
+
+    #[wasm_bindgen(js_name = fromSklearn)]
+    pub fn from_sklearn(json: &str) -> Result<JsLinearRegression, JsError> {
+        let model = LinearRegression::from_sklearn_json(json).map_err(js_error)?;
+        Ok(JsLinearRegression { model })
+    }
+}
+
+impl Default for JsLinearRegression {
+    fn default() -> Self {
+        JsLinearRegression::new()
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsLogisticRegression {
+    model: LogisticRegression,
+}
+
+#[wasm_bindgen]
+impl JsLogisticRegression {
+    // alpha is the L2 penalty, a small one by default
+    #[wasm_bindgen(constructor)]
+    pub fn new(alpha: Option<f64>) -> JsLogisticRegression {
+        let model = LogisticRegression::new();
+        let model = match alpha {
+            Some(alpha) => model.with_alpha(alpha),
+            None => model,
+        };
+        JsLogisticRegression { model }
+    }
+
+    // y holds the class, from 0, of every row of x
+    pub fn fit(
+        &mut self,
+        x: &Float64Array,
+        n_features: usize,
+        y: &Uint32Array,
+    ) -> Result<(), JsError> {
+        let x = rows(x, n_features)?;
+        self.model.try_fit_matrix(&x, &classes(y)).map_err(js_error)
+    }
+
+    pub fn predict(&self, x: &Float64Array, n_features: usize) -> Result<Uint32Array, JsError> {
+        Ok(labels(
+            &self
+                .model
+                .try_predict_matrix(&rows(x, n_features)?)
+                .map_err(js_error)?,
+        ))
+    }
+
+    // A row of nClasses probabilities per row of x
+    #[wasm_bindgen(js_name = predictProba)]
+    pub fn predict_proba(
+        &self,
+        x: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let probabilities = self.model.predict_proba_matrix(&rows(x, n_features)?);
+        Ok(flatten(&probabilities))
+    }
+
+    #[wasm_bindgen(js_name = nClasses)]
+    pub fn n_classes(&self) -> usize {
+        self.model.n_classes()
+    }
+
+    // A row of coefficients per class
+    pub fn coefficients(&self) -> Float64Array {
+        flatten(self.model.coefficients())
+    }
+
+    pub fn intercepts(&self) -> Float64Array {
+        Float64Array::from(self.model.intercepts())
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsLogisticRegression, JsError> {
+        let model = LogisticRegression::load_json(json).map_err(js_error)?;
+        Ok(JsLogisticRegression { model })
+    }
+
+    #[wasm_bindgen(js_name = fromSklearn)]
+    pub fn from_sklearn(json: &str) -> Result<JsLogisticRegression, JsError> {
+        let model = LogisticRegression::from_sklearn_json(json).map_err(js_error)?;
+        Ok(JsLogisticRegression { model })
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsRandomForestClassifier {
+    model: RandomForestClassifier,
+}
+
+#[wasm_bindgen]
+impl JsRandomForestClassifier {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        n_estimators: usize,
+        max_depth: Option<usize>,
+        seed: Option<u32>,
+    ) -> JsRandomForestClassifier {
+        let mut model = RandomForestClassifier::new(n_estimators);
+        if let Some(max_depth) = max_depth {
+            model = model.with_max_depth(max_depth);
+        }
+        if let Some(seed) = seed {
+            model = model.with_random_state(u64::from(seed));
+        }
+        JsRandomForestClassifier { model }
+    }
+
+    // y holds the class, from 0, of every row of x
+    pub fn fit(
+        &mut self,
+        x: &Float64Array,
+        n_features: usize,
+        y: &Uint32Array,
+    ) -> Result<(), JsError> {
+        let x = rows(x, n_features)?;
+        self.model.try_fit_matrix(&x, &classes(y)).map_err(js_error)
+    }
+
+    pub fn predict(&self, x: &Float64Array, n_features: usize) -> Result<Uint32Array, JsError> {
+        Ok(labels(
+            &self
+                .model
+                .try_predict_matrix(&rows(x, n_features)?)
+                .map_err(js_error)?,
+        ))
+    }
+
+    // A row of nClasses probabilities per row of x
+    #[wasm_bindgen(js_name = predictProba)]
+    pub fn predict_proba(
+        &self,
+        x: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let probabilities = Classifier::predict_proba_matrix(&self.model, &rows(x, n_features)?);
+        Ok(flatten(&probabilities))
+    }
+
+    #[wasm_bindgen(js_name = nClasses)]
+    pub fn n_classes(&self) -> usize {
+        self.model.n_classes()
+    }
+
+    #[wasm_bindgen(js_name = featureImportances)]
+    pub fn feature_importances(&self) -> Float64Array {
+        Float64Array::from(self.model.feature_importances().as_slice())
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsRandomForestClassifier, JsError> {
+        let model = RandomForestClassifier::load_json(json).map_err(js_error)?;
+        Ok(JsRandomForestClassifier { model })
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsPca {
+    model: Pca,
+}
+
+#[wasm_bindgen]
+impl JsPca {
+    #[wasm_bindgen(constructor)]
+    pub fn new(n_components: usize, whiten: Option<bool>) -> JsPca {
+        JsPca {
+            model: Pca::new(n_components).with_whiten(whiten.unwrap_or(false)),
+        }
+    }
+
+    pub fn fit(&mut self, data: &Float64Array, n_features: usize) -> Result<(), JsError> {
+        self.model
+            .try_fit_matrix(&rows(data, n_features)?, &())
+            .map_err(js_error)
+    }
+
+    // Rows of nComponents values
+    pub fn transform(
+        &self,
+        data: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let transformed = self
+            .model
+            .try_transform_matrix(&rows(data, n_features)?)
+            .map_err(js_error)?;
+        Ok(Float64Array::from(transformed.as_slice()))
+    }
+
+    #[wasm_bindgen(js_name = fitTransform)]
+    pub fn fit_transform(
+        &mut self,
+        data: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let transformed = self
+            .model
+            .try_fit_transform_matrix(&rows(data, n_features)?, &())
+            .map_err(js_error)?;
+        Ok(Float64Array::from(transformed.as_slice()))
+    }
+
+    // Unit vectors as rows, by decreasing variance
+    pub fn components(&self) -> Float64Array {
+        flatten(self.model.components())
+    }
+
+    #[wasm_bindgen(js_name = nComponents)]
+    pub fn n_components(&self) -> usize {
+        self.model.components().len()
+    }
+
+    #[wasm_bindgen(js_name = explainedVarianceRatio)]
+    pub fn explained_variance_ratio(&self) -> Float64Array {
+        Float64Array::from(self.model.explained_variance_ratio())
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsPca, JsError> {
+        let model = Pca::load_json(json).map_err(js_error)?;
+        Ok(JsPca { model })
+    }
+
+    #[wasm_bindgen(js_name = fromSklearn)]
+    pub fn from_sklearn(json: &str) -> Result<JsPca, JsError> {
+        let model = Pca::from_sklearn_json(json).map_err(js_error)?;
+        Ok(JsPca { model })
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsStandardScaler {
+    model: StandardScaler,
+}
+
+#[wasm_bindgen]
+impl JsStandardScaler {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsStandardScaler {
+        JsStandardScaler {
+            model: StandardScaler::new(),
+        }
+    }
+
+    pub fn fit(&mut self, data: &Float64Array, n_features: usize) -> Result<(), JsError> {
+        self.model
+            .try_fit_matrix(&rows(data, n_features)?, &())
+            .map_err(js_error)
+    }
+
+    pub fn transform(
+        &self,
+        data: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let transformed = self
+            .model
+            .try_transform_matrix(&rows(data, n_features)?)
+            .map_err(js_error)?;
+        Ok(Float64Array::from(transformed.as_slice()))
+    }
+
+    #[wasm_bindgen(js_name = fitTransform)]
+    pub fn fit_transform(
+        &mut self,
+        data: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let transformed = self
+            .model
+            .try_fit_transform_matrix(&rows(data, n_features)?, &())
+            .map_err(js_error)?;
+        Ok(Float64Array::from(transformed.as_slice()))
+    }
+
+    #[wasm_bindgen(js_name = inverseTransform)]
+    pub fn inverse_transform(
+        &self,
+        data: &Float64Array,
+        n_features: usize,
+    ) -> Result<Float64Array, JsError> {
+        let restored = self
+            .model
+            .inverse_transform_matrix(&rows(data, n_features)?);
+        Ok(Float64Array::from(restored.as_slice()))
+    }
+
+    pub fn mean(&self) -> Float64Array {
+        Float64Array::from(self.model.mean())
+    }
+
+    pub fn scale(&self) -> Float64Array {
+        Float64Array::from(self.model.scale())
+    }
+
+    #[wasm_bindgen(js_name = saveJson)]
+    pub fn save_json(&self) -> Result<String, JsError> {
+        self.model.save_json().map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = loadJson)]
+    pub fn load_json(json: &str) -> Result<JsStandardScaler, JsError> {
+        let model = StandardScaler::load_json(json).map_err(js_error)?;
+        Ok(JsStandardScaler { model })
+    }
+
+    #[wasm_bindgen(js_name = fromSklearn)]
+    pub fn from_sklearn(json: &str) -> Result<JsStandardScaler, JsError> {
+        let model = StandardScaler::from_sklearn_json(json).map_err(js_error)?;
+        Ok(JsStandardScaler { model })
+    }
+}
+
+impl Default for JsStandardScaler {
+    fn default() -> Self {
+        JsStandardScaler::new()
+    }
+}
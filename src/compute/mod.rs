@@ -0,0 +1,116 @@
+// Running fits away from the UI thread. A fit is described by data, an
+// EstimatorSpec, so that it can be sent to a Web Worker and run there, see
+// worker::spawn_fit. With the threads feature, the heaviest loops of some
+// estimators also run on several threads, see parallel, which in a browser
+// are Web Workers pool starts.
+pub mod parallel;
+#[cfg(all(feature = "web", feature = "threads"))]
+pub mod pool;
+#[cfg(feature = "web")]
+pub mod worker;
+
+use crate::error::Result;
+use crate::math::matrix::Matrix;
+use crate::ml::base::Estimator;
+use crate::ml::clustering::dbscan::Dbscan;
+use crate::ml::clustering::gmm::GaussianMixture;
+use crate::ml::clustering::kmeans::KMeans;
+use crate::ml::params::{Configurable, Params};
+use serde::{Deserialize, Serialize};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Estimators a worker can fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Model {
+    KMeans,
+    Dbscan,
+    GaussianMixture,
+}
+
+// An unfitted estimator as data: which one and its parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EstimatorSpec {
+    pub model: Model,
+    pub params: Params,
+    pub random_state: Option<u64>,
+}
+
+impl EstimatorSpec {
+    pub fn kmeans(model: &KMeans) -> Self {
+        EstimatorSpec {
+            model: Model::KMeans,
+            params: model.params(),
+            random_state: model.random_state,
+        }
+    }
+
+    pub fn dbscan(model: &Dbscan) -> Self {
+        EstimatorSpec {
+            model: Model::Dbscan,
+            params: model.params(),
+            random_state: None,
+        }
+    }
+
+    pub fn gaussian_mixture(model: &GaussianMixture) -> Self {
+        EstimatorSpec {
+            model: Model::GaussianMixture,
+            params: model.params(),
+            random_state: model.random_state,
+        }
+    }
+}
+
+// What a fit found, whichever estimator ran it
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct FitResult {
+    // Cluster of every point, None for noise
+    pub labels: Vec<Option<usize>>,
+    // Centroids or component means, empty for Dbscan
+    pub centers: Vec<Point>,
+    // 0 for Dbscan, which doesn't iterate
+    pub n_iter: usize,
+    pub converged: bool,
+}
+
+// Fits the estimator spec describes to data on this thread, failing on
+// data try_fit refuses
+pub fn run_fit(spec: &EstimatorSpec, data: &Matrix) -> Result<FitResult> {
+    let result = match spec.model {
+        Model::KMeans => {
+            let mut model = KMeans::new(1).with_params(&spec.params);
+            model.random_state = spec.random_state;
+            model.try_fit_matrix(data, &())?;
+            FitResult {
+                labels: model.labels().iter().map(|&label| Some(label)).collect(),
+                centers: model.centroids().to_vec(),
+                n_iter: model.n_iter(),
+                converged: model.converged(),
+            }
+        }
+        Model::Dbscan => {
+            let mut model = Dbscan::default().with_params(&spec.params);
+            model.try_fit_matrix(data, &())?;
+            FitResult {
+                labels: model.labels().to_vec(),
+                centers: vec![],
+                n_iter: 0,
+                converged: true,
+            }
+        }
+        Model::GaussianMixture => {
+            let mut model = GaussianMixture::new(1).with_params(&spec.params);
+            model.random_state = spec.random_state;
+            model.try_fit_matrix(data, &())?;
+            FitResult {
+                labels: model.predict_matrix(data).into_iter().map(Some).collect(),
+                centers: model.means().to_vec(),
+                n_iter: model.n_iter(),
+                converged: model.converged(),
+            }
+        }
+    };
+    Ok(result)
+}
@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set once pool::init_thread_pool has started rayon's threads in a browser
+static POOL_STARTED: AtomicBool = AtomicBool::new(false);
+
+// Whether work is spread over threads: built with the threads feature and,
+// in a browser, once a pool of Web Workers runs rayon's threads. Without
+// one, rayon would wait on threads that never start.
+pub fn threads_available() -> bool {
+    if !cfg!(feature = "threads") {
+        return false;
+    }
+    !cfg!(target_arch = "wasm32") || POOL_STARTED.load(Ordering::Acquire)
+}
+
+#[cfg(all(feature = "threads", feature = "web"))]
+pub(super) fn mark_pool_started() {
+    POOL_STARTED.store(true, Ordering::Release);
+}
+
+// f of every item in order, on several threads when threads_available
+pub(crate) fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    #[cfg(feature = "threads")]
+    if threads_available() {
+        use rayon::prelude::*;
+        return items.par_iter().map(f).collect();
+    }
+    items.iter().map(f).collect()
+}
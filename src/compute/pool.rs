@@ -0,0 +1,121 @@
+use super::parallel::mark_pool_started;
+use super::worker::{module_urls, module_worker, set, ComputeError};
+use rayon::{ThreadBuilder, ThreadPoolBuilder};
+use std::io;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{global, Object, Promise, Reflect};
+use web_sys::{ErrorEvent, MessageEvent};
+
+// rayon threads on their way from the worker building the pool to the
+// workers that run them, all in the module's shared memory
+struct Handoff {
+    sender: Sender<ThreadBuilder>,
+    receiver: Mutex<Receiver<ThreadBuilder>>,
+}
+
+fn handoff() -> &'static Handoff {
+    static HANDOFF: OnceLock<Handoff> = OnceLock::new();
+    HANDOFF.get_or_init(|| {
+        let (sender, receiver) = channel();
+        Handoff {
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    })
+}
+
+// Starts rayon's global pool on n_threads Web Workers sharing this module's
+// memory, as wasm-bindgen-rayon does, after which par_map spreads its work
+// over them. Needs a cross-origin isolated page and a build with the
+// atomics target feature; until it resolves, or when it fails, work runs on
+// the calling thread. The pool is built in a worker of its own, since
+// building waits for the threads and the page's thread can't wait.
+#[wasm_bindgen(js_name = initThreadPool)]
+pub async fn init_thread_pool(n_threads: usize) -> Result<(), JsValue> {
+    if !cross_origin_isolated() {
+        return Err(ComputeError::Unsupported.to_string().into());
+    }
+    let (module, _) = module_urls()?;
+    let script = format!(
+        r#"import init, {{ worker_build_pool, worker_run_pool_thread }} from "{module}";
+self.onmessage = async (event) => {{
+    const {{ module, memory, n_threads }} = event.data;
+    await init({{ module_or_path: module, memory }});
+    if (n_threads === undefined) {{
+        worker_run_pool_thread();
+    }} else {{
+        worker_build_pool(n_threads);
+        self.postMessage("built");
+    }}
+}};"#
+    );
+    let message = Object::new();
+    set(&message, "module", &wasm_bindgen::module())?;
+    set(&message, "memory", &wasm_bindgen::memory())?;
+    // Each thread keeps its worker until the page closes
+    for _ in 0..n_threads {
+        module_worker(&script)?.post_message(&message)?;
+    }
+
+    let builder = module_worker(&script)?;
+    let mut callbacks = None;
+    let built = Promise::new(&mut |resolve, reject| callbacks = Some((resolve, reject)));
+    let (resolve, reject) = callbacks.ok_or(ComputeError::Unsupported)?;
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        resolve.call1(&JsValue::NULL, &event.data()).ok();
+    });
+    let on_error = Closure::<dyn FnMut(ErrorEvent)>::new(move |event: ErrorEvent| {
+        reject
+            .call1(&JsValue::NULL, &JsValue::from_str(&event.message()))
+            .ok();
+    });
+    builder.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    builder.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    set(&message, "n_threads", &JsValue::from(n_threads as u32))?;
+    builder.post_message(&message)?;
+    JsFuture::from(built).await?;
+    builder.terminate();
+    mark_pool_started();
+    Ok(())
+}
+
+// Pool init_thread_pool builds, called by the script of the worker it
+// builds it in. Returns once every thread has started.
+#[wasm_bindgen]
+pub fn worker_build_pool(n_threads: usize) -> Result<(), JsValue> {
+    ThreadPoolBuilder::new()
+        .num_threads(n_threads)
+        .spawn_handler(|thread| {
+            handoff()
+                .sender
+                .send(thread)
+                .map_err(|_| io::Error::other("the pool's threads are gone"))
+        })
+        .build_global()
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+// Runs one of the pool's threads in a worker init_thread_pool started,
+// called by the worker's script. Never returns while the page is open.
+#[wasm_bindgen]
+pub fn worker_run_pool_thread() -> Result<(), JsValue> {
+    let thread = handoff()
+        .receiver
+        .lock()
+        .map_err(|err| JsValue::from_str(&err.to_string()))?
+        .recv()
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    thread.run();
+    Ok(())
+}
+
+// Whether the page can share memory with workers, i.e. has
+// SharedArrayBuffer
+fn cross_origin_isolated() -> bool {
+    Reflect::get(&global(), &JsValue::from_str("crossOriginIsolated"))
+        .is_ok_and(|isolated| isolated.is_truthy())
+}
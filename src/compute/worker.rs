@@ -0,0 +1,215 @@
+use super::{run_fit, EstimatorSpec, FitResult};
+use crate::math::matrix::Matrix;
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Array, Float64Array, Int32Array, Object, Promise, Reflect};
+use web_sys::{
+    Blob, BlobPropertyBag, ErrorEvent, MessageEvent, Url, Worker, WorkerOptions, WorkerType,
+};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// The app's own bindings and module, which workers load again to run fits.
+// Served under base_path from Dioxus.toml, see index.html.
+const MODULE_PATH: &str = "/moonlight/wasm/moonlight.js";
+const WASM_PATH: &str = "/moonlight/wasm/moonlight_bg.wasm";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputeError {
+    // Workers can't be started here, e.g. outside a browser
+    Unsupported,
+    // The worker failed to start or the fit failed in it
+    Worker(String),
+    // A message that isn't a fit request or result
+    Message(String),
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeError::Unsupported => write!(f, "web workers are not available"),
+            ComputeError::Worker(message) => write!(f, "worker failed: {}", message),
+            ComputeError::Message(message) => write!(f, "invalid worker message: {}", message),
+        }
+    }
+}
+
+impl Error for ComputeError {}
+
+impl From<ComputeError> for JsValue {
+    fn from(err: ComputeError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+pub(super) fn js_error(err: JsValue) -> ComputeError {
+    ComputeError::Worker(err.as_string().unwrap_or_else(|| format!("{:?}", err)))
+}
+
+// Fits the estimator spec describes to data in a dedicated Web Worker, so
+// that a long fit leaves the page responsive. Every call starts a worker,
+// which loads the app's module again before fitting. Dropping the future,
+// e.g. by cancelling the task awaiting it, stops the worker and its fit.
+// Outside a browser the fit runs on this thread.
+pub async fn spawn_fit(spec: EstimatorSpec, data: Vec<Point>) -> Result<FitResult, ComputeError> {
+    let data = Matrix::from_rows(&data)
+        .ok_or_else(|| ComputeError::Message("points of different lengths".to_string()))?;
+    if !cfg!(target_arch = "wasm32") {
+        return run_fit(&spec, &data).map_err(|err| ComputeError::Worker(err.to_string()));
+    }
+    let worker = FitWorker::start()?;
+
+    let mut callbacks = None;
+    let reply = Promise::new(&mut |resolve, reject| callbacks = Some((resolve, reject)));
+    let (resolve, reject) = callbacks.ok_or(ComputeError::Unsupported)?;
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        resolve.call1(&JsValue::NULL, &event.data()).ok();
+    });
+    let on_error = Closure::<dyn FnMut(ErrorEvent)>::new(move |event: ErrorEvent| {
+        reject
+            .call1(&JsValue::NULL, &JsValue::from_str(&event.message()))
+            .ok();
+    });
+    worker
+        .0
+        .set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    worker
+        .0
+        .set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+    // The points go as one typed array whose buffer moves to the worker
+    // rather than being copied
+    let values = data.to_float64_array();
+    let message = Object::new();
+    let spec = serde_wasm_bindgen::to_value(&spec)
+        .map_err(|err| ComputeError::Message(err.to_string()))?;
+    set(&message, "spec", &spec)
+        .and_then(|_| set(&message, "data", &values))
+        .and_then(|_| set(&message, "n_features", &JsValue::from(data.n_cols() as u32)))
+        .map_err(js_error)?;
+    worker
+        .0
+        .post_message_with_transfer(&message, &Array::of1(&values.buffer()))
+        .map_err(js_error)?;
+
+    let reply = JsFuture::from(reply).await.map_err(js_error)?;
+    decode_result(&reply)
+}
+
+// Fit a worker started by spawn_fit runs on a message from it, called by
+// the worker's script
+#[wasm_bindgen]
+pub fn worker_fit(message: JsValue) -> Result<JsValue, JsValue> {
+    let spec: EstimatorSpec = serde_wasm_bindgen::from_value(get(&message, "spec")?)?;
+    let values = Float64Array::new(&get(&message, "data")?);
+    let n_features = get(&message, "n_features")?.as_f64().unwrap_or(0.0) as usize;
+    let data = Matrix::from_float64_array(&values, n_features)
+        .ok_or_else(|| JsValue::from_str("ragged data"))?;
+    // Thrown, the error reaches spawn_fit through the worker's error event
+    let result = run_fit(&spec, &data).map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+    // Typed arrays the script hands back without copying: -1 for noise
+    let labels: Vec<i32> = result
+        .labels
+        .iter()
+        .map(|label| label.map_or(-1, |label| label as i32))
+        .collect();
+    let centers =
+        Matrix::from_rows(&result.centers).ok_or_else(|| JsValue::from_str("ragged centers"))?;
+    let reply = Object::new();
+    set(&reply, "labels", &Int32Array::from(labels.as_slice()))?;
+    set(&reply, "centers", &centers.to_float64_array())?;
+    set(
+        &reply,
+        "n_features",
+        &JsValue::from(centers.n_cols() as u32),
+    )?;
+    set(&reply, "n_iter", &JsValue::from(result.n_iter as u32))?;
+    set(&reply, "converged", &JsValue::from(result.converged))?;
+    Ok(reply.into())
+}
+
+fn decode_result(reply: &JsValue) -> Result<FitResult, ComputeError> {
+    let field = |name: &str| get(reply, name).map_err(|_| ComputeError::Message(name.to_string()));
+    let labels = Int32Array::new(&field("labels")?)
+        .to_vec()
+        .into_iter()
+        .map(|label| usize::try_from(label).ok())
+        .collect();
+    let centers = Float64Array::new(&field("centers")?);
+    let n_features = field("n_features")?.as_f64().unwrap_or(0.0) as usize;
+    Ok(FitResult {
+        labels,
+        centers: Matrix::from_float64_array(&centers, n_features)
+            .ok_or_else(|| ComputeError::Message("centers".to_string()))?
+            .to_points(),
+        n_iter: field("n_iter")?.as_f64().unwrap_or(0.0) as usize,
+        converged: field("converged")?.is_truthy(),
+    })
+}
+
+// A worker running the app's module, stopped when dropped
+struct FitWorker(Worker);
+
+impl FitWorker {
+    fn start() -> Result<Self, ComputeError> {
+        let (module, wasm) = module_urls()?;
+        let script = format!(
+            r#"import init, {{ worker_fit }} from "{module}";
+const ready = init("{wasm}");
+self.onmessage = async (event) => {{
+    await ready;
+    const reply = worker_fit(event.data);
+    self.postMessage(reply, [reply.labels.buffer, reply.centers.buffer]);
+}};"#
+        );
+        module_worker(&script).map(FitWorker)
+    }
+}
+
+// Absolute URLs of the app's bindings and module. Worker scripts are
+// blobs, which can't import by a path relative to the page.
+pub(super) fn module_urls() -> Result<(String, String), ComputeError> {
+    let origin = web_sys::window()
+        .ok_or(ComputeError::Unsupported)?
+        .location()
+        .origin()
+        .map_err(js_error)?;
+    Ok((
+        format!("{}{}", origin, MODULE_PATH),
+        format!("{}{}", origin, WASM_PATH),
+    ))
+}
+
+// A module worker running script
+pub(super) fn module_worker(script: &str) -> Result<Worker, ComputeError> {
+    let options = BlobPropertyBag::new();
+    options.set_type("text/javascript");
+    let blob = Blob::new_with_str_sequence_and_options(&Array::of1(&script.into()), &options)
+        .map_err(js_error)?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+    let worker_options = WorkerOptions::new();
+    worker_options.set_type(WorkerType::Module);
+    let worker = Worker::new_with_options(&url, &worker_options);
+    // The worker resolved the URL when it was constructed
+    Url::revoke_object_url(&url).ok();
+    worker.map_err(js_error)
+}
+
+impl Drop for FitWorker {
+    fn drop(&mut self) {
+        self.0.terminate();
+    }
+}
+
+fn get(object: &JsValue, name: &str) -> Result<JsValue, JsValue> {
+    Reflect::get(object, &JsValue::from_str(name))
+}
+
+pub(super) fn set(object: &Object, name: &str, value: &JsValue) -> Result<(), JsValue> {
+    Reflect::set(object, &JsValue::from_str(name), value).map(|_| ())
+}
@@ -0,0 +1,125 @@
+use crate::data::frame::{Column, DataFrame};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type};
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+};
+use arrow_cast::cast;
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::io::Cursor;
+use std::sync::Arc;
+
+// Leading bytes of the Arrow IPC file format; streams start differently
+const FILE_MAGIC: &[u8] = b"ARROW1";
+
+// DataFrame from an Arrow IPC buffer in the stream or file format, e.g. from
+// tableToIPC in Arquero or apache-arrow. Batches are concatenated.
+pub fn read_ipc(bytes: &[u8]) -> Result<DataFrame, ArrowError> {
+    let batches = if bytes.starts_with(FILE_MAGIC) {
+        FileReader::try_new(Cursor::new(bytes), None)?.collect::<Result<Vec<_>, _>>()?
+    } else {
+        StreamReader::try_new(bytes, None)?.collect::<Result<Vec<_>, _>>()?
+    };
+    from_record_batches(&batches)
+}
+
+// DataFrame as an Arrow IPC stream, the default format of tableFromIPC
+pub fn write_ipc(frame: &DataFrame) -> Result<Vec<u8>, ArrowError> {
+    let batch = to_record_batch(frame)?;
+    let mut writer = StreamWriter::try_new(vec![], &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    writer.into_inner()
+}
+
+// Columns by Arrow type:
+// - booleans stay bool and integers become i64, or f64 with nulls as NaN
+// - floats and decimals become f64 with nulls as NaN
+// - everything else (strings, dictionaries, dates, ...) is cast to String
+//   with nulls as ""
+pub fn from_record_batches(batches: &[RecordBatch]) -> Result<DataFrame, ArrowError> {
+    let mut frame = DataFrame::new();
+    let Some(first) = batches.first() else {
+        return Ok(frame);
+    };
+    for (j, field) in first.schema().fields().iter().enumerate() {
+        let arrays: Vec<&ArrayRef> = batches.iter().map(|batch| batch.column(j)).collect();
+        let column = convert(&arrays, field.data_type())?;
+        frame
+            .add_column(field.name(), column)
+            .map_err(|err| ArrowError::InvalidArgumentError(err.to_string()))?;
+    }
+    Ok(frame)
+}
+
+// One batch with a nullable field per column. NaN floats are written as
+// nulls, the missing values of Arrow.
+pub fn to_record_batch(frame: &DataFrame) -> Result<RecordBatch, ArrowError> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = frame
+        .column_names()
+        .iter()
+        .filter_map(|name| Some((name, frame.column(name)?)))
+        .map(|(name, column)| {
+            let array: ArrayRef = match column {
+                Column::Float(v) => Arc::new(
+                    v.iter()
+                        .map(|&x| (!x.is_nan()).then_some(x))
+                        .collect::<Float64Array>(),
+                ),
+                Column::Int(v) => Arc::new(Int64Array::from(v.clone())),
+                Column::Str(v) => Arc::new(StringArray::from(v.clone())),
+                Column::Bool(v) => Arc::new(BooleanArray::from(v.clone())),
+            };
+            (Field::new(name, array.data_type().clone(), true), array)
+        })
+        .unzip();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+}
+
+fn convert(arrays: &[&ArrayRef], data_type: &DataType) -> Result<Column, ArrowError> {
+    let has_nulls = arrays.iter().any(|a| a.null_count() > 0);
+    if data_type.is_integer() && !has_nulls {
+        let mut values = vec![];
+        for array in arrays {
+            values.extend(
+                cast(array, &DataType::Int64)?
+                    .as_primitive::<Int64Type>()
+                    .values(),
+            );
+        }
+        return Ok(Column::Int(values));
+    }
+    if *data_type == DataType::Boolean && !has_nulls {
+        let values = arrays
+            .iter()
+            .flat_map(|a| a.as_boolean().values())
+            .collect();
+        return Ok(Column::Bool(values));
+    }
+    if data_type.is_numeric() || *data_type == DataType::Boolean {
+        let mut values = vec![];
+        for array in arrays {
+            let floats = cast(array, &DataType::Float64)?;
+            values.extend(
+                floats
+                    .as_primitive::<Float64Type>()
+                    .iter()
+                    .map(|x| x.unwrap_or(f64::NAN)),
+            );
+        }
+        return Ok(Column::Float(values));
+    }
+    let mut values = vec![];
+    for array in arrays {
+        let strings = cast(array, &DataType::Utf8)?;
+        values.extend(
+            strings
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.unwrap_or_default().to_string()),
+        );
+    }
+    Ok(Column::Str(values))
+}
@@ -0,0 +1,439 @@
+// Fitting datasets too big to hold in the WASM heap at once. A ChunkSource
+// reads the rows a chunk at a time, e.g. from IndexedDB or a streamed
+// download, and ChunkedDataset regroups them into batches of batch_size for
+// IncrementalEstimator::partial_fit, so that only a batch or so is ever in
+// memory:
+//
+//     let source = CsvFetchSource::open_unlabeled(url, CsvReader::new(), &["x", "y"]).await?;
+//     ChunkedDataset::new(source).partial_fit(&mut kmeans).await?;
+use crate::data::stream::Batch;
+use crate::error::Result;
+use crate::ml::base::IncrementalEstimator;
+use std::fmt;
+
+#[cfg(feature = "web")]
+use crate::data::csv::{CsvError, CsvReader};
+#[cfg(feature = "web")]
+use crate::data::fetch::{BodyStream, Progress};
+#[cfg(feature = "web")]
+use crate::data::frame::FrameError;
+#[cfg(feature = "web")]
+use crate::storage;
+#[cfg(feature = "web")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "web")]
+use serde::Serialize;
+#[cfg(feature = "web")]
+use std::marker::PhantomData;
+
+const BATCH_SIZE: usize = 1000;
+
+// Where a ChunkedDataset reads its rows. Chunks can be any size, and may
+// differ from one to the next.
+#[allow(async_fn_in_trait)]
+pub trait ChunkSource<T = ()> {
+    // The next rows, None once every row has been read
+    async fn next_chunk(&mut self) -> Result<Option<Batch<T>>>;
+}
+
+// Chunks already in memory, e.g. for tests
+impl<T> ChunkSource<T> for std::vec::IntoIter<Batch<T>> {
+    async fn next_chunk(&mut self) -> Result<Option<Batch<T>>> {
+        Ok(self.next())
+    }
+}
+
+// The targets of a batch as the estimator's partial_fit takes them: a
+// slice of labels or values, or () for unlabeled data
+pub trait BatchTargets<Y: ?Sized> {
+    fn targets(&self) -> &Y;
+}
+
+impl<T> BatchTargets<[T]> for Batch<T> {
+    fn targets(&self) -> &[T] {
+        &self.targets
+    }
+}
+
+impl BatchTargets<()> for Batch<()> {
+    fn targets(&self) -> &() {
+        &()
+    }
+}
+
+// A target read from a number, e.g. a CSV field: a value, a class label or
+// nothing. None for a number that isn't one, e.g. 1.5 as a label.
+pub trait TargetValue: Sized {
+    fn from_value(value: f64) -> Option<Self>;
+}
+
+impl TargetValue for () {
+    fn from_value(_: f64) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl TargetValue for f64 {
+    fn from_value(value: f64) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl TargetValue for usize {
+    fn from_value(value: f64) -> Option<Self> {
+        (value >= 0.0 && value.fract() == 0.0).then_some(value as usize)
+    }
+}
+
+// Rows of a source handed out in batches of batch_size, the last one
+// smaller when the rows run out. The source is read once: a second pass,
+// e.g. another epoch, needs a new dataset.
+pub struct ChunkedDataset<S, T = ()> {
+    pub batch_size: usize,
+
+    // Current state
+    source: S,
+    // Rows read from the source but not handed out yet
+    pending: Batch<T>,
+    exhausted: bool,
+    n_batches: usize,
+    n_seen: usize,
+}
+
+impl<S, T> fmt::Display for ChunkedDataset<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ChunkedDataset{{ batch_size: {}, n_batches: {}, n_seen: {} }}",
+            self.batch_size, self.n_batches, self.n_seen
+        )
+    }
+}
+
+impl<S: ChunkSource<T>, T> ChunkedDataset<S, T> {
+    pub fn new(source: S) -> Self {
+        ChunkedDataset {
+            batch_size: BATCH_SIZE,
+            source,
+            pending: Batch {
+                points: vec![],
+                targets: vec![],
+            },
+            exhausted: false,
+            n_batches: 0,
+            n_seen: 0,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    // Batches handed out so far
+    pub fn n_batches(&self) -> usize {
+        self.n_batches
+    }
+
+    // Rows handed out so far
+    pub fn n_seen(&self) -> usize {
+        self.n_seen
+    }
+
+    // The next batch_size rows, fewer once the source runs out, None after
+    // the last. Points without a target, or targets without a point, are
+    // dropped, as StreamingDataset::push does.
+    pub async fn next_batch(&mut self) -> Result<Option<Batch<T>>> {
+        let batch_size = self.batch_size.max(1);
+        while self.pending.points.len() < batch_size && !self.exhausted {
+            match self.source.next_chunk().await? {
+                Some(mut chunk) => {
+                    let len = chunk.points.len().min(chunk.targets.len());
+                    chunk.points.truncate(len);
+                    chunk.targets.truncate(len);
+                    self.pending.points.extend(chunk.points);
+                    self.pending.targets.extend(chunk.targets);
+                }
+                None => self.exhausted = true,
+            }
+        }
+        if self.pending.points.is_empty() {
+            return Ok(None);
+        }
+
+        let len = batch_size.min(self.pending.points.len());
+        let points = self.pending.points.split_off(len);
+        let targets = self.pending.targets.split_off(len);
+        let batch = Batch {
+            points: std::mem::replace(&mut self.pending.points, points),
+            targets: std::mem::replace(&mut self.pending.targets, targets),
+        };
+        self.n_batches += 1;
+        self.n_seen += len;
+        Ok(Some(batch))
+    }
+
+    // Calls on_batch with every remaining batch, and returns how many there
+    // were
+    pub async fn for_each_batch(&mut self, mut on_batch: impl FnMut(&Batch<T>)) -> Result<usize> {
+        let mut n_batches = 0;
+        while let Some(batch) = self.next_batch().await? {
+            on_batch(&batch);
+            n_batches += 1;
+        }
+        Ok(n_batches)
+    }
+
+    // Fits model on every remaining batch in turn, e.g. KMeans on points
+    // or LinearRegression on points with values. Returns the number of
+    // batches.
+    pub async fn partial_fit<E>(&mut self, model: &mut E) -> Result<usize>
+    where
+        E: IncrementalEstimator,
+        Batch<T>: BatchTargets<E::Target>,
+    {
+        self.for_each_batch(|batch| model.partial_fit(&batch.points, batch.targets()))
+            .await
+    }
+}
+
+// Chunks saved in IndexedDB under a name, see ChunkWriter, read back in the
+// order they were written
+#[cfg(feature = "web")]
+pub struct StoredChunks<T = ()> {
+    name: String,
+    next: usize,
+    // Read from storage on the first chunk
+    n_chunks: Option<usize>,
+    target: PhantomData<T>,
+}
+
+#[cfg(feature = "web")]
+impl<T> StoredChunks<T> {
+    pub fn new(name: &str) -> Self {
+        StoredChunks {
+            name: name.to_string(),
+            next: 0,
+            n_chunks: None,
+            target: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "web")]
+impl<T: DeserializeOwned> ChunkSource<T> for StoredChunks<T> {
+    async fn next_chunk(&mut self) -> Result<Option<Batch<T>>> {
+        let n_chunks = match self.n_chunks {
+            Some(n_chunks) => n_chunks,
+            None => {
+                let n_chunks = storage::load(&count_key(&self.name)).await?.unwrap_or(0);
+                *self.n_chunks.insert(n_chunks)
+            }
+        };
+        if self.next >= n_chunks {
+            return Ok(None);
+        }
+        let chunk = storage::load(&chunk_key(&self.name, self.next)).await?;
+        self.next += 1;
+        Ok(chunk)
+    }
+}
+
+// Saves batches to IndexedDB under a name, one key per batch, for
+// StoredChunks to read back, e.g. the batches of a StreamingDataset as
+// they arrive. Starts over any chunks saved under the name before.
+#[cfg(feature = "web")]
+pub struct ChunkWriter {
+    name: String,
+    n_chunks: usize,
+}
+
+#[cfg(feature = "web")]
+impl ChunkWriter {
+    pub fn new(name: &str) -> Self {
+        ChunkWriter {
+            name: name.to_string(),
+            n_chunks: 0,
+        }
+    }
+
+    pub fn n_chunks(&self) -> usize {
+        self.n_chunks
+    }
+
+    // The count is saved after the chunk, so that a reader never sees a
+    // chunk that isn't there
+    pub async fn write<T: Serialize>(&mut self, batch: &Batch<T>) -> Result<()> {
+        storage::save(&chunk_key(&self.name, self.n_chunks), batch).await?;
+        self.n_chunks += 1;
+        storage::save(&count_key(&self.name), &self.n_chunks).await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "web")]
+fn chunk_key(name: &str, index: usize) -> String {
+    format!("{}/chunks/{}", name, index)
+}
+
+#[cfg(feature = "web")]
+fn count_key(name: &str) -> String {
+    format!("{}/chunks", name)
+}
+
+// A CSV downloaded with the browser's fetch and parsed as it arrives,
+// every chunk the complete lines received so far. The features and the
+// target are columns named by the header, or x0, x1, ... without one, as
+// CsvReader names them. Fields can't contain line breaks.
+#[cfg(feature = "web")]
+pub struct CsvFetchSource<T = ()> {
+    body: BodyStream,
+    reader: CsvReader,
+    features: Vec<String>,
+    // Unlabeled when None
+    target: Option<String>,
+    // Column names, once the first line has been read
+    names: Option<Vec<String>>,
+    // Bytes after the last complete line
+    pending: Vec<u8>,
+    // Lines parsed so far, for the line numbers of errors
+    n_lines: usize,
+    done: bool,
+    target_type: PhantomData<T>,
+}
+
+#[cfg(feature = "web")]
+impl CsvFetchSource {
+    // Points without targets, e.g. for KMeans
+    pub async fn open_unlabeled(url: &str, reader: CsvReader, features: &[&str]) -> Result<Self> {
+        CsvFetchSource::connect(url, reader, features, None).await
+    }
+}
+
+#[cfg(feature = "web")]
+impl<T: TargetValue> CsvFetchSource<T> {
+    // Points with the target column as labels or values, e.g. for
+    // LogisticRegression or LinearRegression
+    pub async fn open(
+        url: &str,
+        reader: CsvReader,
+        features: &[&str],
+        target: &str,
+    ) -> Result<Self> {
+        CsvFetchSource::connect(url, reader, features, Some(target)).await
+    }
+
+    async fn connect(
+        url: &str,
+        reader: CsvReader,
+        features: &[&str],
+        target: Option<&str>,
+    ) -> Result<Self> {
+        Ok(CsvFetchSource {
+            body: BodyStream::open(url).await?,
+            reader,
+            features: features.iter().map(|name| name.to_string()).collect(),
+            target: target.map(str::to_string),
+            names: None,
+            pending: vec![],
+            n_lines: 0,
+            done: false,
+            target_type: PhantomData,
+        })
+    }
+
+    // Bytes of the body read so far
+    pub fn progress(&self) -> Progress {
+        self.body.progress()
+    }
+
+    // The rows of complete lines of CSV
+    fn parse(&mut self, lines: &[u8]) -> Result<Batch<T>> {
+        let text = std::str::from_utf8(lines).map_err(|_| CsvError::InvalidUtf8)?;
+        let mut records = self.reader.records(text.trim_start_matches('\u{feff}'))?;
+        let offset = self.n_lines;
+        self.n_lines += text.matches('\n').count();
+        if self.names.is_none() && !records.is_empty() {
+            let (_, first) = &records[0];
+            if self.reader.is_header(first) {
+                self.names = Some(records.remove(0).1);
+            } else {
+                self.names = Some((0..first.len()).map(|j| format!("x{}", j)).collect());
+            }
+        }
+
+        let names = self.names.as_deref().unwrap_or_default();
+        let column = |name: &str| {
+            names
+                .iter()
+                .position(|other| other == name)
+                .ok_or_else(|| FrameError::UnknownColumn(name.to_string()))
+        };
+        let features: Vec<usize> = self
+            .features
+            .iter()
+            .map(|name| column(name))
+            .collect::<Result<_, _>>()?;
+        let target = self.target.as_deref().map(column).transpose()?;
+
+        let mut batch = Batch {
+            points: Vec::with_capacity(records.len()),
+            targets: Vec::with_capacity(records.len()),
+        };
+        for (line, record) in &records {
+            if record.len() != names.len() {
+                return Err(CsvError::RaggedRow {
+                    line: offset + line,
+                    expected: names.len(),
+                    found: record.len(),
+                }
+                .into());
+            }
+            let point = features
+                .iter()
+                .map(|&j| {
+                    self.reader
+                        .number(&record[j])
+                        .ok_or_else(|| FrameError::NotNumeric(names[j].clone()))
+                })
+                .collect::<Result<_, _>>()?;
+            // Only CsvFetchSource<()> has no target, see open_unlabeled
+            let value = match target {
+                Some(j) => self.reader.number(&record[j]).and_then(T::from_value),
+                None => T::from_value(f64::NAN),
+            };
+            let value = value
+                .ok_or_else(|| FrameError::NotNumeric(self.target.clone().unwrap_or_default()))?;
+            batch.points.push(point);
+            batch.targets.push(value);
+        }
+        Ok(batch)
+    }
+}
+
+#[cfg(feature = "web")]
+impl<T: TargetValue> ChunkSource<T> for CsvFetchSource<T> {
+    async fn next_chunk(&mut self) -> Result<Option<Batch<T>>> {
+        while !self.done {
+            match self.body.next_chunk().await? {
+                Some(bytes) => {
+                    self.pending.extend(bytes);
+                    let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+                        continue;
+                    };
+                    let rest = self.pending.split_off(end + 1);
+                    let lines = std::mem::replace(&mut self.pending, rest);
+                    return self.parse(&lines).map(Some);
+                }
+                None => {
+                    self.done = true;
+                    if !self.pending.is_empty() {
+                        let lines = std::mem::take(&mut self.pending);
+                        return self.parse(&lines).map(Some);
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,293 @@
+use crate::data::frame::{Column, DataFrame, FrameError};
+use std::error::Error;
+use std::fmt;
+
+const DELIMITER: char = ',';
+const NA_VALUES: [&str; 9] = [
+    "", "NA", "N/A", "NaN", "nan", "null", "NULL", "None", "#N/A",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvError {
+    InvalidUtf8,
+    // Quote still open at the end of the text
+    UnterminatedQuote {
+        line: usize,
+    },
+    // Row with a different number of fields than the first
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    Frame(FrameError),
+}
+
+// Parses CSV text (RFC 4180 quoting) into a DataFrame. Every column gets the
+// narrowest type all its values parse as: bool, then i64, then f64, else
+// String. Missing values become NaN, turning bool and i64 columns into f64,
+// or empty strings in String columns.
+#[derive(Debug, Clone)]
+pub struct CsvReader {
+    pub delimiter: char,
+    // None detects a header: a first row without any number
+    pub has_header: Option<bool>,
+    // Fields read as missing
+    pub na_values: Vec<String>,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::InvalidUtf8 => write!(f, "input is not valid UTF-8"),
+            CsvError::UnterminatedQuote { line } => {
+                write!(f, "quote opened on line {} is never closed", line)
+            }
+            CsvError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} fields, expected {}",
+                line, found, expected
+            ),
+            CsvError::Frame(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+impl From<FrameError> for CsvError {
+    fn from(err: FrameError) -> Self {
+        CsvError::Frame(err)
+    }
+}
+
+impl fmt::Display for CsvReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CsvReader{{ delimiter: {:?}, has_header: {:?} }}",
+            self.delimiter, self.has_header
+        )
+    }
+}
+
+impl Default for CsvReader {
+    fn default() -> Self {
+        CsvReader::new()
+    }
+}
+
+impl CsvReader {
+    pub fn new() -> Self {
+        CsvReader {
+            delimiter: DELIMITER,
+            has_header: None,
+            na_values: NA_VALUES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = Some(has_header);
+        self
+    }
+
+    pub fn with_na_values(mut self, na_values: &[&str]) -> Self {
+        self.na_values = na_values.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    // Bytes of a file, e.g. from a browser upload. A UTF-8 byte order mark
+    // is skipped.
+    pub fn parse_bytes(&self, bytes: &[u8]) -> Result<DataFrame, CsvError> {
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        let text = std::str::from_utf8(bytes).map_err(|_| CsvError::InvalidUtf8)?;
+        self.parse(text)
+    }
+
+    // Columns without a header are named x0, x1, ...
+    pub fn parse(&self, text: &str) -> Result<DataFrame, CsvError> {
+        let records = self.records(text)?;
+        let Some((_, first)) = records.first() else {
+            return Ok(DataFrame::new());
+        };
+        for (line, record) in &records {
+            if record.len() != first.len() {
+                return Err(CsvError::RaggedRow {
+                    line: *line,
+                    expected: first.len(),
+                    found: record.len(),
+                });
+            }
+        }
+
+        let has_header = self.is_header(first);
+        let (names, rows) = if has_header {
+            (first.clone(), &records[1..])
+        } else {
+            let names = (0..first.len()).map(|j| format!("x{}", j)).collect();
+            (names, &records[..])
+        };
+
+        let mut frame = DataFrame::new();
+        for (j, name) in names.iter().enumerate() {
+            let fields: Vec<&str> = rows.iter().map(|(_, record)| record[j].as_str()).collect();
+            frame.add_column(name, self.infer_column(&fields))?;
+        }
+        Ok(frame)
+    }
+
+    // Fields of every non-blank record, with the line it starts on
+    pub(crate) fn records(&self, text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+        let mut records = vec![];
+        let mut record = vec![];
+        let mut field = String::new();
+        let (mut line, mut start_line) = (1, 1);
+        let mut in_quotes = false;
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if in_quotes {
+                match c {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' => in_quotes = false,
+                    _ => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        field.push(c);
+                    }
+                }
+                continue;
+            }
+            match c {
+                '"' if field.trim().is_empty() => {
+                    field.clear();
+                    in_quotes = true;
+                    quoted = true;
+                }
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' | '\r' => {
+                    if quoted || !field.is_empty() || !record.is_empty() {
+                        record.push(std::mem::take(&mut field));
+                        records.push((start_line, std::mem::take(&mut record)));
+                    }
+                    quoted = false;
+                    line += 1;
+                    start_line = line;
+                }
+                _ if c == self.delimiter => {
+                    record.push(std::mem::take(&mut field));
+                    quoted = false;
+                }
+                _ => field.push(c),
+            }
+        }
+        if in_quotes {
+            return Err(CsvError::UnterminatedQuote { line: start_line });
+        }
+        if quoted || !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push((start_line, record));
+        }
+        Ok(records)
+    }
+
+    fn is_na(&self, field: &str) -> bool {
+        let field = field.trim();
+        self.na_values.iter().any(|na| na == field)
+    }
+
+    // A field as a number, as in a Float column: NaN when missing, 0 or 1
+    // for a bool. None for text. For CsvFetchSource, which parses rows
+    // without a DataFrame.
+    #[cfg(feature = "web")]
+    pub(crate) fn number(&self, field: &str) -> Option<f64> {
+        if self.is_na(field) {
+            return Some(f64::NAN);
+        }
+        parse_float(field).or_else(|| parse_bool(field).map(|b| f64::from(u8::from(b))))
+    }
+
+    // Whether the first record is a header: set, or detected as a record
+    // without any number
+    pub(crate) fn is_header(&self, first: &[String]) -> bool {
+        self.has_header
+            .unwrap_or_else(|| !first.iter().any(|field| parse_float(field).is_some()))
+    }
+
+    fn infer_column(&self, fields: &[&str]) -> Column {
+        let present: Vec<&str> = fields.iter().copied().filter(|f| !self.is_na(f)).collect();
+        let complete = present.len() == fields.len();
+
+        if complete && !present.is_empty() {
+            if let Some(values) = fields.iter().map(|f| parse_bool(f)).collect() {
+                return Column::Bool(values);
+            }
+            if let Some(values) = fields.iter().map(|f| f.trim().parse().ok()).collect() {
+                return Column::Int(values);
+            }
+        }
+        if present.iter().all(|f| parse_float(f).is_some()) {
+            return Column::Float(
+                fields
+                    .iter()
+                    .map(|f| {
+                        parse_float(f)
+                            .filter(|_| !self.is_na(f))
+                            .unwrap_or(f64::NAN)
+                    })
+                    .collect(),
+            );
+        }
+        if present.iter().all(|f| parse_bool(f).is_some()) {
+            return Column::Float(
+                fields
+                    .iter()
+                    .map(|f| parse_bool(f).map_or(f64::NAN, |b| f64::from(u8::from(b))))
+                    .collect(),
+            );
+        }
+        Column::Str(
+            fields
+                .iter()
+                .map(|f| {
+                    if self.is_na(f) {
+                        String::new()
+                    } else {
+                        f.to_string()
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+// Parses CSV text with the default settings
+pub fn read_csv(text: &str) -> Result<DataFrame, CsvError> {
+    CsvReader::new().parse(text)
+}
+
+fn parse_float(field: &str) -> Option<f64> {
+    field.trim().parse().ok()
+}
+
+fn parse_bool(field: &str) -> Option<bool> {
+    match field.trim().to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
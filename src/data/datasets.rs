@@ -0,0 +1,131 @@
+use crate::data::frame::{Column, DataFrame, FrameError};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Small classic classification datasets compiled into the binary, so
+// examples and tests have real data next to the synthetic generators.
+// The files hold one sample per line: the features, then the class index.
+const IRIS: &str = include_str!("datasets/iris.csv");
+const BREAST_CANCER: &str = include_str!("datasets/breast_cancer.csv");
+const DIGITS: &str = include_str!("datasets/digits.csv");
+
+const IRIS_FEATURES: [&str; 4] = ["sepal_length", "sepal_width", "petal_length", "petal_width"];
+const IRIS_TARGETS: [&str; 3] = ["setosa", "versicolor", "virginica"];
+
+const BREAST_CANCER_MEASURES: [&str; 10] = [
+    "radius",
+    "texture",
+    "perimeter",
+    "area",
+    "smoothness",
+    "compactness",
+    "concavity",
+    "concave points",
+    "symmetry",
+    "fractal dimension",
+];
+const BREAST_CANCER_TARGETS: [&str; 2] = ["malignant", "benign"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    pub data: Vec<Point>,
+    // Class index of each point, into target_names
+    pub target: Vec<usize>,
+    pub feature_names: Vec<String>,
+    pub target_names: Vec<String>,
+    pub description: String,
+}
+
+impl Dataset {
+    pub fn n_samples(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn n_features(&self) -> usize {
+        self.feature_names.len()
+    }
+
+    // One Float column per feature and a "target" Str column of class names
+    pub fn to_frame(&self) -> Result<DataFrame, FrameError> {
+        let names: Vec<&str> = self.feature_names.iter().map(String::as_str).collect();
+        let targets = self
+            .target
+            .iter()
+            .map(|&t| self.target_names[t].clone())
+            .collect();
+        DataFrame::from_points(&self.data, &names)?.with_column("target", Column::Str(targets))
+    }
+}
+
+// Fisher's iris: 150 flowers, 4 measurements in cm, 3 species
+pub fn load_iris() -> Dataset {
+    let (data, target) = parse(IRIS);
+    Dataset {
+        data,
+        target,
+        feature_names: to_strings(&IRIS_FEATURES),
+        target_names: to_strings(&IRIS_TARGETS),
+        description: "Iris plants (Fisher, 1936): 150 samples, 50 per species".to_string(),
+    }
+}
+
+// Breast Cancer Wisconsin (Diagnostic): 569 tumors, 30 features of the cell
+// nuclei (mean, standard error and worst of 10 measures), 2 classes
+pub fn load_breast_cancer() -> Dataset {
+    let (data, target) = parse(BREAST_CANCER);
+    let feature_names = ["mean {}", "{} error", "worst {}"]
+        .iter()
+        .flat_map(|pattern| {
+            BREAST_CANCER_MEASURES
+                .iter()
+                .map(move |measure| pattern.replace("{}", measure))
+        })
+        .collect();
+    Dataset {
+        data,
+        target,
+        feature_names,
+        target_names: to_strings(&BREAST_CANCER_TARGETS),
+        description: "Breast Cancer Wisconsin (Diagnostic), UCI: 569 samples, 212 malignant \
+                      and 357 benign"
+            .to_string(),
+    }
+}
+
+// Handwritten digits: 1797 8x8 images with pixels in 0..=16, row by row,
+// and the digit drawn
+pub fn load_digits() -> Dataset {
+    let (data, target) = parse(DIGITS);
+    Dataset {
+        data,
+        target,
+        feature_names: (0..8)
+            .flat_map(|row| (0..8).map(move |col| format!("pixel_{}_{}", row, col)))
+            .collect(),
+        target_names: (0..10).map(|digit| digit.to_string()).collect(),
+        description: "Optical recognition of handwritten digits, UCI: 1797 samples of 8x8 \
+                      images"
+            .to_string(),
+    }
+}
+
+fn parse(text: &str) -> (Vec<Point>, Vec<usize>) {
+    text.lines()
+        .map(|line| {
+            let (features, target) = line.rsplit_once(',').expect("bundled dataset row");
+            let point = features
+                .split(',')
+                .map(|v| v.parse().expect("bundled dataset feature"))
+                .collect();
+            (
+                point,
+                target.parse::<usize>().expect("bundled dataset target"),
+            )
+        })
+        .unzip()
+}
+
+fn to_strings(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
@@ -0,0 +1,154 @@
+use crate::data::csv::{CsvError, CsvReader};
+use crate::data::frame::DataFrame;
+use gloo_net::http::Request;
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Uint8Array;
+use web_sys::{ReadableStreamDefaultReader, ReadableStreamReadResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    // The request failed or the body could not be read, e.g. offline or
+    // blocked by CORS
+    Network(String),
+    // Response outside 200-299
+    Status { status: u16, status_text: String },
+    Csv(CsvError),
+}
+
+// Bytes of the body read so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub loaded: usize,
+    // From Content-Length, None when the server doesn't send it
+    pub total: Option<usize>,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network(message) => write!(f, "network error: {}", message),
+            FetchError::Status {
+                status,
+                status_text,
+            } => write!(f, "HTTP {} {}", status, status_text),
+            FetchError::Csv(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+impl From<CsvError> for FetchError {
+    fn from(err: CsvError) -> Self {
+        FetchError::Csv(err)
+    }
+}
+
+impl From<gloo_net::Error> for FetchError {
+    fn from(err: gloo_net::Error) -> Self {
+        FetchError::Network(err.to_string())
+    }
+}
+
+impl From<JsValue> for FetchError {
+    fn from(err: JsValue) -> Self {
+        FetchError::Network(format!("{:?}", err))
+    }
+}
+
+impl Progress {
+    // Share of the body read, in [0, 1], when the total is known
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|&total| total > 0)
+            .map(|total| (self.loaded as f64 / total as f64).min(1.0))
+    }
+}
+
+// Downloads a CSV with the browser's fetch and parses it with the default
+// CsvReader. The server must allow the page's origin (CORS).
+pub async fn load_csv_url(url: &str) -> Result<DataFrame, FetchError> {
+    load_csv_url_with_progress(url, &CsvReader::new(), |_| {}).await
+}
+
+// As load_csv_url, with a reader for the format and on_progress called
+// after every chunk of the body, e.g. to drive a loading bar
+pub async fn load_csv_url_with_progress(
+    url: &str,
+    reader: &CsvReader,
+    on_progress: impl FnMut(Progress),
+) -> Result<DataFrame, FetchError> {
+    let bytes = fetch_bytes(url, on_progress).await?;
+    Ok(reader.parse_bytes(&bytes)?)
+}
+
+// Body of a GET request, streamed chunk by chunk
+pub async fn fetch_bytes(
+    url: &str,
+    mut on_progress: impl FnMut(Progress),
+) -> Result<Vec<u8>, FetchError> {
+    let mut body = BodyStream::open(url).await?;
+    let mut bytes = Vec::with_capacity(body.progress.total.unwrap_or(0));
+    if body.reader.is_none() {
+        on_progress(body.progress);
+    }
+    while let Some(chunk) = body.next_chunk().await? {
+        bytes.extend(chunk);
+        on_progress(body.progress);
+    }
+    Ok(bytes)
+}
+
+// Body of a GET request read as it arrives, for bodies too big to hold at
+// once, e.g. a CSV parsed a few lines at a time
+pub struct BodyStream {
+    // None for a response without a body
+    reader: Option<ReadableStreamDefaultReader>,
+    progress: Progress,
+}
+
+impl BodyStream {
+    pub async fn open(url: &str) -> Result<Self, FetchError> {
+        let response = Request::get(url).send().await?;
+        if !response.ok() {
+            return Err(FetchError::Status {
+                status: response.status(),
+                status_text: response.status_text(),
+            });
+        }
+        let total = response
+            .headers()
+            .get("content-length")
+            .and_then(|length| length.parse().ok());
+        let reader = response
+            .body()
+            .map(|body| ReadableStreamDefaultReader::new(&body))
+            .transpose()?;
+        Ok(BodyStream {
+            reader,
+            progress: Progress { loaded: 0, total },
+        })
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    // The bytes that arrived next, None once the body has been read
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, FetchError> {
+        let Some(reader) = &self.reader else {
+            return Ok(None);
+        };
+        let chunk: ReadableStreamReadResult = JsFuture::from(reader.read()).await?.unchecked_into();
+        if chunk.get_done().unwrap_or(true) {
+            self.reader = None;
+            return Ok(None);
+        }
+        let bytes = Uint8Array::new(&chunk.get_value()).to_vec();
+        self.progress.loaded += bytes.len();
+        Ok(Some(bytes))
+    }
+}
@@ -0,0 +1,335 @@
+use crate::math::matrix::Matrix;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use std::error::Error;
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Values of one column, all of the same type. Missing floats are NaN.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Column {
+    Float(Vec<f64>),
+    Int(Vec<i64>),
+    Str(Vec<String>),
+    Bool(Vec<bool>),
+}
+
+// One value of a column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Scalar {
+    Float(f64),
+    Int(i64),
+    Str(String),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameError {
+    UnknownColumn(String),
+    DuplicateColumn(String),
+    LengthMismatch {
+        column: String,
+        expected: usize,
+        found: usize,
+    },
+    // A string column where numbers are needed
+    NotNumeric(String),
+}
+
+// Named columns of equal length
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataFrame {
+    names: Vec<String>,
+    columns: Vec<Column>,
+}
+
+// One row of a DataFrame, as seen by filter
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    frame: &'a DataFrame,
+    index: usize,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::UnknownColumn(name) => write!(f, "unknown column {:?}", name),
+            FrameError::DuplicateColumn(name) => write!(f, "duplicate column {:?}", name),
+            FrameError::LengthMismatch {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "column {:?} has {} rows, expected {}",
+                column, found, expected
+            ),
+            FrameError::NotNumeric(name) => write!(f, "column {:?} is not numeric", name),
+        }
+    }
+}
+
+impl Error for FrameError {}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scalar::Float(v) => write!(f, "{}", v),
+            Scalar::Int(v) => write!(f, "{}", v),
+            Scalar::Str(v) => write!(f, "{}", v),
+            Scalar::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl fmt::Display for DataFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DataFrame{{ n_rows: {}, columns: {:?} }}",
+            self.n_rows(),
+            self.names
+        )
+    }
+}
+
+impl Column {
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Float(v) => v.len(),
+            Column::Int(v) => v.len(),
+            Column::Str(v) => v.len(),
+            Column::Bool(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<Scalar> {
+        match self {
+            Column::Float(v) => v.get(index).map(|&x| Scalar::Float(x)),
+            Column::Int(v) => v.get(index).map(|&x| Scalar::Int(x)),
+            Column::Str(v) => v.get(index).map(|x| Scalar::Str(x.clone())),
+            Column::Bool(v) => v.get(index).map(|&x| Scalar::Bool(x)),
+        }
+    }
+
+    // Values as floats, booleans as 0 and 1. None for strings.
+    pub fn as_f64(&self) -> Option<Vec<f64>> {
+        match self {
+            Column::Float(v) => Some(v.clone()),
+            Column::Int(v) => Some(v.iter().map(|&x| x as f64).collect()),
+            Column::Bool(v) => Some(v.iter().map(|&x| f64::from(u8::from(x))).collect()),
+            Column::Str(_) => None,
+        }
+    }
+
+    // Values at the given rows
+    pub fn take(&self, indices: &[usize]) -> Column {
+        match self {
+            Column::Float(v) => Column::Float(indices.iter().map(|&i| v[i]).collect()),
+            Column::Int(v) => Column::Int(indices.iter().map(|&i| v[i]).collect()),
+            Column::Str(v) => Column::Str(indices.iter().map(|&i| v[i].clone()).collect()),
+            Column::Bool(v) => Column::Bool(indices.iter().map(|&i| v[i]).collect()),
+        }
+    }
+
+    // Rows in increasing order of their values, missing floats last. Ties
+    // keep their order.
+    pub fn argsort(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.len()).collect();
+        match self {
+            Column::Float(v) => indices
+                .sort_by(|&a, &b| (v[a].is_nan().cmp(&v[b].is_nan())).then(v[a].total_cmp(&v[b]))),
+            Column::Int(v) => indices.sort_by_key(|&i| v[i]),
+            Column::Str(v) => indices.sort_by(|&a, &b| v[a].cmp(&v[b])),
+            Column::Bool(v) => indices.sort_by_key(|&i| v[i]),
+        }
+        indices
+    }
+
+    fn json(&self, index: usize) -> serde_json::Value {
+        match self {
+            Column::Float(v) => json!(v[index]),
+            Column::Int(v) => json!(v[index]),
+            Column::Str(v) => json!(v[index]),
+            Column::Bool(v) => json!(v[index]),
+        }
+    }
+}
+
+impl<'a> Row<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get(&self, name: &str) -> Option<Scalar> {
+        self.frame.column(name)?.get(self.index)
+    }
+
+    // Numeric value of the column, None for strings and unknown columns
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            Scalar::Float(v) => Some(v),
+            Scalar::Int(v) => Some(v as f64),
+            Scalar::Bool(v) => Some(f64::from(u8::from(v))),
+            Scalar::Str(_) => None,
+        }
+    }
+}
+
+impl DataFrame {
+    pub fn new() -> Self {
+        DataFrame::default()
+    }
+
+    // Numeric columns from points, named by names
+    pub fn from_points(points: &[Point], names: &[&str]) -> Result<Self, FrameError> {
+        let mut frame = DataFrame::new();
+        for (j, name) in names.iter().enumerate() {
+            let values = points.iter().map(|p| p[j]).collect();
+            frame.add_column(name, Column::Float(values))?;
+        }
+        Ok(frame)
+    }
+
+    pub fn with_column(mut self, name: &str, column: Column) -> Result<Self, FrameError> {
+        self.add_column(name, column)?;
+        Ok(self)
+    }
+
+    // The first column sets the number of rows
+    pub fn add_column(&mut self, name: &str, column: Column) -> Result<(), FrameError> {
+        if self.names.iter().any(|n| n == name) {
+            return Err(FrameError::DuplicateColumn(name.to_string()));
+        }
+        if !self.columns.is_empty() && column.len() != self.n_rows() {
+            return Err(FrameError::LengthMismatch {
+                column: name.to_string(),
+                expected: self.n_rows(),
+                found: column.len(),
+            });
+        }
+        self.names.push(name.to_string());
+        self.columns.push(column);
+        Ok(())
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.columns.first().map_or(0, Column::len)
+    }
+
+    pub fn n_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn column_names(&self) -> &[String] {
+        &self.names
+    }
+
+    // Names of the columns to_points accepts
+    pub fn numeric_column_names(&self) -> Vec<&str> {
+        self.names
+            .iter()
+            .zip(&self.columns)
+            .filter(|(_, column)| !matches!(column, Column::Str(_)))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        let j = self.names.iter().position(|n| n == name)?;
+        Some(&self.columns[j])
+    }
+
+    pub fn row(&self, index: usize) -> Option<Row<'_>> {
+        (index < self.n_rows()).then_some(Row { frame: self, index })
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        (0..self.n_rows()).map(move |index| Row { frame: self, index })
+    }
+
+    // The named columns, in the given order
+    pub fn select(&self, names: &[&str]) -> Result<DataFrame, FrameError> {
+        let mut frame = DataFrame::new();
+        for &name in names {
+            let column = self
+                .column(name)
+                .ok_or_else(|| FrameError::UnknownColumn(name.to_string()))?;
+            frame.add_column(name, column.clone())?;
+        }
+        Ok(frame)
+    }
+
+    // The given rows, in the given order
+    pub fn take(&self, indices: &[usize]) -> DataFrame {
+        DataFrame {
+            names: self.names.clone(),
+            columns: self.columns.iter().map(|c| c.take(indices)).collect(),
+        }
+    }
+
+    // Rows for which predicate holds
+    pub fn filter<F: Fn(&Row) -> bool>(&self, predicate: F) -> DataFrame {
+        let kept: Vec<usize> = self
+            .rows()
+            .filter(|row| predicate(row))
+            .map(|row| row.index)
+            .collect();
+        self.take(&kept)
+    }
+
+    // The named columns as points, e.g. the features of an estimator
+    pub fn to_points(&self, names: &[&str]) -> Result<Vec<Point>, FrameError> {
+        let columns = self.numeric_columns(names)?;
+        Ok((0..self.n_rows())
+            .map(|i| columns.iter().map(|c| c[i]).collect())
+            .collect())
+    }
+
+    // The named columns as a dense matrix, without a Vec per row
+    pub fn to_matrix(&self, names: &[&str]) -> Result<Matrix, FrameError> {
+        let columns = self.numeric_columns(names)?;
+        let mut matrix = Matrix::zeros(self.n_rows(), columns.len());
+        for (j, column) in columns.iter().enumerate() {
+            for (i, value) in column.iter().enumerate() {
+                matrix.row_mut(i)[j] = *value;
+            }
+        }
+        Ok(matrix)
+    }
+
+    // Values of the named columns as floats
+    fn numeric_columns(&self, names: &[&str]) -> Result<Vec<Vec<f64>>, FrameError> {
+        names
+            .iter()
+            .map(|&name| {
+                self.column(name)
+                    .ok_or_else(|| FrameError::UnknownColumn(name.to_string()))?
+                    .as_f64()
+                    .ok_or_else(|| FrameError::NotNumeric(name.to_string()))
+            })
+            .collect()
+    }
+
+    // One JSON object per row, the data format of Vega-Lite. NaN becomes
+    // null.
+    pub fn to_records(&self) -> Vec<serde_json::Value> {
+        self.rows()
+            .map(|row| {
+                let record: Map<String, serde_json::Value> = self
+                    .names
+                    .iter()
+                    .zip(&self.columns)
+                    .map(|(name, column)| (name.clone(), column.json(row.index)))
+                    .collect();
+                serde_json::Value::Object(record)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,279 @@
+use crate::ml::utils::seeded_rng;
+use log::{debug, error};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use std::f64::consts::PI;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Every generator returns the points together with the index of the group
+// (cluster, moon, ring or arm) each point was drawn from
+
+struct ClusterParams {
+    center_x: f64,
+    center_y: f64,
+    std_dev_x: f64,
+    std_dev_y: f64,
+    size: usize,
+}
+
+// 2D Gaussian clusters with random centers in [-range, range), random
+// per-axis spread and roughly equal sizes
+pub fn generate_realistic_clusters(
+    n_clusters: usize,
+    total_points: usize,
+    range: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let mut rng = seeded_rng(random_state);
+
+    // Ensure we have at least 1 point per cluster
+    let min_points_per_cluster = 1;
+    let remaining_points = total_points.saturating_sub(n_clusters * min_points_per_cluster);
+
+    // Generate random cluster parameters
+    let cluster_params: Vec<ClusterParams> = (0..n_clusters)
+        .map(|_| {
+            let center_x = rng.gen_range(-range..range);
+            let center_y = rng.gen_range(-range..range);
+            let std_dev_x = rng.gen_range(0.3..2.0);
+            let std_dev_y = rng.gen_range(0.3..2.0);
+
+            // Ensure each cluster gets at least one point
+            let extra_points = if remaining_points > 0 {
+                let base = (remaining_points / n_clusters) as i64;
+                let variation = std::cmp::max(1, base / 4);
+                rng.gen_range(-variation..=variation) + base
+            } else {
+                0
+            } as usize;
+
+            // avoid stack overflow
+            if extra_points > 1000 {
+                error!("Extra points: {}", extra_points);
+                return ClusterParams {
+                    center_x,
+                    center_y,
+                    std_dev_x,
+                    std_dev_y,
+                    size: 0,
+                };
+            }
+
+            debug!(
+                "Min points per cluster: {}, extra points: {}",
+                min_points_per_cluster, extra_points
+            );
+            let size = min_points_per_cluster + extra_points;
+
+            ClusterParams {
+                center_x,
+                center_y,
+                std_dev_x,
+                std_dev_y,
+                size,
+            }
+        })
+        .collect();
+
+    // Generate points for each cluster
+    let mut data = vec![];
+    let mut labels = vec![];
+    for (label, cluster) in cluster_params.iter().enumerate() {
+        let normal_x = match Normal::new(cluster.center_x, cluster.std_dev_x) {
+            Ok(normal) => normal,
+            Err(err) => {
+                error!("Error creating normal distribution for x: {:?}", err);
+                continue;
+            }
+        };
+        let normal_y = match Normal::new(cluster.center_y, cluster.std_dev_y) {
+            Ok(normal) => normal,
+            Err(err) => {
+                error!("Error creating normal distribution for y: {:?}", err);
+                continue;
+            }
+        };
+
+        for _ in 0..cluster.size {
+            data.push(vec![normal_x.sample(&mut rng), normal_y.sample(&mut rng)]);
+            labels.push(label);
+        }
+    }
+    (data, labels)
+}
+
+// Isotropic Gaussian blobs in n_features dimensions with centers drawn
+// uniformly from [-10, 10)
+pub fn make_blobs(
+    n_samples: usize,
+    n_centers: usize,
+    n_features: usize,
+    cluster_std: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let mut rng = seeded_rng(random_state);
+    if n_centers == 0 {
+        return (vec![], vec![]);
+    }
+    let centers: Vec<Point> = (0..n_centers)
+        .map(|_| {
+            (0..n_features)
+                .map(|_| rng.gen_range(-10.0..10.0))
+                .collect()
+        })
+        .collect();
+
+    let mut data = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, size) in split_evenly(n_samples, n_centers).into_iter().enumerate() {
+        for _ in 0..size {
+            data.push(
+                centers[label]
+                    .iter()
+                    .map(|c| c + cluster_std * gaussian(&mut rng))
+                    .collect(),
+            );
+            labels.push(label);
+        }
+    }
+    (data, labels)
+}
+
+// Two interleaving half circles
+pub fn make_moons(
+    n_samples: usize,
+    noise: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let mut rng = seeded_rng(random_state);
+    let sizes = split_evenly(n_samples, 2);
+
+    let mut data = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, &size) in sizes.iter().enumerate() {
+        for i in 0..size {
+            let t = PI * i as f64 / (size.max(2) - 1) as f64;
+            let (x, y) = if label == 0 {
+                (t.cos(), t.sin())
+            } else {
+                (1.0 - t.cos(), 0.5 - t.sin())
+            };
+            data.push(vec![
+                x + noise * gaussian(&mut rng),
+                y + noise * gaussian(&mut rng),
+            ]);
+            labels.push(label);
+        }
+    }
+    (data, labels)
+}
+
+// A large circle containing a smaller one scaled by factor (0 < factor < 1)
+pub fn make_circles(
+    n_samples: usize,
+    noise: f64,
+    factor: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let mut rng = seeded_rng(random_state);
+    let sizes = split_evenly(n_samples, 2);
+
+    let mut data = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, &size) in sizes.iter().enumerate() {
+        let radius = if label == 0 { 1.0 } else { factor };
+        for i in 0..size {
+            let t = 2.0 * PI * i as f64 / size as f64;
+            data.push(vec![
+                radius * t.cos() + noise * gaussian(&mut rng),
+                radius * t.sin() + noise * gaussian(&mut rng),
+            ]);
+            labels.push(label);
+        }
+    }
+    (data, labels)
+}
+
+// n_arms interleaved Archimedean spiral arms
+pub fn make_spiral(
+    n_samples: usize,
+    n_arms: usize,
+    noise: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let mut rng = seeded_rng(random_state);
+    if n_arms == 0 {
+        return (vec![], vec![]);
+    }
+
+    let mut data = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, size) in split_evenly(n_samples, n_arms).into_iter().enumerate() {
+        let offset = 2.0 * PI * label as f64 / n_arms as f64;
+        for i in 0..size {
+            // Two full turns from the center outwards
+            let r = i as f64 / size as f64;
+            let t = 4.0 * PI * r + offset;
+            data.push(vec![
+                r * t.cos() + noise * gaussian(&mut rng),
+                r * t.sin() + noise * gaussian(&mut rng),
+            ]);
+            labels.push(label);
+        }
+    }
+    (data, labels)
+}
+
+// 2D Gaussian blobs stretched by a shared linear transformation, which breaks
+// the spherical-cluster assumption KMeans relies on
+pub fn make_anisotropic(
+    n_samples: usize,
+    n_centers: usize,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<usize>) {
+    let transformation = [[0.6, -0.64], [-0.41, 0.85]];
+    let (data, labels) = make_blobs(n_samples, n_centers, 2, 1.0, random_state);
+    let data = data
+        .into_iter()
+        .map(|p| {
+            vec![
+                p[0] * transformation[0][0] + p[1] * transformation[1][0],
+                p[0] * transformation[0][1] + p[1] * transformation[1][1],
+            ]
+        })
+        .collect();
+    (data, labels)
+}
+
+// Points x drawn uniformly from [-10, 10) with targets on the line
+// slope * x + intercept plus Gaussian noise of standard deviation noise. Unlike
+// the other generators the targets are values, not groups.
+pub fn make_regression(
+    n_samples: usize,
+    slope: f64,
+    intercept: f64,
+    noise: f64,
+    random_state: Option<u64>,
+) -> (Vec<Point>, Vec<f64>) {
+    let mut rng = seeded_rng(random_state);
+    (0..n_samples)
+        .map(|_| {
+            let x = rng.gen_range(-10.0..10.0);
+            (vec![x], slope * x + intercept + noise * gaussian(&mut rng))
+        })
+        .unzip()
+}
+
+// Sizes of n_groups groups that sum to n, differing by at most one
+fn split_evenly(n: usize, n_groups: usize) -> Vec<usize> {
+    (0..n_groups)
+        .map(|i| n / n_groups + usize::from(i < n % n_groups))
+        .collect()
+}
+
+fn gaussian(rng: &mut StdRng) -> f64 {
+    rng.sample(rand_distr::StandardNormal)
+}
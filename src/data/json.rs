@@ -0,0 +1,134 @@
+use crate::data::frame::{Column, DataFrame, FrameError};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonError {
+    // Invalid JSON, with the line of the record for JSON Lines
+    Parse {
+        line: Option<usize>,
+        message: String,
+    },
+    NotAnArray,
+    // Record that is not a JSON object
+    NotAnObject {
+        row: usize,
+    },
+    Frame(FrameError),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Parse {
+                line: Some(line),
+                message,
+            } => write!(f, "invalid JSON on line {}: {}", line, message),
+            JsonError::Parse {
+                line: None,
+                message,
+            } => write!(f, "invalid JSON: {}", message),
+            JsonError::NotAnArray => write!(f, "expected an array of records"),
+            JsonError::NotAnObject { row } => write!(f, "record {} is not an object", row),
+            JsonError::Frame(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<FrameError> for JsonError {
+    fn from(err: FrameError) -> Self {
+        JsonError::Frame(err)
+    }
+}
+
+// Text holding an array of objects, e.g. [{"x": 1, "y": 2}, ...]
+pub fn read_json(text: &str) -> Result<DataFrame, JsonError> {
+    let value: Value = serde_json::from_str(text).map_err(|err| JsonError::Parse {
+        line: None,
+        message: err.to_string(),
+    })?;
+    match value {
+        Value::Array(records) => from_records(&records),
+        _ => Err(JsonError::NotAnArray),
+    }
+}
+
+// JSON Lines (NDJSON): one object per line, blank lines skipped
+pub fn read_json_lines(text: &str) -> Result<DataFrame, JsonError> {
+    let records = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|err| JsonError::Parse {
+                line: Some(i + 1),
+                message: err.to_string(),
+            })
+        })
+        .collect::<Result<Vec<Value>, _>>()?;
+    from_records(&records)
+}
+
+// Columns are the keys of all records, sorted by name; absent keys and
+// nulls are missing. Every column is coerced to one type:
+// - bool or integer when all values are, with none missing
+// - f64 when all values are numbers, booleans (0 or 1) or numeric strings,
+//   with missing values as NaN
+// - String otherwise, numbers and nested values written as JSON and
+//   missing values as ""
+// The inverse of DataFrame::to_records.
+pub fn from_records(records: &[Value]) -> Result<DataFrame, JsonError> {
+    let objects = records
+        .iter()
+        .enumerate()
+        .map(|(row, record)| record.as_object().ok_or(JsonError::NotAnObject { row }))
+        .collect::<Result<Vec<&Map<String, Value>>, _>>()?;
+
+    let names: BTreeSet<&String> = objects.iter().flat_map(|object| object.keys()).collect();
+
+    let mut frame = DataFrame::new();
+    for name in names {
+        let values: Vec<&Value> = objects
+            .iter()
+            .map(|object| object.get(name).unwrap_or(&Value::Null))
+            .collect();
+        frame.add_column(name, coerce(&values))?;
+    }
+    Ok(frame)
+}
+
+fn coerce(values: &[&Value]) -> Column {
+    if let Some(values) = values.iter().map(|v| v.as_bool()).collect() {
+        return Column::Bool(values);
+    }
+    if let Some(values) = values.iter().map(|v| v.as_i64()).collect() {
+        return Column::Int(values);
+    }
+    let numbers: Option<Vec<f64>> = values
+        .iter()
+        .map(|v| match v {
+            Value::Null => Some(f64::NAN),
+            Value::Number(n) => n.as_f64(),
+            Value::Bool(b) => Some(f64::from(u8::from(*b))),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+        .collect();
+    if let Some(numbers) = numbers {
+        return Column::Float(numbers);
+    }
+    Column::Str(
+        values
+            .iter()
+            .map(|v| match v {
+                Value::Null => String::new(),
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect(),
+    )
+}
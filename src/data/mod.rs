@@ -0,0 +1,16 @@
+pub mod arrow;
+pub mod chunked;
+pub mod csv;
+pub mod datasets;
+#[cfg(feature = "web")]
+pub mod fetch;
+pub mod frame;
+pub mod generators;
+pub mod json;
+pub mod sparse;
+pub mod stats;
+pub mod stream;
+#[cfg(feature = "web")]
+pub mod table;
+#[cfg(feature = "web")]
+pub mod upload;
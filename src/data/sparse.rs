@@ -0,0 +1,215 @@
+use crate::math::float::Float;
+use crate::math::simd;
+use crate::ml::utils::dot;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Compressed sparse row matrix: the nonzeros of row i are
+// values[indptr[i]..indptr[i + 1]], in the columns at the same positions of
+// indices, sorted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsrMatrix {
+    n_rows: usize,
+    n_cols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+// The row operations estimators need, so they run on dense points and
+// sparse matrices alike, touching only the nonzeros of sparse rows. Values
+// are f64 unless T says otherwise, e.g. the rows of a Matrix<f32>.
+pub trait Rows<T: Float = f64> {
+    fn n_rows(&self) -> usize;
+
+    fn n_cols(&self) -> usize;
+
+    // Row i as a dense point
+    fn row_to_dense(&self, i: usize) -> Vec<T>;
+
+    // row i · dense
+    fn row_dot(&self, i: usize, dense: &[T]) -> T;
+
+    // out += scale * row i
+    fn row_add_to(&self, i: usize, scale: T, out: &mut [T]);
+
+    // ||row i - dense||²
+    fn row_squared_distance(&self, i: usize, dense: &[T]) -> T;
+
+    // Sum of all entries
+    fn sum(&self) -> T;
+}
+
+impl fmt::Display for CsrMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CsrMatrix{{ n_rows: {}, n_cols: {}, nnz: {} }}",
+            self.n_rows,
+            self.n_cols,
+            self.nnz()
+        )
+    }
+}
+
+impl CsrMatrix {
+    // Rows given as (column, value) pairs in any order. Duplicate columns
+    // are summed and zeros dropped.
+    pub fn from_rows(n_cols: usize, rows: &[Vec<(usize, f64)>]) -> Self {
+        let mut matrix = CsrMatrix {
+            n_rows: 0,
+            n_cols,
+            indptr: vec![0],
+            indices: vec![],
+            values: vec![],
+        };
+        for row in rows {
+            matrix.push_row(row);
+        }
+        matrix
+    }
+
+    // (row, column, value) entries in any order, as from_rows
+    pub fn from_triplets(n_rows: usize, n_cols: usize, triplets: &[(usize, usize, f64)]) -> Self {
+        let mut rows = vec![vec![]; n_rows];
+        for &(i, j, v) in triplets {
+            rows[i].push((j, v));
+        }
+        CsrMatrix::from_rows(n_cols, &rows)
+    }
+
+    pub fn from_dense(points: &[Point]) -> Self {
+        let n_cols = points.first().map_or(0, |p| p.len());
+        let rows: Vec<Vec<(usize, f64)>> = points
+            .iter()
+            .map(|p| p.iter().copied().enumerate().collect())
+            .collect();
+        CsrMatrix::from_rows(n_cols, &rows)
+    }
+
+    // Append a row of (column, value) pairs, as from_rows
+    pub fn push_row(&mut self, row: &[(usize, f64)]) {
+        let mut entries = row.to_vec();
+        entries.sort_by_key(|&(j, _)| j);
+        for run in entries.chunk_by(|a, b| a.0 == b.0) {
+            let value: f64 = run.iter().map(|&(_, v)| v).sum();
+            if value != 0.0 {
+                self.indices.push(run[0].0);
+                self.values.push(value);
+            }
+        }
+        self.indptr.push(self.indices.len());
+        self.n_rows += 1;
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_rows, self.n_cols)
+    }
+
+    // Number of stored nonzeros
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    // Fraction of entries that are nonzero
+    pub fn density(&self) -> f64 {
+        let size = self.n_rows * self.n_cols;
+        if size == 0 {
+            0.0
+        } else {
+            self.nnz() as f64 / size as f64
+        }
+    }
+
+    // Columns and values of the nonzeros of row i
+    pub fn row(&self, i: usize) -> (&[usize], &[f64]) {
+        let range = self.indptr[i]..self.indptr[i + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    pub fn to_dense(&self) -> Vec<Point> {
+        (0..self.n_rows).map(|i| self.row_to_dense(i)).collect()
+    }
+}
+
+impl Rows for CsrMatrix {
+    fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    fn row_to_dense(&self, i: usize) -> Point {
+        let mut dense = vec![0.0; self.n_cols];
+        self.row_add_to(i, 1.0, &mut dense);
+        dense
+    }
+
+    fn row_dot(&self, i: usize, dense: &[f64]) -> f64 {
+        let (indices, values) = self.row(i);
+        indices.iter().zip(values).map(|(&j, v)| v * dense[j]).sum()
+    }
+
+    fn row_add_to(&self, i: usize, scale: f64, out: &mut [f64]) {
+        let (indices, values) = self.row(i);
+        for (&j, v) in indices.iter().zip(values) {
+            out[j] += scale * v;
+        }
+    }
+
+    // ||dense||² corrected at the nonzeros of the row
+    fn row_squared_distance(&self, i: usize, dense: &[f64]) -> f64 {
+        let (indices, values) = self.row(i);
+        let correction: f64 = indices
+            .iter()
+            .zip(values)
+            .map(|(&j, v)| (v - dense[j]).powi(2) - dense[j].powi(2))
+            .sum();
+        (dot(dense, dense) + correction).max(0.0)
+    }
+
+    fn sum(&self) -> f64 {
+        self.values.iter().sum()
+    }
+}
+
+impl<T: Float> Rows<T> for [Vec<T>] {
+    fn n_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn n_cols(&self) -> usize {
+        self.first().map_or(0, |p| p.len())
+    }
+
+    fn row_to_dense(&self, i: usize) -> Vec<T> {
+        self[i].clone()
+    }
+
+    fn row_dot(&self, i: usize, dense: &[T]) -> T {
+        simd::dot(&self[i], dense)
+    }
+
+    fn row_add_to(&self, i: usize, scale: T, out: &mut [T]) {
+        out.iter_mut()
+            .zip(&self[i])
+            .for_each(|(o, &v)| *o += scale * v);
+    }
+
+    fn row_squared_distance(&self, i: usize, dense: &[T]) -> T {
+        self[i]
+            .iter()
+            .zip(dense)
+            .map(|(&v, &r)| (v - r).powi(2))
+            .sum()
+    }
+
+    fn sum(&self) -> T {
+        self.iter().flatten().copied().sum()
+    }
+}
@@ -0,0 +1,214 @@
+use crate::data::frame::DataFrame;
+use crate::ml::utils::quantile;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Correlation {
+    // Strength of the linear relationship
+    Pearson,
+    // Pearson on the ranks: strength of any monotonic relationship
+    Spearman,
+}
+
+// Summary statistics of a numeric column, NaNs skipped
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnSummary {
+    pub name: String,
+    pub count: usize,
+    pub mean: f64,
+    // Sample standard deviation, like pandas
+    pub std: f64,
+    pub min: f64,
+    pub q25: f64,
+    pub median: f64,
+    pub q75: f64,
+    pub max: f64,
+}
+
+// Correlation between every pair of numeric columns
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorrelationMatrix {
+    pub columns: Vec<String>,
+    // values[i][j] between columns[i] and columns[j]
+    pub values: Vec<Vec<f64>>,
+}
+
+// One cell of a correlation matrix, the long format heatmaps are drawn from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorrelationCell {
+    pub row: String,
+    pub column: String,
+    pub value: f64,
+}
+
+// Per-column statistics skip NaNs and are None for unknown and string
+// columns. Booleans count as 0 and 1.
+impl DataFrame {
+    // Non-missing values of a numeric column
+    pub fn numeric_values(&self, name: &str) -> Option<Vec<f64>> {
+        let values = self.column(name)?.as_f64()?;
+        Some(values.into_iter().filter(|v| !v.is_nan()).collect())
+    }
+
+    // Non-missing values of a numeric column split by the value of another
+    // column, e.g. a feature per cluster or class. Groups come in order of
+    // first appearance.
+    pub fn grouped_values(&self, name: &str, by: &str) -> Option<Vec<(String, Vec<f64>)>> {
+        let values = self.column(name)?.as_f64()?;
+        let keys = self.column(by)?;
+        let mut groups: Vec<(String, Vec<f64>)> = vec![];
+        for (i, value) in values.into_iter().enumerate() {
+            let key = keys.get(i)?.to_string();
+            let position = match groups.iter().position(|(k, _)| *k == key) {
+                Some(position) => position,
+                None => {
+                    groups.push((key, vec![]));
+                    groups.len() - 1
+                }
+            };
+            if !value.is_nan() {
+                groups[position].1.push(value);
+            }
+        }
+        Some(groups)
+    }
+
+    // NaN for an empty column
+    pub fn mean(&self, name: &str) -> Option<f64> {
+        let values = self.numeric_values(name)?;
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    // Sample standard deviation, NaN for fewer than two values
+    pub fn std(&self, name: &str) -> Option<f64> {
+        let values = self.numeric_values(name)?;
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Some(if values.len() > 1 {
+            variance.sqrt()
+        } else {
+            f64::NAN
+        })
+    }
+
+    pub fn min(&self, name: &str) -> Option<f64> {
+        self.quantile(name, 0.0)
+    }
+
+    pub fn max(&self, name: &str) -> Option<f64> {
+        self.quantile(name, 1.0)
+    }
+
+    // q in [0, 1], interpolating linearly between values
+    pub fn quantile(&self, name: &str, q: f64) -> Option<f64> {
+        Some(quantile(&self.numeric_values(name)?, q))
+    }
+
+    // Summary of every numeric column
+    pub fn describe(&self) -> Vec<ColumnSummary> {
+        self.numeric_column_names()
+            .into_iter()
+            .filter_map(|name| {
+                let values = self.numeric_values(name)?;
+                Some(ColumnSummary {
+                    name: name.to_string(),
+                    count: values.len(),
+                    mean: self.mean(name)?,
+                    std: self.std(name)?,
+                    min: quantile(&values, 0.0),
+                    q25: quantile(&values, 0.25),
+                    median: quantile(&values, 0.5),
+                    q75: quantile(&values, 0.75),
+                    max: quantile(&values, 1.0),
+                })
+            })
+            .collect()
+    }
+
+    // Correlation of every pair of numeric columns over the rows where both
+    // are present. NaN when a column is constant over those rows.
+    pub fn correlation(&self, method: Correlation) -> CorrelationMatrix {
+        let columns = self.numeric_column_names();
+        let data: Vec<Vec<f64>> = columns
+            .iter()
+            .filter_map(|name| self.column(name)?.as_f64())
+            .collect();
+        let values = data
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                data.iter()
+                    .enumerate()
+                    .map(|(j, b)| {
+                        if i == j {
+                            return 1.0;
+                        }
+                        let (a, b): (Vec<f64>, Vec<f64>) = a
+                            .iter()
+                            .zip(b)
+                            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+                            .unzip();
+                        match method {
+                            Correlation::Pearson => pearson(&a, &b),
+                            Correlation::Spearman => pearson(&ranks(&a), &ranks(&b)),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        CorrelationMatrix {
+            columns: columns.into_iter().map(String::from).collect(),
+            values,
+        }
+    }
+}
+
+impl CorrelationMatrix {
+    // Every cell, row by row
+    pub fn cells(&self) -> Vec<CorrelationCell> {
+        self.columns
+            .iter()
+            .zip(&self.values)
+            .flat_map(|(row, values)| {
+                self.columns
+                    .iter()
+                    .zip(values)
+                    .map(move |(column, &value)| CorrelationCell {
+                        row: row.clone(),
+                        column: column.clone(),
+                        value,
+                    })
+            })
+            .collect()
+    }
+}
+
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (mut covariance, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    covariance / (var_a * var_b).sqrt()
+}
+
+// 1-based ranks, ties sharing the mean of their ranks
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    for tied in order.chunk_by(|&a, &b| values[a] == values[b]) {
+        let rank = start as f64 + (tied.len() + 1) as f64 / 2.0;
+        for &i in tied {
+            ranks[i] = rank;
+        }
+        start += tied.len();
+    }
+    ranks
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Points appended together, with their targets: class labels or values, or
+// () for unlabeled data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Batch<T = ()> {
+    pub points: Vec<Point>,
+    pub targets: Vec<T>,
+}
+
+type Subscriber<T> = Box<dyn FnMut(&Batch<T>)>;
+
+// Handle from subscribe, to unsubscribe later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(usize);
+
+// Dataset that grows as batches arrive, e.g. from a WebSocket or a timer.
+// Every subscriber is called with each new batch, so models can learn from
+// it with IncrementalEstimator::partial_fit and charts can redraw:
+//
+//     stream.subscribe(move |batch| kmeans.borrow_mut().partial_fit(&batch.points));
+//     stream.push_points(points);
+pub struct StreamingDataset<T = ()> {
+    // Keep only this many of the latest points, all when None
+    pub capacity: Option<usize>,
+
+    // Current state
+    points: Vec<Point>,
+    targets: Vec<T>,
+    n_batches: usize,
+    n_seen: usize,
+    subscribers: Vec<(SubscriptionId, Subscriber<T>)>,
+    next_id: usize,
+}
+
+impl<T> fmt::Display for StreamingDataset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "StreamingDataset{{ capacity: {:?}, len: {}, n_batches: {}, n_subscribers: {} }}",
+            self.capacity,
+            self.len(),
+            self.n_batches,
+            self.subscribers.len()
+        )
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StreamingDataset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingDataset")
+            .field("capacity", &self.capacity)
+            .field("points", &self.points)
+            .field("targets", &self.targets)
+            .field("n_batches", &self.n_batches)
+            .field("n_seen", &self.n_seen)
+            .field("n_subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl<T> Default for StreamingDataset<T> {
+    fn default() -> Self {
+        StreamingDataset::new()
+    }
+}
+
+impl<T> StreamingDataset<T> {
+    pub fn new() -> Self {
+        StreamingDataset {
+            capacity: None,
+            points: vec![],
+            targets: vec![],
+            n_batches: 0,
+            n_seen: 0,
+            subscribers: vec![],
+            next_id: 0,
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    // Points currently kept, oldest first
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn targets(&self) -> &[T] {
+        &self.targets
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn n_batches(&self) -> usize {
+        self.n_batches
+    }
+
+    // Points ever pushed, including those dropped for capacity
+    pub fn n_seen(&self) -> usize {
+        self.n_seen
+    }
+
+    // Calls on_batch with every batch pushed from now on
+    pub fn subscribe(&mut self, on_batch: impl FnMut(&Batch<T>) + 'static) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, Box::new(on_batch)));
+        id
+    }
+
+    // False when the subscription was already gone
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|(other, _)| *other != id);
+        self.subscribers.len() < before
+    }
+
+    // Appends the batch and notifies subscribers, in the order they
+    // subscribed. Points without a target, or targets without a point, are
+    // dropped.
+    pub fn push(&mut self, mut batch: Batch<T>) {
+        let len = batch.points.len().min(batch.targets.len());
+        batch.points.truncate(len);
+        batch.targets.truncate(len);
+        if len == 0 {
+            return;
+        }
+        for (_, on_batch) in &mut self.subscribers {
+            on_batch(&batch);
+        }
+
+        self.points.extend(batch.points);
+        self.targets.extend(batch.targets);
+        self.n_batches += 1;
+        self.n_seen += len;
+        if let Some(capacity) = self.capacity {
+            let excess = self.points.len().saturating_sub(capacity);
+            self.points.drain(..excess);
+            self.targets.drain(..excess);
+        }
+    }
+
+    // Forgets the points, keeping the subscribers
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.targets.clear();
+    }
+}
+
+impl StreamingDataset {
+    pub fn push_points(&mut self, points: Vec<Point>) {
+        let targets = vec![(); points.len()];
+        self.push(Batch { points, targets });
+    }
+}
@@ -0,0 +1,270 @@
+use crate::data::frame::{Column, DataFrame};
+use crate::plot::linked::{linked_rows, select_rows, use_linked_selection};
+use dioxus::prelude::*;
+use std::collections::BTreeSet;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const PAGE_SIZES: [usize; 4] = [10, 20, 50, 100];
+// Floats this large or this small, but not 0, are shown in scientific
+// notation
+const LARGE_FLOAT: f64 = 1e6;
+const SMALL_FLOAT: f64 = 1e-4;
+const FLOAT_DECIMALS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+// Text of one cell: numbers with at most FLOAT_DECIMALS decimals, missing
+// floats empty, booleans as check marks
+pub fn format_cell(column: &Column, row: usize) -> String {
+    match column {
+        Column::Float(v) => v.get(row).map_or(String::new(), |&x| format_float(x)),
+        Column::Int(v) => v.get(row).map_or(String::new(), |x| x.to_string()),
+        Column::Str(v) => v.get(row).cloned().unwrap_or_default(),
+        Column::Bool(v) => match v.get(row) {
+            Some(true) => "✓".to_string(),
+            Some(false) => "✗".to_string(),
+            None => String::new(),
+        },
+    }
+}
+
+fn format_float(x: f64) -> String {
+    if x.is_nan() {
+        return String::new();
+    }
+    if x != 0.0 && (x.abs() >= LARGE_FLOAT || x.abs() < SMALL_FLOAT) {
+        return format!("{:.3e}", x);
+    }
+    let fixed = format!("{:.*}", FLOAT_DECIMALS, x);
+    fixed
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+// Numbers line up on the right, text and check marks elsewhere
+fn alignment(column: &Column) -> &'static str {
+    match column {
+        Column::Float(_) | Column::Int(_) => "text-right",
+        Column::Str(_) => "text-left",
+        Column::Bool(_) => "text-center",
+    }
+}
+
+fn type_name(column: &Column) -> &'static str {
+    match column {
+        Column::Float(_) => "float",
+        Column::Int(_) => "int",
+        Column::Str(_) => "string",
+        Column::Bool(_) => "bool",
+    }
+}
+
+// A page of frame's rows at a time. Clicking a header sorts by its column,
+// ascending, then descending, then in the frame's order again. Clicking a
+// row selects it, Ctrl or Cmd clicking adds or removes it and Shift
+// clicking selects every row from the last one clicked, passing the
+// selected rows to on_select. Linked to a dataset the frame's rows are, the
+// selection is shared with the charts linked to it, see LinkedSelection.
+#[component]
+pub fn DataTable(
+    frame: Signal<DataFrame>,
+    #[props(default = DEFAULT_PAGE_SIZE)] page_size: usize,
+    #[props(default)] on_select: EventHandler<Vec<usize>>,
+    #[props(default)] linked: Option<String>,
+) -> Element {
+    let mut sort = use_signal(|| None::<(usize, SortOrder)>);
+    let mut page = use_signal(|| 0_usize);
+    let mut page_size = use_signal(|| page_size);
+    let mut selected = use_signal(BTreeSet::<usize>::new);
+    // Row the last plain or Ctrl click was on, where Shift clicks start
+    let mut anchor = use_signal(|| None::<usize>);
+    let linked_selection = use_linked_selection();
+    let dataset = use_signal(|| linked);
+
+    // Positions and rows mean nothing once the frame changes
+    use_effect(move || {
+        frame.read();
+        sort.set(None);
+        page.set(0);
+        selected.set(BTreeSet::new());
+        anchor.set(None);
+    });
+
+    // Rows in the order shown
+    let order = use_memo(move || {
+        let frame = frame.read();
+        let sorted = sort().and_then(|(c, order)| {
+            let column = frame.column(frame.column_names().get(c)?)?;
+            let mut rows = column.argsort();
+            if order == SortOrder::Descending {
+                rows.reverse();
+            }
+            Some(rows)
+        });
+        sorted.unwrap_or_else(|| (0..frame.n_rows()).collect())
+    });
+
+    // Selected rows, those of the linked dataset when linked
+    let current = move || match (linked_selection, dataset.read().as_deref()) {
+        (Some(_), Some(name)) => linked_rows(linked_selection, Some(name)).unwrap_or_default(),
+        _ => selected(),
+    };
+    let mut choose = move |rows: BTreeSet<usize>| {
+        select_rows(linked_selection, dataset.read().as_deref(), rows.clone());
+        on_select.call(rows.iter().copied().collect());
+        selected.set(rows);
+    };
+
+    let n_rows = order.read().len();
+    let size = page_size().max(1);
+    let n_pages = n_rows.div_ceil(size).max(1);
+    let first = page().min(n_pages - 1) * size;
+    let shown: Vec<usize> = order
+        .read()
+        .iter()
+        .skip(first)
+        .take(size)
+        .copied()
+        .collect();
+    let last = first + shown.len();
+    // The shown rows of every column
+    let (names, columns) = {
+        let frame = frame.read();
+        let names = frame.column_names().to_vec();
+        let columns: Vec<Column> = names
+            .iter()
+            .filter_map(|name| frame.column(name).map(|column| column.take(&shown)))
+            .collect();
+        (names, columns)
+    };
+    let sorted_by = sort();
+    let highlighted = current();
+    let n_selected = highlighted.len();
+
+    rsx! {
+        div {
+            class: "overflow-x-auto",
+            table {
+                class: "w-full text-sm border-collapse",
+                thead {
+                    tr {
+                        th { class: "px-2 border-b text-right text-gray-500", "#" }
+                        for (c, (name, column)) in names.iter().zip(&columns).enumerate() {
+                            th {
+                                key: "{name}",
+                                class: "px-2 border-b cursor-pointer select-none {alignment(column)}",
+                                title: "{type_name(column)}",
+                                onclick: move |_| {
+                                    sort.set(match sort() {
+                                        Some((sorted, SortOrder::Ascending)) if sorted == c => {
+                                            Some((c, SortOrder::Descending))
+                                        }
+                                        Some((sorted, SortOrder::Descending)) if sorted == c => None,
+                                        _ => Some((c, SortOrder::Ascending)),
+                                    });
+                                    page.set(0);
+                                },
+                                "{name}"
+                                match sorted_by {
+                                    Some((sorted, SortOrder::Ascending)) if sorted == c => " ▲",
+                                    Some((sorted, SortOrder::Descending)) if sorted == c => " ▼",
+                                    _ => "",
+                                }
+                            }
+                        }
+                    }
+                }
+                tbody {
+                    for (position, &row) in shown.iter().enumerate() {
+                        tr {
+                            key: "{row}",
+                            class: if highlighted.contains(&row) { "bg-blue-100 cursor-pointer" } else { "cursor-pointer" },
+                            onclick: move |event| {
+                                let modifiers = event.modifiers();
+                                let mut rows = current();
+                                match anchor() {
+                                    Some(from) if modifiers.shift() => {
+                                        let order = order.read();
+                                        let start = order.iter().position(|&r| r == from);
+                                        let end = order.iter().position(|&r| r == row);
+                                        if let (Some(start), Some(end)) = (start, end) {
+                                            rows = order[start.min(end)..=start.max(end)]
+                                                .iter()
+                                                .copied()
+                                                .collect();
+                                        }
+                                    }
+                                    _ if modifiers.ctrl() || modifiers.meta() => {
+                                        if !rows.remove(&row) {
+                                            rows.insert(row);
+                                        }
+                                        anchor.set(Some(row));
+                                    }
+                                    _ => {
+                                        rows = BTreeSet::from([row]);
+                                        anchor.set(Some(row));
+                                    }
+                                }
+                                choose(rows);
+                            },
+                            td { class: "px-2 text-right text-gray-500", "{row}" }
+                            for column in &columns {
+                                td { class: "px-2 {alignment(column)}", "{format_cell(column, position)}" }
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "flex items-center gap-2 mt-1 text-sm",
+                button {
+                    class: "px-2 border rounded",
+                    disabled: first == 0,
+                    onclick: move |_| page.set(page().min(n_pages - 1).saturating_sub(1)),
+                    "◀"
+                }
+                span {
+                    if n_rows == 0 {
+                        "No rows"
+                    } else {
+                        "Rows {first + 1}–{last} of {n_rows}"
+                    }
+                }
+                button {
+                    class: "px-2 border rounded",
+                    disabled: last >= n_rows,
+                    onclick: move |_| page.set((page() + 1).min(n_pages - 1)),
+                    "▶"
+                }
+                select {
+                    class: "px-1 border rounded",
+                    onchange: move |event| {
+                        if let Ok(size) = event.value().parse::<usize>() {
+                            page_size.set(size);
+                            page.set(0);
+                        }
+                    },
+                    for choice in PAGE_SIZES {
+                        option { value: "{choice}", selected: choice == size, "{choice} per page" }
+                    }
+                }
+                if n_selected > 0 {
+                    span { "{n_selected} selected" }
+                    button {
+                        class: "px-2 border rounded",
+                        onclick: move |_| {
+                            anchor.set(None);
+                            choose(BTreeSet::new());
+                        },
+                        "Clear"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::data::arrow::read_ipc;
+use crate::data::csv::CsvReader;
+use crate::data::fetch::{load_csv_url_with_progress, Progress};
+use crate::data::frame::DataFrame;
+use crate::data::json::{read_json, read_json_lines};
+use dioxus::html::{FileEngine, HasFileData};
+use dioxus::prelude::*;
+use log::{error, info};
+use std::sync::Arc;
+
+// File picker and drop zone that parses a CSV, TSV, JSON, JSON Lines or
+// Arrow IPC file, chosen by its extension, into dataset. The browser reads
+// the file with a FileReader through the dioxus file engine. A CSV or TSV
+// can also be downloaded from a URL, with a loading bar.
+#[component]
+pub fn DatasetUpload(dataset: Signal<DataFrame>) -> Element {
+    let mut status = use_signal(String::new);
+    let mut hovering = use_signal(|| false);
+    let mut url = use_signal(String::new);
+    let mut progress = use_signal(|| None::<Progress>);
+
+    let mut finish = move |name: &str, result: Result<DataFrame, String>| match result {
+        Ok(frame) => {
+            info!("Loaded {}: {}", name, frame);
+            status.set(format!(
+                "{}: {} rows, {} columns",
+                name,
+                frame.n_rows(),
+                frame.n_columns()
+            ));
+            dataset.set(frame);
+        }
+        Err(err) => {
+            error!("Error parsing {}: {}", name, err);
+            status.set(format!("Error parsing {}: {}", name, err));
+        }
+    };
+
+    let load = move |files: Option<Arc<dyn FileEngine>>| {
+        let Some(files) = files else {
+            return;
+        };
+        let Some(name) = files.files().into_iter().next() else {
+            return;
+        };
+        spawn(async move {
+            let Some(bytes) = files.read_file(&name).await else {
+                status.set(format!("Could not read {}", name));
+                return;
+            };
+            finish(&name, parse_file(&name, &bytes));
+        });
+    };
+
+    let fetch = move |_| {
+        let url = url.read().trim().to_string();
+        if url.is_empty() {
+            return;
+        }
+        spawn(async move {
+            status.set(format!("Downloading {}", url));
+            progress.set(Some(Progress {
+                loaded: 0,
+                total: None,
+            }));
+            let delimiter = if url.to_lowercase().ends_with(".tsv") {
+                '\t'
+            } else {
+                ','
+            };
+            let reader = CsvReader::new().with_delimiter(delimiter);
+            let result =
+                load_csv_url_with_progress(&url, &reader, move |p| progress.set(Some(p))).await;
+            progress.set(None);
+            finish(&url, result.map_err(|err| err.to_string()));
+        });
+    };
+
+    let border = if hovering() {
+        "border-blue-500"
+    } else {
+        "border-gray-300"
+    };
+    rsx! {
+        div {
+            class: "p-4 border-2 border-dashed rounded {border}",
+            ondragover: move |event| {
+                event.prevent_default();
+                hovering.set(true);
+            },
+            ondragleave: move |_| hovering.set(false),
+            ondrop: move |event| {
+                event.prevent_default();
+                hovering.set(false);
+                load(event.files());
+            },
+            p {
+                "Drop a CSV or JSON file here or "
+                input {
+                    type: "file",
+                    accept: ".csv,.tsv,.txt,.json,.jsonl,.ndjson,.arrow,.arrows",
+                    onchange: move |event| load(event.files()),
+                }
+            }
+            p {
+                "or download a CSV from "
+                input {
+                    type: "url",
+                    placeholder: "https://...",
+                    value: "{url}",
+                    oninput: move |event| url.set(event.value()),
+                }
+                button {
+                    class: "ml-2 px-2 border rounded",
+                    disabled: progress.read().is_some(),
+                    onclick: fetch,
+                    "Load"
+                }
+            }
+            if let Some(p) = progress() {
+                // Indeterminate until the size is known
+                progress { max: 1.0, value: p.fraction().map(|f| f.to_string()) }
+            }
+            if !status.read().is_empty() {
+                p { "{status}" }
+            }
+        }
+    }
+}
+
+fn parse_file(name: &str, bytes: &[u8]) -> Result<DataFrame, String> {
+    let name = name.to_lowercase();
+    if name.ends_with(".arrow") || name.ends_with(".arrows") {
+        return read_ipc(bytes).map_err(|err| err.to_string());
+    }
+    if name.ends_with(".json") || name.ends_with(".jsonl") || name.ends_with(".ndjson") {
+        let text = std::str::from_utf8(bytes).map_err(|err| err.to_string())?;
+        let parsed = if name.ends_with(".json") {
+            read_json(text)
+        } else {
+            read_json_lines(text)
+        };
+        return parsed.map_err(|err| err.to_string());
+    }
+    let delimiter = if name.ends_with(".tsv") { '\t' } else { ',' };
+    CsvReader::new()
+        .with_delimiter(delimiter)
+        .parse_bytes(bytes)
+        .map_err(|err| err.to_string())
+}
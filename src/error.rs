@@ -0,0 +1,93 @@
+// The crate's errors in one type, for callers that handle everything the
+// same way, e.g. showing a message in the app. Every module keeps its own
+// error type, which converts into MoonlightError with `?`.
+use crate::data::csv::CsvError;
+use crate::data::frame::FrameError;
+use crate::data::json::JsonError;
+use crate::ml::onnx::OnnxError;
+use crate::ml::params::ParamError;
+use crate::ml::persist::PersistError;
+use crate::ml::sklearn::SklearnError;
+use arrow_schema::ArrowError;
+use thiserror::Error;
+
+#[cfg(feature = "web")]
+use crate::compute::worker::ComputeError;
+#[cfg(feature = "web")]
+use crate::data::fetch::FetchError;
+#[cfg(feature = "web")]
+use crate::plot::vega::spec::VegaError;
+#[cfg(feature = "web")]
+use crate::storage::StorageError;
+
+pub type Result<T, E = MoonlightError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum MoonlightError {
+    // Points with another number of features than the model or the first
+    // point has, or targets other than one per point
+    #[error("expected {expected} features or targets, found {found}")]
+    DimensionMismatch { expected: usize, found: usize },
+    #[error("the dataset is empty")]
+    EmptyDataset,
+    // E.g. fewer points than clusters asked for
+    #[error("needs at least {required} samples, found {n_samples}")]
+    TooFewSamples { required: usize, n_samples: usize },
+    #[error("non-finite value {value} in row {row}, column {column}")]
+    NonFinite {
+        row: usize,
+        column: usize,
+        value: f64,
+    },
+    // Calling into JavaScript or the DOM failed
+    #[error("JavaScript error: {0}")]
+    Js(String),
+    #[error(transparent)]
+    Csv(#[from] CsvError),
+    #[error(transparent)]
+    Json(#[from] JsonError),
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+    // ArrowError isn't Clone, so only its message is kept
+    #[error("arrow error: {0}")]
+    Arrow(String),
+    #[error(transparent)]
+    Param(#[from] ParamError),
+    #[error(transparent)]
+    Persist(#[from] PersistError),
+    #[error(transparent)]
+    Sklearn(#[from] SklearnError),
+    #[error(transparent)]
+    Onnx(#[from] OnnxError),
+    #[cfg(feature = "web")]
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[cfg(feature = "web")]
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[cfg(feature = "web")]
+    #[error(transparent)]
+    Compute(#[from] ComputeError),
+    #[cfg(feature = "web")]
+    #[error(transparent)]
+    Vega(#[from] VegaError),
+}
+
+impl From<ArrowError> for MoonlightError {
+    fn from(err: ArrowError) -> Self {
+        MoonlightError::Arrow(err.to_string())
+    }
+}
+
+// A value thrown by JavaScript: its message when it's a string or an Error
+#[cfg(feature = "web")]
+impl From<wasm_bindgen::JsValue> for MoonlightError {
+    fn from(value: wasm_bindgen::JsValue) -> Self {
+        use wasm_bindgen::JsCast;
+        let message = match value.dyn_ref::<web_sys::js_sys::Error>() {
+            Some(error) => String::from(error.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{:?}", value)),
+        };
+        MoonlightError::Js(message)
+    }
+}
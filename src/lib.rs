@@ -1,2 +1,9 @@
+pub mod compute;
+pub mod data;
+pub mod error;
+pub mod math;
 pub mod ml;
+#[cfg(feature = "web")]
 pub mod plot;
+#[cfg(feature = "web")]
+pub mod storage;
@@ -0,0 +1,73 @@
+use crate::math::simd::Kernels;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+// The float types the numeric core computes in. f64 is the default
+// everywhere; f32 halves the memory of a dataset and fits twice the lanes
+// in a SIMD register, at the cost of precision, e.g. for big datasets in
+// the browser.
+//
+// KMeans, the k-nearest-neighbors models, LogisticRegression,
+// LinearRegression, Ridge, Lasso, Pca, FastIca and Nmf fit a Matrix<T> where
+// the rows stay in T, with no f64 copy of the data; sums and the fitted
+// parameters are f64. Every other estimator takes f64 only.
+pub trait Float:
+    num_traits::Float
+    + Kernels
+    + Default
+    + fmt::Debug
+    + fmt::Display
+    + Sum
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    // The nearest value, e.g. a constant or a user-set tolerance
+    fn from_f64(value: f64) -> Self;
+
+    // For what's reported in f64 whatever the type, e.g. inertia
+    fn as_f64(self) -> f64;
+
+    fn from_usize(value: usize) -> Self {
+        Self::from_f64(value as f64)
+    }
+}
+
+// The same values in another float type, e.g. f64 coefficients to dot with
+// the rows of a Matrix<f32>
+pub(crate) fn cast_slice<T: Float, U: Float>(values: &[T]) -> Vec<U> {
+    values.iter().map(|&v| U::from_f64(v.as_f64())).collect()
+}
+
+pub(crate) fn cast_points<T: Float, U: Float>(points: &[Vec<T>]) -> Vec<Vec<U>> {
+    points.iter().map(|point| cast_slice(point)).collect()
+}
+
+impl Float for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Float for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
@@ -0,0 +1,199 @@
+use crate::data::sparse::Rows;
+use crate::math::float::Float;
+use crate::math::simd;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+#[cfg(feature = "web")]
+use web_sys::js_sys::{Float32Array, Float64Array};
+
+// Dense row-major matrix: row i is data[i * cols..(i + 1) * cols]. One
+// allocation for the whole dataset, with each row next to the one before,
+// where a Vec of points allocates every row on its own. Values are f64
+// unless the type says otherwise, e.g. Matrix<f32> for half the memory.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Matrix<T = f64> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Matrix{{ rows: {}, cols: {} }}", self.rows, self.cols)
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix {
+            data: vec![T::zero(); rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    // Row-major values, None unless there are rows * cols of them
+    pub fn from_vec(data: Vec<T>, rows: usize, cols: usize) -> Option<Self> {
+        (data.len() == rows * cols).then_some(Matrix { data, rows, cols })
+    }
+
+    // One row per point, None unless every point has as many values as the
+    // first
+    pub fn from_rows(points: &[Vec<T>]) -> Option<Self> {
+        let cols = points.first().map_or(0, Vec::len);
+        if points.iter().any(|point| point.len() != cols) {
+            return None;
+        }
+        Some(Matrix {
+            data: points.concat(),
+            rows: points.len(),
+            cols,
+        })
+    }
+
+    // Value (i, j) is f(i, j)
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let mut data = Vec::with_capacity(rows * cols);
+        for i in 0..rows {
+            data.extend((0..cols).map(|j| f(i, j)));
+        }
+        Matrix { data, rows, cols }
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.data[row * self.cols + col]
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> + Clone + '_ {
+        (0..self.rows).map(move |i| self.row(i))
+    }
+
+    // Rows without columns have nothing to change and are skipped
+    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> + '_ {
+        self.data.chunks_exact_mut(self.cols.max(1))
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = T> + '_ {
+        self.iter_rows().map(move |row| row[j])
+    }
+
+    // The values row after row
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    // The rows as points, for the APIs that still take them
+    pub fn to_points(&self) -> Vec<Vec<T>> {
+        self.iter_rows().map(<[T]>::to_vec).collect()
+    }
+
+    // The same values in another float type, e.g. f64 data for an f32 fit
+    pub fn cast<U: Float>(&self) -> Matrix<U> {
+        Matrix {
+            data: self.data.iter().map(|&v| U::from_f64(v.as_f64())).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+// Typed arrays of JavaScript, Float64Array for f64 values and Float32Array
+// for f32
+#[cfg(feature = "web")]
+impl Matrix<f64> {
+    // Rows of cols values from a typed array handed over by JavaScript,
+    // copied into wasm memory once. None unless the length is a multiple of
+    // cols.
+    pub fn from_float64_array(values: &Float64Array, cols: usize) -> Option<Self> {
+        if cols == 0 {
+            return (values.length() == 0).then(Matrix::default);
+        }
+        let rows = values.length() as usize / cols;
+        let mut data = vec![0.0; values.length() as usize];
+        values.copy_to(&mut data);
+        Matrix::from_vec(data, rows, cols)
+    }
+
+    // The values row after row in a new typed array, which can be
+    // transferred to a worker or handed to a chart without copying again
+    pub fn to_float64_array(&self) -> Float64Array {
+        Float64Array::from(self.data.as_slice())
+    }
+}
+
+#[cfg(feature = "web")]
+impl Matrix<f32> {
+    // As from_float64_array
+    pub fn from_float32_array(values: &Float32Array, cols: usize) -> Option<Self> {
+        if cols == 0 {
+            return (values.length() == 0).then(Matrix::default);
+        }
+        let rows = values.length() as usize / cols;
+        let mut data = vec![0.0; values.length() as usize];
+        values.copy_to(&mut data);
+        Matrix::from_vec(data, rows, cols)
+    }
+
+    // As to_float64_array
+    pub fn to_float32_array(&self) -> Float32Array {
+        Float32Array::from(self.data.as_slice())
+    }
+}
+
+impl<T: Float> Rows<T> for Matrix<T> {
+    fn n_rows(&self) -> usize {
+        self.rows
+    }
+
+    fn n_cols(&self) -> usize {
+        self.cols
+    }
+
+    fn row_to_dense(&self, i: usize) -> Vec<T> {
+        self.row(i).to_vec()
+    }
+
+    fn row_dot(&self, i: usize, dense: &[T]) -> T {
+        simd::dot(self.row(i), dense)
+    }
+
+    fn row_add_to(&self, i: usize, scale: T, out: &mut [T]) {
+        simd::axpy(scale, self.row(i), out);
+    }
+
+    fn row_squared_distance(&self, i: usize, dense: &[T]) -> T {
+        simd::squared_euclidean(self.row(i), dense)
+    }
+
+    fn sum(&self) -> T {
+        self.data.iter().copied().sum()
+    }
+}
@@ -0,0 +1,5 @@
+// Numeric kernels and the dense matrix shared by the estimators, in f64 or
+// f32, see Float
+pub mod float;
+pub mod matrix;
+pub mod simd;
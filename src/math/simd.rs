@@ -0,0 +1,189 @@
+// Kernels of the inner loops of fitting: dot products, Euclidean distances
+// and axpy, for every Float type. Wasm builds with the simd128 target
+// feature, e.g. RUSTFLAGS="-C target-feature=+simd128", work on two f64 or
+// four f32 lanes at a time; other builds use plain loops, which the
+// compiler vectorizes where the target allows. Like zip, every kernel stops
+// at the shorter slice.
+use crate::math::float::Float;
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::arch::wasm32::{
+    f32x4, f32x4_add, f32x4_extract_lane, f32x4_mul, f32x4_splat, f32x4_sub, f64x2, f64x2_add,
+    f64x2_extract_lane, f64x2_mul, f64x2_splat, f64x2_sub, v128,
+};
+
+// The kernels of one float type, which the functions below dispatch to
+pub trait Kernels: Sized {
+    fn dot(a: &[Self], b: &[Self]) -> Self;
+
+    fn squared_euclidean(a: &[Self], b: &[Self]) -> Self;
+
+    fn axpy(alpha: Self, x: &[Self], y: &mut [Self]);
+}
+
+pub fn dot<T: Kernels>(a: &[T], b: &[T]) -> T {
+    T::dot(a, b)
+}
+
+pub fn squared_euclidean<T: Kernels>(a: &[T], b: &[T]) -> T {
+    T::squared_euclidean(a, b)
+}
+
+pub fn euclidean<T: Float>(a: &[T], b: &[T]) -> T {
+    squared_euclidean(a, b).sqrt()
+}
+
+// y ← y + alpha * x
+pub fn axpy<T: Kernels>(alpha: T, x: &[T], y: &mut [T]) {
+    T::axpy(alpha, x, y)
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+macro_rules! loop_kernels {
+    ($float:ty) => {
+        impl Kernels for $float {
+            fn dot(a: &[$float], b: &[$float]) -> $float {
+                a.iter().zip(b).map(|(x, y)| x * y).sum()
+            }
+
+            fn squared_euclidean(a: &[$float], b: &[$float]) -> $float {
+                a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+            }
+
+            fn axpy(alpha: $float, x: &[$float], y: &mut [$float]) {
+                for (x, y) in x.iter().zip(y) {
+                    *y += alpha * x;
+                }
+            }
+        }
+    };
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+loop_kernels!(f64);
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+loop_kernels!(f32);
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl Kernels for f64 {
+    fn dot(a: &[f64], b: &[f64]) -> f64 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = f64x2_splat(0.0);
+        for (x, y) in a.chunks_exact(2).zip(b.chunks_exact(2)) {
+            sum = f64x2_add(sum, f64x2_mul(f64x2(x[0], x[1]), f64x2(y[0], y[1])));
+        }
+        let tail = n - n % 2;
+        f64_lanes_sum(sum)
+            + a[tail..]
+                .iter()
+                .zip(&b[tail..])
+                .map(|(x, y)| x * y)
+                .sum::<f64>()
+    }
+
+    fn squared_euclidean(a: &[f64], b: &[f64]) -> f64 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = f64x2_splat(0.0);
+        for (x, y) in a.chunks_exact(2).zip(b.chunks_exact(2)) {
+            let difference = f64x2_sub(f64x2(x[0], x[1]), f64x2(y[0], y[1]));
+            sum = f64x2_add(sum, f64x2_mul(difference, difference));
+        }
+        let tail = n - n % 2;
+        f64_lanes_sum(sum)
+            + a[tail..]
+                .iter()
+                .zip(&b[tail..])
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f64>()
+    }
+
+    fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+        let n = x.len().min(y.len());
+        let (x, y) = (&x[..n], &mut y[..n]);
+        let scale = f64x2_splat(alpha);
+        for (x, y) in x.chunks_exact(2).zip(y.chunks_exact_mut(2)) {
+            let sum = f64x2_add(f64x2(y[0], y[1]), f64x2_mul(scale, f64x2(x[0], x[1])));
+            y[0] = f64x2_extract_lane::<0>(sum);
+            y[1] = f64x2_extract_lane::<1>(sum);
+        }
+        let tail = n - n % 2;
+        for (x, y) in x[tail..].iter().zip(&mut y[tail..]) {
+            *y += alpha * x;
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+impl Kernels for f32 {
+    fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = f32x4_splat(0.0);
+        for (x, y) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+            sum = f32x4_add(
+                sum,
+                f32x4_mul(f32x4(x[0], x[1], x[2], x[3]), f32x4(y[0], y[1], y[2], y[3])),
+            );
+        }
+        let tail = n - n % 4;
+        f32_lanes_sum(sum)
+            + a[tail..]
+                .iter()
+                .zip(&b[tail..])
+                .map(|(x, y)| x * y)
+                .sum::<f32>()
+    }
+
+    fn squared_euclidean(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let (a, b) = (&a[..n], &b[..n]);
+        let mut sum = f32x4_splat(0.0);
+        for (x, y) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+            let difference =
+                f32x4_sub(f32x4(x[0], x[1], x[2], x[3]), f32x4(y[0], y[1], y[2], y[3]));
+            sum = f32x4_add(sum, f32x4_mul(difference, difference));
+        }
+        let tail = n - n % 4;
+        f32_lanes_sum(sum)
+            + a[tail..]
+                .iter()
+                .zip(&b[tail..])
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+    }
+
+    fn axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+        let n = x.len().min(y.len());
+        let (x, y) = (&x[..n], &mut y[..n]);
+        let scale = f32x4_splat(alpha);
+        for (x, y) in x.chunks_exact(4).zip(y.chunks_exact_mut(4)) {
+            let sum = f32x4_add(
+                f32x4(y[0], y[1], y[2], y[3]),
+                f32x4_mul(scale, f32x4(x[0], x[1], x[2], x[3])),
+            );
+            y[0] = f32x4_extract_lane::<0>(sum);
+            y[1] = f32x4_extract_lane::<1>(sum);
+            y[2] = f32x4_extract_lane::<2>(sum);
+            y[3] = f32x4_extract_lane::<3>(sum);
+        }
+        let tail = n - n % 4;
+        for (x, y) in x[tail..].iter().zip(&mut y[tail..]) {
+            *y += alpha * x;
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn f64_lanes_sum(lanes: v128) -> f64 {
+    f64x2_extract_lane::<0>(lanes) + f64x2_extract_lane::<1>(lanes)
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn f32_lanes_sum(lanes: v128) -> f32 {
+    f32x4_extract_lane::<0>(lanes)
+        + f32x4_extract_lane::<1>(lanes)
+        + f32x4_extract_lane::<2>(lanes)
+        + f32x4_extract_lane::<3>(lanes)
+}
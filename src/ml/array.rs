@@ -0,0 +1,63 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{Estimator, Predictor, Transformer};
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Interop with ndarray. Models keep working on matrices; arrays are
+// converted at the boundary, so every Estimator, Predictor and Transformer
+// also takes and returns arrays through the extension traits below.
+
+// One row per point. Points must all have the same dimension.
+pub fn array_from_points(points: &[Point]) -> Array2<f64> {
+    let dim = points.first().map_or(0, |p| p.len());
+    Array2::from_shape_fn((points.len(), dim), |(i, j)| points[i][j])
+}
+
+// One point per row
+pub fn points_from_array(array: ArrayView2<f64>) -> Vec<Point> {
+    array.axis_iter(Axis(0)).map(|row| row.to_vec()).collect()
+}
+
+// The same rows in a matrix, whatever the array's memory layout
+pub fn matrix_from_array(array: ArrayView2<f64>) -> Matrix {
+    let (rows, cols) = array.dim();
+    Matrix::from_vec(array.iter().copied().collect(), rows, cols)
+        .expect("an array has rows * cols values")
+}
+
+pub fn array_from_matrix(matrix: Matrix) -> Array2<f64> {
+    let shape = matrix.shape();
+    Array2::from_shape_vec(shape, matrix.into_vec()).expect("a matrix has rows * cols values")
+}
+
+// Estimator::fit on a feature array. Targets stay slices; pass
+// y.as_slice() for an Array1.
+pub trait ArrayEstimator: Estimator {
+    fn fit_array(&mut self, x: ArrayView2<f64>, y: &Self::Target) {
+        self.fit_matrix(&matrix_from_array(x), y);
+    }
+}
+
+pub trait ArrayPredictor: Predictor {
+    fn predict_array(&self, x: ArrayView2<f64>) -> Array1<Self::Output> {
+        Array1::from(self.predict_matrix(&matrix_from_array(x)))
+    }
+}
+
+pub trait ArrayTransformer: Transformer {
+    fn transform_array(&self, x: ArrayView2<f64>) -> Array2<f64> {
+        array_from_matrix(self.transform_matrix(&matrix_from_array(x)))
+    }
+
+    fn fit_transform_array(&mut self, x: ArrayView2<f64>, y: &Self::Target) -> Array2<f64> {
+        array_from_matrix(self.fit_transform_matrix(&matrix_from_array(x), y))
+    }
+}
+
+impl<E: Estimator + ?Sized> ArrayEstimator for E {}
+
+impl<P: Predictor + ?Sized> ArrayPredictor for P {}
+
+impl<T: Transformer + ?Sized> ArrayTransformer for T {}
@@ -0,0 +1,209 @@
+use crate::error::{MoonlightError, Result};
+use crate::math::float::Float;
+use crate::math::matrix::Matrix;
+use crate::ml::observer::FitObserver;
+use crate::ml::validation::{check_array, check_matrix, ArrayCheck, NanPolicy};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Anything that learns from data. Generic pipeline and UI code drives every
+// model through these traits, whatever the model's own method names.
+//
+// Models work on the rows of a dense Matrix. The methods taking points copy
+// them into one first, and panic on points of different lengths. Only the
+// try_ methods validate their input, see validation; the others assume it
+// is usable.
+pub trait Estimator {
+    // What fit learns from besides the features: [usize] class labels,
+    // [f64] values, or () for unsupervised models
+    type Target: Targets + ?Sized;
+
+    // Learns from the rows of x
+    fn fit_matrix(&mut self, x: &Matrix, y: &Self::Target);
+
+    fn fit(&mut self, x: &[Point], y: &Self::Target) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    // Fewest points fit can learn from, e.g. k for k clusters
+    fn min_samples(&self) -> usize {
+        1
+    }
+
+    // Whether fit takes NaN for a missing value
+    fn nan_policy(&self) -> NanPolicy {
+        NanPolicy::Reject
+    }
+
+    // Features of the points fit saw, None before fitting or when the model
+    // doesn't keep track
+    fn n_features_in(&self) -> Option<usize> {
+        None
+    }
+
+    // What try_fit requires of the points
+    fn fit_check(&self) -> ArrayCheck {
+        ArrayCheck::new()
+            .with_min_samples(self.min_samples())
+            .with_nan_policy(self.nan_policy())
+    }
+
+    // What try_predict and try_transform require of the points
+    fn input_check(&self) -> ArrayCheck {
+        ArrayCheck::new()
+            .with_min_samples(0)
+            .with_n_features(self.n_features_in())
+            .with_nan_policy(self.nan_policy())
+    }
+
+    // fit, refusing data it can't learn from rather than quietly fitting
+    // nothing or nonsense, see check_array. Refuses targets other than one
+    // per point too.
+    fn try_fit(&mut self, x: &[Point], y: &Self::Target) -> Result<()> {
+        check_array(x, &self.fit_check())?;
+        check_targets(x.len(), y)?;
+        self.fit(x, y);
+        Ok(())
+    }
+
+    // try_fit on the rows of a matrix
+    fn try_fit_matrix(&mut self, x: &Matrix, y: &Self::Target) -> Result<()> {
+        check_matrix(x, &self.fit_check())?;
+        check_targets(x.n_rows(), y)?;
+        self.fit_matrix(x, y);
+        Ok(())
+    }
+}
+
+// A fitted estimator that assigns an output to every new point: a class,
+// a cluster or a value
+pub trait Predictor: Estimator {
+    type Output;
+
+    // One output per row of x
+    fn predict_matrix(&self, x: &Matrix) -> Vec<Self::Output>;
+
+    fn predict(&self, x: &[Point]) -> Vec<Self::Output> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    // predict, refusing points with other than n_features_in features or
+    // values the model can't take
+    fn try_predict(&self, x: &[Point]) -> Result<Vec<Self::Output>> {
+        check_array(x, &self.input_check())?;
+        Ok(self.predict(x))
+    }
+
+    // try_predict on the rows of a matrix
+    fn try_predict_matrix(&self, x: &Matrix) -> Result<Vec<Self::Output>> {
+        check_matrix(x, &self.input_check())?;
+        Ok(self.predict_matrix(x))
+    }
+}
+
+// A fitted estimator that maps points to new features
+pub trait Transformer: Estimator {
+    // New features of every row of x, a row each
+    fn transform_matrix(&self, x: &Matrix) -> Matrix;
+
+    fn transform(&self, x: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(x)).to_points()
+    }
+
+    // Whether transform returns its input unchanged, as Passthrough does.
+    // Pipelines then check their input against the next step instead.
+    fn is_identity(&self) -> bool {
+        false
+    }
+
+    fn fit_transform_matrix(&mut self, x: &Matrix, y: &Self::Target) -> Matrix {
+        self.fit_matrix(x, y);
+        self.transform_matrix(x)
+    }
+
+    fn fit_transform(&mut self, x: &[Point], y: &Self::Target) -> Vec<Point> {
+        self.fit_transform_matrix(&point_matrix(x), y).to_points()
+    }
+
+    // transform, refusing points as try_predict does
+    fn try_transform(&self, x: &[Point]) -> Result<Vec<Point>> {
+        check_array(x, &self.input_check())?;
+        Ok(self.transform(x))
+    }
+
+    // try_transform on the rows of a matrix
+    fn try_transform_matrix(&self, x: &Matrix) -> Result<Matrix> {
+        check_matrix(x, &self.input_check())?;
+        Ok(self.transform_matrix(x))
+    }
+
+    // fit_transform, refusing points as try_fit does
+    fn try_fit_transform(&mut self, x: &[Point], y: &Self::Target) -> Result<Vec<Point>> {
+        check_array(x, &self.fit_check())?;
+        check_targets(x.len(), y)?;
+        Ok(self.fit_transform(x, y))
+    }
+
+    // try_fit_transform on the rows of a matrix
+    fn try_fit_transform_matrix(&mut self, x: &Matrix, y: &Self::Target) -> Result<Matrix> {
+        check_matrix(x, &self.fit_check())?;
+        check_targets(x.n_rows(), y)?;
+        Ok(self.fit_transform_matrix(x, y))
+    }
+}
+
+// An estimator that can keep learning from new batches without revisiting
+// old ones, for data that arrives over time. Starts from scratch when
+// nothing has been fitted yet, and from the fitted model otherwise.
+pub trait IncrementalEstimator: Estimator {
+    fn partial_fit_matrix(&mut self, x: &Matrix, y: &Self::Target);
+
+    fn partial_fit(&mut self, x: &[Point], y: &Self::Target) {
+        self.partial_fit_matrix(&point_matrix(x), y);
+    }
+}
+
+// An estimator that fits in iterations or epochs and can report each to an
+// observer as it goes, e.g. to draw a progress bar or the loss so far
+pub trait IterativeEstimator: Estimator {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &Self::Target, observer: &mut dyn FitObserver);
+
+    fn fit_observed(&mut self, x: &[Point], y: &Self::Target, observer: &mut dyn FitObserver) {
+        self.fit_observed_matrix(&point_matrix(x), y, observer);
+    }
+}
+
+// An estimator's Target, as far as try_fit checks it
+pub trait Targets {
+    // One target per point, None for unsupervised models
+    fn n_targets(&self) -> Option<usize>;
+}
+
+impl<T> Targets for [T] {
+    fn n_targets(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl Targets for () {
+    fn n_targets(&self) -> Option<usize> {
+        None
+    }
+}
+
+fn check_targets<Y: Targets + ?Sized>(n_samples: usize, y: &Y) -> Result<()> {
+    match y.n_targets() {
+        Some(found) if found != n_samples => Err(MoonlightError::DimensionMismatch {
+            expected: n_samples,
+            found,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Points as the rows of a matrix, for the methods that take points and
+// wrap the ones taking a matrix
+pub(crate) fn point_matrix<T: Float>(x: &[Vec<T>]) -> Matrix<T> {
+    Matrix::from_rows(x).expect("points of different lengths")
+}
@@ -0,0 +1,129 @@
+// Builders for the estimators: X::builder() takes the parameters by name
+// and build() returns the estimator, every parameter not given at its
+// default, e.g.
+//
+//     KMeans::builder().k(5).max_iter(100).tolerance(1e-4).build()
+//
+// Parameters without a sensible default, e.g. the number of clusters, are
+// required: build() only exists once they're given, so leaving one out
+// doesn't compile. Builders make the estimator with its default metric,
+// optimizer and float type; see its with_metric, with_optimizer or cast for
+// others.
+
+// The required parameter of a builder that hasn't been given yet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Unset;
+
+// Defines $builder with a setter per parameter and $model::builder(). The
+// required parameter, if any, is passed to $model::new, which must exist,
+// and otherwise the build starts from $model::default(). Parameters under
+// some are Option fields that the setter sets to Some.
+macro_rules! builder {
+    (
+        $model:ident => $builder:ident,
+        new($required:ident: $required_ty:ty),
+        { $($field:ident: $ty:ty),* $(,)? }
+        $(, some { $($some:ident: $some_ty:ty),* $(,)? })?
+    ) => {
+        #[derive(Debug, Clone)]
+        pub struct $builder<R = $crate::ml::builder::Unset> {
+            $required: R,
+            $($field: Option<$ty>,)*
+            $($($some: Option<$some_ty>,)*)?
+        }
+
+        impl $model {
+            pub fn builder() -> $builder {
+                $builder {
+                    $required: $crate::ml::builder::Unset,
+                    $($field: None,)*
+                    $($($some: None,)*)?
+                }
+            }
+        }
+
+        impl $builder {
+            pub fn $required(self, $required: $required_ty) -> $builder<$required_ty> {
+                $builder {
+                    $required,
+                    $($field: self.$field,)*
+                    $($($some: self.$some,)*)?
+                }
+            }
+        }
+
+        impl<R> $builder<R> {
+            $crate::ml::builder::builder!(@setters $($field: $ty),* $(; $($some: $some_ty),*)?);
+        }
+
+        impl $builder<$required_ty> {
+            pub fn build(self) -> $model {
+                #[allow(unused_mut)]
+                let mut model = <$model>::new(self.$required);
+                $crate::ml::builder::builder!(
+                    @apply self, model, $($field),* $(; $($some),*)?
+                );
+                model
+            }
+        }
+    };
+    (
+        $model:ident => $builder:ident,
+        default,
+        { $($field:ident: $ty:ty),* $(,)? }
+        $(, some { $($some:ident: $some_ty:ty),* $(,)? })?
+    ) => {
+        #[derive(Debug, Clone, Default)]
+        pub struct $builder {
+            $($field: Option<$ty>,)*
+            $($($some: Option<$some_ty>,)*)?
+        }
+
+        impl $model {
+            pub fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+
+        impl $builder {
+            $crate::ml::builder::builder!(@setters $($field: $ty),* $(; $($some: $some_ty),*)?);
+
+            pub fn build(self) -> $model {
+                #[allow(unused_mut)]
+                let mut model = <$model>::default();
+                $crate::ml::builder::builder!(
+                    @apply self, model, $($field),* $(; $($some),*)?
+                );
+                model
+            }
+        }
+    };
+    (@setters $($field:ident: $ty:ty),* $(; $($some:ident: $some_ty:ty),*)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.$field = Some($field);
+                self
+            }
+        )*
+        $($(
+            pub fn $some(mut self, $some: $some_ty) -> Self {
+                self.$some = Some($some);
+                self
+            }
+        )*)?
+    };
+    (@apply $builder:ident, $model:ident, $($field:ident),* $(; $($some:ident),*)?) => {
+        $(
+            if let Some($field) = $builder.$field {
+                $model.$field = $field;
+            }
+        )*
+        $($(
+            if $builder.$some.is_some() {
+                $model.$some = $builder.$some;
+            }
+        )*)?
+    };
+}
+
+pub(crate) use builder;
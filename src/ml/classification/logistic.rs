@@ -0,0 +1,417 @@
+use super::{most_probable, Classifier};
+use crate::data::sparse::{CsrMatrix, Rows};
+use crate::math::float::{cast_points, cast_slice, Float};
+use crate::math::matrix::Matrix;
+use crate::ml::base::{Estimator, IncrementalEstimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::onnx::{self, OnnxError, ToOnnx};
+use crate::ml::optim::{Adam, Optimizer};
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::{dot, softmax};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const LEARNING_RATE: f64 = 0.1;
+const ALPHA: f64 = 1e-4;
+const MAX_ITER: usize = 1000;
+const EPSILON: f64 = 1e-6;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Multinomial logistic regression: class probabilities are the softmax of
+// one linear function per class. Trained by full-batch gradient descent on
+// the mean cross-entropy with the given optimizer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticRegression<O: Optimizer = Adam> {
+    pub fit_intercept: bool,
+    // L2 penalty on the coefficients
+    pub alpha: f64,
+    pub max_iter: usize,
+    // Stop once an iteration changes the loss by less than this
+    pub tolerance: f64,
+    // Update rule for the coefficients, carrying its own learning rate
+    pub optimizer: O,
+
+    // Current state
+    // One row of coefficients per class
+    coefficients: Vec<Point>,
+    intercepts: Vec<f64>,
+    loss_curve: Vec<f64>,
+    converged: bool,
+}
+
+impl<O: Optimizer> fmt::Display for LogisticRegression<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LogisticRegression{{ fit_intercept: {}, alpha: {}, max_iter: {}, optimizer: {:?} }}",
+            self.fit_intercept, self.alpha, self.max_iter, self.optimizer
+        )
+    }
+}
+
+impl Default for LogisticRegression {
+    fn default() -> Self {
+        LogisticRegression::new()
+    }
+}
+
+builder!(LogisticRegression => LogisticRegressionBuilder, default, {
+    fit_intercept: bool,
+    alpha: f64,
+    max_iter: usize,
+    tolerance: f64,
+    optimizer: Adam,
+});
+
+impl LogisticRegression {
+    pub fn new() -> Self {
+        LogisticRegression::with_optimizer(Adam::new(LEARNING_RATE))
+    }
+}
+
+impl<O: Optimizer> LogisticRegression<O> {
+    pub fn with_optimizer(optimizer: O) -> Self {
+        LogisticRegression {
+            fit_intercept: true,
+            alpha: ALPHA,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            optimizer,
+            coefficients: vec![],
+            intercepts: vec![],
+            loss_curve: vec![],
+            converged: false,
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn coefficients(&self) -> &[Point] {
+        &self.coefficients
+    }
+
+    pub fn intercepts(&self) -> &[f64] {
+        &self.intercepts
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.intercepts.len()
+    }
+
+    // Penalized training loss after every iteration of the last fit, and
+    // after every partial_fit batch since
+    pub fn loss_curve(&self) -> &[f64] {
+        &self.loss_curve
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.loss_curve.len()
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    // Linear score of every class for each point, before the softmax
+    pub fn decision_function(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.decision_rows(x)
+    }
+
+    // Estimator::fit on the rows of a sparse matrix, e.g. TF-IDF features. The
+    // gradient of every row costs only its nonzeros.
+    pub fn fit_sparse(&mut self, x: &CsrMatrix, y: &[usize]) {
+        self.fit_rows(x, y);
+    }
+
+    // Estimator::fit on the rows of a dense matrix of any float type, e.g.
+    // Matrix<f32> for half the memory. The rows are swept in T; the fitted
+    // coefficients are f64 whatever T.
+    pub fn fit_matrix<T: Float>(&mut self, x: &Matrix<T>, y: &[usize]) {
+        self.fit_rows(x, y);
+    }
+
+    pub fn predict_proba_matrix<T: Float>(&self, x: &Matrix<T>) -> Vec<Vec<f64>> {
+        self.decision_rows(x)
+            .iter()
+            .map(|scores| softmax(scores))
+            .collect()
+    }
+
+    pub fn predict_sparse(&self, x: &CsrMatrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_sparse(x))
+    }
+
+    pub fn predict_proba_sparse(&self, x: &CsrMatrix) -> Vec<Vec<f64>> {
+        self.decision_rows(x)
+            .iter()
+            .map(|scores| softmax(scores))
+            .collect()
+    }
+
+    fn decision_rows<T: Float, R: Rows<T> + ?Sized>(&self, x: &R) -> Vec<Vec<f64>> {
+        let coefficients: Vec<Vec<T>> = cast_points(&self.coefficients);
+        (0..x.n_rows())
+            .map(|i| {
+                coefficients
+                    .iter()
+                    .zip(&self.intercepts)
+                    .map(|(w, b)| x.row_dot(i, w).as_f64() + b)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn fit_rows<T: Float, R: Rows<T> + ?Sized>(&mut self, x: &R, y: &[usize]) {
+        self.fit_notified(x, y, &mut Notifier::none());
+    }
+
+    fn fit_notified<T: Float, R: Rows<T> + ?Sized>(
+        &mut self,
+        x: &R,
+        y: &[usize],
+        notifier: &mut Notifier,
+    ) {
+        self.coefficients.clear();
+        self.intercepts.clear();
+        self.loss_curve.clear();
+        self.converged = false;
+        if x.n_rows() == 0 || x.n_cols() == 0 {
+            return;
+        }
+
+        let n_classes = y.iter().max().map_or(0, |&m| m + 1);
+        let dim = x.n_cols();
+        // Coefficients of every class row by row, followed by the intercepts
+        let n_weights = n_classes * dim;
+        let mut params = vec![0.0; n_weights + n_classes];
+        let mut optimizer = self.optimizer.clone();
+        optimizer.reset();
+        let mut previous_loss = f64::INFINITY;
+
+        for _ in 0..self.max_iter {
+            let (loss, gradient) = self.loss_and_gradient(x, y, &params, n_classes);
+            self.loss_curve.push(loss);
+            notifier.iteration(|| FitMetrics::new(self.loss_curve.len()).with("loss", loss));
+
+            optimizer.step(&mut params, &gradient);
+            if (previous_loss - loss).abs() < self.tolerance {
+                self.converged = true;
+                notifier.converged();
+                break;
+            }
+            previous_loss = loss;
+        }
+
+        self.unpack(&params, n_classes, dim);
+    }
+
+    // Penalized mean cross-entropy of the rows and its gradient, at params
+    // packed as in fit_rows. The products with the rows are in T, the rest
+    // in f64.
+    fn loss_and_gradient<T: Float, R: Rows<T> + ?Sized>(
+        &self,
+        x: &R,
+        y: &[usize],
+        params: &[f64],
+        n_classes: usize,
+    ) -> (f64, Vec<f64>) {
+        let dim = x.n_cols();
+        let n = x.n_rows() as f64;
+        let n_weights = n_classes * dim;
+        let (weights, intercepts) = params.split_at(n_weights);
+        let row_weights: Vec<T> = cast_slice(weights);
+        let mut weight_gradient = vec![T::zero(); n_weights];
+        let mut intercept_gradient = vec![0.0; n_classes];
+        let mut loss = 0.0;
+        for (i, &label) in y.iter().enumerate().take(x.n_rows()) {
+            let scores: Vec<f64> = row_weights
+                .chunks(dim)
+                .zip(intercepts)
+                .map(|(w, b)| x.row_dot(i, w).as_f64() + b)
+                .collect();
+            let proba = softmax(&scores);
+            loss -= proba[label].max(f64::MIN_POSITIVE).ln();
+
+            // d(cross-entropy)/d(score) = p - one_hot(label)
+            for (class, ((row, b), p)) in weight_gradient
+                .chunks_mut(dim)
+                .zip(&mut intercept_gradient)
+                .zip(&proba)
+                .enumerate()
+            {
+                let delta = p - if class == label { 1.0 } else { 0.0 };
+                x.row_add_to(i, T::from_f64(delta), row);
+                if self.fit_intercept {
+                    *b += delta;
+                }
+            }
+        }
+
+        let mut gradient: Vec<f64> = cast_slice(&weight_gradient);
+        gradient.extend(intercept_gradient);
+        gradient.iter_mut().for_each(|g| *g /= n);
+        for (g, w) in gradient.iter_mut().zip(weights) {
+            *g += self.alpha * w;
+        }
+        let loss = loss / n + 0.5 * self.alpha * dot(weights, weights);
+        (loss, gradient)
+    }
+
+    // Coefficients and intercepts from params packed as in fit_rows
+    fn unpack(&mut self, params: &[f64], n_classes: usize, dim: usize) {
+        let (weights, intercepts) = params.split_at(n_classes * dim);
+        self.coefficients = weights.chunks(dim).map(|row| row.to_vec()).collect();
+        self.intercepts = intercepts.to_vec();
+    }
+
+    fn partial_fit_rows<R: Rows<f64> + ?Sized>(&mut self, x: &R, y: &[usize]) {
+        if x.n_rows() == 0 || x.n_cols() == 0 {
+            return;
+        }
+        let dim = x.n_cols();
+        if self.coefficients.first().is_some_and(|w| w.len() != dim) {
+            self.coefficients.clear();
+            self.intercepts.clear();
+        }
+        let n_classes = y.iter().max().map_or(0, |&m| m + 1).max(self.n_classes());
+        if n_classes > self.n_classes() {
+            self.coefficients.resize(n_classes, vec![0.0; dim]);
+            self.intercepts.resize(n_classes, 0.0);
+            self.optimizer.reset();
+        }
+
+        let mut params = self.coefficients.concat();
+        params.extend(&self.intercepts);
+        let (loss, gradient) = self.loss_and_gradient(x, y, &params, n_classes);
+        self.loss_curve.push(loss);
+        self.optimizer.step(&mut params, &gradient);
+        self.unpack(&params, n_classes, dim);
+    }
+}
+
+impl<O: Optimizer> Estimator for LogisticRegression<O> {
+    type Target = [usize];
+
+    fn fit(&mut self, x: &[Point], y: &[usize]) {
+        self.fit_rows(x, y);
+    }
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        LogisticRegression::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.coefficients.first().map(Vec::len)
+    }
+}
+
+// An iteration per gradient step on the whole data, reporting the loss
+impl<O: Optimizer> IterativeEstimator for LogisticRegression<O> {
+    fn fit_observed(&mut self, x: &[Point], y: &[usize], observer: &mut dyn FitObserver) {
+        self.fit_notified(x, y, &mut Notifier::new(observer));
+    }
+
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[usize], observer: &mut dyn FitObserver) {
+        self.fit_notified(x, y, &mut Notifier::new(observer));
+    }
+}
+
+impl<O: Optimizer> IncrementalEstimator for LogisticRegression<O> {
+    // One optimizer step on the loss of the batch, so successive batches run
+    // mini-batch gradient descent, with the optimizer field keeping its
+    // state in between. A batch with a new, higher label adds classes and
+    // restarts the optimizer, whose state is sized to the old parameters.
+    fn partial_fit(&mut self, x: &[Point], y: &[usize]) {
+        self.partial_fit_rows(x, y);
+    }
+
+    fn partial_fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        self.partial_fit_rows(x, y);
+    }
+}
+
+impl<O: Optimizer> Predictor for LogisticRegression<O> {
+    type Output = usize;
+
+    fn predict(&self, x: &[Point]) -> Vec<usize> {
+        most_probable(&self.predict_proba(x))
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&LogisticRegression::predict_proba_matrix(self, x))
+    }
+}
+
+impl<O: Optimizer> Classifier for LogisticRegression<O> {
+    fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.decision_function(x)
+            .iter()
+            .map(|scores| softmax(scores))
+            .collect()
+    }
+
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        LogisticRegression::predict_proba_matrix(self, x)
+    }
+}
+
+impl<O: Optimizer> ToOnnx for LogisticRegression<O> {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.coefficients.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::softmax_classifier(
+            &self.coefficients,
+            &self.intercepts,
+        ))
+    }
+}
+
+// A multinomial model, as scikit-learn fits by default. One-vs-rest scores
+// are loaded as they are, so their probabilities come out different.
+impl FromSklearn for LogisticRegression {
+    const CLASS: &'static str = "LogisticRegression";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let n_classes = attributes.n_classes()?;
+        let mut coefficients = attributes.matrix("coef_")?;
+        let mut intercepts = if attributes.contains("intercept_") {
+            attributes.vector("intercept_")?
+        } else {
+            vec![0.0; coefficients.len()]
+        };
+        if intercepts.len() != coefficients.len() {
+            return Err(sklearn::invalid(
+                "intercept_",
+                format!(
+                    "{} values for {} rows of coef_",
+                    intercepts.len(),
+                    coefficients.len()
+                ),
+            ));
+        }
+        // Two classes have one row, scoring class 1 against a class 0 of 0
+        if n_classes == 2 && coefficients.len() == 1 {
+            coefficients.insert(0, vec![0.0; coefficients[0].len()]);
+            intercepts.insert(0, 0.0);
+        }
+        if coefficients.len() != n_classes {
+            return Err(sklearn::invalid(
+                "coef_",
+                format!("{} rows for {} classes", coefficients.len(), n_classes),
+            ));
+        }
+        sklearn::check_features(attributes, coefficients[0].len(), "coef_")?;
+        let mut model = LogisticRegression::new();
+        model.fit_intercept = attributes.optional("fit_intercept")?.unwrap_or(true);
+        model.coefficients = coefficients;
+        model.intercepts = intercepts;
+        Ok(model)
+    }
+}
@@ -0,0 +1,31 @@
+pub mod logistic;
+pub mod naive_bayes;
+
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Predictor};
+use crate::ml::metrics::classification::accuracy_score;
+use crate::ml::utils::argmax;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Shared interface of the models that predict a probability per class, so
+// any of them can drive the same UI components. Labels are 0..n_classes.
+pub trait Classifier: Estimator<Target = [usize]> + Predictor<Output = usize> {
+    // One row per row of x with a probability per class
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>>;
+
+    fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.predict_proba_matrix(&point_matrix(x))
+    }
+
+    // Mean accuracy on x against y
+    fn score(&self, x: &[Point], y: &[usize]) -> f64 {
+        accuracy_score(y, &self.predict(x))
+    }
+}
+
+// Most probable class of every row of probabilities
+pub(crate) fn most_probable(proba: &[Vec<f64>]) -> Vec<usize> {
+    proba.iter().map(|row| argmax(row)).collect()
+}
@@ -0,0 +1,399 @@
+use super::{most_probable, Classifier};
+use crate::math::matrix::Matrix;
+use crate::ml::base::{Estimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::utils::log_sum_exp;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Portion of the largest feature variance added to every variance
+const VAR_SMOOTHING: f64 = 1e-9;
+
+// Features are independent normals within each class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GaussianNB {
+    pub var_smoothing: f64,
+
+    // Current state
+    class_log_prior: Vec<f64>,
+    // Per class and feature
+    means: Vec<Vec<f64>>,
+    variances: Vec<Vec<f64>>,
+}
+
+// Features are counts (e.g. word occurrences) drawn from a multinomial per
+// class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultinomialNB {
+    // Additive (Laplace/Lidstone) smoothing of the counts
+    pub alpha: f64,
+
+    // Current state
+    class_log_prior: Vec<f64>,
+    feature_log_prob: Vec<Vec<f64>>,
+}
+
+// Features are binary occurrences drawn from a Bernoulli per class. Absent
+// features count as evidence too, unlike MultinomialNB.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BernoulliNB {
+    // Additive (Laplace/Lidstone) smoothing of the counts
+    pub alpha: f64,
+    // Values above the threshold count as present. None when the features
+    // are already 0/1.
+    pub binarize: Option<f64>,
+
+    // Current state
+    class_log_prior: Vec<f64>,
+    // log P(feature present | class)
+    feature_log_prob: Vec<Vec<f64>>,
+}
+
+impl fmt::Display for GaussianNB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GaussianNB{{ var_smoothing: {} }}", self.var_smoothing)
+    }
+}
+
+impl fmt::Display for MultinomialNB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MultinomialNB{{ alpha: {} }}", self.alpha)
+    }
+}
+
+impl fmt::Display for BernoulliNB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BernoulliNB{{ alpha: {}, binarize: {:?} }}",
+            self.alpha, self.binarize
+        )
+    }
+}
+
+impl Default for GaussianNB {
+    fn default() -> Self {
+        GaussianNB::new()
+    }
+}
+
+builder!(GaussianNB => GaussianNBBuilder, default, {
+    var_smoothing: f64,
+});
+
+impl Default for MultinomialNB {
+    fn default() -> Self {
+        MultinomialNB::new(1.0)
+    }
+}
+
+builder!(MultinomialNB => MultinomialNBBuilder, default, {
+    alpha: f64,
+});
+
+impl Default for BernoulliNB {
+    fn default() -> Self {
+        BernoulliNB::new(1.0)
+    }
+}
+
+builder!(BernoulliNB => BernoulliNBBuilder, default, {
+    alpha: f64,
+    binarize: Option<f64>,
+});
+
+impl GaussianNB {
+    pub fn new() -> Self {
+        GaussianNB {
+            var_smoothing: VAR_SMOOTHING,
+            class_log_prior: vec![],
+            means: vec![],
+            variances: vec![],
+        }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.class_log_prior.len()
+    }
+
+    pub fn means(&self) -> &[Vec<f64>] {
+        &self.means
+    }
+
+    pub fn variances(&self) -> &[Vec<f64>] {
+        &self.variances
+    }
+
+    fn joint_log_likelihood(&self, point: &[f64]) -> Vec<f64> {
+        self.class_log_prior
+            .iter()
+            .zip(self.means.iter().zip(&self.variances))
+            .map(|(log_prior, (means, variances))| {
+                log_prior
+                    + point
+                        .iter()
+                        .zip(means.iter().zip(variances))
+                        .map(|(x, (mean, var))| {
+                            -0.5 * ((2.0 * std::f64::consts::PI * var).ln()
+                                + (x - mean).powi(2) / var)
+                        })
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+impl Estimator for GaussianNB {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        let groups = group_by_class(x, y);
+        let dim = x.n_cols();
+        self.class_log_prior = class_log_prior(&groups, x.n_rows());
+
+        self.means = groups
+            .iter()
+            .map(|members| mean_per_feature(members, dim))
+            .collect();
+        self.variances = groups
+            .iter()
+            .zip(&self.means)
+            .map(|(members, means)| {
+                let mut variances = vec![0.0; dim];
+                for point in members {
+                    for ((var, x), mean) in variances.iter_mut().zip(*point).zip(means) {
+                        *var += (x - mean).powi(2);
+                    }
+                }
+                let count = members.len().max(1) as f64;
+                variances.iter().map(|v| v / count).collect()
+            })
+            .collect();
+
+        // Keep every variance positive, scaled to the data
+        let overall = mean_per_feature(&x.iter_rows().collect::<Vec<_>>(), dim);
+        let largest = (0..dim)
+            .map(|j| x.column(j).map(|v| (v - overall[j]).powi(2)).sum::<f64>() / x.n_rows() as f64)
+            .fold(0.0, f64::max);
+        let epsilon = (self.var_smoothing * largest).max(f64::MIN_POSITIVE);
+        for variances in &mut self.variances {
+            variances.iter_mut().for_each(|v| *v += epsilon);
+        }
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.means.first().map(Vec::len)
+    }
+}
+
+impl Predictor for GaussianNB {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+}
+
+impl Classifier for GaussianNB {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        x.iter_rows()
+            .map(|point| normalize(self.joint_log_likelihood(point)))
+            .collect()
+    }
+}
+
+impl MultinomialNB {
+    pub fn new(alpha: f64) -> Self {
+        MultinomialNB {
+            alpha,
+            class_log_prior: vec![],
+            feature_log_prob: vec![],
+        }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.class_log_prior.len()
+    }
+
+    // log P(feature | class) per class and feature
+    pub fn feature_log_prob(&self) -> &[Vec<f64>] {
+        &self.feature_log_prob
+    }
+
+    fn joint_log_likelihood(&self, point: &[f64]) -> Vec<f64> {
+        self.class_log_prior
+            .iter()
+            .zip(&self.feature_log_prob)
+            .map(|(log_prior, log_prob)| {
+                log_prior
+                    + point
+                        .iter()
+                        .zip(log_prob)
+                        .map(|(count, lp)| count * lp)
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+impl Estimator for MultinomialNB {
+    type Target = [usize];
+
+    // Features must be non-negative counts or frequencies
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        let groups = group_by_class(x, y);
+        let dim = x.n_cols();
+        self.class_log_prior = class_log_prior(&groups, x.n_rows());
+
+        self.feature_log_prob = groups
+            .iter()
+            .map(|members| {
+                let mut counts = vec![self.alpha; dim];
+                for point in members {
+                    counts.iter_mut().zip(*point).for_each(|(c, x)| *c += x);
+                }
+                let total: f64 = counts.iter().sum();
+                counts.iter().map(|c| (c / total).ln()).collect()
+            })
+            .collect();
+    }
+}
+
+impl Predictor for MultinomialNB {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+}
+
+impl Classifier for MultinomialNB {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        x.iter_rows()
+            .map(|point| normalize(self.joint_log_likelihood(point)))
+            .collect()
+    }
+}
+
+impl BernoulliNB {
+    pub fn new(alpha: f64) -> Self {
+        BernoulliNB {
+            alpha,
+            binarize: Some(0.0),
+            class_log_prior: vec![],
+            feature_log_prob: vec![],
+        }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.class_log_prior.len()
+    }
+
+    // log P(feature present | class) per class and feature
+    pub fn feature_log_prob(&self) -> &[Vec<f64>] {
+        &self.feature_log_prob
+    }
+
+    fn present(&self, value: f64) -> bool {
+        match self.binarize {
+            Some(threshold) => value > threshold,
+            None => value != 0.0,
+        }
+    }
+
+    fn joint_log_likelihood(&self, point: &[f64]) -> Vec<f64> {
+        self.class_log_prior
+            .iter()
+            .zip(&self.feature_log_prob)
+            .map(|(log_prior, log_prob)| {
+                log_prior
+                    + point
+                        .iter()
+                        .zip(log_prob)
+                        .map(|(&x, &lp)| {
+                            if self.present(x) {
+                                lp
+                            } else {
+                                (-lp.exp()).ln_1p()
+                            }
+                        })
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+impl Estimator for BernoulliNB {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        let groups = group_by_class(x, y);
+        let dim = x.n_cols();
+        self.class_log_prior = class_log_prior(&groups, x.n_rows());
+
+        self.feature_log_prob = groups
+            .iter()
+            .map(|members| {
+                let mut present = vec![self.alpha; dim];
+                for point in members {
+                    for (count, &x) in present.iter_mut().zip(*point) {
+                        if self.present(x) {
+                            *count += 1.0;
+                        }
+                    }
+                }
+                let total = members.len() as f64 + 2.0 * self.alpha;
+                present.iter().map(|c| (c / total).ln()).collect()
+            })
+            .collect();
+    }
+}
+
+impl Predictor for BernoulliNB {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+}
+
+impl Classifier for BernoulliNB {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        x.iter_rows()
+            .map(|point| normalize(self.joint_log_likelihood(point)))
+            .collect()
+    }
+}
+
+// Points of each class, indexed by label
+fn group_by_class<'a>(x: &'a Matrix, y: &[usize]) -> Vec<Vec<&'a [f64]>> {
+    let n_classes = y.iter().max().map_or(0, |&m| m + 1);
+    let mut groups = vec![vec![]; n_classes];
+    for (point, &label) in x.iter_rows().zip(y) {
+        groups[label].push(point);
+    }
+    groups
+}
+
+// Empirical class frequencies. Labels missing from y get a prior of zero.
+fn class_log_prior(groups: &[Vec<&[f64]>], n_samples: usize) -> Vec<f64> {
+    groups
+        .iter()
+        .map(|members| (members.len() as f64 / n_samples as f64).ln())
+        .collect()
+}
+
+fn mean_per_feature(points: &[&[f64]], dim: usize) -> Vec<f64> {
+    let mut means = vec![0.0; dim];
+    for point in points {
+        means.iter_mut().zip(*point).for_each(|(m, x)| *m += x);
+    }
+    let count = points.len().max(1) as f64;
+    means.iter().map(|m| m / count).collect()
+}
+
+// Turn joint log-likelihoods into probabilities summing to 1
+fn normalize(log_likelihood: Vec<f64>) -> Vec<f64> {
+    let total = log_sum_exp(&log_likelihood);
+    log_likelihood.iter().map(|ll| (ll - total).exp()).collect()
+}
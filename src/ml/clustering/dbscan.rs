@@ -0,0 +1,176 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator};
+use crate::ml::builder::builder;
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use crate::ml::neighbors::{build_index, Algorithm, NeighborSearch};
+use crate::ml::params::{param_value, Configurable, ParamSpec, Params};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+const EPS: f64 = 0.5;
+const MIN_SAMPLES: usize = 5;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Density-based clustering: points with at least min_samples neighbors
+// within eps are core points, and clusters grow through chains of core
+// points. Points reachable from no core point are labelled noise (None).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dbscan<M: Metric = Euclidean> {
+    pub eps: f64,
+    pub min_samples: usize,
+    pub metric: M,
+    // Index used for the eps-neighborhood queries
+    pub algorithm: Algorithm,
+
+    // Current state
+    labels: Vec<Option<usize>>,
+    core_samples: Vec<usize>,
+    n_clusters: usize,
+}
+
+impl<M: Metric> fmt::Display for Dbscan<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Dbscan{{ eps: {}, min_samples: {}, metric: {:?} }}",
+            self.eps, self.min_samples, self.metric
+        )
+    }
+}
+
+impl Dbscan {
+    pub fn new(eps: f64, min_samples: usize) -> Self {
+        Dbscan::with_metric(eps, min_samples, Euclidean)
+    }
+}
+
+impl Default for Dbscan {
+    fn default() -> Self {
+        Dbscan::new(EPS, MIN_SAMPLES)
+    }
+}
+
+builder!(Dbscan => DbscanBuilder, default, {
+    eps: f64,
+    min_samples: usize,
+    algorithm: Algorithm,
+});
+
+impl<M: Metric> Dbscan<M> {
+    pub fn with_metric(eps: f64, min_samples: usize, metric: M) -> Self {
+        Dbscan {
+            eps,
+            min_samples,
+            metric,
+            algorithm: Algorithm::Auto,
+            labels: vec![],
+            core_samples: vec![],
+            n_clusters: 0,
+        }
+    }
+
+    // Cluster per point, None for noise
+    pub fn labels(&self) -> &[Option<usize>] {
+        &self.labels
+    }
+
+    pub fn core_samples(&self) -> &[usize] {
+        &self.core_samples
+    }
+
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        // A point counts as its own neighbor, as in scikit-learn
+        let index = build_index(data.clone(), self.metric.clone(), self.algorithm);
+        let neighborhoods: Vec<Vec<usize>> = data
+            .iter_rows()
+            .map(|point| {
+                index
+                    .query_radius(point, self.eps)
+                    .iter()
+                    .map(|n| n.index)
+                    .collect()
+            })
+            .collect();
+        let is_core: Vec<bool> = neighborhoods
+            .iter()
+            .map(|n| n.len() >= self.min_samples)
+            .collect();
+
+        self.labels = vec![None; data.n_rows()];
+        self.core_samples = (0..data.n_rows()).filter(|&i| is_core[i]).collect();
+        self.n_clusters = 0;
+
+        let mut queue = VecDeque::new();
+        for start in 0..data.n_rows() {
+            if !is_core[start] || self.labels[start].is_some() {
+                continue;
+            }
+            let cluster = self.n_clusters;
+            self.n_clusters += 1;
+            self.labels[start] = Some(cluster);
+            queue.push_back(start);
+
+            while let Some(i) = queue.pop_front() {
+                for &j in &neighborhoods[i] {
+                    if self.labels[j].is_some() {
+                        continue;
+                    }
+                    self.labels[j] = Some(cluster);
+                    // Border points join the cluster but don't expand it
+                    if is_core[j] {
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn fit_predict(&mut self, data: &[Point]) -> Vec<Option<usize>> {
+        self.fit(data);
+        self.labels.clone()
+    }
+}
+
+impl<M: Metric> Configurable for Dbscan<M> {
+    fn param_specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("eps", EPS).with_min(0.0).with_step(0.1),
+            ParamSpec::int("min_samples", MIN_SAMPLES).with_min(1.0),
+        ]
+    }
+
+    fn params(&self) -> Params {
+        Params::default()
+            .with("eps", self.eps)
+            .with("min_samples", self.min_samples as f64)
+    }
+
+    fn set_params(&mut self, params: &Params) {
+        let specs = Self::param_specs();
+        if let Some(eps) = param_value(&specs, params, "eps") {
+            self.eps = eps;
+        }
+        if let Some(min_samples) = param_value(&specs, params, "min_samples") {
+            self.min_samples = min_samples as usize;
+        }
+    }
+}
+
+impl<M: Metric> Estimator for Dbscan<M> {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        Dbscan::fit_matrix(self, x);
+    }
+}
@@ -0,0 +1,366 @@
+use super::kmeans::KMeans;
+use crate::math::matrix::Matrix as DenseMatrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::params::{param_value, Configurable, ParamSpec, Params};
+use crate::ml::utils::{argmax, cholesky, forward_substitution, log_sum_exp, Matrix};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fmt;
+
+const EPSILON: f64 = 1e-3;
+const MAX_ITER: usize = 100;
+const REG_COVAR: f64 = 1e-6;
+// Most components a form accepts
+const MAX_COMPONENTS: usize = 50;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CovarianceType {
+    // Each component has its own general covariance matrix
+    Full,
+    // Each component has its own axis-aligned (diagonal) covariance
+    Diagonal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GaussianMixture {
+    pub n_components: usize,
+    pub covariance_type: CovarianceType,
+    pub max_iter: usize,
+    pub tolerance: f64,
+    // Added to the covariance diagonal to keep it positive definite
+    pub reg_covar: f64,
+    // Seed for the KMeans initialization, random when None
+    pub random_state: Option<u64>,
+
+    // Current state
+    weights: Vec<f64>,
+    means: Vec<Point>,
+    covariances: Vec<Matrix>,
+    #[serde(with = "crate::ml::persist::non_finite")]
+    lower_bound: f64,
+    n_iter: usize,
+    converged: bool,
+}
+
+impl fmt::Display for GaussianMixture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GaussianMixture{{ n_components: {}, covariance_type: {:?}, max_iter: {}, tolerance: {} }}",
+            self.n_components, self.covariance_type, self.max_iter, self.tolerance
+        )
+    }
+}
+
+builder!(GaussianMixture => GaussianMixtureBuilder, new(n_components: usize), {
+    covariance_type: CovarianceType,
+    max_iter: usize,
+    tolerance: f64,
+    reg_covar: f64,
+}, some {
+    random_state: u64,
+});
+
+impl GaussianMixture {
+    pub fn new(n_components: usize) -> Self {
+        GaussianMixture {
+            n_components,
+            covariance_type: CovarianceType::Full,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            reg_covar: REG_COVAR,
+            random_state: None,
+            weights: vec![],
+            means: vec![],
+            covariances: vec![],
+            lower_bound: f64::NEG_INFINITY,
+            n_iter: 0,
+            converged: false,
+        }
+    }
+
+    pub fn with_covariance_type(mut self, covariance_type: CovarianceType) -> Self {
+        self.covariance_type = covariance_type;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn means(&self) -> &[Point] {
+        &self.means
+    }
+
+    // Diagonal covariances are stored as full matrices with zero off-diagonals
+    pub fn covariances(&self) -> &[Matrix] {
+        &self.covariances
+    }
+
+    // Mean per-sample log-likelihood reached by the last fit
+    pub fn lower_bound(&self) -> f64 {
+        self.lower_bound
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &DenseMatrix) {
+        self.fit_notified(data, &mut Notifier::none());
+    }
+
+    fn fit_notified(&mut self, data: &DenseMatrix, notifier: &mut Notifier) {
+        self.weights.clear();
+        self.means.clear();
+        self.covariances.clear();
+        self.lower_bound = f64::NEG_INFINITY;
+        self.n_iter = 0;
+        self.converged = false;
+        if data.is_empty() || self.n_components == 0 {
+            return;
+        }
+
+        // Seed responsibilities from a hard KMeans partition
+        let mut kmeans = KMeans::new(self.n_components.min(data.n_rows()));
+        kmeans.random_state = self.random_state;
+        kmeans.fit_matrix(data);
+        let mut resp = vec![vec![0.0; self.n_components]; data.n_rows()];
+        for (row, &label) in resp.iter_mut().zip(kmeans.labels()) {
+            row[label] = 1.0;
+        }
+        self.m_step(data, &resp);
+
+        while self.n_iter < self.max_iter {
+            let (log_likelihood, log_resp) = self.e_step(data);
+            resp = log_resp
+                .iter()
+                .map(|row| row.iter().map(|v| v.exp()).collect())
+                .collect();
+            self.m_step(data, &resp);
+            self.n_iter += 1;
+
+            let change = log_likelihood - self.lower_bound;
+            self.lower_bound = log_likelihood;
+            notifier
+                .iteration(|| FitMetrics::new(self.n_iter).with("log_likelihood", log_likelihood));
+            if change.abs() < self.tolerance {
+                self.converged = true;
+                notifier.converged();
+                break;
+            }
+        }
+    }
+
+    pub fn predict(&self, data: &[Point]) -> Vec<usize> {
+        self.predict_matrix(&point_matrix(data))
+    }
+
+    pub fn predict_matrix(&self, data: &DenseMatrix) -> Vec<usize> {
+        self.weighted_log_prob(data)
+            .iter()
+            .map(|row| argmax(row))
+            .collect()
+    }
+
+    pub fn fit_predict(&mut self, data: &[Point]) -> Vec<usize> {
+        let data = point_matrix(data);
+        self.fit_matrix(&data);
+        self.predict_matrix(&data)
+    }
+
+    // Posterior probability of each component for every point
+    pub fn predict_proba(&self, data: &[Point]) -> Vec<Vec<f64>> {
+        self.weighted_log_prob(&point_matrix(data))
+            .iter()
+            .map(|row| {
+                let norm = log_sum_exp(row);
+                row.iter().map(|p| (p - norm).exp()).collect()
+            })
+            .collect()
+    }
+
+    // Mean per-sample log-likelihood of the data under the model
+    pub fn score(&self, data: &[Point]) -> f64 {
+        if data.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .weighted_log_prob(&point_matrix(data))
+            .iter()
+            .map(|row| log_sum_exp(row))
+            .sum();
+        total / data.len() as f64
+    }
+
+    // Returns the mean log-likelihood and the log responsibilities
+    fn e_step(&self, data: &DenseMatrix) -> (f64, Vec<Vec<f64>>) {
+        let weighted = self.weighted_log_prob(data);
+        let mut total = 0.0;
+        let log_resp = weighted
+            .into_iter()
+            .map(|row| {
+                let norm = log_sum_exp(&row);
+                total += norm;
+                row.into_iter().map(|p| p - norm).collect()
+            })
+            .collect();
+        (total / data.n_rows() as f64, log_resp)
+    }
+
+    fn m_step(&mut self, data: &DenseMatrix, resp: &[Vec<f64>]) {
+        let n = data.n_rows() as f64;
+        let dim = data.n_cols();
+        self.weights.clear();
+        self.means.clear();
+        self.covariances.clear();
+
+        for component in 0..self.n_components {
+            let nk: f64 = resp.iter().map(|r| r[component]).sum::<f64>() + 10.0 * f64::EPSILON;
+
+            let mut mean = vec![0.0; dim];
+            for (point, r) in data.iter_rows().zip(resp) {
+                for (m, x) in mean.iter_mut().zip(point) {
+                    *m += r[component] * x;
+                }
+            }
+            mean.iter_mut().for_each(|m| *m /= nk);
+
+            let mut cov = vec![vec![0.0; dim]; dim];
+            for (point, r) in data.iter_rows().zip(resp) {
+                let diff: Vec<f64> = point.iter().zip(&mean).map(|(x, m)| x - m).collect();
+                for i in 0..dim {
+                    match self.covariance_type {
+                        CovarianceType::Full => {
+                            for j in 0..dim {
+                                cov[i][j] += r[component] * diff[i] * diff[j];
+                            }
+                        }
+                        CovarianceType::Diagonal => {
+                            cov[i][i] += r[component] * diff[i] * diff[i];
+                        }
+                    }
+                }
+            }
+            for (i, row) in cov.iter_mut().enumerate() {
+                row.iter_mut().for_each(|c| *c /= nk);
+                row[i] += self.reg_covar;
+            }
+
+            self.weights.push(nk / n);
+            self.means.push(mean);
+            self.covariances.push(cov);
+        }
+    }
+
+    // log(weight_k) + log N(x | mean_k, cov_k) for every point and component
+    fn weighted_log_prob(&self, data: &DenseMatrix) -> Vec<Vec<f64>> {
+        let factors: Vec<Option<Matrix>> = self.covariances.iter().map(cholesky).collect();
+        data.iter_rows()
+            .map(|point| {
+                (0..self.means.len())
+                    .map(|k| {
+                        let Some(l) = &factors[k] else {
+                            return f64::NEG_INFINITY;
+                        };
+                        let diff: Vec<f64> = point
+                            .iter()
+                            .zip(&self.means[k])
+                            .map(|(x, m)| x - m)
+                            .collect();
+                        let z = forward_substitution(l, &diff);
+                        let mahalanobis: f64 = z.iter().map(|v| v * v).sum();
+                        let log_det: f64 = (0..l.len()).map(|i| l[i][i].ln()).sum::<f64>() * 2.0;
+                        let dim = diff.len() as f64;
+                        self.weights[k].ln() - 0.5 * (dim * (2.0 * PI).ln() + log_det + mahalanobis)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Configurable for GaussianMixture {
+    fn param_specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::int("n_components", 1).with_range(1.0, MAX_COMPONENTS as f64),
+            ParamSpec::int("max_iter", MAX_ITER).with_min(1.0),
+            ParamSpec::float("tolerance", EPSILON).with_min(0.0),
+            ParamSpec::float("reg_covar", REG_COVAR).with_min(0.0),
+        ]
+    }
+
+    fn params(&self) -> Params {
+        Params::default()
+            .with("n_components", self.n_components as f64)
+            .with("max_iter", self.max_iter as f64)
+            .with("tolerance", self.tolerance)
+            .with("reg_covar", self.reg_covar)
+    }
+
+    fn set_params(&mut self, params: &Params) {
+        let specs = Self::param_specs();
+        if let Some(n_components) = param_value(&specs, params, "n_components") {
+            self.n_components = n_components as usize;
+        }
+        if let Some(max_iter) = param_value(&specs, params, "max_iter") {
+            self.max_iter = max_iter as usize;
+        }
+        if let Some(tolerance) = param_value(&specs, params, "tolerance") {
+            self.tolerance = tolerance;
+        }
+        if let Some(reg_covar) = param_value(&specs, params, "reg_covar") {
+            self.reg_covar = reg_covar;
+        }
+    }
+}
+
+impl Estimator for GaussianMixture {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &DenseMatrix, _: &()) {
+        GaussianMixture::fit_matrix(self, x);
+    }
+
+    fn min_samples(&self) -> usize {
+        self.n_components
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.means.first().map(Vec::len)
+    }
+}
+
+// An iteration per EM step, reporting the mean log-likelihood the E step
+// found
+impl IterativeEstimator for GaussianMixture {
+    fn fit_observed_matrix(&mut self, x: &DenseMatrix, _: &(), observer: &mut dyn FitObserver) {
+        self.fit_notified(x, &mut Notifier::new(observer));
+    }
+}
+
+impl Predictor for GaussianMixture {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &DenseMatrix) -> Vec<usize> {
+        GaussianMixture::predict_matrix(self, x)
+    }
+}
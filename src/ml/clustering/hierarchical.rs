@@ -0,0 +1,282 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator};
+use crate::ml::builder::builder;
+use crate::ml::utils::squared_distance;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Linkage {
+    // Distance between the closest members
+    Single,
+    // Distance between the farthest members
+    Complete,
+    // Mean distance over all member pairs
+    Average,
+    // Increase in within-cluster variance caused by the merge
+    Ward,
+}
+
+// One row of a SciPy-style linkage matrix. Ids below n_samples are original
+// points; id n_samples + i refers to the cluster created by merge i.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Merge {
+    pub left: usize,
+    pub right: usize,
+    pub distance: f64,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgglomerativeClustering {
+    pub linkage: Linkage,
+
+    // Current state
+    merges: Vec<Merge>,
+    n_samples: usize,
+}
+
+impl fmt::Display for AgglomerativeClustering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AgglomerativeClustering{{ linkage: {:?} }}",
+            self.linkage
+        )
+    }
+}
+
+// Ward linkage, as scikit-learn defaults to
+impl Default for AgglomerativeClustering {
+    fn default() -> Self {
+        AgglomerativeClustering::new(Linkage::Ward)
+    }
+}
+
+builder!(AgglomerativeClustering => AgglomerativeClusteringBuilder, default, {
+    linkage: Linkage,
+});
+
+impl AgglomerativeClustering {
+    pub fn new(linkage: Linkage) -> Self {
+        AgglomerativeClustering {
+            linkage,
+            merges: vec![],
+            n_samples: 0,
+        }
+    }
+
+    // Merges ordered by increasing distance, n_samples - 1 rows
+    pub fn merges(&self) -> &[Merge] {
+        &self.merges
+    }
+
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    // The merge tree as [left, right, distance, size] rows, matching
+    // scipy.cluster.hierarchy.linkage
+    pub fn linkage_matrix(&self) -> Vec<[f64; 4]> {
+        self.merges
+            .iter()
+            .map(|m| [m.left as f64, m.right as f64, m.distance, m.size as f64])
+            .collect()
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        self.n_samples = data.n_rows();
+        self.merges = nn_chain(data, self.linkage);
+    }
+
+    // Flat labels obtained by stopping the merges once k clusters remain
+    pub fn cut(&self, k: usize) -> Vec<usize> {
+        if self.n_samples == 0 {
+            return vec![];
+        }
+        let n = self.n_samples;
+        let k = k.clamp(1, n.max(1));
+        let mut sets = DisjointSet::new(2 * n);
+        for (step, merge) in self.merges.iter().take(n - k).enumerate() {
+            sets.union(merge.left, n + step);
+            sets.union(merge.right, n + step);
+        }
+        relabel(&mut sets, n)
+    }
+
+    // Flat labels obtained by applying only the merges at or below height,
+    // e.g. a horizontal cut through the dendrogram
+    pub fn cut_at_height(&self, height: f64) -> Vec<usize> {
+        if self.n_samples == 0 {
+            return vec![];
+        }
+        let merged = self
+            .merges
+            .iter()
+            .take_while(|m| m.distance <= height)
+            .count();
+        self.cut(self.n_samples - merged)
+    }
+
+    pub fn fit_predict(&mut self, data: &[Point], k: usize) -> Vec<usize> {
+        self.fit(data);
+        self.cut(k)
+    }
+}
+
+impl Estimator for AgglomerativeClustering {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        AgglomerativeClustering::fit_matrix(self, x);
+    }
+}
+
+// Nearest-neighbor chain clustering with Lance-Williams distance updates.
+// Runs in O(n^2) time for all supported (reducible) linkages.
+fn nn_chain(data: &Matrix, linkage: Linkage) -> Vec<Merge> {
+    let n = data.n_rows();
+    if n < 2 {
+        return vec![];
+    }
+
+    let mut dist = vec![0.0; n * n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let d = squared_distance(data.row(i), data.row(j)).sqrt();
+            dist[i * n + j] = d;
+            dist[j * n + i] = d;
+        }
+    }
+
+    let mut size = vec![1usize; n];
+    let mut active = vec![true; n];
+    let mut chain: Vec<usize> = Vec::with_capacity(n);
+    // (slot a, slot b, distance); slot a holds the merged cluster afterwards
+    let mut raw: Vec<(usize, usize, f64)> = Vec::with_capacity(n - 1);
+
+    while raw.len() < n - 1 {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|&a| a).unwrap_or(0));
+        }
+
+        let (a, b, d) = loop {
+            let a = chain[chain.len() - 1];
+            let prev = chain.len().checked_sub(2).map(|i| chain[i]);
+            // Prefer the previous chain element on ties so the chain terminates
+            let mut best = prev.unwrap_or(usize::MAX);
+            let mut best_dist = prev.map(|p| dist[a * n + p]).unwrap_or(f64::INFINITY);
+            for c in 0..n {
+                if active[c] && c != a && dist[a * n + c] < best_dist {
+                    best = c;
+                    best_dist = dist[a * n + c];
+                }
+            }
+            if Some(best) == prev {
+                chain.pop();
+                chain.pop();
+                break (a, best, best_dist);
+            }
+            chain.push(best);
+        };
+
+        // Merge b into a's slot
+        let (na, nb) = (size[a] as f64, size[b] as f64);
+        for c in 0..n {
+            if !active[c] || c == a || c == b {
+                continue;
+            }
+            let (dac, dbc) = (dist[a * n + c], dist[b * n + c]);
+            let updated = match linkage {
+                Linkage::Single => dac.min(dbc),
+                Linkage::Complete => dac.max(dbc),
+                Linkage::Average => (na * dac + nb * dbc) / (na + nb),
+                Linkage::Ward => {
+                    let nc = size[c] as f64;
+                    (((na + nc) * dac * dac + (nb + nc) * dbc * dbc - nc * d * d) / (na + nb + nc))
+                        .max(0.0)
+                        .sqrt()
+                }
+            };
+            dist[a * n + c] = updated;
+            dist[c * n + a] = updated;
+        }
+        active[b] = false;
+        size[a] += size[b];
+        raw.push((a, b, d));
+    }
+
+    // The chain finds merges out of order; sort them and translate slots
+    // into SciPy cluster ids
+    raw.sort_by(|x, y| x.2.total_cmp(&y.2));
+    let mut sets = DisjointSet::new(2 * n - 1);
+    let mut cluster_id: Vec<usize> = (0..2 * n - 1).collect();
+    let mut cluster_size = vec![1usize; 2 * n - 1];
+    raw.iter()
+        .enumerate()
+        .map(|(step, &(a, b, distance))| {
+            let (ra, rb) = (sets.find(a), sets.find(b));
+            let (left, right) = (cluster_id[ra], cluster_id[rb]);
+            let merged = sets.union(ra, rb);
+            let new_id = n + step;
+            cluster_id[merged] = new_id;
+            cluster_size[new_id] = cluster_size[left] + cluster_size[right];
+            Merge {
+                left: left.min(right),
+                right: left.max(right),
+                distance,
+                size: cluster_size[new_id],
+            }
+        })
+        .collect()
+}
+
+// Map the roots of the first n elements to consecutive labels
+fn relabel(sets: &mut DisjointSet, n: usize) -> Vec<usize> {
+    let mut labels = vec![usize::MAX; 2 * n];
+    let mut next = 0;
+    (0..n)
+        .map(|i| {
+            let root = sets.find(i);
+            if labels[root] == usize::MAX {
+                labels[root] = next;
+                next += 1;
+            }
+            labels[root]
+        })
+        .collect()
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // Returns the new root
+    fn union(&mut self, a: usize, b: usize) -> usize {
+        let (ra, rb) = (self.find(a), self.find(b));
+        self.parent[rb] = ra;
+        ra
+    }
+}
@@ -1,44 +1,864 @@
+use crate::compute::parallel::par_map;
+use crate::data::sparse::{CsrMatrix, Rows};
+use crate::math::float::{cast_points, Float};
+use crate::math::matrix::Matrix;
+use crate::math::simd;
+use crate::ml::base::{
+    point_matrix, Estimator, IncrementalEstimator, IterativeEstimator, Predictor,
+};
+use crate::ml::builder::builder;
+use crate::ml::early_stopping::{EarlyStopping, Tracker};
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::params::{param_value, Configurable, ParamSpec, Params};
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::seeded_rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::index::sample;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 const EPSILON: f64 = 1e-6;
 const MAX_ITER: usize = 300;
+const N_INIT: usize = 1;
+// Upper bounds of the parameters a form accepts
+const MAX_K: usize = 50;
+const MAX_MAX_ITER: usize = 10_000;
+const MAX_N_INIT: usize = 50;
 
 // Point in n-dimensional space
 type Point = Vec<f64>;
 
-#[derive(Debug, Clone)]
-pub struct KMeans {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    // Classic assign/update iteration, O(n * k) distances per iteration
+    Lloyd,
+    // Lloyd with triangle-inequality bounds that skip most distance
+    // computations once centroids settle. Falls back to Lloyd for distances
+    // that aren't true metrics.
+    Elkan,
+}
+
+// How fit picks the starting centroids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Init {
+    // k distinct points drawn uniformly
+    #[default]
+    Random,
+    // k-means++ (Arthur & Vassilvitskii, 2007): the first centroid drawn
+    // uniformly and every next one with probability proportional to its
+    // squared distance to the nearest so far, which spreads them out and
+    // takes fewer iterations to converge
+    KMeansPlusPlus,
+}
+
+// State after one assign/update iteration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IterationSnapshot<T = f64> {
+    pub iteration: usize,
+    // Centroids after the update step
+    pub centroids: Vec<Vec<T>>,
+    // Assignments the update step was computed from
+    pub assignments: Vec<usize>,
+    pub inertia: f64,
+}
+
+// Centroids are always updated to the mean of their members, so metrics
+// other than Euclidean change the assignment step only. Points and
+// centroids are f64 unless T says otherwise, see cast; inertia is f64
+// either way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KMeans<M: Metric = Euclidean, T = f64> {
     pub k: usize,
     pub max_iter: usize,
     pub tolerance: f64,
+    pub algorithm: Algorithm,
+    pub metric: M,
+    // fit_sparse and partial_fit always pick at random
+    #[serde(default)]
+    pub init: Init,
+    // Runs of fit from different starting centroids, keeping the one with
+    // the lowest inertia. Lloyd and Elkan converge to a local minimum that
+    // depends on the start, so more runs give steadier results.
+    #[serde(default = "default_n_init")]
+    pub n_init: usize,
+    // Stop fit once the inertia stops improving, before the centroids
+    // settle. Only fit and fit_matrix, which then compute the inertia of
+    // every iteration.
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    // Keep a snapshot of every iteration, see `history`
+    pub record_history: bool,
+    // Seed for centroid initialization, random when None
+    pub random_state: Option<u64>,
 
     // Current state
-    centroids: Vec<Point>,
+    centroids: Vec<Vec<T>>,
     assignments: Vec<usize>,
     inertia: f64,
     n_iter: usize,
     converged: bool,
+    #[serde(default)]
+    best_iteration: Option<usize>,
+    history: Vec<IterationSnapshot<T>>,
+    // Points each centroid has absorbed, the weight of partial_fit updates
+    counts: Vec<usize>,
 }
 
-impl KMeans {
-    pub fn to_string(&self) -> String {
-        format!(
-            "KMeans{{ k: {}, max_iter: {}, tolerance: {} }}",
-            self.k, self.max_iter, self.tolerance
+impl<M: Metric, T> fmt::Display for KMeans<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KMeans{{ k: {}, max_iter: {}, tolerance: {}, algorithm: {:?}, metric: {:?} }}",
+            self.k, self.max_iter, self.tolerance, self.algorithm, self.metric
         )
     }
+}
 
+impl KMeans {
     pub fn new(k: usize) -> Self {
+        KMeans::with_metric(k, Euclidean)
+    }
+
+    // Lloyd iterations on the rows of a sparse matrix, e.g. TF-IDF features.
+    // Centroids are dense, but each distance costs only the nonzeros of the
+    // row. Only Euclidean, since the expansion relies on it.
+    pub fn fit_sparse(&mut self, data: &CsrMatrix) {
+        self.reset();
+        if data.n_rows() == 0 || self.k == 0 {
+            return;
+        }
+
+        let mut rng = seeded_rng(self.random_state);
+        let k = self.k.min(data.n_rows());
+        self.centroids = sample(&mut rng, data.n_rows(), k)
+            .iter()
+            .map(|i| data.row_to_dense(i))
+            .collect();
+        while self.n_iter < self.max_iter {
+            self.assignments = self.predict_sparse(data);
+            let mut sums = vec![vec![0.0; data.n_cols()]; self.centroids.len()];
+            let mut counts = vec![0usize; self.centroids.len()];
+            for (i, &cluster) in self.assignments.iter().enumerate() {
+                counts[cluster] += 1;
+                data.row_add_to(i, 1.0, &mut sums[cluster]);
+            }
+            let shifts = self.move_centroids(sums, &counts);
+            self.n_iter += 1;
+            self.record_snapshot(|model| model.sparse_inertia(data));
+            if self.settled(&shifts) {
+                self.converged = true;
+                break;
+            }
+        }
+
+        self.assignments = self.predict_sparse(data);
+        self.inertia = self.sparse_inertia(data);
+        self.counts = self.cluster_sizes();
+    }
+
+    pub fn predict_sparse(&self, data: &CsrMatrix) -> Vec<usize> {
+        (0..data.n_rows())
+            .map(|i| {
+                self.centroids
+                    .iter()
+                    .map(|centroid| data.row_squared_distance(i, centroid))
+                    .enumerate()
+                    .fold((0, f64::INFINITY), |best, (c, dist)| {
+                        if dist < best.1 {
+                            (c, dist)
+                        } else {
+                            best
+                        }
+                    })
+                    .0
+            })
+            .collect()
+    }
+
+    fn sparse_inertia(&self, data: &CsrMatrix) -> f64 {
+        self.assignments
+            .iter()
+            .enumerate()
+            .map(|(i, &cluster)| data.row_squared_distance(i, &self.centroids[cluster]))
+            .sum()
+    }
+}
+
+impl<M: Metric> KMeans<M> {
+    pub fn with_metric(k: usize, metric: M) -> Self {
         KMeans {
             k,
             max_iter: MAX_ITER,
             tolerance: EPSILON,
+            algorithm: Algorithm::Lloyd,
+            metric,
+            init: Init::Random,
+            n_init: N_INIT,
+            early_stopping: None,
+            record_history: false,
+            random_state: None,
             centroids: vec![],
             assignments: vec![],
             inertia: 0.0,
             n_iter: 0,
             converged: false,
+            best_iteration: None,
+            history: vec![],
+            counts: vec![],
+        }
+    }
+}
+
+impl<M: Metric, T: Float> KMeans<M, T> {
+    pub fn with_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    pub fn with_n_init(mut self, n_init: usize) -> Self {
+        self.n_init = n_init;
+        self
+    }
+
+    // The model computing in another float type, fitted state included,
+    // e.g. KMeans::new(k).cast::<f32>() to fit a Matrix<f32>
+    pub fn cast<U: Float>(self) -> KMeans<M, U> {
+        KMeans {
+            k: self.k,
+            max_iter: self.max_iter,
+            tolerance: self.tolerance,
+            algorithm: self.algorithm,
+            metric: self.metric,
+            init: self.init,
+            n_init: self.n_init,
+            early_stopping: self.early_stopping,
+            record_history: self.record_history,
+            random_state: self.random_state,
+            centroids: cast_points(&self.centroids),
+            assignments: self.assignments,
+            inertia: self.inertia,
+            n_iter: self.n_iter,
+            converged: self.converged,
+            best_iteration: self.best_iteration,
+            history: self
+                .history
+                .into_iter()
+                .map(|snapshot| IterationSnapshot {
+                    iteration: snapshot.iteration,
+                    centroids: cast_points(&snapshot.centroids),
+                    assignments: snapshot.assignments,
+                    inertia: snapshot.inertia,
+                })
+                .collect(),
+            counts: self.counts,
+        }
+    }
+
+    pub fn centroids(&self) -> &[Vec<T>] {
+        &self.centroids
+    }
+
+    // Cluster index of every point in the last fitted dataset
+    pub fn labels(&self) -> &[usize] {
+        &self.assignments
+    }
+
+    // Sum of squared distances from each point to its centroid
+    pub fn inertia(&self) -> f64 {
+        self.inertia
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    // Iteration with the lowest inertia, counted from 1. None unless early
+    // stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.best_iteration
+    }
+
+    // Per-iteration snapshots from the last fit, empty unless record_history
+    // was set
+    pub fn history(&self) -> &[IterationSnapshot<T>] {
+        &self.history
+    }
+
+    pub fn fit(&mut self, data: &[Vec<T>]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    // fit on the rows of a dense matrix, which the iterations sweep
+    // without chasing a pointer per point
+    pub fn fit_matrix(&mut self, data: &Matrix<T>) {
+        self.fit_notified(data, &mut Notifier::none());
+    }
+
+    // Runs the restarts on several threads when threads_available, unless
+    // observed: an observer hears every restart's iterations in turn.
+    // Restart i starts from random_state + i, so that the first run is the
+    // same whatever n_init.
+    fn fit_notified(&mut self, data: &Matrix<T>, notifier: &mut Notifier) {
+        self.reset();
+        if data.is_empty() || self.k == 0 {
+            return;
+        }
+
+        let seeds: Vec<Option<u64>> = (0..self.n_init.max(1) as u64)
+            .map(|restart| self.random_state.map(|seed| seed.wrapping_add(restart)))
+            .collect();
+        if seeds.len() == 1 {
+            self.fit_once(data, seeds[0], notifier);
+            return;
+        }
+        let restart = |seed: Option<u64>, notifier: &mut Notifier| {
+            let mut run = self.clone();
+            run.fit_once(data, seed, notifier);
+            run
+        };
+        let runs: Vec<Self> = if notifier.is_observed() {
+            seeds.iter().map(|&seed| restart(seed, notifier)).collect()
+        } else {
+            par_map(&seeds, |&seed| restart(seed, &mut Notifier::none()))
+        };
+        if let Some(best) = runs
+            .into_iter()
+            .min_by(|a, b| a.inertia.total_cmp(&b.inertia))
+        {
+            *self = best;
+        }
+    }
+
+    fn fit_once(&mut self, data: &Matrix<T>, seed: Option<u64>, notifier: &mut Notifier) {
+        self.init_centroids(data, seed);
+        match self.algorithm {
+            Algorithm::Elkan if self.metric.satisfies_triangle_inequality() => {
+                self.fit_elkan(data, notifier)
+            }
+            _ => self.fit_lloyd(data, notifier),
+        }
+
+        self.finish_fit(data);
+    }
+
+    // Starts a fit that step advances one iteration at a time, for callers
+    // that do other work in between, e.g. keeping a page responsive. Steps
+    // are Lloyd iterations whatever the algorithm, from a single start
+    // whatever n_init.
+    pub fn begin_fit(&mut self, data: &Matrix<T>) {
+        self.reset();
+        if !data.is_empty() && self.k > 0 {
+            self.init_centroids(data, self.random_state);
+        }
+    }
+
+    // One iteration of a fit begun with begin_fit, true once it has
+    // converged or run max_iter iterations. Meanwhile labels and inertia
+    // describe the iteration's assignments, and once done the settled
+    // centroids, as after fit.
+    pub fn step(&mut self, data: &Matrix<T>) -> bool {
+        if self.centroids.is_empty() {
+            return true;
+        }
+        if !self.converged && self.n_iter < self.max_iter {
+            self.lloyd_iteration(data, &mut Notifier::none());
+            self.inertia = self.compute_inertia(data);
+        }
+        let done = self.converged || self.n_iter >= self.max_iter;
+        if done {
+            self.finish_fit(data);
+        }
+        done
+    }
+
+    // Final assignments and inertia against the settled centroids
+    fn finish_fit(&mut self, data: &Matrix<T>) {
+        self.assignments = self.assign(data);
+        self.inertia = self.compute_inertia(data);
+        self.counts = self.cluster_sizes();
+    }
+
+    // Mini-batch k-means (Sculley, 2010): every point of the batch pulls its
+    // nearest centroid towards it by 1 / (points the centroid has absorbed),
+    // keeping each centroid the mean of everything assigned to it so far.
+    // Points of the first batches seed the centroids until there are k.
+    // labels, inertia and converged describe the last batch; n_iter and
+    // history grow by one per batch.
+    pub fn partial_fit(&mut self, data: &[Vec<T>]) {
+        self.partial_fit_matrix(&point_matrix(data));
+    }
+
+    pub fn partial_fit_matrix(&mut self, data: &Matrix<T>) {
+        if data.is_empty() || self.k == 0 {
+            return;
+        }
+
+        let missing = self.k.saturating_sub(self.centroids.len());
+        if missing > 0 {
+            let mut rng = seeded_rng(self.random_state);
+            for i in sample(&mut rng, data.n_rows(), missing.min(data.n_rows())) {
+                self.centroids.push(data.row(i).to_vec());
+                self.counts.push(0);
+            }
+        }
+
+        self.assignments = self.assign(data);
+        let previous = self.centroids.clone();
+        for (point, &cluster) in data.iter_rows().zip(&self.assignments) {
+            self.counts[cluster] += 1;
+            let rate = T::from_usize(self.counts[cluster]).recip();
+            for (c, &v) in self.centroids[cluster].iter_mut().zip(point) {
+                *c += rate * (v - *c);
+            }
         }
+        self.n_iter += 1;
+        self.record_snapshot(|model| model.compute_inertia(data));
+        self.converged = previous
+            .iter()
+            .zip(&self.centroids)
+            .all(|(old, new)| self.metric.distance(old, new).as_f64() <= self.tolerance);
+
+        self.assignments = self.assign(data);
+        self.inertia = self.compute_inertia(data);
+    }
+
+    pub fn predict(&self, data: &[Vec<T>]) -> Vec<usize> {
+        par_map(data, |point| {
+            nearest_centroid(&self.metric, point, &self.centroids).0
+        })
+    }
+
+    pub fn predict_matrix(&self, data: &Matrix<T>) -> Vec<usize> {
+        self.assign(data)
     }
 
-    // Core functionality
-    fn step(&mut self, data: &Vec<Point>) {}
+    // Nearest centroid of every row
+    fn assign(&self, data: &Matrix<T>) -> Vec<usize> {
+        let rows: Vec<&[T]> = data.iter_rows().collect();
+        par_map(&rows, |row| {
+            nearest_centroid(&self.metric, row, &self.centroids).0
+        })
+    }
+
+    pub fn fit_predict(&mut self, data: &[Vec<T>]) -> Vec<usize> {
+        self.fit(data);
+        self.assignments.clone()
+    }
+
+    fn reset(&mut self) {
+        self.centroids.clear();
+        self.assignments.clear();
+        self.inertia = 0.0;
+        self.n_iter = 0;
+        self.converged = false;
+        self.best_iteration = None;
+        self.history.clear();
+        self.counts.clear();
+    }
+
+    // Pick k distinct data points as the starting centroids
+    fn init_centroids(&mut self, data: &Matrix<T>, seed: Option<u64>) {
+        let mut rng = seeded_rng(seed);
+        let k = self.k.min(data.n_rows());
+        self.centroids = match self.init {
+            Init::Random => sample(&mut rng, data.n_rows(), k)
+                .iter()
+                .map(|i| data.row(i).to_vec())
+                .collect(),
+            Init::KMeansPlusPlus => plus_plus_centroids(&self.metric, data, k, &mut rng),
+        };
+    }
+
+    fn fit_lloyd(&mut self, data: &Matrix<T>, notifier: &mut Notifier) {
+        let mut tracker = self
+            .early_stopping
+            .map(|early_stopping| early_stopping.tracker());
+        while !self.converged && self.n_iter < self.max_iter {
+            self.lloyd_iteration(data, notifier);
+            if !self.converged && self.stops_early(&mut tracker, data) {
+                notifier.converged();
+                break;
+            }
+        }
+    }
+
+    // Whether early stopping ends the fit after this iteration. Records the
+    // best iteration so far.
+    fn stops_early(&mut self, tracker: &mut Option<Tracker>, data: &Matrix<T>) -> bool {
+        let Some(tracker) = tracker else {
+            return false;
+        };
+        let stop = tracker.update(self.compute_inertia(data));
+        self.best_iteration = Some(tracker.best_iteration());
+        stop
+    }
+
+    // Assigns every point to its nearest centroid and moves the centroids
+    // to the means of their points
+    fn lloyd_iteration(&mut self, data: &Matrix<T>, notifier: &mut Notifier) {
+        self.assignments = self.assign(data);
+        let shifts = self.update_centroids(data);
+        self.n_iter += 1;
+        self.record_snapshot(|model| model.compute_inertia(data));
+        notifier.iteration(|| self.iteration_metrics(data));
+        if self.settled(&shifts) {
+            self.converged = true;
+            notifier.converged();
+        }
+    }
+
+    // Elkan (2003): keep an upper bound on each point's distance to its own
+    // centroid and a lower bound to every other centroid, and only compute a
+    // distance when the bounds can't rule the centroid out
+    fn fit_elkan(&mut self, data: &Matrix<T>, notifier: &mut Notifier) {
+        let k = self.centroids.len();
+        let mut upper = vec![T::zero(); data.n_rows()];
+        let mut lower = vec![T::zero(); data.n_rows() * k];
+
+        self.assignments = data
+            .iter_rows()
+            .enumerate()
+            .map(|(i, point)| {
+                let mut best = (0, T::infinity());
+                for (c, centroid) in self.centroids.iter().enumerate() {
+                    let dist = self.metric.distance(point, centroid);
+                    lower[i * k + c] = dist;
+                    if dist < best.1 {
+                        best = (c, dist);
+                    }
+                }
+                upper[i] = best.1;
+                best.0
+            })
+            .collect();
+
+        let mut tracker = self
+            .early_stopping
+            .map(|early_stopping| early_stopping.tracker());
+        while self.n_iter < self.max_iter {
+            let shifts = self.update_centroids(data);
+            self.n_iter += 1;
+            self.record_snapshot(|model| model.compute_inertia(data));
+            notifier.iteration(|| self.iteration_metrics(data));
+
+            // Moving centroids loosens the bounds by at most their shift
+            for (i, &assigned) in self.assignments.iter().enumerate() {
+                upper[i] += shifts[assigned];
+                for (c, shift) in shifts.iter().enumerate() {
+                    lower[i * k + c] = (lower[i * k + c] - *shift).max(T::zero());
+                }
+            }
+            if self.settled(&shifts) {
+                self.converged = true;
+                notifier.converged();
+                break;
+            }
+            if self.stops_early(&mut tracker, data) {
+                notifier.converged();
+                break;
+            }
+
+            // Half the distance between centroids, and to each centroid's
+            // nearest other centroid
+            let mut half_dist = vec![T::zero(); k * k];
+            let mut nearest = vec![T::infinity(); k];
+            let half = T::from_f64(0.5);
+            for a in 0..k {
+                for b in (a + 1)..k {
+                    let d = half * self.metric.distance(&self.centroids[a], &self.centroids[b]);
+                    half_dist[a * k + b] = d;
+                    half_dist[b * k + a] = d;
+                    nearest[a] = nearest[a].min(d);
+                    nearest[b] = nearest[b].min(d);
+                }
+            }
+
+            for (i, point) in data.iter_rows().enumerate() {
+                let mut assigned = self.assignments[i];
+                if upper[i] <= nearest[assigned] {
+                    continue;
+                }
+                let mut tight = false;
+                for c in 0..k {
+                    if c == assigned
+                        || upper[i] <= lower[i * k + c]
+                        || upper[i] <= half_dist[assigned * k + c]
+                    {
+                        continue;
+                    }
+                    if !tight {
+                        upper[i] = self.metric.distance(point, &self.centroids[assigned]);
+                        lower[i * k + assigned] = upper[i];
+                        tight = true;
+                        if upper[i] <= lower[i * k + c] || upper[i] <= half_dist[assigned * k + c] {
+                            continue;
+                        }
+                    }
+                    let dist = self.metric.distance(point, &self.centroids[c]);
+                    lower[i * k + c] = dist;
+                    if dist < upper[i] {
+                        assigned = c;
+                        upper[i] = dist;
+                    }
+                }
+                self.assignments[i] = assigned;
+            }
+        }
+    }
+
+    // Move each centroid to the mean of its assigned points. Returns the
+    // distance every centroid moved.
+    fn update_centroids(&mut self, data: &Matrix<T>) -> Vec<T> {
+        let mut sums = vec![vec![T::zero(); data.n_cols()]; self.centroids.len()];
+        let mut counts = vec![0usize; self.centroids.len()];
+        for (point, &cluster) in data.iter_rows().zip(&self.assignments) {
+            counts[cluster] += 1;
+            simd::axpy(T::one(), point, &mut sums[cluster]);
+        }
+        self.move_centroids(sums, &counts)
+    }
+
+    // Centroids from the sums and counts of their members
+    fn move_centroids(&mut self, sums: Vec<Vec<T>>, counts: &[usize]) -> Vec<T> {
+        let mut shifts = vec![T::zero(); self.centroids.len()];
+        for (cluster, centroid) in self.centroids.iter_mut().enumerate() {
+            // Empty clusters keep their previous position
+            if counts[cluster] == 0 {
+                continue;
+            }
+            let count = T::from_usize(counts[cluster]);
+            let updated: Vec<T> = sums[cluster].iter().map(|&sum| sum / count).collect();
+            shifts[cluster] = self.metric.distance(centroid, &updated);
+            *centroid = updated;
+        }
+
+        shifts
+    }
+
+    // Whether no centroid moved further than the tolerance
+    fn settled(&self, shifts: &[T]) -> bool {
+        shifts.iter().cloned().fold(T::zero(), T::max).as_f64() <= self.tolerance
+    }
+
+    fn record_snapshot(&mut self, inertia: impl FnOnce(&Self) -> f64) {
+        if !self.record_history {
+            return;
+        }
+        let inertia = inertia(self);
+        self.history.push(IterationSnapshot {
+            iteration: self.n_iter,
+            centroids: self.centroids.clone(),
+            assignments: self.assignments.clone(),
+            inertia,
+        });
+    }
+
+    fn iteration_metrics(&self, data: &Matrix<T>) -> FitMetrics {
+        FitMetrics::new(self.n_iter).with("inertia", self.compute_inertia(data))
+    }
+
+    fn cluster_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.centroids.len()];
+        for &cluster in &self.assignments {
+            sizes[cluster] += 1;
+        }
+        sizes
+    }
+
+    fn compute_inertia(&self, data: &Matrix<T>) -> f64 {
+        data.iter_rows()
+            .zip(&self.assignments)
+            .map(|(point, &cluster)| {
+                self.metric
+                    .distance(point, &self.centroids[cluster])
+                    .as_f64()
+                    .powi(2)
+            })
+            .sum()
+    }
+}
+
+// Centroids fitted by sklearn.cluster.KMeans, with the labels, inertia and
+// iteration count of the fit when they are given
+impl FromSklearn for KMeans {
+    const CLASS: &'static str = "KMeans";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let centroids = attributes.matrix("cluster_centers_")?;
+        let n_features = centroids.first().map_or(0, Vec::len);
+        sklearn::check_features(attributes, n_features, "cluster_centers_")?;
+        let assignments: Vec<usize> = attributes.optional("labels_")?.unwrap_or_default();
+        if assignments.iter().any(|&label| label >= centroids.len()) {
+            return Err(sklearn::invalid("labels_", "a label without a centroid"));
+        }
+        let mut model = KMeans::new(centroids.len());
+        model.max_iter = attributes.optional("max_iter")?.unwrap_or(MAX_ITER);
+        model.random_state = attributes.optional("random_state")?;
+        model.n_iter = attributes.optional("n_iter_")?.unwrap_or(0);
+        model.converged = model.n_iter < model.max_iter;
+        model.inertia = attributes.optional("inertia_")?.unwrap_or(0.0);
+        model.centroids = centroids;
+        model.assignments = assignments;
+        model.counts = model.cluster_sizes();
+        Ok(model)
+    }
+}
+
+builder!(KMeans => KMeansBuilder, new(k: usize), {
+    max_iter: usize,
+    tolerance: f64,
+    algorithm: Algorithm,
+    init: Init,
+    n_init: usize,
+    record_history: bool,
+}, some {
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl<M: Metric> Configurable for KMeans<M> {
+    fn param_specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::int("k", 8).with_range(1.0, MAX_K as f64),
+            ParamSpec::int("max_iter", MAX_ITER).with_range(1.0, MAX_MAX_ITER as f64),
+            ParamSpec::float("tolerance", EPSILON).with_min(0.0),
+            ParamSpec::int("n_init", N_INIT).with_range(1.0, MAX_N_INIT as f64),
+        ]
+    }
+
+    fn params(&self) -> Params {
+        Params::default()
+            .with("k", self.k as f64)
+            .with("max_iter", self.max_iter as f64)
+            .with("tolerance", self.tolerance)
+            .with("n_init", self.n_init as f64)
+    }
+
+    fn set_params(&mut self, params: &Params) {
+        let specs = Self::param_specs();
+        if let Some(k) = param_value(&specs, params, "k") {
+            self.k = k as usize;
+        }
+        if let Some(max_iter) = param_value(&specs, params, "max_iter") {
+            self.max_iter = max_iter as usize;
+        }
+        if let Some(tolerance) = param_value(&specs, params, "tolerance") {
+            self.tolerance = tolerance;
+        }
+        if let Some(n_init) = param_value(&specs, params, "n_init") {
+            self.n_init = n_init as usize;
+        }
+    }
+}
+
+impl<M: Metric> Estimator for KMeans<M> {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        KMeans::fit_matrix(self, x);
+    }
+
+    fn min_samples(&self) -> usize {
+        self.k
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.centroids.first().map(Vec::len)
+    }
+}
+
+// An iteration per assign/update step, reporting the inertia
+impl<M: Metric> IterativeEstimator for KMeans<M> {
+    fn fit_observed_matrix(&mut self, x: &Matrix, _: &(), observer: &mut dyn FitObserver) {
+        self.fit_notified(x, &mut Notifier::new(observer));
+    }
+}
+
+impl<M: Metric> IncrementalEstimator for KMeans<M> {
+    fn partial_fit_matrix(&mut self, x: &Matrix, _: &()) {
+        KMeans::partial_fit_matrix(self, x);
+    }
+}
+
+impl<M: Metric> Predictor for KMeans<M> {
+    type Output = usize;
+
+    fn predict(&self, x: &[Point]) -> Vec<usize> {
+        KMeans::predict(self, x)
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        KMeans::predict_matrix(self, x)
+    }
+}
+
+// k centroids seeded as Init::KMeansPlusPlus describes. Once every point
+// coincides with a centroid, the rest are drawn uniformly.
+fn plus_plus_centroids<M: Metric, T: Float>(
+    metric: &M,
+    data: &Matrix<T>,
+    k: usize,
+    rng: &mut impl Rng,
+) -> Vec<Vec<T>> {
+    let squared_distance =
+        |point: &[T], centroid: &[T]| metric.distance(point, centroid).as_f64().powi(2);
+    let first = data.row(rng.gen_range(0..data.n_rows())).to_vec();
+    // Squared distance of every point to its nearest centroid so far
+    let mut weights: Vec<f64> = data
+        .iter_rows()
+        .map(|point| squared_distance(point, &first))
+        .collect();
+    let mut centroids = vec![first];
+    while centroids.len() < k {
+        let i = match WeightedIndex::new(&weights) {
+            Ok(distribution) => distribution.sample(rng),
+            Err(_) => rng.gen_range(0..data.n_rows()),
+        };
+        let centroid = data.row(i).to_vec();
+        for (weight, point) in weights.iter_mut().zip(data.iter_rows()) {
+            *weight = weight.min(squared_distance(point, &centroid));
+        }
+        centroids.push(centroid);
+    }
+    centroids
+}
+
+// Index of and distance to the closest centroid
+fn nearest_centroid<M: Metric, T: Float>(
+    metric: &M,
+    point: &[T],
+    centroids: &[Vec<T>],
+) -> (usize, T) {
+    centroids
+        .iter()
+        .map(|centroid| metric.distance(point, centroid))
+        .enumerate()
+        .fold((0, T::infinity()), |best, (i, dist)| {
+            if dist < best.1 {
+                (i, dist)
+            } else {
+                best
+            }
+        })
+}
+
+// For models saved before n_init existed
+fn default_n_init() -> usize {
+    N_INIT
 }
@@ -1 +1,5 @@
+pub mod dbscan;
+pub mod gmm;
+pub mod hierarchical;
 pub mod kmeans;
+pub mod select_k;
@@ -0,0 +1,91 @@
+use super::kmeans::KMeans;
+use crate::ml::metrics::clustering::silhouette_score;
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KScore {
+    pub k: usize,
+    pub inertia: f64,
+    // Undefined for a single cluster
+    pub silhouette: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KSelection {
+    pub scores: Vec<KScore>,
+    // k at the knee of the inertia curve
+    pub elbow: Option<usize>,
+    // k with the highest mean silhouette
+    pub best_silhouette: Option<usize>,
+}
+
+impl KSelection {
+    // Elbow when one was found, otherwise the best silhouette
+    pub fn suggested_k(&self) -> Option<usize> {
+        self.elbow.or(self.best_silhouette)
+    }
+}
+
+// Fit KMeans for every k in the range and score each clustering
+pub fn select_k(
+    data: &[Point],
+    k_range: RangeInclusive<usize>,
+    random_state: Option<u64>,
+) -> KSelection {
+    let max_k = data.len();
+    let scores: Vec<KScore> = k_range
+        .filter(|&k| k >= 1 && k <= max_k)
+        .map(|k| {
+            let mut model = KMeans::new(k);
+            model.random_state = random_state;
+            model.fit(data);
+            KScore {
+                k,
+                inertia: model.inertia(),
+                silhouette: silhouette_score(data, model.labels()),
+            }
+        })
+        .collect();
+
+    let best_silhouette = scores
+        .iter()
+        .filter_map(|s| s.silhouette.map(|v| (s.k, v)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k);
+
+    KSelection {
+        elbow: find_elbow(&scores),
+        best_silhouette,
+        scores,
+    }
+}
+
+// Kneedle-style elbow: after scaling both axes to [0, 1], the point farthest
+// below the straight line joining the first and last scores
+fn find_elbow(scores: &[KScore]) -> Option<usize> {
+    if scores.len() < 3 {
+        return None;
+    }
+    let (first, last) = (&scores[0], &scores[scores.len() - 1]);
+    let k_span = (last.k - first.k) as f64;
+    let inertia_span = first.inertia - last.inertia;
+    if inertia_span <= 0.0 {
+        return None;
+    }
+
+    scores
+        .iter()
+        .map(|s| {
+            let x = (s.k - first.k) as f64 / k_span;
+            let y = (s.inertia - last.inertia) / inertia_span;
+            // The chord runs from (0, 1) to (1, 0)
+            (s.k, 1.0 - x - y)
+        })
+        .filter(|&(_, gap)| gap > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k)
+}
@@ -0,0 +1,411 @@
+use super::pca::Pca;
+use crate::math::float::{cast_points, cast_slice, Float};
+use crate::math::matrix::Matrix as DenseMatrix;
+use crate::math::simd;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator, Transformer};
+use crate::ml::builder::builder;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::utils::{dot, seeded_rng, symmetric_eigen, Matrix};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const MAX_ITER: usize = 200;
+const EPSILON: f64 = 1e-4;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    // Update every unmixing vector at once, then decorrelate them
+    // symmetrically. No component is favored.
+    Parallel,
+    // Extract components one at a time, keeping each orthogonal to the ones
+    // already found. Errors in early components carry over to later ones.
+    Deflation,
+}
+
+// Contrast function G approximating negentropy, used through its first
+// and second derivatives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Nonlinearity {
+    // log(cosh(u)), a good general-purpose choice
+    LogCosh,
+    // -exp(-u²/2), robust for super-Gaussian sources
+    Exp,
+    // u⁴/4, fast but sensitive to outliers
+    Cube,
+}
+
+// Blind source separation: finds the unmixing that makes the recovered
+// sources as non-Gaussian, and so as independent, as possible (Hyvärinen
+// 1999). Data is centered and whitened with PCA first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastIca {
+    pub n_components: usize,
+    pub algorithm: Algorithm,
+    pub nonlinearity: Nonlinearity,
+    pub max_iter: usize,
+    pub tolerance: f64,
+    // Seed for the initial unmixing matrix, random when None
+    pub random_state: Option<u64>,
+
+    // Current state
+    mean: Vec<f64>,
+    // Maps centered data to sources, one row per source
+    components: Matrix,
+    // Maps sources back to centered data, one row per feature
+    mixing: Matrix,
+    n_iter: usize,
+    converged: bool,
+}
+
+impl fmt::Display for FastIca {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FastIca{{ n_components: {}, algorithm: {:?}, nonlinearity: {:?}, max_iter: {}, tolerance: {} }}",
+            self.n_components, self.algorithm, self.nonlinearity, self.max_iter, self.tolerance
+        )
+    }
+}
+
+builder!(FastIca => FastIcaBuilder, new(n_components: usize), {
+    algorithm: Algorithm,
+    nonlinearity: Nonlinearity,
+    max_iter: usize,
+    tolerance: f64,
+}, some {
+    random_state: u64,
+});
+
+impl FastIca {
+    pub fn new(n_components: usize) -> Self {
+        FastIca {
+            n_components,
+            algorithm: Algorithm::Parallel,
+            nonlinearity: Nonlinearity::LogCosh,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            random_state: None,
+            mean: vec![],
+            components: vec![],
+            mixing: vec![],
+            n_iter: 0,
+            converged: false,
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    // Unmixing matrix, one row per source
+    pub fn components(&self) -> &[Point] {
+        &self.components
+    }
+
+    // Mixing matrix, one row per feature and a column per source
+    pub fn mixing(&self) -> &[Point] {
+        &self.mixing
+    }
+
+    // Iterations of the last fit, summed over components for Deflation
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    // FastIca::fit on the rows of a dense matrix of any float type. The
+    // whitening, see Pca::fit_matrix, and the fixed-point iterations over the
+    // whitened rows run in T; the fitted unmixing is f64 whatever T.
+    pub fn fit_matrix<T: Float>(&mut self, data: &DenseMatrix<T>) {
+        self.fit_notified(data, &mut Notifier::none());
+    }
+
+    fn fit_notified<T: Float>(&mut self, data: &DenseMatrix<T>, notifier: &mut Notifier) {
+        self.components.clear();
+        self.mixing.clear();
+        self.n_iter = 0;
+        self.converged = false;
+
+        let mut pca = Pca::new(self.n_components).with_whiten(true);
+        pca.fit_matrix(data);
+        let whitened = pca.transform_matrix(data);
+        self.mean = pca.mean().to_vec();
+        let k = pca.components().len();
+        if k == 0 {
+            return;
+        }
+
+        let mut rng = seeded_rng(self.random_state);
+        let initial: Matrix = (0..k)
+            .map(|_| (0..k).map(|_| rng.sample(StandardNormal)).collect())
+            .collect();
+        let unmixing = match self.algorithm {
+            Algorithm::Parallel => self.fit_parallel(&whitened, initial, notifier),
+            Algorithm::Deflation => self.fit_deflation(&whitened, initial, notifier),
+        };
+        if self.converged {
+            notifier.converged();
+        }
+
+        // Compose with the whitening: a source is w · (x - mean) / sqrt(var)
+        // projected on the principal axes
+        let scaled_axes: Matrix = pca
+            .components()
+            .iter()
+            .zip(pca.explained_variance())
+            .map(|(axis, var)| {
+                let scale = var.sqrt().max(f64::EPSILON);
+                axis.iter().map(|a| a / scale).collect()
+            })
+            .collect();
+        self.components = unmixing.iter().map(|w| combine(w, &scaled_axes)).collect();
+
+        // Whitening is orthogonal up to scale, so its pseudo-inverse is the
+        // axes scaled the other way
+        let dim = self.mean.len();
+        self.mixing = (0..dim)
+            .map(|feature| {
+                unmixing
+                    .iter()
+                    .map(|w| {
+                        w.iter()
+                            .zip(pca.components())
+                            .zip(pca.explained_variance())
+                            .map(|((wc, axis), var)| wc * axis[feature] * var.sqrt())
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    // Recovered sources for each point
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    // FastIca::transform on the rows of a dense matrix, in T
+    pub fn transform_matrix<T: Float>(&self, data: &DenseMatrix<T>) -> DenseMatrix {
+        let mean: Vec<T> = cast_slice(&self.mean);
+        let components: Vec<Vec<T>> = cast_points(&self.components);
+        let mut sources = DenseMatrix::zeros(data.n_rows(), components.len());
+        for (point, source) in data.iter_rows().zip(sources.iter_rows_mut()) {
+            let centered: Vec<T> = point.iter().zip(&mean).map(|(&x, &m)| x - m).collect();
+            for (s, component) in source.iter_mut().zip(&components) {
+                *s = simd::dot(&centered, component).as_f64();
+            }
+        }
+        sources
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    // Mix sources back into the original feature space
+    pub fn inverse_transform(&self, sources: &[Point]) -> Vec<Point> {
+        sources
+            .iter()
+            .map(|source| {
+                self.mixing
+                    .iter()
+                    .zip(&self.mean)
+                    .map(|(row, mean)| dot(row, source) + mean)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // W+ = E[x g(Wx)] - diag(E[g'(Wx)]) W for all rows, then
+    // W = (W W^T)^(-1/2) W
+    fn fit_parallel<T: Float>(
+        &mut self,
+        x: &DenseMatrix<T>,
+        initial: Matrix,
+        notifier: &mut Notifier,
+    ) -> Matrix {
+        let mut w = symmetric_decorrelation(&initial);
+        while self.n_iter < self.max_iter {
+            let updated: Matrix = w.iter().map(|row| self.fixed_point(x, row)).collect();
+            let updated = symmetric_decorrelation(&updated);
+            self.n_iter += 1;
+
+            // Converged once every vector stops turning: |w+ · w| = 1
+            let change = updated
+                .iter()
+                .zip(&w)
+                .map(|(new, old)| (dot(new, old).abs() - 1.0).abs())
+                .fold(0.0, f64::max);
+            w = updated;
+            notifier.iteration(|| FitMetrics::new(self.n_iter).with("change", change));
+            if change < self.tolerance {
+                self.converged = true;
+                break;
+            }
+        }
+        w
+    }
+
+    // One vector at a time, with Gram-Schmidt against the earlier ones
+    // after every update
+    fn fit_deflation<T: Float>(
+        &mut self,
+        x: &DenseMatrix<T>,
+        initial: Matrix,
+        notifier: &mut Notifier,
+    ) -> Matrix {
+        let mut w: Matrix = Vec::with_capacity(initial.len());
+        self.converged = true;
+        for start in initial {
+            let mut current = normalize(orthogonalize(start, &w));
+            let mut converged = false;
+            for _ in 0..self.max_iter {
+                let updated = self.fixed_point(x, &current);
+                let updated = normalize(orthogonalize(updated, &w));
+                self.n_iter += 1;
+                let change = (dot(&updated, &current).abs() - 1.0).abs();
+                current = updated;
+                notifier.iteration(|| FitMetrics::new(self.n_iter).with("change", change));
+                if change < self.tolerance {
+                    converged = true;
+                    break;
+                }
+            }
+            self.converged &= converged;
+            w.push(current);
+        }
+        w
+    }
+
+    // E[x g(w · x)] - E[g'(w · x)] w, with the sums over the rows in T
+    fn fixed_point<T: Float>(&self, x: &DenseMatrix<T>, w: &[f64]) -> Vec<f64> {
+        let row_w: Vec<T> = cast_slice(w);
+        let mut expectation = vec![T::zero(); w.len()];
+        let mut derivative_mean = 0.0;
+        for point in x.iter_rows() {
+            let (g, g_prime) = self
+                .nonlinearity
+                .derivatives(simd::dot(&row_w, point).as_f64());
+            simd::axpy(T::from_f64(g), point, &mut expectation);
+            derivative_mean += g_prime;
+        }
+        let n = x.n_rows().max(1) as f64;
+        expectation
+            .iter()
+            .zip(w)
+            .map(|(e, wi)| e.as_f64() / n - derivative_mean / n * wi)
+            .collect()
+    }
+}
+
+impl Estimator for FastIca {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &DenseMatrix, _: &()) {
+        FastIca::fit_matrix(self, x);
+    }
+}
+
+// An iteration per fixed-point update, of all vectors or, deflating, of
+// one, reporting how far from 1 |w+ · w| still is
+impl IterativeEstimator for FastIca {
+    fn fit_observed_matrix(&mut self, x: &DenseMatrix, _: &(), observer: &mut dyn FitObserver) {
+        self.fit_notified(x, &mut Notifier::new(observer));
+    }
+}
+
+impl Transformer for FastIca {
+    fn transform_matrix(&self, x: &DenseMatrix) -> DenseMatrix {
+        FastIca::transform_matrix(self, x)
+    }
+}
+
+impl Nonlinearity {
+    // g = G' and g' = G'' at u
+    fn derivatives(&self, u: f64) -> (f64, f64) {
+        match self {
+            Nonlinearity::LogCosh => {
+                let t = u.tanh();
+                (t, 1.0 - t * t)
+            }
+            Nonlinearity::Exp => {
+                let e = (-u * u / 2.0).exp();
+                (u * e, (1.0 - u * u) * e)
+            }
+            Nonlinearity::Cube => (u.powi(3), 3.0 * u * u),
+        }
+    }
+}
+
+// (W W^T)^(-1/2) W, the closest matrix with orthonormal rows
+fn symmetric_decorrelation(w: &Matrix) -> Matrix {
+    let gram: Matrix = w
+        .iter()
+        .map(|a| w.iter().map(|b| dot(a, b)).collect())
+        .collect();
+    let (values, vectors) = symmetric_eigen(&gram);
+    // U diag(1 / sqrt(s)) U^T, with eigenvectors as the rows of vectors
+    let k = w.len();
+    let inverse_sqrt: Matrix = (0..k)
+        .map(|i| {
+            (0..k)
+                .map(|j| {
+                    vectors
+                        .iter()
+                        .zip(&values)
+                        .map(|(v, s)| v[i] * v[j] / s.max(f64::EPSILON).sqrt())
+                        .sum()
+                })
+                .collect()
+        })
+        .collect();
+    inverse_sqrt.iter().map(|row| combine(row, w)).collect()
+}
+
+// Linear combination of rows with the given weights
+fn combine(weights: &[f64], rows: &[Point]) -> Vec<f64> {
+    let mut result = vec![0.0; rows.first().map_or(0, |r| r.len())];
+    for (weight, row) in weights.iter().zip(rows) {
+        for (r, v) in result.iter_mut().zip(row) {
+            *r += weight * v;
+        }
+    }
+    result
+}
+
+// Remove the projections on the given orthonormal vectors
+fn orthogonalize(mut w: Vec<f64>, basis: &[Point]) -> Vec<f64> {
+    for b in basis {
+        let projection = dot(&w, b);
+        w.iter_mut().zip(b).for_each(|(x, y)| *x -= projection * y);
+    }
+    w
+}
+
+fn normalize(w: Vec<f64>) -> Vec<f64> {
+    let norm = dot(&w, &w).sqrt().max(f64::EPSILON);
+    w.iter().map(|x| x / norm).collect()
+}
@@ -0,0 +1,3 @@
+pub mod ica;
+pub mod nmf;
+pub mod pca;
@@ -0,0 +1,345 @@
+use crate::data::sparse::{CsrMatrix, Rows};
+use crate::math::float::{cast_points, Float};
+use crate::math::matrix::Matrix as DenseMatrix;
+use crate::math::simd;
+use crate::ml::base::{Estimator, IterativeEstimator, Transformer};
+use crate::ml::builder::builder;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::utils::{seeded_rng, Matrix};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const MAX_ITER: usize = 200;
+const EPSILON: f64 = 1e-4;
+// Keeps the multiplicative updates from dividing by zero
+const DIVISION_GUARD: f64 = 1e-10;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Non-negative matrix factorization X ≈ W H with Lee and Seung's (2001)
+// multiplicative updates on the Frobenius norm. Data must be non-negative.
+// Each row of X becomes a non-negative mix (a row of W) of n_components
+// parts (the rows of H).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nmf {
+    pub n_components: usize,
+    pub max_iter: usize,
+    // Stop once an iteration lowers the error by less than this fraction of
+    // the initial error
+    pub tolerance: f64,
+    // Seed for the initial factors, random when None
+    pub random_state: Option<u64>,
+
+    // Current state
+    // H, one row per component
+    components: Matrix,
+    // ||X - W H|| after each iteration of the last fit
+    reconstruction_errors: Vec<f64>,
+    converged: bool,
+}
+
+impl fmt::Display for Nmf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Nmf{{ n_components: {}, max_iter: {}, tolerance: {} }}",
+            self.n_components, self.max_iter, self.tolerance
+        )
+    }
+}
+
+builder!(Nmf => NmfBuilder, new(n_components: usize), {
+    max_iter: usize,
+    tolerance: f64,
+}, some {
+    random_state: u64,
+});
+
+impl Nmf {
+    pub fn new(n_components: usize) -> Self {
+        Nmf {
+            n_components,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            random_state: None,
+            components: vec![],
+            reconstruction_errors: vec![],
+            converged: false,
+        }
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn components(&self) -> &[Point] {
+        &self.components
+    }
+
+    // Frobenius norm of the residual after every iteration, for plotting
+    // convergence
+    pub fn reconstruction_errors(&self) -> &[f64] {
+        &self.reconstruction_errors
+    }
+
+    // Frobenius norm of the residual of the last fit
+    pub fn reconstruction_err(&self) -> f64 {
+        self.reconstruction_errors.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.reconstruction_errors.len()
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_transform(data);
+    }
+
+    // Fit and return W, the weights of every row on the components
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        cast_points(&self.fit_transform_rows(data))
+    }
+
+    // Weights of each row on the fitted components, found with the
+    // components held fixed
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        cast_points(&self.transform_rows(data))
+    }
+
+    // Nmf::fit on the rows of a sparse matrix, e.g. word counts. Each update
+    // touches only the nonzeros of X.
+    pub fn fit_sparse(&mut self, data: &CsrMatrix) {
+        self.fit_transform_rows(data);
+    }
+
+    pub fn fit_transform_sparse(&mut self, data: &CsrMatrix) -> Vec<Point> {
+        cast_points(&self.fit_transform_rows(data))
+    }
+
+    pub fn transform_sparse(&self, data: &CsrMatrix) -> Vec<Point> {
+        cast_points(&self.transform_rows(data))
+    }
+
+    // Nmf::fit_transform on the rows of a dense matrix of any float type,
+    // e.g. Matrix<f32> for half the memory. The updates run in T; the
+    // components and weights come out f64 whatever T.
+    pub fn fit_transform_matrix<T: Float>(&mut self, data: &DenseMatrix<T>) -> DenseMatrix {
+        weights_matrix(&self.fit_transform_rows(data))
+    }
+
+    pub fn transform_matrix<T: Float>(&self, data: &DenseMatrix<T>) -> DenseMatrix {
+        weights_matrix(&self.transform_rows(data))
+    }
+
+    // W H, the data as reconstructed from its weights
+    pub fn inverse_transform(&self, weights: &[Point]) -> Vec<Point> {
+        weights
+            .iter()
+            .map(|row| product_row(row, &self.components))
+            .collect()
+    }
+
+    fn fit_transform_rows<T: Float, R: Rows<T> + ?Sized>(&mut self, data: &R) -> Vec<Vec<T>> {
+        self.fit_transform_notified(data, &mut Notifier::none())
+    }
+
+    fn fit_transform_notified<T: Float, R: Rows<T> + ?Sized>(
+        &mut self,
+        data: &R,
+        notifier: &mut Notifier,
+    ) -> Vec<Vec<T>> {
+        self.components.clear();
+        self.reconstruction_errors.clear();
+        self.converged = false;
+        let dim = data.n_cols();
+        if data.n_rows() == 0 || dim == 0 || self.n_components == 0 {
+            return vec![vec![]; data.n_rows()];
+        }
+
+        // Uniform in [0, 2 * scale) so W H starts at the scale of X
+        let mut rng = seeded_rng(self.random_state);
+        let mean = data.sum().as_f64() / (data.n_rows() * dim) as f64;
+        let scale = (mean.max(0.0) / self.n_components as f64).sqrt();
+        let mut random_factor = |rows: usize, cols: usize| -> Vec<Vec<T>> {
+            (0..rows)
+                .map(|_| {
+                    (0..cols)
+                        .map(|_| T::from_f64(rng.gen::<f64>() * 2.0 * scale))
+                        .collect()
+                })
+                .collect()
+        };
+        let mut w = random_factor(data.n_rows(), self.n_components);
+        let mut h = random_factor(self.n_components, dim);
+
+        let initial_error = reconstruction_error(data, &w, &h);
+        let mut previous_error = initial_error;
+        for _ in 0..self.max_iter {
+            h = update_components(data, &w, &h);
+            w = update_weights(data, &w, &h);
+
+            let error = reconstruction_error(data, &w, &h);
+            self.reconstruction_errors.push(error);
+            notifier.iteration(|| {
+                FitMetrics::new(self.reconstruction_errors.len())
+                    .with("reconstruction_error", error)
+            });
+            if (previous_error - error) < self.tolerance * initial_error {
+                self.converged = true;
+                notifier.converged();
+                break;
+            }
+            previous_error = error;
+        }
+        self.components = cast_points(&h);
+        w
+    }
+
+    fn transform_rows<T: Float, R: Rows<T> + ?Sized>(&self, data: &R) -> Vec<Vec<T>> {
+        if self.components.is_empty() {
+            return vec![vec![]; data.n_rows()];
+        }
+        let h: Vec<Vec<T>> = cast_points(&self.components);
+        let mut w = vec![vec![T::one(); h.len()]; data.n_rows()];
+        let initial_error = reconstruction_error(data, &w, &h);
+        let mut previous_error = initial_error;
+        for _ in 0..self.max_iter {
+            w = update_weights(data, &w, &h);
+            let error = reconstruction_error(data, &w, &h);
+            if (previous_error - error) < self.tolerance * initial_error {
+                break;
+            }
+            previous_error = error;
+        }
+        w
+    }
+}
+
+impl Estimator for Nmf {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &DenseMatrix, _: &()) {
+        Nmf::fit_transform_matrix(self, x);
+    }
+}
+
+// An iteration per pair of multiplicative updates, reporting the
+// reconstruction error
+impl IterativeEstimator for Nmf {
+    fn fit_observed_matrix(&mut self, x: &DenseMatrix, _: &(), observer: &mut dyn FitObserver) {
+        self.fit_transform_notified(x, &mut Notifier::new(observer));
+    }
+}
+
+impl Transformer for Nmf {
+    fn transform_matrix(&self, x: &DenseMatrix) -> DenseMatrix {
+        Nmf::transform_matrix(self, x)
+    }
+
+    // The weights found while fitting, rather than solved again
+    fn fit_transform_matrix(&mut self, x: &DenseMatrix, _: &()) -> DenseMatrix {
+        Nmf::fit_transform_matrix(self, x)
+    }
+}
+
+// W as the f64 rows of a matrix
+fn weights_matrix<T: Float>(w: &[Vec<T>]) -> DenseMatrix {
+    let cols = w.first().map_or(0, Vec::len);
+    DenseMatrix::from_fn(w.len(), cols, |i, j| w[i][j].as_f64())
+}
+
+// H ← H ⊙ (Wᵀ X) / (Wᵀ W H)
+fn update_components<T: Float, R: Rows<T> + ?Sized>(
+    x: &R,
+    w: &[Vec<T>],
+    h: &[Vec<T>],
+) -> Vec<Vec<T>> {
+    let k = h.len();
+    let dim = h[0].len();
+    let guard = T::from_f64(DIVISION_GUARD);
+    let mut numerator = vec![vec![T::zero(); dim]; k];
+    for (i, weights) in w.iter().enumerate() {
+        for (num, &weight) in numerator.iter_mut().zip(weights) {
+            x.row_add_to(i, weight, num);
+        }
+    }
+    let gram = column_gram(w);
+    h.iter()
+        .zip(&numerator)
+        .zip(&gram)
+        .map(|((h_row, num), gram_row)| {
+            let denominator = product_row(gram_row, h);
+            h_row
+                .iter()
+                .zip(num)
+                .zip(denominator)
+                .map(|((&h, &n), d)| h * n / (d + guard))
+                .collect()
+        })
+        .collect()
+}
+
+// W ← W ⊙ (X Hᵀ) / (W H Hᵀ)
+fn update_weights<T: Float, R: Rows<T> + ?Sized>(x: &R, w: &[Vec<T>], h: &[Vec<T>]) -> Vec<Vec<T>> {
+    let guard = T::from_f64(DIVISION_GUARD);
+    let gram = row_gram(h);
+    w.iter()
+        .enumerate()
+        .map(|(i, w_row)| {
+            let denominator = product_row(w_row, &gram);
+            w_row
+                .iter()
+                .zip(h)
+                .zip(denominator)
+                .map(|((&w, h_row), d)| w * x.row_dot(i, h_row) / (d + guard))
+                .collect()
+        })
+        .collect()
+}
+
+// M Mᵀ, dot products between rows
+fn row_gram<T: Float>(m: &[Vec<T>]) -> Vec<Vec<T>> {
+    m.iter()
+        .map(|a| m.iter().map(|b| simd::dot(a, b)).collect())
+        .collect()
+}
+
+// Mᵀ M, dot products between columns
+fn column_gram<T: Float>(m: &[Vec<T>]) -> Vec<Vec<T>> {
+    let cols = m.first().map_or(0, |r| r.len());
+    (0..cols)
+        .map(|i| {
+            (0..cols)
+                .map(|j| m.iter().map(|r| r[i] * r[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+// row · M
+fn product_row<T: Float>(row: &[T], m: &[Vec<T>]) -> Vec<T> {
+    let mut result = vec![T::zero(); m.first().map_or(0, |r| r.len())];
+    for (&weight, m_row) in row.iter().zip(m) {
+        result
+            .iter_mut()
+            .zip(m_row)
+            .for_each(|(r, &v)| *r += weight * v);
+    }
+    result
+}
+
+// ||X - W H||_F
+fn reconstruction_error<T: Float, R: Rows<T> + ?Sized>(x: &R, w: &[Vec<T>], h: &[Vec<T>]) -> f64 {
+    w.iter()
+        .enumerate()
+        .map(|(i, weights)| x.row_squared_distance(i, &product_row(weights, h)).as_f64())
+        .sum::<f64>()
+        .sqrt()
+}
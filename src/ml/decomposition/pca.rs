@@ -0,0 +1,258 @@
+use crate::math::float::{cast_points, cast_slice, Float};
+use crate::math::matrix::Matrix as DenseMatrix;
+use crate::math::simd;
+use crate::ml::base::{point_matrix, Estimator, Transformer};
+use crate::ml::builder::builder;
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::{symmetric_eigen, Matrix};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Projects data onto the directions of largest variance, found from the
+// eigenvectors of the covariance matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pca {
+    pub n_components: usize,
+    // Scale every component to unit variance after projecting
+    pub whiten: bool,
+
+    // Current state
+    mean: Vec<f64>,
+    // Unit vectors as rows, by decreasing variance
+    components: Matrix,
+    explained_variance: Vec<f64>,
+    explained_variance_ratio: Vec<f64>,
+}
+
+impl fmt::Display for Pca {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Pca{{ n_components: {}, whiten: {} }}",
+            self.n_components, self.whiten
+        )
+    }
+}
+
+builder!(Pca => PcaBuilder, new(n_components: usize), {
+    whiten: bool,
+});
+
+impl Pca {
+    pub fn new(n_components: usize) -> Self {
+        Pca {
+            n_components,
+            whiten: false,
+            mean: vec![],
+            components: vec![],
+            explained_variance: vec![],
+            explained_variance_ratio: vec![],
+        }
+    }
+
+    pub fn with_whiten(mut self, whiten: bool) -> Self {
+        self.whiten = whiten;
+        self
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    pub fn components(&self) -> &[Point] {
+        &self.components
+    }
+
+    // Variance along each component
+    pub fn explained_variance(&self) -> &[f64] {
+        &self.explained_variance
+    }
+
+    // Share of the total variance along each component
+    pub fn explained_variance_ratio(&self) -> &[f64] {
+        &self.explained_variance_ratio
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    // Pca::fit on the rows of a dense matrix of any float type, e.g.
+    // Matrix<f32> for half the memory. The products of the centered rows are
+    // in T and summed in f64; the components are f64 whatever T.
+    pub fn fit_matrix<T: Float>(&mut self, data: &DenseMatrix<T>) {
+        let (n, dim) = data.shape();
+        if n == 0 || dim == 0 {
+            self.mean.clear();
+            self.components.clear();
+            self.explained_variance.clear();
+            self.explained_variance_ratio.clear();
+            return;
+        }
+
+        self.mean = (0..dim)
+            .map(|j| data.column(j).map(T::as_f64).sum::<f64>() / n as f64)
+            .collect();
+
+        // Sample covariance, unbiased like numpy and sklearn
+        let mean: Vec<T> = cast_slice(&self.mean);
+        let mut covariance = vec![vec![0.0; dim]; dim];
+        for point in data.iter_rows() {
+            let centered: Vec<T> = point.iter().zip(&mean).map(|(&x, &m)| x - m).collect();
+            for (row, &a) in covariance.iter_mut().zip(&centered) {
+                for (cell, &b) in row.iter_mut().zip(&centered) {
+                    *cell += (a * b).as_f64();
+                }
+            }
+        }
+        let denominator = (n.max(2) - 1) as f64;
+        covariance
+            .iter_mut()
+            .flatten()
+            .for_each(|cell| *cell /= denominator);
+
+        let (values, vectors) = symmetric_eigen(&covariance);
+        let total: f64 = values.iter().map(|v| v.max(0.0)).sum();
+        let k = self.n_components.min(dim);
+
+        // Flip signs so the largest coordinate of every component is positive
+        // and results don't depend on the solver's arbitrary choice
+        self.components = vectors
+            .into_iter()
+            .take(k)
+            .map(|vector| {
+                let largest = vector
+                    .iter()
+                    .cloned()
+                    .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+                    .unwrap_or(0.0);
+                if largest < 0.0 {
+                    vector.iter().map(|v| -v).collect()
+                } else {
+                    vector
+                }
+            })
+            .collect();
+        self.explained_variance = values.iter().take(k).map(|v| v.max(0.0)).collect();
+        self.explained_variance_ratio = self
+            .explained_variance
+            .iter()
+            .map(|v| if total > 0.0 { v / total } else { 0.0 })
+            .collect();
+    }
+
+    // Coordinates of each point along the fitted components
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    // Pca::transform on the rows of a dense matrix, in T, the coordinates
+    // too
+    pub fn transform_matrix<T: Float>(&self, data: &DenseMatrix<T>) -> DenseMatrix<T> {
+        let mean: Vec<T> = cast_slice(&self.mean);
+        let components: Vec<Vec<T>> = cast_points(&self.components);
+        let scales: Vec<T> = self
+            .explained_variance
+            .iter()
+            .map(|variance| {
+                T::from_f64(if self.whiten {
+                    variance.sqrt().max(f64::EPSILON)
+                } else {
+                    1.0
+                })
+            })
+            .collect();
+        let mut projected = DenseMatrix::zeros(data.n_rows(), components.len());
+        for (point, coords) in data.iter_rows().zip(projected.iter_rows_mut()) {
+            let centered: Vec<T> = point.iter().zip(&mean).map(|(&x, &m)| x - m).collect();
+            for ((coord, component), &scale) in coords.iter_mut().zip(&components).zip(&scales) {
+                *coord = simd::dot(&centered, component) / scale;
+            }
+        }
+        projected
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    // Map projected points back to the original space. Exact only when every
+    // component was kept.
+    pub fn inverse_transform(&self, projected: &[Point]) -> Vec<Point> {
+        projected
+            .iter()
+            .map(|coords| {
+                let mut point = self.mean.clone();
+                for ((coord, component), variance) in coords
+                    .iter()
+                    .zip(&self.components)
+                    .zip(&self.explained_variance)
+                {
+                    let scale = if self.whiten {
+                        coord * variance.sqrt()
+                    } else {
+                        *coord
+                    };
+                    for (value, c) in point.iter_mut().zip(component) {
+                        *value += scale * c;
+                    }
+                }
+                point
+            })
+            .collect()
+    }
+}
+
+impl FromSklearn for Pca {
+    const CLASS: &'static str = "PCA";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let components = attributes.matrix("components_")?;
+        let mean = attributes.vector("mean_")?;
+        if components
+            .first()
+            .is_some_and(|row| row.len() != mean.len())
+        {
+            return Err(sklearn::invalid("components_", "rows not as long as mean_"));
+        }
+        let explained_variance = attributes.vector("explained_variance_")?;
+        if explained_variance.len() != components.len() {
+            return Err(sklearn::invalid(
+                "explained_variance_",
+                "not one value per component",
+            ));
+        }
+        sklearn::check_features(attributes, mean.len(), "mean_")?;
+        let mut model = Pca::new(components.len());
+        model.whiten = attributes.optional("whiten")?.unwrap_or(false);
+        model.explained_variance_ratio = attributes
+            .optional("explained_variance_ratio_")?
+            .unwrap_or_default();
+        model.mean = mean;
+        model.components = components;
+        model.explained_variance = explained_variance;
+        Ok(model)
+    }
+}
+
+impl Estimator for Pca {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &DenseMatrix, _: &()) {
+        Pca::fit_matrix(self, x);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.mean.is_empty()).then_some(self.mean.len())
+    }
+}
+
+impl Transformer for Pca {
+    fn transform_matrix(&self, x: &DenseMatrix) -> DenseMatrix {
+        Pca::transform_matrix(self, x)
+    }
+}
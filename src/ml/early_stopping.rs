@@ -0,0 +1,114 @@
+// Stopping a fit once the metric it monitors stops improving, shared by the
+// estimators that fit in epochs, stages or iterations, e.g. the MLPs,
+// gradient boosting, SGD and KMeans. Every monitored metric is a loss, so
+// lower is better.
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+const PATIENCE: usize = 10;
+const MIN_DELTA: f64 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Monitor {
+    // The loss on the samples being fitted. KMeans always monitors its
+    // inertia.
+    #[default]
+    TrainingLoss,
+    // The loss on this fraction of the samples, held out of the fit, e.g.
+    // 0.1. Catches overfitting the training loss can't show.
+    ValidationLoss(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EarlyStopping {
+    // Iterations in a row without improvement before the fit stops
+    pub patience: usize,
+    // Smallest decrease of the metric that counts as an improvement
+    pub min_delta: f64,
+    pub monitor: Monitor,
+}
+
+impl Default for EarlyStopping {
+    fn default() -> Self {
+        EarlyStopping::new(PATIENCE)
+    }
+}
+
+impl EarlyStopping {
+    pub fn new(patience: usize) -> Self {
+        EarlyStopping {
+            patience,
+            min_delta: MIN_DELTA,
+            monitor: Monitor::TrainingLoss,
+        }
+    }
+
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+
+    pub fn with_monitor(mut self, monitor: Monitor) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    // The held-out and the training indices of n samples. Nothing is held
+    // out when monitoring the training loss; otherwise the samples are
+    // shuffled and at least one lands on each side.
+    pub(crate) fn split(&self, n: usize, rng: &mut StdRng) -> (Vec<usize>, Vec<usize>) {
+        let mut order: Vec<usize> = (0..n).collect();
+        let n_validation = match self.monitor {
+            Monitor::ValidationLoss(fraction) if n > 1 => {
+                order.shuffle(rng);
+                ((n as f64 * fraction).round() as usize).clamp(1, n - 1)
+            }
+            _ => 0,
+        };
+        let train = order.split_off(n_validation);
+        (order, train)
+    }
+
+    pub(crate) fn tracker(&self) -> Tracker {
+        Tracker {
+            patience: self.patience,
+            min_delta: self.min_delta,
+            best: f64::INFINITY,
+            best_iteration: 0,
+            iteration: 0,
+            without_improvement: 0,
+        }
+    }
+}
+
+// The best value of the monitored metric so far and how long ago it was
+pub(crate) struct Tracker {
+    patience: usize,
+    min_delta: f64,
+    best: f64,
+    best_iteration: usize,
+    iteration: usize,
+    without_improvement: usize,
+}
+
+impl Tracker {
+    // Records the metric after the next iteration and returns whether the
+    // fit should stop
+    pub(crate) fn update(&mut self, value: f64) -> bool {
+        self.iteration += 1;
+        if value < self.best - self.min_delta {
+            self.best = value;
+            self.best_iteration = self.iteration;
+            self.without_improvement = 0;
+        } else {
+            self.without_improvement += 1;
+        }
+        self.without_improvement >= self.patience
+    }
+
+    // Counted from 1, 0 before any update
+    pub(crate) fn best_iteration(&self) -> usize {
+        self.best_iteration
+    }
+}
@@ -0,0 +1,672 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::classification::Classifier;
+use crate::ml::early_stopping::EarlyStopping;
+use crate::ml::metrics::classification::accuracy_score;
+use crate::ml::metrics::regression::r2_score;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::onnx::{OnnxError, ToOnnx, TreeEnsemble};
+use crate::ml::tree::{build_tree, Criterion, MaxFeatures, Target, TreeNode, TreeParams};
+use crate::ml::utils::seeded_rng;
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const N_ESTIMATORS: usize = 100;
+const LEARNING_RATE: f64 = 0.1;
+const MAX_DEPTH: usize = 3;
+// Keeps the log-odds finite when every label is the same
+const PROBA_CLIP: f64 = 1e-15;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Additive model of shallow regression trees, each fitted to the residuals
+// of the ones before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientBoostingRegressor {
+    pub n_estimators: usize,
+    // Shrinks every tree's contribution
+    pub learning_rate: f64,
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    // Fraction of the training samples each tree sees, drawn without
+    // replacement. Below 1.0 this is stochastic gradient boosting.
+    pub subsample: f64,
+    // Stop adding trees once the monitored loss stops improving, after
+    // every tree when None
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    pub random_state: Option<u64>,
+
+    // Current state
+    model: Boosted,
+}
+
+// Binary classifier boosting trees on the log-loss. Labels are 0 and 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradientBoostingClassifier {
+    pub n_estimators: usize,
+    // Shrinks every tree's contribution
+    pub learning_rate: f64,
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    // Fraction of the training samples each tree sees, drawn without
+    // replacement. Below 1.0 this is stochastic gradient boosting.
+    pub subsample: f64,
+    // Stop adding trees once the monitored loss stops improving, after
+    // every tree when None
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    pub random_state: Option<u64>,
+
+    // Current state
+    model: Boosted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Loss {
+    // Half the squared error, so the negative gradient is the residual
+    Squared,
+    // Binary cross-entropy on log-odds
+    LogLoss,
+}
+
+// Everything boost needs besides the data
+struct Boosting {
+    loss: Loss,
+    n_estimators: usize,
+    learning_rate: f64,
+    tree: TreeParams,
+    subsample: f64,
+    early_stopping: Option<EarlyStopping>,
+    random_state: Option<u64>,
+}
+
+// A fitted additive model: init + learning_rate * sum of tree outputs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Boosted {
+    init: f64,
+    learning_rate: f64,
+    trees: Vec<TreeNode>,
+    train_loss: Vec<f64>,
+    validation_loss: Vec<f64>,
+    // Stage with the lowest monitored loss, counted from 1, when early
+    // stopping was enabled
+    #[serde(default)]
+    best_iteration: Option<usize>,
+}
+
+impl fmt::Display for GradientBoostingRegressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GradientBoostingRegressor{{ n_estimators: {}, learning_rate: {}, max_depth: {:?}, subsample: {} }}",
+            self.n_estimators, self.learning_rate, self.max_depth, self.subsample
+        )
+    }
+}
+
+impl fmt::Display for GradientBoostingClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GradientBoostingClassifier{{ n_estimators: {}, learning_rate: {}, max_depth: {:?}, subsample: {} }}",
+            self.n_estimators, self.learning_rate, self.max_depth, self.subsample
+        )
+    }
+}
+
+impl Default for GradientBoostingRegressor {
+    fn default() -> Self {
+        GradientBoostingRegressor::new(N_ESTIMATORS)
+    }
+}
+
+builder!(GradientBoostingRegressor => GradientBoostingRegressorBuilder, default, {
+    n_estimators: usize,
+    learning_rate: f64,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    subsample: f64,
+}, some {
+    max_depth: usize,
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl Default for GradientBoostingClassifier {
+    fn default() -> Self {
+        GradientBoostingClassifier::new(N_ESTIMATORS)
+    }
+}
+
+builder!(GradientBoostingClassifier => GradientBoostingClassifierBuilder, default, {
+    n_estimators: usize,
+    learning_rate: f64,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    subsample: f64,
+}, some {
+    max_depth: usize,
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl GradientBoostingRegressor {
+    pub fn new(n_estimators: usize) -> Self {
+        GradientBoostingRegressor {
+            n_estimators,
+            learning_rate: LEARNING_RATE,
+            max_depth: Some(MAX_DEPTH),
+            min_samples_split: 2,
+            min_samples_leaf: 1,
+            subsample: 1.0,
+            early_stopping: None,
+            random_state: None,
+            model: Boosted::default(),
+        }
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // Fewer than n_estimators when early stopping kicked in
+    pub fn trees(&self) -> &[TreeNode] {
+        &self.model.trees
+    }
+
+    // Mean squared error on the training samples after every stage
+    pub fn train_loss(&self) -> &[f64] {
+        &self.model.train_loss
+    }
+
+    // Mean squared error on the held-out samples after every stage, empty
+    // unless early stopping monitors the validation loss
+    pub fn validation_loss(&self) -> &[f64] {
+        &self.model.validation_loss
+    }
+
+    // Stage with the lowest monitored loss, counted from 1. None unless
+    // early stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.model.best_iteration
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[f64]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        self.model = boost(x, y, &self.boosting(), &mut Notifier::none());
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<f64> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        x.iter_rows().map(|point| self.model.raw(point)).collect()
+    }
+
+    // Predictions after each stage, for plotting how the fit evolves
+    pub fn staged_predict(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.model.staged_raw(&point_matrix(x))
+    }
+
+    // R² of the predictions for x against y
+    pub fn score(&self, x: &[Point], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+
+    fn boosting(&self) -> Boosting {
+        Boosting {
+            loss: Loss::Squared,
+            n_estimators: self.n_estimators,
+            learning_rate: self.learning_rate,
+            tree: tree_params(
+                self.max_depth,
+                self.min_samples_split,
+                self.min_samples_leaf,
+            ),
+            subsample: self.subsample,
+            early_stopping: self.early_stopping,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl Estimator for GradientBoostingRegressor {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        GradientBoostingRegressor::fit_matrix(self, x, y);
+    }
+}
+
+// An epoch per stage, reporting the training loss and, with early stopping,
+// the validation loss. Converged when early stopping ends the fit.
+impl IterativeEstimator for GradientBoostingRegressor {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[f64], observer: &mut dyn FitObserver) {
+        self.model = boost(x, y, &self.boosting(), &mut Notifier::new(observer));
+    }
+}
+
+impl Predictor for GradientBoostingRegressor {
+    type Output = f64;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        GradientBoostingRegressor::predict_matrix(self, x)
+    }
+}
+
+impl ToOnnx for GradientBoostingRegressor {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.model.trees.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(self
+            .model
+            .ensemble()
+            .regressor(None, "SUM", self.model.init))
+    }
+}
+
+impl GradientBoostingClassifier {
+    pub fn new(n_estimators: usize) -> Self {
+        GradientBoostingClassifier {
+            n_estimators,
+            learning_rate: LEARNING_RATE,
+            max_depth: Some(MAX_DEPTH),
+            min_samples_split: 2,
+            min_samples_leaf: 1,
+            subsample: 1.0,
+            early_stopping: None,
+            random_state: None,
+            model: Boosted::default(),
+        }
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // Fewer than n_estimators when early stopping kicked in
+    pub fn trees(&self) -> &[TreeNode] {
+        &self.model.trees
+    }
+
+    // Mean log-loss on the training samples after every stage
+    pub fn train_loss(&self) -> &[f64] {
+        &self.model.train_loss
+    }
+
+    // Mean log-loss on the held-out samples after every stage, empty
+    // unless early stopping monitors the validation loss
+    pub fn validation_loss(&self) -> &[f64] {
+        &self.model.validation_loss
+    }
+
+    // Stage with the lowest monitored loss, counted from 1. None unless
+    // early stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.model.best_iteration
+    }
+
+    // Any label other than 0 counts as the positive class
+    pub fn fit(&mut self, x: &[Point], y: &[usize]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        self.fit_notified(x, y, &mut Notifier::none());
+    }
+
+    fn fit_notified(&mut self, x: &Matrix, y: &[usize], notifier: &mut Notifier) {
+        let y: Vec<f64> = y.iter().map(|&label| (label != 0) as u8 as f64).collect();
+        self.model = boost(x, &y, &self.boosting(), notifier);
+    }
+
+    // Log-odds of the positive class
+    pub fn decision_function(&self, x: &[Point]) -> Vec<f64> {
+        self.decision_function_matrix(&point_matrix(x))
+    }
+
+    pub fn decision_function_matrix(&self, x: &Matrix) -> Vec<f64> {
+        x.iter_rows().map(|point| self.model.raw(point)).collect()
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<usize> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        self.decision_function_matrix(x)
+            .iter()
+            .map(|&raw| (raw > 0.0) as usize)
+            .collect()
+    }
+
+    // [P(0), P(1)] for every point
+    pub fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.predict_proba_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        self.decision_function_matrix(x)
+            .iter()
+            .map(|&raw| proba(raw))
+            .collect()
+    }
+
+    // Labels after each stage
+    pub fn staged_predict(&self, x: &[Point]) -> Vec<Vec<usize>> {
+        self.model
+            .staged_raw(&point_matrix(x))
+            .iter()
+            .map(|stage| stage.iter().map(|&raw| (raw > 0.0) as usize).collect())
+            .collect()
+    }
+
+    // Probabilities after each stage
+    pub fn staged_predict_proba(&self, x: &[Point]) -> Vec<Vec<Vec<f64>>> {
+        self.model
+            .staged_raw(&point_matrix(x))
+            .iter()
+            .map(|stage| stage.iter().map(|&raw| proba(raw)).collect())
+            .collect()
+    }
+
+    // Mean accuracy on x against y
+    pub fn score(&self, x: &[Point], y: &[usize]) -> f64 {
+        accuracy_score(y, &self.predict(x))
+    }
+
+    fn boosting(&self) -> Boosting {
+        Boosting {
+            loss: Loss::LogLoss,
+            n_estimators: self.n_estimators,
+            learning_rate: self.learning_rate,
+            tree: tree_params(
+                self.max_depth,
+                self.min_samples_split,
+                self.min_samples_leaf,
+            ),
+            subsample: self.subsample,
+            early_stopping: self.early_stopping,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl Estimator for GradientBoostingClassifier {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        GradientBoostingClassifier::fit_matrix(self, x, y);
+    }
+}
+
+// Like the regressor's, with the log-loss
+impl IterativeEstimator for GradientBoostingClassifier {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[usize], observer: &mut dyn FitObserver) {
+        self.fit_notified(x, y, &mut Notifier::new(observer));
+    }
+}
+
+impl Predictor for GradientBoostingClassifier {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        GradientBoostingClassifier::predict_matrix(self, x)
+    }
+}
+
+impl Classifier for GradientBoostingClassifier {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        GradientBoostingClassifier::predict_proba_matrix(self, x)
+    }
+}
+
+// Log-odds of the positive class through the logistic function, as
+// predict_proba
+impl ToOnnx for GradientBoostingClassifier {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.model.trees.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(self
+            .model
+            .ensemble()
+            .classifier(None, 2, "LOGISTIC", vec![self.model.init]))
+    }
+}
+
+impl Loss {
+    // Best constant prediction
+    fn init(&self, y: &[f64]) -> f64 {
+        let mean = y.iter().sum::<f64>() / y.len().max(1) as f64;
+        match self {
+            Loss::Squared => mean,
+            Loss::LogLoss => {
+                let p = mean.clamp(PROBA_CLIP, 1.0 - PROBA_CLIP);
+                (p / (1.0 - p)).ln()
+            }
+        }
+    }
+
+    fn negative_gradient(&self, y: f64, raw: f64) -> f64 {
+        match self {
+            Loss::Squared => y - raw,
+            Loss::LogLoss => y - sigmoid(raw),
+        }
+    }
+
+    fn mean_loss(&self, y: &[f64], raw: &[f64]) -> f64 {
+        let total: f64 = y
+            .iter()
+            .zip(raw)
+            .map(|(&y, &raw)| match self {
+                Loss::Squared => (y - raw).powi(2),
+                // log(1 + e^raw) - y * raw, written to avoid overflow
+                Loss::LogLoss => raw.max(0.0) + (-raw.abs()).exp().ln_1p() - y * raw,
+            })
+            .sum();
+        total / y.len().max(1) as f64
+    }
+}
+
+impl Boosted {
+    // The losses after the last stage
+    fn stage_metrics(&self) -> FitMetrics {
+        let mut metrics = FitMetrics::new(self.trees.len())
+            .with("train_loss", self.train_loss[self.trees.len() - 1]);
+        if let Some(&loss) = self.validation_loss.last() {
+            metrics = metrics.with("validation_loss", loss);
+        }
+        metrics
+    }
+
+    // The trees with their outputs scaled by the learning rate, the model
+    // less init
+    fn ensemble(&self) -> TreeEnsemble {
+        self.trees
+            .iter()
+            .fold(TreeEnsemble::new(), |ensemble, tree| {
+                ensemble.with_tree(tree, |value| vec![(0, self.learning_rate * value[0])])
+            })
+    }
+
+    fn raw(&self, point: &[f64]) -> f64 {
+        self.init
+            + self.learning_rate
+                * self
+                    .trees
+                    .iter()
+                    .map(|tree| tree.leaf_value(point)[0])
+                    .sum::<f64>()
+    }
+
+    fn staged_raw(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        let mut raw = vec![self.init; x.n_rows()];
+        self.trees
+            .iter()
+            .map(|tree| {
+                for (value, point) in raw.iter_mut().zip(x.iter_rows()) {
+                    *value += self.learning_rate * tree.leaf_value(point)[0];
+                }
+                raw.clone()
+            })
+            .collect()
+    }
+}
+
+fn tree_params(
+    max_depth: Option<usize>,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+) -> TreeParams {
+    TreeParams {
+        // Ignored for regression targets
+        criterion: Criterion::Gini,
+        max_depth,
+        min_samples_split,
+        min_samples_leaf,
+        max_features: MaxFeatures::All,
+    }
+}
+
+// Friedman's gradient boosting: every stage fits a regression tree to the
+// negative gradient of the loss on a subsample of the training set
+fn boost(x: &Matrix, y: &[f64], settings: &Boosting, notifier: &mut Notifier) -> Boosted {
+    let n = x.n_rows();
+    let mut model = Boosted {
+        learning_rate: settings.learning_rate,
+        ..Boosted::default()
+    };
+    if n == 0 {
+        return model;
+    }
+
+    let mut rng = seeded_rng(settings.random_state);
+    let (validation, train) = match &settings.early_stopping {
+        Some(early_stopping) => early_stopping.split(n, &mut rng),
+        None => (vec![], (0..n).collect()),
+    };
+    let mut tracker = settings
+        .early_stopping
+        .map(|early_stopping| early_stopping.tracker());
+    let y_train: Vec<f64> = train.iter().map(|&i| y[i]).collect();
+    let y_validation: Vec<f64> = validation.iter().map(|&i| y[i]).collect();
+
+    model.init = settings.loss.init(&y_train);
+    // Current raw predictions for every sample, indexed like x
+    let mut raw = vec![model.init; n];
+    let mut residuals = vec![0.0; n];
+    let n_subsample =
+        ((train.len() as f64 * settings.subsample).round() as usize).clamp(1, train.len());
+
+    for _ in 0..settings.n_estimators {
+        for &i in &train {
+            residuals[i] = settings.loss.negative_gradient(y[i], raw[i]);
+        }
+        let mut indices: Vec<usize> = if n_subsample < train.len() {
+            sample(&mut rng, train.len(), n_subsample)
+                .iter()
+                .map(|j| train[j])
+                .collect()
+        } else {
+            train.clone()
+        };
+        let mut tree = build_tree(
+            x,
+            Target::Values(&residuals),
+            &mut indices,
+            &settings.tree,
+            &mut rng,
+        );
+
+        // One Newton step per leaf instead of the mean residual
+        if settings.loss == Loss::LogLoss {
+            tree.update_leaves(x, &mut indices, &mut |leaf| {
+                let (numerator, denominator) = leaf.iter().fold((0.0, 0.0), |(num, den), &i| {
+                    let p = sigmoid(raw[i]);
+                    (num + residuals[i], den + p * (1.0 - p))
+                });
+                vec![if denominator > 0.0 {
+                    numerator / denominator
+                } else {
+                    0.0
+                }]
+            });
+        }
+
+        for (value, point) in raw.iter_mut().zip(x.iter_rows()) {
+            *value += settings.learning_rate * tree.leaf_value(point)[0];
+        }
+        model.trees.push(tree);
+
+        let raw_train: Vec<f64> = train.iter().map(|&i| raw[i]).collect();
+        model
+            .train_loss
+            .push(settings.loss.mean_loss(&y_train, &raw_train));
+
+        if !validation.is_empty() {
+            let raw_validation: Vec<f64> = validation.iter().map(|&i| raw[i]).collect();
+            let loss = settings.loss.mean_loss(&y_validation, &raw_validation);
+            model.validation_loss.push(loss);
+        }
+        notifier.epoch(|| model.stage_metrics());
+        if let Some(tracker) = &mut tracker {
+            let monitored = match model.validation_loss.last() {
+                Some(&loss) => loss,
+                None => model.train_loss[model.train_loss.len() - 1],
+            };
+            let stop = tracker.update(monitored);
+            model.best_iteration = Some(tracker.best_iteration());
+            if stop {
+                notifier.converged();
+                break;
+            }
+        }
+    }
+
+    model
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+fn proba(raw: f64) -> Vec<f64> {
+    let p = sigmoid(raw);
+    vec![1.0 - p, p]
+}
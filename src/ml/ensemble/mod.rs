@@ -0,0 +1,2 @@
+pub mod gbm;
+pub mod random_forest;
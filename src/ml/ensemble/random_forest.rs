@@ -0,0 +1,524 @@
+use crate::compute::parallel::par_map;
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::classification::{most_probable, Classifier};
+use crate::ml::metrics::classification::accuracy_score;
+use crate::ml::metrics::regression::r2_score;
+use crate::ml::onnx::{OnnxError, ToOnnx, TreeEnsemble};
+use crate::ml::tree::{build_tree, Criterion, MaxFeatures, Target, TreeNode, TreeParams};
+use crate::ml::utils::{argmax, seeded_rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const N_ESTIMATORS: usize = 100;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Bagged decision trees with a random feature subset at every split,
+// predicting the mean of the trees' class probabilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomForestClassifier {
+    pub n_estimators: usize,
+    pub criterion: Criterion,
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    pub max_features: MaxFeatures,
+    // Fit each tree on a bootstrap sample instead of the full dataset
+    pub bootstrap: bool,
+    // Score every sample with the trees that didn't see it (bootstrap only)
+    pub oob_score: bool,
+    pub random_state: Option<u64>,
+
+    // Current state
+    trees: Vec<TreeNode>,
+    n_classes: usize,
+    n_features: usize,
+    oob_accuracy: Option<f64>,
+}
+
+// Bagged regression trees predicting the mean of the trees' outputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomForestRegressor {
+    pub n_estimators: usize,
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    pub max_features: MaxFeatures,
+    // Fit each tree on a bootstrap sample instead of the full dataset
+    pub bootstrap: bool,
+    // Score every sample with the trees that didn't see it (bootstrap only)
+    pub oob_score: bool,
+    pub random_state: Option<u64>,
+
+    // Current state
+    trees: Vec<TreeNode>,
+    n_features: usize,
+    oob_r2: Option<f64>,
+}
+
+// How the ensemble samples its training sets
+struct Bagging {
+    n_estimators: usize,
+    bootstrap: bool,
+    oob_score: bool,
+    random_state: Option<u64>,
+}
+
+impl fmt::Display for RandomForestClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RandomForestClassifier{{ n_estimators: {}, criterion: {:?}, max_depth: {:?}, max_features: {:?} }}",
+            self.n_estimators, self.criterion, self.max_depth, self.max_features
+        )
+    }
+}
+
+impl fmt::Display for RandomForestRegressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RandomForestRegressor{{ n_estimators: {}, max_depth: {:?}, max_features: {:?} }}",
+            self.n_estimators, self.max_depth, self.max_features
+        )
+    }
+}
+
+impl Default for RandomForestClassifier {
+    fn default() -> Self {
+        RandomForestClassifier::new(N_ESTIMATORS)
+    }
+}
+
+builder!(RandomForestClassifier => RandomForestClassifierBuilder, default, {
+    n_estimators: usize,
+    criterion: Criterion,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    max_features: MaxFeatures,
+    bootstrap: bool,
+    oob_score: bool,
+}, some {
+    max_depth: usize,
+    random_state: u64,
+});
+
+impl Default for RandomForestRegressor {
+    fn default() -> Self {
+        RandomForestRegressor::new(N_ESTIMATORS)
+    }
+}
+
+builder!(RandomForestRegressor => RandomForestRegressorBuilder, default, {
+    n_estimators: usize,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    max_features: MaxFeatures,
+    bootstrap: bool,
+    oob_score: bool,
+}, some {
+    max_depth: usize,
+    random_state: u64,
+});
+
+impl RandomForestClassifier {
+    pub fn new(n_estimators: usize) -> Self {
+        RandomForestClassifier {
+            n_estimators,
+            criterion: Criterion::Gini,
+            max_depth: None,
+            min_samples_split: 2,
+            min_samples_leaf: 1,
+            max_features: MaxFeatures::Sqrt,
+            bootstrap: true,
+            oob_score: false,
+            random_state: None,
+            trees: vec![],
+            n_classes: 0,
+            n_features: 0,
+            oob_accuracy: None,
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Criterion) -> Self {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_oob_score(mut self, oob_score: bool) -> Self {
+        self.oob_score = oob_score;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn trees(&self) -> &[TreeNode] {
+        &self.trees
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.n_classes
+    }
+
+    // Out-of-bag accuracy from the last fit, None unless oob_score and
+    // bootstrap were set
+    pub fn oob_score(&self) -> Option<f64> {
+        self.oob_accuracy
+    }
+
+    // Mean of the trees' normalized impurity decreases per feature
+    pub fn feature_importances(&self) -> Vec<f64> {
+        mean_importances(&self.trees, self.n_features)
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[usize]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        self.n_classes = y.iter().max().map_or(0, |&m| m + 1);
+        self.n_features = x.n_cols();
+        let target = Target::Classes {
+            labels: y,
+            n_classes: self.n_classes,
+        };
+        let (trees, oob) = grow_forest(x, target, &self.tree_params(), &self.bagging());
+        self.trees = trees;
+
+        self.oob_accuracy = oob.map(|oob| {
+            let scored: Vec<bool> = oob
+                .iter()
+                .zip(y)
+                .filter_map(|(proba, &label)| proba.as_ref().map(|p| argmax(p) == label))
+                .collect();
+            let correct = scored.iter().filter(|&&hit| hit).count();
+            correct as f64 / scored.len().max(1) as f64
+        });
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<usize> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+
+    pub fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.predict_proba_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        x.iter_rows()
+            .map(|point| mean_leaf_value(&self.trees, point, self.n_classes))
+            .collect()
+    }
+
+    // Mean accuracy on x against y
+    pub fn score(&self, x: &[Point], y: &[usize]) -> f64 {
+        accuracy_score(y, &self.predict(x))
+    }
+
+    fn tree_params(&self) -> TreeParams {
+        TreeParams {
+            criterion: self.criterion,
+            max_depth: self.max_depth,
+            min_samples_split: self.min_samples_split,
+            min_samples_leaf: self.min_samples_leaf,
+            max_features: self.max_features,
+        }
+    }
+
+    fn bagging(&self) -> Bagging {
+        Bagging {
+            n_estimators: self.n_estimators,
+            bootstrap: self.bootstrap,
+            oob_score: self.oob_score,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl Estimator for RandomForestClassifier {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        RandomForestClassifier::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.trees.is_empty()).then_some(self.n_features)
+    }
+}
+
+impl Predictor for RandomForestClassifier {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        RandomForestClassifier::predict_matrix(self, x)
+    }
+}
+
+impl Classifier for RandomForestClassifier {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        RandomForestClassifier::predict_proba_matrix(self, x)
+    }
+}
+
+// The mean of the trees' class proportions, as predict_proba
+impl ToOnnx for RandomForestClassifier {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.trees.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        let n_trees = self.trees.len() as f64;
+        let ensemble = self
+            .trees
+            .iter()
+            .fold(TreeEnsemble::new(), |ensemble, tree| {
+                ensemble.with_tree(tree, |proportions| {
+                    proportions
+                        .iter()
+                        .map(|proportion| proportion / n_trees)
+                        .enumerate()
+                        .collect()
+                })
+            });
+        Ok(ensemble.classifier(Some(self.n_features), self.n_classes, "NONE", vec![]))
+    }
+}
+
+impl RandomForestRegressor {
+    pub fn new(n_estimators: usize) -> Self {
+        RandomForestRegressor {
+            n_estimators,
+            max_depth: None,
+            min_samples_split: 2,
+            min_samples_leaf: 1,
+            max_features: MaxFeatures::All,
+            bootstrap: true,
+            oob_score: false,
+            random_state: None,
+            trees: vec![],
+            n_features: 0,
+            oob_r2: None,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_oob_score(mut self, oob_score: bool) -> Self {
+        self.oob_score = oob_score;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn trees(&self) -> &[TreeNode] {
+        &self.trees
+    }
+
+    // Out-of-bag R² from the last fit, None unless oob_score and bootstrap
+    // were set
+    pub fn oob_score(&self) -> Option<f64> {
+        self.oob_r2
+    }
+
+    // Mean of the trees' normalized impurity decreases per feature
+    pub fn feature_importances(&self) -> Vec<f64> {
+        mean_importances(&self.trees, self.n_features)
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[f64]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        self.n_features = x.n_cols();
+        let (trees, oob) = grow_forest(x, Target::Values(y), &self.tree_params(), &self.bagging());
+        self.trees = trees;
+
+        self.oob_r2 = oob.map(|oob| {
+            let (y_true, y_pred): (Vec<f64>, Vec<f64>) = oob
+                .iter()
+                .zip(y)
+                .filter_map(|(value, &target)| value.as_ref().map(|v| (target, v[0])))
+                .unzip();
+            r2_score(&y_true, &y_pred)
+        });
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<f64> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        x.iter_rows()
+            .map(|point| mean_leaf_value(&self.trees, point, 1)[0])
+            .collect()
+    }
+
+    // R² of the predictions for x against y
+    pub fn score(&self, x: &[Point], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+
+    fn tree_params(&self) -> TreeParams {
+        TreeParams {
+            // Ignored for regression targets
+            criterion: Criterion::Gini,
+            max_depth: self.max_depth,
+            min_samples_split: self.min_samples_split,
+            min_samples_leaf: self.min_samples_leaf,
+            max_features: self.max_features,
+        }
+    }
+
+    fn bagging(&self) -> Bagging {
+        Bagging {
+            n_estimators: self.n_estimators,
+            bootstrap: self.bootstrap,
+            oob_score: self.oob_score,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl Estimator for RandomForestRegressor {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        RandomForestRegressor::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.trees.is_empty()).then_some(self.n_features)
+    }
+}
+
+impl Predictor for RandomForestRegressor {
+    type Output = f64;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        RandomForestRegressor::predict_matrix(self, x)
+    }
+}
+
+impl ToOnnx for RandomForestRegressor {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.trees.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        let ensemble = self
+            .trees
+            .iter()
+            .fold(TreeEnsemble::new(), |ensemble, tree| {
+                ensemble.with_tree(tree, |value| vec![(0, value[0])])
+            });
+        Ok(ensemble.regressor(Some(self.n_features), "AVERAGE", 0.0))
+    }
+}
+
+// Mean out-of-bag leaf value per sample, None for samples every tree saw
+type OutOfBag = Vec<Option<Vec<f64>>>;
+
+// Fit every tree on its own sample of the data, and average each sample's
+// predictions from the trees it was left out of when requested
+fn grow_forest(
+    x: &Matrix,
+    target: Target,
+    params: &TreeParams,
+    bagging: &Bagging,
+) -> (Vec<TreeNode>, Option<OutOfBag>) {
+    let n = x.n_rows();
+    if n == 0 {
+        return (vec![], None);
+    }
+    let mut rng = seeded_rng(bagging.random_state);
+    let track_oob = bagging.bootstrap && bagging.oob_score;
+    let mut oob_sums: OutOfBag = vec![None; n];
+    let mut oob_counts = vec![0usize; n];
+
+    // One stream per tree, drawn from the forest's seed up front so that
+    // trees grown on several threads are the same as grown in turn
+    let seeds: Vec<u64> = (0..bagging.n_estimators).map(|_| rng.gen()).collect();
+    let grown = par_map(&seeds, |&seed| {
+        let mut tree_rng = StdRng::seed_from_u64(seed);
+        let mut indices: Vec<usize> = if bagging.bootstrap {
+            (0..n).map(|_| tree_rng.gen_range(0..n)).collect()
+        } else {
+            (0..n).collect()
+        };
+        let mut in_bag = vec![false; n];
+        indices.iter().for_each(|&i| in_bag[i] = true);
+        let tree = build_tree(x, target, &mut indices, params, &mut tree_rng);
+        (tree, in_bag)
+    });
+
+    let mut trees = Vec::with_capacity(grown.len());
+    for (tree, in_bag) in grown {
+        if track_oob {
+            for i in (0..n).filter(|&i| !in_bag[i]) {
+                let value = tree.leaf_value(x.row(i));
+                let sum = oob_sums[i].get_or_insert_with(|| vec![0.0; value.len()]);
+                sum.iter_mut().zip(value).for_each(|(s, v)| *s += v);
+                oob_counts[i] += 1;
+            }
+        }
+        trees.push(tree);
+    }
+
+    if !track_oob {
+        return (trees, None);
+    }
+    for (sum, &count) in oob_sums.iter_mut().zip(&oob_counts) {
+        if let Some(sum) = sum {
+            sum.iter_mut().for_each(|s| *s /= count as f64);
+        }
+    }
+    (trees, Some(oob_sums))
+}
+
+fn mean_leaf_value(trees: &[TreeNode], point: &[f64], width: usize) -> Vec<f64> {
+    let mut sum = vec![0.0; width];
+    for tree in trees {
+        sum.iter_mut()
+            .zip(tree.leaf_value(point))
+            .for_each(|(s, v)| *s += v);
+    }
+    let count = trees.len().max(1) as f64;
+    sum.iter().map(|s| s / count).collect()
+}
+
+fn mean_importances(trees: &[TreeNode], n_features: usize) -> Vec<f64> {
+    let mut importances = vec![0.0; n_features];
+    for tree in trees {
+        importances
+            .iter_mut()
+            .zip(tree.feature_importances(n_features))
+            .for_each(|(total, v)| *total += v);
+    }
+    let count = trees.len().max(1) as f64;
+    importances.iter().map(|v| v / count).collect()
+}
@@ -0,0 +1,285 @@
+// Plane geometry of point sets, e.g. the regions KMeans partitions the
+// plane into
+
+// Point in the plane
+pub type Point2 = (f64, f64);
+
+// Corners in order around the boundary, the last joined to the first
+pub type Polygon = Vec<Point2>;
+
+// Voronoi cell of every site within the rectangle spanned by x and y: the
+// points nearer to it than to any other site, i.e. the region KMeans
+// assigns to a centroid. Each cell is the rectangle clipped by the
+// perpendicular bisectors between its site and the others, O(n^2) for n
+// sites. Sites without two finite coordinates get an empty cell, as do
+// repeated sites after the first.
+pub fn voronoi_cells(sites: &[Vec<f64>], x: (f64, f64), y: (f64, f64)) -> Vec<Polygon> {
+    let sites: Vec<Option<Point2>> = sites
+        .iter()
+        .enumerate()
+        .map(|(i, site)| {
+            let point = (*site.first()?, *site.get(1)?);
+            let finite = point.0.is_finite() && point.1.is_finite();
+            let repeated = sites[..i]
+                .iter()
+                .any(|earlier| earlier.len() > 1 && (earlier[0], earlier[1]) == point);
+            (finite && !repeated).then_some(point)
+        })
+        .collect();
+    let bounds = vec![(x.0, y.0), (x.1, y.0), (x.1, y.1), (x.0, y.1)];
+
+    sites
+        .iter()
+        .enumerate()
+        .map(|(i, site)| {
+            let Some(site) = site else {
+                return vec![];
+            };
+            sites
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .filter_map(|(_, other)| *other)
+                .fold(bounds.clone(), |cell, other| {
+                    clip_nearer(&cell, *site, other)
+                })
+        })
+        .collect()
+}
+
+// Part of polygon at least as near to a as to b, by Sutherland-Hodgman
+// clipping against their perpendicular bisector
+fn clip_nearer(polygon: &[Point2], a: Point2, b: Point2) -> Polygon {
+    // Positive on b's side of the bisector
+    let normal = (b.0 - a.0, b.1 - a.1);
+    let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let side = |p: Point2| (p.0 - middle.0) * normal.0 + (p.1 - middle.1) * normal.1;
+
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    let Some(&last) = polygon.last() else {
+        return clipped;
+    };
+    let mut previous = (last, side(last));
+    for &corner in polygon {
+        let current = (corner, side(corner));
+        // Where the edge crosses the bisector
+        if (previous.1 > 0.0) != (current.1 > 0.0) {
+            let t = previous.1 / (previous.1 - current.1);
+            clipped.push((
+                previous.0 .0 + t * (corner.0 - previous.0 .0),
+                previous.0 .1 + t * (corner.1 - previous.0 .1),
+            ));
+        }
+        if current.1 <= 0.0 {
+            clipped.push(corner);
+        }
+        previous = current;
+    }
+    clipped
+}
+
+// Smallest convex polygon holding every point, counter-clockwise from the
+// leftmost point, by Andrew's monotone chain in O(n log n). Points
+// without two finite coordinates are skipped. Fewer than three corners for
+// points all on a line.
+pub fn convex_hull(points: &[Vec<f64>]) -> Polygon {
+    let mut points = finite_points(points);
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    // Lower hull left to right, then upper hull right to left, each
+    // dropping corners that don't turn left
+    let mut hull: Polygon = Vec::with_capacity(2 * points.len());
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last corner starts the other pass
+        hull.pop();
+    }
+    hull
+}
+
+// Delaunay triangulation by the Bowyer-Watson algorithm, O(n^2): no
+// point lies inside the circle through the corners of any triangle.
+// Triangles are indices into points. Points without two finite coordinates
+// are left out of every triangle, as are repeated points after the first.
+pub fn delaunay(points: &[Vec<f64>]) -> Vec<[usize; 3]> {
+    let indexed: Vec<(usize, Point2)> = points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| Some((i, (*p.first()?, *p.get(1)?))))
+        .filter(|(_, (x, y))| x.is_finite() && y.is_finite())
+        .collect();
+    let Some(bounds) = bounding_box(indexed.iter().map(|&(_, p)| p)) else {
+        return vec![];
+    };
+
+    // Corners of a triangle around every point go after the points
+    let n = points.len();
+    let ((min_x, min_y), (max_x, max_y)) = bounds;
+    let span = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let (center_x, center_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let mut corners: Vec<Point2> = points
+        .iter()
+        .map(|p| {
+            (
+                p.first().copied().unwrap_or(0.0),
+                p.get(1).copied().unwrap_or(0.0),
+            )
+        })
+        .collect();
+    corners.extend([
+        (center_x - 20.0 * span, center_y - span),
+        (center_x, center_y + 20.0 * span),
+        (center_x + 20.0 * span, center_y - span),
+    ]);
+
+    let mut triangles = vec![Triangle::new([n, n + 1, n + 2], &corners)];
+    let mut seen: Vec<Point2> = vec![];
+    for (i, point) in indexed {
+        if seen.contains(&point) {
+            continue;
+        }
+        seen.push(point);
+        // Triangles whose circle holds the point give way to triangles
+        // joining it to the edges around them
+        let (bad, good): (Vec<Triangle>, Vec<Triangle>) = triangles
+            .into_iter()
+            .partition(|t| t.circle_contains(point));
+        triangles = good;
+        let edges: Vec<(usize, usize)> = bad.iter().flat_map(Triangle::edges).collect();
+        for &(a, b) in &edges {
+            let shared = edges
+                .iter()
+                .filter(|&&(c, d)| (c, d) == (a, b) || (c, d) == (b, a))
+                .count();
+            if shared == 1 {
+                triangles.push(Triangle::new([a, b, i], &corners));
+            }
+        }
+    }
+    triangles
+        .into_iter()
+        .map(|t| t.corners)
+        .filter(|corners| corners.iter().all(|&c| c < n))
+        .collect()
+}
+
+// Outline of the points tighter than their convex hull: the boundary edges
+// of the Delaunay triangles whose circumcircle has at most radius. Large
+// radii give the convex hull, small ones follow concave clusters and split
+// apart distant groups. Edges come in no particular order.
+pub fn alpha_shape(points: &[Vec<f64>], radius: f64) -> Vec<(Point2, Point2)> {
+    let corners: Vec<Point2> = points
+        .iter()
+        .map(|p| {
+            (
+                p.first().copied().unwrap_or(f64::NAN),
+                p.get(1).copied().unwrap_or(f64::NAN),
+            )
+        })
+        .collect();
+    let kept: Vec<Triangle> = delaunay(points)
+        .into_iter()
+        .map(|t| Triangle::new(t, &corners))
+        .filter(|t| t.radius_squared <= radius * radius)
+        .collect();
+    let mut edges: Vec<(usize, usize)> = kept
+        .iter()
+        .flat_map(Triangle::edges)
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    edges.sort_unstable();
+    // Interior edges are shared by two triangles
+    let mut boundary = vec![];
+    let mut i = 0;
+    while i < edges.len() {
+        let repeats = edges[i..].iter().take_while(|&&e| e == edges[i]).count();
+        if repeats == 1 {
+            let (a, b) = edges[i];
+            boundary.push((corners[a], corners[b]));
+        }
+        i += repeats;
+    }
+    boundary
+}
+
+// Triangle of a triangulation with its circumcircle
+struct Triangle {
+    corners: [usize; 3],
+    center: Point2,
+    radius_squared: f64,
+}
+
+impl Triangle {
+    fn new(corners: [usize; 3], points: &[Point2]) -> Self {
+        let [a, b, c] = corners.map(|i| points[i]);
+        let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+        let norm = |p: Point2| p.0 * p.0 + p.1 * p.1;
+        // Degenerate triangles have a circle holding everything
+        let center = if d.abs() < f64::EPSILON {
+            (f64::NAN, f64::NAN)
+        } else {
+            (
+                (norm(a) * (b.1 - c.1) + norm(b) * (c.1 - a.1) + norm(c) * (a.1 - b.1)) / d,
+                (norm(a) * (c.0 - b.0) + norm(b) * (a.0 - c.0) + norm(c) * (b.0 - a.0)) / d,
+            )
+        };
+        let radius_squared = if center.0.is_nan() {
+            f64::INFINITY
+        } else {
+            (a.0 - center.0).powi(2) + (a.1 - center.1).powi(2)
+        };
+        Triangle {
+            corners,
+            center,
+            radius_squared,
+        }
+    }
+
+    fn circle_contains(&self, p: Point2) -> bool {
+        if self.radius_squared.is_infinite() {
+            return true;
+        }
+        (p.0 - self.center.0).powi(2) + (p.1 - self.center.1).powi(2) < self.radius_squared
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.corners;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+// Positive when o, a, b turn left, negative when they turn right
+fn cross(o: Point2, a: Point2, b: Point2) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+// First two coordinates of the points that have two finite ones
+fn finite_points(points: &[Vec<f64>]) -> Vec<Point2> {
+    points
+        .iter()
+        .filter_map(|p| Some((*p.first()?, *p.get(1)?)))
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .collect()
+}
+
+// Lowest and highest corner of the rectangle around points, None without
+// any
+fn bounding_box(points: impl Iterator<Item = Point2>) -> Option<(Point2, Point2)> {
+    points.fold(None, |bounds, (x, y)| match bounds {
+        Some(((min_x, min_y), (max_x, max_y))) => {
+            Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+        }
+        None => Some(((x, y), (x, y))),
+    })
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+pub mod tsne;
+pub mod umap;
+
+// Starting layout of an embedding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Init {
+    // Random coordinates
+    Random,
+    // Leading principal components, rescaled to the embedding's starting
+    // spread. Preserves global structure better and is deterministic.
+    Pca,
+}
@@ -0,0 +1,586 @@
+use super::Init;
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator};
+use crate::ml::builder::builder;
+use crate::ml::decomposition::pca::Pca;
+use crate::ml::metrics::distance::Euclidean;
+use crate::ml::neighbors::{build_index, Algorithm, NeighborSearch};
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::params::{param_value, Configurable, ParamSpec, Params};
+use crate::ml::utils::seeded_rng;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+const PERPLEXITY: f64 = 30.0;
+// Largest perplexity a form accepts
+const MAX_PERPLEXITY: f64 = 100.0;
+const LEARNING_RATE: f64 = 200.0;
+const MAX_ITER: usize = 1000;
+const THETA: f64 = 0.5;
+const EARLY_EXAGGERATION: f64 = 12.0;
+const EXAGGERATION_ITER: usize = 250;
+const MIN_GRAD_NORM: f64 = 1e-7;
+// Spread of the initial embedding, small so early exaggeration can form
+// clusters before points repel each other
+const INIT_STD: f64 = 1e-4;
+const MIN_GAIN: f64 = 0.01;
+// Binary search for each point's Gaussian bandwidth
+const PERPLEXITY_TOLERANCE: f64 = 1e-5;
+const PERPLEXITY_STEPS: usize = 100;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// State after one gradient step, passed to the callback of
+// `fit_with_callback`
+#[derive(Debug, Clone, Copy)]
+pub struct TsneProgress<'a> {
+    pub iteration: usize,
+    pub embedding: &'a [Point],
+    // Of the (exaggerated, during the first iterations) input affinities
+    // from the current embedding
+    pub kl_divergence: f64,
+    pub gradient_norm: f64,
+}
+
+// t-distributed stochastic neighbor embedding (van der Maaten 2014): input
+// affinities from each point's nearest neighbors, and repulsion between
+// embedded points approximated with a Barnes-Hut space-partitioning tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tsne {
+    // Usually 2, for plotting. Cost of the tree grows as 2^n_components.
+    pub n_components: usize,
+    // Effective number of neighbors each point keeps
+    pub perplexity: f64,
+    pub learning_rate: f64,
+    pub max_iter: usize,
+    // Barnes-Hut accuracy: cells smaller than theta times their distance
+    // count as a single point. 0 is exact.
+    pub theta: f64,
+    pub early_exaggeration: f64,
+    pub init: Init,
+    pub random_state: Option<u64>,
+
+    // Current state
+    embedding: Vec<Point>,
+    kl_divergence: f64,
+    n_iter: usize,
+}
+
+// Cell of the space-partitioning tree over the embedding
+struct Cell {
+    center_of_mass: Vec<f64>,
+    count: usize,
+    // Longest side
+    width: f64,
+    children: Vec<Cell>,
+    // Points held directly, for leaves
+    points: Vec<usize>,
+}
+
+impl fmt::Display for Tsne {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tsne{{ n_components: {}, perplexity: {}, learning_rate: {}, max_iter: {}, theta: {} }}",
+            self.n_components, self.perplexity, self.learning_rate, self.max_iter, self.theta
+        )
+    }
+}
+
+impl Default for Tsne {
+    fn default() -> Self {
+        Tsne::new(2)
+    }
+}
+
+builder!(Tsne => TsneBuilder, default, {
+    n_components: usize,
+    perplexity: f64,
+    learning_rate: f64,
+    max_iter: usize,
+    theta: f64,
+    early_exaggeration: f64,
+    init: Init,
+}, some {
+    random_state: u64,
+});
+
+impl Tsne {
+    pub fn new(n_components: usize) -> Self {
+        Tsne {
+            n_components,
+            perplexity: PERPLEXITY,
+            learning_rate: LEARNING_RATE,
+            max_iter: MAX_ITER,
+            theta: THETA,
+            early_exaggeration: EARLY_EXAGGERATION,
+            init: Init::Pca,
+            random_state: None,
+            embedding: vec![],
+            kl_divergence: 0.0,
+            n_iter: 0,
+        }
+    }
+
+    pub fn with_perplexity(mut self, perplexity: f64) -> Self {
+        self.perplexity = perplexity;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn embedding(&self) -> &[Point] {
+        &self.embedding
+    }
+
+    // KL divergence between input and embedding affinities after the last
+    // iteration
+    pub fn kl_divergence(&self) -> f64 {
+        self.kl_divergence
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        self.fit_matrix_with_callback(data, |_| {});
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.embedding.clone()
+    }
+
+    // Fit, calling back after every gradient step so the embedding can be
+    // drawn as it converges
+    pub fn fit_with_callback<F>(&mut self, data: &[Point], callback: F)
+    where
+        F: FnMut(&TsneProgress),
+    {
+        self.fit_matrix_with_callback(&point_matrix(data), callback);
+    }
+
+    pub fn fit_matrix_with_callback<F>(&mut self, data: &Matrix, mut callback: F)
+    where
+        F: FnMut(&TsneProgress),
+    {
+        self.embedding.clear();
+        self.kl_divergence = 0.0;
+        self.n_iter = 0;
+        let n = data.n_rows();
+        if n == 0 || self.n_components == 0 {
+            return;
+        }
+
+        let affinities = self.input_affinities(data);
+        self.embedding = self.initial_embedding(data);
+        let dim = self.n_components;
+        let mut update = vec![vec![0.0; dim]; n];
+        let mut gains = vec![vec![1.0_f64; dim]; n];
+
+        while self.n_iter < self.max_iter {
+            let exaggerating = self.n_iter < EXAGGERATION_ITER;
+            let exaggeration = if exaggerating {
+                self.early_exaggeration
+            } else {
+                1.0
+            };
+            let momentum = if exaggerating { 0.5 } else { 0.8 };
+
+            let (gradient, kl_divergence) = self.gradient(&affinities, exaggeration);
+            let mut squared_norm = 0.0;
+            let rows = gradient.iter().zip(&mut update).zip(&mut gains);
+            for (((g, u), gain), point) in rows.zip(&mut self.embedding) {
+                for (((&g, u), gain), y) in g.iter().zip(u).zip(gain).zip(point) {
+                    squared_norm += g * g;
+                    // Delta-bar-delta: speed up coordinates whose gradient
+                    // keeps pointing the same way, slow down those that
+                    // oscillate
+                    *gain = if *u * g < 0.0 {
+                        *gain + 0.2
+                    } else {
+                        (*gain * 0.8).max(MIN_GAIN)
+                    };
+                    *u = momentum * *u - self.learning_rate * *gain * g;
+                    *y += *u;
+                }
+            }
+
+            self.n_iter += 1;
+            self.kl_divergence = kl_divergence;
+            let gradient_norm = squared_norm.sqrt();
+            callback(&TsneProgress {
+                iteration: self.n_iter,
+                embedding: &self.embedding,
+                kl_divergence,
+                gradient_norm,
+            });
+            if !exaggerating && gradient_norm < MIN_GRAD_NORM {
+                break;
+            }
+        }
+    }
+
+    // Symmetric joint probabilities P_ij, sparse over each point's
+    // 3 * perplexity nearest neighbors
+    fn input_affinities(&self, data: &Matrix) -> Vec<Vec<(usize, f64)>> {
+        let n = data.n_rows();
+        let k = ((3.0 * self.perplexity) as usize).clamp(1, n.saturating_sub(1).max(1));
+        let index = build_index(data.clone(), Euclidean, Algorithm::Auto);
+
+        let mut joint: Vec<HashMap<usize, f64>> = vec![HashMap::new(); n];
+        for (i, point) in data.iter_rows().enumerate() {
+            let neighbors: Vec<(usize, f64)> = index
+                .query(point, k + 1)
+                .into_iter()
+                .filter(|neighbor| neighbor.index != i)
+                .take(k)
+                .map(|neighbor| (neighbor.index, neighbor.distance * neighbor.distance))
+                .collect();
+            let conditional = conditional_probabilities(&neighbors, self.perplexity);
+            for (&(j, _), p) in neighbors.iter().zip(conditional) {
+                *joint[i].entry(j).or_insert(0.0) += p;
+                *joint[j].entry(i).or_insert(0.0) += p;
+            }
+        }
+
+        let total: f64 = joint.iter().flat_map(|row| row.values()).sum();
+        joint
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<(usize, f64)> = row
+                    .into_iter()
+                    .map(|(j, p)| (j, p / total.max(f64::MIN_POSITIVE)))
+                    .collect();
+                row.sort_by_key(|&(j, _)| j);
+                row
+            })
+            .collect()
+    }
+
+    fn initial_embedding(&self, data: &Matrix) -> Vec<Point> {
+        let dim = self.n_components;
+        match self.init {
+            Init::Pca if data.n_cols() >= dim => {
+                let mut pca = Pca::new(dim);
+                pca.fit_matrix(data);
+                let projected = pca.transform_matrix(data);
+                let n = projected.n_rows() as f64;
+                let mean = projected.column(0).sum::<f64>() / n;
+                let std = (projected.column(0).map(|v| (v - mean).powi(2)).sum::<f64>() / n)
+                    .sqrt()
+                    .max(f64::MIN_POSITIVE);
+                projected
+                    .iter_rows()
+                    .map(|p| p.iter().map(|v| v / std * INIT_STD).collect())
+                    .collect()
+            }
+            _ => {
+                let mut rng = seeded_rng(self.random_state);
+                (0..data.n_rows())
+                    .map(|_| {
+                        (0..dim)
+                            .map(|_| INIT_STD * rng.sample::<f64, _>(StandardNormal))
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // Gradient of the KL divergence for every embedded point, and the KL
+    // divergence itself
+    fn gradient(&self, affinities: &[Vec<(usize, f64)>], exaggeration: f64) -> (Vec<Point>, f64) {
+        let embedding = &self.embedding;
+        let tree = Cell::build(embedding, (0..embedding.len()).collect());
+
+        // Repulsion, with the normalization Z = sum of all Student-t kernels
+        let mut normalization = 0.0;
+        let repulsion: Vec<Point> = embedding
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let mut force = vec![0.0; point.len()];
+                normalization += tree.repulsion(embedding, i, self.theta, &mut force);
+                force
+            })
+            .collect();
+        let normalization = normalization.max(f64::MIN_POSITIVE);
+
+        let mut kl_divergence = 0.0;
+        let gradient = embedding
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let mut attraction = vec![0.0; point.len()];
+                for &(j, p) in &affinities[i] {
+                    let p = p * exaggeration;
+                    let diff: Vec<f64> = point
+                        .iter()
+                        .zip(&embedding[j])
+                        .map(|(a, b)| a - b)
+                        .collect();
+                    let kernel = 1.0 / (1.0 + diff.iter().map(|d| d * d).sum::<f64>());
+                    for (a, d) in attraction.iter_mut().zip(&diff) {
+                        *a += p * kernel * d;
+                    }
+                    let q = (kernel / normalization).max(f64::MIN_POSITIVE);
+                    kl_divergence += p * (p.max(f64::MIN_POSITIVE) / q).ln();
+                }
+                attraction
+                    .iter()
+                    .zip(&repulsion[i])
+                    .map(|(a, r)| 4.0 * (a - r / normalization))
+                    .collect()
+            })
+            .collect();
+        (gradient, kl_divergence)
+    }
+}
+
+impl Configurable for Tsne {
+    fn param_specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::float("perplexity", PERPLEXITY)
+                .with_range(1.0, MAX_PERPLEXITY)
+                .with_step(1.0),
+            ParamSpec::float("learning_rate", LEARNING_RATE)
+                .with_min(1.0)
+                .with_step(10.0),
+            ParamSpec::int("max_iter", MAX_ITER).with_min(EXAGGERATION_ITER as f64),
+            ParamSpec::float("theta", THETA)
+                .with_range(0.0, 1.0)
+                .with_step(0.1),
+            ParamSpec::float("early_exaggeration", EARLY_EXAGGERATION)
+                .with_min(1.0)
+                .with_step(1.0),
+        ]
+    }
+
+    fn params(&self) -> Params {
+        Params::default()
+            .with("perplexity", self.perplexity)
+            .with("learning_rate", self.learning_rate)
+            .with("max_iter", self.max_iter as f64)
+            .with("theta", self.theta)
+            .with("early_exaggeration", self.early_exaggeration)
+    }
+
+    fn set_params(&mut self, params: &Params) {
+        let specs = Self::param_specs();
+        if let Some(perplexity) = param_value(&specs, params, "perplexity") {
+            self.perplexity = perplexity;
+        }
+        if let Some(learning_rate) = param_value(&specs, params, "learning_rate") {
+            self.learning_rate = learning_rate;
+        }
+        if let Some(max_iter) = param_value(&specs, params, "max_iter") {
+            self.max_iter = max_iter as usize;
+        }
+        if let Some(theta) = param_value(&specs, params, "theta") {
+            self.theta = theta;
+        }
+        if let Some(early_exaggeration) = param_value(&specs, params, "early_exaggeration") {
+            self.early_exaggeration = early_exaggeration;
+        }
+    }
+}
+
+impl Estimator for Tsne {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        Tsne::fit_matrix(self, x);
+    }
+}
+
+// An iteration per gradient step, reporting the KL divergence and the
+// gradient's norm. Converged means the gradient vanished before max_iter.
+impl IterativeEstimator for Tsne {
+    fn fit_observed_matrix(&mut self, x: &Matrix, _: &(), observer: &mut dyn FitObserver) {
+        let mut notifier = Notifier::new(observer);
+        self.fit_matrix_with_callback(x, |progress| {
+            notifier.iteration(|| {
+                FitMetrics::new(progress.iteration)
+                    .with("kl_divergence", progress.kl_divergence)
+                    .with("gradient_norm", progress.gradient_norm)
+            });
+        });
+        if self.n_iter > 0 && self.n_iter < self.max_iter {
+            notifier.converged();
+        }
+    }
+}
+
+// p_j|i for each (neighbor, squared distance), with the Gaussian bandwidth
+// found by binary search so the distribution has the requested perplexity
+fn conditional_probabilities(neighbors: &[(usize, f64)], perplexity: f64) -> Vec<f64> {
+    let target_entropy = perplexity.ln();
+    // beta = 1 / (2 sigma^2)
+    let (mut beta, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+    let mut probabilities = vec![0.0; neighbors.len()];
+
+    for _ in 0..PERPLEXITY_STEPS {
+        // Shift by the closest distance so the exponentials don't underflow
+        let nearest = neighbors.iter().map(|n| n.1).fold(f64::INFINITY, f64::min);
+        for (p, &(_, d)) in probabilities.iter_mut().zip(neighbors) {
+            *p = (-(d - nearest) * beta).exp();
+        }
+        let sum: f64 = probabilities.iter().sum::<f64>().max(f64::MIN_POSITIVE);
+        let weighted: f64 = probabilities
+            .iter()
+            .zip(neighbors)
+            .map(|(p, &(_, d))| p * (d - nearest))
+            .sum();
+        probabilities.iter_mut().for_each(|p| *p /= sum);
+        let entropy = sum.ln() + beta * weighted / sum;
+
+        let error = entropy - target_entropy;
+        if error.abs() < PERPLEXITY_TOLERANCE {
+            break;
+        }
+        // Too flat: narrow the kernel, too peaked: widen it
+        if error > 0.0 {
+            low = beta;
+            beta = if high.is_finite() {
+                (beta + high) / 2.0
+            } else {
+                beta * 2.0
+            };
+        } else {
+            high = beta;
+            beta = (beta + low) / 2.0;
+        }
+    }
+    probabilities
+}
+
+impl Cell {
+    fn build(points: &[Point], indices: Vec<usize>) -> Cell {
+        let dim = points[indices[0]].len();
+        let mut low = vec![f64::INFINITY; dim];
+        let mut high = vec![f64::NEG_INFINITY; dim];
+        for &i in &indices {
+            for ((l, h), &x) in low.iter_mut().zip(&mut high).zip(&points[i]) {
+                *l = l.min(x);
+                *h = h.max(x);
+            }
+        }
+        Cell::build_in(points, indices, &low, &high)
+    }
+
+    fn build_in(points: &[Point], indices: Vec<usize>, low: &[f64], high: &[f64]) -> Cell {
+        let dim = low.len();
+        let count = indices.len();
+        let mut center_of_mass = vec![0.0; dim];
+        for &i in &indices {
+            for (c, x) in center_of_mass.iter_mut().zip(&points[i]) {
+                *c += x / count as f64;
+            }
+        }
+        let width = low.iter().zip(high).map(|(l, h)| h - l).fold(0.0, f64::max);
+
+        // Single points and stacks of duplicates stay leaves
+        if count == 1
+            || width
+                <= f64::EPSILON * (1.0 + center_of_mass.iter().map(|c| c.abs()).fold(0.0, f64::max))
+        {
+            return Cell {
+                center_of_mass,
+                count,
+                width,
+                children: vec![],
+                points: indices,
+            };
+        }
+
+        // Split evenly along every axis into 2^dim orthants
+        let middle: Vec<f64> = low.iter().zip(high).map(|(l, h)| (l + h) / 2.0).collect();
+        let mut orthants: Vec<Vec<usize>> = vec![vec![]; 1 << dim];
+        for i in indices {
+            let orthant = (0..dim).fold(0, |code, d| {
+                code | (((points[i][d] > middle[d]) as usize) << d)
+            });
+            orthants[orthant].push(i);
+        }
+        let children = orthants
+            .into_iter()
+            .enumerate()
+            .filter(|(_, members)| !members.is_empty())
+            .map(|(orthant, members)| {
+                let (child_low, child_high): (Vec<f64>, Vec<f64>) = (0..dim)
+                    .map(|d| {
+                        if orthant >> d & 1 == 1 {
+                            (middle[d], high[d])
+                        } else {
+                            (low[d], middle[d])
+                        }
+                    })
+                    .unzip();
+                Cell::build_in(points, members, &child_low, &child_high)
+            })
+            .collect();
+
+        Cell {
+            center_of_mass,
+            count,
+            width,
+            children,
+            points: vec![],
+        }
+    }
+
+    // Add the repulsive force sum_j q_ij^2 * Z^2 * (y_i - y_j) on point i to
+    // force, and return its share of Z
+    fn repulsion(&self, embedding: &[Point], i: usize, theta: f64, force: &mut [f64]) -> f64 {
+        let point = &embedding[i];
+        if self.children.is_empty() {
+            return self
+                .points
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| add_kernel_force(point, &embedding[j], 1.0, force))
+                .sum();
+        }
+
+        let squared_distance: f64 = point
+            .iter()
+            .zip(&self.center_of_mass)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        if self.width * self.width < theta * theta * squared_distance {
+            return add_kernel_force(point, &self.center_of_mass, self.count as f64, force);
+        }
+        self.children
+            .iter()
+            .map(|child| child.repulsion(embedding, i, theta, force))
+            .sum()
+    }
+}
+
+// Student-t kernel between a point and weight points at other, adding
+// weight * kernel^2 * (point - other) to force. Returns weight * kernel.
+fn add_kernel_force(point: &[f64], other: &[f64], weight: f64, force: &mut [f64]) -> f64 {
+    let squared_distance: f64 = point
+        .iter()
+        .zip(other)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum();
+    let kernel = 1.0 / (1.0 + squared_distance);
+    for ((f, a), b) in force.iter_mut().zip(point).zip(other) {
+        *f += weight * kernel * kernel * (a - b);
+    }
+    weight * kernel
+}
@@ -0,0 +1,445 @@
+use super::Init;
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator};
+use crate::ml::builder::builder;
+use crate::ml::decomposition::pca::Pca;
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use crate::ml::neighbors::{build_index, Algorithm, NeighborSearch};
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::utils::seeded_rng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+const N_NEIGHBORS: usize = 15;
+const MIN_DIST: f64 = 0.1;
+const SPREAD: f64 = 1.0;
+const LEARNING_RATE: f64 = 1.0;
+const NEGATIVE_SAMPLE_RATE: usize = 5;
+// Epochs when n_epochs is None: small datasets can afford more
+const SMALL_DATASET: usize = 10_000;
+const SMALL_EPOCHS: usize = 500;
+const LARGE_EPOCHS: usize = 200;
+// Half-width of the starting layout
+const INIT_RANGE: f64 = 10.0;
+// Cap on every coordinate step, keeps early epochs from flinging points
+const GRADIENT_CLIP: f64 = 4.0;
+// Binary search for each point's kernel bandwidth
+const BANDWIDTH_TOLERANCE: f64 = 1e-5;
+const BANDWIDTH_STEPS: usize = 64;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Uniform manifold approximation and projection (McInnes et al. 2018): a
+// fuzzy k-nearest-neighbor graph of the data, laid out in low dimensions by
+// stochastic gradient descent with negative sampling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Umap<M: Metric = Euclidean> {
+    pub n_components: usize,
+    // Size of the local neighborhood. Larger values favor global structure.
+    pub n_neighbors: usize,
+    // Closest distance between embedded points. Smaller values pack
+    // clusters tighter.
+    pub min_dist: f64,
+    // Scale of the embedded distances, together with min_dist
+    pub spread: f64,
+    // Chosen from the dataset size when None
+    pub n_epochs: Option<usize>,
+    pub learning_rate: f64,
+    // Repulsive samples per attractive edge update
+    pub negative_sample_rate: usize,
+    pub init: Init,
+    pub metric: M,
+    pub random_state: Option<u64>,
+
+    // Current state
+    embedding: Vec<Point>,
+}
+
+// Weighted edge of the fuzzy neighbor graph, stored once per direction
+struct Edge {
+    head: usize,
+    tail: usize,
+    weight: f64,
+}
+
+impl<M: Metric> fmt::Display for Umap<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Umap{{ n_components: {}, n_neighbors: {}, min_dist: {}, metric: {:?} }}",
+            self.n_components, self.n_neighbors, self.min_dist, self.metric
+        )
+    }
+}
+
+impl Default for Umap {
+    fn default() -> Self {
+        Umap::new(2)
+    }
+}
+
+builder!(Umap => UmapBuilder, default, {
+    n_components: usize,
+    n_neighbors: usize,
+    min_dist: f64,
+    spread: f64,
+    learning_rate: f64,
+    negative_sample_rate: usize,
+    init: Init,
+}, some {
+    n_epochs: usize,
+    random_state: u64,
+});
+
+impl Umap {
+    pub fn new(n_components: usize) -> Self {
+        Umap::with_metric(n_components, Euclidean)
+    }
+}
+
+impl<M: Metric> Umap<M> {
+    pub fn with_metric(n_components: usize, metric: M) -> Self {
+        Umap {
+            n_components,
+            n_neighbors: N_NEIGHBORS,
+            min_dist: MIN_DIST,
+            spread: SPREAD,
+            n_epochs: None,
+            learning_rate: LEARNING_RATE,
+            negative_sample_rate: NEGATIVE_SAMPLE_RATE,
+            init: Init::Pca,
+            metric,
+            random_state: None,
+            embedding: vec![],
+        }
+    }
+
+    pub fn with_n_neighbors(mut self, n_neighbors: usize) -> Self {
+        self.n_neighbors = n_neighbors;
+        self
+    }
+
+    pub fn with_min_dist(mut self, min_dist: f64) -> Self {
+        self.min_dist = min_dist;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn embedding(&self) -> &[Point] {
+        &self.embedding
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        self.fit_notified(data, &mut Notifier::none());
+    }
+
+    fn fit_notified(&mut self, data: &Matrix, notifier: &mut Notifier) {
+        self.embedding.clear();
+        let n = data.n_rows();
+        if n == 0 || self.n_components == 0 {
+            return;
+        }
+        let mut rng = seeded_rng(self.random_state);
+        self.embedding = self.initial_embedding(data, &mut rng);
+        if n == 1 {
+            return;
+        }
+
+        let edges = self.fuzzy_graph(data);
+        let n_epochs = self.n_epochs.unwrap_or(if n <= SMALL_DATASET {
+            SMALL_EPOCHS
+        } else {
+            LARGE_EPOCHS
+        });
+        self.optimize_layout(&edges, n_epochs, &mut rng, notifier);
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.embedding.clone()
+    }
+
+    // Symmetrized fuzzy simplicial set: directed memberships from each
+    // point's neighbors, combined by fuzzy union w_ij + w_ji - w_ij * w_ji
+    fn fuzzy_graph(&self, data: &Matrix) -> Vec<Edge> {
+        let n = data.n_rows();
+        let k = self.n_neighbors.clamp(1, n - 1);
+        let index = build_index(data.clone(), self.metric.clone(), Algorithm::Auto);
+
+        let mut memberships: HashMap<(usize, usize), f64> = HashMap::new();
+        for (i, point) in data.iter_rows().enumerate() {
+            let neighbors: Vec<(usize, f64)> = index
+                .query(point, k + 1)
+                .into_iter()
+                .filter(|neighbor| neighbor.index != i)
+                .take(k)
+                .map(|neighbor| (neighbor.index, neighbor.distance))
+                .collect();
+            for (j, weight) in local_memberships(&neighbors, k) {
+                memberships.insert((i, j), weight);
+            }
+        }
+
+        let mut edges = vec![];
+        for (&(i, j), &forward) in &memberships {
+            let backward = memberships.get(&(j, i)).copied().unwrap_or(0.0);
+            let weight = forward + backward - forward * backward;
+            edges.push(Edge {
+                head: i,
+                tail: j,
+                weight,
+            });
+            // Edges only one side knows about still go both ways
+            if backward == 0.0 {
+                edges.push(Edge {
+                    head: j,
+                    tail: i,
+                    weight,
+                });
+            }
+        }
+        // HashMap order is random, keep the layout reproducible
+        edges.sort_by_key(|edge| (edge.head, edge.tail));
+        edges
+    }
+
+    fn initial_embedding(&self, data: &Matrix, rng: &mut impl Rng) -> Vec<Point> {
+        let dim = self.n_components;
+        match self.init {
+            Init::Pca if data.n_rows() > 1 && data.n_cols() >= dim => {
+                let mut pca = Pca::new(dim);
+                pca.fit_matrix(data);
+                let projected = pca.transform_matrix(data);
+                let largest = projected
+                    .as_slice()
+                    .iter()
+                    .fold(0.0, |max: f64, v| max.max(v.abs()))
+                    .max(f64::MIN_POSITIVE);
+                // Jitter keeps points that project to the same spot apart
+                projected
+                    .iter_rows()
+                    .map(|p| {
+                        p.iter()
+                            .map(|v| v / largest * INIT_RANGE + rng.gen_range(-1e-4..1e-4))
+                            .collect()
+                    })
+                    .collect()
+            }
+            _ => (0..data.n_rows())
+                .map(|_| {
+                    (0..dim)
+                        .map(|_| rng.gen_range(-INIT_RANGE..INIT_RANGE))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    // SGD on the fuzzy cross-entropy: every edge is sampled in proportion
+    // to its weight and pulls its ends together, and each pull is followed
+    // by pushes away from randomly chosen points
+    fn optimize_layout(
+        &mut self,
+        edges: &[Edge],
+        n_epochs: usize,
+        rng: &mut impl Rng,
+        notifier: &mut Notifier,
+    ) {
+        let (a, b) = fit_curve(self.min_dist, self.spread);
+        let n = self.embedding.len();
+        let max_weight = edges.iter().map(|e| e.weight).fold(0.0, f64::max);
+        if max_weight <= 0.0 || n_epochs == 0 {
+            return;
+        }
+
+        // Edges too weak to be sampled even once are dropped
+        let edges: Vec<&Edge> = edges
+            .iter()
+            .filter(|e| e.weight >= max_weight / n_epochs as f64)
+            .collect();
+        let epochs_per_sample: Vec<f64> = edges.iter().map(|e| max_weight / e.weight).collect();
+        let epochs_per_negative: Vec<f64> = epochs_per_sample
+            .iter()
+            .map(|eps| eps / self.negative_sample_rate.max(1) as f64)
+            .collect();
+        let mut next_sample = epochs_per_sample.clone();
+        let mut next_negative = epochs_per_negative.clone();
+
+        for epoch in 0..n_epochs {
+            let alpha = self.learning_rate * (1.0 - epoch as f64 / n_epochs as f64);
+            let now = epoch as f64;
+            let mut n_sampled = 0;
+            for (e, edge) in edges.iter().enumerate() {
+                if next_sample[e] > now {
+                    continue;
+                }
+                n_sampled += 1;
+                let (head, tail) = (edge.head, edge.tail);
+
+                let diff = difference(&self.embedding[head], &self.embedding[tail]);
+                let squared = diff.iter().map(|d| d * d).sum::<f64>();
+                let coefficient = if squared > 0.0 {
+                    -2.0 * a * b * squared.powf(b - 1.0) / (1.0 + a * squared.powf(b))
+                } else {
+                    0.0
+                };
+                for (d, delta) in diff.iter().enumerate() {
+                    let step = (coefficient * delta).clamp(-GRADIENT_CLIP, GRADIENT_CLIP) * alpha;
+                    self.embedding[head][d] += step;
+                    self.embedding[tail][d] -= step;
+                }
+                next_sample[e] += epochs_per_sample[e];
+
+                let n_negative =
+                    ((now - next_negative[e]) / epochs_per_negative[e]).max(0.0) as usize;
+                for _ in 0..n_negative {
+                    let other = rng.gen_range(0..n);
+                    if other == head {
+                        continue;
+                    }
+                    let diff = difference(&self.embedding[head], &self.embedding[other]);
+                    let squared = diff.iter().map(|d| d * d).sum::<f64>();
+                    for (d, delta) in diff.iter().enumerate() {
+                        let step = if squared > 0.0 {
+                            let coefficient =
+                                2.0 * b / ((0.001 + squared) * (1.0 + a * squared.powf(b)));
+                            (coefficient * delta).clamp(-GRADIENT_CLIP, GRADIENT_CLIP)
+                        } else {
+                            GRADIENT_CLIP
+                        };
+                        self.embedding[head][d] += step * alpha;
+                    }
+                }
+                next_negative[e] += n_negative as f64 * epochs_per_negative[e];
+            }
+            notifier.epoch(|| {
+                FitMetrics::new(epoch + 1)
+                    .with("learning_rate", alpha)
+                    .with("edges_sampled", n_sampled as f64)
+            });
+        }
+    }
+}
+
+impl<M: Metric> Estimator for Umap<M> {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        Umap::fit_matrix(self, x);
+    }
+}
+
+// An epoch per pass over the edges due for sampling, reporting the decayed
+// learning rate and how many edges were pulled together
+impl<M: Metric> IterativeEstimator for Umap<M> {
+    fn fit_observed_matrix(&mut self, x: &Matrix, _: &(), observer: &mut dyn FitObserver) {
+        self.fit_notified(x, &mut Notifier::new(observer));
+    }
+}
+
+// Membership of each neighbor in the point's local fuzzy set:
+// exp(-(d - rho) / sigma), where rho is the distance to the nearest neighbor
+// and sigma is chosen so the memberships sum to log2(k)
+fn local_memberships(neighbors: &[(usize, f64)], k: usize) -> Vec<(usize, f64)> {
+    let target = (k as f64).log2();
+    let rho = neighbors
+        .iter()
+        .map(|n| n.1)
+        .find(|&d| d > 0.0)
+        .unwrap_or(0.0);
+    let total = |sigma: f64| -> f64 {
+        neighbors
+            .iter()
+            .map(|&(_, d)| (-(d - rho).max(0.0) / sigma).exp())
+            .sum()
+    };
+
+    let (mut sigma, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+    for _ in 0..BANDWIDTH_STEPS {
+        let error = total(sigma) - target;
+        if error.abs() < BANDWIDTH_TOLERANCE {
+            break;
+        }
+        // Memberships grow with sigma
+        if error > 0.0 {
+            high = sigma;
+            sigma = (low + high) / 2.0;
+        } else {
+            low = sigma;
+            sigma = if high.is_finite() {
+                (low + high) / 2.0
+            } else {
+                sigma * 2.0
+            };
+        }
+    }
+
+    neighbors
+        .iter()
+        .map(|&(j, d)| (j, (-(d - rho).max(0.0) / sigma).exp()))
+        .collect()
+}
+
+// Fit 1 / (1 + a * x^(2b)) to the target membership curve, which is 1 up to
+// min_dist and decays exponentially with the given spread after it, by
+// Gauss-Newton on a grid of distances
+fn fit_curve(min_dist: f64, spread: f64) -> (f64, f64) {
+    let xs: Vec<f64> = (1..=300).map(|i| i as f64 * 3.0 * spread / 300.0).collect();
+    let ys: Vec<f64> = xs
+        .iter()
+        .map(|&x| {
+            if x < min_dist {
+                1.0
+            } else {
+                (-(x - min_dist) / spread).exp()
+            }
+        })
+        .collect();
+
+    let (mut a, mut b): (f64, f64) = (1.8, 0.8);
+    for _ in 0..100 {
+        // Normal equations of the linearized residuals
+        let (mut jtj, mut jtr) = ([[0.0; 2]; 2], [0.0; 2]);
+        for (&x, &y) in xs.iter().zip(&ys) {
+            let power = x.powf(2.0 * b);
+            let denominator = 1.0 + a * power;
+            let residual = 1.0 / denominator - y;
+            let d_a = -power / (denominator * denominator);
+            let d_b = -2.0 * a * power * x.ln() / (denominator * denominator);
+            let jacobian = [d_a, d_b];
+            for r in 0..2 {
+                jtr[r] += jacobian[r] * residual;
+                for c in 0..2 {
+                    jtj[r][c] += jacobian[r] * jacobian[c];
+                }
+            }
+        }
+        let det = jtj[0][0] * jtj[1][1] - jtj[0][1] * jtj[1][0];
+        if det.abs() < f64::MIN_POSITIVE {
+            break;
+        }
+        let step_a = (jtj[1][1] * jtr[0] - jtj[0][1] * jtr[1]) / det;
+        let step_b = (jtj[0][0] * jtr[1] - jtj[1][0] * jtr[0]) / det;
+        a = (a - step_a).max(1e-3);
+        b = (b - step_b).max(1e-3);
+        if step_a.abs() < 1e-10 && step_b.abs() < 1e-10 {
+            break;
+        }
+    }
+    (a, b)
+}
+
+fn difference(a: &[f64], b: &[f64]) -> Vec<f64> {
+    a.iter().zip(b).map(|(x, y)| x - y).collect()
+}
@@ -0,0 +1,310 @@
+use serde::Serialize;
+
+// How per-class precision, recall and F1 are combined into one number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Average {
+    // Unweighted mean over classes, so rare classes count as much as common
+    // ones
+    Macro,
+    // Computed from the counts pooled over classes. Equals accuracy when
+    // every point has one label.
+    Micro,
+    // Mean over classes weighted by their support
+    Weighted,
+}
+
+// Counts of every (true class, predicted class) pair
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfusionMatrix {
+    // counts[true class][predicted class]
+    pub counts: Vec<Vec<usize>>,
+}
+
+// One cell of a confusion matrix, the long format heatmaps are drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConfusionCell {
+    pub actual: usize,
+    pub predicted: usize,
+    pub count: usize,
+    // Share of the actual class predicted as this class
+    pub rate: f64,
+}
+
+// Scores of one class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ClassScores {
+    pub class: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    // Points of this class in y_true
+    pub support: usize,
+}
+
+// Point of a ROC curve: the rates when everything scoring at least threshold
+// is predicted as class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RocPoint {
+    pub class: usize,
+    pub threshold: f64,
+    pub false_positive_rate: f64,
+    pub true_positive_rate: f64,
+}
+
+// Point of a precision-recall curve, like RocPoint
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PrPoint {
+    pub class: usize,
+    pub threshold: f64,
+    pub recall: f64,
+    pub precision: f64,
+}
+
+impl ConfusionMatrix {
+    // Classes are 0..=the largest label in either input
+    pub fn new(y_true: &[usize], y_pred: &[usize]) -> Self {
+        let n_classes = y_true.iter().chain(y_pred).max().map_or(0, |&m| m + 1);
+        let mut counts = vec![vec![0; n_classes]; n_classes];
+        for (&t, &p) in y_true.iter().zip(y_pred) {
+            counts[t][p] += 1;
+        }
+        ConfusionMatrix { counts }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.counts.len()
+    }
+
+    // Rows divided by their sums, so each row is the distribution of
+    // predictions for one actual class
+    pub fn normalized(&self) -> Vec<Vec<f64>> {
+        self.counts
+            .iter()
+            .map(|row| {
+                let total = row.iter().sum::<usize>().max(1) as f64;
+                row.iter().map(|&c| c as f64 / total).collect()
+            })
+            .collect()
+    }
+
+    // Every cell, row by row
+    pub fn cells(&self) -> Vec<ConfusionCell> {
+        self.counts
+            .iter()
+            .zip(self.normalized())
+            .enumerate()
+            .flat_map(|(actual, (row, rates))| {
+                row.iter()
+                    .zip(rates)
+                    .enumerate()
+                    .map(move |(predicted, (&count, rate))| ConfusionCell {
+                        actual,
+                        predicted,
+                        count,
+                        rate,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        let total: usize = self.counts.iter().flatten().sum();
+        ratio(self.true_positives().iter().sum(), total)
+    }
+
+    // Precision, recall, F1 and support of every class. Scores with a zero
+    // denominator are 0.
+    pub fn class_scores(&self) -> Vec<ClassScores> {
+        let true_positives = self.true_positives();
+        let predicted = self.predicted_counts();
+        true_positives
+            .iter()
+            .zip(&predicted)
+            .zip(&self.counts)
+            .enumerate()
+            .map(|(class, ((&tp, &predicted), row))| {
+                let support = row.iter().sum();
+                let precision = ratio(tp, predicted);
+                let recall = ratio(tp, support);
+                ClassScores {
+                    class,
+                    precision,
+                    recall,
+                    f1: f1(precision, recall),
+                    support,
+                }
+            })
+            .collect()
+    }
+
+    // Precision, recall and F1 combined over classes
+    pub fn averaged(&self, average: Average) -> (f64, f64, f64) {
+        let scores = self.class_scores();
+        match average {
+            Average::Micro => {
+                let tp: usize = self.true_positives().iter().sum();
+                let total: usize = self.counts.iter().flatten().sum();
+                // Every false positive of one class is a false negative of
+                // another, so pooled precision and recall coincide
+                let score = ratio(tp, total);
+                (score, score, score)
+            }
+            Average::Macro => {
+                let n = scores.len().max(1) as f64;
+                let mean = |pick: fn(&ClassScores) -> f64| scores.iter().map(pick).sum::<f64>() / n;
+                (mean(|s| s.precision), mean(|s| s.recall), mean(|s| s.f1))
+            }
+            Average::Weighted => {
+                let total = scores.iter().map(|s| s.support).sum::<usize>().max(1) as f64;
+                let mean = |pick: fn(&ClassScores) -> f64| {
+                    scores
+                        .iter()
+                        .map(|s| pick(s) * s.support as f64)
+                        .sum::<f64>()
+                        / total
+                };
+                (mean(|s| s.precision), mean(|s| s.recall), mean(|s| s.f1))
+            }
+        }
+    }
+
+    fn true_positives(&self) -> Vec<usize> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[i])
+            .collect()
+    }
+
+    // Column sums
+    fn predicted_counts(&self) -> Vec<usize> {
+        (0..self.n_classes())
+            .map(|j| self.counts.iter().map(|row| row[j]).sum())
+            .collect()
+    }
+}
+
+// Share of points whose predicted label is correct
+pub fn accuracy_score(y_true: &[usize], y_pred: &[usize]) -> f64 {
+    let correct = y_true.iter().zip(y_pred).filter(|(t, p)| t == p).count();
+    ratio(correct, y_true.len())
+}
+
+// Of the points predicted as a class, the share that belong to it
+pub fn precision_score(y_true: &[usize], y_pred: &[usize], average: Average) -> f64 {
+    ConfusionMatrix::new(y_true, y_pred).averaged(average).0
+}
+
+// Of the points of a class, the share predicted as it
+pub fn recall_score(y_true: &[usize], y_pred: &[usize], average: Average) -> f64 {
+    ConfusionMatrix::new(y_true, y_pred).averaged(average).1
+}
+
+// Harmonic mean of precision and recall
+pub fn f1_score(y_true: &[usize], y_pred: &[usize], average: Average) -> f64 {
+    ConfusionMatrix::new(y_true, y_pred).averaged(average).2
+}
+
+// Precision, recall, F1 and support of every class
+pub fn classification_report(y_true: &[usize], y_pred: &[usize]) -> Vec<ClassScores> {
+    ConfusionMatrix::new(y_true, y_pred).class_scores()
+}
+
+// ROC curve of one class against the rest, given the score of that class
+// for every point (e.g. a column of predict_proba). Starts at (0, 0) with an
+// infinite threshold and adds a point per distinct score, highest first.
+pub fn roc_curve(y_true: &[usize], scores: &[f64], class: usize) -> Vec<RocPoint> {
+    let counts = threshold_counts(y_true, scores, class);
+    let (positives, negatives) = counts.last().map_or((0, 0), |&(_, tp, fp)| (tp, fp));
+    std::iter::once((f64::INFINITY, 0, 0))
+        .chain(counts)
+        .map(|(threshold, tp, fp)| RocPoint {
+            class,
+            threshold,
+            false_positive_rate: ratio(fp, negatives),
+            true_positive_rate: ratio(tp, positives),
+        })
+        .collect()
+}
+
+// Precision-recall curve of one class against the rest, by decreasing
+// threshold. Starts at recall 0 with precision 1.
+pub fn precision_recall_curve(y_true: &[usize], scores: &[f64], class: usize) -> Vec<PrPoint> {
+    let counts = threshold_counts(y_true, scores, class);
+    let positives = counts.last().map_or(0, |&(_, tp, _)| tp);
+    let start = PrPoint {
+        class,
+        threshold: f64::INFINITY,
+        recall: 0.0,
+        precision: 1.0,
+    };
+    std::iter::once(start)
+        .chain(counts.into_iter().map(|(threshold, tp, fp)| PrPoint {
+            class,
+            threshold,
+            recall: ratio(tp, positives),
+            precision: ratio(tp, tp + fp),
+        }))
+        .collect()
+}
+
+// Area under a curve by the trapezoidal rule, points in order of x
+pub fn auc(x: &[f64], y: &[f64]) -> f64 {
+    x.windows(2)
+        .zip(y.windows(2))
+        .map(|(x, y)| (x[1] - x[0]) * (y[0] + y[1]) / 2.0)
+        .sum::<f64>()
+        .abs()
+}
+
+// Area under the ROC curve: the chance a random point of class scores
+// higher than a random point of another class
+pub fn roc_auc_score(y_true: &[usize], scores: &[f64], class: usize) -> f64 {
+    let curve = roc_curve(y_true, scores, class);
+    let fpr: Vec<f64> = curve.iter().map(|p| p.false_positive_rate).collect();
+    let tpr: Vec<f64> = curve.iter().map(|p| p.true_positive_rate).collect();
+    auc(&fpr, &tpr)
+}
+
+// Mean precision over thresholds, weighted by the recall gained at each.
+// Unlike the trapezoidal area it does not interpolate optimistically.
+pub fn average_precision_score(y_true: &[usize], scores: &[f64], class: usize) -> f64 {
+    precision_recall_curve(y_true, scores, class)
+        .windows(2)
+        .map(|w| (w[1].recall - w[0].recall) * w[1].precision)
+        .sum()
+}
+
+// Cumulative (threshold, true positives, false positives) at every distinct
+// score, highest first
+fn threshold_counts(y_true: &[usize], scores: &[f64], class: usize) -> Vec<(f64, usize, usize)> {
+    let mut order: Vec<usize> = (0..scores.len().min(y_true.len())).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    let (mut tp, mut fp) = (0, 0);
+    order
+        .chunk_by(|&a, &b| scores[a] == scores[b])
+        .map(|tied| {
+            let hits = tied.iter().filter(|&&i| y_true[i] == class).count();
+            tp += hits;
+            fp += tied.len() - hits;
+            (scores[tied[0]], tp, fp)
+        })
+        .collect()
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+fn f1(precision: f64, recall: f64) -> f64 {
+    if precision + recall == 0.0 {
+        0.0
+    } else {
+        2.0 * precision * recall / (precision + recall)
+    }
+}
@@ -0,0 +1,210 @@
+use crate::ml::metrics::distance::{Euclidean, Metric};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Silhouette coefficient of every point: (b - a) / max(a, b), where a is the
+// mean distance to the rest of its own cluster and b the mean distance to the
+// nearest other cluster. Points in singleton clusters score 0.
+pub fn silhouette_samples(data: &[Point], labels: &[usize]) -> Vec<f64> {
+    let n_clusters = labels.iter().max().map_or(0, |&m| m + 1);
+    let mut sizes = vec![0usize; n_clusters];
+    labels.iter().for_each(|&l| sizes[l] += 1);
+
+    data.iter()
+        .zip(labels)
+        .map(|(point, &own)| {
+            if sizes[own] <= 1 {
+                return 0.0;
+            }
+            let mut sums = vec![0.0; n_clusters];
+            for (other, &label) in data.iter().zip(labels) {
+                sums[label] += Euclidean.distance(point, other);
+            }
+            let a = sums[own] / (sizes[own] - 1) as f64;
+            let b = (0..n_clusters)
+                .filter(|&c| c != own && sizes[c] > 0)
+                .map(|c| sums[c] / sizes[c] as f64)
+                .fold(f64::INFINITY, f64::min);
+            if !b.is_finite() {
+                return 0.0;
+            }
+            (b - a) / a.max(b)
+        })
+        .collect()
+}
+
+// Mean silhouette coefficient, None unless there are between 2 and
+// n_samples - 1 clusters
+pub fn silhouette_score(data: &[Point], labels: &[usize]) -> Option<f64> {
+    let mut distinct = labels.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() < 2 || distinct.len() >= data.len() {
+        return None;
+    }
+    let samples = silhouette_samples(data, labels);
+    Some(samples.iter().sum::<f64>() / samples.len() as f64)
+}
+
+// Mean over clusters of the worst ratio of within-cluster scatter to
+// between-centroid separation. Lower is better, 0 is the minimum.
+pub fn davies_bouldin_score(data: &[Point], labels: &[usize]) -> Option<f64> {
+    let (centroids, sizes) = cluster_centroids(data, labels);
+    let clusters: Vec<usize> = (0..sizes.len()).filter(|&c| sizes[c] > 0).collect();
+    if clusters.len() < 2 {
+        return None;
+    }
+
+    let mut scatter = vec![0.0; sizes.len()];
+    for (point, &label) in data.iter().zip(labels) {
+        scatter[label] += Euclidean.distance(point, &centroids[label]);
+    }
+    for &c in &clusters {
+        scatter[c] /= sizes[c] as f64;
+    }
+
+    let total: f64 = clusters
+        .iter()
+        .map(|&i| {
+            clusters
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| {
+                    let separation = Euclidean.distance(&centroids[i], &centroids[j]);
+                    if separation == 0.0 {
+                        return 0.0;
+                    }
+                    (scatter[i] + scatter[j]) / separation
+                })
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    Some(total / clusters.len() as f64)
+}
+
+// Ratio of between-cluster to within-cluster dispersion, each normalized by
+// its degrees of freedom. Higher is better.
+pub fn calinski_harabasz_score(data: &[Point], labels: &[usize]) -> Option<f64> {
+    let (centroids, sizes) = cluster_centroids(data, labels);
+    let k = sizes.iter().filter(|&&s| s > 0).count();
+    let n = data.len();
+    if k < 2 || k >= n {
+        return None;
+    }
+
+    let dim = data[0].len();
+    let mut mean = vec![0.0; dim];
+    for point in data {
+        for (m, x) in mean.iter_mut().zip(point) {
+            *m += x / n as f64;
+        }
+    }
+
+    let between: f64 = centroids
+        .iter()
+        .zip(&sizes)
+        .map(|(centroid, &size)| size as f64 * Euclidean.distance(centroid, &mean).powi(2))
+        .sum();
+    let within: f64 = data
+        .iter()
+        .zip(labels)
+        .map(|(point, &label)| Euclidean.distance(point, &centroids[label]).powi(2))
+        .sum();
+    if within == 0.0 {
+        return Some(1.0);
+    }
+    Some(between * (n - k) as f64 / (within * (k - 1) as f64))
+}
+
+// Rand index corrected for chance: 1 for identical partitions, around 0 for
+// random labelings. Label values don't need to match between the two.
+pub fn adjusted_rand_score(labels_true: &[usize], labels_pred: &[usize]) -> f64 {
+    let table = contingency(labels_true, labels_pred);
+    let pairs = |count: usize| (count * count.saturating_sub(1)) as f64 / 2.0;
+
+    let sum_cells: f64 = table.iter().flatten().map(|&c| pairs(c)).sum();
+    let sum_rows: f64 = table.iter().map(|row| pairs(row.iter().sum())).sum();
+    let sum_cols: f64 = (0..table.first().map_or(0, |row| row.len()))
+        .map(|j| pairs(table.iter().map(|row| row[j]).sum()))
+        .sum();
+
+    let expected = sum_rows * sum_cols / pairs(labels_true.len()).max(1.0);
+    let max_index = (sum_rows + sum_cols) / 2.0;
+    if max_index == expected {
+        // Both partitions are trivial (all one cluster or all singletons)
+        return 1.0;
+    }
+    (sum_cells - expected) / (max_index - expected)
+}
+
+// Mutual information normalized by the arithmetic mean of the two label
+// entropies, in [0, 1]
+pub fn normalized_mutual_info_score(labels_true: &[usize], labels_pred: &[usize]) -> f64 {
+    let n = labels_true.len() as f64;
+    if n == 0.0 {
+        return 1.0;
+    }
+    let table = contingency(labels_true, labels_pred);
+    let rows: Vec<f64> = table
+        .iter()
+        .map(|row| row.iter().sum::<usize>() as f64)
+        .collect();
+    let cols: Vec<f64> = (0..table.first().map_or(0, |row| row.len()))
+        .map(|j| table.iter().map(|row| row[j]).sum::<usize>() as f64)
+        .collect();
+
+    let entropy = |counts: &[f64]| -> f64 {
+        counts
+            .iter()
+            .filter(|&&c| c > 0.0)
+            .map(|&c| -(c / n) * (c / n).ln())
+            .sum()
+    };
+    let (h_true, h_pred) = (entropy(&rows), entropy(&cols));
+    if h_true == 0.0 && h_pred == 0.0 {
+        return 1.0;
+    }
+
+    let mut mutual_info = 0.0;
+    for (i, row) in table.iter().enumerate() {
+        for (j, &count) in row.iter().enumerate() {
+            if count > 0 {
+                let c = count as f64;
+                mutual_info += (c / n) * (n * c / (rows[i] * cols[j])).ln();
+            }
+        }
+    }
+    (mutual_info / ((h_true + h_pred) / 2.0)).clamp(0.0, 1.0)
+}
+
+// Mean of each cluster's points, and the cluster sizes
+fn cluster_centroids(data: &[Point], labels: &[usize]) -> (Vec<Point>, Vec<usize>) {
+    let n_clusters = labels.iter().max().map_or(0, |&m| m + 1);
+    let dim = data.first().map_or(0, |p| p.len());
+    let mut centroids = vec![vec![0.0; dim]; n_clusters];
+    let mut sizes = vec![0usize; n_clusters];
+    for (point, &label) in data.iter().zip(labels) {
+        sizes[label] += 1;
+        for (c, x) in centroids[label].iter_mut().zip(point) {
+            *c += x;
+        }
+    }
+    for (centroid, &size) in centroids.iter_mut().zip(&sizes) {
+        if size > 0 {
+            centroid.iter_mut().for_each(|c| *c /= size as f64);
+        }
+    }
+    (centroids, sizes)
+}
+
+// Counts of points per (true label, predicted label) pair
+fn contingency(labels_true: &[usize], labels_pred: &[usize]) -> Vec<Vec<usize>> {
+    let rows = labels_true.iter().max().map_or(0, |&m| m + 1);
+    let cols = labels_pred.iter().max().map_or(0, |&m| m + 1);
+    let mut table = vec![vec![0usize; cols]; rows];
+    for (&t, &p) in labels_true.iter().zip(labels_pred) {
+        table[t][p] += 1;
+    }
+    table
+}
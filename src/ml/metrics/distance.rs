@@ -0,0 +1,111 @@
+use crate::math::float::Float;
+use crate::math::simd;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// A distance function between two points of equal dimension, in any Float
+// type. Send and Sync so that distances can be computed on several threads.
+pub trait Metric: fmt::Debug + Clone + Send + Sync {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T;
+
+    // Whether d(a, c) <= d(a, b) + d(b, c) always holds. Pruning strategies
+    // such as Elkan's KMeans are only exact for true metrics.
+    fn satisfies_triangle_inequality(&self) -> bool {
+        true
+    }
+
+    // Whether d(a, b) >= |a_i - b_i| for every coordinate i, which lets a
+    // kd-tree discard whole half-spaces during a search
+    fn bounded_by_coordinates(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Euclidean;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manhattan;
+
+// 1 - cosine similarity; zero vectors are treated as maximally distant
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cosine;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Chebyshev;
+
+// Generalization of Manhattan (p = 1) and Euclidean (p = 2)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Minkowski(pub f64);
+
+impl Metric for Euclidean {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T {
+        simd::euclidean(a, b)
+    }
+
+    fn bounded_by_coordinates(&self) -> bool {
+        true
+    }
+}
+
+impl Metric for Manhattan {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T {
+        a.iter().zip(b).map(|(&x, &y)| (x - y).abs()).sum()
+    }
+
+    fn bounded_by_coordinates(&self) -> bool {
+        true
+    }
+}
+
+impl Metric for Cosine {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T {
+        let (mut dot, mut norm_a, mut norm_b) = (T::zero(), T::zero(), T::zero());
+        for (&x, &y) in a.iter().zip(b) {
+            dot += x * y;
+            norm_a += x * x;
+            norm_b += y * y;
+        }
+        if norm_a.is_zero() || norm_b.is_zero() {
+            return T::one();
+        }
+        (T::one() - dot / (norm_a.sqrt() * norm_b.sqrt())).max(T::zero())
+    }
+
+    fn satisfies_triangle_inequality(&self) -> bool {
+        false
+    }
+}
+
+impl Metric for Chebyshev {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T {
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| (x - y).abs())
+            .fold(T::zero(), T::max)
+    }
+
+    fn bounded_by_coordinates(&self) -> bool {
+        true
+    }
+}
+
+impl Metric for Minkowski {
+    fn distance<T: Float>(&self, a: &[T], b: &[T]) -> T {
+        let p = T::from_f64(self.0);
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| (x - y).abs().powf(p))
+            .sum::<T>()
+            .powf(p.recip())
+    }
+
+    // Minkowski is only a metric for p >= 1
+    fn satisfies_triangle_inequality(&self) -> bool {
+        self.0 >= 1.0
+    }
+
+    fn bounded_by_coordinates(&self) -> bool {
+        self.0 >= 1.0
+    }
+}
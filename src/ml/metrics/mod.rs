@@ -0,0 +1,4 @@
+pub mod classification;
+pub mod clustering;
+pub mod distance;
+pub mod regression;
@@ -1,4 +1,26 @@
-pub mod clustering;
+pub mod array;
+pub mod base;
+pub mod builder;
 pub mod classification;
+pub mod clustering;
+pub mod decomposition;
+pub mod early_stopping;
+pub mod ensemble;
+pub mod geometry;
+pub mod manifold;
+pub mod metrics;
+pub mod model_selection;
+pub mod neighbors;
+pub mod nn;
+pub mod observer;
+pub mod onnx;
+pub mod optim;
+pub mod params;
+pub mod persist;
+pub mod pipeline;
+pub mod preprocessing;
 pub mod regression;
+pub mod sklearn;
+pub mod tree;
 pub mod utils;
+pub mod validation;
@@ -0,0 +1,102 @@
+use super::split::Split;
+use crate::ml::classification::Classifier;
+use crate::ml::metrics::classification::{
+    accuracy_score, f1_score, precision_score, recall_score, Average,
+};
+use serde::Serialize;
+
+// What models are ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Accuracy,
+    // Macro averages over the classes
+    Precision,
+    Recall,
+    F1,
+}
+
+impl Score {
+    pub const ALL: [Score; 4] = [Score::Accuracy, Score::Precision, Score::Recall, Score::F1];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Score::Accuracy => "accuracy",
+            Score::Precision => "precision",
+            Score::Recall => "recall",
+            Score::F1 => "f1",
+        }
+    }
+}
+
+// Scores of a classifier's predictions for held-out points
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelScores {
+    pub name: String,
+    pub accuracy: f64,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+impl ModelScores {
+    pub fn new(name: &str, y_true: &[usize], y_pred: &[usize]) -> Self {
+        ModelScores {
+            name: name.to_string(),
+            accuracy: accuracy_score(y_true, y_pred),
+            precision: precision_score(y_true, y_pred, Average::Macro),
+            recall: recall_score(y_true, y_pred, Average::Macro),
+            f1: f1_score(y_true, y_pred, Average::Macro),
+        }
+    }
+
+    pub fn get(&self, score: Score) -> f64 {
+        match score {
+            Score::Accuracy => self.accuracy,
+            Score::Precision => self.precision,
+            Score::Recall => self.recall,
+            Score::F1 => self.f1,
+        }
+    }
+}
+
+// A named classifier to compare with others on the same data
+pub struct Candidate {
+    pub name: String,
+    pub model: Box<dyn Classifier>,
+}
+
+impl Candidate {
+    pub fn new(name: &str, model: impl Classifier + 'static) -> Self {
+        Candidate {
+            name: name.to_string(),
+            model: Box::new(model),
+        }
+    }
+}
+
+// Fits every candidate on the training rows of split and scores it on the
+// test rows, in the order of candidates. The candidates are left fitted.
+pub fn compare_classifiers(candidates: &mut [Candidate], split: &Split<usize>) -> Vec<ModelScores> {
+    candidates
+        .iter_mut()
+        .map(|candidate| {
+            candidate.model.fit(&split.x_train, &split.y_train);
+            let predicted = candidate.model.predict(&split.x_test);
+            ModelScores::new(&candidate.name, &split.y_test, &predicted)
+        })
+        .collect()
+}
+
+// Position of the model scoring highest, the first of any ties. None
+// without a model scoring a number.
+pub fn best_model(scores: &[ModelScores], by: Score) -> Option<usize> {
+    scores
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.get(by).is_nan())
+        .fold(None, |best: Option<(usize, f64)>, (i, s)| match best {
+            Some((_, top)) if top >= s.get(by) => best,
+            _ => Some((i, s.get(by))),
+        })
+        .map(|(i, _)| i)
+}
@@ -0,0 +1,3 @@
+pub mod comparison;
+pub mod split;
+pub mod validation;
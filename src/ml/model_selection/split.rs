@@ -0,0 +1,321 @@
+use crate::ml::utils::seeded_rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::fmt;
+
+const TEST_SIZE: f64 = 0.25;
+const N_SPLITS: usize = 5;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Rows of one train/test partition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+// Data and targets divided into a training and a test set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split<T> {
+    pub x_train: Vec<Point>,
+    pub x_test: Vec<Point>,
+    pub y_train: Vec<T>,
+    pub y_test: Vec<T>,
+}
+
+// Holds out a fraction of the rows for testing
+#[derive(Debug, Clone)]
+pub struct TrainTestSplit {
+    // Fraction of the rows in the test set, rounded up
+    pub test_size: f64,
+    // Without shuffling the last rows are the test set
+    pub shuffle: bool,
+    pub random_state: Option<u64>,
+}
+
+// Splits the rows into n_splits consecutive folds; each fold is the test
+// set once while the others train
+#[derive(Debug, Clone)]
+pub struct KFold {
+    pub n_splits: usize,
+    pub shuffle: bool,
+    pub random_state: Option<u64>,
+}
+
+// KFold with every class spread evenly over the folds, so each test set
+// has the class proportions of the whole
+#[derive(Debug, Clone)]
+pub struct StratifiedKFold {
+    pub n_splits: usize,
+    pub shuffle: bool,
+    pub random_state: Option<u64>,
+}
+
+impl fmt::Display for TrainTestSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TrainTestSplit{{ test_size: {}, shuffle: {} }}",
+            self.test_size, self.shuffle
+        )
+    }
+}
+
+impl fmt::Display for KFold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KFold{{ n_splits: {}, shuffle: {} }}",
+            self.n_splits, self.shuffle
+        )
+    }
+}
+
+impl fmt::Display for StratifiedKFold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "StratifiedKFold{{ n_splits: {}, shuffle: {} }}",
+            self.n_splits, self.shuffle
+        )
+    }
+}
+
+impl Default for TrainTestSplit {
+    fn default() -> Self {
+        TrainTestSplit::new(TEST_SIZE)
+    }
+}
+
+impl Default for KFold {
+    fn default() -> Self {
+        KFold::new(N_SPLITS)
+    }
+}
+
+impl Default for StratifiedKFold {
+    fn default() -> Self {
+        StratifiedKFold::new(N_SPLITS)
+    }
+}
+
+impl TrainTestSplit {
+    pub fn new(test_size: f64) -> Self {
+        TrainTestSplit {
+            test_size,
+            shuffle: true,
+            random_state: None,
+        }
+    }
+
+    pub fn with_shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // Train and test rows out of n_samples. With stratify labels, every
+    // class is split in the same proportion.
+    pub fn fold(&self, n_samples: usize, stratify: Option<&[usize]>) -> Fold {
+        let mut rng = seeded_rng(self.random_state);
+        let n_test = ((self.test_size * n_samples as f64).ceil() as usize).min(n_samples);
+        let Some(labels) = stratify else {
+            let order = self.order((0..n_samples).collect(), &mut rng);
+            let (train, test) = order.split_at(n_samples - n_test);
+            return Fold {
+                train: train.to_vec(),
+                test: test.to_vec(),
+            };
+        };
+
+        let classes = class_rows(labels);
+        let sizes: Vec<usize> = classes.iter().map(|rows| rows.len()).collect();
+        let mut fold = Fold {
+            train: vec![],
+            test: vec![],
+        };
+        for (rows, class_test) in classes.into_iter().zip(allocate(&sizes, n_test)) {
+            let rows = self.order(rows, &mut rng);
+            let (train, test) = rows.split_at(rows.len() - class_test);
+            fold.train.extend_from_slice(train);
+            fold.test.extend_from_slice(test);
+        }
+        if self.shuffle {
+            fold.train.shuffle(&mut rng);
+            fold.test.shuffle(&mut rng);
+        }
+        fold
+    }
+
+    pub fn split<T: Clone>(&self, x: &[Point], y: &[T], stratify: Option<&[usize]>) -> Split<T> {
+        let fold = self.fold(x.len(), stratify);
+        Split {
+            x_train: select(x, &fold.train),
+            x_test: select(x, &fold.test),
+            y_train: select(y, &fold.train),
+            y_test: select(y, &fold.test),
+        }
+    }
+
+    fn order(&self, mut rows: Vec<usize>, rng: &mut StdRng) -> Vec<usize> {
+        if self.shuffle {
+            rows.shuffle(rng);
+        }
+        rows
+    }
+}
+
+impl KFold {
+    pub fn new(n_splits: usize) -> Self {
+        KFold {
+            n_splits,
+            shuffle: false,
+            random_state: None,
+        }
+    }
+
+    pub fn with_shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // The first n_samples % n_splits folds get one extra row
+    pub fn split(&self, n_samples: usize) -> Vec<Fold> {
+        let mut rows: Vec<usize> = (0..n_samples).collect();
+        if self.shuffle {
+            rows.shuffle(&mut seeded_rng(self.random_state));
+        }
+        let k = self.n_splits.clamp(1, n_samples.max(1));
+        let mut start = 0;
+        (0..k)
+            .map(|i| {
+                let size = n_samples / k + usize::from(i < n_samples % k);
+                let fold = hold_out(&rows, start..start + size);
+                start += size;
+                fold
+            })
+            .collect()
+    }
+}
+
+impl StratifiedKFold {
+    pub fn new(n_splits: usize) -> Self {
+        StratifiedKFold {
+            n_splits,
+            shuffle: false,
+            random_state: None,
+        }
+    }
+
+    pub fn with_shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // Rows are dealt to the folds class by class, like cards, so fold sizes
+    // differ by at most one and so do the class counts within them
+    pub fn split(&self, labels: &[usize]) -> Vec<Fold> {
+        let mut rng = seeded_rng(self.random_state);
+        let dealt: Vec<usize> = class_rows(labels)
+            .into_iter()
+            .flat_map(|mut rows| {
+                if self.shuffle {
+                    rows.shuffle(&mut rng);
+                }
+                rows
+            })
+            .collect();
+        let k = self.n_splits.clamp(1, labels.len().max(1));
+        (0..k)
+            .map(|i| {
+                let mut test: Vec<usize> = dealt.iter().skip(i).step_by(k).copied().collect();
+                test.sort_unstable();
+                let mut train: Vec<usize> = dealt
+                    .iter()
+                    .enumerate()
+                    .filter(|(position, _)| position % k != i)
+                    .map(|(_, &row)| row)
+                    .collect();
+                train.sort_unstable();
+                Fold { train, test }
+            })
+            .collect()
+    }
+}
+
+// Shuffled and divided with the given fraction held out for testing
+pub fn train_test_split<T: Clone>(
+    x: &[Point],
+    y: &[T],
+    test_size: f64,
+    random_state: Option<u64>,
+) -> Split<T> {
+    let mut splitter = TrainTestSplit::new(test_size);
+    splitter.random_state = random_state;
+    splitter.split(x, y, None)
+}
+
+// Items at the given positions
+pub(crate) fn select<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| items[i].clone()).collect()
+}
+
+// rows[range] as the test set, the rest for training
+fn hold_out(rows: &[usize], range: std::ops::Range<usize>) -> Fold {
+    Fold {
+        train: rows[..range.start]
+            .iter()
+            .chain(&rows[range.end..])
+            .copied()
+            .collect(),
+        test: rows[range].to_vec(),
+    }
+}
+
+// Rows of every label, by label
+fn class_rows(labels: &[usize]) -> Vec<Vec<usize>> {
+    let n_classes = labels.iter().max().map_or(0, |&m| m + 1);
+    let mut rows = vec![vec![]; n_classes];
+    for (i, &label) in labels.iter().enumerate() {
+        rows[label].push(i);
+    }
+    rows
+}
+
+// Split total over groups in proportion to their sizes, giving leftover
+// units to the largest remainders
+fn allocate(sizes: &[usize], total: usize) -> Vec<usize> {
+    let n: usize = sizes.iter().sum();
+    if n == 0 {
+        return vec![0; sizes.len()];
+    }
+    let exact: Vec<f64> = sizes
+        .iter()
+        .map(|&s| s as f64 * total as f64 / n as f64)
+        .collect();
+    let mut shares: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..sizes.len()).collect();
+    by_remainder
+        .sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let missing = total - shares.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(missing) {
+        shares[i] += 1;
+    }
+    shares
+}
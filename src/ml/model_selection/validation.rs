@@ -0,0 +1,152 @@
+use super::split::{select, Fold};
+use crate::ml::base::Estimator;
+use serde::Serialize;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Mean and standard deviation over the folds of the training and test
+// scores at one training size or parameter value, one row of a line chart
+// with error bands
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub train_mean: f64,
+    pub train_std: f64,
+    pub test_mean: f64,
+    pub test_std: f64,
+}
+
+// Score of a fresh copy of the estimator on the test rows of every fold,
+// after fitting it on the training rows. scoring is called with the fitted
+// model and the test data, e.g. |model, x, y| model.score(x, y).
+pub fn cross_val_score<E, T, F>(
+    estimator: &E,
+    x: &[Point],
+    y: &[T],
+    folds: &[Fold],
+    scoring: F,
+) -> Vec<f64>
+where
+    E: Estimator<Target = [T]> + Clone,
+    T: Clone,
+    F: Fn(&E, &[Point], &[T]) -> f64,
+{
+    folds
+        .iter()
+        .map(|fold| {
+            let mut model = estimator.clone();
+            model.fit(&select(x, &fold.train), &select(y, &fold.train));
+            scoring(&model, &select(x, &fold.test), &select(y, &fold.test))
+        })
+        .collect()
+}
+
+// Scores as the training set grows. train_sizes are fractions of each
+// fold's training rows, which are taken from the front, so shuffle the
+// folds for random subsets. x is the number of training rows.
+pub fn learning_curve<E, T, F>(
+    estimator: &E,
+    x: &[Point],
+    y: &[T],
+    folds: &[Fold],
+    train_sizes: &[f64],
+    scoring: F,
+) -> Vec<CurvePoint>
+where
+    E: Estimator<Target = [T]> + Clone,
+    T: Clone,
+    F: Fn(&E, &[Point], &[T]) -> f64,
+{
+    train_sizes
+        .iter()
+        .map(|&size| {
+            let mut n_train = 0;
+            let scores: Vec<(f64, f64)> = folds
+                .iter()
+                .map(|fold| {
+                    n_train = ((size * fold.train.len() as f64).round() as usize)
+                        .clamp(1, fold.train.len().max(1));
+                    let train = &fold.train[..n_train.min(fold.train.len())];
+                    fit_and_score(estimator.clone(), x, y, train, &fold.test, &scoring)
+                })
+                .collect();
+            summarize(n_train as f64, &scores)
+        })
+        .collect()
+}
+
+// Scores across values of one hyperparameter, which set_param applies to
+// a fresh copy of the estimator, e.g. |model, k| model.k = k as usize
+pub fn validation_curve<E, T, P, F>(
+    estimator: &E,
+    x: &[Point],
+    y: &[T],
+    folds: &[Fold],
+    values: &[f64],
+    set_param: P,
+    scoring: F,
+) -> Vec<CurvePoint>
+where
+    E: Estimator<Target = [T]> + Clone,
+    T: Clone,
+    P: Fn(&mut E, f64),
+    F: Fn(&E, &[Point], &[T]) -> f64,
+{
+    values
+        .iter()
+        .map(|&value| {
+            let mut model = estimator.clone();
+            set_param(&mut model, value);
+            let scores: Vec<(f64, f64)> = folds
+                .iter()
+                .map(|fold| fit_and_score(model.clone(), x, y, &fold.train, &fold.test, &scoring))
+                .collect();
+            summarize(value, &scores)
+        })
+        .collect()
+}
+
+// Training and test score of the model fitted on the train rows
+fn fit_and_score<E, T, F>(
+    mut model: E,
+    x: &[Point],
+    y: &[T],
+    train: &[usize],
+    test: &[usize],
+    scoring: &F,
+) -> (f64, f64)
+where
+    E: Estimator<Target = [T]>,
+    T: Clone,
+    F: Fn(&E, &[Point], &[T]) -> f64,
+{
+    let (x_train, y_train) = (select(x, train), select(y, train));
+    model.fit(&x_train, &y_train);
+    (
+        scoring(&model, &x_train, &y_train),
+        scoring(&model, &select(x, test), &select(y, test)),
+    )
+}
+
+fn summarize(x: f64, scores: &[(f64, f64)]) -> CurvePoint {
+    let train: Vec<f64> = scores.iter().map(|s| s.0).collect();
+    let test: Vec<f64> = scores.iter().map(|s| s.1).collect();
+    let (train_mean, train_std) = mean_std(&train);
+    let (test_mean, test_std) = mean_std(&test);
+    CurvePoint {
+        x,
+        train_mean,
+        train_std,
+        test_mean,
+        test_std,
+    }
+}
+
+// Population standard deviation, NaN for no values
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
@@ -0,0 +1,211 @@
+use super::{Neighbor, NeighborSearch};
+use crate::math::float::Float;
+use crate::math::matrix::Matrix;
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const LEAF_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    // Range of `order` covered by this node
+    start: usize,
+    end: usize,
+    // Split axis and value, in f64 whatever the points, and the children
+    // for inner nodes
+    axis: usize,
+    split: f64,
+    children: Option<(usize, usize)>,
+}
+
+// kd-tree: recursively splits the points at the median of their widest
+// coordinate. Searches are exact for metrics that are bounded by coordinate
+// differences (Euclidean, Manhattan, Chebyshev, Minkowski with p >= 1).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdTree<M: Metric = Euclidean, T = f64> {
+    pub metric: M,
+    points: Matrix<T>,
+    // Permutation of point indices; every node owns a contiguous range
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl<M: Metric, T: Float> KdTree<M, T> {
+    pub fn new(points: Matrix<T>, metric: M) -> Self {
+        let mut tree = KdTree {
+            metric,
+            order: (0..points.n_rows()).collect(),
+            points,
+            nodes: vec![],
+        };
+        if !tree.points.is_empty() {
+            tree.build(0, tree.points.n_rows());
+        }
+        tree
+    }
+
+    // The same tree over the points in another float type
+    pub fn cast<U: Float>(self) -> KdTree<M, U> {
+        KdTree {
+            metric: self.metric,
+            points: self.points.cast(),
+            order: self.order,
+            nodes: self.nodes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.n_rows()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    // Returns the id of the node covering order[start..end]
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            start,
+            end,
+            axis: 0,
+            split: 0.0,
+            children: None,
+        });
+        if end - start <= LEAF_SIZE {
+            return id;
+        }
+
+        let dim = self.points.n_cols();
+        let axis = (0..dim)
+            .map(|axis| {
+                let (lo, hi) = self.order[start..end]
+                    .iter()
+                    .map(|&i| self.points.get(i, axis).as_f64())
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                        (lo.min(v), hi.max(v))
+                    });
+                (axis, hi - lo)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(axis, _)| axis);
+        if dim == 0 {
+            return id;
+        }
+
+        let mid = (end - start) / 2;
+        let points = &self.points;
+        self.order[start..end].select_nth_unstable_by(mid, |&a, &b| {
+            points
+                .get(a, axis)
+                .as_f64()
+                .total_cmp(&points.get(b, axis).as_f64())
+        });
+        let split = self.points.get(self.order[start + mid], axis).as_f64();
+
+        let left = self.build(start, start + mid);
+        let right = self.build(start + mid, end);
+        let node = &mut self.nodes[id];
+        node.axis = axis;
+        node.split = split;
+        node.children = Some((left, right));
+        id
+    }
+
+    fn search_k(&self, node: usize, point: &[T], k: usize, heap: &mut BinaryHeap<Candidate>) {
+        let node = &self.nodes[node];
+        let Some((left, right)) = node.children else {
+            for &i in &self.order[node.start..node.end] {
+                let distance = self.metric.distance(point, self.points.row(i)).as_f64();
+                if heap.len() < k {
+                    heap.push(Candidate(Neighbor { index: i, distance }));
+                } else if heap.peek().is_some_and(|worst| distance < worst.0.distance) {
+                    heap.pop();
+                    heap.push(Candidate(Neighbor { index: i, distance }));
+                }
+            }
+            return;
+        };
+
+        let gap = point[node.axis].as_f64() - node.split;
+        let (near, far) = if gap < 0.0 {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        self.search_k(near, point, k, heap);
+        // The far side can't contain anything closer than the plane itself
+        let worst = heap.peek().map_or(f64::INFINITY, |c| c.0.distance);
+        if heap.len() < k || gap.abs() < worst {
+            self.search_k(far, point, k, heap);
+        }
+    }
+
+    fn search_radius(&self, node: usize, point: &[T], radius: f64, found: &mut Vec<Neighbor>) {
+        let node = &self.nodes[node];
+        let Some((left, right)) = node.children else {
+            for &i in &self.order[node.start..node.end] {
+                let distance = self.metric.distance(point, self.points.row(i)).as_f64();
+                if distance <= radius {
+                    found.push(Neighbor { index: i, distance });
+                }
+            }
+            return;
+        };
+
+        let gap = point[node.axis].as_f64() - node.split;
+        if gap < 0.0 || gap.abs() <= radius {
+            self.search_radius(left, point, radius, found);
+        }
+        if gap >= 0.0 || gap.abs() <= radius {
+            self.search_radius(right, point, radius, found);
+        }
+    }
+}
+
+impl<M: Metric, T: Float> NeighborSearch<T> for KdTree<M, T> {
+    fn query(&self, point: &[T], k: usize) -> Vec<Neighbor> {
+        if self.is_empty() || k == 0 {
+            return vec![];
+        }
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.search_k(0, point, k, &mut heap);
+        let mut neighbors: Vec<Neighbor> = heap.into_iter().map(|c| c.0).collect();
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        neighbors
+    }
+
+    fn query_radius(&self, point: &[T], radius: f64) -> Vec<Neighbor> {
+        let mut found = vec![];
+        if !self.is_empty() {
+            self.search_radius(0, point, radius, &mut found);
+        }
+        found.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        found
+    }
+}
+
+// Max-heap entry ordered by distance, so the worst candidate is on top
+struct Candidate(Neighbor);
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.distance == other.0.distance
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.distance.total_cmp(&other.0.distance)
+    }
+}
@@ -0,0 +1,372 @@
+use super::{build_index, Algorithm, Neighbor, NeighborSearch, SearchIndex};
+use crate::compute::parallel::par_map;
+use crate::math::float::Float;
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::classification::{most_probable, Classifier};
+use crate::ml::metrics::classification::accuracy_score;
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use crate::ml::metrics::regression::r2_score;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const N_NEIGHBORS: usize = 5;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weights {
+    // Every neighbor votes equally
+    Uniform,
+    // Neighbors vote with weight 1 / distance
+    Distance,
+}
+
+// Classifies by majority vote among the k nearest training points. The
+// points are f64 unless T says otherwise, see cast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KNeighborsClassifier<M: Metric = Euclidean, T = f64> {
+    pub k: usize,
+    pub weights: Weights,
+    pub metric: M,
+    pub algorithm: Algorithm,
+
+    // Current state
+    index: Option<SearchIndex<M, T>>,
+    labels: Vec<usize>,
+    n_classes: usize,
+}
+
+// Predicts the (weighted) mean target of the k nearest training points,
+// f64 points unless T says otherwise as for the classifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KNeighborsRegressor<M: Metric = Euclidean, T = f64> {
+    pub k: usize,
+    pub weights: Weights,
+    pub metric: M,
+    pub algorithm: Algorithm,
+
+    // Current state
+    index: Option<SearchIndex<M, T>>,
+    targets: Vec<f64>,
+}
+
+impl<M: Metric, T> fmt::Display for KNeighborsClassifier<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KNeighborsClassifier{{ k: {}, weights: {:?}, metric: {:?} }}",
+            self.k, self.weights, self.metric
+        )
+    }
+}
+
+impl<M: Metric, T> fmt::Display for KNeighborsRegressor<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KNeighborsRegressor{{ k: {}, weights: {:?}, metric: {:?} }}",
+            self.k, self.weights, self.metric
+        )
+    }
+}
+
+impl KNeighborsClassifier {
+    pub fn new(k: usize) -> Self {
+        KNeighborsClassifier::with_metric(k, Euclidean)
+    }
+}
+
+impl Default for KNeighborsClassifier {
+    fn default() -> Self {
+        KNeighborsClassifier::new(N_NEIGHBORS)
+    }
+}
+
+builder!(KNeighborsClassifier => KNeighborsClassifierBuilder, default, {
+    k: usize,
+    weights: Weights,
+    algorithm: Algorithm,
+});
+
+impl<M: Metric> KNeighborsClassifier<M> {
+    pub fn with_metric(k: usize, metric: M) -> Self {
+        KNeighborsClassifier {
+            k,
+            weights: Weights::Uniform,
+            metric,
+            algorithm: Algorithm::Auto,
+            index: None,
+            labels: vec![],
+            n_classes: 0,
+        }
+    }
+}
+
+impl<M: Metric, T: Float> KNeighborsClassifier<M, T> {
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    // The model computing in another float type, fitted points included,
+    // e.g. KNeighborsClassifier::new(k).cast::<f32>() to store f32 points
+    pub fn cast<U: Float>(self) -> KNeighborsClassifier<M, U> {
+        KNeighborsClassifier {
+            k: self.k,
+            weights: self.weights,
+            metric: self.metric,
+            algorithm: self.algorithm,
+            index: self.index.map(SearchIndex::cast),
+            labels: self.labels,
+            n_classes: self.n_classes,
+        }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.n_classes
+    }
+
+    // Stores the training set; all work happens at prediction time
+    pub fn fit(&mut self, x: &[Vec<T>], y: &[usize]) {
+        self.fit_owned(point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix<T>, y: &[usize]) {
+        self.fit_owned(x.clone(), y);
+    }
+
+    fn fit_owned(&mut self, x: Matrix<T>, y: &[usize]) {
+        self.index = Some(build_index(x, self.metric.clone(), self.algorithm));
+        self.labels = y.to_vec();
+        self.n_classes = y.iter().max().map_or(0, |&m| m + 1);
+    }
+
+    pub fn predict(&self, x: &[Vec<T>]) -> Vec<usize> {
+        most_probable(&self.predict_proba(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix<T>) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+
+    // Share of the (weighted) neighbor vote each class received
+    pub fn predict_proba(&self, x: &[Vec<T>]) -> Vec<Vec<f64>> {
+        let rows: Vec<&[T]> = x.iter().map(Vec::as_slice).collect();
+        self.vote_shares(&rows)
+    }
+
+    pub fn predict_proba_matrix(&self, x: &Matrix<T>) -> Vec<Vec<f64>> {
+        let rows: Vec<&[T]> = x.iter_rows().collect();
+        self.vote_shares(&rows)
+    }
+
+    fn vote_shares(&self, rows: &[&[T]]) -> Vec<Vec<f64>> {
+        let Some(index) = &self.index else {
+            return vec![vec![]; rows.len()];
+        };
+        par_map(rows, |point| {
+            let neighbors = index.query(point, self.k);
+            let mut votes = vec![0.0; self.n_classes];
+            for (neighbor, weight) in neighbors.iter().zip(vote_weights(&neighbors, self.weights)) {
+                votes[self.labels[neighbor.index]] += weight;
+            }
+            let total: f64 = votes.iter().sum();
+            if total > 0.0 {
+                votes.iter_mut().for_each(|v| *v /= total);
+            }
+            votes
+        })
+    }
+
+    // Mean accuracy on x against y
+    pub fn score(&self, x: &[Vec<T>], y: &[usize]) -> f64 {
+        accuracy_score(y, &self.predict(x))
+    }
+}
+
+impl<M: Metric> Estimator for KNeighborsClassifier<M> {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        KNeighborsClassifier::fit_matrix(self, x, y);
+    }
+
+    fn min_samples(&self) -> usize {
+        self.k
+    }
+}
+
+impl<M: Metric> Predictor for KNeighborsClassifier<M> {
+    type Output = usize;
+
+    fn predict(&self, x: &[Point]) -> Vec<usize> {
+        KNeighborsClassifier::predict(self, x)
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        KNeighborsClassifier::predict_matrix(self, x)
+    }
+}
+
+impl<M: Metric> Classifier for KNeighborsClassifier<M> {
+    fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        KNeighborsClassifier::predict_proba(self, x)
+    }
+
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        KNeighborsClassifier::predict_proba_matrix(self, x)
+    }
+}
+
+impl KNeighborsRegressor {
+    pub fn new(k: usize) -> Self {
+        KNeighborsRegressor::with_metric(k, Euclidean)
+    }
+}
+
+impl Default for KNeighborsRegressor {
+    fn default() -> Self {
+        KNeighborsRegressor::new(N_NEIGHBORS)
+    }
+}
+
+builder!(KNeighborsRegressor => KNeighborsRegressorBuilder, default, {
+    k: usize,
+    weights: Weights,
+    algorithm: Algorithm,
+});
+
+impl<M: Metric> KNeighborsRegressor<M> {
+    pub fn with_metric(k: usize, metric: M) -> Self {
+        KNeighborsRegressor {
+            k,
+            weights: Weights::Uniform,
+            metric,
+            algorithm: Algorithm::Auto,
+            index: None,
+            targets: vec![],
+        }
+    }
+}
+
+impl<M: Metric, T: Float> KNeighborsRegressor<M, T> {
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    // As KNeighborsClassifier::cast
+    pub fn cast<U: Float>(self) -> KNeighborsRegressor<M, U> {
+        KNeighborsRegressor {
+            k: self.k,
+            weights: self.weights,
+            metric: self.metric,
+            algorithm: self.algorithm,
+            index: self.index.map(SearchIndex::cast),
+            targets: self.targets,
+        }
+    }
+
+    // Stores the training set; all work happens at prediction time
+    pub fn fit(&mut self, x: &[Vec<T>], y: &[f64]) {
+        self.fit_owned(point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix<T>, y: &[f64]) {
+        self.fit_owned(x.clone(), y);
+    }
+
+    fn fit_owned(&mut self, x: Matrix<T>, y: &[f64]) {
+        self.index = Some(build_index(x, self.metric.clone(), self.algorithm));
+        self.targets = y.to_vec();
+    }
+
+    pub fn predict(&self, x: &[Vec<T>]) -> Vec<f64> {
+        let rows: Vec<&[T]> = x.iter().map(Vec::as_slice).collect();
+        self.weighted_means(&rows)
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix<T>) -> Vec<f64> {
+        let rows: Vec<&[T]> = x.iter_rows().collect();
+        self.weighted_means(&rows)
+    }
+
+    fn weighted_means(&self, rows: &[&[T]]) -> Vec<f64> {
+        let Some(index) = &self.index else {
+            return vec![0.0; rows.len()];
+        };
+        par_map(rows, |point| {
+            let neighbors = index.query(point, self.k);
+            let weights = vote_weights(&neighbors, self.weights);
+            let total: f64 = weights.iter().sum();
+            if total == 0.0 {
+                return 0.0;
+            }
+            neighbors
+                .iter()
+                .zip(&weights)
+                .map(|(neighbor, weight)| weight * self.targets[neighbor.index])
+                .sum::<f64>()
+                / total
+        })
+    }
+
+    // R² of the predictions for x against y
+    pub fn score(&self, x: &[Vec<T>], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+}
+
+impl<M: Metric> Estimator for KNeighborsRegressor<M> {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        KNeighborsRegressor::fit_matrix(self, x, y);
+    }
+
+    fn min_samples(&self) -> usize {
+        self.k
+    }
+}
+
+impl<M: Metric> Predictor for KNeighborsRegressor<M> {
+    type Output = f64;
+
+    fn predict(&self, x: &[Point]) -> Vec<f64> {
+        KNeighborsRegressor::predict(self, x)
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        KNeighborsRegressor::predict_matrix(self, x)
+    }
+}
+
+// With distance weighting, exact matches (distance 0) take the whole vote
+pub(crate) fn vote_weights(neighbors: &[Neighbor], weights: Weights) -> Vec<f64> {
+    match weights {
+        Weights::Uniform => vec![1.0; neighbors.len()],
+        Weights::Distance => {
+            if neighbors.iter().any(|n| n.distance == 0.0) {
+                neighbors
+                    .iter()
+                    .map(|n| if n.distance == 0.0 { 1.0 } else { 0.0 })
+                    .collect()
+            } else {
+                neighbors.iter().map(|n| 1.0 / n.distance).collect()
+            }
+        }
+    }
+}
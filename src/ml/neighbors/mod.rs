@@ -0,0 +1,147 @@
+pub mod index;
+pub mod knn;
+
+use crate::math::float::Float;
+use crate::math::matrix::Matrix;
+use crate::ml::metrics::distance::{Euclidean, Metric};
+use index::KdTree;
+use serde::{Deserialize, Serialize};
+
+// The distance is f64 whatever the float type of the points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    // Row of the point in the indexed dataset
+    pub index: usize,
+    pub distance: f64,
+}
+
+// Above this many dimensions a kd-tree prunes too little to beat brute force
+const KD_TREE_MAX_DIM: usize = 16;
+
+// Nearest-neighbor lookup over the rows of a fixed matrix, f64 unless T
+// says otherwise
+pub trait NeighborSearch<T = f64> {
+    // The k closest points, nearest first
+    fn query(&self, point: &[T], k: usize) -> Vec<Neighbor>;
+
+    // All points within radius (inclusive), nearest first
+    fn query_radius(&self, point: &[T], radius: f64) -> Vec<Neighbor>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    // kd-tree when the metric supports it and the data is low-dimensional
+    Auto,
+    BruteForce,
+    // Falls back to brute force for metrics a kd-tree can't prune with
+    KdTree,
+}
+
+// A neighbor index chosen at runtime by `build_index`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SearchIndex<M: Metric = Euclidean, T = f64> {
+    BruteForce(BruteForce<M, T>),
+    KdTree(KdTree<M, T>),
+}
+
+impl<M: Metric, T: Float> SearchIndex<M, T> {
+    // The same index over the points in another float type
+    pub fn cast<U: Float>(self) -> SearchIndex<M, U> {
+        match self {
+            SearchIndex::BruteForce(index) => SearchIndex::BruteForce(index.cast()),
+            SearchIndex::KdTree(index) => SearchIndex::KdTree(index.cast()),
+        }
+    }
+}
+
+impl<M: Metric, T: Float> NeighborSearch<T> for SearchIndex<M, T> {
+    fn query(&self, point: &[T], k: usize) -> Vec<Neighbor> {
+        match self {
+            SearchIndex::BruteForce(index) => index.query(point, k),
+            SearchIndex::KdTree(index) => index.query(point, k),
+        }
+    }
+
+    fn query_radius(&self, point: &[T], radius: f64) -> Vec<Neighbor> {
+        match self {
+            SearchIndex::BruteForce(index) => index.query_radius(point, radius),
+            SearchIndex::KdTree(index) => index.query_radius(point, radius),
+        }
+    }
+}
+
+pub fn build_index<M: Metric, T: Float>(
+    points: Matrix<T>,
+    metric: M,
+    algorithm: Algorithm,
+) -> SearchIndex<M, T> {
+    let dim = points.n_cols();
+    let use_tree = metric.bounded_by_coordinates()
+        && match algorithm {
+            Algorithm::Auto => dim <= KD_TREE_MAX_DIM,
+            Algorithm::BruteForce => false,
+            Algorithm::KdTree => true,
+        };
+    if use_tree {
+        SearchIndex::KdTree(KdTree::new(points, metric))
+    } else {
+        SearchIndex::BruteForce(BruteForce::new(points, metric))
+    }
+}
+
+// Compares the query against every stored point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BruteForce<M: Metric = Euclidean, T = f64> {
+    pub metric: M,
+    points: Matrix<T>,
+}
+
+impl<M: Metric, T: Float> BruteForce<M, T> {
+    pub fn new(points: Matrix<T>, metric: M) -> Self {
+        BruteForce { metric, points }
+    }
+
+    pub fn cast<U: Float>(self) -> BruteForce<M, U> {
+        BruteForce {
+            metric: self.metric,
+            points: self.points.cast(),
+        }
+    }
+}
+
+impl<M: Metric, T: Float> NeighborSearch<T> for BruteForce<M, T> {
+    fn query(&self, point: &[T], k: usize) -> Vec<Neighbor> {
+        let mut neighbors: Vec<Neighbor> = self
+            .points
+            .iter_rows()
+            .enumerate()
+            .map(|(index, other)| Neighbor {
+                index,
+                distance: self.metric.distance(point, other).as_f64(),
+            })
+            .collect();
+        let k = k.min(neighbors.len());
+        if k == 0 {
+            return vec![];
+        }
+        neighbors.select_nth_unstable_by(k - 1, |a, b| a.distance.total_cmp(&b.distance));
+        neighbors.truncate(k);
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        neighbors
+    }
+
+    fn query_radius(&self, point: &[T], radius: f64) -> Vec<Neighbor> {
+        let mut neighbors: Vec<Neighbor> = self
+            .points
+            .iter_rows()
+            .enumerate()
+            .map(|(index, other)| Neighbor {
+                index,
+                distance: self.metric.distance(point, other).as_f64(),
+            })
+            .filter(|n| n.distance <= radius)
+            .collect();
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        neighbors
+    }
+}
@@ -0,0 +1,505 @@
+use super::{train, Activation, EpochProgress, Network, Output, Trained, Training};
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::classification::{most_probable, Classifier};
+use crate::ml::early_stopping::EarlyStopping;
+use crate::ml::metrics::regression::r2_score;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::optim::{Adam, Optimizer};
+use crate::ml::utils::seeded_rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const ALPHA: f64 = 1e-4;
+const BATCH_SIZE: usize = 200;
+const MAX_EPOCHS: usize = 200;
+const EPSILON: f64 = 1e-4;
+const N_ITER_NO_CHANGE: usize = 10;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Feed-forward network with a softmax output trained on cross-entropy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MlpClassifier<O: Optimizer = Adam> {
+    // Width of every hidden layer, input to output
+    pub hidden_layer_sizes: Vec<usize>,
+    pub activation: Activation,
+    // Update rule for the weights, carrying its own learning rate
+    pub optimizer: O,
+    // L2 penalty on the weights
+    pub alpha: f64,
+    pub batch_size: usize,
+    pub max_epochs: usize,
+    // Stop once the loss hasn't improved by tolerance for n_iter_no_change
+    // epochs, unless early_stopping is set
+    pub tolerance: f64,
+    pub n_iter_no_change: usize,
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    // Seed for weight initialization and batch order, random when None
+    pub random_state: Option<u64>,
+
+    // Current state
+    network: Option<Network>,
+    n_classes: usize,
+    loss_curve: Vec<f64>,
+    #[serde(default)]
+    validation_loss_curve: Vec<f64>,
+    #[serde(default)]
+    best_iteration: Option<usize>,
+    converged: bool,
+}
+
+// Feed-forward network with a linear output trained on squared error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MlpRegressor<O: Optimizer = Adam> {
+    // Width of every hidden layer, input to output
+    pub hidden_layer_sizes: Vec<usize>,
+    pub activation: Activation,
+    // Update rule for the weights, carrying its own learning rate
+    pub optimizer: O,
+    // L2 penalty on the weights
+    pub alpha: f64,
+    pub batch_size: usize,
+    pub max_epochs: usize,
+    // Stop once the loss hasn't improved by tolerance for n_iter_no_change
+    // epochs, unless early_stopping is set
+    pub tolerance: f64,
+    pub n_iter_no_change: usize,
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    // Seed for weight initialization and batch order, random when None
+    pub random_state: Option<u64>,
+
+    // Current state
+    network: Option<Network>,
+    loss_curve: Vec<f64>,
+    #[serde(default)]
+    validation_loss_curve: Vec<f64>,
+    #[serde(default)]
+    best_iteration: Option<usize>,
+    converged: bool,
+}
+
+impl<O: Optimizer> fmt::Display for MlpClassifier<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MlpClassifier{{ hidden_layer_sizes: {:?}, activation: {:?}, optimizer: {:?} }}",
+            self.hidden_layer_sizes, self.activation, self.optimizer
+        )
+    }
+}
+
+impl<O: Optimizer> fmt::Display for MlpRegressor<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MlpRegressor{{ hidden_layer_sizes: {:?}, activation: {:?}, optimizer: {:?} }}",
+            self.hidden_layer_sizes, self.activation, self.optimizer
+        )
+    }
+}
+
+impl Default for MlpClassifier {
+    fn default() -> Self {
+        MlpClassifier::new(vec![100])
+    }
+}
+
+builder!(MlpClassifier => MlpClassifierBuilder, default, {
+    hidden_layer_sizes: Vec<usize>,
+    activation: Activation,
+    optimizer: Adam,
+    alpha: f64,
+    batch_size: usize,
+    max_epochs: usize,
+    tolerance: f64,
+    n_iter_no_change: usize,
+}, some {
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl Default for MlpRegressor {
+    fn default() -> Self {
+        MlpRegressor::new(vec![100])
+    }
+}
+
+builder!(MlpRegressor => MlpRegressorBuilder, default, {
+    hidden_layer_sizes: Vec<usize>,
+    activation: Activation,
+    optimizer: Adam,
+    alpha: f64,
+    batch_size: usize,
+    max_epochs: usize,
+    tolerance: f64,
+    n_iter_no_change: usize,
+}, some {
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl MlpClassifier {
+    pub fn new(hidden_layer_sizes: Vec<usize>) -> Self {
+        MlpClassifier::with_optimizer(hidden_layer_sizes, Adam::default())
+    }
+}
+
+impl<O: Optimizer> MlpClassifier<O> {
+    pub fn with_optimizer(hidden_layer_sizes: Vec<usize>, optimizer: O) -> Self {
+        MlpClassifier {
+            hidden_layer_sizes,
+            activation: Activation::Relu,
+            optimizer,
+            alpha: ALPHA,
+            batch_size: BATCH_SIZE,
+            max_epochs: MAX_EPOCHS,
+            tolerance: EPSILON,
+            n_iter_no_change: N_ITER_NO_CHANGE,
+            early_stopping: None,
+            random_state: None,
+            network: None,
+            n_classes: 0,
+            loss_curve: vec![],
+            validation_loss_curve: vec![],
+            best_iteration: None,
+            converged: false,
+        }
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.n_classes
+    }
+
+    // Training loss of every epoch in the last fit
+    pub fn loss_curve(&self) -> &[f64] {
+        &self.loss_curve
+    }
+
+    // Loss on the held-out samples of every epoch, empty unless early
+    // stopping monitors the validation loss
+    pub fn validation_loss_curve(&self) -> &[f64] {
+        &self.validation_loss_curve
+    }
+
+    // Epoch with the lowest monitored loss, counted from 1. None unless
+    // early stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.best_iteration
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    // Fit, calling back after every epoch so training curves can be drawn
+    // live
+    pub fn fit_with_callback<F>(&mut self, x: &[Point], y: &[usize], callback: F)
+    where
+        F: FnMut(&EpochProgress),
+    {
+        self.fit_matrix_with_callback(&point_matrix(x), y, callback);
+    }
+
+    pub fn fit_matrix_with_callback<F>(&mut self, x: &Matrix, y: &[usize], callback: F)
+    where
+        F: FnMut(&EpochProgress),
+    {
+        self.n_classes = y.iter().max().map_or(0, |&m| m + 1);
+        let targets: Vec<Vec<f64>> = y
+            .iter()
+            .map(|&label| {
+                let mut one_hot = vec![0.0; self.n_classes];
+                one_hot[label] = 1.0;
+                one_hot
+            })
+            .collect();
+        let sizes = layer_sizes(x, &self.hidden_layer_sizes, self.n_classes);
+        let (network, trained) = fit_network(
+            x,
+            &targets,
+            sizes,
+            self.activation,
+            Output::Softmax,
+            self.training(),
+            callback,
+        );
+        self.network = network;
+        self.loss_curve = trained.loss_curve;
+        self.validation_loss_curve = trained.validation_loss_curve;
+        self.best_iteration = trained.best_epoch;
+        self.converged = trained.converged;
+    }
+
+    fn training(&self) -> Training<O> {
+        Training {
+            optimizer: self.optimizer.clone(),
+            alpha: self.alpha,
+            batch_size: self.batch_size,
+            max_epochs: self.max_epochs,
+            tolerance: self.tolerance,
+            n_iter_no_change: self.n_iter_no_change,
+            early_stopping: self.early_stopping,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl<O: Optimizer> Estimator for MlpClassifier<O> {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        self.fit_matrix_with_callback(x, y, |_| {});
+    }
+}
+
+// An epoch per pass over the data, reporting the loss
+impl<O: Optimizer> IterativeEstimator for MlpClassifier<O> {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[usize], observer: &mut dyn FitObserver) {
+        let mut notifier = Notifier::new(observer);
+        self.fit_matrix_with_callback(x, y, |progress| {
+            notifier.epoch(|| epoch_metrics(progress));
+        });
+        if self.converged {
+            notifier.converged();
+        }
+    }
+}
+
+impl<O: Optimizer> Predictor for MlpClassifier<O> {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+}
+
+impl<O: Optimizer> Classifier for MlpClassifier<O> {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        match &self.network {
+            Some(network) => x.iter_rows().map(|point| network.predict(point)).collect(),
+            None => vec![vec![]; x.n_rows()],
+        }
+    }
+}
+
+impl MlpRegressor {
+    pub fn new(hidden_layer_sizes: Vec<usize>) -> Self {
+        MlpRegressor::with_optimizer(hidden_layer_sizes, Adam::default())
+    }
+}
+
+impl<O: Optimizer> MlpRegressor<O> {
+    pub fn with_optimizer(hidden_layer_sizes: Vec<usize>, optimizer: O) -> Self {
+        MlpRegressor {
+            hidden_layer_sizes,
+            activation: Activation::Relu,
+            optimizer,
+            alpha: ALPHA,
+            batch_size: BATCH_SIZE,
+            max_epochs: MAX_EPOCHS,
+            tolerance: EPSILON,
+            n_iter_no_change: N_ITER_NO_CHANGE,
+            early_stopping: None,
+            random_state: None,
+            network: None,
+            loss_curve: vec![],
+            validation_loss_curve: vec![],
+            best_iteration: None,
+            converged: false,
+        }
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    pub fn with_random_state(mut self, seed: u64) -> Self {
+        self.random_state = Some(seed);
+        self
+    }
+
+    // Training loss of every epoch in the last fit
+    pub fn loss_curve(&self) -> &[f64] {
+        &self.loss_curve
+    }
+
+    // Loss on the held-out samples of every epoch, empty unless early
+    // stopping monitors the validation loss
+    pub fn validation_loss_curve(&self) -> &[f64] {
+        &self.validation_loss_curve
+    }
+
+    // Epoch with the lowest monitored loss, counted from 1. None unless
+    // early stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.best_iteration
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[f64]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        self.fit_matrix_with_callback(x, y, |_| {});
+    }
+
+    // Fit, calling back after every epoch so training curves can be drawn
+    // live
+    pub fn fit_with_callback<F>(&mut self, x: &[Point], y: &[f64], callback: F)
+    where
+        F: FnMut(&EpochProgress),
+    {
+        self.fit_matrix_with_callback(&point_matrix(x), y, callback);
+    }
+
+    pub fn fit_matrix_with_callback<F>(&mut self, x: &Matrix, y: &[f64], callback: F)
+    where
+        F: FnMut(&EpochProgress),
+    {
+        let targets: Vec<Vec<f64>> = y.iter().map(|&value| vec![value]).collect();
+        let sizes = layer_sizes(x, &self.hidden_layer_sizes, 1);
+        let (network, trained) = fit_network(
+            x,
+            &targets,
+            sizes,
+            self.activation,
+            Output::Identity,
+            self.training(),
+            callback,
+        );
+        self.network = network;
+        self.loss_curve = trained.loss_curve;
+        self.validation_loss_curve = trained.validation_loss_curve;
+        self.best_iteration = trained.best_epoch;
+        self.converged = trained.converged;
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<f64> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        match &self.network {
+            Some(network) => x
+                .iter_rows()
+                .map(|point| network.predict(point)[0])
+                .collect(),
+            None => vec![0.0; x.n_rows()],
+        }
+    }
+
+    // R² of the predictions for x against y
+    pub fn score(&self, x: &[Point], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+
+    fn training(&self) -> Training<O> {
+        Training {
+            optimizer: self.optimizer.clone(),
+            alpha: self.alpha,
+            batch_size: self.batch_size,
+            max_epochs: self.max_epochs,
+            tolerance: self.tolerance,
+            n_iter_no_change: self.n_iter_no_change,
+            early_stopping: self.early_stopping,
+            random_state: self.random_state,
+        }
+    }
+}
+
+impl<O: Optimizer> Estimator for MlpRegressor<O> {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        MlpRegressor::fit_matrix(self, x, y);
+    }
+}
+
+// An epoch per pass over the data, reporting the loss
+impl<O: Optimizer> IterativeEstimator for MlpRegressor<O> {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[f64], observer: &mut dyn FitObserver) {
+        let mut notifier = Notifier::new(observer);
+        self.fit_matrix_with_callback(x, y, |progress| {
+            notifier.epoch(|| epoch_metrics(progress));
+        });
+        if self.converged {
+            notifier.converged();
+        }
+    }
+}
+
+impl<O: Optimizer> Predictor for MlpRegressor<O> {
+    type Output = f64;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        MlpRegressor::predict_matrix(self, x)
+    }
+}
+
+fn epoch_metrics(progress: &EpochProgress) -> FitMetrics {
+    let metrics = FitMetrics::new(progress.epoch).with("loss", progress.loss);
+    match progress.validation_loss {
+        Some(loss) => metrics.with("validation_loss", loss),
+        None => metrics,
+    }
+}
+
+// Input width, hidden widths, output width
+fn layer_sizes(x: &Matrix, hidden: &[usize], n_outputs: usize) -> Vec<usize> {
+    std::iter::once(x.n_cols())
+        .chain(hidden.iter().copied())
+        .chain(std::iter::once(n_outputs))
+        .collect()
+}
+
+fn fit_network<O, F>(
+    x: &Matrix,
+    targets: &[Vec<f64>],
+    sizes: Vec<usize>,
+    activation: Activation,
+    output: Output,
+    mut training: Training<O>,
+    callback: F,
+) -> (Option<Network>, Trained)
+where
+    O: Optimizer,
+    F: FnMut(&EpochProgress),
+{
+    if x.n_rows() == 0 {
+        return (None, Trained::default());
+    }
+    let mut rng = seeded_rng(training.random_state);
+    let mut network = Network::new(sizes, activation, output, &mut rng);
+    let trained = train(&mut network, x, targets, &mut training, &mut rng, callback);
+    (Some(network), trained)
+}
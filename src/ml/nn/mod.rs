@@ -0,0 +1,337 @@
+pub mod mlp;
+
+use crate::math::matrix::Matrix;
+use crate::ml::early_stopping::EarlyStopping;
+use crate::ml::optim::Optimizer;
+use crate::ml::utils::softmax;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    // max(0, z), the usual default for hidden layers
+    Relu,
+    Tanh,
+    // 1 / (1 + e^-z)
+    Sigmoid,
+}
+
+// Training progress after one pass over the data, passed to the callback
+// of `fit_with_callback`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EpochProgress {
+    pub epoch: usize,
+    // Mean training loss over the epoch, including the L2 penalty
+    pub loss: f64,
+    // Mean loss on the held-out samples, when early stopping monitors it
+    pub validation_loss: Option<f64>,
+}
+
+impl Activation {
+    fn apply(&self, z: f64) -> f64 {
+        match self {
+            Activation::Relu => z.max(0.0),
+            Activation::Tanh => z.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-z).exp()),
+        }
+    }
+
+    // Derivative in terms of the activation's output a = f(z)
+    fn derivative(&self, a: f64) -> f64 {
+        match self {
+            Activation::Relu => {
+                if a > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::Tanh => 1.0 - a * a,
+            Activation::Sigmoid => a * (1.0 - a),
+        }
+    }
+}
+
+// What the last layer computes, together with the loss it is trained on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Output {
+    // Class probabilities with cross-entropy loss
+    Softmax,
+    // Raw values with half squared error loss
+    Identity,
+}
+
+// Fully connected feed-forward network. All weights and biases live in one
+// flat vector, layer by layer: the weights of layer l as rows of
+// sizes[l] inputs, followed by its sizes[l + 1] biases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Network {
+    sizes: Vec<usize>,
+    params: Vec<f64>,
+    activation: Activation,
+    output: Output,
+}
+
+// Settings of the training loop shared by the MLP estimators
+pub(crate) struct Training<O: Optimizer> {
+    pub optimizer: O,
+    pub alpha: f64,
+    pub batch_size: usize,
+    pub max_epochs: usize,
+    pub tolerance: f64,
+    pub n_iter_no_change: usize,
+    pub early_stopping: Option<EarlyStopping>,
+    pub random_state: Option<u64>,
+}
+
+// What train returns besides the trained network
+#[derive(Default)]
+pub(crate) struct Trained {
+    pub loss_curve: Vec<f64>,
+    pub validation_loss_curve: Vec<f64>,
+    pub converged: bool,
+    // With early stopping, the epoch with the lowest monitored loss
+    pub best_epoch: Option<usize>,
+}
+
+impl Network {
+    // Glorot-uniform weights and zero biases
+    pub fn new(
+        sizes: Vec<usize>,
+        activation: Activation,
+        output: Output,
+        rng: &mut StdRng,
+    ) -> Self {
+        let mut params = vec![];
+        for pair in sizes.windows(2) {
+            let (fan_in, fan_out) = (pair[0], pair[1]);
+            let factor = if activation == Activation::Sigmoid {
+                2.0
+            } else {
+                6.0
+            };
+            let bound = (factor / (fan_in + fan_out) as f64).sqrt();
+            params.extend((0..fan_in * fan_out).map(|_| rng.gen_range(-bound..=bound)));
+            params.extend(std::iter::repeat_n(0.0, fan_out));
+        }
+        Network {
+            sizes,
+            params,
+            activation,
+            output,
+        }
+    }
+
+    // Output of the last layer
+    pub fn predict(&self, x: &[f64]) -> Vec<f64> {
+        self.forward(x).pop().unwrap_or_default()
+    }
+
+    // Activations of every layer, input first
+    fn forward(&self, x: &[f64]) -> Vec<Vec<f64>> {
+        let mut activations = vec![x.to_vec()];
+        let mut offset = 0;
+        let n_layers = self.sizes.len() - 1;
+        for (l, pair) in self.sizes.windows(2).enumerate() {
+            let (n_in, n_out) = (pair[0], pair[1]);
+            let weights = &self.params[offset..offset + n_in * n_out];
+            let biases = &self.params[offset + n_in * n_out..offset + n_in * n_out + n_out];
+            offset += n_in * n_out + n_out;
+
+            // Rows by index rather than chunks(n_in), which panics for a
+            // layer without inputs, e.g. on points without features
+            let input = &activations[l];
+            let z: Vec<f64> = biases
+                .iter()
+                .enumerate()
+                .map(|(o, b)| {
+                    let row = &weights[o * n_in..(o + 1) * n_in];
+                    row.iter().zip(input).map(|(w, a)| w * a).sum::<f64>() + b
+                })
+                .collect();
+            let a = if l + 1 < n_layers {
+                z.iter().map(|&v| self.activation.apply(v)).collect()
+            } else {
+                match self.output {
+                    Output::Softmax => softmax(&z),
+                    Output::Identity => z,
+                }
+            };
+            activations.push(a);
+        }
+        activations
+    }
+
+    // Loss on one sample, without the penalty
+    fn loss(&self, x: &[f64], target: &[f64]) -> f64 {
+        self.output_loss(&self.predict(x), target)
+    }
+
+    fn output_loss(&self, prediction: &[f64], target: &[f64]) -> f64 {
+        match self.output {
+            Output::Softmax => -target
+                .iter()
+                .zip(prediction)
+                .map(|(t, p)| t * p.max(f64::MIN_POSITIVE).ln())
+                .sum::<f64>(),
+            Output::Identity => {
+                0.5 * target
+                    .iter()
+                    .zip(prediction)
+                    .map(|(t, p)| (p - t).powi(2))
+                    .sum::<f64>()
+            }
+        }
+    }
+
+    // Add the gradient of the loss on one sample to gradient, and return
+    // the loss
+    fn backward(&self, x: &[f64], target: &[f64], gradient: &mut [f64]) -> f64 {
+        let activations = self.forward(x);
+        let prediction = &activations[activations.len() - 1];
+        let loss = self.output_loss(prediction, target);
+
+        // Both output losses have gradient prediction - target with respect
+        // to the last layer's pre-activation
+        let mut delta: Vec<f64> = prediction.iter().zip(target).map(|(p, t)| p - t).collect();
+        let mut offset = self.params.len();
+        for l in (0..self.sizes.len() - 1).rev() {
+            let (n_in, n_out) = (self.sizes[l], self.sizes[l + 1]);
+            offset -= n_in * n_out + n_out;
+            let input = &activations[l];
+            let (weight_gradient, bias_gradient) =
+                gradient[offset..offset + n_in * n_out + n_out].split_at_mut(n_in * n_out);
+            for (o, (b, d)) in bias_gradient.iter_mut().zip(&delta).enumerate() {
+                let row = &mut weight_gradient[o * n_in..(o + 1) * n_in];
+                row.iter_mut().zip(input).for_each(|(g, a)| *g += d * a);
+                *b += d;
+            }
+
+            if l > 0 {
+                let weights = &self.params[offset..offset + n_in * n_out];
+                delta = (0..n_in)
+                    .map(|i| {
+                        let back: f64 = weights
+                            .chunks(n_in)
+                            .zip(&delta)
+                            .map(|(row, d)| row[i] * d)
+                            .sum();
+                        back * self.activation.derivative(input[i])
+                    })
+                    .collect();
+            }
+        }
+        loss
+    }
+
+    // Sum of squared weights, biases excluded
+    fn weight_norm(&self) -> f64 {
+        self.weight_mask()
+            .zip(&self.params)
+            .filter(|(is_weight, _)| *is_weight)
+            .map(|(_, w)| w * w)
+            .sum()
+    }
+
+    // true for weights, false for biases, in parameter order
+    fn weight_mask(&self) -> impl Iterator<Item = bool> + '_ {
+        self.sizes.windows(2).flat_map(|pair| {
+            std::iter::repeat_n(true, pair[0] * pair[1]).chain(std::iter::repeat_n(false, pair[1]))
+        })
+    }
+}
+
+// Mini-batch training with an L2 penalty on the weights. Stops after
+// n_iter_no_change epochs without the loss improving by tolerance, or as
+// early_stopping says when set.
+pub(crate) fn train<O, F>(
+    network: &mut Network,
+    x: &Matrix,
+    targets: &[Vec<f64>],
+    settings: &mut Training<O>,
+    rng: &mut StdRng,
+    mut callback: F,
+) -> Trained
+where
+    O: Optimizer,
+    F: FnMut(&EpochProgress),
+{
+    let (validation, mut order) = match &settings.early_stopping {
+        Some(early_stopping) => early_stopping.split(x.n_rows(), rng),
+        None => (vec![], (0..x.n_rows()).collect()),
+    };
+    let n = order.len();
+    let n_params = network.params.len();
+    let batch_size = settings.batch_size.clamp(1, n.max(1));
+    let mask: Vec<bool> = network.weight_mask().collect();
+    settings.optimizer.reset();
+
+    let mut trained = Trained::default();
+    let mut tracker = settings
+        .early_stopping
+        .map(|early_stopping| early_stopping.tracker());
+    let mut best_loss = f64::INFINITY;
+    let mut epochs_without_improvement = 0;
+
+    for epoch in 1..=settings.max_epochs {
+        order.shuffle(rng);
+        let mut epoch_loss = 0.0;
+        for batch in order.chunks(batch_size) {
+            let mut gradient = vec![0.0; n_params];
+            let mut batch_loss = 0.0;
+            for &i in batch {
+                batch_loss += network.backward(x.row(i), &targets[i], &mut gradient);
+            }
+            let size = batch.len() as f64;
+            let penalty = 0.5 * settings.alpha * network.weight_norm();
+            epoch_loss += batch_loss + penalty;
+            for ((g, w), &is_weight) in gradient.iter_mut().zip(&network.params).zip(&mask) {
+                *g /= size;
+                if is_weight {
+                    *g += settings.alpha * w / size;
+                }
+            }
+            settings.optimizer.step(&mut network.params, &gradient);
+        }
+
+        let loss = epoch_loss / n as f64;
+        trained.loss_curve.push(loss);
+        let validation_loss = (!validation.is_empty()).then(|| {
+            validation
+                .iter()
+                .map(|&i| network.loss(x.row(i), &targets[i]))
+                .sum::<f64>()
+                / validation.len() as f64
+        });
+        trained.validation_loss_curve.extend(validation_loss);
+        callback(&EpochProgress {
+            epoch,
+            loss,
+            validation_loss,
+        });
+
+        let stop = match &mut tracker {
+            Some(tracker) => {
+                let stop = tracker.update(validation_loss.unwrap_or(loss));
+                trained.best_epoch = Some(tracker.best_iteration());
+                stop
+            }
+            None => {
+                if loss > best_loss - settings.tolerance {
+                    epochs_without_improvement += 1;
+                } else {
+                    epochs_without_improvement = 0;
+                }
+                best_loss = best_loss.min(loss);
+                epochs_without_improvement >= settings.n_iter_no_change
+            }
+        };
+        if stop {
+            trained.converged = true;
+            break;
+        }
+    }
+    trained
+}
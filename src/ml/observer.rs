@@ -0,0 +1,156 @@
+// Progress of a fit while it runs, for progress bars and live training
+// charts. Iterative estimators take a FitObserver in fit_observed, see
+// IterativeEstimator, and report every iteration or epoch to it.
+use serde::Serialize;
+
+#[cfg(feature = "web")]
+use dioxus::prelude::{Signal, Writable};
+
+// Where a fit is: its iteration or epoch, counted from 1, and the values
+// the estimator tracks, e.g. ("inertia", 12.5) or ("loss", 0.3)
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct FitMetrics {
+    pub iteration: usize,
+    pub values: Vec<(&'static str, f64)>,
+}
+
+impl FitMetrics {
+    pub fn new(iteration: usize) -> Self {
+        FitMetrics {
+            iteration,
+            values: vec![],
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: f64) -> Self {
+        self.values.push((name, value));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|&(_, value)| value)
+    }
+}
+
+// What an observer is told, for observers that treat every report alike,
+// e.g. by queueing it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum FitEvent {
+    Iteration(FitMetrics),
+    Epoch(FitMetrics),
+    Converged(FitMetrics),
+}
+
+pub trait FitObserver {
+    // After every iteration of a fit that refines one solution, e.g. KMeans
+    // or t-SNE
+    fn on_iteration(&mut self, _metrics: &FitMetrics) {}
+
+    // After every pass over the data of a fit that trains on it, e.g. an
+    // MLP or a boosting stage
+    fn on_epoch(&mut self, _metrics: &FitMetrics) {}
+
+    // Once the fit has converged, with the metrics of its last iteration or
+    // epoch. A fit stopped by its iteration limit doesn't call it.
+    fn on_converged(&mut self, _metrics: &FitMetrics) {}
+}
+
+// Any closure taking events, e.g. one sending them down a channel
+impl<F: FnMut(&FitEvent)> FitObserver for F {
+    fn on_iteration(&mut self, metrics: &FitMetrics) {
+        self(&FitEvent::Iteration(metrics.clone()));
+    }
+
+    fn on_epoch(&mut self, metrics: &FitMetrics) {
+        self(&FitEvent::Epoch(metrics.clone()));
+    }
+
+    fn on_converged(&mut self, metrics: &FitMetrics) {
+        self(&FitEvent::Converged(metrics.clone()));
+    }
+}
+
+// Pushes every event onto a signal, which re-renders the components that
+// read it, e.g. a chart of the loss so far
+#[cfg(feature = "web")]
+#[derive(Clone, Copy)]
+pub struct SignalObserver {
+    pub events: Signal<Vec<FitEvent>>,
+}
+
+#[cfg(feature = "web")]
+impl SignalObserver {
+    pub fn new(events: Signal<Vec<FitEvent>>) -> Self {
+        SignalObserver { events }
+    }
+}
+
+#[cfg(feature = "web")]
+impl FitObserver for SignalObserver {
+    fn on_iteration(&mut self, metrics: &FitMetrics) {
+        self.events
+            .write()
+            .push(FitEvent::Iteration(metrics.clone()));
+    }
+
+    fn on_epoch(&mut self, metrics: &FitMetrics) {
+        self.events.write().push(FitEvent::Epoch(metrics.clone()));
+    }
+
+    fn on_converged(&mut self, metrics: &FitMetrics) {
+        self.events
+            .write()
+            .push(FitEvent::Converged(metrics.clone()));
+    }
+}
+
+// The observer a fit loop reports to, if any. Metrics are only computed
+// when there is one, since some cost a pass over the data, e.g. inertia.
+pub(crate) struct Notifier<'a> {
+    observer: Option<&'a mut dyn FitObserver>,
+    last: FitMetrics,
+}
+
+impl<'a> Notifier<'a> {
+    pub(crate) fn none() -> Self {
+        Notifier {
+            observer: None,
+            last: FitMetrics::default(),
+        }
+    }
+
+    pub(crate) fn new(observer: &'a mut dyn FitObserver) -> Self {
+        Notifier {
+            observer: Some(observer),
+            last: FitMetrics::default(),
+        }
+    }
+
+    pub(crate) fn is_observed(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    pub(crate) fn iteration(&mut self, metrics: impl FnOnce() -> FitMetrics) {
+        if let Some(observer) = self.observer.as_deref_mut() {
+            self.last = metrics();
+            observer.on_iteration(&self.last);
+        }
+    }
+
+    pub(crate) fn epoch(&mut self, metrics: impl FnOnce() -> FitMetrics) {
+        if let Some(observer) = self.observer.as_deref_mut() {
+            self.last = metrics();
+            observer.on_epoch(&self.last);
+        }
+    }
+
+    // With the metrics last reported
+    pub(crate) fn converged(&mut self) {
+        if let Some(observer) = self.observer.as_deref_mut() {
+            observer.on_converged(&self.last);
+        }
+    }
+}
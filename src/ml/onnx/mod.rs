@@ -0,0 +1,350 @@
+// Fitted estimators as ONNX models, to run them outside the browser, e.g.
+// with onnxruntime. Every model is one operator of the ai.onnx.ml domain
+// reading float features X of shape [N, n_features]. Regressors output Y of
+// shape [N, 1], scalers Y of shape [N, n_features] and classifiers label of
+// shape [N] and probabilities of shape [N, n_classes]. ONNX computes in
+// 32-bit floats, so results can differ from the estimator's in the last
+// digits, and points right at a split threshold can go the other way.
+mod proto;
+
+use crate::ml::tree::TreeNode;
+use proto::Message;
+use std::error::Error;
+use std::fmt;
+
+const IR_VERSION: i64 = 8;
+const OPSET_VERSION: i64 = 15;
+const ML_DOMAIN: &str = "ai.onnx.ml";
+const ML_OPSET_VERSION: i64 = 3;
+const PRODUCER: &str = "moonlight";
+
+// TensorProto.DataType
+const FLOAT: i64 = 1;
+const INT64: i64 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OnnxError {
+    NotFitted,
+    // A fitted estimator ONNX has no operator for
+    Unsupported(String),
+}
+
+impl fmt::Display for OnnxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnnxError::NotFitted => write!(f, "the estimator has not been fitted"),
+            OnnxError::Unsupported(message) => write!(f, "cannot export to ONNX: {}", message),
+        }
+    }
+}
+
+impl Error for OnnxError {}
+
+pub trait ToOnnx {
+    // The fitted estimator as a serialized ONNX ModelProto, the contents of
+    // a .onnx file
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError>;
+}
+
+pub(crate) enum Attribute {
+    Int(i64),
+    String(&'static str),
+    Floats(Vec<f64>),
+    Ints(Vec<i64>),
+    Strings(Vec<&'static str>),
+}
+
+impl Attribute {
+    // AttributeProto
+    fn encode(&self, name: &str) -> Message {
+        let message = Message::new().string(1, name);
+        match self {
+            Attribute::Int(value) => message.int(3, *value).int(20, 2),
+            Attribute::String(value) => message.string(4, value).int(20, 3),
+            Attribute::Floats(values) => message.packed_floats(7, &floats(values)).int(20, 6),
+            Attribute::Ints(values) => message.packed_ints(8, values).int(20, 7),
+            Attribute::Strings(values) => message.strings(9, values).int(20, 8),
+        }
+    }
+}
+
+// An ai.onnx.ml operator with its attributes
+pub(crate) struct Operator {
+    op_type: &'static str,
+    attributes: Vec<(&'static str, Attribute)>,
+}
+
+impl Operator {
+    pub fn new(op_type: &'static str) -> Self {
+        Operator {
+            op_type,
+            attributes: vec![],
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: Attribute) -> Self {
+        self.attributes.push((name, value));
+        self
+    }
+}
+
+// What a model outputs
+pub(crate) enum Output {
+    // Floats with this many columns
+    Values(&'static str, usize),
+    // One int64 label per row
+    Labels(&'static str),
+}
+
+// The model applying operator to X, whose width isn't checked when
+// n_features is None
+pub(crate) fn model(operator: Operator, n_features: Option<usize>, outputs: &[Output]) -> Vec<u8> {
+    let output_names: Vec<&str> = outputs
+        .iter()
+        .map(|output| match output {
+            Output::Values(name, _) | Output::Labels(name) => *name,
+        })
+        .collect();
+    let attributes = operator
+        .attributes
+        .iter()
+        .map(|(name, value)| value.encode(name));
+    let node = Message::new()
+        .string(1, "X")
+        .strings(2, &output_names)
+        .string(3, operator.op_type)
+        .string(4, operator.op_type)
+        .messages(5, attributes)
+        .string(7, ML_DOMAIN);
+    let graph = Message::new()
+        .message(1, node)
+        .string(2, PRODUCER)
+        .message(11, value_info("X", FLOAT, n_features))
+        .messages(
+            12,
+            outputs.iter().map(|output| match output {
+                Output::Values(name, columns) => value_info(name, FLOAT, Some(*columns)),
+                Output::Labels(name) => value_info(name, INT64, None),
+            }),
+        );
+    Message::new()
+        .int(1, IR_VERSION)
+        .string(2, PRODUCER)
+        .string(3, env!("CARGO_PKG_VERSION"))
+        .message(7, graph)
+        .message(8, opset("", OPSET_VERSION))
+        .message(8, opset(ML_DOMAIN, ML_OPSET_VERSION))
+        .into_bytes()
+}
+
+// y = x · coefficients + intercept
+pub(crate) fn linear_regressor(coefficients: &[f64], intercept: f64) -> Vec<u8> {
+    let operator = Operator::new("LinearRegressor")
+        .with("coefficients", Attribute::Floats(coefficients.to_vec()))
+        .with("intercepts", Attribute::Floats(vec![intercept]))
+        .with("targets", Attribute::Int(1));
+    model(
+        operator,
+        Some(coefficients.len()),
+        &[Output::Values("Y", 1)],
+    )
+}
+
+// Softmax of x · coefficients[class] + intercepts[class] over the classes
+pub(crate) fn softmax_classifier(coefficients: &[Vec<f64>], intercepts: &[f64]) -> Vec<u8> {
+    let n_features = coefficients.first().map(Vec::len);
+    let classes: Vec<i64> = (0..coefficients.len() as i64).collect();
+    let operator = Operator::new("LinearClassifier")
+        .with("coefficients", Attribute::Floats(coefficients.concat()))
+        .with("intercepts", Attribute::Floats(intercepts.to_vec()))
+        .with("classlabels_ints", Attribute::Ints(classes))
+        .with("multi_class", Attribute::Int(1))
+        .with("post_transform", Attribute::String("SOFTMAX"));
+    model(
+        operator,
+        n_features,
+        &[
+            Output::Labels("label"),
+            Output::Values("probabilities", coefficients.len()),
+        ],
+    )
+}
+
+// (x - offset) / divisor, feature by feature
+pub(crate) fn scaler(offset: &[f64], divisor: &[f64]) -> Vec<u8> {
+    let scale = divisor.iter().map(|divisor| 1.0 / divisor).collect();
+    let operator = Operator::new("Scaler")
+        .with("offset", Attribute::Floats(offset.to_vec()))
+        .with("scale", Attribute::Floats(scale));
+    model(
+        operator,
+        Some(offset.len()),
+        &[Output::Values("Y", offset.len())],
+    )
+}
+
+// Trees flattened into the parallel node and leaf lists of the
+// TreeEnsembleRegressor and TreeEnsembleClassifier operators
+#[derive(Default)]
+pub(crate) struct TreeEnsemble {
+    tree_ids: Vec<i64>,
+    node_ids: Vec<i64>,
+    features: Vec<i64>,
+    thresholds: Vec<f64>,
+    modes: Vec<&'static str>,
+    true_ids: Vec<i64>,
+    false_ids: Vec<i64>,
+    leaf_tree_ids: Vec<i64>,
+    leaf_node_ids: Vec<i64>,
+    leaf_targets: Vec<i64>,
+    leaf_weights: Vec<f64>,
+    n_trees: i64,
+}
+
+impl TreeEnsemble {
+    pub fn new() -> Self {
+        TreeEnsemble::default()
+    }
+
+    // Adds tree, each leaf contributing the (target or class, weight) pairs
+    // leaf returns for its value
+    pub fn with_tree(
+        mut self,
+        tree: &TreeNode,
+        leaf: impl Fn(&[f64]) -> Vec<(usize, f64)>,
+    ) -> Self {
+        self.add_node(tree, &leaf, &mut 0);
+        self.n_trees += 1;
+        self
+    }
+
+    // Sum or average, per aggregate, of the leaf weights plus base_value
+    pub fn regressor(
+        self,
+        n_features: Option<usize>,
+        aggregate: &'static str,
+        base_value: f64,
+    ) -> Vec<u8> {
+        let operator = self
+            .nodes(Operator::new("TreeEnsembleRegressor"))
+            .with("target_treeids", Attribute::Ints(self.leaf_tree_ids))
+            .with("target_nodeids", Attribute::Ints(self.leaf_node_ids))
+            .with("target_ids", Attribute::Ints(self.leaf_targets))
+            .with("target_weights", Attribute::Floats(self.leaf_weights))
+            .with("n_targets", Attribute::Int(1))
+            .with("aggregate_function", Attribute::String(aggregate))
+            .with("base_values", Attribute::Floats(vec![base_value]));
+        model(operator, n_features, &[Output::Values("Y", 1)])
+    }
+
+    // Sums of the leaf weights of every class plus base_values, then
+    // post_transform. With two classes and all weights on class 0 those are
+    // scores of class 1, as boosting writes them.
+    pub fn classifier(
+        self,
+        n_features: Option<usize>,
+        n_classes: usize,
+        post_transform: &'static str,
+        base_values: Vec<f64>,
+    ) -> Vec<u8> {
+        let labels = (0..n_classes as i64).collect();
+        let operator = self
+            .nodes(Operator::new("TreeEnsembleClassifier"))
+            .with("class_treeids", Attribute::Ints(self.leaf_tree_ids))
+            .with("class_nodeids", Attribute::Ints(self.leaf_node_ids))
+            .with("class_ids", Attribute::Ints(self.leaf_targets))
+            .with("class_weights", Attribute::Floats(self.leaf_weights))
+            .with("classlabels_int64s", Attribute::Ints(labels))
+            .with("post_transform", Attribute::String(post_transform))
+            .with("base_values", Attribute::Floats(base_values));
+        model(
+            operator,
+            n_features,
+            &[
+                Output::Labels("label"),
+                Output::Values("probabilities", n_classes),
+            ],
+        )
+    }
+
+    // Nodes numbered depth first, from 0 at the root of every tree
+    fn add_node(
+        &mut self,
+        node: &TreeNode,
+        leaf: &impl Fn(&[f64]) -> Vec<(usize, f64)>,
+        next_id: &mut i64,
+    ) -> i64 {
+        let id = *next_id;
+        *next_id += 1;
+        let index = self.node_ids.len();
+        self.tree_ids.push(self.n_trees);
+        self.node_ids.push(id);
+        match node {
+            TreeNode::Leaf { value, .. } => {
+                self.features.push(0);
+                self.thresholds.push(0.0);
+                self.modes.push("LEAF");
+                self.true_ids.push(0);
+                self.false_ids.push(0);
+                for (target, weight) in leaf(value) {
+                    self.leaf_tree_ids.push(self.n_trees);
+                    self.leaf_node_ids.push(id);
+                    self.leaf_targets.push(target as i64);
+                    self.leaf_weights.push(weight);
+                }
+            }
+            TreeNode::Split {
+                feature,
+                threshold,
+                left,
+                right,
+                ..
+            } => {
+                self.features.push(*feature as i64);
+                self.thresholds.push(*threshold);
+                self.modes.push("BRANCH_LEQ");
+                self.true_ids.push(0);
+                self.false_ids.push(0);
+                self.true_ids[index] = self.add_node(left, leaf, next_id);
+                self.false_ids[index] = self.add_node(right, leaf, next_id);
+            }
+        }
+        id
+    }
+
+    fn nodes(&self, operator: Operator) -> Operator {
+        operator
+            .with("nodes_treeids", Attribute::Ints(self.tree_ids.clone()))
+            .with("nodes_nodeids", Attribute::Ints(self.node_ids.clone()))
+            .with("nodes_featureids", Attribute::Ints(self.features.clone()))
+            .with("nodes_values", Attribute::Floats(self.thresholds.clone()))
+            .with("nodes_modes", Attribute::Strings(self.modes.clone()))
+            .with("nodes_truenodeids", Attribute::Ints(self.true_ids.clone()))
+            .with(
+                "nodes_falsenodeids",
+                Attribute::Ints(self.false_ids.clone()),
+            )
+    }
+}
+
+// ValueInfoProto of a tensor with a free first dimension N and, when given,
+// a fixed second one
+fn value_info(name: &str, elem_type: i64, columns: Option<usize>) -> Message {
+    let mut shape = Message::new().message(1, Message::new().string(2, "N"));
+    if let Some(columns) = columns {
+        shape = shape.message(1, Message::new().int(1, columns as i64));
+    }
+    let tensor = Message::new().int(1, elem_type).message(2, shape);
+    Message::new()
+        .string(1, name)
+        .message(2, Message::new().message(1, tensor))
+}
+
+// OperatorSetIdProto
+fn opset(domain: &str, version: i64) -> Message {
+    Message::new().string(1, domain).int(2, version)
+}
+
+fn floats(values: &[f64]) -> Vec<f32> {
+    values.iter().map(|&value| value as f32).collect()
+}
@@ -0,0 +1,81 @@
+// The protocol buffers wire format, as much of it as writing ONNX models
+// takes: varints and length-delimited fields. Repeated numbers are written
+// packed, which every parser accepts.
+
+const VARINT: u64 = 0;
+const LENGTH_DELIMITED: u64 = 2;
+
+// An encoded message, built field by field
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Message {
+    bytes: Vec<u8>,
+}
+
+impl Message {
+    pub fn new() -> Self {
+        Message::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn int(mut self, field: u64, value: i64) -> Self {
+        self.key(field, VARINT);
+        self.varint(value as u64);
+        self
+    }
+
+    pub fn string(self, field: u64, value: &str) -> Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    pub fn strings<S: AsRef<str>>(self, field: u64, values: &[S]) -> Self {
+        values
+            .iter()
+            .fold(self, |message, value| message.string(field, value.as_ref()))
+    }
+
+    pub fn bytes(mut self, field: u64, value: &[u8]) -> Self {
+        self.key(field, LENGTH_DELIMITED);
+        self.varint(value.len() as u64);
+        self.bytes.extend(value);
+        self
+    }
+
+    pub fn message(self, field: u64, value: Message) -> Self {
+        self.bytes(field, &value.bytes)
+    }
+
+    pub fn messages(self, field: u64, values: impl IntoIterator<Item = Message>) -> Self {
+        values
+            .into_iter()
+            .fold(self, |message, value| message.message(field, value))
+    }
+
+    pub fn packed_ints(self, field: u64, values: &[i64]) -> Self {
+        let mut packed = Message::new();
+        values.iter().for_each(|&value| packed.varint(value as u64));
+        self.bytes(field, &packed.bytes)
+    }
+
+    pub fn packed_floats(self, field: u64, values: &[f32]) -> Self {
+        let packed: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        self.bytes(field, &packed)
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.varint(field << 3 | wire_type);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::math::simd;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const LEARNING_RATE: f64 = 0.01;
+const ADAM_LEARNING_RATE: f64 = 1e-3;
+const MOMENTUM: f64 = 0.9;
+const RMSPROP_DECAY: f64 = 0.9;
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+// Keeps the adaptive methods from dividing by zero
+const EPSILON: f64 = 1e-8;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Update rule of a first-order method. Optimizers keep whatever state they
+// need between steps (velocity, moment estimates), sized on the first step.
+pub trait Optimizer: fmt::Debug + Clone {
+    // Move params one step against the gradient of the loss
+    fn step(&mut self, params: &mut [f64], gradient: &[f64]);
+
+    // Forget the state of previous steps, so the next step starts a fresh
+    // run
+    fn reset(&mut self);
+}
+
+// Plain gradient descent: p ← p - lr * g
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sgd {
+    pub learning_rate: f64,
+}
+
+// Gradient descent with classical (heavy ball) momentum: v ← μ v - lr * g,
+// p ← p + v
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Momentum {
+    pub learning_rate: f64,
+    pub momentum: f64,
+
+    // Current state
+    velocity: Vec<f64>,
+}
+
+// Tieleman and Hinton (2012), steps scaled by a running average of the
+// squared gradient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RmsProp {
+    pub learning_rate: f64,
+    // Weight of the old average in every update
+    pub decay: f64,
+    pub epsilon: f64,
+
+    // Current state
+    mean_square: Vec<f64>,
+}
+
+// Kingma and Ba (2015), per-parameter step sizes from bias-corrected
+// running moments of the gradient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adam {
+    pub learning_rate: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+
+    // Current state
+    first_moment: Vec<f64>,
+    second_moment: Vec<f64>,
+    n_steps: i32,
+}
+
+impl fmt::Display for Sgd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sgd{{ learning_rate: {} }}", self.learning_rate)
+    }
+}
+
+impl fmt::Display for Momentum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Momentum{{ learning_rate: {}, momentum: {} }}",
+            self.learning_rate, self.momentum
+        )
+    }
+}
+
+impl fmt::Display for RmsProp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RmsProp{{ learning_rate: {}, decay: {} }}",
+            self.learning_rate, self.decay
+        )
+    }
+}
+
+impl fmt::Display for Adam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Adam{{ learning_rate: {}, beta1: {}, beta2: {} }}",
+            self.learning_rate, self.beta1, self.beta2
+        )
+    }
+}
+
+impl Default for Sgd {
+    fn default() -> Self {
+        Sgd::new(LEARNING_RATE)
+    }
+}
+
+impl Default for Momentum {
+    fn default() -> Self {
+        Momentum::new(LEARNING_RATE, MOMENTUM)
+    }
+}
+
+impl Default for RmsProp {
+    fn default() -> Self {
+        RmsProp::new(ADAM_LEARNING_RATE)
+    }
+}
+
+impl Default for Adam {
+    fn default() -> Self {
+        Adam::new(ADAM_LEARNING_RATE)
+    }
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f64) -> Self {
+        Sgd { learning_rate }
+    }
+}
+
+impl Momentum {
+    pub fn new(learning_rate: f64, momentum: f64) -> Self {
+        Momentum {
+            learning_rate,
+            momentum,
+            velocity: vec![],
+        }
+    }
+}
+
+impl RmsProp {
+    pub fn new(learning_rate: f64) -> Self {
+        RmsProp {
+            learning_rate,
+            decay: RMSPROP_DECAY,
+            epsilon: EPSILON,
+            mean_square: vec![],
+        }
+    }
+}
+
+impl Adam {
+    pub fn new(learning_rate: f64) -> Self {
+        Adam {
+            learning_rate,
+            beta1: BETA1,
+            beta2: BETA2,
+            epsilon: EPSILON,
+            first_moment: vec![],
+            second_moment: vec![],
+            n_steps: 0,
+        }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, params: &mut [f64], gradient: &[f64]) {
+        simd::axpy(-self.learning_rate, gradient, params);
+    }
+
+    fn reset(&mut self) {}
+}
+
+impl Optimizer for Momentum {
+    fn step(&mut self, params: &mut [f64], gradient: &[f64]) {
+        resize_state(&mut self.velocity, params.len());
+        for ((p, g), v) in params.iter_mut().zip(gradient).zip(&mut self.velocity) {
+            *v = self.momentum * *v - self.learning_rate * g;
+            *p += *v;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.velocity.clear();
+    }
+}
+
+impl Optimizer for RmsProp {
+    fn step(&mut self, params: &mut [f64], gradient: &[f64]) {
+        resize_state(&mut self.mean_square, params.len());
+        for ((p, g), s) in params.iter_mut().zip(gradient).zip(&mut self.mean_square) {
+            *s = self.decay * *s + (1.0 - self.decay) * g * g;
+            *p -= self.learning_rate * g / (s.sqrt() + self.epsilon);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.mean_square.clear();
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, params: &mut [f64], gradient: &[f64]) {
+        if self.first_moment.len() != params.len() {
+            self.reset();
+        }
+        resize_state(&mut self.first_moment, params.len());
+        resize_state(&mut self.second_moment, params.len());
+        self.n_steps += 1;
+
+        // Bias correction folded into the step size
+        let t = self.n_steps;
+        let rate =
+            self.learning_rate * (1.0 - self.beta2.powi(t)).sqrt() / (1.0 - self.beta1.powi(t));
+        for (((p, g), m), v) in params
+            .iter_mut()
+            .zip(gradient)
+            .zip(&mut self.first_moment)
+            .zip(&mut self.second_moment)
+        {
+            *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+            *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+            *p -= rate * *m / (v.sqrt() + self.epsilon);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.first_moment.clear();
+        self.second_moment.clear();
+        self.n_steps = 0;
+    }
+}
+
+// Run n_steps of the optimizer from start on a loss given by its gradient,
+// and return every point visited, start included. Lets different optimizers
+// be compared on the same loss surface.
+pub fn trajectory<O, F>(optimizer: &mut O, start: &[f64], gradient: F, n_steps: usize) -> Vec<Point>
+where
+    O: Optimizer,
+    F: Fn(&[f64]) -> Vec<f64>,
+{
+    optimizer.reset();
+    let mut params = start.to_vec();
+    let mut path = vec![params.clone()];
+    for _ in 0..n_steps {
+        let g = gradient(&params);
+        optimizer.step(&mut params, &g);
+        path.push(params.clone());
+    }
+    path
+}
+
+// State starts at zero and follows the number of parameters
+fn resize_state(state: &mut Vec<f64>, len: usize) {
+    if state.len() != len {
+        state.clear();
+        state.resize(len, 0.0);
+    }
+}
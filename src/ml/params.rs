@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+// Kind of value a hyperparameter takes. Every value is held as an f64,
+// whole numbers for Int and 0 or 1 for Bool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Int,
+    Float,
+    Bool,
+}
+
+// A hyperparameter of an estimator, with the values it accepts, so that a
+// form can be built for any estimator, see ParamForm
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    // Bounds, inclusive, None when unbounded
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    // Increment of the form's arrows, None for any step
+    pub step: Option<f64>,
+    pub default: f64,
+}
+
+impl ParamSpec {
+    pub fn int(name: &'static str, default: usize) -> Self {
+        ParamSpec {
+            name,
+            kind: ParamKind::Int,
+            min: None,
+            max: None,
+            step: Some(1.0),
+            default: default as f64,
+        }
+    }
+
+    pub fn float(name: &'static str, default: f64) -> Self {
+        ParamSpec {
+            name,
+            kind: ParamKind::Float,
+            min: None,
+            max: None,
+            step: None,
+            default,
+        }
+    }
+
+    pub fn bool(name: &'static str, default: bool) -> Self {
+        ParamSpec {
+            name,
+            kind: ParamKind::Bool,
+            min: Some(0.0),
+            max: Some(1.0),
+            step: Some(1.0),
+            default: if default { 1.0 } else { 0.0 },
+        }
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    // Nearest value the parameter accepts: rounded for Int and Bool, then
+    // brought within the bounds. NaN gives the default.
+    pub fn clamp(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            return self.default;
+        }
+        let value = match self.kind {
+            ParamKind::Int | ParamKind::Bool => value.round(),
+            ParamKind::Float => value,
+        };
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    // Value typed into a form, clamped. Bool takes true and false too.
+    pub fn parse(&self, text: &str) -> Result<f64, ParamError> {
+        let text = text.trim();
+        let value = match (self.kind, text) {
+            (ParamKind::Bool, "true") => Ok(1.0),
+            (ParamKind::Bool, "false") => Ok(0.0),
+            _ => text.parse::<f64>(),
+        };
+        match value {
+            Ok(value) if !value.is_nan() => Ok(self.clamp(value)),
+            _ => Err(ParamError::NotANumber {
+                name: self.name.to_string(),
+                text: text.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    NotANumber { name: String, text: String },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::NotANumber { name, text } => {
+                write!(f, "{}: {:?} is not a number", name, text)
+            }
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+// Values of hyperparameters by name
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Params {
+    values: BTreeMap<String, f64>,
+}
+
+impl Params {
+    // Every spec at its default
+    pub fn defaults(specs: &[ParamSpec]) -> Self {
+        Params {
+            values: specs
+                .iter()
+                .map(|spec| (spec.name.to_string(), spec.default))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn with(mut self, name: &str, value: f64) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    // Value of spec's parameter clamped, its default when unset
+    pub fn value(&self, spec: &ParamSpec) -> f64 {
+        self.get(spec.name)
+            .map_or(spec.default, |value| spec.clamp(value))
+    }
+}
+
+// An estimator whose hyperparameters can be read and set by name
+pub trait Configurable {
+    fn param_specs() -> Vec<ParamSpec>
+    where
+        Self: Sized;
+
+    fn params(&self) -> Params;
+
+    // Sets the parameters in params, clamped to their specs, and leaves the
+    // others
+    fn set_params(&mut self, params: &Params);
+
+    fn with_params(mut self, params: &Params) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_params(params);
+        self
+    }
+}
+
+// Value of the parameter named name in params clamped to its spec, None
+// when unset. For implementations of set_params.
+pub(crate) fn param_value(specs: &[ParamSpec], params: &Params, name: &str) -> Option<f64> {
+    let spec = specs.iter().find(|spec| spec.name == name)?;
+    params.get(name).map(|value| spec.clamp(value))
+}
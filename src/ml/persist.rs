@@ -0,0 +1,164 @@
+// Saving fitted estimators and loading them back, parameters and fitted
+// state alike. A saved model wraps the estimator in an envelope with the
+// format version and the estimator's type, as JSON to read and diff or as
+// compact CBOR to store.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+
+// Bumped whenever a saved estimator's layout changes
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PersistError {
+    Encode(String),
+    Decode(String),
+    // Saved by another format version
+    Version { found: u32, expected: u32 },
+    // Saved from another estimator, or one with another metric or optimizer
+    Model { found: String, expected: String },
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Encode(message) => write!(f, "cannot save model: {}", message),
+            PersistError::Decode(message) => write!(f, "cannot load model: {}", message),
+            PersistError::Version { found, expected } => write!(
+                f,
+                "model saved with format version {}, expected {}",
+                found, expected
+            ),
+            PersistError::Model { found, expected } => {
+                write!(f, "saved model is a {}, expected a {}", found, expected)
+            }
+        }
+    }
+}
+
+impl Error for PersistError {}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    model: String,
+    estimator: &'a T,
+}
+
+// Read first, so that a model from another version or estimator is
+// reported as such rather than as whichever field fails to parse
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct Saved<T> {
+    estimator: T,
+}
+
+// Every estimator, fitted or not, e.g.
+// `let model = KMeans::<Euclidean>::load_json(&model.save_json()?)?`
+pub trait Persist: Serialize + DeserializeOwned {
+    fn save_json(&self) -> Result<String, PersistError> {
+        serde_json::to_string(&envelope(self)).map_err(|err| PersistError::Encode(err.to_string()))
+    }
+
+    fn load_json(json: &str) -> Result<Self, PersistError> {
+        let decode = |err: serde_json::Error| PersistError::Decode(err.to_string());
+        check::<Self>(serde_json::from_str(json).map_err(decode)?)?;
+        let saved: Saved<Self> = serde_json::from_str(json).map_err(decode)?;
+        Ok(saved.estimator)
+    }
+
+    fn save_binary(&self) -> Result<Vec<u8>, PersistError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&envelope(self), &mut bytes)
+            .map_err(|err| PersistError::Encode(err.to_string()))?;
+        Ok(bytes)
+    }
+
+    fn load_binary(bytes: &[u8]) -> Result<Self, PersistError> {
+        let decode =
+            |err: ciborium::de::Error<std::io::Error>| PersistError::Decode(err.to_string());
+        check::<Self>(ciborium::from_reader(bytes).map_err(decode)?)?;
+        let saved: Saved<Self> = ciborium::from_reader(bytes).map_err(decode)?;
+        Ok(saved.estimator)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Persist for T {}
+
+fn envelope<T>(estimator: &T) -> Envelope<'_, T> {
+    Envelope {
+        version: FORMAT_VERSION,
+        model: model_name::<T>(),
+        estimator,
+    }
+}
+
+fn check<T>(header: Header) -> Result<(), PersistError> {
+    if header.version != FORMAT_VERSION {
+        return Err(PersistError::Version {
+            found: header.version,
+            expected: FORMAT_VERSION,
+        });
+    }
+    let expected = model_name::<T>();
+    if header.model != expected {
+        return Err(PersistError::Model {
+            found: header.model,
+            expected,
+        });
+    }
+    Ok(())
+}
+
+// The type's name without module paths, e.g. KMeans<Manhattan>, so that
+// moving a module doesn't invalidate saved models
+fn model_name<T>() -> String {
+    let mut name = String::new();
+    let mut path = String::new();
+    for c in std::any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            name.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            name.push(c);
+        }
+    }
+    name.push_str(path.rsplit("::").next().unwrap_or_default());
+    name
+}
+
+// serde with-module for floats that may be NaN or infinite, which JSON has
+// no numbers for: those are written as the strings "NaN", "inf" and "-inf"
+pub(crate) mod non_finite {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Float {
+        Number(f64),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            Float::Number(*value)
+        } else {
+            Float::Text(value.to_string())
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        match Float::deserialize(deserializer)? {
+            Float::Number(value) => Ok(value),
+            Float::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
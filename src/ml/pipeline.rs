@@ -0,0 +1,264 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{Estimator, Predictor, Transformer};
+use crate::ml::classification::Classifier;
+use crate::ml::validation::NanPolicy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Transformer that leaves its input unchanged. Starts every pipeline, and
+// stands in for the final estimator until one is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Passthrough;
+
+// Two transformers applied one after the other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chain<A, B> {
+    pub first: A,
+    pub second: B,
+}
+
+// Preprocessing transformers followed by a final estimator, fitted and
+// applied as one model: each transformer is fitted on the output of the
+// previous one, and the estimator on the output of the last.
+//
+//     Pipeline::new()
+//         .with_step("scaler", scaler)
+//         .with_step("pca", Pca::new(2))
+//         .with_estimator("kmeans", KMeans::new(3))
+//
+// A pipeline without an estimator is itself a transformer. Pipelines
+// serialize whenever their steps do, so a whole workflow can be saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline<T = Passthrough, E = Passthrough> {
+    // Transformers in order, nested as Chains
+    preprocessing: T,
+    estimator: E,
+    step_names: Vec<String>,
+    estimator_name: Option<String>,
+}
+
+impl<T, E> fmt::Display for Pipeline<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pipeline{{ steps: {:?} }}", self.step_names())
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline {
+            preprocessing: Passthrough,
+            estimator: Passthrough,
+            step_names: vec![],
+            estimator_name: None,
+        }
+    }
+}
+
+impl<T, E> Pipeline<T, E> {
+    // Append a transformer after the existing ones
+    pub fn with_step<S>(mut self, name: &str, transformer: S) -> Pipeline<Chain<T, S>, E>
+    where
+        S: Transformer<Target = ()>,
+    {
+        self.step_names.push(name.to_string());
+        Pipeline {
+            preprocessing: Chain {
+                first: self.preprocessing,
+                second: transformer,
+            },
+            estimator: self.estimator,
+            step_names: self.step_names,
+            estimator_name: self.estimator_name,
+        }
+    }
+
+    // Set the model fitted on the preprocessed data
+    pub fn with_estimator<F: Estimator>(self, name: &str, estimator: F) -> Pipeline<T, F> {
+        Pipeline {
+            preprocessing: self.preprocessing,
+            estimator,
+            step_names: self.step_names,
+            estimator_name: Some(name.to_string()),
+        }
+    }
+
+    // Names of the transformers in order, then of the estimator
+    pub fn step_names(&self) -> Vec<&str> {
+        self.step_names
+            .iter()
+            .chain(&self.estimator_name)
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn preprocessing(&self) -> &T {
+        &self.preprocessing
+    }
+
+    pub fn estimator(&self) -> &E {
+        &self.estimator
+    }
+}
+
+impl<T, E> Pipeline<T, E>
+where
+    T: Transformer<Target = ()>,
+{
+    // Run the fitted transformers only, giving the features the estimator
+    // sees
+    pub fn preprocess(&self, x: &[Point]) -> Vec<Point> {
+        self.preprocessing.transform(x)
+    }
+
+    pub fn preprocess_matrix(&self, x: &Matrix) -> Matrix {
+        self.preprocessing.transform_matrix(x)
+    }
+}
+
+impl Estimator for Passthrough {
+    type Target = ();
+
+    fn fit_matrix(&mut self, _: &Matrix, _: &()) {}
+}
+
+impl Transformer for Passthrough {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        x.clone()
+    }
+
+    fn is_identity(&self) -> bool {
+        true
+    }
+}
+
+impl<A, B> Estimator for Chain<A, B>
+where
+    A: Transformer<Target = ()>,
+    B: Transformer<Target = ()>,
+{
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        let transformed = self.first.fit_transform_matrix(x, &());
+        self.second.fit_matrix(&transformed, &());
+    }
+
+    // The points the first transformer takes, skipping the Passthrough
+    // every pipeline starts with
+    fn nan_policy(&self) -> NanPolicy {
+        if self.first.is_identity() {
+            self.second.nan_policy()
+        } else {
+            self.first.nan_policy()
+        }
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        if self.first.is_identity() {
+            self.second.n_features_in()
+        } else {
+            self.first.n_features_in()
+        }
+    }
+}
+
+impl<A, B> Transformer for Chain<A, B>
+where
+    A: Transformer<Target = ()>,
+    B: Transformer<Target = ()>,
+{
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        self.second
+            .transform_matrix(&self.first.transform_matrix(x))
+    }
+
+    fn fit_transform_matrix(&mut self, x: &Matrix, _: &()) -> Matrix {
+        let transformed = self.first.fit_transform_matrix(x, &());
+        self.second.fit_transform_matrix(&transformed, &())
+    }
+
+    fn is_identity(&self) -> bool {
+        self.first.is_identity() && self.second.is_identity()
+    }
+}
+
+impl<T, E> Estimator for Pipeline<T, E>
+where
+    T: Transformer<Target = ()>,
+    E: Estimator,
+{
+    type Target = E::Target;
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &E::Target) {
+        let transformed = self.preprocessing.fit_transform_matrix(x, &());
+        self.estimator.fit_matrix(&transformed, y);
+    }
+
+    fn min_samples(&self) -> usize {
+        self.estimator.min_samples()
+    }
+
+    // The points the preprocessing takes, or the estimator without any
+    fn nan_policy(&self) -> NanPolicy {
+        if self.preprocessing.is_identity() {
+            self.estimator.nan_policy()
+        } else {
+            self.preprocessing.nan_policy()
+        }
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        if self.preprocessing.is_identity() {
+            self.estimator.n_features_in()
+        } else {
+            self.preprocessing.n_features_in()
+        }
+    }
+}
+
+impl<T, E> Predictor for Pipeline<T, E>
+where
+    T: Transformer<Target = ()>,
+    E: Predictor,
+{
+    type Output = E::Output;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<E::Output> {
+        self.estimator.predict_matrix(&self.preprocess_matrix(x))
+    }
+}
+
+impl<T, E> Transformer for Pipeline<T, E>
+where
+    T: Transformer<Target = ()>,
+    E: Transformer,
+{
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        self.estimator.transform_matrix(&self.preprocess_matrix(x))
+    }
+
+    fn fit_transform_matrix(&mut self, x: &Matrix, y: &E::Target) -> Matrix {
+        let transformed = self.preprocessing.fit_transform_matrix(x, &());
+        self.estimator.fit_transform_matrix(&transformed, y)
+    }
+}
+
+impl<T, E> Classifier for Pipeline<T, E>
+where
+    T: Transformer<Target = ()>,
+    E: Classifier,
+{
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        self.estimator
+            .predict_proba_matrix(&self.preprocess_matrix(x))
+    }
+}
@@ -0,0 +1,395 @@
+use crate::ml::builder::builder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// What transform does with a category it did not see in fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandleUnknown {
+    // Fail with UnknownCategory
+    Error,
+    // All zeros for OneHotEncoder, unknown_value for OrdinalEncoder
+    Ignore,
+    // Share one extra category with the categories seen fewer than
+    // min_frequency times in fit
+    Infrequent { min_frequency: usize },
+}
+
+// A category transform could not encode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCategory {
+    pub feature: usize,
+    pub category: String,
+}
+
+impl fmt::Display for UnknownCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown category {:?} in feature {}",
+            self.category, self.feature
+        )
+    }
+}
+
+impl std::error::Error for UnknownCategory {}
+
+// Every categorical feature becomes one 0/1 column per category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneHotEncoder {
+    pub handle_unknown: HandleUnknown,
+    // Categories of every feature in output order. Found in fit, sorted,
+    // when None.
+    pub categories: Option<Vec<Vec<String>>>,
+
+    // Current state
+    features: Vec<FeatureCategories>,
+}
+
+// Every categorical feature becomes one column of integer codes, in the
+// order of its categories
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrdinalEncoder {
+    pub handle_unknown: HandleUnknown,
+    // Code of unknown categories under HandleUnknown::Ignore
+    #[serde(with = "crate::ml::persist::non_finite")]
+    pub unknown_value: f64,
+    // Categories of every feature, code 0 first. Found in fit, sorted, when
+    // None. Set it when the order means something (low < medium < high).
+    pub categories: Option<Vec<Vec<String>>>,
+
+    // Current state
+    features: Vec<FeatureCategories>,
+}
+
+// Fitted categories of one feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FeatureCategories {
+    categories: Vec<String>,
+    // Seen in fit but grouped into the infrequent bucket
+    infrequent: Vec<String>,
+    // Position of every frequent category, and of the bucket for infrequent
+    // ones
+    codes: HashMap<String, usize>,
+    has_bucket: bool,
+}
+
+// Where a value falls among the fitted categories
+enum Code {
+    Known(usize),
+    Infrequent,
+    Unknown,
+}
+
+impl fmt::Display for OneHotEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OneHotEncoder{{ handle_unknown: {:?} }}",
+            self.handle_unknown
+        )
+    }
+}
+
+impl fmt::Display for OrdinalEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OrdinalEncoder{{ handle_unknown: {:?}, unknown_value: {} }}",
+            self.handle_unknown, self.unknown_value
+        )
+    }
+}
+
+impl Default for OneHotEncoder {
+    fn default() -> Self {
+        OneHotEncoder::new()
+    }
+}
+
+builder!(OneHotEncoder => OneHotEncoderBuilder, default, {
+    handle_unknown: HandleUnknown,
+}, some {
+    categories: Vec<Vec<String>>,
+});
+
+impl Default for OrdinalEncoder {
+    fn default() -> Self {
+        OrdinalEncoder::new()
+    }
+}
+
+builder!(OrdinalEncoder => OrdinalEncoderBuilder, default, {
+    handle_unknown: HandleUnknown,
+    unknown_value: f64,
+}, some {
+    categories: Vec<Vec<String>>,
+});
+
+impl OneHotEncoder {
+    pub fn new() -> Self {
+        OneHotEncoder {
+            handle_unknown: HandleUnknown::Error,
+            categories: None,
+            features: vec![],
+        }
+    }
+
+    pub fn with_handle_unknown(mut self, handle_unknown: HandleUnknown) -> Self {
+        self.handle_unknown = handle_unknown;
+        self
+    }
+
+    pub fn with_categories(mut self, categories: Vec<Vec<String>>) -> Self {
+        self.categories = Some(categories);
+        self
+    }
+
+    // Fitted categories of every feature, one output column each
+    pub fn categories(&self) -> Vec<&[String]> {
+        self.features.iter().map(|f| &f.categories[..]).collect()
+    }
+
+    // Categories of every feature grouped into its infrequent column
+    pub fn infrequent_categories(&self) -> Vec<&[String]> {
+        self.features.iter().map(|f| &f.infrequent[..]).collect()
+    }
+
+    // Names of the output columns, "feature_category", given the input
+    // feature names
+    pub fn feature_names_out(&self, input_names: &[&str]) -> Vec<String> {
+        self.features
+            .iter()
+            .zip(input_names)
+            .flat_map(|(feature, name)| {
+                let bucket = feature.has_bucket.then_some("infrequent");
+                feature
+                    .categories
+                    .iter()
+                    .map(String::as_str)
+                    .chain(bucket)
+                    .map(move |category| format!("{}_{}", name, category))
+            })
+            .collect()
+    }
+
+    pub fn fit<S: AsRef<str>>(&mut self, data: &[Vec<S>]) {
+        self.features = fit_features(data, self.categories.as_deref(), self.handle_unknown);
+    }
+
+    pub fn transform<S: AsRef<str>>(&self, data: &[Vec<S>]) -> Result<Vec<Point>, UnknownCategory> {
+        data.iter()
+            .map(|row| {
+                let mut encoded = vec![];
+                for (feature, (categories, value)) in self.features.iter().zip(row).enumerate() {
+                    let mut columns = vec![0.0; categories.n_columns()];
+                    match categories.code(value.as_ref()) {
+                        Code::Known(i) => columns[i] = 1.0,
+                        Code::Infrequent => columns[categories.categories.len()] = 1.0,
+                        Code::Unknown => {
+                            unknown(self.handle_unknown, feature, value.as_ref())?;
+                        }
+                    }
+                    encoded.extend(columns);
+                }
+                Ok(encoded)
+            })
+            .collect()
+    }
+
+    pub fn fit_transform<S: AsRef<str>>(
+        &mut self,
+        data: &[Vec<S>],
+    ) -> Result<Vec<Point>, UnknownCategory> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    // Category of the hot column of every feature. None when no column is
+    // set, or for the infrequent column.
+    pub fn inverse_transform(&self, data: &[Point]) -> Vec<Vec<Option<String>>> {
+        data.iter()
+            .map(|row| {
+                let mut offset = 0;
+                self.features
+                    .iter()
+                    .map(|feature| {
+                        let columns = &row[offset..offset + feature.n_columns()];
+                        offset += feature.n_columns();
+                        columns
+                            .iter()
+                            .zip(&feature.categories)
+                            .position(|(v, _)| *v > 0.5)
+                            .map(|i| feature.categories[i].clone())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl OrdinalEncoder {
+    pub fn new() -> Self {
+        OrdinalEncoder {
+            handle_unknown: HandleUnknown::Error,
+            unknown_value: f64::NAN,
+            categories: None,
+            features: vec![],
+        }
+    }
+
+    pub fn with_handle_unknown(mut self, handle_unknown: HandleUnknown) -> Self {
+        self.handle_unknown = handle_unknown;
+        self
+    }
+
+    pub fn with_categories(mut self, categories: Vec<Vec<String>>) -> Self {
+        self.categories = Some(categories);
+        self
+    }
+
+    // Fitted categories of every feature, in code order
+    pub fn categories(&self) -> Vec<&[String]> {
+        self.features.iter().map(|f| &f.categories[..]).collect()
+    }
+
+    // Categories of every feature that share the code after the last
+    // category
+    pub fn infrequent_categories(&self) -> Vec<&[String]> {
+        self.features.iter().map(|f| &f.infrequent[..]).collect()
+    }
+
+    pub fn fit<S: AsRef<str>>(&mut self, data: &[Vec<S>]) {
+        self.features = fit_features(data, self.categories.as_deref(), self.handle_unknown);
+    }
+
+    pub fn transform<S: AsRef<str>>(&self, data: &[Vec<S>]) -> Result<Vec<Point>, UnknownCategory> {
+        data.iter()
+            .map(|row| {
+                self.features
+                    .iter()
+                    .zip(row)
+                    .enumerate()
+                    .map(
+                        |(feature, (categories, value))| match categories.code(value.as_ref()) {
+                            Code::Known(i) => Ok(i as f64),
+                            Code::Infrequent => Ok(categories.categories.len() as f64),
+                            Code::Unknown => unknown(self.handle_unknown, feature, value.as_ref())
+                                .map(|_| self.unknown_value),
+                        },
+                    )
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn fit_transform<S: AsRef<str>>(
+        &mut self,
+        data: &[Vec<S>],
+    ) -> Result<Vec<Point>, UnknownCategory> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    // Category of every code. None for the infrequent code, unknown_value
+    // and codes out of range.
+    pub fn inverse_transform(&self, data: &[Point]) -> Vec<Vec<Option<String>>> {
+        data.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&self.features)
+                    .map(|(&code, feature)| {
+                        if code.is_finite() && code >= 0.0 {
+                            feature.categories.get(code.round() as usize).cloned()
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl FeatureCategories {
+    // One column per category, plus the infrequent bucket
+    fn n_columns(&self) -> usize {
+        self.categories.len() + self.has_bucket as usize
+    }
+
+    fn code(&self, value: &str) -> Code {
+        match self.codes.get(value) {
+            Some(&i) if i < self.categories.len() => Code::Known(i),
+            Some(_) => Code::Infrequent,
+            None if self.has_bucket => Code::Infrequent,
+            None => Code::Unknown,
+        }
+    }
+}
+
+// Categories of every feature, given or found sorted, with the rare ones
+// split off when grouping infrequent categories
+fn fit_features<S: AsRef<str>>(
+    data: &[Vec<S>],
+    given: Option<&[Vec<String>]>,
+    handle_unknown: HandleUnknown,
+) -> Vec<FeatureCategories> {
+    let dim = given.map_or_else(|| data.first().map_or(0, |row| row.len()), |g| g.len());
+    (0..dim)
+        .map(|j| {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for row in data {
+                *counts.entry(row[j].as_ref()).or_default() += 1;
+            }
+            let candidates = match given {
+                Some(categories) => categories[j].clone(),
+                None => {
+                    let mut found: Vec<String> = counts.keys().map(|c| c.to_string()).collect();
+                    found.sort();
+                    found
+                }
+            };
+
+            let (categories, infrequent, has_bucket) = match handle_unknown {
+                HandleUnknown::Infrequent { min_frequency } => {
+                    let (frequent, rare): (Vec<String>, Vec<String>) =
+                        candidates.into_iter().partition(|c| {
+                            counts.get(c.as_str()).copied().unwrap_or(0) >= min_frequency
+                        });
+                    (frequent, rare, true)
+                }
+                _ => (candidates, vec![], false),
+            };
+            let codes = categories
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (c.clone(), i))
+                .chain(infrequent.iter().map(|c| (c.clone(), categories.len())))
+                .collect();
+            FeatureCategories {
+                categories,
+                infrequent,
+                codes,
+                has_bucket,
+            }
+        })
+        .collect()
+}
+
+// Ok under the Ignore policy, the error otherwise
+fn unknown(
+    handle_unknown: HandleUnknown,
+    feature: usize,
+    category: &str,
+) -> Result<(), UnknownCategory> {
+    match handle_unknown {
+        HandleUnknown::Ignore => Ok(()),
+        _ => Err(UnknownCategory {
+            feature,
+            category: category.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,307 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Transformer};
+use crate::ml::builder::builder;
+use crate::ml::neighbors::knn::{vote_weights, Weights};
+use crate::ml::neighbors::Neighbor;
+use crate::ml::utils::quantile;
+use crate::ml::validation::NanPolicy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const N_NEIGHBORS: usize = 5;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Strategy {
+    Mean,
+    Median,
+    // Smallest of the most common values
+    MostFrequent,
+    Constant(f64),
+}
+
+// Replaces missing values (NaN) with a per-feature statistic of the values
+// seen in fit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimpleImputer {
+    pub strategy: Strategy,
+
+    // Current state
+    // Fill value of every feature
+    statistics: Vec<f64>,
+}
+
+// Replaces missing values (NaN) with the mean of that feature over the k
+// nearest training rows that have it. Distances skip coordinates missing
+// in either row and are scaled up for them (nan-Euclidean).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnnImputer {
+    pub k: usize,
+    pub weights: Weights,
+
+    // Current state
+    data: Matrix,
+    // Fill value when no neighbor has the feature
+    means: Vec<f64>,
+}
+
+impl fmt::Display for SimpleImputer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SimpleImputer{{ strategy: {:?} }}", self.strategy)
+    }
+}
+
+impl fmt::Display for KnnImputer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "KnnImputer{{ k: {}, weights: {:?} }}",
+            self.k, self.weights
+        )
+    }
+}
+
+impl Default for SimpleImputer {
+    fn default() -> Self {
+        SimpleImputer::new(Strategy::Mean)
+    }
+}
+
+builder!(SimpleImputer => SimpleImputerBuilder, default, {
+    strategy: Strategy,
+});
+
+impl Default for KnnImputer {
+    fn default() -> Self {
+        KnnImputer::new(N_NEIGHBORS)
+    }
+}
+
+builder!(KnnImputer => KnnImputerBuilder, default, {
+    k: usize,
+    weights: Weights,
+});
+
+impl SimpleImputer {
+    pub fn new(strategy: Strategy) -> Self {
+        SimpleImputer {
+            strategy,
+            statistics: vec![],
+        }
+    }
+
+    pub fn statistics(&self) -> &[f64] {
+        &self.statistics
+    }
+
+    // Features with no values at all are filled with 0
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        self.statistics = present_values(data)
+            .iter()
+            .map(|values| {
+                let statistic = match self.strategy {
+                    Strategy::Mean => values.iter().sum::<f64>() / values.len() as f64,
+                    Strategy::Median => quantile(values, 0.5),
+                    Strategy::MostFrequent => most_frequent(values),
+                    Strategy::Constant(value) => value,
+                };
+                if statistic.is_nan() {
+                    0.0
+                } else {
+                    statistic
+                }
+            })
+            .collect();
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        let mut filled = data.clone();
+        for row in filled.iter_rows_mut() {
+            for (v, &fill) in row.iter_mut().zip(&self.statistics) {
+                if v.is_nan() {
+                    *v = fill;
+                }
+            }
+        }
+        filled
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+}
+
+impl Estimator for SimpleImputer {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        SimpleImputer::fit_matrix(self, x);
+    }
+
+    fn nan_policy(&self) -> NanPolicy {
+        NanPolicy::AllowMissing
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.statistics.is_empty()).then_some(self.statistics.len())
+    }
+}
+
+impl Transformer for SimpleImputer {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        SimpleImputer::transform_matrix(self, x)
+    }
+}
+
+impl KnnImputer {
+    pub fn new(k: usize) -> Self {
+        KnnImputer {
+            k,
+            weights: Weights::Uniform,
+            data: Matrix::default(),
+            means: vec![],
+        }
+    }
+
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        self.data = data.clone();
+        let mut mean_imputer = SimpleImputer::new(Strategy::Mean);
+        mean_imputer.fit_matrix(data);
+        self.means = mean_imputer.statistics;
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        let mut filled = data.clone();
+        for row in filled.iter_rows_mut() {
+            self.impute(row);
+        }
+        filled
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    // Fills the NaNs of row in place
+    fn impute(&self, row: &mut [f64]) {
+        if !row.iter().any(|v| v.is_nan()) {
+            return;
+        }
+        let distances: Vec<Option<f64>> = self
+            .data
+            .iter_rows()
+            .map(|other| nan_euclidean(row, other))
+            .collect();
+
+        let filled: Vec<f64> = row
+            .iter()
+            .zip(&self.means)
+            .enumerate()
+            .map(|(feature, (&value, &mean))| {
+                if !value.is_nan() {
+                    return value;
+                }
+                // Nearest training rows that have this feature
+                let mut donors: Vec<Neighbor> = distances
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !self.data.get(*i, feature).is_nan())
+                    .filter_map(|(index, d)| d.map(|distance| Neighbor { index, distance }))
+                    .collect();
+                if donors.is_empty() {
+                    return mean;
+                }
+                donors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+                donors.truncate(self.k.max(1));
+
+                let weights = vote_weights(&donors, self.weights);
+                let total: f64 = weights.iter().sum();
+                donors
+                    .iter()
+                    .zip(&weights)
+                    .map(|(n, w)| w * self.data.get(n.index, feature))
+                    .sum::<f64>()
+                    / total
+            })
+            .collect();
+        row.copy_from_slice(&filled);
+    }
+}
+
+impl Estimator for KnnImputer {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        KnnImputer::fit_matrix(self, x);
+    }
+
+    fn nan_policy(&self) -> NanPolicy {
+        NanPolicy::AllowMissing
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.means.is_empty()).then_some(self.means.len())
+    }
+}
+
+impl Transformer for KnnImputer {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        KnnImputer::transform_matrix(self, x)
+    }
+}
+
+// Non-NaN values of every feature
+fn present_values(data: &Matrix) -> Vec<Vec<f64>> {
+    (0..data.n_cols())
+        .map(|j| data.column(j).filter(|v| !v.is_nan()).collect())
+        .collect()
+}
+
+// Smallest of the most common values, NaN for no values
+fn most_frequent(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mut best = (f64::NAN, 0);
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > best.1 {
+            best = (run[0], run.len());
+        }
+    }
+    best.0
+}
+
+// Euclidean distance over the coordinates present in both rows, scaled by
+// sqrt(dim / present). None when the rows share no coordinate.
+fn nan_euclidean(a: &[f64], b: &[f64]) -> Option<f64> {
+    let (sum, present) = a
+        .iter()
+        .zip(b)
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .fold((0.0, 0), |(sum, n), (x, y)| (sum + (x - y).powi(2), n + 1));
+    (present > 0).then(|| (sum * a.len() as f64 / present as f64).sqrt())
+}
@@ -0,0 +1,4 @@
+pub mod encoders;
+pub mod imputers;
+pub mod polynomial;
+pub mod scalers;
@@ -0,0 +1,178 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Transformer};
+use crate::ml::builder::builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Expands every point into all monomials of its features up to degree, so
+// linear models can fit curves: [a, b] at degree 2 becomes
+// [1, a, b, a², ab, b²]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolynomialFeatures {
+    pub degree: usize,
+    // Only products of distinct features: a·b but not a² or b²
+    pub interaction_only: bool,
+    // Start with the constant 1 column
+    pub include_bias: bool,
+
+    // Current state
+    // Exponent of every input feature in every output column
+    powers: Vec<Vec<usize>>,
+}
+
+impl fmt::Display for PolynomialFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PolynomialFeatures{{ degree: {}, interaction_only: {}, include_bias: {} }}",
+            self.degree, self.interaction_only, self.include_bias
+        )
+    }
+}
+
+impl Default for PolynomialFeatures {
+    fn default() -> Self {
+        PolynomialFeatures::new(2)
+    }
+}
+
+builder!(PolynomialFeatures => PolynomialFeaturesBuilder, default, {
+    degree: usize,
+    interaction_only: bool,
+    include_bias: bool,
+});
+
+impl PolynomialFeatures {
+    pub fn new(degree: usize) -> Self {
+        PolynomialFeatures {
+            degree,
+            interaction_only: false,
+            include_bias: true,
+            powers: vec![],
+        }
+    }
+
+    pub fn with_interaction_only(mut self, interaction_only: bool) -> Self {
+        self.interaction_only = interaction_only;
+        self
+    }
+
+    pub fn with_include_bias(mut self, include_bias: bool) -> Self {
+        self.include_bias = include_bias;
+        self
+    }
+
+    pub fn powers(&self) -> &[Vec<usize>] {
+        &self.powers
+    }
+
+    pub fn n_output_features(&self) -> usize {
+        self.powers.len()
+    }
+
+    // Names of the output columns such as "a^2 b", given the input feature
+    // names
+    pub fn feature_names_out(&self, input_names: &[&str]) -> Vec<String> {
+        self.powers
+            .iter()
+            .map(|exponents| {
+                let terms: Vec<String> = exponents
+                    .iter()
+                    .zip(input_names)
+                    .filter(|(&e, _)| e > 0)
+                    .map(|(&e, name)| match e {
+                        1 => name.to_string(),
+                        _ => format!("{}^{}", name, e),
+                    })
+                    .collect();
+                if terms.is_empty() {
+                    "1".to_string()
+                } else {
+                    terms.join(" ")
+                }
+            })
+            .collect()
+    }
+
+    // Monomials by increasing degree, then in lexicographic order of the
+    // features they use
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        let dim = data.n_cols();
+        let start = if self.include_bias { 0 } else { 1 };
+        self.powers = (start..=self.degree)
+            .flat_map(|degree| {
+                let mut combinations = vec![];
+                let max_factor = if self.interaction_only { 1 } else { degree };
+                combine(&mut vec![0; dim], 0, degree, max_factor, &mut combinations);
+                combinations
+            })
+            .collect();
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        Matrix::from_fn(data.n_rows(), self.powers.len(), |i, j| {
+            data.row(i)
+                .iter()
+                .zip(&self.powers[j])
+                .map(|(v, &e)| v.powi(e as i32))
+                .product()
+        })
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+}
+
+impl Estimator for PolynomialFeatures {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        PolynomialFeatures::fit_matrix(self, x);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.powers.first().map(Vec::len)
+    }
+}
+
+impl Transformer for PolynomialFeatures {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        PolynomialFeatures::transform_matrix(self, x)
+    }
+}
+
+// Every way to spread the remaining degree over features from `feature`
+// on, each taking at most max_factor, earlier features first
+fn combine(
+    exponents: &mut Vec<usize>,
+    feature: usize,
+    remaining: usize,
+    max_factor: usize,
+    out: &mut Vec<Vec<usize>>,
+) {
+    if remaining == 0 {
+        out.push(exponents.clone());
+        return;
+    }
+    if feature == exponents.len() {
+        return;
+    }
+    for e in (0..=remaining.min(max_factor)).rev() {
+        exponents[feature] = e;
+        combine(exponents, feature + 1, remaining - e, max_factor, out);
+    }
+    exponents[feature] = 0;
+}
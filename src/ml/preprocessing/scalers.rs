@@ -0,0 +1,527 @@
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Transformer};
+use crate::ml::builder::builder;
+use crate::ml::onnx::{self, OnnxError, ToOnnx};
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::quantile;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Removes the mean and scales every feature to unit variance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandardScaler {
+    pub with_mean: bool,
+    pub with_std: bool,
+
+    // Current state
+    mean: Vec<f64>,
+    // Standard deviation of every feature, 1 for constant features
+    scale: Vec<f64>,
+}
+
+// Maps every feature linearly onto feature_range, by default [0, 1]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinMaxScaler {
+    pub feature_range: (f64, f64),
+
+    // Current state
+    data_min: Vec<f64>,
+    data_max: Vec<f64>,
+}
+
+// Centers on the median and scales by the interquartile range, so outliers
+// barely move the result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustScaler {
+    pub with_centering: bool,
+    pub with_scaling: bool,
+    // Percentiles whose distance is the scale, by default (25, 75)
+    pub quantile_range: (f64, f64),
+
+    // Current state
+    center: Vec<f64>,
+    scale: Vec<f64>,
+}
+
+impl fmt::Display for StandardScaler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "StandardScaler{{ with_mean: {}, with_std: {} }}",
+            self.with_mean, self.with_std
+        )
+    }
+}
+
+impl fmt::Display for MinMaxScaler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MinMaxScaler{{ feature_range: {:?} }}",
+            self.feature_range
+        )
+    }
+}
+
+impl fmt::Display for RobustScaler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RobustScaler{{ with_centering: {}, with_scaling: {}, quantile_range: {:?} }}",
+            self.with_centering, self.with_scaling, self.quantile_range
+        )
+    }
+}
+
+impl Default for StandardScaler {
+    fn default() -> Self {
+        StandardScaler::new()
+    }
+}
+
+builder!(StandardScaler => StandardScalerBuilder, default, {
+    with_mean: bool,
+    with_std: bool,
+});
+
+impl Default for MinMaxScaler {
+    fn default() -> Self {
+        MinMaxScaler::new()
+    }
+}
+
+builder!(MinMaxScaler => MinMaxScalerBuilder, default, {
+    feature_range: (f64, f64),
+});
+
+impl Default for RobustScaler {
+    fn default() -> Self {
+        RobustScaler::new()
+    }
+}
+
+builder!(RobustScaler => RobustScalerBuilder, default, {
+    with_centering: bool,
+    with_scaling: bool,
+    quantile_range: (f64, f64),
+});
+
+impl StandardScaler {
+    pub fn new() -> Self {
+        StandardScaler {
+            with_mean: true,
+            with_std: true,
+            mean: vec![],
+            scale: vec![],
+        }
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    pub fn scale(&self) -> &[f64] {
+        &self.scale
+    }
+
+    // NaNs are ignored when fitting and kept as they are when transforming
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        let features = feature_values(data);
+        self.mean = features
+            .iter()
+            .map(|values| match values.len() {
+                0 => 0.0,
+                n => values.iter().sum::<f64>() / n as f64,
+            })
+            .collect();
+        self.scale = features
+            .iter()
+            .zip(&self.mean)
+            .map(|(values, mean)| {
+                let n = values.len().max(1) as f64;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                nonzero_scale(variance.sqrt())
+            })
+            .collect();
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        scale(data, &self.offset(), &self.divisor())
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    pub fn inverse_transform(&self, data: &[Point]) -> Vec<Point> {
+        self.inverse_transform_matrix(&point_matrix(data))
+            .to_points()
+    }
+
+    pub fn inverse_transform_matrix(&self, data: &Matrix) -> Matrix {
+        unscale(data, &self.offset(), &self.divisor())
+    }
+
+    fn offset(&self) -> Vec<f64> {
+        if self.with_mean {
+            self.mean.clone()
+        } else {
+            vec![0.0; self.mean.len()]
+        }
+    }
+
+    fn divisor(&self) -> Vec<f64> {
+        if self.with_std {
+            self.scale.clone()
+        } else {
+            vec![1.0; self.scale.len()]
+        }
+    }
+}
+
+impl Estimator for StandardScaler {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        StandardScaler::fit_matrix(self, x);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.mean.is_empty()).then_some(self.mean.len())
+    }
+}
+
+impl Transformer for StandardScaler {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        StandardScaler::transform_matrix(self, x)
+    }
+}
+
+impl ToOnnx for StandardScaler {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.mean.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::scaler(&self.offset(), &self.divisor()))
+    }
+}
+
+impl FromSklearn for StandardScaler {
+    const CLASS: &'static str = "StandardScaler";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let mean = attributes.optional::<Vec<f64>>("mean_")?;
+        let scale = attributes.optional::<Vec<f64>>("scale_")?;
+        let n_features = match (&mean, &scale) {
+            (Some(mean), _) => mean.len(),
+            (None, Some(scale)) => scale.len(),
+            (None, None) => return Err(SklearnError::Missing("mean_".to_string())),
+        };
+        if scale
+            .as_ref()
+            .is_some_and(|scale| scale.len() != n_features)
+        {
+            return Err(sklearn::invalid("scale_", "not as long as mean_"));
+        }
+        sklearn::check_features(attributes, n_features, "mean_")?;
+        Ok(StandardScaler {
+            with_mean: attributes.optional("with_mean")?.unwrap_or(mean.is_some()),
+            with_std: attributes.optional("with_std")?.unwrap_or(scale.is_some()),
+            mean: mean.unwrap_or_else(|| vec![0.0; n_features]),
+            scale: scale.unwrap_or_else(|| vec![1.0; n_features]),
+        })
+    }
+}
+
+impl MinMaxScaler {
+    pub fn new() -> Self {
+        MinMaxScaler {
+            feature_range: (0.0, 1.0),
+            data_min: vec![],
+            data_max: vec![],
+        }
+    }
+
+    pub fn with_feature_range(mut self, min: f64, max: f64) -> Self {
+        self.feature_range = (min, max);
+        self
+    }
+
+    pub fn data_min(&self) -> &[f64] {
+        &self.data_min
+    }
+
+    pub fn data_max(&self) -> &[f64] {
+        &self.data_max
+    }
+
+    // NaNs are ignored when fitting and kept as they are when transforming
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        let features = feature_values(data);
+        let bound = |values: &Vec<f64>, pick: fn(f64, f64) -> f64| {
+            values.iter().copied().reduce(pick).unwrap_or(0.0)
+        };
+        self.data_min = features.iter().map(|v| bound(v, f64::min)).collect();
+        self.data_max = features.iter().map(|v| bound(v, f64::max)).collect();
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        let (offset, divisor) = self.affine();
+        scale(data, &offset, &divisor)
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    pub fn inverse_transform(&self, data: &[Point]) -> Vec<Point> {
+        self.inverse_transform_matrix(&point_matrix(data))
+            .to_points()
+    }
+
+    pub fn inverse_transform_matrix(&self, data: &Matrix) -> Matrix {
+        let (offset, divisor) = self.affine();
+        unscale(data, &offset, &divisor)
+    }
+
+    // (x - min) / (max - min) * (high - low) + low, written as
+    // (x - offset) / divisor
+    fn affine(&self) -> (Vec<f64>, Vec<f64>) {
+        let (low, high) = self.feature_range;
+        self.data_min
+            .iter()
+            .zip(&self.data_max)
+            .map(|(min, max)| {
+                let divisor = nonzero_scale(max - min) / (high - low);
+                (min - low * divisor, divisor)
+            })
+            .unzip()
+    }
+}
+
+impl Estimator for MinMaxScaler {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        MinMaxScaler::fit_matrix(self, x);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.data_min.is_empty()).then_some(self.data_min.len())
+    }
+}
+
+impl Transformer for MinMaxScaler {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        MinMaxScaler::transform_matrix(self, x)
+    }
+}
+
+impl ToOnnx for MinMaxScaler {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.data_min.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        let (offset, divisor) = self.affine();
+        Ok(onnx::scaler(&offset, &divisor))
+    }
+}
+
+impl FromSklearn for MinMaxScaler {
+    const CLASS: &'static str = "MinMaxScaler";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let data_min: Vec<f64> = attributes.get("data_min_")?;
+        let data_max: Vec<f64> = attributes.get("data_max_")?;
+        if data_max.len() != data_min.len() {
+            return Err(sklearn::invalid("data_max_", "not as long as data_min_"));
+        }
+        sklearn::check_features(attributes, data_min.len(), "data_min_")?;
+        Ok(MinMaxScaler {
+            feature_range: attributes.optional("feature_range")?.unwrap_or((0.0, 1.0)),
+            data_min,
+            data_max,
+        })
+    }
+}
+
+impl RobustScaler {
+    pub fn new() -> Self {
+        RobustScaler {
+            with_centering: true,
+            with_scaling: true,
+            quantile_range: (25.0, 75.0),
+            center: vec![],
+            scale: vec![],
+        }
+    }
+
+    pub fn with_quantile_range(mut self, low: f64, high: f64) -> Self {
+        self.quantile_range = (low, high);
+        self
+    }
+
+    // Median of every feature
+    pub fn center(&self) -> &[f64] {
+        &self.center
+    }
+
+    // Interquartile range of every feature, 1 for constant features
+    pub fn scale(&self) -> &[f64] {
+        &self.scale
+    }
+
+    // NaNs are ignored when fitting and kept as they are when transforming
+    pub fn fit(&mut self, data: &[Point]) {
+        self.fit_matrix(&point_matrix(data));
+    }
+
+    pub fn fit_matrix(&mut self, data: &Matrix) {
+        let features = feature_values(data);
+        let (low, high) = self.quantile_range;
+        self.center = features
+            .iter()
+            .map(|values| or_zero(quantile(values, 0.5)))
+            .collect();
+        self.scale = features
+            .iter()
+            .map(|values| {
+                let range = quantile(values, high / 100.0) - quantile(values, low / 100.0);
+                nonzero_scale(or_zero(range))
+            })
+            .collect();
+    }
+
+    pub fn transform(&self, data: &[Point]) -> Vec<Point> {
+        self.transform_matrix(&point_matrix(data)).to_points()
+    }
+
+    pub fn transform_matrix(&self, data: &Matrix) -> Matrix {
+        scale(data, &self.offset(), &self.divisor())
+    }
+
+    pub fn fit_transform(&mut self, data: &[Point]) -> Vec<Point> {
+        self.fit(data);
+        self.transform(data)
+    }
+
+    pub fn inverse_transform(&self, data: &[Point]) -> Vec<Point> {
+        self.inverse_transform_matrix(&point_matrix(data))
+            .to_points()
+    }
+
+    pub fn inverse_transform_matrix(&self, data: &Matrix) -> Matrix {
+        unscale(data, &self.offset(), &self.divisor())
+    }
+
+    fn offset(&self) -> Vec<f64> {
+        if self.with_centering {
+            self.center.clone()
+        } else {
+            vec![0.0; self.center.len()]
+        }
+    }
+
+    fn divisor(&self) -> Vec<f64> {
+        if self.with_scaling {
+            self.scale.clone()
+        } else {
+            vec![1.0; self.scale.len()]
+        }
+    }
+}
+
+impl Estimator for RobustScaler {
+    type Target = ();
+
+    fn fit_matrix(&mut self, x: &Matrix, _: &()) {
+        RobustScaler::fit_matrix(self, x);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.center.is_empty()).then_some(self.center.len())
+    }
+}
+
+impl Transformer for RobustScaler {
+    fn transform_matrix(&self, x: &Matrix) -> Matrix {
+        RobustScaler::transform_matrix(self, x)
+    }
+}
+
+impl ToOnnx for RobustScaler {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.center.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::scaler(&self.offset(), &self.divisor()))
+    }
+}
+
+// Non-NaN values of every feature
+fn feature_values(data: &Matrix) -> Vec<Vec<f64>> {
+    (0..data.n_cols())
+        .map(|j| data.column(j).filter(|v| !v.is_nan()).collect())
+        .collect()
+}
+
+// Constant features are left unscaled rather than divided by zero
+fn nonzero_scale(scale: f64) -> f64 {
+    if scale.abs() < f64::EPSILON {
+        1.0
+    } else {
+        scale
+    }
+}
+
+// Features that were all NaN
+fn or_zero(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value
+    }
+}
+
+// (x - offset) / divisor for every feature
+fn scale(data: &Matrix, offset: &[f64], divisor: &[f64]) -> Matrix {
+    let mut scaled = data.clone();
+    for row in scaled.iter_rows_mut() {
+        for ((x, o), d) in row.iter_mut().zip(offset).zip(divisor) {
+            *x = (*x - o) / d;
+        }
+    }
+    scaled
+}
+
+// x * divisor + offset, undoing scale
+fn unscale(data: &Matrix, offset: &[f64], divisor: &[f64]) -> Matrix {
+    let mut unscaled = data.clone();
+    for row in unscaled.iter_rows_mut() {
+        for ((x, o), d) in row.iter_mut().zip(offset).zip(divisor) {
+            *x = *x * d + o;
+        }
+    }
+    unscaled
+}
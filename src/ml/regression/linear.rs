@@ -0,0 +1,444 @@
+use super::{dot_f64, predict_linear, LinearModel};
+use crate::math::float::{cast_slice, Float};
+use crate::math::matrix::Matrix as DenseMatrix;
+use crate::ml::base::{Estimator, IncrementalEstimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::early_stopping::EarlyStopping;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::onnx::{self, OnnxError, ToOnnx};
+use crate::ml::optim::{Optimizer, Sgd};
+use crate::ml::params::{param_value, Configurable, ParamSpec, Params};
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::{dot, seeded_rng, solve, Matrix};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const EPSILON: f64 = 1e-6;
+const MAX_ITER: usize = 1000;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Solver {
+    // Closed-form least squares via the normal equation
+    NormalEquation,
+    // Gradient descent on the squared error with the model's optimizer, one
+    // sample at a time
+    Sgd,
+}
+
+// Ordinary least squares: y = X * coefficients + intercept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinearRegression<O: Optimizer = Sgd> {
+    pub fit_intercept: bool,
+    pub solver: Solver,
+    // SGD and partial_fit only
+    pub optimizer: O,
+    pub max_iter: usize,
+    // SGD stops once an epoch's loss is within tolerance of the last one,
+    // unless early_stopping is set
+    pub tolerance: f64,
+    #[serde(default)]
+    pub early_stopping: Option<EarlyStopping>,
+    pub random_state: Option<u64>,
+
+    // Current state
+    coefficients: Vec<f64>,
+    intercept: f64,
+    n_iter: usize,
+    #[serde(default)]
+    best_iteration: Option<usize>,
+}
+
+impl<O: Optimizer> fmt::Display for LinearRegression<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LinearRegression{{ fit_intercept: {}, solver: {:?}, optimizer: {:?} }}",
+            self.fit_intercept, self.solver, self.optimizer
+        )
+    }
+}
+
+impl Default for LinearRegression {
+    fn default() -> Self {
+        LinearRegression::new()
+    }
+}
+
+builder!(LinearRegression => LinearRegressionBuilder, default, {
+    fit_intercept: bool,
+    solver: Solver,
+    optimizer: Sgd,
+    max_iter: usize,
+    tolerance: f64,
+}, some {
+    early_stopping: EarlyStopping,
+    random_state: u64,
+});
+
+impl LinearRegression {
+    pub fn new() -> Self {
+        LinearRegression::with_optimizer(Sgd::default())
+    }
+}
+
+impl<O: Optimizer> LinearRegression<O> {
+    pub fn with_optimizer(optimizer: O) -> Self {
+        LinearRegression {
+            fit_intercept: true,
+            solver: Solver::NormalEquation,
+            optimizer,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            early_stopping: None,
+            random_state: None,
+            coefficients: vec![],
+            intercept: 0.0,
+            n_iter: 0,
+            best_iteration: None,
+        }
+    }
+
+    pub fn with_solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    // Epochs run by SGD, 0 for the closed-form solver, plus one per
+    // partial_fit batch
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    // SGD epoch with the lowest monitored loss, counted from 1. None unless
+    // early stopping was enabled.
+    pub fn best_iteration(&self) -> Option<usize> {
+        self.best_iteration
+    }
+
+    fn fit_sgd<T: Float>(&mut self, x: &DenseMatrix<T>, y: &[f64], notifier: &mut Notifier) {
+        let mut rng = seeded_rng(self.random_state);
+        let (validation, mut order) = match &self.early_stopping {
+            Some(early_stopping) => early_stopping.split(x.n_rows(), &mut rng),
+            None => (vec![], (0..x.n_rows()).collect()),
+        };
+        let mut tracker = self
+            .early_stopping
+            .map(|early_stopping| early_stopping.tracker());
+        let dim = x.n_cols();
+        // Coefficients followed by the intercept
+        let mut params = vec![0.0; dim + 1];
+        let mut optimizer = self.optimizer.clone();
+        optimizer.reset();
+        let mut previous_loss = f64::INFINITY;
+
+        while self.n_iter < self.max_iter {
+            order.shuffle(&mut rng);
+            let loss = sgd_epoch(
+                x,
+                y,
+                &order,
+                &mut params,
+                &mut optimizer,
+                self.fit_intercept,
+            );
+            self.n_iter += 1;
+            let validation_loss =
+                (!validation.is_empty()).then(|| squared_error(x, y, &validation, &params));
+            notifier.epoch(|| {
+                let metrics = FitMetrics::new(self.n_iter).with("loss", loss);
+                match validation_loss {
+                    Some(loss) => metrics.with("validation_loss", loss),
+                    None => metrics,
+                }
+            });
+            let stop = match &mut tracker {
+                Some(tracker) => {
+                    let stop = tracker.update(validation_loss.unwrap_or(loss));
+                    self.best_iteration = Some(tracker.best_iteration());
+                    stop
+                }
+                None => (previous_loss - loss).abs() < self.tolerance,
+            };
+            if stop {
+                notifier.converged();
+                break;
+            }
+            previous_loss = loss;
+        }
+        self.intercept = params.pop().unwrap_or(0.0);
+        self.coefficients = params;
+    }
+}
+
+impl<O: Optimizer> IncrementalEstimator for LinearRegression<O> {
+    // One SGD epoch over the batch, in order, whatever the solver. Continues
+    // from the fitted coefficients, and the optimizer field keeps its state
+    // from batch to batch.
+    fn partial_fit_matrix(&mut self, x: &DenseMatrix, y: &[f64]) {
+        if x.n_rows() == 0 {
+            return;
+        }
+        let dim = x.n_cols();
+        let mut params = if self.coefficients.len() == dim {
+            self.coefficients.clone()
+        } else {
+            vec![0.0; dim]
+        };
+        params.push(if self.fit_intercept {
+            self.intercept
+        } else {
+            0.0
+        });
+        let order: Vec<usize> = (0..x.n_rows()).collect();
+        sgd_epoch(
+            x,
+            y,
+            &order,
+            &mut params,
+            &mut self.optimizer,
+            self.fit_intercept,
+        );
+        self.n_iter += 1;
+        self.intercept = params.pop().unwrap_or(0.0);
+        self.coefficients = params;
+    }
+}
+
+impl<O: Optimizer> Configurable for LinearRegression<O> {
+    fn param_specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::bool("fit_intercept", true),
+            ParamSpec::int("max_iter", MAX_ITER).with_min(1.0),
+            ParamSpec::float("tolerance", EPSILON).with_min(0.0),
+        ]
+    }
+
+    fn params(&self) -> Params {
+        Params::default()
+            .with("fit_intercept", if self.fit_intercept { 1.0 } else { 0.0 })
+            .with("max_iter", self.max_iter as f64)
+            .with("tolerance", self.tolerance)
+    }
+
+    fn set_params(&mut self, params: &Params) {
+        let specs = Self::param_specs();
+        if let Some(fit_intercept) = param_value(&specs, params, "fit_intercept") {
+            self.fit_intercept = fit_intercept != 0.0;
+        }
+        if let Some(max_iter) = param_value(&specs, params, "max_iter") {
+            self.max_iter = max_iter as usize;
+        }
+        if let Some(tolerance) = param_value(&specs, params, "tolerance") {
+            self.tolerance = tolerance;
+        }
+    }
+}
+
+impl<O: Optimizer> LinearRegression<O> {
+    // Estimator::fit on the rows of a dense matrix of any float type, e.g.
+    // Matrix<f32> for half the memory. The normal equation takes the
+    // products of the rows in T; SGD reads the rows in place and steps in
+    // f64. The coefficients are f64 whatever T.
+    pub fn fit_matrix<T: Float>(&mut self, x: &DenseMatrix<T>, y: &[f64]) {
+        self.fit_notified(x, y, &mut Notifier::none());
+    }
+
+    fn fit_notified<T: Float>(&mut self, x: &DenseMatrix<T>, y: &[f64], notifier: &mut Notifier) {
+        self.coefficients.clear();
+        self.intercept = 0.0;
+        self.n_iter = 0;
+        self.best_iteration = None;
+        if x.n_rows() == 0 {
+            return;
+        }
+
+        match self.solver {
+            Solver::NormalEquation => {
+                (self.coefficients, self.intercept) =
+                    solve_normal_equation(x, y, self.fit_intercept, 0.0);
+            }
+            Solver::Sgd => self.fit_sgd(x, y, notifier),
+        }
+    }
+
+    // Predictor::predict on the rows of a dense matrix of any float type,
+    // e.g. Matrix<f32>, with the products in T
+    pub fn predict_matrix<T: Float>(&self, x: &DenseMatrix<T>) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+}
+
+impl<O: Optimizer> Estimator for LinearRegression<O> {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &DenseMatrix, y: &[f64]) {
+        LinearRegression::fit_matrix(self, x, y);
+    }
+
+    // A line with an intercept needs two points
+    fn min_samples(&self) -> usize {
+        if self.fit_intercept {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.coefficients.is_empty()).then_some(self.coefficients.len())
+    }
+}
+
+// An epoch per pass of SGD, reporting its mean squared error. The
+// closed-form solver reports nothing.
+impl<O: Optimizer> IterativeEstimator for LinearRegression<O> {
+    fn fit_observed_matrix(&mut self, x: &DenseMatrix, y: &[f64], observer: &mut dyn FitObserver) {
+        self.fit_notified(x, y, &mut Notifier::new(observer));
+    }
+}
+
+impl<O: Optimizer> Predictor for LinearRegression<O> {
+    type Output = f64;
+
+    fn predict(&self, x: &[Point]) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+
+    fn predict_matrix(&self, x: &DenseMatrix) -> Vec<f64> {
+        LinearRegression::predict_matrix(self, x)
+    }
+}
+
+impl<O: Optimizer> LinearModel for LinearRegression<O> {
+    fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    fn intercept(&self) -> f64 {
+        self.intercept
+    }
+}
+
+impl<O: Optimizer> ToOnnx for LinearRegression<O> {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.coefficients.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::linear_regressor(&self.coefficients, self.intercept))
+    }
+}
+
+impl FromSklearn for LinearRegression {
+    const CLASS: &'static str = "LinearRegression";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let (coefficients, intercept) = sklearn::linear_attributes(attributes)?;
+        let mut model = LinearRegression::new();
+        model.fit_intercept = attributes.optional("fit_intercept")?.unwrap_or(true);
+        model.coefficients = coefficients;
+        model.intercept = intercept;
+        Ok(model)
+    }
+}
+
+// Mean squared error of the samples at indices, params holding the
+// coefficients followed by the intercept
+fn squared_error<T: Float>(
+    x: &DenseMatrix<T>,
+    y: &[f64],
+    indices: &[usize],
+    params: &[f64],
+) -> f64 {
+    let dim = params.len() - 1;
+    indices
+        .iter()
+        .map(|&i| (dot_f64(x.row(i), &params[..dim]) + params[dim] - y[i]).powi(2))
+        .sum::<f64>()
+        / indices.len() as f64
+}
+
+// One step per sample in the given order on params, the coefficients
+// followed by the intercept. Returns the mean squared error seen on the way.
+fn sgd_epoch<T: Float, O: Optimizer>(
+    x: &DenseMatrix<T>,
+    y: &[f64],
+    order: &[usize],
+    params: &mut [f64],
+    optimizer: &mut O,
+    fit_intercept: bool,
+) -> f64 {
+    let dim = params.len() - 1;
+    let mut gradient = vec![0.0; dim + 1];
+    let mut loss = 0.0;
+    for &i in order {
+        let error = dot_f64(x.row(i), &params[..dim]) + params[dim] - y[i];
+        loss += error * error;
+        for (g, v) in gradient.iter_mut().zip(x.row(i)) {
+            *g = error * v.as_f64();
+        }
+        gradient[dim] = if fit_intercept { error } else { 0.0 };
+        optimizer.step(params, &gradient);
+    }
+    loss / order.len() as f64
+}
+
+// Least squares with an optional L2 penalty: solves
+// (X^T X + l2 * I) w = X^T y on centered data. Returns the coefficients and
+// intercept. The products of the centered rows are in T, their sums and the
+// solve in f64.
+pub(crate) fn solve_normal_equation<T: Float>(
+    x: &DenseMatrix<T>,
+    y: &[f64],
+    fit_intercept: bool,
+    l2: f64,
+) -> (Vec<f64>, f64) {
+    let dim = x.n_cols();
+    let n = x.n_rows() as f64;
+
+    // Centering the data lets the intercept be recovered afterwards
+    // instead of solving for it
+    let (x_mean, y_mean) = if fit_intercept {
+        let mut x_mean = vec![0.0; dim];
+        for row in x.iter_rows() {
+            for (m, v) in x_mean.iter_mut().zip(row) {
+                *m += v.as_f64() / n;
+            }
+        }
+        (x_mean, y.iter().sum::<f64>() / n)
+    } else {
+        (vec![0.0; dim], 0.0)
+    };
+
+    // X^T X and X^T y on the centered data
+    let row_mean: Vec<T> = cast_slice(&x_mean);
+    let mut xtx: Matrix = vec![vec![0.0; dim]; dim];
+    let mut xty = vec![0.0; dim];
+    for (row, target) in x.iter_rows().zip(y) {
+        let centered: Vec<T> = row.iter().zip(&row_mean).map(|(&v, &m)| v - m).collect();
+        for i in 0..dim {
+            xty[i] += centered[i].as_f64() * (target - y_mean);
+            for j in 0..dim {
+                xtx[i][j] += (centered[i] * centered[j]).as_f64();
+            }
+        }
+    }
+    for (i, row) in xtx.iter_mut().enumerate() {
+        row[i] += l2;
+    }
+
+    // Collinear features make X^T X singular; a tiny ridge term picks
+    // one of the equally good solutions
+    let coefficients = solve(&xtx, &xty).unwrap_or_else(|| {
+        let jitter = 1e-8 * (0..dim).map(|i| xtx[i][i]).sum::<f64>().max(1.0);
+        for (i, row) in xtx.iter_mut().enumerate() {
+            row[i] += jitter;
+        }
+        solve(&xtx, &xty).unwrap_or_else(|| vec![0.0; dim])
+    });
+    let intercept = y_mean - dot(&x_mean, &coefficients);
+    (coefficients, intercept)
+}
@@ -0,0 +1,44 @@
+pub mod linear;
+pub mod regularized;
+
+use crate::data::sparse::Rows;
+use crate::math::float::{cast_slice, Float};
+use crate::ml::base::{Estimator, Predictor};
+use crate::ml::metrics::regression::r2_score;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Shared interface of the models that predict y = x · coefficients + intercept
+pub trait LinearModel: Estimator<Target = [f64]> + Predictor<Output = f64> {
+    fn coefficients(&self) -> &[f64];
+
+    fn intercept(&self) -> f64;
+
+    // R² of the predictions for x against y
+    fn score(&self, x: &[Point], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+}
+
+// row · coefficients summed in f64, for rows of any float type against
+// coefficients that change every step, e.g. in SGD
+pub(crate) fn dot_f64<T: Float>(row: &[T], coefficients: &[f64]) -> f64 {
+    row.iter()
+        .zip(coefficients)
+        .map(|(&v, c)| v.as_f64() * c)
+        .sum()
+}
+
+// x · coefficients + intercept for every row, the products in the float
+// type of the rows
+pub(crate) fn predict_linear<T: Float, R: Rows<T> + ?Sized>(
+    x: &R,
+    coefficients: &[f64],
+    intercept: f64,
+) -> Vec<f64> {
+    let coefficients: Vec<T> = cast_slice(coefficients);
+    (0..x.n_rows())
+        .map(|i| x.row_dot(i, &coefficients).as_f64() + intercept)
+        .collect()
+}
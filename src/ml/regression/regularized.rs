@@ -0,0 +1,427 @@
+use super::linear::solve_normal_equation;
+use super::{dot_f64, predict_linear, LinearModel};
+use crate::math::float::{cast_slice, Float};
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, IterativeEstimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::observer::{FitMetrics, FitObserver, Notifier};
+use crate::ml::onnx::{self, OnnxError, ToOnnx};
+use crate::ml::sklearn::{self, Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::dot;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const ALPHA: f64 = 1.0;
+const EPSILON: f64 = 1e-4;
+const MAX_ITER: usize = 1000;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Least squares with an L2 penalty alpha * ||w||², solved in closed form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ridge {
+    pub alpha: f64,
+    pub fit_intercept: bool,
+
+    // Current state
+    coefficients: Vec<f64>,
+    intercept: f64,
+}
+
+impl fmt::Display for Ridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Ridge{{ alpha: {}, fit_intercept: {} }}",
+            self.alpha, self.fit_intercept
+        )
+    }
+}
+
+impl Default for Ridge {
+    fn default() -> Self {
+        Ridge::new(ALPHA)
+    }
+}
+
+builder!(Ridge => RidgeBuilder, default, {
+    alpha: f64,
+    fit_intercept: bool,
+});
+
+impl Ridge {
+    pub fn new(alpha: f64) -> Self {
+        Ridge {
+            alpha,
+            fit_intercept: true,
+            coefficients: vec![],
+            intercept: 0.0,
+        }
+    }
+
+    // Estimator::fit on the rows of a dense matrix of any float type, with
+    // the products of the rows in T, see solve_normal_equation
+    pub fn fit_matrix<T: Float>(&mut self, x: &Matrix<T>, y: &[f64]) {
+        self.coefficients.clear();
+        self.intercept = 0.0;
+        if x.n_rows() == 0 {
+            return;
+        }
+        (self.coefficients, self.intercept) =
+            solve_normal_equation(x, y, self.fit_intercept, self.alpha);
+    }
+
+    // As LinearRegression::predict_matrix
+    pub fn predict_matrix<T: Float>(&self, x: &Matrix<T>) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+}
+
+impl Estimator for Ridge {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        Ridge::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.coefficients.is_empty()).then_some(self.coefficients.len())
+    }
+}
+
+impl Predictor for Ridge {
+    type Output = f64;
+
+    fn predict(&self, x: &[Point]) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        Ridge::predict_matrix(self, x)
+    }
+}
+
+impl LinearModel for Ridge {
+    fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    fn intercept(&self) -> f64 {
+        self.intercept
+    }
+}
+
+impl ToOnnx for Ridge {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.coefficients.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::linear_regressor(&self.coefficients, self.intercept))
+    }
+}
+
+impl FromSklearn for Ridge {
+    const CLASS: &'static str = "Ridge";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let (coefficients, intercept) = sklearn::linear_attributes(attributes)?;
+        let mut model = Ridge::new(attributes.optional("alpha")?.unwrap_or(ALPHA));
+        model.fit_intercept = attributes.optional("fit_intercept")?.unwrap_or(true);
+        model.coefficients = coefficients;
+        model.intercept = intercept;
+        Ok(model)
+    }
+}
+
+// Least squares with an L1 penalty, minimizing
+// 1 / (2 * n) * ||y - Xw||² + alpha * ||w||₁ by cyclic coordinate descent.
+// The L1 term drives uninformative coefficients to exactly zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lasso {
+    pub alpha: f64,
+    pub fit_intercept: bool,
+    pub max_iter: usize,
+    pub tolerance: f64,
+
+    // Current state
+    coefficients: Vec<f64>,
+    intercept: f64,
+    n_iter: usize,
+}
+
+impl fmt::Display for Lasso {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Lasso{{ alpha: {}, fit_intercept: {}, max_iter: {}, tolerance: {} }}",
+            self.alpha, self.fit_intercept, self.max_iter, self.tolerance
+        )
+    }
+}
+
+impl Default for Lasso {
+    fn default() -> Self {
+        Lasso::new(ALPHA)
+    }
+}
+
+builder!(Lasso => LassoBuilder, default, {
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tolerance: f64,
+});
+
+impl Lasso {
+    pub fn new(alpha: f64) -> Self {
+        Lasso {
+            alpha,
+            fit_intercept: true,
+            max_iter: MAX_ITER,
+            tolerance: EPSILON,
+            coefficients: vec![],
+            intercept: 0.0,
+            n_iter: 0,
+        }
+    }
+
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    // As LinearRegression::predict_matrix
+    pub fn predict_matrix<T: Float>(&self, x: &Matrix<T>) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+
+    // Estimator::fit on the rows of a dense matrix of any float type. The
+    // centered copy of the rows stays in T; the coordinate updates run in
+    // f64.
+    pub fn fit_matrix<T: Float>(&mut self, x: &Matrix<T>, y: &[f64]) {
+        self.fit_notified(x, y, &mut Notifier::none());
+    }
+
+    fn fit_notified<T: Float>(&mut self, x: &Matrix<T>, y: &[f64], notifier: &mut Notifier) {
+        self.coefficients.clear();
+        self.intercept = 0.0;
+        self.n_iter = 0;
+        if x.n_rows() == 0 {
+            return;
+        }
+        self.fit_from(x, y, vec![0.0; x.n_cols()], notifier);
+    }
+
+    // Coordinate descent starting from the given coefficients, which lets
+    // lasso_path warm-start each alpha from the previous solution
+    fn fit_from<T: Float>(
+        &mut self,
+        x: &Matrix<T>,
+        y: &[f64],
+        start: Vec<f64>,
+        notifier: &mut Notifier,
+    ) {
+        let n = x.n_rows() as f64;
+        let dim = x.n_cols();
+        let (x_mean, y_mean) = means(x, y, self.fit_intercept);
+        let row_mean: Vec<T> = cast_slice(&x_mean);
+        let mut centered = x.clone();
+        for row in centered.iter_rows_mut() {
+            for (v, &m) in row.iter_mut().zip(&row_mean) {
+                *v -= m;
+            }
+        }
+        let col_norms: Vec<f64> = (0..dim)
+            .map(|j| centered.column(j).map(|v| (v * v).as_f64()).sum::<f64>() / n)
+            .collect();
+
+        let mut w = start;
+        let mut residual: Vec<f64> = centered
+            .iter_rows()
+            .zip(y)
+            .map(|(row, target)| target - y_mean - dot_f64(row, &w))
+            .collect();
+
+        self.n_iter = 0;
+        while self.n_iter < self.max_iter {
+            let mut max_change: f64 = 0.0;
+            for j in 0..dim {
+                if col_norms[j] == 0.0 {
+                    continue;
+                }
+                // Correlation of feature j with the residual it explains
+                let rho: f64 = centered
+                    .iter_rows()
+                    .zip(&residual)
+                    .map(|(row, r)| row[j].as_f64() * (r + row[j].as_f64() * w[j]))
+                    .sum::<f64>()
+                    / n;
+                let updated = soft_threshold(rho, self.alpha) / col_norms[j];
+                let change = updated - w[j];
+                if change != 0.0 {
+                    for (r, row) in residual.iter_mut().zip(centered.iter_rows()) {
+                        *r -= change * row[j].as_f64();
+                    }
+                }
+                max_change = max_change.max(change.abs());
+                w[j] = updated;
+            }
+            self.n_iter += 1;
+            notifier.iteration(|| FitMetrics::new(self.n_iter).with("max_change", max_change));
+            if max_change < self.tolerance {
+                notifier.converged();
+                break;
+            }
+        }
+
+        self.intercept = y_mean - dot(&x_mean, &w);
+        self.coefficients = w;
+    }
+}
+
+impl Estimator for Lasso {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        Lasso::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        (!self.coefficients.is_empty()).then_some(self.coefficients.len())
+    }
+}
+
+// An iteration per sweep over the coefficients, reporting the largest
+// change to any of them
+impl IterativeEstimator for Lasso {
+    fn fit_observed_matrix(&mut self, x: &Matrix, y: &[f64], observer: &mut dyn FitObserver) {
+        self.fit_notified(x, y, &mut Notifier::new(observer));
+    }
+}
+
+impl Predictor for Lasso {
+    type Output = f64;
+
+    fn predict(&self, x: &[Point]) -> Vec<f64> {
+        predict_linear(x, &self.coefficients, self.intercept)
+    }
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        Lasso::predict_matrix(self, x)
+    }
+}
+
+impl LinearModel for Lasso {
+    fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    fn intercept(&self) -> f64 {
+        self.intercept
+    }
+}
+
+impl ToOnnx for Lasso {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        if self.coefficients.is_empty() {
+            return Err(OnnxError::NotFitted);
+        }
+        Ok(onnx::linear_regressor(&self.coefficients, self.intercept))
+    }
+}
+
+impl FromSklearn for Lasso {
+    const CLASS: &'static str = "Lasso";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let (coefficients, intercept) = sklearn::linear_attributes(attributes)?;
+        let mut model = Lasso::new(attributes.optional("alpha")?.unwrap_or(ALPHA));
+        model.fit_intercept = attributes.optional("fit_intercept")?.unwrap_or(true);
+        model.n_iter = attributes.optional("n_iter_")?.unwrap_or(0);
+        model.coefficients = coefficients;
+        model.intercept = intercept;
+        Ok(model)
+    }
+}
+
+// Coefficients fitted at one regularization strength
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathPoint {
+    pub alpha: f64,
+    pub coefficients: Vec<f64>,
+}
+
+// n alphas spaced evenly on a log scale from max down to min, the order the
+// path helpers expect
+pub fn alpha_grid(min: f64, max: f64, n: usize) -> Vec<f64> {
+    if n < 2 {
+        return vec![max];
+    }
+    let (log_min, log_max) = (min.ln(), max.ln());
+    (0..n)
+        .map(|i| (log_max - (log_max - log_min) * i as f64 / (n - 1) as f64).exp())
+        .collect()
+}
+
+// Ridge coefficients for every alpha, for plotting coefficient shrinkage
+pub fn ridge_path(x: &[Point], y: &[f64], alphas: &[f64]) -> Vec<PathPoint> {
+    let x = point_matrix(x);
+    alphas
+        .iter()
+        .map(|&alpha| {
+            let mut model = Ridge::new(alpha);
+            model.fit_matrix(&x, y);
+            PathPoint {
+                alpha,
+                coefficients: model.coefficients,
+            }
+        })
+        .collect()
+}
+
+// Lasso coefficients for every alpha. Each fit is warm-started from the
+// previous one, so pass alphas in decreasing order.
+pub fn lasso_path(x: &[Point], y: &[f64], alphas: &[f64]) -> Vec<PathPoint> {
+    if x.is_empty() {
+        return vec![];
+    }
+    let x = point_matrix(x);
+    let mut start = vec![0.0; x.n_cols()];
+    alphas
+        .iter()
+        .map(|&alpha| {
+            let mut model = Lasso::new(alpha);
+            model.fit_from(&x, y, start.clone(), &mut Notifier::none());
+            start = model.coefficients.clone();
+            PathPoint {
+                alpha,
+                coefficients: model.coefficients,
+            }
+        })
+        .collect()
+}
+
+fn soft_threshold(value: f64, threshold: f64) -> f64 {
+    if value > threshold {
+        value - threshold
+    } else if value < -threshold {
+        value + threshold
+    } else {
+        0.0
+    }
+}
+
+fn means<T: Float>(x: &Matrix<T>, y: &[f64], fit_intercept: bool) -> (Vec<f64>, f64) {
+    let dim = x.n_cols();
+    if !fit_intercept {
+        return (vec![0.0; dim], 0.0);
+    }
+    let n = x.n_rows() as f64;
+    let mut x_mean = vec![0.0; dim];
+    for row in x.iter_rows() {
+        for (m, v) in x_mean.iter_mut().zip(row) {
+            *m += v.as_f64() / n;
+        }
+    }
+    (x_mean, y.iter().sum::<f64>() / n)
+}
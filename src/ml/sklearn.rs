@@ -0,0 +1,278 @@
+// Estimators fitted by scikit-learn, loaded from their fitted attributes to
+// plot and run them in the browser. A model is one JSON object with the
+// class name under "estimator" and the fitted attributes, plus any
+// parameters the model needs, under their scikit-learn names, arrays as
+// nested lists:
+//
+//   {"estimator": "LinearRegression", "coef_": [1.5], "intercept_": 4.0}
+//
+// Python writes that for most estimators with
+//
+//   attributes = {k: getattr(v, "tolist", lambda: v)()
+//                 for k, v in vars(model).items() if k.endswith("_")}
+//   json.dumps({"estimator": type(model).__name__, **attributes,
+//               **model.get_params()})
+//
+// Decision trees need tree_ as an object of its node arrays instead,
+// children_left, children_right, feature, threshold, value and optionally
+// impurity and n_node_samples, e.g. {k: getattr(model.tree_, k).tolist()}.
+// Classifiers predict positions in classes_ rather than the labels
+// themselves.
+use crate::ml::tree::TreeNode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::fmt;
+
+// Node index scikit-learn gives the children of a leaf
+const TREE_LEAF: i64 = -1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SklearnError {
+    Decode(String),
+    // Exported from another scikit-learn class
+    Estimator { found: String, expected: String },
+    Missing(String),
+    // An attribute of the wrong type or shape
+    Invalid { attribute: String, message: String },
+}
+
+impl fmt::Display for SklearnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SklearnError::Decode(message) => write!(f, "cannot read model: {}", message),
+            SklearnError::Estimator { found, expected } => {
+                write!(f, "model is a {}, expected a {}", found, expected)
+            }
+            SklearnError::Missing(attribute) => write!(f, "model has no {}", attribute),
+            SklearnError::Invalid { attribute, message } => {
+                write!(f, "invalid {}: {}", attribute, message)
+            }
+        }
+    }
+}
+
+impl Error for SklearnError {}
+
+pub(crate) fn invalid(attribute: &str, message: impl Into<String>) -> SklearnError {
+    SklearnError::Invalid {
+        attribute: attribute.to_string(),
+        message: message.into(),
+    }
+}
+
+// The attributes of an exported model, read with the shapes scikit-learn
+// uses interchangeably, e.g. intercept_ as 4.0 or [4.0]
+#[derive(Debug, Clone)]
+pub struct Attributes {
+    estimator: String,
+    values: Map<String, Value>,
+}
+
+impl Attributes {
+    pub fn parse(json: &str) -> Result<Self, SklearnError> {
+        let mut values: Map<String, Value> =
+            serde_json::from_str(json).map_err(|err| SklearnError::Decode(err.to_string()))?;
+        let estimator = match values.remove("estimator") {
+            Some(Value::String(estimator)) => estimator,
+            Some(_) => return Err(invalid("estimator", "expected a class name")),
+            None => return Err(SklearnError::Missing("estimator".to_string())),
+        };
+        Ok(Attributes { estimator, values })
+    }
+
+    // The scikit-learn class name
+    pub fn estimator(&self) -> &str {
+        &self.estimator
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|value| !value.is_null())
+    }
+
+    // Any attribute as T, None when absent or null
+    pub fn optional<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, SklearnError> {
+        match self.values.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => T::deserialize(value)
+                .map(Some)
+                .map_err(|err| invalid(name, err.to_string())),
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T, SklearnError> {
+        self.optional(name)?
+            .ok_or_else(|| SklearnError::Missing(name.to_string()))
+    }
+
+    // Length of classes_, whatever the labels are
+    pub fn n_classes(&self) -> Result<usize, SklearnError> {
+        Ok(self.get::<Vec<Value>>("classes_")?.len())
+    }
+
+    // A number, or an array of exactly one
+    pub fn number(&self, name: &str) -> Result<f64, SklearnError> {
+        match self.vector(name)?.as_slice() {
+            [value] => Ok(*value),
+            values => Err(invalid(
+                name,
+                format!("expected 1 value, found {}", values.len()),
+            )),
+        }
+    }
+
+    // An array of numbers, or a matrix of exactly one row
+    pub fn vector(&self, name: &str) -> Result<Vec<f64>, SklearnError> {
+        match self.get(name)? {
+            Shape::Scalar(value) => Ok(vec![value]),
+            Shape::Vector(values) => Ok(values),
+            Shape::Matrix(mut rows) if rows.len() == 1 => Ok(rows.remove(0)),
+            Shape::Matrix(rows) => Err(invalid(
+                name,
+                format!("expected 1 row, found {}", rows.len()),
+            )),
+        }
+    }
+
+    // A matrix with rows of equal length, an array of numbers being one row
+    pub fn matrix(&self, name: &str) -> Result<Vec<Vec<f64>>, SklearnError> {
+        let rows = match self.get(name)? {
+            Shape::Scalar(value) => vec![vec![value]],
+            Shape::Vector(values) => vec![values],
+            Shape::Matrix(rows) => rows,
+        };
+        if rows.windows(2).any(|pair| pair[0].len() != pair[1].len()) {
+            return Err(invalid(name, "rows of different lengths"));
+        }
+        Ok(rows)
+    }
+
+    // tree_ as its root node, classifier leaves holding class proportions
+    pub fn tree(&self, name: &str, classifier: bool) -> Result<TreeNode, SklearnError> {
+        let tree: Tree = self.get(name)?;
+        let n_nodes = tree.children_left.len();
+        let lengths = [
+            tree.children_right.len(),
+            tree.feature.len(),
+            tree.threshold.len(),
+            tree.value.len(),
+        ];
+        if n_nodes == 0 || lengths.iter().any(|&length| length != n_nodes) {
+            return Err(invalid(name, "node arrays of different lengths"));
+        }
+        tree.node(0, classifier, &mut vec![false; n_nodes])
+            .ok_or_else(|| invalid(name, "children out of range or shared"))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Shape {
+    Scalar(f64),
+    Vector(Vec<f64>),
+    Matrix(Vec<Vec<f64>>),
+}
+
+// sklearn.tree._tree.Tree as parallel node arrays. value is
+// [node][output][class], of which only the first output is read.
+#[derive(Deserialize)]
+struct Tree {
+    children_left: Vec<i64>,
+    children_right: Vec<i64>,
+    feature: Vec<i64>,
+    threshold: Vec<f64>,
+    value: Vec<Vec<Vec<f64>>>,
+    #[serde(default)]
+    impurity: Vec<f64>,
+    #[serde(default)]
+    n_node_samples: Vec<usize>,
+}
+
+impl Tree {
+    // None if a node is reached twice, which a tree never does
+    fn node(&self, i: usize, classifier: bool, seen: &mut [bool]) -> Option<TreeNode> {
+        if std::mem::replace(&mut seen[i], true) {
+            return None;
+        }
+        let impurity = self.impurity.get(i).copied().unwrap_or(0.0);
+        let n_samples = self.n_node_samples.get(i).copied().unwrap_or(0);
+        if self.children_left[i] == TREE_LEAF {
+            let mut value = self.value[i].first()?.clone();
+            // Weighted counts before scikit-learn 1.4, proportions since
+            let total: f64 = value.iter().sum();
+            if classifier && total > 0.0 {
+                value.iter_mut().for_each(|v| *v /= total);
+            }
+            return Some(TreeNode::Leaf {
+                value,
+                n_samples,
+                impurity,
+            });
+        }
+        let mut child = |id: i64| {
+            usize::try_from(id)
+                .ok()
+                .filter(|&id| id < self.children_left.len())
+                .and_then(|id| self.node(id, classifier, seen))
+        };
+        let left = child(self.children_left[i])?;
+        let right = child(self.children_right[i])?;
+        Some(TreeNode::Split {
+            feature: usize::try_from(self.feature[i]).ok()?,
+            threshold: self.threshold[i],
+            n_samples,
+            impurity,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+}
+
+// Estimators that can be loaded from a model fitted by scikit-learn, e.g.
+// `let model = KMeans::from_sklearn_json(&json)?`
+pub trait FromSklearn: Sized {
+    // The scikit-learn class the model comes from
+    const CLASS: &'static str;
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError>;
+
+    fn from_sklearn_json(json: &str) -> Result<Self, SklearnError> {
+        let attributes = Attributes::parse(json)?;
+        if attributes.estimator() != Self::CLASS {
+            return Err(SklearnError::Estimator {
+                found: attributes.estimator().to_string(),
+                expected: Self::CLASS.to_string(),
+            });
+        }
+        Self::from_sklearn(&attributes)
+    }
+}
+
+// coef_ and intercept_ of a single-target linear model, with as many
+// coefficients as n_features_in_ when that is given
+pub(crate) fn linear_attributes(attributes: &Attributes) -> Result<(Vec<f64>, f64), SklearnError> {
+    let coefficients = attributes.vector("coef_")?;
+    let intercept = if attributes.contains("intercept_") {
+        attributes.number("intercept_")?
+    } else {
+        0.0
+    };
+    check_features(attributes, coefficients.len(), "coef_")?;
+    Ok((coefficients, intercept))
+}
+
+// An error unless n_features_in_ is absent or n_features
+pub(crate) fn check_features(
+    attributes: &Attributes,
+    n_features: usize,
+    name: &str,
+) -> Result<(), SklearnError> {
+    match attributes.optional::<usize>("n_features_in_")? {
+        Some(expected) if expected != n_features => Err(invalid(
+            name,
+            format!("{} features, n_features_in_ is {}", n_features, expected),
+        )),
+        _ => Ok(()),
+    }
+}
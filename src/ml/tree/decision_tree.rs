@@ -0,0 +1,384 @@
+use super::{build_tree, Criterion, MaxFeatures, Target, TreeNode, TreeParams};
+use crate::math::matrix::Matrix;
+use crate::ml::base::{point_matrix, Estimator, Predictor};
+use crate::ml::builder::builder;
+use crate::ml::classification::{most_probable, Classifier};
+use crate::ml::metrics::classification::accuracy_score;
+use crate::ml::metrics::regression::r2_score;
+use crate::ml::onnx::{OnnxError, ToOnnx, TreeEnsemble};
+use crate::ml::sklearn::{Attributes, FromSklearn, SklearnError};
+use crate::ml::utils::seeded_rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const MIN_SAMPLES_SPLIT: usize = 2;
+const MIN_SAMPLES_LEAF: usize = 1;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// CART classifier splitting on Gini impurity or entropy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionTreeClassifier {
+    pub criterion: Criterion,
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    // Features tried at each split, chosen at random unless All
+    pub max_features: MaxFeatures,
+    pub random_state: Option<u64>,
+
+    // Current state
+    root: Option<TreeNode>,
+    n_features: usize,
+    n_classes: usize,
+}
+
+// CART regressor splitting on variance reduction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionTreeRegressor {
+    // Unlimited when None
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    // Features tried at each split, chosen at random unless All
+    pub max_features: MaxFeatures,
+    pub random_state: Option<u64>,
+
+    // Current state
+    root: Option<TreeNode>,
+    n_features: usize,
+}
+
+impl fmt::Display for DecisionTreeClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DecisionTreeClassifier{{ criterion: {:?}, max_depth: {:?}, min_samples_split: {}, min_samples_leaf: {} }}",
+            self.criterion, self.max_depth, self.min_samples_split, self.min_samples_leaf
+        )
+    }
+}
+
+impl fmt::Display for DecisionTreeRegressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DecisionTreeRegressor{{ max_depth: {:?}, min_samples_split: {}, min_samples_leaf: {} }}",
+            self.max_depth, self.min_samples_split, self.min_samples_leaf
+        )
+    }
+}
+
+impl Default for DecisionTreeClassifier {
+    fn default() -> Self {
+        DecisionTreeClassifier::new()
+    }
+}
+
+builder!(DecisionTreeClassifier => DecisionTreeClassifierBuilder, default, {
+    criterion: Criterion,
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    max_features: MaxFeatures,
+}, some {
+    max_depth: usize,
+    random_state: u64,
+});
+
+impl Default for DecisionTreeRegressor {
+    fn default() -> Self {
+        DecisionTreeRegressor::new()
+    }
+}
+
+builder!(DecisionTreeRegressor => DecisionTreeRegressorBuilder, default, {
+    min_samples_split: usize,
+    min_samples_leaf: usize,
+    max_features: MaxFeatures,
+}, some {
+    max_depth: usize,
+    random_state: u64,
+});
+
+impl DecisionTreeClassifier {
+    pub fn new() -> Self {
+        DecisionTreeClassifier {
+            criterion: Criterion::Gini,
+            max_depth: None,
+            min_samples_split: MIN_SAMPLES_SPLIT,
+            min_samples_leaf: MIN_SAMPLES_LEAF,
+            max_features: MaxFeatures::All,
+            random_state: None,
+            root: None,
+            n_features: 0,
+            n_classes: 0,
+        }
+    }
+
+    pub fn with_criterion(mut self, criterion: Criterion) -> Self {
+        self.criterion = criterion;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // The fitted tree, serializable for rendering
+    pub fn tree(&self) -> Option<&TreeNode> {
+        self.root.as_ref()
+    }
+
+    // Normalized impurity decrease per feature
+    pub fn feature_importances(&self) -> Vec<f64> {
+        self.root
+            .as_ref()
+            .map_or(vec![0.0; self.n_features], |root| {
+                root.feature_importances(self.n_features)
+            })
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.n_classes
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[usize]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        self.n_classes = y.iter().max().map_or(0, |&m| m + 1);
+        if x.is_empty() {
+            self.root = None;
+            return;
+        }
+        let target = Target::Classes {
+            labels: y,
+            n_classes: self.n_classes,
+        };
+        self.n_features = x.n_cols();
+        let mut indices: Vec<usize> = (0..x.n_rows()).collect();
+        let mut rng = seeded_rng(self.random_state);
+        self.root = Some(build_tree(
+            x,
+            target,
+            &mut indices,
+            &self.params(),
+            &mut rng,
+        ));
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<usize> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        most_probable(&self.predict_proba_matrix(x))
+    }
+
+    // Class proportions of the training samples in each point's leaf
+    pub fn predict_proba(&self, x: &[Point]) -> Vec<Vec<f64>> {
+        self.predict_proba_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        let Some(root) = &self.root else {
+            return vec![vec![]; x.n_rows()];
+        };
+        x.iter_rows()
+            .map(|point| root.leaf_value(point).to_vec())
+            .collect()
+    }
+
+    // Mean accuracy on x against y
+    pub fn score(&self, x: &[Point], y: &[usize]) -> f64 {
+        accuracy_score(y, &self.predict(x))
+    }
+
+    fn params(&self) -> TreeParams {
+        TreeParams {
+            criterion: self.criterion,
+            max_depth: self.max_depth,
+            min_samples_split: self.min_samples_split,
+            min_samples_leaf: self.min_samples_leaf,
+            max_features: self.max_features,
+        }
+    }
+}
+
+impl Estimator for DecisionTreeClassifier {
+    type Target = [usize];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[usize]) {
+        DecisionTreeClassifier::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.root.as_ref().map(|_| self.n_features)
+    }
+}
+
+impl Predictor for DecisionTreeClassifier {
+    type Output = usize;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<usize> {
+        DecisionTreeClassifier::predict_matrix(self, x)
+    }
+}
+
+impl Classifier for DecisionTreeClassifier {
+    fn predict_proba_matrix(&self, x: &Matrix) -> Vec<Vec<f64>> {
+        DecisionTreeClassifier::predict_proba_matrix(self, x)
+    }
+}
+
+impl ToOnnx for DecisionTreeClassifier {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        let root = self.root.as_ref().ok_or(OnnxError::NotFitted)?;
+        Ok(TreeEnsemble::new()
+            .with_tree(root, |proportions| {
+                proportions.iter().copied().enumerate().collect()
+            })
+            .classifier(Some(self.n_features), self.n_classes, "NONE", vec![]))
+    }
+}
+
+impl FromSklearn for DecisionTreeClassifier {
+    const CLASS: &'static str = "DecisionTreeClassifier";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let mut model = DecisionTreeClassifier::new();
+        model.max_depth = attributes.optional("max_depth")?;
+        model.root = Some(attributes.tree("tree_", true)?);
+        model.n_features = attributes.get("n_features_in_")?;
+        model.n_classes = attributes.n_classes()?;
+        Ok(model)
+    }
+}
+
+impl DecisionTreeRegressor {
+    pub fn new() -> Self {
+        DecisionTreeRegressor {
+            max_depth: None,
+            min_samples_split: MIN_SAMPLES_SPLIT,
+            min_samples_leaf: MIN_SAMPLES_LEAF,
+            max_features: MaxFeatures::All,
+            random_state: None,
+            root: None,
+            n_features: 0,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // The fitted tree, serializable for rendering
+    pub fn tree(&self) -> Option<&TreeNode> {
+        self.root.as_ref()
+    }
+
+    // Normalized impurity decrease per feature
+    pub fn feature_importances(&self) -> Vec<f64> {
+        self.root
+            .as_ref()
+            .map_or(vec![0.0; self.n_features], |root| {
+                root.feature_importances(self.n_features)
+            })
+    }
+
+    pub fn fit(&mut self, x: &[Point], y: &[f64]) {
+        self.fit_matrix(&point_matrix(x), y);
+    }
+
+    pub fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        if x.is_empty() {
+            self.root = None;
+            return;
+        }
+        self.n_features = x.n_cols();
+        let mut indices: Vec<usize> = (0..x.n_rows()).collect();
+        let mut rng = seeded_rng(self.random_state);
+        self.root = Some(build_tree(
+            x,
+            Target::Values(y),
+            &mut indices,
+            &self.params(),
+            &mut rng,
+        ));
+    }
+
+    pub fn predict(&self, x: &[Point]) -> Vec<f64> {
+        self.predict_matrix(&point_matrix(x))
+    }
+
+    pub fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        let Some(root) = &self.root else {
+            return vec![0.0; x.n_rows()];
+        };
+        x.iter_rows()
+            .map(|point| root.leaf_value(point)[0])
+            .collect()
+    }
+
+    // R² of the predictions for x against y
+    pub fn score(&self, x: &[Point], y: &[f64]) -> f64 {
+        r2_score(y, &self.predict(x))
+    }
+
+    fn params(&self) -> TreeParams {
+        TreeParams {
+            // Ignored for regression, which always uses variance
+            criterion: Criterion::Gini,
+            max_depth: self.max_depth,
+            min_samples_split: self.min_samples_split,
+            min_samples_leaf: self.min_samples_leaf,
+            max_features: self.max_features,
+        }
+    }
+}
+
+impl Estimator for DecisionTreeRegressor {
+    type Target = [f64];
+
+    fn fit_matrix(&mut self, x: &Matrix, y: &[f64]) {
+        DecisionTreeRegressor::fit_matrix(self, x, y);
+    }
+
+    fn n_features_in(&self) -> Option<usize> {
+        self.root.as_ref().map(|_| self.n_features)
+    }
+}
+
+impl Predictor for DecisionTreeRegressor {
+    type Output = f64;
+
+    fn predict_matrix(&self, x: &Matrix) -> Vec<f64> {
+        DecisionTreeRegressor::predict_matrix(self, x)
+    }
+}
+
+impl ToOnnx for DecisionTreeRegressor {
+    fn to_onnx(&self) -> Result<Vec<u8>, OnnxError> {
+        let root = self.root.as_ref().ok_or(OnnxError::NotFitted)?;
+        Ok(TreeEnsemble::new()
+            .with_tree(root, |value| vec![(0, value[0])])
+            .regressor(Some(self.n_features), "SUM", 0.0))
+    }
+}
+
+impl FromSklearn for DecisionTreeRegressor {
+    const CLASS: &'static str = "DecisionTreeRegressor";
+
+    fn from_sklearn(attributes: &Attributes) -> Result<Self, SklearnError> {
+        let mut model = DecisionTreeRegressor::new();
+        model.max_depth = attributes.optional("max_depth")?;
+        model.root = Some(attributes.tree("tree_", false)?);
+        model.n_features = attributes.get("n_features_in_")?;
+        Ok(model)
+    }
+}
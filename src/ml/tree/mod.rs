@@ -0,0 +1,389 @@
+pub mod decision_tree;
+
+use crate::math::matrix::Matrix;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Criterion {
+    // 1 - sum(p²), cheap and the usual default
+    Gini,
+    // -sum(p * log2(p)), information gain
+    Entropy,
+}
+
+// Number of features considered at each split
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MaxFeatures {
+    All,
+    Sqrt,
+    Log2,
+    Count(usize),
+}
+
+impl MaxFeatures {
+    pub fn resolve(&self, n_features: usize) -> usize {
+        let count = match self {
+            MaxFeatures::All => n_features,
+            MaxFeatures::Sqrt => (n_features as f64).sqrt() as usize,
+            MaxFeatures::Log2 => (n_features as f64).log2() as usize,
+            MaxFeatures::Count(count) => *count,
+        };
+        // At least one feature, unless the points have none
+        count.clamp(n_features.min(1), n_features)
+    }
+}
+
+// A fitted tree. Leaves hold class proportions for classifiers and a single
+// mean for regressors. Serializes as nested objects tagged by "type" so the
+// UI can render it directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TreeNode {
+    Leaf {
+        value: Vec<f64>,
+        n_samples: usize,
+        impurity: f64,
+    },
+    Split {
+        feature: usize,
+        // Samples with feature <= threshold go left
+        threshold: f64,
+        n_samples: usize,
+        impurity: f64,
+        left: Box<TreeNode>,
+        right: Box<TreeNode>,
+    },
+}
+
+impl TreeNode {
+    // Value of the leaf the point falls into
+    pub fn leaf_value(&self, point: &[f64]) -> &[f64] {
+        let mut node = self;
+        loop {
+            match node {
+                TreeNode::Leaf { value, .. } => return value,
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                    ..
+                } => {
+                    node = if point[*feature] <= *threshold {
+                        left
+                    } else {
+                        right
+                    };
+                }
+            }
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            TreeNode::Leaf { .. } => 0,
+            TreeNode::Split { left, right, .. } => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    pub fn n_leaves(&self) -> usize {
+        match self {
+            TreeNode::Leaf { .. } => 1,
+            TreeNode::Split { left, right, .. } => left.n_leaves() + right.n_leaves(),
+        }
+    }
+
+    // Impurity decrease contributed by each feature, weighted by the number
+    // of samples reaching the split and normalized to sum to 1
+    pub fn feature_importances(&self, n_features: usize) -> Vec<f64> {
+        let mut importances = vec![0.0; n_features];
+        self.accumulate_importances(&mut importances);
+        let total: f64 = importances.iter().sum();
+        if total > 0.0 {
+            importances.iter_mut().for_each(|v| *v /= total);
+        }
+        importances
+    }
+
+    // Replace every leaf value with one computed from the samples (out of
+    // indices) that reach it. Used by boosting for per-leaf line searches.
+    pub(crate) fn update_leaves(
+        &mut self,
+        x: &Matrix,
+        indices: &mut [usize],
+        leaf_value: &mut impl FnMut(&[usize]) -> Vec<f64>,
+    ) {
+        match self {
+            TreeNode::Leaf { value, .. } => *value = leaf_value(indices),
+            TreeNode::Split {
+                feature,
+                threshold,
+                left,
+                right,
+                ..
+            } => {
+                let boundary = partition(x, indices, *feature, *threshold);
+                let (left_indices, right_indices) = indices.split_at_mut(boundary);
+                left.update_leaves(x, left_indices, leaf_value);
+                right.update_leaves(x, right_indices, leaf_value);
+            }
+        }
+    }
+
+    fn n_samples(&self) -> usize {
+        match self {
+            TreeNode::Leaf { n_samples, .. } | TreeNode::Split { n_samples, .. } => *n_samples,
+        }
+    }
+
+    fn impurity(&self) -> f64 {
+        match self {
+            TreeNode::Leaf { impurity, .. } | TreeNode::Split { impurity, .. } => *impurity,
+        }
+    }
+
+    fn accumulate_importances(&self, importances: &mut [f64]) {
+        if let TreeNode::Split {
+            feature,
+            n_samples,
+            impurity,
+            left,
+            right,
+            ..
+        } = self
+        {
+            importances[*feature] += *n_samples as f64 * impurity
+                - left.n_samples() as f64 * left.impurity()
+                - right.n_samples() as f64 * right.impurity();
+            left.accumulate_importances(importances);
+            right.accumulate_importances(importances);
+        }
+    }
+}
+
+// What the tree is fitted against
+#[derive(Clone, Copy)]
+pub(crate) enum Target<'a> {
+    Classes {
+        labels: &'a [usize],
+        n_classes: usize,
+    },
+    Values(&'a [f64]),
+}
+
+// Running sufficient statistics of a set of samples, updated one sample at a
+// time while sweeping split positions
+#[derive(Clone)]
+enum Stats {
+    Counts { counts: Vec<f64>, n: f64 },
+    Moments { n: f64, sum: f64, sum_sq: f64 },
+}
+
+impl Stats {
+    fn empty(target: Target) -> Self {
+        match target {
+            Target::Classes { n_classes, .. } => Stats::Counts {
+                counts: vec![0.0; n_classes],
+                n: 0.0,
+            },
+            Target::Values(_) => Stats::Moments {
+                n: 0.0,
+                sum: 0.0,
+                sum_sq: 0.0,
+            },
+        }
+    }
+
+    fn add(&mut self, target: Target, i: usize, sign: f64) {
+        match (self, target) {
+            (Stats::Counts { counts, n }, Target::Classes { labels, .. }) => {
+                counts[labels[i]] += sign;
+                *n += sign;
+            }
+            (Stats::Moments { n, sum, sum_sq }, Target::Values(values)) => {
+                *n += sign;
+                *sum += sign * values[i];
+                *sum_sq += sign * values[i] * values[i];
+            }
+            _ => unreachable!("stats and target kinds always match"),
+        }
+    }
+
+    fn n(&self) -> f64 {
+        match self {
+            Stats::Counts { n, .. } | Stats::Moments { n, .. } => *n,
+        }
+    }
+
+    // Gini or entropy for classes, variance for values
+    fn impurity(&self, criterion: Criterion) -> f64 {
+        match self {
+            Stats::Counts { counts, n } => {
+                if *n == 0.0 {
+                    return 0.0;
+                }
+                match criterion {
+                    Criterion::Gini => 1.0 - counts.iter().map(|c| (c / n) * (c / n)).sum::<f64>(),
+                    Criterion::Entropy => counts
+                        .iter()
+                        .filter(|&&c| c > 0.0)
+                        .map(|c| -(c / n) * (c / n).log2())
+                        .sum(),
+                }
+            }
+            Stats::Moments { n, sum, sum_sq } => {
+                if *n == 0.0 {
+                    return 0.0;
+                }
+                let mean = sum / n;
+                (sum_sq / n - mean * mean).max(0.0)
+            }
+        }
+    }
+
+    fn leaf_value(&self) -> Vec<f64> {
+        match self {
+            Stats::Counts { counts, n } => counts.iter().map(|c| c / n.max(1.0)).collect(),
+            Stats::Moments { n, sum, .. } => vec![sum / n.max(1.0)],
+        }
+    }
+}
+
+// Growth limits shared by every tree-based estimator
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TreeParams {
+    pub criterion: Criterion,
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    pub max_features: MaxFeatures,
+}
+
+// Grow a CART tree over the given sample indices, which may repeat (as in a
+// bootstrap sample). rng picks the candidate features at each split.
+pub(crate) fn build_tree(
+    x: &Matrix,
+    target: Target,
+    indices: &mut [usize],
+    params: &TreeParams,
+    rng: &mut StdRng,
+) -> TreeNode {
+    grow(x, target, indices, params, rng, 0)
+}
+
+fn grow(
+    x: &Matrix,
+    target: Target,
+    indices: &mut [usize],
+    params: &TreeParams,
+    rng: &mut StdRng,
+    depth: usize,
+) -> TreeNode {
+    let mut stats = Stats::empty(target);
+    for &i in indices.iter() {
+        stats.add(target, i, 1.0);
+    }
+    let impurity = stats.impurity(params.criterion);
+    let leaf = TreeNode::Leaf {
+        value: stats.leaf_value(),
+        n_samples: indices.len(),
+        impurity,
+    };
+
+    let depth_reached = params.max_depth.is_some_and(|max| depth >= max);
+    if depth_reached || indices.len() < params.min_samples_split || impurity <= 0.0 {
+        return leaf;
+    }
+    let Some((feature, threshold)) = best_split(x, target, indices, &stats, params, rng) else {
+        return leaf;
+    };
+
+    let boundary = partition(x, indices, feature, threshold);
+    let (left, right) = indices.split_at_mut(boundary);
+    TreeNode::Split {
+        feature,
+        threshold,
+        n_samples: left.len() + right.len(),
+        impurity,
+        left: Box::new(grow(x, target, left, params, rng, depth + 1)),
+        right: Box::new(grow(x, target, right, params, rng, depth + 1)),
+    }
+}
+
+// Reorder indices in place so samples going left come first, and return how
+// many there are
+fn partition(x: &Matrix, indices: &mut [usize], feature: usize, threshold: f64) -> usize {
+    let mut boundary = 0;
+    for j in 0..indices.len() {
+        if x.get(indices[j], feature) <= threshold {
+            indices.swap(boundary, j);
+            boundary += 1;
+        }
+    }
+    boundary
+}
+
+// Feature and threshold with the lowest weighted child impurity, if any split
+// improves on the parent and respects min_samples_leaf
+fn best_split(
+    x: &Matrix,
+    target: Target,
+    indices: &[usize],
+    total: &Stats,
+    params: &TreeParams,
+    rng: &mut StdRng,
+) -> Option<(usize, f64)> {
+    let dim = x.n_cols();
+    let parent_impurity = total.impurity(params.criterion) - 1e-12;
+    let mut sorted = indices.to_vec();
+    let candidates = sample(rng, dim, params.max_features.resolve(dim));
+    candidates
+        .into_iter()
+        .filter_map(|feature| {
+            split_on_feature(x, target, &mut sorted, total, params, feature)
+                .map(|(impurity, threshold)| (feature, threshold, impurity))
+        })
+        .filter(|&(_, _, impurity)| impurity < parent_impurity)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(feature, threshold, _)| (feature, threshold))
+}
+
+// Sweep the samples in order of one feature and return the lowest weighted
+// child impurity with its threshold
+fn split_on_feature(
+    x: &Matrix,
+    target: Target,
+    sorted: &mut [usize],
+    total: &Stats,
+    params: &TreeParams,
+    feature: usize,
+) -> Option<(f64, f64)> {
+    let n = sorted.len();
+    sorted.sort_by(|&a, &b| x.get(a, feature).total_cmp(&x.get(b, feature)));
+    let mut left = Stats::empty(target);
+    let mut right = total.clone();
+    let mut best: Option<(f64, f64)> = None;
+
+    for (pos, pair) in sorted.windows(2).enumerate() {
+        let i = pair[0];
+        left.add(target, i, 1.0);
+        right.add(target, i, -1.0);
+
+        let (value, next) = (x.get(i, feature), x.get(pair[1], feature));
+        let n_left = pos + 1;
+        if value == next || n_left < params.min_samples_leaf || n - n_left < params.min_samples_leaf
+        {
+            continue;
+        }
+
+        let weighted = (left.n() * left.impurity(params.criterion)
+            + right.n() * right.impurity(params.criterion))
+            / n as f64;
+        if best.is_none_or(|(impurity, _)| weighted < impurity) {
+            best = Some((weighted, (value + next) / 2.0));
+        }
+    }
+    best
+}
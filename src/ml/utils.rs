@@ -0,0 +1,195 @@
+// Small dense linear algebra helpers shared by the estimators
+use crate::math::simd;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// Upper bound on Jacobi sweeps, convergence usually takes under 10
+const JACOBI_SWEEPS: usize = 100;
+
+// Square matrix stored as rows
+pub type Matrix = Vec<Vec<f64>>;
+
+pub fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    simd::dot(a, b)
+}
+
+// Solve a * x = b by Gaussian elimination with partial pivoting, or None if
+// a is singular
+pub fn solve(a: &Matrix, b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    let mut m: Matrix = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| {
+            let mut row = row.clone();
+            row.push(rhs);
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))?;
+        if m[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        let pivot_row = m[col].clone();
+        for row in m.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = ((i + 1)..n).map(|j| m[i][j] * x[j]).sum();
+        x[i] = (m[i][n] - sum) / m[i][i];
+    }
+    Some(x)
+}
+
+// Lower-triangular L with a = L * L^T, or None if a is not positive definite
+pub fn cholesky(a: &Matrix) -> Option<Matrix> {
+    let n = a.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let diag = a[i][i] - sum;
+                if diag <= 0.0 || !diag.is_finite() {
+                    return None;
+                }
+                l[i][j] = diag.sqrt();
+            } else {
+                l[i][j] = (a[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+// Solve L * x = b for lower-triangular L
+pub fn forward_substitution(l: &Matrix, b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| l[i][k] * x[k]).sum();
+        x[i] = (b[i] - sum) / l[i][i];
+    }
+    x
+}
+
+// Deterministic generator when a seed is given, OS entropy otherwise
+pub fn seeded_rng(random_state: Option<u64>) -> StdRng {
+    match random_state {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+// Numerically stable log(sum(exp(values)))
+pub fn log_sum_exp(values: &[f64]) -> f64 {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        return max;
+    }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+// Index of the largest value, the first of ties, 0 for no values
+pub fn argmax(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (i, &v)| {
+            if v > best.1 {
+                (i, v)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+// Probabilities proportional to exp(values), shifted by the max to avoid
+// overflow
+pub fn softmax(values: &[f64]) -> Vec<f64> {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exp: Vec<f64> = values.iter().map(|v| (v - max).exp()).collect();
+    let sum: f64 = exp.iter().sum();
+    exp.iter().map(|e| e / sum).collect()
+}
+
+// q-th quantile (q in [0, 1]) with linear interpolation between the
+// closest ranks, NaN for no values
+pub fn quantile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+// Eigen-decomposition of a symmetric matrix by cyclic Jacobi rotations.
+// Returns eigenvalues in decreasing order and the matching unit eigenvectors
+// as rows.
+pub fn symmetric_eigen(a: &Matrix) -> (Vec<f64>, Matrix) {
+    let n = a.len();
+    let mut a = a.clone();
+    let mut vectors: Matrix = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    let scale: f64 = a.iter().flatten().map(|v| v * v).sum();
+
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * scale {
+            break;
+        }
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // Rotation in the (p, q) plane that zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut().chain(vectors.iter_mut()) {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = c * x - s * y;
+                    row[q] = s * x + c * y;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                for (k, (x, y)) in row_p.iter().zip(&row_q).enumerate() {
+                    a[p][k] = c * x - s * y;
+                    a[q][k] = s * x + c * y;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[j][j].total_cmp(&a[i][i]));
+    let values = order.iter().map(|&i| a[i][i]).collect();
+    // Eigenvectors are the columns of the accumulated rotations
+    let vectors = order
+        .iter()
+        .map(|&i| vectors.iter().map(|row| row[i]).collect())
+        .collect();
+    (values, vectors)
+}
@@ -0,0 +1,115 @@
+// Checks of the points handed to fit, transform and predict, so that data
+// an estimator can't use fails with an error saying what's wrong instead of
+// fitting nonsense. Only the try_ methods of the estimator traits run them,
+// e.g. Estimator::try_fit; fit, predict and transform trust their input.
+// check_array and check_matrix are public for callers that drive a fit
+// themselves, e.g. step by step.
+use crate::error::{MoonlightError, Result};
+use crate::math::matrix::Matrix;
+use serde::{Deserialize, Serialize};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NanPolicy {
+    // NaN is an error like any non-finite value
+    #[default]
+    Reject,
+    // NaN marks a missing value the estimator fills in or skips, e.g. an
+    // imputer. Infinities are still errors.
+    AllowMissing,
+}
+
+// What check_array requires of the points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrayCheck {
+    // 0 accepts no points at all, e.g. to predict nothing
+    pub min_samples: usize,
+    // Features every point must have, the first point's number when None
+    pub n_features: Option<usize>,
+    pub nan_policy: NanPolicy,
+}
+
+impl Default for ArrayCheck {
+    fn default() -> Self {
+        ArrayCheck::new()
+    }
+}
+
+impl ArrayCheck {
+    // At least one point, all with as many features as the first, all
+    // finite
+    pub fn new() -> Self {
+        ArrayCheck {
+            min_samples: 1,
+            n_features: None,
+            nan_policy: NanPolicy::Reject,
+        }
+    }
+
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    pub fn with_n_features(mut self, n_features: Option<usize>) -> Self {
+        self.n_features = n_features;
+        self
+    }
+
+    pub fn with_nan_policy(mut self, nan_policy: NanPolicy) -> Self {
+        self.nan_policy = nan_policy;
+        self
+    }
+}
+
+// Checks x against check and returns its number of features: the first
+// error among too few points, a point with another number of features and
+// a value the NaN policy doesn't allow
+pub fn check_array(x: &[Point], check: &ArrayCheck) -> Result<usize> {
+    check_rows(x.iter().map(Vec::as_slice), x.len(), check)
+}
+
+// check_array on the rows of a matrix
+pub fn check_matrix(x: &Matrix, check: &ArrayCheck) -> Result<usize> {
+    check_rows(x.iter_rows(), x.n_rows(), check)
+}
+
+fn check_rows<'a>(
+    rows: impl Iterator<Item = &'a [f64]> + Clone,
+    n_rows: usize,
+    check: &ArrayCheck,
+) -> Result<usize> {
+    if n_rows == 0 && check.min_samples > 0 {
+        return Err(MoonlightError::EmptyDataset);
+    }
+    if n_rows < check.min_samples {
+        return Err(MoonlightError::TooFewSamples {
+            required: check.min_samples,
+            n_samples: n_rows,
+        });
+    }
+    let expected = check
+        .n_features
+        .or_else(|| rows.clone().next().map(<[f64]>::len))
+        .unwrap_or(0);
+    for (row, point) in rows.enumerate() {
+        if point.len() != expected {
+            return Err(MoonlightError::DimensionMismatch {
+                expected,
+                found: point.len(),
+            });
+        }
+        let allowed =
+            |v: f64| v.is_finite() || (v.is_nan() && check.nan_policy == NanPolicy::AllowMissing);
+        if let Some(column) = point.iter().position(|&v| !allowed(v)) {
+            return Err(MoonlightError::NonFinite {
+                row,
+                column,
+                value: point[column],
+            });
+        }
+    }
+    Ok(expected)
+}
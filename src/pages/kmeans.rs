@@ -0,0 +1,925 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use moonlight::compute::worker::spawn_fit;
+use moonlight::compute::EstimatorSpec;
+use moonlight::data::datasets::load_iris;
+use moonlight::data::frame::{Column, DataFrame};
+use moonlight::data::generators::{
+    generate_realistic_clusters, make_anisotropic, make_blobs, make_circles, make_moons,
+    make_spiral,
+};
+use moonlight::data::table::DataTable;
+use moonlight::data::upload::DatasetUpload;
+use moonlight::ml::base::Estimator;
+use moonlight::ml::clustering::hierarchical::{AgglomerativeClustering, Linkage};
+use moonlight::ml::clustering::kmeans::{IterationSnapshot, KMeans};
+use moonlight::ml::clustering::select_k::select_k;
+use moonlight::ml::decomposition::pca::Pca;
+use moonlight::ml::params::{Configurable, Params};
+use moonlight::ml::sklearn::FromSklearn;
+use moonlight::ml::validation::check_matrix;
+use moonlight::plot::animation::{use_timeline, yield_now, TimelineControls};
+use moonlight::plot::comparison::ModelComparison;
+use moonlight::plot::dendrogram::{Dendrogram, DendrogramData};
+use moonlight::plot::linked::{LinkedSelection, LinkedSelectionProvider};
+use moonlight::plot::pairplot::{PairPlot, PairPlotData, MAX_COLUMNS};
+use moonlight::plot::params::ParamForm;
+use moonlight::plot::scatter::{Outline, ScatterPlot, ScatterPlotData};
+use moonlight::plot::scatter3d::{Scatter3d, Scatter3dData};
+use moonlight::plot::vega::chart::{FacetChart, VegaLiteChart};
+use moonlight::plot::vega::selection::{Param, Selection};
+use moonlight::plot::vega::spec::{Channel, Facet, FieldDef, FieldType, ScaleDef, VegaSpec};
+use moonlight::storage;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::f64;
+
+// Cluster counts compared side by side under the main chart
+const COMPARED_KS: [usize; 4] = [2, 3, 4, 5];
+// Hierarchical clustering takes memory quadratic in the points, so only
+// the first of larger datasets are clustered
+const MAX_HIERARCHICAL_POINTS: usize = 1000;
+
+// Keys the page's state is kept under across reloads, see storage
+const STORED_SETTINGS: &str = "kmeans/settings";
+const STORED_UPLOAD: &str = "kmeans/upload";
+const STORED_FIT: &str = "kmeans/fit";
+
+// The dataset and parameter choices the page starts from on the next visit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    dataset: String,
+    num_points: usize,
+    n_clusters: usize,
+    seed: Option<u64>,
+    params: Params,
+}
+
+// The value saved under key, None when there is none or it can't be read
+async fn restore<T: DeserializeOwned>(key: &str) -> Option<T> {
+    storage::load(key).await.unwrap_or_else(|err| {
+        error!("Error restoring {}: {}", key, err);
+        None
+    })
+}
+
+// Saves value under key in the background
+fn remember<T: Serialize + 'static>(key: &'static str, value: T) {
+    spawn(async move {
+        if let Err(err) = storage::save(key, &value).await {
+            error!("Error saving {}: {}", key, err);
+        }
+    });
+}
+
+// Progress of the fit the Run button starts
+#[derive(Debug, Clone, PartialEq)]
+enum RunState {
+    Idle,
+    Running {
+        iteration: usize,
+        inertia: f64,
+    },
+    Finished {
+        iterations: usize,
+        inertia: f64,
+        converged: bool,
+    },
+    Cancelled {
+        iteration: usize,
+    },
+    // What went wrong, for the page to show
+    Failed(String),
+}
+
+// Sum of squared distances from every point to the center of its cluster
+fn inertia(points: &[Vec<f64>], labels: &[usize], centers: &[Vec<f64>]) -> f64 {
+    points
+        .iter()
+        .zip(labels)
+        .filter_map(|(point, &label)| {
+            let center = centers.get(label)?;
+            Some(
+                point
+                    .iter()
+                    .zip(center)
+                    .map(|(p, c)| (p - c).powi(2))
+                    .sum::<f64>(),
+            )
+        })
+        .sum()
+}
+
+// Points of x_field against y_field, colored by color_field in the theme's
+// palette when given
+fn point_spec(x_field: &str, y_field: &str, color_field: Option<&str>) -> VegaSpec {
+    let zero_free = || ScaleDef {
+        zero: Some(false),
+        ..ScaleDef::default()
+    };
+    let spec = VegaSpec::new()
+        .mark_point()
+        .encode(
+            Channel::X,
+            FieldDef::new(x_field, FieldType::Quantitative).with_scale(zero_free()),
+        )
+        .encode(
+            Channel::Y,
+            FieldDef::new(y_field, FieldType::Quantitative).with_scale(zero_free()),
+        )
+        .tooltip(FieldDef::new(x_field, FieldType::Quantitative).with_format(".2f"))
+        .tooltip(FieldDef::new(y_field, FieldType::Quantitative).with_format(".2f"));
+
+    match color_field {
+        Some(color) => spec
+            .color(color, FieldType::Nominal)
+            .tooltip(FieldDef::new(color, FieldType::Nominal)),
+        None => spec,
+    }
+}
+
+#[component]
+pub fn KMeansComponent(k: usize, max_iter: usize, tolerance: f64) -> Element {
+    // state
+    // data
+    let mut num_points = use_signal(|| 10);
+    let mut n_clusters = use_signal(|| 2);
+    let mut seed = use_signal(|| None::<u64>);
+    let mut dataset = use_signal(|| "gaussian".to_string());
+    let mut uploaded = use_signal(DataFrame::new);
+    let mut pair_data = use_signal(PairPlotData::default);
+    let mut frame = use_signal(DataFrame::new);
+    let mut vega_data = use_signal(Vec::new);
+    let mut kmeans_params = use_signal(|| {
+        KMeans::builder()
+            .k(k)
+            .max_iter(max_iter)
+            .tolerance(tolerance)
+            .build()
+            .params()
+    });
+    // Unfitted KMeans with the parameters of the form
+    let kmeans = move || KMeans::new(k).with_params(&kmeans_params.read());
+    let mut model = kmeans();
+    model.random_state = *seed.read();
+    // KMeans fit on the brushed points only
+    let mut selection_fit = use_signal(|| None::<String>);
+    // The points clustered with each of COMPARED_KS
+    let mut k_comparison = use_signal(Vec::new);
+    // Ward clustering of the points, and the points colored by where its
+    // dendrogram is cut
+    let mut dendrogram =
+        use_signal(|| DendrogramData::new(AgglomerativeClustering::new(Linkage::Ward)));
+    let mut hierarchical_points = use_signal(Vec::<Vec<f64>>::new);
+    let mut hierarchical_plot = use_signal(ScatterPlotData::default);
+    let iris_components = use_signal(iris_pca);
+    // The iris flowers in three linked views, brushing one highlighting the
+    // same flowers in the others
+    let iris_petals = use_signal(|| {
+        let (points, species) = iris_points();
+        ScatterPlotData::from_clusters(&points, &species, &[])
+    });
+    let iris_measurements = use_signal(iris_pairs);
+    let iris_selection = use_signal(LinkedSelection::default);
+    let iris_frame = use_signal(iris_table);
+    // Every iteration of a KMeans fit, played back one frame each
+    let mut iterations = use_signal(Vec::<IterationSnapshot>::new);
+    let mut iteration_points = use_signal(Vec::<Vec<f64>>::new);
+    let mut iteration_plot = use_signal(ScatterPlotData::default);
+    let mut timeline = use_timeline(0, 2.0);
+    // The points with the group they were generated in, for classifiers to
+    // learn
+    let mut labeled_points = use_signal(Vec::<Vec<f64>>::new);
+    let mut true_labels = use_signal(Vec::<usize>::new);
+    // The points with the cluster the KMeans fit assigns them
+    let mut assignments = use_signal(DataFrame::new);
+    // Shade the region of the plane each centroid claims
+    let mut show_voronoi = use_signal(|| true);
+
+    // The fit the Run button starts, a step at a time between redraws
+    let mut run_task = use_signal(|| None::<Task>);
+    let mut run_state = use_signal(|| RunState::Idle);
+    let mut run_plot = use_signal(ScatterPlotData::default);
+    // Fit in a Web Worker instead, all at once
+    let mut run_in_worker = use_signal(|| false);
+    // The last Run fit of the previous visit, shown until the next Run
+    let mut restored_fit = use_signal(|| None::<KMeans>);
+
+    // Settings, upload and Run fit of the previous visit, then saved
+    // whenever they change
+    let mut restored = use_signal(|| false);
+    use_hook(|| {
+        spawn(async move {
+            if let Some(settings) = restore::<Settings>(STORED_SETTINGS).await {
+                dataset.set(settings.dataset);
+                num_points.set(settings.num_points);
+                n_clusters.set(settings.n_clusters);
+                seed.set(settings.seed);
+                kmeans_params.set(settings.params);
+            }
+            if let Some(frame) = restore::<DataFrame>(STORED_UPLOAD).await {
+                uploaded.set(frame);
+            }
+            restored_fit.set(restore(STORED_FIT).await);
+            restored.set(true);
+        })
+    });
+    use_effect(move || {
+        let settings = Settings {
+            dataset: dataset(),
+            num_points: num_points(),
+            n_clusters: n_clusters(),
+            seed: seed(),
+            params: kmeans_params(),
+        };
+        if restored() {
+            remember(STORED_SETTINGS, settings);
+        }
+    });
+    use_effect(move || {
+        let frame = uploaded();
+        if restored() && frame.n_rows() > 0 {
+            remember(STORED_UPLOAD, frame);
+        }
+    });
+    use_effect(move || {
+        let points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+        if let Some(model) = restored_fit.read().as_ref() {
+            let labels = model.predict(&points);
+            run_plot.set(ScatterPlotData::from_clusters(
+                &points,
+                &labels,
+                model.centroids(),
+            ));
+            run_state.set(RunState::Finished {
+                iterations: model.n_iter(),
+                inertia: inertia(&points, &labels, model.centroids()),
+                converged: model.converged(),
+            });
+        }
+    });
+
+    // Convert cluster points to Vega-Lite compatible format
+    use_effect(move || {
+        let data = {
+            let n_samples = *num_points.read();
+            let n_groups = *n_clusters.read();
+            let random_state = *seed.read();
+            let (points, labels) = match dataset.read().as_str() {
+                "upload" => uploaded_points(&uploaded.read()),
+                "iris" => iris_points(),
+                "blobs" => make_blobs(n_samples, n_groups, 2, 1.0, random_state),
+                "moons" => make_moons(n_samples, 0.1, random_state),
+                "circles" => make_circles(n_samples, 0.05, 0.5, random_state),
+                "spiral" => make_spiral(n_samples, n_groups, 0.02, random_state),
+                "anisotropic" => make_anisotropic(n_samples, n_groups, random_state),
+                _ => generate_realistic_clusters(n_groups, n_samples, 10.0, random_state),
+            };
+            let clusters = labels.iter().map(|c| format!("Cluster {}", c)).collect();
+            labeled_points.set(points.clone());
+            true_labels.set(labels);
+            let table = DataFrame::from_points(&points, &["x", "y"])
+                .and_then(|table| table.with_column("cluster", Column::Str(clusters)))
+                .unwrap_or_else(|err| {
+                    error!("Error building data table: {}", err);
+                    DataFrame::new()
+                });
+            let data = table.to_records();
+            frame.set(table);
+
+            // Debug: Log the first few data points
+            if !data.is_empty() {
+                web_sys::console::log_1(&format!("First data point: {:?}", data[0]).into());
+                web_sys::console::log_1(&format!("Total points: {}", data.len()).into());
+            } else {
+                web_sys::console::log_1(&"No data points generated".into());
+            }
+
+            data
+        };
+        vega_data.set(data);
+    });
+
+    use_effect(move || {
+        pair_data.set(upload_pairs(&uploaded.read()));
+    });
+
+    use_effect(move || {
+        let points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+        let random_state = seed();
+        k_comparison.set(compare_ks(&points, random_state));
+    });
+
+    use_effect(move || {
+        let mut points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+        points.truncate(MAX_HIERARCHICAL_POINTS);
+        let mut model = AgglomerativeClustering::new(Linkage::Ward);
+        model.fit(&points);
+        hierarchical_plot.set(ScatterPlotData::from_clusters(
+            &points,
+            &vec![0; points.len()],
+            &[],
+        ));
+        hierarchical_points.set(points);
+        dendrogram.set(DendrogramData::new(model));
+    });
+
+    use_effect(move || {
+        let points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+        let mut model = kmeans().with_history(true);
+        model.random_state = seed();
+        if let Err(err) = model.try_fit(&points, &()) {
+            error!("Error fitting KMeans: {}", err);
+            assignments.set(frame.read().clone());
+            timeline.write().set_frames(0);
+            iterations.set(vec![]);
+            return;
+        }
+        let history = model.history().to_vec();
+        let labels = model.labels().iter().map(|&label| label as i64).collect();
+        let table = frame
+            .read()
+            .clone()
+            .with_column("assigned", Column::Int(labels));
+        assignments.set(table.unwrap_or_else(|err| {
+            error!("Error building assignments table: {}", err);
+            DataFrame::new()
+        }));
+        timeline.write().set_frames(history.len());
+        iterations.set(history);
+        iteration_points.set(points);
+    });
+
+    use_effect(move || {
+        let frame = timeline.read().frame;
+        if let Some(snapshot) = iterations.read().get(frame) {
+            let plot = ScatterPlotData::from_clusters(
+                &iteration_points.read(),
+                &snapshot.assignments,
+                &snapshot.centroids,
+            );
+            iteration_plot.set(if show_voronoi() {
+                plot.with_voronoi(&snapshot.centroids)
+            } else {
+                plot
+            });
+        }
+    });
+
+    let run = move |_| {
+        if let Some(task) = run_task.take() {
+            task.cancel();
+        }
+        restored_fit.set(None);
+        let points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+        let mut model = kmeans();
+        model.random_state = seed();
+        run_state.set(RunState::Running {
+            iteration: 0,
+            inertia: f64::NAN,
+        });
+        let task = if run_in_worker() {
+            spawn(async move {
+                match spawn_fit(EstimatorSpec::kmeans(&model), points.clone()).await {
+                    Ok(result) => {
+                        let labels: Vec<usize> = result
+                            .labels
+                            .iter()
+                            .map(|label| label.unwrap_or(0))
+                            .collect();
+                        run_plot.set(ScatterPlotData::from_clusters(
+                            &points,
+                            &labels,
+                            &result.centers,
+                        ));
+                        run_state.set(RunState::Finished {
+                            iterations: result.n_iter,
+                            inertia: inertia(&points, &labels, &result.centers),
+                            converged: result.converged,
+                        });
+                    }
+                    Err(err) => {
+                        error!("Error fitting KMeans in a worker: {}", err);
+                        run_state.set(RunState::Failed(err.to_string()));
+                    }
+                }
+                run_task.set(None);
+            })
+        } else {
+            let matrix = frame.read().to_matrix(&["x", "y"]).unwrap_or_default();
+            spawn(async move {
+                // Stepping bypasses try_fit, so check the points as it would
+                if let Err(err) = check_matrix(&matrix, &model.fit_check()) {
+                    run_state.set(RunState::Failed(err.to_string()));
+                    run_task.set(None);
+                    return;
+                }
+                model.begin_fit(&matrix);
+                loop {
+                    let done = model.step(&matrix);
+                    run_plot.set(ScatterPlotData::from_clusters(
+                        &points,
+                        model.labels(),
+                        model.centroids(),
+                    ));
+                    if done {
+                        break;
+                    }
+                    run_state.set(RunState::Running {
+                        iteration: model.n_iter(),
+                        inertia: model.inertia(),
+                    });
+                    yield_now().await;
+                }
+                run_state.set(RunState::Finished {
+                    iterations: model.n_iter(),
+                    inertia: model.inertia(),
+                    converged: model.converged(),
+                });
+                remember(STORED_FIT, model);
+                run_task.set(None);
+            })
+        };
+        run_task.set(Some(task));
+    };
+
+    let cancel = move |_| {
+        if let Some(task) = run_task.take() {
+            task.cancel();
+            if let RunState::Running { iteration, .. } = run_state() {
+                run_state.set(RunState::Cancelled { iteration });
+            }
+        }
+    };
+
+    // A KMeans fitted by scikit-learn, shown like a finished Run
+    let import = move |event: FormEvent| {
+        let Some(files) = event.files() else {
+            return;
+        };
+        let Some(name) = files.files().into_iter().next() else {
+            return;
+        };
+        spawn(async move {
+            let Some(json) = files.read_file_to_string(&name).await else {
+                error!("Could not read {}", name);
+                return;
+            };
+            match KMeans::from_sklearn_json(&json) {
+                Ok(model) if model.centroids().iter().all(|c| c.len() == 2) => {
+                    if let Some(task) = run_task.take() {
+                        task.cancel();
+                    }
+                    restored_fit.set(Some(model));
+                }
+                Ok(_) => error!("{} has centroids of other than 2 features", name),
+                Err(err) => error!("Error importing {}: {}", name, err),
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            h1 {
+                "KMeans"
+            }
+            p {
+                "Currently only 2D data is supported."
+            }
+            h4 {
+                "Inputs"
+            }
+            fieldset {
+                legend {
+                    "Data"
+                }
+                p {
+                    "Enter the x and y coordinates of your data. "
+                    "Each coordinate should be separated by a comma."
+                }
+                label {
+                    "Dataset: "
+                    select {
+                        name: "dataset",
+                        value: dataset,
+                        onchange: move |event| dataset.set(event.value()),
+                        option { value: "gaussian", "Gaussian clusters" }
+                        option { value: "blobs", "Blobs" }
+                        option { value: "moons", "Moons" }
+                        option { value: "circles", "Circles" }
+                        option { value: "spiral", "Spiral" }
+                        option { value: "anisotropic", "Anisotropic" }
+                        option { value: "iris", "Iris (petals)" }
+                        option { value: "upload", "Upload CSV" }
+                    }
+                }
+                if dataset.read().as_str() == "upload" {
+                    DatasetUpload { dataset: uploaded }
+                    if pair_data.read().columns.len() >= 2 {
+                        PairPlot {
+                            data: pair_data,
+                            width: 500.0,
+                            height: 500.0,
+                            id: "upload_pairs".to_string(),
+                        }
+                    }
+                    if uploaded.read().n_columns() > 0 {
+                        DataTable { frame: uploaded }
+                    }
+                }
+                label {
+                    "Number of points: "
+                    input {
+                        type: "number",
+                        name: "num_points",
+                        placeholder: "Number of points",
+                        value: num_points,
+                        min: "0",
+                        onchange: move |event| {
+                            let value = event.value().parse();
+                            match value {
+                                Ok(value) => num_points.set(value),
+                                Err(err) => error!("Error parsing number of points: {:?}", err),
+                            }
+                        }
+                    }
+                }
+                label{
+                    "Number of Gaussian clusters: "
+                    input {
+                        type: "number",
+                        value: n_clusters,
+                        name: "n_clusters",
+                        placeholder: "Number of clusters",
+                        oninput: move |event| {
+                            let value = event.value().parse();
+                            match value {
+                                Ok(value) => n_clusters.set(value),
+                                Err(err) => error!("Error parsing number of clusters: {:?}", err),
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Seed: "
+                    input {
+                        type: "number",
+                        value: seed().map(|s| s.to_string()).unwrap_or_default(),
+                        name: "seed",
+                        placeholder: "Random",
+                        min: "0",
+                        oninput: move |event| {
+                            let value = event.value();
+                            if value.is_empty() {
+                                seed.set(None);
+                                return;
+                            }
+                            match value.parse() {
+                                Ok(value) => seed.set(Some(value)),
+                                Err(err) => error!("Error parsing seed: {:?}", err),
+                            }
+                        }
+                    }
+                }
+            }
+            fieldset {
+                legend {
+                    "Parameters"
+                }
+                ParamForm { specs: <KMeans>::param_specs(), values: kmeans_params }
+                button {
+                    onclick: move |_| {
+                        let points = frame.read().to_points(&["x", "y"]).unwrap_or_default();
+                        match select_k(&points, 1..=10, *seed.read()).suggested_k() {
+                            Some(best) => kmeans_params.write().set("k", best as f64),
+                            None => error!("Could not detect k for {} points", points.len()),
+                        }
+                    },
+                    "Auto-detect k"
+                }
+            }
+        }
+        div {
+            h4 {
+                "Results"
+            }
+            p {
+                "Model: "
+                { model.to_string() }
+            }
+            div {
+                class: "flex items-center gap-2 mt-1 text-sm",
+                button {
+                    class: "px-2 border rounded",
+                    disabled: run_task.read().is_some(),
+                    onclick: run,
+                    "Run"
+                }
+                button {
+                    class: "px-2 border rounded",
+                    disabled: run_task.read().is_none(),
+                    onclick: cancel,
+                    "Cancel"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: run_in_worker(),
+                        disabled: run_task.read().is_some(),
+                        onchange: move |event| run_in_worker.set(event.checked()),
+                    }
+                    " In a worker"
+                }
+                label {
+                    "Import scikit-learn fit: "
+                    input {
+                        r#type: "file",
+                        accept: ".json",
+                        disabled: run_task.read().is_some(),
+                        onchange: import,
+                    }
+                }
+                span {
+                    match run_state() {
+                        RunState::Idle => "Run fits KMeans to the points.".to_string(),
+                        RunState::Running { iteration: 0, .. } if run_in_worker() => {
+                            "Fitting in a worker".to_string()
+                        }
+                        RunState::Running { iteration: 0, .. } => "Starting".to_string(),
+                        RunState::Running { iteration, inertia } => format!(
+                            "Iteration {} of at most {}, inertia {:.3}",
+                            iteration, model.max_iter, inertia
+                        ),
+                        RunState::Finished { iterations, inertia, converged: true } => format!(
+                            "Converged after {} iterations, inertia {:.3}",
+                            iterations, inertia
+                        ),
+                        RunState::Finished { iterations, inertia, converged: false } => format!(
+                            "Stopped after {} iterations without converging, inertia {:.3}",
+                            iterations, inertia
+                        ),
+                        RunState::Cancelled { iteration } => {
+                            format!("Cancelled after {} iterations", iteration)
+                        }
+                        RunState::Failed(message) => format!("The fit failed: {}.", message),
+                    }
+                }
+            }
+            if run_state() != RunState::Idle {
+                div {
+                    class: "w-full border rounded shadow-lg",
+                    ScatterPlot { data: run_plot, width: 400.0, height: 300.0, id: "kmeans-run" }
+                }
+            }
+            p {
+                "Data: "
+            }
+
+            div {
+                class: "w-full border rounded shadow-lg",
+                VegaLiteChart {
+                    data: vega_data,
+                    spec: point_spec("x", "y", Some("cluster")).title("KMeans Clustering"),
+                    width: 400.0,
+                    height: 200.0,
+                    id: "kmeans_chart".to_string(),
+                    selection: Param::interval("brush"),
+                    export: "kmeans_chart".to_string(),
+                    on_select: move |selection: Selection| {
+                        let rows = vega_data.read();
+                        let points: Vec<Vec<f64>> = selection
+                            .filter(&rows)
+                            .into_iter()
+                            .filter_map(|i| Some(vec![rows[i]["x"].as_f64()?, rows[i]["y"].as_f64()?]))
+                            .collect();
+                        if points.is_empty() {
+                            selection_fit.set(None);
+                            return;
+                        }
+                        let mut selected_model = kmeans();
+                        selected_model.random_state = seed();
+                        selection_fit.set(Some(match selected_model.try_fit(&points, &()) {
+                            Ok(()) => format!(
+                                "KMeans on {} selected points: inertia {:.3}",
+                                points.len(),
+                                selected_model.inertia()
+                            ),
+                            Err(err) => format!("Can't fit KMeans on the selection: {}.", err),
+                        }));
+                    }
+                }
+            }
+            p {
+                match selection_fit() {
+                    Some(fit) => fit,
+                    None => "Drag over the chart to fit KMeans on a selection.".to_string(),
+                }
+            }
+            h4 {
+                "Clusters for k = 2 to 5"
+            }
+            div {
+                class: "w-full border rounded shadow-lg",
+                FacetChart {
+                    data: k_comparison,
+                    spec: point_spec("x", "y", Some("cluster")),
+                    facet: Facet::wrap("k", FieldType::Ordinal, 2),
+                    width: 400.0,
+                    height: 400.0,
+                    id: "k_comparison_chart".to_string(),
+                }
+            }
+            h4 {
+                "KMeans iterations"
+            }
+            ScatterPlot {
+                data: iteration_plot,
+                width: 500.0,
+                height: 300.0,
+                id: "kmeans_iterations".to_string(),
+            }
+            TimelineControls { timeline }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: show_voronoi(),
+                    onchange: move |evt: FormEvent| show_voronoi.set(evt.checked()),
+                }
+                " Voronoi regions"
+            }
+            DataTable { frame: assignments, page_size: 10 }
+            h4 { "Classifier comparison" }
+            p { "Classifiers learning the groups the points were generated in, scored on held-out points." }
+            ModelComparison {
+                points: labeled_points,
+                labels: true_labels,
+                width: 600.0,
+                height: 250.0,
+                id: "model_comparison".to_string(),
+                random_state: seed(),
+            }
+            h4 {
+                "Hierarchical clustering"
+            }
+            p {
+                "Click or drag on the dendrogram to cut it into clusters, outlined by their convex hulls."
+            }
+            Dendrogram {
+                data: dendrogram,
+                width: 500.0,
+                height: 300.0,
+                id: "dendrogram".to_string(),
+                on_cut: move |labels: Vec<usize>| {
+                    let points = hierarchical_points.read();
+                    hierarchical_plot.set(
+                        ScatterPlotData::from_clusters(&points, &labels, &[])
+                            .with_outlines(&points, &labels, Outline::ConvexHull),
+                    );
+                },
+            }
+            ScatterPlot {
+                data: hierarchical_plot,
+                width: 500.0,
+                height: 300.0,
+                id: "hierarchical_clusters".to_string(),
+            }
+            h4 {
+                "Iris"
+            }
+            p {
+                "Shift-drag over the petals to highlight the same flowers in the other views. Drag the principal components to rotate them, scroll to zoom, double-click to reset."
+            }
+            LinkedSelectionProvider {
+                selection: iris_selection,
+                ScatterPlot {
+                    data: iris_petals,
+                    width: 500.0,
+                    height: 300.0,
+                    id: "iris_petals".to_string(),
+                    linked: "iris".to_string(),
+                }
+                Scatter3d {
+                    data: iris_components,
+                    width: 500.0,
+                    height: 400.0,
+                    id: "iris_pca".to_string(),
+                    linked: "iris".to_string(),
+                }
+                PairPlot {
+                    data: iris_measurements,
+                    width: 500.0,
+                    height: 500.0,
+                    id: "iris_pairs".to_string(),
+                    linked: "iris".to_string(),
+                }
+                DataTable { frame: iris_frame, page_size: 10, linked: "iris".to_string() }
+            }
+        }
+    }
+}
+
+// Rows of points with the cluster KMeans assigns them for each of
+// COMPARED_KS, for k too large for the points none
+fn compare_ks(points: &[Vec<f64>], random_state: Option<u64>) -> Vec<Value> {
+    COMPARED_KS
+        .iter()
+        .filter(|&&k| k <= points.len())
+        .flat_map(|&k| {
+            let mut model = KMeans::new(k);
+            model.random_state = random_state;
+            let labels = model.fit_predict(points);
+            points.iter().zip(labels).map(move |(p, label)| {
+                json!({"x": p[0], "y": p[1], "k": k, "cluster": format!("Cluster {}", label)})
+            })
+        })
+        .collect()
+}
+
+// The iris flowers projected onto their first three principal components,
+// colored by species
+fn iris_pca() -> Scatter3dData {
+    let iris = load_iris();
+    let components = Pca::new(3).fit_transform(&iris.data);
+    let species = iris
+        .target
+        .iter()
+        .map(|&t| iris.target_names.get(t).cloned().unwrap_or_default())
+        .collect();
+    Scatter3dData::from_points(&components)
+        .with_axis_names("PC1", "PC2", "PC3")
+        .with_classes(species)
+}
+
+// The four measurements of the iris flowers, colored by species
+fn iris_pairs() -> PairPlotData {
+    let iris = load_iris();
+    let columns = (0..iris.feature_names.len())
+        .map(|c| iris.data.iter().map(|p| p[c]).collect())
+        .collect();
+    let species = iris
+        .target
+        .iter()
+        .map(|&t| iris.target_names.get(t).cloned().unwrap_or_default())
+        .collect();
+    PairPlotData::new(iris.feature_names, columns).with_classes(species)
+}
+
+// The iris flowers as a table of their measurements and species
+fn iris_table() -> DataFrame {
+    let iris = load_iris();
+    let names: Vec<&str> = iris.feature_names.iter().map(String::as_str).collect();
+    let species = iris
+        .target
+        .iter()
+        .map(|&t| iris.target_names.get(t).cloned().unwrap_or_default())
+        .collect();
+    DataFrame::from_points(&iris.data, &names)
+        .and_then(|table| table.with_column("species", Column::Str(species)))
+        .unwrap_or_else(|err| {
+            error!("Error building iris table: {}", err);
+            DataFrame::new()
+        })
+}
+
+// Petal length and width of the iris flowers, grouped by species
+fn iris_points() -> (Vec<Vec<f64>>, Vec<usize>) {
+    let iris = load_iris();
+    let points = iris.data.iter().map(|p| vec![p[2], p[3]]).collect();
+    (points, iris.target)
+}
+
+// Pair plot of the first numeric columns of an uploaded table, colored by
+// its first string column if it has one
+fn upload_pairs(table: &DataFrame) -> PairPlotData {
+    let names = table.numeric_column_names();
+    let names = &names[..names.len().min(MAX_COLUMNS)];
+    let class_column = table
+        .column_names()
+        .iter()
+        .find(|name| matches!(table.column(name), Some(Column::Str(_))));
+    PairPlotData::from_frame(table, names, class_column.map(String::as_str)).unwrap_or_else(|err| {
+        error!("Error building pair plot: {}", err);
+        PairPlotData::default()
+    })
+}
+
+// First two numeric columns of an uploaded table as points, all in group 0.
+// Rows with missing values are dropped.
+fn uploaded_points(table: &DataFrame) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let names = table.numeric_column_names();
+    if names.len() < 2 {
+        if table.n_columns() > 0 {
+            error!("Uploaded data needs two numeric columns, found {:?}", names);
+        }
+        return (vec![], vec![]);
+    }
+    let points: Vec<Vec<f64>> = table
+        .to_points(&names[..2])
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| p.iter().all(|v| !v.is_nan()))
+        .collect();
+    let labels = vec![0; points.len()];
+    (points, labels)
+}
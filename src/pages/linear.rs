@@ -0,0 +1,137 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use moonlight::data::generators::make_regression;
+use moonlight::error::MoonlightError;
+use moonlight::ml::base::{Estimator, Predictor};
+use moonlight::ml::metrics::regression::r2_score;
+use moonlight::ml::onnx::ToOnnx;
+use moonlight::ml::params::Configurable;
+use moonlight::ml::regression::linear::LinearRegression;
+use moonlight::ml::regression::LinearModel;
+use moonlight::plot::export::download_bytes;
+use moonlight::plot::params::ParamForm;
+use moonlight::plot::scatter::{Layer, ScatterPlot, ScatterPlotData};
+
+// Line the points are drawn around
+const SLOPE: f64 = 1.5;
+const INTERCEPT: f64 = 4.0;
+
+// Noisy points around a line, and the line least squares fits to them
+#[component]
+pub fn LinearRegressionPage() -> Element {
+    let mut num_points = use_signal(|| 100_usize);
+    let mut noise = use_signal(|| 3.0);
+    let mut seed = use_signal(|| None::<u64>);
+    let params = use_signal(|| LinearRegression::new().params());
+    let mut plot = use_signal(ScatterPlotData::default);
+    let mut summary = use_signal(String::new);
+    let mut fitted_model = use_signal(|| None::<LinearRegression>);
+
+    use_effect(move || {
+        let (x, y) = make_regression(num_points(), SLOPE, INTERCEPT, noise(), seed());
+        let points = ScatterPlotData::new(x.iter().map(|p| p[0]).collect(), y.clone());
+        let mut model = LinearRegression::new().with_params(&params.read());
+        if let Err(err) = model.try_fit(&x, &y) {
+            plot.set(points);
+            summary.set(format!("Can't fit a line: {}.", err));
+            fitted_model.set(None);
+            return;
+        }
+        let r2 = r2_score(&y, &model.predict(&x));
+        let ends = [-10.0, 10.0];
+        let fitted = model.predict(&ends.map(|end| vec![end]));
+        plot.set(
+            points.with_layer(Layer::line("Fit", ends.to_vec(), fitted).with_palette_index(1)),
+        );
+        match model.coefficients().first() {
+            Some(slope) => summary.set(format!(
+                "y = {:.3} x + {:.3}, R² {:.3} (drawn around y = {} x + {})",
+                slope,
+                model.intercept(),
+                r2,
+                SLOPE,
+                INTERCEPT
+            )),
+            None => error!("Linear regression fit no coefficients"),
+        }
+        fitted_model.set(Some(model));
+    });
+
+    // The fitted line as an ONNX model, to serve it with another runtime
+    let export_onnx = move |_| {
+        let Some(model) = fitted_model.read().clone() else {
+            return;
+        };
+        let saved = model
+            .to_onnx()
+            .map_err(MoonlightError::from)
+            .and_then(|bytes| {
+                download_bytes(&bytes, "linear-regression.onnx", "application/octet-stream")
+            });
+        if let Err(err) = saved {
+            error!("Error exporting ONNX model: {}", err);
+        }
+    };
+
+    rsx! {
+        h2 { class: "text-lg font-semibold", "Linear regression" }
+        fieldset {
+            legend { "Inputs" }
+            label {
+                "Number of points: "
+                input {
+                    r#type: "number",
+                    value: num_points,
+                    min: "0",
+                    onchange: move |event| match event.value().parse() {
+                        Ok(value) => num_points.set(value),
+                        Err(err) => error!("Error parsing number of points: {:?}", err),
+                    },
+                }
+            }
+            label {
+                "Noise: "
+                input {
+                    r#type: "number",
+                    value: noise,
+                    min: "0",
+                    step: "0.5",
+                    onchange: move |event| match event.value().parse::<f64>() {
+                        Ok(value) => noise.set(value.max(0.0)),
+                        Err(err) => error!("Error parsing noise: {:?}", err),
+                    },
+                }
+            }
+            label {
+                "Seed: "
+                input {
+                    r#type: "number",
+                    value: seed().map(|s| s.to_string()).unwrap_or_default(),
+                    placeholder: "Random",
+                    min: "0",
+                    onchange: move |event| {
+                        let value = event.value();
+                        if value.is_empty() {
+                            seed.set(None);
+                            return;
+                        }
+                        match value.parse() {
+                            Ok(value) => seed.set(Some(value)),
+                            Err(err) => error!("Error parsing seed: {:?}", err),
+                        }
+                    },
+                }
+            }
+        }
+        fieldset {
+            legend { "Parameters" }
+            ParamForm { specs: <LinearRegression>::param_specs(), values: params }
+        }
+        div {
+            class: "w-full border rounded shadow-lg",
+            ScatterPlot { data: plot, width: 600.0, height: 400.0, id: "linear-regression" }
+        }
+        p { "{summary}" }
+        button { disabled: fitted_model.read().is_none(), onclick: export_onnx, "Export ONNX" }
+    }
+}
@@ -0,0 +1,4 @@
+// One page of the playground per algorithm, see Route
+pub mod kmeans;
+pub mod linear;
+pub mod tsne;
@@ -0,0 +1,68 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use moonlight::data::datasets::load_iris;
+use moonlight::ml::manifold::tsne::Tsne;
+use moonlight::ml::params::Configurable;
+use moonlight::plot::params::ParamForm;
+use moonlight::plot::scatter::{ScatterPlot, ScatterPlotData};
+
+// The four iris measurements embedded in the plane by t-SNE, colored by
+// species, embedded again whenever a parameter changes
+#[component]
+pub fn TsnePage() -> Element {
+    let mut seed = use_signal(|| Some(0_u64));
+    let params = use_signal(|| Tsne::default().params());
+    let mut plot = use_signal(ScatterPlotData::default);
+    let mut summary = use_signal(String::new);
+
+    use_effect(move || {
+        let iris = load_iris();
+        let mut model = Tsne::new(2).with_params(&params.read());
+        model.random_state = seed();
+        let embedding = model.fit_transform(&iris.data);
+        plot.set(ScatterPlotData::from_clusters(
+            &embedding,
+            &iris.target,
+            &[],
+        ));
+        summary.set(format!(
+            "{} flowers embedded in {} iterations, KL divergence {:.3}",
+            embedding.len(),
+            model.n_iter(),
+            model.kl_divergence()
+        ));
+    });
+
+    rsx! {
+        h2 { class: "text-lg font-semibold", "t-SNE" }
+        fieldset {
+            legend { "Parameters" }
+            ParamForm { specs: Tsne::param_specs(), values: params }
+            label {
+                "Seed: "
+                input {
+                    r#type: "number",
+                    value: seed().map(|s| s.to_string()).unwrap_or_default(),
+                    placeholder: "Random",
+                    min: "0",
+                    onchange: move |event| {
+                        let value = event.value();
+                        if value.is_empty() {
+                            seed.set(None);
+                            return;
+                        }
+                        match value.parse() {
+                            Ok(value) => seed.set(Some(value)),
+                            Err(err) => error!("Error parsing seed: {:?}", err),
+                        }
+                    },
+                }
+            }
+        }
+        div {
+            class: "w-full border rounded shadow-lg",
+            ScatterPlot { data: plot, width: 600.0, height: 400.0, id: "tsne" }
+        }
+        p { "{summary}" }
+    }
+}
@@ -0,0 +1,279 @@
+use dioxus::prelude::*;
+use log::error;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Promise;
+
+const DEFAULT_FPS: f64 = 5.0;
+// Speeds offered by TimelineControls, in frames per second
+const FPS_CHOICES: [f64; 6] = [1.0, 2.0, 5.0, 10.0, 20.0, 30.0];
+
+// Callback run on every animation frame while a timeline plays
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+// Position in a sequence of frames, e.g. the snapshots of an iterative
+// algorithm, and whether it is playing through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeline {
+    pub n_frames: usize,
+    pub frame: usize,
+    pub playing: bool,
+    pub fps: f64,
+    // Start over after the last frame instead of stopping there
+    pub looping: bool,
+    // When the frame last changed while playing, in milliseconds
+    last_change: Option<f64>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline::new(0)
+    }
+}
+
+impl Timeline {
+    pub fn new(n_frames: usize) -> Self {
+        Timeline {
+            n_frames,
+            frame: 0,
+            playing: false,
+            fps: DEFAULT_FPS,
+            looping: false,
+            last_change: None,
+        }
+    }
+
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.frame + 1 >= self.n_frames
+    }
+
+    // Plays from the current frame, from the first when at the last
+    pub fn play(&mut self) {
+        if self.n_frames < 2 {
+            return;
+        }
+        if self.is_last() {
+            self.frame = 0;
+        }
+        self.playing = true;
+        self.last_change = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    // Shows frame, clamped to the frames there are, pausing
+    pub fn seek(&mut self, frame: usize) {
+        self.pause();
+        self.frame = frame.min(self.n_frames.saturating_sub(1));
+    }
+
+    pub fn step_forward(&mut self) {
+        self.seek(self.frame + 1);
+    }
+
+    pub fn step_back(&mut self) {
+        self.seek(self.frame.saturating_sub(1));
+    }
+
+    // Replaces the frames, e.g. after refitting, keeping the current frame
+    // where it still exists
+    pub fn set_frames(&mut self, n_frames: usize) {
+        self.n_frames = n_frames;
+        self.frame = self.frame.min(n_frames.saturating_sub(1));
+        if n_frames < 2 {
+            self.pause();
+        }
+    }
+
+    // Moves on by as many frames as fps allows since the last change, at
+    // time now in milliseconds. Returns whether the frame changed. Stops at
+    // the last frame unless looping.
+    pub fn tick(&mut self, now: f64) -> bool {
+        if !self.playing || self.n_frames == 0 {
+            return false;
+        }
+        let Some(last_change) = self.last_change else {
+            self.last_change = Some(now);
+            return false;
+        };
+        let interval = 1000.0 / self.fps.max(f64::EPSILON);
+        let steps = ((now - last_change) / interval) as usize;
+        if steps == 0 {
+            return false;
+        }
+        self.last_change = Some(last_change + steps as f64 * interval);
+        if self.looping {
+            self.frame = (self.frame + steps) % self.n_frames;
+        } else {
+            self.frame = (self.frame + steps).min(self.n_frames - 1);
+            if self.is_last() {
+                self.playing = false;
+            }
+        }
+        true
+    }
+}
+
+// Timeline of n_frames that advances on the browser's animation frames
+// while playing
+pub fn use_timeline(n_frames: usize, fps: f64) -> Signal<Timeline> {
+    let mut timeline = use_signal(|| Timeline::new(n_frames).with_fps(fps));
+    // The callback, re-requested from itself, and the pending frame if any
+    let callback = use_hook(|| Rc::new(RefCell::new(None::<FrameCallback>)));
+    let pending = use_hook(|| Rc::new(Cell::new(None::<i32>)));
+
+    use_effect({
+        let callback = callback.clone();
+        let pending = pending.clone();
+        move || {
+            if !timeline.read().playing || pending.get().is_some() {
+                return;
+            }
+            if callback.borrow().is_none() {
+                let next = Rc::downgrade(&callback);
+                let pending = pending.clone();
+                *callback.borrow_mut() = Some(Closure::new(move |now: f64| {
+                    pending.set(None);
+                    let mut updated = *timeline.peek();
+                    updated.tick(now);
+                    if updated != *timeline.peek() {
+                        timeline.set(updated);
+                    }
+                    if !updated.playing {
+                        return;
+                    }
+                    if let Some(next) = next.upgrade() {
+                        pending.set(request_frame(next.borrow().as_ref()));
+                    }
+                }));
+            }
+            pending.set(request_frame(callback.borrow().as_ref()));
+        }
+    });
+
+    use_drop(move || {
+        if let (Some(id), Some(window)) = (pending.take(), web_sys::window()) {
+            window.cancel_animation_frame(id).unwrap_or(());
+        }
+    });
+
+    timeline
+}
+
+// Id of the animation frame requested for callback, None when the browser
+// refused it
+fn request_frame(callback: Option<&FrameCallback>) -> Option<i32> {
+    web_sys::window()?
+        .request_animation_frame(callback?.as_ref().unchecked_ref())
+        .ok()
+}
+
+// Resolves once the browser has had a turn to draw and handle input, so
+// that long work done a step at a time between awaits doesn't freeze the
+// page. Resolves at once outside a browser.
+pub async fn yield_now() {
+    let promise = Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window()
+            .is_some_and(|window| window.set_timeout_with_callback(&resolve).is_ok());
+        if !scheduled {
+            // Resolving can't fail for a function the promise made itself
+            resolve.call0(&JsValue::UNDEFINED).ok();
+        }
+    });
+    if let Err(err) = JsFuture::from(promise).await {
+        error!("Error yielding to the browser: {:?}", err);
+    }
+}
+
+// Play, pause and step buttons with a slider over the frames and a speed
+// choice
+#[component]
+pub fn TimelineControls(timeline: Signal<Timeline>) -> Element {
+    let current = timeline();
+    let last = current.n_frames.saturating_sub(1);
+
+    rsx! {
+        div {
+            class: "flex items-center gap-2 mt-1 text-sm",
+            button {
+                class: "px-2 border rounded",
+                title: "First frame",
+                onclick: move |_| timeline.write().seek(0),
+                "⏮"
+            }
+            button {
+                class: "px-2 border rounded",
+                title: "Previous frame",
+                onclick: move |_| timeline.write().step_back(),
+                "◀"
+            }
+            button {
+                class: "px-2 border rounded",
+                title: if current.playing { "Pause" } else { "Play" },
+                onclick: move |_| timeline.write().toggle(),
+                if current.playing { "⏸" } else { "▶" }
+            }
+            button {
+                class: "px-2 border rounded",
+                title: "Next frame",
+                onclick: move |_| timeline.write().step_forward(),
+                "▶|"
+            }
+            input {
+                r#type: "range",
+                min: "0",
+                max: "{last}",
+                value: "{current.frame}",
+                oninput: move |event| {
+                    if let Ok(frame) = event.value().parse::<usize>() {
+                        timeline.write().seek(frame);
+                    }
+                },
+            }
+            span { "Frame {current.frame + 1} / {current.n_frames}" }
+            select {
+                class: "px-1 border rounded",
+                value: "{current.fps}",
+                onchange: move |event| {
+                    if let Ok(fps) = event.value().parse::<f64>() {
+                        timeline.write().fps = fps;
+                    }
+                },
+                for fps in FPS_CHOICES {
+                    option { value: "{fps}", selected: fps == current.fps, "{fps} fps" }
+                }
+            }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: current.looping,
+                    onchange: move |event| timeline.write().looping = event.checked(),
+                }
+                " Loop"
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_category_axis, draw_grid, draw_title, draw_y_axis, MARGIN,
+};
+use super::color::Palette;
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scale::{AxisRange, Scale};
+use super::scatter::Drawable;
+use super::theme::{themed, use_theme};
+use crate::data::frame::DataFrame;
+use crate::ml::utils::quantile;
+use dioxus::prelude::*;
+use serde::Serialize;
+use std::f64;
+use web_sys::CanvasRenderingContext2d;
+
+// Tukey's fences: values further than this many IQRs outside the box are
+// outliers
+const WHISKER_IQR: f64 = 1.5;
+// Points the density of a violin is evaluated at
+const DENSITY_POINTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxPlotKind {
+    // Quartile box, whiskers and outlier dots
+    Box,
+    // Kernel density estimate mirrored around the group's axis, with the
+    // quartiles and median drawn inside
+    Violin,
+}
+
+// Tukey box plot summary of one group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoxStats {
+    pub group: String,
+    pub count: usize,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    // Furthest values still within 1.5 IQR of the box
+    pub lower_whisker: f64,
+    pub upper_whisker: f64,
+    // Values beyond the whiskers
+    pub outliers: Vec<f64>,
+}
+
+// Distributions of one variable side by side, one per group (cluster,
+// class, ...). NaNs are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxPlotData {
+    pub groups: Vec<(String, Vec<f64>)>,
+    pub kind: BoxPlotKind,
+    // CSS color of the boxes and violins
+    pub color: String,
+}
+
+impl BoxStats {
+    // None without values
+    pub fn new(group: &str, values: &[f64]) -> Option<Self> {
+        let values: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+        if values.is_empty() {
+            return None;
+        }
+        let q1 = quantile(&values, 0.25);
+        let q3 = quantile(&values, 0.75);
+        let low_fence = q1 - WHISKER_IQR * (q3 - q1);
+        let high_fence = q3 + WHISKER_IQR * (q3 - q1);
+        let inside = values
+            .iter()
+            .copied()
+            .filter(|v| (low_fence..=high_fence).contains(v));
+        Some(BoxStats {
+            group: group.to_string(),
+            count: values.len(),
+            q1,
+            median: quantile(&values, 0.5),
+            q3,
+            lower_whisker: inside.clone().fold(f64::INFINITY, f64::min),
+            upper_whisker: inside.fold(f64::NEG_INFINITY, f64::max),
+            outliers: values
+                .iter()
+                .copied()
+                .filter(|v| !(low_fence..=high_fence).contains(v))
+                .collect(),
+        })
+    }
+}
+
+impl Default for BoxPlotData {
+    fn default() -> Self {
+        BoxPlotData::new(vec![])
+    }
+}
+
+impl BoxPlotData {
+    pub fn new(groups: Vec<(String, Vec<f64>)>) -> Self {
+        BoxPlotData {
+            groups,
+            kind: BoxPlotKind::Box,
+            color: Palette::default().color(0).to_string(),
+        }
+    }
+
+    // A single group with every value of a numeric column
+    pub fn from_column(frame: &DataFrame, name: &str) -> Option<Self> {
+        Some(BoxPlotData::new(vec![(
+            name.to_string(),
+            frame.numeric_values(name)?,
+        )]))
+    }
+
+    // A numeric column split by the values of another, e.g. a feature per
+    // cluster
+    pub fn from_grouped_column(frame: &DataFrame, name: &str, by: &str) -> Option<Self> {
+        Some(BoxPlotData::new(frame.grouped_values(name, by)?))
+    }
+
+    pub fn with_kind(mut self, kind: BoxPlotKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    // Summary of every group with values
+    pub fn box_stats(&self) -> Vec<BoxStats> {
+        self.groups
+            .iter()
+            .filter_map(|(group, values)| BoxStats::new(group, values))
+            .collect()
+    }
+}
+
+impl Drawable for BoxPlotData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let margin = MARGIN;
+        let groups: Vec<(BoxStats, &[f64])> = self
+            .groups
+            .iter()
+            .filter_map(|(group, values)| Some((BoxStats::new(group, values)?, values.as_slice())))
+            .collect();
+        let y_range = AxisRange::from_values(
+            self.groups.iter().flat_map(|(_, v)| v).copied(),
+            Scale::Linear,
+        );
+
+        draw_grid(context, width, height, None, Some(y_range), config);
+        draw_y_axis(context, height, y_range, config);
+        let labels: Vec<String> = groups.iter().map(|(s, _)| s.group.clone()).collect();
+        draw_category_axis(context, width, height, &labels, config);
+
+        let to_y = |v: f64| height - margin - y_range.fraction(v) * (height - 2.0 * margin);
+        let slot = (width - 2.0 * margin) / groups.len().max(1) as f64;
+
+        context.set_line_width(1.5);
+        for (i, (group_stats, values)) in groups.iter().enumerate() {
+            let center = margin + (i as f64 + 0.5) * slot;
+            context.set_stroke_style_str(&config.axis_color);
+            context.set_fill_style_str(&self.color);
+            match self.kind {
+                BoxPlotKind::Box => {
+                    draw_box(context, group_stats, center, 0.25 * slot, &to_y);
+                    for &outlier in &group_stats.outliers {
+                        context.begin_path();
+                        context
+                            .arc(center, to_y(outlier), 2.5, 0.0, 2.0 * f64::consts::PI)
+                            .unwrap_or(());
+                        context.stroke();
+                    }
+                }
+                BoxPlotKind::Violin => {
+                    let density = kernel_density(values);
+                    let peak = density.iter().map(|&(_, d)| d).fold(0.0, f64::max);
+                    let half = 0.4 * slot;
+                    context.begin_path();
+                    for &(v, d) in &density {
+                        context.line_to(center + half * d / peak, to_y(v));
+                    }
+                    for &(v, d) in density.iter().rev() {
+                        context.line_to(center - half * d / peak, to_y(v));
+                    }
+                    context.close_path();
+                    context.set_global_alpha(0.6);
+                    context.fill();
+                    context.set_global_alpha(1.0);
+                    context.stroke();
+                    draw_box(context, group_stats, center, 0.04 * slot, &to_y);
+                }
+            }
+        }
+        draw_title(context, width, config);
+    }
+}
+
+// Box or violin plot on a canvas, redrawn whenever data changes
+#[component]
+pub fn BoxPlot(
+    data: Signal<BoxPlotData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
+
+// Quartile box with the median and whiskers, half wide either side of center
+fn draw_box(
+    context: &CanvasRenderingContext2d,
+    stats: &BoxStats,
+    center: f64,
+    half: f64,
+    to_y: &dyn Fn(f64) -> f64,
+) {
+    // Whiskers with caps
+    context.begin_path();
+    context.move_to(center, to_y(stats.upper_whisker));
+    context.line_to(center, to_y(stats.q3));
+    context.move_to(center, to_y(stats.q1));
+    context.line_to(center, to_y(stats.lower_whisker));
+    for whisker in [stats.lower_whisker, stats.upper_whisker] {
+        context.move_to(center - half / 2.0, to_y(whisker));
+        context.line_to(center + half / 2.0, to_y(whisker));
+    }
+    context.stroke();
+
+    context.set_global_alpha(0.6);
+    context.fill_rect(
+        center - half,
+        to_y(stats.q3),
+        2.0 * half,
+        to_y(stats.q1) - to_y(stats.q3),
+    );
+    context.set_global_alpha(1.0);
+    context.stroke_rect(
+        center - half,
+        to_y(stats.q3),
+        2.0 * half,
+        to_y(stats.q1) - to_y(stats.q3),
+    );
+
+    context.begin_path();
+    context.move_to(center - half, to_y(stats.median));
+    context.line_to(center + half, to_y(stats.median));
+    context.stroke();
+}
+
+// Gaussian kernel density estimate over the range of the values, with
+// Scott's bandwidth 1.06 * std * n^(-1/5). (value, density) pairs.
+fn kernel_density(values: &[f64]) -> Vec<(f64, f64)> {
+    let values: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    let n = values.len() as f64;
+    let Some(min) = values.iter().copied().reduce(f64::min) else {
+        return vec![];
+    };
+    let max = values.iter().copied().fold(min, f64::max);
+    let mean = values.iter().sum::<f64>() / n;
+    let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let bandwidth = if std > 0.0 {
+        1.06 * std * n.powf(-0.2)
+    } else {
+        1.0
+    };
+
+    let norm = 1.0 / (n * bandwidth * (2.0 * f64::consts::PI).sqrt());
+    (0..DENSITY_POINTS)
+        .map(|i| {
+            let x = min + (max - min) * i as f64 / (DENSITY_POINTS - 1) as f64;
+            let density: f64 = values
+                .iter()
+                .map(|v| (-0.5 * ((x - v) / bandwidth).powi(2)).exp())
+                .sum();
+            (x, density * norm)
+        })
+        .collect()
+}
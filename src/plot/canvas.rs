@@ -0,0 +1,446 @@
+use super::config::{LegendPosition, PlotConfig};
+use super::responsive::ChartSize;
+use super::scale::AxisRange;
+use super::scatter::MarkerShape;
+use std::f64;
+use wasm_bindgen::JsCast;
+use web_sys::CanvasRenderingContext2d;
+
+// Space around the plot area for the axes and their labels
+pub(crate) const MARGIN: f64 = 50.0;
+// Least room in pixels between ticks, so that their labels don't overlap
+const X_TICK_SPACING: f64 = 70.0;
+const Y_TICK_SPACING: f64 = 40.0;
+// Gap between the legend box and the plot area's edges
+const LEGEND_INSET: f64 = 10.0;
+
+// How a legend entry shows what it labels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Swatch {
+    Line,
+    Marker(MarkerShape),
+    Fill,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LegendEntry {
+    pub label: String,
+    pub color: String,
+    pub swatch: Swatch,
+}
+
+// 2D context of the canvas element with the given id, None until it is in
+// the document
+pub(crate) fn context_2d(canvas_id: &str) -> Option<CanvasRenderingContext2d> {
+    web_sys::window()?
+        .document()?
+        .get_element_by_id(canvas_id)?
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .ok()?
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()
+}
+
+// CSS font of the given size in pixels, in the configured family
+pub(crate) fn font(size: f64, config: &PlotConfig) -> String {
+    format!("{}px {}", size, config.font_family)
+}
+
+// Scales drawing to the screen's pixel ratio, so that charts draw in CSS
+// pixels, then clears the canvas and fills it with the configured background
+pub(crate) fn clear_canvas(
+    context: &CanvasRenderingContext2d,
+    size: ChartSize,
+    config: &PlotConfig,
+) {
+    let ratio = size.pixel_ratio;
+    context
+        .set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0)
+        .unwrap_or(());
+    context.clear_rect(0.0, 0.0, size.width, size.height);
+    if let Some(background) = &config.background {
+        context.set_fill_style_str(background);
+        context.fill_rect(0.0, 0.0, size.width, size.height);
+    }
+}
+
+pub(crate) fn draw_axes(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    x: AxisRange,
+    y: AxisRange,
+    config: &PlotConfig,
+) {
+    draw_grid(context, width, height, Some(x), Some(y), config);
+    draw_x_axis(context, width, height, x, config);
+    draw_y_axis(context, height, y, config);
+}
+
+// Bottom axis with nice ticks over the range, and its label
+pub(crate) fn draw_x_axis(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    range: AxisRange,
+    config: &PlotConfig,
+) {
+    let margin = MARGIN;
+    context.set_stroke_style_str(&config.axis_color);
+    context.set_fill_style_str(&config.axis_color);
+    context.set_line_width(2.0);
+    context.begin_path();
+    context.move_to(margin, height - margin);
+    context.line_to(width - margin, height - margin);
+    context.stroke();
+
+    let length = width - 2.0 * margin;
+    let ticks = x_ticks(range, width);
+    let labels = range.tick_labels(&ticks, config.number_format);
+    context.set_font(&font(config.tick_font_size, config));
+    context.set_text_align("center");
+    for (&tick, label) in ticks.iter().zip(&labels) {
+        let x_pos = margin + range.fraction(tick) * length;
+
+        context.begin_path();
+        context.move_to(x_pos, height - margin);
+        context.line_to(x_pos, height - margin + 5.0);
+        context.stroke();
+
+        context
+            .fill_text(label, x_pos, height - margin + 20.0)
+            .unwrap_or(());
+    }
+    context.set_text_align("start");
+    draw_x_label(context, width, height, config);
+}
+
+// Left axis with nice ticks over the range, and its label
+pub(crate) fn draw_y_axis(
+    context: &CanvasRenderingContext2d,
+    height: f64,
+    range: AxisRange,
+    config: &PlotConfig,
+) {
+    let margin = MARGIN;
+    context.set_stroke_style_str(&config.axis_color);
+    context.set_fill_style_str(&config.axis_color);
+    context.set_line_width(2.0);
+    context.begin_path();
+    context.move_to(margin, margin);
+    context.line_to(margin, height - margin);
+    context.stroke();
+
+    let length = height - 2.0 * margin;
+    let ticks = y_ticks(range, height);
+    let labels = range.tick_labels(&ticks, config.number_format);
+    context.set_font(&font(config.tick_font_size, config));
+    context.set_text_align("right");
+    for (&tick, label) in ticks.iter().zip(&labels) {
+        let y_pos = height - margin - range.fraction(tick) * length;
+
+        context.begin_path();
+        context.move_to(margin - 5.0, y_pos);
+        context.line_to(margin, y_pos);
+        context.stroke();
+
+        context
+            .fill_text(label, margin - 7.0, y_pos + 3.0)
+            .unwrap_or(());
+    }
+    context.set_text_align("start");
+    draw_y_label(context, height, config);
+}
+
+fn x_ticks(range: AxisRange, width: f64) -> Vec<f64> {
+    range.ticks(((width - 2.0 * MARGIN) / X_TICK_SPACING) as usize)
+}
+
+fn y_ticks(range: AxisRange, height: f64) -> Vec<f64> {
+    range.ticks(((height - 2.0 * MARGIN) / Y_TICK_SPACING) as usize)
+}
+
+// Lines across the plot area at the ticks of the given axes, under the
+// axes and data. Nothing without a configured grid color.
+pub(crate) fn draw_grid(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    x: Option<AxisRange>,
+    y: Option<AxisRange>,
+    config: &PlotConfig,
+) {
+    let Some(grid_color) = &config.grid_color else {
+        return;
+    };
+    let margin = MARGIN;
+    context.set_stroke_style_str(grid_color);
+    context.set_line_width(1.0);
+    context.begin_path();
+    if let Some(range) = x {
+        for tick in x_ticks(range, width) {
+            let x_pos = margin + range.fraction(tick) * (width - 2.0 * margin);
+            context.move_to(x_pos, margin);
+            context.line_to(x_pos, height - margin);
+        }
+    }
+    if let Some(range) = y {
+        for tick in y_ticks(range, height) {
+            let y_pos = height - margin - range.fraction(tick) * (height - 2.0 * margin);
+            context.move_to(margin, y_pos);
+            context.line_to(width - margin, y_pos);
+        }
+    }
+    context.stroke();
+}
+
+// Bottom axis with one label centered under each of the equal-width slots,
+// and the axis label
+pub(crate) fn draw_category_axis(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    labels: &[String],
+    config: &PlotConfig,
+) {
+    let margin = MARGIN;
+    context.set_stroke_style_str(&config.axis_color);
+    context.set_fill_style_str(&config.axis_color);
+    context.set_line_width(2.0);
+    context.begin_path();
+    context.move_to(margin, height - margin);
+    context.line_to(width - margin, height - margin);
+    context.stroke();
+
+    let slot = (width - 2.0 * margin) / labels.len().max(1) as f64;
+    context.set_font(&font(config.tick_font_size, config));
+    context.set_text_align("center");
+    for (i, label) in labels.iter().enumerate() {
+        let x_pos = margin + (i as f64 + 0.5) * slot;
+        context
+            .fill_text(label, x_pos, height - margin + 20.0)
+            .unwrap_or(());
+    }
+    context.set_text_align("start");
+    draw_x_label(context, width, height, config);
+}
+
+// Chart title centered in the top margin
+pub(crate) fn draw_title(context: &CanvasRenderingContext2d, width: f64, config: &PlotConfig) {
+    let Some(title) = &config.title else {
+        return;
+    };
+    context.set_fill_style_str(&config.text_color);
+    context.set_font(&format!("bold {}", font(config.title_font_size, config)));
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+    context
+        .fill_text(title, width / 2.0, MARGIN / 2.0)
+        .unwrap_or(());
+    context.set_text_align("start");
+    context.set_text_baseline("alphabetic");
+}
+
+// x axis title centered under the tick labels
+pub(crate) fn draw_x_label(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    config: &PlotConfig,
+) {
+    let Some(label) = &config.x_label else {
+        return;
+    };
+    context.set_fill_style_str(&config.text_color);
+    context.set_font(&font(config.label_font_size, config));
+    context.set_text_align("center");
+    context.set_text_baseline("bottom");
+    context
+        .fill_text(label, width / 2.0, height - 4.0)
+        .unwrap_or(());
+    context.set_text_align("start");
+    context.set_text_baseline("alphabetic");
+}
+
+// y axis title read bottom to top along the left edge
+pub(crate) fn draw_y_label(context: &CanvasRenderingContext2d, height: f64, config: &PlotConfig) {
+    let Some(label) = &config.y_label else {
+        return;
+    };
+    context.set_fill_style_str(&config.text_color);
+    context.set_font(&font(config.label_font_size, config));
+    context.set_text_align("center");
+    context.set_text_baseline("top");
+    context.save();
+    context.translate(4.0, height / 2.0).unwrap_or(());
+    context.rotate(-f64::consts::FRAC_PI_2).unwrap_or(());
+    context.fill_text(label, 0.0, 0.0).unwrap_or(());
+    context.restore();
+    context.set_text_align("start");
+    context.set_text_baseline("alphabetic");
+}
+
+// Box with a swatch and label per entry in the configured corner of the
+// plot area. Nothing without entries or with the legend hidden.
+pub(crate) fn draw_legend(
+    context: &CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    entries: &[LegendEntry],
+    config: &PlotConfig,
+) {
+    if entries.is_empty() || config.legend == LegendPosition::Hidden {
+        return;
+    }
+    let font_size = config.legend_font_size;
+    let row_height = font_size + 5.0;
+    let swatch_width = 20.0;
+    context.set_font(&font(font_size, config));
+    let longest = entries
+        .iter()
+        .filter_map(|entry| context.measure_text(&entry.label).ok())
+        .map(|metrics| metrics.width())
+        .fold(0.0, f64::max);
+    let box_width = swatch_width + longest + 16.0;
+    let box_height = row_height * entries.len() as f64 + 8.0;
+
+    let left = match config.legend {
+        LegendPosition::TopLeft | LegendPosition::BottomLeft => MARGIN + LEGEND_INSET,
+        _ => width - MARGIN - LEGEND_INSET - box_width,
+    };
+    let top = match config.legend {
+        LegendPosition::BottomLeft | LegendPosition::BottomRight => {
+            height - MARGIN - LEGEND_INSET - box_height
+        }
+        _ => MARGIN + LEGEND_INSET,
+    };
+
+    context.set_fill_style_str(&config.legend_background);
+    context.fill_rect(left, top, box_width, box_height);
+    context.set_text_baseline("middle");
+    for (i, entry) in entries.iter().enumerate() {
+        let y = top + 4.0 + row_height * (i as f64 + 0.5);
+        let x = left + 6.0;
+        context.set_stroke_style_str(&entry.color);
+        context.set_fill_style_str(&entry.color);
+        context.set_line_width(2.0);
+        match entry.swatch {
+            Swatch::Line => {
+                context.begin_path();
+                context.move_to(x, y);
+                context.line_to(x + swatch_width, y);
+                context.stroke();
+            }
+            Swatch::Marker(shape) => {
+                draw_marker(context, shape, x + swatch_width / 2.0, y, font_size / 3.0);
+            }
+            Swatch::Fill => {
+                let side = font_size - 2.0;
+                context.fill_rect(x + (swatch_width - side) / 2.0, y - side / 2.0, side, side);
+            }
+        }
+        context.set_fill_style_str(&config.text_color);
+        context
+            .fill_text(&entry.label, x + swatch_width + 4.0, y)
+            .unwrap_or(());
+    }
+    context.set_text_baseline("alphabetic");
+}
+
+// Vertical bar from low to high at x, with caps cap_width wide, in the
+// current stroke style. Positions are in canvas pixels, low below high.
+pub(crate) fn draw_error_bar(
+    context: &CanvasRenderingContext2d,
+    x: f64,
+    low: f64,
+    high: f64,
+    cap_width: f64,
+) {
+    let half = cap_width / 2.0;
+    context.begin_path();
+    context.move_to(x, low);
+    context.line_to(x, high);
+    context.move_to(x - half, low);
+    context.line_to(x + half, low);
+    context.move_to(x - half, high);
+    context.line_to(x + half, high);
+    context.stroke();
+}
+
+// Area between two curves given as canvas positions at the same x, filled
+// with the current fill style at the given opacity. Positions with a NaN
+// coordinate split the band in two.
+pub(crate) fn draw_band(
+    context: &CanvasRenderingContext2d,
+    upper: &[(f64, f64)],
+    lower: &[(f64, f64)],
+    opacity: f64,
+) {
+    let is_drawn = |(x, y): (f64, f64)| !x.is_nan() && !y.is_nan();
+    let points: Vec<_> = upper
+        .iter()
+        .zip(lower)
+        .map(|(&u, &l)| (is_drawn(u) && is_drawn(l)).then_some((u, l)))
+        .collect();
+    context.save();
+    context.set_global_alpha(opacity);
+    for run in points.split(Option::is_none) {
+        let run: Vec<_> = run.iter().flatten().collect();
+        if run.len() < 2 {
+            continue;
+        }
+        context.begin_path();
+        for (i, ((x, y), _)) in run.iter().enumerate() {
+            if i == 0 {
+                context.move_to(*x, *y);
+            } else {
+                context.line_to(*x, *y);
+            }
+        }
+        for (_, (x, y)) in run.iter().rev() {
+            context.line_to(*x, *y);
+        }
+        context.close_path();
+        context.fill();
+    }
+    context.restore();
+}
+
+// Marker of the given radius centered on (x, y), in the current fill and
+// stroke style
+pub(crate) fn draw_marker(
+    context: &CanvasRenderingContext2d,
+    shape: MarkerShape,
+    x: f64,
+    y: f64,
+    size: f64,
+) {
+    context.begin_path();
+    match shape {
+        MarkerShape::Circle => {
+            context
+                .arc(x, y, size, 0.0, 2.0 * f64::consts::PI)
+                .unwrap_or(());
+            context.fill();
+        }
+        MarkerShape::Square => {
+            context.fill_rect(x - size, y - size, 2.0 * size, 2.0 * size);
+        }
+        MarkerShape::Triangle => {
+            context.move_to(x, y - size);
+            context.line_to(x + size, y + size);
+            context.line_to(x - size, y + size);
+            context.close_path();
+            context.fill();
+        }
+        MarkerShape::Cross => {
+            context.move_to(x - size, y - size);
+            context.line_to(x + size, y + size);
+            context.move_to(x + size, y - size);
+            context.line_to(x - size, y + size);
+            context.stroke();
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use super::vega::spec::ScaleDef;
+use std::fmt;
+
+// Samples of matplotlib's perceptually uniform colormaps, evenly spaced
+const VIRIDIS: [&str; 9] = [
+    "#440154", "#472c7a", "#3b518b", "#2c718e", "#21908d", "#27ad81", "#5cc863", "#aadc32",
+    "#fde725",
+];
+const PLASMA: [&str; 9] = [
+    "#0d0887", "#4c02a1", "#7e03a8", "#a92395", "#cc4778", "#e56b5d", "#f89441", "#fdc328",
+    "#f0f921",
+];
+const BLUES: [&str; 3] = ["#f7fbff", "#6baed6", "#08306b"];
+const RED_BLUE: [&str; 3] = ["#2166ac", "#f7f7f7", "#b2182b"];
+
+// matplotlib's tab10
+const TAB10: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+// ColorBrewer's Set2 and Dark2, softer and darker alternatives
+const SET2: [&str; 8] = [
+    "#66c2a5", "#fc8d62", "#8da0cb", "#e78ac3", "#a6d854", "#ffd92f", "#e5c494", "#b3b3b3",
+];
+const DARK2: [&str; 8] = [
+    "#1b9e77", "#d95f02", "#7570b3", "#e7298a", "#66a61e", "#e6ab02", "#a6761d", "#666666",
+];
+
+// Color with an opacity, alpha from 0 (transparent) to 1 (opaque). Displays
+// as a CSS color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f64,
+}
+
+// Colors for categories such as clusters, classes or series, taken in order
+// and repeated when there are more categories than colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    #[default]
+    Tab10,
+    Set2,
+    Dark2,
+}
+
+// Continuous colors for values scaled to [0, 1]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Colormap {
+    // Dark purple to yellow, for any magnitude
+    Viridis,
+    // Dark blue through magenta to yellow
+    Plasma,
+    // White to dark blue, for counts, rates and distances
+    Blues,
+    // Diverging blue through white to red, for signed values such as
+    // correlations
+    RedBlue,
+    // Evenly spaced stops, interpolated linearly
+    Custom(Vec<Rgba>),
+}
+
+impl Rgba {
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Rgba { r, g, b, a: 1.0 }
+    }
+
+    // "#rrggbb" or "#rgb", None when malformed
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#')?;
+        if !digits.is_ascii() {
+            return None;
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+        match digits.len() {
+            6 => Some(Rgba::rgb(
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            )),
+            3 => Some(Rgba::rgb(
+                channel(&digits[0..1])? * 17,
+                channel(&digits[1..2])? * 17,
+                channel(&digits[2..3])? * 17,
+            )),
+            _ => None,
+        }
+    }
+
+    // Hex, rgb() and rgba() colors and a few common names, None for
+    // anything else CSS understands
+    pub fn from_css(color: &str) -> Option<Self> {
+        let color = color.trim().to_lowercase();
+        if color.starts_with('#') {
+            return Rgba::from_hex(&color);
+        }
+        if let Some(args) = color
+            .strip_prefix("rgba(")
+            .or_else(|| color.strip_prefix("rgb("))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+            let channel = |i: usize| parts.get(i)?.parse::<f64>().ok();
+            let rgba = Rgba::rgb(
+                channel(0)?.clamp(0.0, 255.0) as u8,
+                channel(1)?.clamp(0.0, 255.0) as u8,
+                channel(2)?.clamp(0.0, 255.0) as u8,
+            );
+            return match parts.len() {
+                3 => Some(rgba),
+                4 => Some(rgba.with_alpha(channel(3)?)),
+                _ => None,
+            };
+        }
+        let hex = match color.as_str() {
+            "black" => "#000000",
+            "white" => "#ffffff",
+            "gray" | "grey" => "#808080",
+            "red" => "#ff0000",
+            "green" => "#008000",
+            "blue" => "#0000ff",
+            "orange" => "#ffa500",
+            "purple" => "#800080",
+            _ => return None,
+        };
+        Rgba::from_hex(hex)
+    }
+
+    pub fn with_alpha(mut self, a: f64) -> Self {
+        self.a = a.clamp(0.0, 1.0);
+        self
+    }
+
+    // "#rrggbb", ignoring alpha
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    // Perceived brightness from 0 (black) to 255 (white)
+    pub fn luminance(&self) -> f64 {
+        0.299 * self.r as f64 + 0.587 * self.g as f64 + 0.114 * self.b as f64
+    }
+
+    // Black or white, whichever is easier to read on this color
+    pub fn contrasting_text(&self) -> &'static str {
+        if self.luminance() < 128.0 {
+            "white"
+        } else {
+            "black"
+        }
+    }
+
+    // Color t of the way to other, t in [0, 1]
+    pub fn lerp(&self, other: &Rgba, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgba {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+}
+
+impl fmt::Display for Rgba {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.a >= 1.0 {
+            write!(f, "rgb({}, {}, {})", self.r, self.g, self.b)
+        } else {
+            write!(f, "rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl Palette {
+    // The colors as "#rrggbb"
+    pub fn colors(&self) -> &'static [&'static str] {
+        match self {
+            Palette::Tab10 => &TAB10,
+            Palette::Set2 => &SET2,
+            Palette::Dark2 => &DARK2,
+        }
+    }
+
+    // Color of category i as "#rrggbb"
+    pub fn color(&self, i: usize) -> &'static str {
+        let colors = self.colors();
+        colors[i % colors.len()]
+    }
+
+    pub fn rgba(&self, i: usize) -> Rgba {
+        Rgba::from_hex(self.color(i)).unwrap_or(Rgba::rgb(0, 0, 0))
+    }
+
+    // Vega-Lite scale giving nominal values the same colors, in order
+    pub fn vega_scale(&self) -> ScaleDef {
+        ScaleDef {
+            range: Some(self.colors().iter().map(|c| c.to_string()).collect()),
+            ..ScaleDef::default()
+        }
+    }
+}
+
+impl Colormap {
+    fn stops(&self) -> Vec<Rgba> {
+        let parse = |hexes: &[&str]| hexes.iter().filter_map(|h| Rgba::from_hex(h)).collect();
+        match self {
+            Colormap::Viridis => parse(&VIRIDIS),
+            Colormap::Plasma => parse(&PLASMA),
+            Colormap::Blues => parse(&BLUES),
+            Colormap::RedBlue => parse(&RED_BLUE),
+            Colormap::Custom(stops) => stops.clone(),
+        }
+    }
+
+    // Color at t in [0, 1], t outside clamped to the ends
+    pub fn map(&self, t: f64) -> Rgba {
+        let stops = self.stops();
+        if stops.len() < 2 {
+            return stops.first().copied().unwrap_or(Rgba::rgb(0, 0, 0));
+        }
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let position = t * (stops.len() - 1) as f64;
+        let i = (position.floor() as usize).min(stops.len() - 2);
+        stops[i].lerp(&stops[i + 1], position - i as f64)
+    }
+
+    // n colors evenly spaced from one end to the other
+    pub fn sample(&self, n: usize) -> Vec<Rgba> {
+        match n {
+            0 => vec![],
+            1 => vec![self.map(0.5)],
+            _ => (0..n)
+                .map(|i| self.map(i as f64 / (n - 1) as f64))
+                .collect(),
+        }
+    }
+
+    // Vega-Lite scale with the same colors, its built-in scheme where it has
+    // one
+    pub fn vega_scale(&self) -> ScaleDef {
+        let scheme = |name: &str| ScaleDef {
+            scheme: Some(name.to_string()),
+            ..ScaleDef::default()
+        };
+        match self {
+            Colormap::Viridis => scheme("viridis"),
+            Colormap::Plasma => scheme("plasma"),
+            Colormap::Blues => scheme("blues"),
+            Colormap::RedBlue => ScaleDef {
+                reverse: Some(true),
+                ..scheme("redblue")
+            },
+            Colormap::Custom(stops) => ScaleDef {
+                range: Some(stops.iter().map(|c| c.to_string()).collect()),
+                ..ScaleDef::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use super::vega::chart::FacetChart;
+use super::vega::spec::{Channel, Facet, FieldDef, FieldType, ScaleDef, VegaSpec};
+use crate::ml::classification::logistic::LogisticRegression;
+use crate::ml::classification::naive_bayes::GaussianNB;
+use crate::ml::ensemble::gbm::GradientBoostingClassifier;
+use crate::ml::ensemble::random_forest::RandomForestClassifier;
+use crate::ml::model_selection::comparison::{
+    best_model, compare_classifiers, Candidate, ModelScores, Score,
+};
+use crate::ml::model_selection::split::train_test_split;
+use crate::ml::neighbors::knn::KNeighborsClassifier;
+use crate::ml::nn::mlp::MlpClassifier;
+use crate::ml::tree::decision_tree::DecisionTreeClassifier;
+use dioxus::prelude::*;
+use serde_json::{json, Value};
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+const DEFAULT_TEST_SIZE: f64 = 0.3;
+// Bars of the best model stand out from the others
+const BEST_COLOR: &str = "#2ca02c";
+const OTHER_COLOR: &str = "#9ca3af";
+
+// Classifiers ModelComparison offers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelChoice {
+    LogisticRegression,
+    GaussianNB,
+    KNeighbors,
+    DecisionTree,
+    RandomForest,
+    GradientBoosting,
+    Mlp,
+}
+
+impl ModelChoice {
+    pub const ALL: [ModelChoice; 7] = [
+        ModelChoice::LogisticRegression,
+        ModelChoice::GaussianNB,
+        ModelChoice::KNeighbors,
+        ModelChoice::DecisionTree,
+        ModelChoice::RandomForest,
+        ModelChoice::GradientBoosting,
+        ModelChoice::Mlp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelChoice::LogisticRegression => "Logistic regression",
+            ModelChoice::GaussianNB => "Gaussian naive Bayes",
+            ModelChoice::KNeighbors => "5 nearest neighbors",
+            ModelChoice::DecisionTree => "Decision tree",
+            ModelChoice::RandomForest => "Random forest",
+            ModelChoice::GradientBoosting => "Gradient boosting",
+            ModelChoice::Mlp => "Neural network",
+        }
+    }
+
+    // Unfitted model with settings that suit small datasets
+    pub fn candidate(&self, random_state: Option<u64>) -> Candidate {
+        let name = self.name();
+        match self {
+            ModelChoice::LogisticRegression => Candidate::new(name, LogisticRegression::new()),
+            ModelChoice::GaussianNB => Candidate::new(name, GaussianNB::new()),
+            ModelChoice::KNeighbors => Candidate::new(name, KNeighborsClassifier::new(5)),
+            ModelChoice::DecisionTree => {
+                Candidate::new(name, DecisionTreeClassifier::new().with_max_depth(5))
+            }
+            ModelChoice::RandomForest => {
+                let model = RandomForestClassifier::new(50).with_max_depth(8);
+                match random_state {
+                    Some(seed) => Candidate::new(name, model.with_random_state(seed)),
+                    None => Candidate::new(name, model),
+                }
+            }
+            ModelChoice::GradientBoosting => {
+                let model = GradientBoostingClassifier::new(50);
+                match random_state {
+                    Some(seed) => Candidate::new(name, model.with_random_state(seed)),
+                    None => Candidate::new(name, model),
+                }
+            }
+            ModelChoice::Mlp => {
+                let model = MlpClassifier::new(vec![16]);
+                match random_state {
+                    Some(seed) => Candidate::new(name, model.with_random_state(seed)),
+                    None => Candidate::new(name, model),
+                }
+            }
+        }
+    }
+}
+
+// Rows of the metrics chart: every score of every model, the best model by
+// rank_by marked
+fn score_records(scores: &[ModelScores], rank_by: Score) -> Vec<Value> {
+    let best = best_model(scores, rank_by);
+    scores
+        .iter()
+        .enumerate()
+        .flat_map(|(i, model)| {
+            let status = if Some(i) == best { "best" } else { "other" };
+            Score::ALL.iter().map(move |&score| {
+                json!({
+                    "model": model.name,
+                    "metric": score.name(),
+                    "score": model.get(score),
+                    "status": status,
+                })
+            })
+        })
+        .collect()
+}
+
+// One bar per model in a panel per metric
+fn scores_spec() -> VegaSpec {
+    VegaSpec::new()
+        .mark_bar()
+        .x("model", FieldType::Nominal)
+        .encode(
+            Channel::Y,
+            FieldDef::new("score", FieldType::Quantitative).with_scale(ScaleDef {
+                domain: Some(vec![0.0, 1.0]),
+                ..ScaleDef::default()
+            }),
+        )
+        .encode(
+            Channel::Color,
+            FieldDef::new("status", FieldType::Nominal).with_scale(ScaleDef {
+                range: Some(vec![BEST_COLOR.to_string(), OTHER_COLOR.to_string()]),
+                ..ScaleDef::default()
+            }),
+        )
+        .tooltip(FieldDef::new("model", FieldType::Nominal))
+        .tooltip(FieldDef::new("score", FieldType::Quantitative).with_format(".3f"))
+}
+
+// Fits the chosen classifiers on the same training rows of points and
+// labels, scores them on the rest and shows the scores in a table and a
+// chart per metric, the best model by the chosen metric highlighted
+#[component]
+pub fn ModelComparison(
+    points: Signal<Vec<Point>>,
+    labels: Signal<Vec<usize>>,
+    width: f64,
+    height: f64,
+    id: String,
+    // Fraction of the rows held out for scoring
+    #[props(default = DEFAULT_TEST_SIZE)] test_size: f64,
+    #[props(default)] random_state: Option<u64>,
+) -> Element {
+    let mut chosen = use_signal(|| ModelChoice::ALL[..4].to_vec());
+    let mut rank_by = use_signal(|| Score::Accuracy);
+    let mut results = use_signal(Vec::<ModelScores>::new);
+    let mut chart_data = use_signal(Vec::<Value>::new);
+    let mut status = use_signal(String::new);
+
+    // Scores of other data mean nothing
+    use_effect(move || {
+        points.read();
+        labels.read();
+        results.set(vec![]);
+        status.set(String::new());
+    });
+
+    use_effect(move || {
+        chart_data.set(score_records(&results.read(), rank_by()));
+    });
+
+    let compare = move |_| {
+        let split = train_test_split(&points.read(), &labels.read(), test_size, random_state);
+        if split.x_train.is_empty() || split.x_test.is_empty() {
+            status.set("Too few points to hold some out for scoring.".to_string());
+            return;
+        }
+        let mut candidates: Vec<Candidate> = ModelChoice::ALL
+            .iter()
+            .filter(|choice| chosen.read().contains(choice))
+            .map(|choice| choice.candidate(random_state))
+            .collect();
+        results.set(compare_classifiers(&mut candidates, &split));
+        status.set(format!(
+            "Trained on {} points, scored on {}.",
+            split.x_train.len(),
+            split.x_test.len()
+        ));
+    };
+
+    let best = best_model(&results.read(), rank_by());
+
+    rsx! {
+        div {
+            div {
+                class: "flex flex-wrap items-center gap-2 mt-1 text-sm",
+                for choice in ModelChoice::ALL {
+                    label {
+                        input {
+                            r#type: "checkbox",
+                            checked: chosen.read().contains(&choice),
+                            onchange: move |event| {
+                                let mut chosen = chosen.write();
+                                chosen.retain(|&c| c != choice);
+                                if event.checked() {
+                                    chosen.push(choice);
+                                }
+                            },
+                        }
+                        " {choice.name()}"
+                    }
+                }
+            }
+            div {
+                class: "flex items-center gap-2 mt-1 text-sm",
+                button {
+                    class: "px-2 border rounded",
+                    disabled: chosen.read().is_empty(),
+                    onclick: compare,
+                    "Compare"
+                }
+                label {
+                    "Best by "
+                    select {
+                        class: "px-1 border rounded",
+                        onchange: move |event| {
+                            let value = event.value();
+                            if let Some(&score) = Score::ALL.iter().find(|s| s.name() == value) {
+                                rank_by.set(score);
+                            }
+                        },
+                        for score in Score::ALL {
+                            option { value: score.name(), selected: score == rank_by(), "{score.name()}" }
+                        }
+                    }
+                }
+                span { "{status}" }
+            }
+            if !results.read().is_empty() {
+                table {
+                    class: "w-full text-sm border-collapse mt-1",
+                    thead {
+                        tr {
+                            th { class: "px-2 border-b text-left", "Model" }
+                            for score in Score::ALL {
+                                th { class: "px-2 border-b text-right", "{score.name()}" }
+                            }
+                        }
+                    }
+                    tbody {
+                        for (i, model) in results.read().iter().enumerate() {
+                            tr {
+                                key: "{model.name}",
+                                class: if Some(i) == best { "bg-green-100 font-semibold" } else { "" },
+                                td {
+                                    class: "px-2",
+                                    "{model.name}"
+                                    if Some(i) == best { " ★" }
+                                }
+                                for score in Score::ALL {
+                                    td { class: "px-2 text-right", "{model.get(score):.3}" }
+                                }
+                            }
+                        }
+                    }
+                }
+                FacetChart {
+                    data: chart_data,
+                    spec: scores_spec(),
+                    facet: Facet::columns("metric", FieldType::Nominal),
+                    width,
+                    height,
+                    id,
+                }
+            }
+        }
+    }
+}
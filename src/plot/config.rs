@@ -0,0 +1,125 @@
+use super::color::Palette;
+use super::scale::{NumberFormat, Scale};
+
+// Corner of the plot area the legend is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegendPosition {
+    Hidden,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+// Titles, legend and styling shared by every canvas chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotConfig {
+    // Drawn centered above the plot area
+    pub title: Option<String>,
+    // Drawn under the x axis and rotated left of the y axis
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub legend: LegendPosition,
+    // Scatter and line plots can put either axis on a log scale, the other
+    // charts are always linear
+    pub x_scale: Scale,
+    pub y_scale: Scale,
+    // How tick labels are written
+    pub number_format: NumberFormat,
+    // Colors of series, clusters and classes without a color of their own
+    pub palette: Palette,
+    pub font_family: String,
+    // Font sizes in pixels
+    pub title_font_size: f64,
+    pub label_font_size: f64,
+    pub tick_font_size: f64,
+    pub legend_font_size: f64,
+    // CSS colors of the axes with their ticks, and of all other text
+    pub axis_color: String,
+    pub text_color: String,
+    // CSS color of lines across the plot area at the ticks, none when None
+    pub grid_color: Option<String>,
+    pub legend_background: String,
+    // CSS color the canvas is filled with before drawing, transparent when
+    // None
+    pub background: Option<String>,
+}
+
+impl Default for PlotConfig {
+    fn default() -> Self {
+        PlotConfig {
+            title: None,
+            x_label: None,
+            y_label: None,
+            legend: LegendPosition::TopRight,
+            x_scale: Scale::Linear,
+            y_scale: Scale::Linear,
+            number_format: NumberFormat::Auto,
+            palette: Palette::Tab10,
+            font_family: "sans-serif".to_string(),
+            title_font_size: 16.0,
+            label_font_size: 12.0,
+            tick_font_size: 10.0,
+            legend_font_size: 11.0,
+            axis_color: "black".to_string(),
+            text_color: "black".to_string(),
+            grid_color: None,
+            legend_background: "rgba(255, 255, 255, 0.8)".to_string(),
+            background: None,
+        }
+    }
+}
+
+impl PlotConfig {
+    pub fn new() -> Self {
+        PlotConfig::default()
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_labels(mut self, x_label: &str, y_label: &str) -> Self {
+        self.x_label = Some(x_label.to_string());
+        self.y_label = Some(y_label.to_string());
+        self
+    }
+
+    pub fn with_legend(mut self, legend: LegendPosition) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    pub fn with_scales(mut self, x_scale: Scale, y_scale: Scale) -> Self {
+        self.x_scale = x_scale;
+        self.y_scale = y_scale;
+        self
+    }
+
+    pub fn with_number_format(mut self, number_format: NumberFormat) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn with_colors(mut self, axis_color: &str, text_color: &str) -> Self {
+        self.axis_color = axis_color.to_string();
+        self.text_color = text_color.to_string();
+        self
+    }
+
+    pub fn with_grid(mut self, grid_color: &str) -> Self {
+        self.grid_color = Some(grid_color.to_string());
+        self
+    }
+
+    pub fn with_background(mut self, background: &str) -> Self {
+        self.background = Some(background.to_string());
+        self
+    }
+}
@@ -0,0 +1,280 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_axes, draw_legend, draw_title, LegendEntry, Swatch, MARGIN,
+};
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scale::AxisRange;
+use super::scatter::{Drawable, MarkerShape};
+use super::theme::{themed, use_theme};
+use crate::ml::base::Predictor;
+use dioxus::prelude::*;
+use std::f64;
+use web_sys::CanvasRenderingContext2d;
+
+// Grid cells along each axis
+const RESOLUTION: usize = 100;
+// Fraction of the data range added on every side, so that no point sits on
+// the edge of the plot
+const PADDING: f64 = 0.05;
+const REGION_ALPHA: f64 = 0.25;
+const POINT_RADIUS: f64 = 4.0;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Labeled 2D points drawn over the regions a model assigns to each class
+// (or cluster), with the boundaries between regions outlined
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionBoundaryData {
+    pub points: Vec<Point>,
+    pub labels: Vec<usize>,
+    // Predicted class of every grid cell, regions[i][j] in row i from the
+    // bottom and column j from the left
+    pub regions: Vec<Vec<usize>>,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+}
+
+// Predicted class at the center of every cell of a resolution x resolution
+// grid over the given region, rows from the bottom (lowest y) up. The model
+// must have been fitted on 2D points.
+pub fn decision_boundary<P: Predictor<Output = usize>>(
+    model: &P,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    resolution: usize,
+) -> Vec<Vec<usize>> {
+    let resolution = resolution.max(1);
+    let cell_width = (x_range.1 - x_range.0) / resolution as f64;
+    let cell_height = (y_range.1 - y_range.0) / resolution as f64;
+    let grid: Vec<Point> = (0..resolution)
+        .flat_map(|i| {
+            (0..resolution).map(move |j| {
+                vec![
+                    x_range.0 + (j as f64 + 0.5) * cell_width,
+                    y_range.0 + (i as f64 + 0.5) * cell_height,
+                ]
+            })
+        })
+        .collect();
+
+    // One call for the whole grid, models batch their predictions
+    model
+        .predict(&grid)
+        .chunks(resolution)
+        .map(|row| row.to_vec())
+        .collect()
+}
+
+impl Default for DecisionBoundaryData {
+    fn default() -> Self {
+        DecisionBoundaryData {
+            points: vec![],
+            labels: vec![],
+            regions: vec![],
+            x_range: (0.0, 1.0),
+            y_range: (0.0, 1.0),
+        }
+    }
+}
+
+impl DecisionBoundaryData {
+    // Regions of a fitted model over the range of the points, padded a little
+    pub fn new<P: Predictor<Output = usize>>(
+        model: &P,
+        points: &[Point],
+        labels: &[usize],
+    ) -> Self {
+        DecisionBoundaryData::with_resolution(model, points, labels, RESOLUTION)
+    }
+
+    // Finer grids show smoother boundaries at the cost of resolution^2
+    // predictions
+    pub fn with_resolution<P: Predictor<Output = usize>>(
+        model: &P,
+        points: &[Point],
+        labels: &[usize],
+        resolution: usize,
+    ) -> Self {
+        let x_range = padded_range(points.iter().filter_map(|p| p.first().copied()));
+        let y_range = padded_range(points.iter().filter_map(|p| p.get(1).copied()));
+        DecisionBoundaryData {
+            points: points.to_vec(),
+            labels: labels.to_vec(),
+            regions: decision_boundary(model, x_range, y_range, resolution),
+            x_range,
+            y_range,
+        }
+    }
+}
+
+impl Drawable for DecisionBoundaryData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let margin = MARGIN;
+        let (x_min, x_max) = self.x_range;
+        let (y_min, y_max) = self.y_range;
+
+        draw_axes(
+            context,
+            width,
+            height,
+            AxisRange::new(x_min, x_max),
+            AxisRange::new(y_min, y_max),
+            config,
+        );
+
+        let x_scale = (width - 2.0 * margin) / (x_max - x_min);
+        let y_scale = (height - 2.0 * margin) / (y_max - y_min);
+        let to_canvas = |x: f64, y: f64| {
+            (
+                margin + (x - x_min) * x_scale,
+                height - margin - (y - y_min) * y_scale,
+            )
+        };
+        let color = |class: usize| config.palette.color(class);
+
+        // Filled regions, rows of the grid from the bottom of the plot up
+        let n_rows = self.regions.len();
+        let n_columns = self.regions.first().map_or(0, |row| row.len());
+        if n_rows > 0 && n_columns > 0 {
+            let cell_width = (width - 2.0 * margin) / n_columns as f64;
+            let cell_height = (height - 2.0 * margin) / n_rows as f64;
+            let cell_left = |j: usize| margin + j as f64 * cell_width;
+            let cell_top = |i: usize| height - margin - (i + 1) as f64 * cell_height;
+
+            context.set_global_alpha(REGION_ALPHA);
+            for (i, row) in self.regions.iter().enumerate() {
+                for (j, &class) in row.iter().enumerate() {
+                    context.set_fill_style_str(color(class));
+                    // Overlap by half a pixel so no seams show between cells
+                    context.fill_rect(
+                        cell_left(j),
+                        cell_top(i),
+                        cell_width + 0.5,
+                        cell_height + 0.5,
+                    );
+                }
+            }
+            context.set_global_alpha(1.0);
+
+            // Boundaries between cells of different classes
+            context.set_stroke_style_str("#444");
+            context.set_line_width(1.0);
+            context.begin_path();
+            for (i, row) in self.regions.iter().enumerate() {
+                for (j, &class) in row.iter().enumerate() {
+                    if row.get(j + 1).is_some_and(|&next| next != class) {
+                        context.move_to(cell_left(j + 1), cell_top(i));
+                        context.line_to(cell_left(j + 1), cell_top(i) + cell_height);
+                    }
+                    let above = self.regions.get(i + 1).and_then(|r| r.get(j));
+                    if above.is_some_and(|&next| next != class) {
+                        context.move_to(cell_left(j), cell_top(i));
+                        context.line_to(cell_left(j) + cell_width, cell_top(i));
+                    }
+                }
+            }
+            context.stroke();
+        }
+
+        // Points on top, outlined so they stand out from their own region
+        context.set_stroke_style_str("white");
+        context.set_line_width(1.0);
+        for (point, &label) in self.points.iter().zip(&self.labels) {
+            let [x, y, ..] = point[..] else {
+                continue;
+            };
+            let (x_pos, y_pos) = to_canvas(x, y);
+            context.set_fill_style_str(color(label));
+            context.begin_path();
+            context
+                .arc(x_pos, y_pos, POINT_RADIUS, 0.0, 2.0 * f64::consts::PI)
+                .unwrap_or(());
+            context.fill();
+            context.stroke();
+        }
+
+        let mut classes = self.labels.clone();
+        classes.sort_unstable();
+        classes.dedup();
+        let entries: Vec<LegendEntry> = classes
+            .into_iter()
+            .map(|class| LegendEntry {
+                label: format!("Class {}", class),
+                color: color(class).to_string(),
+                swatch: Swatch::Marker(MarkerShape::Circle),
+            })
+            .collect();
+        draw_legend(context, width, height, &entries, config);
+        draw_title(context, width, config);
+    }
+}
+
+// Classifier regions with the labeled points on top, redrawn whenever data
+// changes
+#[component]
+pub fn DecisionBoundary(
+    data: Signal<DecisionBoundaryData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
+
+// Smallest and largest value, widened by PADDING of the range on each side.
+// (0, 1) without values, and a unit range around a single value.
+fn padded_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values
+        .filter(|v| !v.is_nan())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if !min.is_finite() {
+        return (0.0, 1.0);
+    }
+    if max <= min {
+        return (min - 0.5, max + 0.5);
+    }
+    let pad = PADDING * (max - min);
+    (min - pad, max + pad)
+}
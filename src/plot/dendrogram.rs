@@ -0,0 +1,246 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_category_axis, draw_title, draw_y_axis, font, MARGIN,
+};
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scale::AxisRange;
+use super::scatter::Drawable;
+use super::theme::{themed, use_theme};
+use crate::ml::clustering::hierarchical::AgglomerativeClustering;
+use dioxus::prelude::*;
+use web_sys::js_sys::Array;
+use web_sys::CanvasRenderingContext2d;
+
+const LINK_WIDTH: f64 = 1.5;
+// Leaves are labeled only while their labels fit side by side
+const MAX_LEAF_LABELS: usize = 40;
+
+// Merge tree of a fitted agglomerative clustering, optionally cut at a
+// height into flat clusters
+#[derive(Debug, Clone)]
+pub struct DendrogramData {
+    pub model: AgglomerativeClustering,
+    // Merges at or below the cut join clusters, drawn in their cluster's
+    // color. No clusters when None.
+    pub cut: Option<f64>,
+    // Name of every sample, shown under its leaf when there are few
+    pub leaf_labels: Vec<String>,
+}
+
+impl DendrogramData {
+    pub fn new(model: AgglomerativeClustering) -> Self {
+        DendrogramData {
+            model,
+            cut: None,
+            leaf_labels: vec![],
+        }
+    }
+
+    pub fn with_cut(mut self, height: f64) -> Self {
+        self.cut = Some(height);
+        self
+    }
+
+    pub fn with_leaf_labels(mut self, labels: Vec<String>) -> Self {
+        self.leaf_labels = labels;
+        self
+    }
+
+    // Cluster of every sample below the cut, None without a cut
+    pub fn labels(&self) -> Option<Vec<usize>> {
+        self.cut.map(|height| self.model.cut_at_height(height))
+    }
+
+    // Height of the last merge, where the tree's root is drawn
+    pub fn max_height(&self) -> f64 {
+        self.model.merges().last().map_or(0.0, |m| m.distance)
+    }
+
+    // Samples in the order their leaves are drawn, so that no links cross
+    pub fn leaf_order(&self) -> Vec<usize> {
+        let n = self.model.n_samples();
+        let merges = self.model.merges();
+        if merges.is_empty() {
+            return (0..n).collect();
+        }
+        let mut order = Vec::with_capacity(n);
+        let mut stack = vec![n + merges.len() - 1];
+        while let Some(id) = stack.pop() {
+            match id.checked_sub(n).and_then(|i| merges.get(i)) {
+                Some(merge) => {
+                    stack.push(merge.right);
+                    stack.push(merge.left);
+                }
+                None => order.push(id),
+            }
+        }
+        order
+    }
+
+    // Horizontal position, in leaf slots, and height of every node: the
+    // samples, then the cluster of every merge
+    fn node_positions(&self) -> Vec<(f64, f64)> {
+        let n = self.model.n_samples();
+        let mut positions = vec![(0.0, 0.0); n + self.model.merges().len()];
+        for (slot, leaf) in self.leaf_order().into_iter().enumerate() {
+            positions[leaf] = (slot as f64, 0.0);
+        }
+        for (i, merge) in self.model.merges().iter().enumerate() {
+            let (left, right) = (positions[merge.left], positions[merge.right]);
+            positions[n + i] = ((left.0 + right.0) / 2.0, merge.distance);
+        }
+        positions
+    }
+
+    // Height at a vertical canvas position, for cutting where the viewer
+    // points
+    pub fn height_at(&self, y: f64, height: f64) -> f64 {
+        let fraction = (height - MARGIN - y) / (height - 2.0 * MARGIN);
+        self.y_range().value_at(fraction.clamp(0.0, 1.0))
+    }
+
+    fn y_range(&self) -> AxisRange {
+        AxisRange::new(0.0, self.max_height().max(f64::EPSILON))
+    }
+}
+
+impl Drawable for DendrogramData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let margin = MARGIN;
+        let n = self.model.n_samples();
+        let y_range = self.y_range();
+        draw_y_axis(context, height, y_range, config);
+        let order = self.leaf_order();
+        let labels: Vec<String> = if n <= MAX_LEAF_LABELS && self.leaf_labels.len() == n {
+            order.iter().map(|&i| self.leaf_labels[i].clone()).collect()
+        } else {
+            vec![String::new(); n]
+        };
+        draw_category_axis(context, width, height, &labels, config);
+
+        let slot = (width - 2.0 * margin) / n.max(1) as f64;
+        let to_x = |position: f64| margin + (position + 0.5) * slot;
+        let to_y = |h: f64| height - margin - y_range.fraction(h) * (height - 2.0 * margin);
+
+        // A merge below the cut is colored by the cluster of any sample
+        // under it, the leftmost one
+        let cluster_of = self.labels();
+        let mut first_leaf: Vec<usize> = (0..n).collect();
+        let positions = self.node_positions();
+        context.set_line_width(LINK_WIDTH);
+        for (i, merge) in self.model.merges().iter().enumerate() {
+            first_leaf.push(first_leaf[merge.left]);
+            let color = match (&cluster_of, self.cut) {
+                (Some(clusters), Some(cut)) if merge.distance <= cut => {
+                    config.palette.color(clusters[first_leaf[n + i]])
+                }
+                _ => config.axis_color.as_str(),
+            };
+            let (left, right) = (positions[merge.left], positions[merge.right]);
+            context.set_stroke_style_str(color);
+            context.begin_path();
+            context.move_to(to_x(left.0), to_y(left.1));
+            context.line_to(to_x(left.0), to_y(merge.distance));
+            context.line_to(to_x(right.0), to_y(merge.distance));
+            context.line_to(to_x(right.0), to_y(right.1));
+            context.stroke();
+        }
+
+        if let (Some(cut), Some(clusters)) = (self.cut, &cluster_of) {
+            let y = to_y(cut.min(y_range.max));
+            context.set_stroke_style_str(&config.text_color);
+            context.set_line_width(1.0);
+            context
+                .set_line_dash(&Array::of2(&6.0.into(), &4.0.into()))
+                .unwrap_or(());
+            context.begin_path();
+            context.move_to(margin, y);
+            context.line_to(width - margin, y);
+            context.stroke();
+            context.set_line_dash(&Array::new()).unwrap_or(());
+
+            let n_clusters = clusters.iter().map(|&c| c + 1).max().unwrap_or(0);
+            context.set_fill_style_str(&config.text_color);
+            context.set_font(&font(config.tick_font_size, config));
+            context.set_text_align("right");
+            context
+                .fill_text(&format!("{} clusters", n_clusters), width - margin, y - 4.0)
+                .unwrap_or(());
+            context.set_text_align("start");
+        }
+        draw_title(context, width, config);
+    }
+}
+
+// Dendrogram of a hierarchical clustering. Clicking or dragging on it cuts
+// the tree at the pointer's height, passing the resulting cluster of every
+// sample to on_cut, e.g. to color a linked scatter plot.
+#[component]
+pub fn Dendrogram(
+    data: Signal<DendrogramData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    #[props(default)] on_cut: EventHandler<Vec<usize>>,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+    let mut cutting = use_signal(|| false);
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    let mut cut_at = move |y: f64| {
+        let height = data.peek().height_at(y, size().height);
+        data.write().cut = Some(height);
+        if let Some(labels) = data.peek().labels() {
+            on_cut.call(labels);
+        }
+    };
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black; cursor: row-resize;",
+                onmousedown: move |event| {
+                    cutting.set(true);
+                    cut_at(event.element_coordinates().y);
+                },
+                onmousemove: move |event| {
+                    if cutting() {
+                        cut_at(event.element_coordinates().y);
+                    }
+                },
+                onmouseup: move |_| cutting.set(false),
+                onmouseleave: move |_| cutting.set(false),
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
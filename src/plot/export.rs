@@ -0,0 +1,160 @@
+use crate::error::{MoonlightError, Result};
+use dioxus::prelude::*;
+use log::error;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::js_sys::{Array, Uint8Array};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlCanvasElement, Url, XmlSerializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // Snapshot of a canvas chart at its device resolution
+    Png,
+    // Markup of a chart rendered as SVG, e.g. a Vega-Lite chart embedded
+    // with the SVG renderer
+    Svg,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "image/png",
+            ExportFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+// filename with the format's extension, added when missing. Blank names
+// become "chart".
+pub fn export_filename(filename: &str, format: ExportFormat) -> String {
+    let name = filename.trim();
+    let name = if name.is_empty() { "chart" } else { name };
+    let extension = format!(".{}", format.extension());
+    if name.to_lowercase().ends_with(&extension) {
+        name.to_string()
+    } else {
+        format!("{}{}", name, extension)
+    }
+}
+
+// Downloads the chart inside the element with container_id as filename:
+// the first canvas as a PNG, or the first svg element as SVG markup. PNGs
+// are encoded asynchronously, so the download starts after this returns.
+pub fn export_chart(container_id: &str, format: ExportFormat, filename: &str) -> Result<()> {
+    let container = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(container_id))
+        .ok_or_else(|| MoonlightError::Js(format!("no element with id {}", container_id)))?;
+    let filename = export_filename(filename, format);
+    let selector = match format {
+        ExportFormat::Png => "canvas",
+        ExportFormat::Svg => "svg",
+    };
+    let element = container
+        .query_selector(selector)
+        .ok()
+        .flatten()
+        .ok_or_else(|| MoonlightError::Js(format!("no {} in {}", selector, container_id)))?;
+
+    match format {
+        ExportFormat::Png => {
+            let canvas = element
+                .dyn_into::<HtmlCanvasElement>()
+                .map_err(|_| MoonlightError::Js("not a canvas".to_string()))?;
+            let callback = Closure::once_into_js(move |blob: Option<Blob>| match blob {
+                Some(blob) => {
+                    if let Err(err) = download_blob(&blob, &filename) {
+                        error!("Error exporting {}: {}", filename, err);
+                    }
+                }
+                None => error!("Error exporting {}: canvas is empty", filename),
+            });
+            canvas.to_blob_with_type(callback.unchecked_ref(), format.mime_type())?;
+            Ok(())
+        }
+        ExportFormat::Svg => {
+            let markup = XmlSerializer::new()
+                .and_then(|serializer| serializer.serialize_to_string(&element))?;
+            let options = BlobPropertyBag::new();
+            options.set_type(format.mime_type());
+            let parts = Array::of1(&markup.into());
+            let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+            download_blob(&blob, &filename)
+        }
+    }
+}
+
+// Saves bytes under filename, e.g. a model exported for another runtime
+pub fn download_bytes(bytes: &[u8], filename: &str, mime_type: &str) -> Result<()> {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let parts = Array::of1(&Uint8Array::from(bytes));
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    download_blob(&blob, filename)
+}
+
+// Saves blob through a temporary link, as a click on it would
+fn download_blob(blob: &Blob, filename: &str) -> Result<()> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| MoonlightError::Js("no document".to_string()))?;
+    let url = Url::create_object_url_with_blob(blob)?;
+    let link = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()
+        .map_err(|_| MoonlightError::Js("could not create a link".to_string()))?;
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+    Ok(Url::revoke_object_url(&url)?)
+}
+
+// Filename field with a download button per format, exporting the chart
+// inside the element with target as its id
+#[component]
+pub fn ExportToolbar(
+    target: String,
+    // Filename the field starts with
+    #[props(default = "chart".to_string())] filename: String,
+    #[props(default = vec![ExportFormat::Png])] formats: Vec<ExportFormat>,
+) -> Element {
+    let mut name = use_signal(|| filename.clone());
+    let mut status = use_signal(String::new);
+
+    rsx! {
+        div {
+            class: "flex items-center gap-2 mt-1 text-sm",
+            input {
+                class: "px-1 border rounded",
+                value: "{name}",
+                oninput: move |event| name.set(event.value()),
+            }
+            for format in formats {
+                button {
+                    class: "px-2 border rounded",
+                    onclick: {
+                        let target = target.clone();
+                        move |_| match export_chart(&target, format, &name.read()) {
+                            Ok(()) => status.set(String::new()),
+                            Err(err) => {
+                                error!("Error exporting chart: {}", err);
+                                status.set(err.to_string());
+                            }
+                        }
+                    },
+                    "{format.extension().to_uppercase()}"
+                }
+            }
+            if !status.read().is_empty() {
+                span { class: "text-red-600", "{status}" }
+            }
+        }
+    }
+}
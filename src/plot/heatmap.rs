@@ -0,0 +1,288 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_title, draw_x_label, draw_y_label, font, MARGIN,
+};
+use super::color::Colormap;
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scatter::Drawable;
+use super::theme::{themed, use_theme};
+use crate::data::stats::CorrelationMatrix;
+use crate::ml::metrics::classification::ConfusionMatrix;
+use crate::ml::metrics::distance::Metric;
+use dioxus::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+// Room for the row labels left of the cells and the color bar right of them
+const LABEL_WIDTH: f64 = 60.0;
+const COLOR_BAR_WIDTH: f64 = 12.0;
+const COLOR_BAR_GAP: f64 = 10.0;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+// Matrix drawn as a grid of colored cells, e.g. a confusion, correlation or
+// distance matrix. NaN cells are left blank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapData {
+    // values[i][j] in row i and column j
+    pub values: Vec<Vec<f64>>,
+    pub row_labels: Vec<String>,
+    pub column_labels: Vec<String>,
+    pub colormap: Colormap,
+    // Values mapped to the ends of the colormap, the data range when None
+    pub domain: Option<(f64, f64)>,
+    // Write the value in every cell
+    pub show_values: bool,
+}
+
+impl Default for HeatmapData {
+    fn default() -> Self {
+        HeatmapData::new(vec![])
+    }
+}
+
+impl HeatmapData {
+    // Rows and columns labeled by their index
+    pub fn new(values: Vec<Vec<f64>>) -> Self {
+        let n_columns = values.first().map_or(0, |row| row.len());
+        HeatmapData {
+            row_labels: (0..values.len()).map(|i| i.to_string()).collect(),
+            column_labels: (0..n_columns).map(|j| j.to_string()).collect(),
+            values,
+            colormap: Colormap::Blues,
+            domain: None,
+            show_values: false,
+        }
+    }
+
+    // Counts with actual classes as rows and predicted classes as columns
+    pub fn from_confusion_matrix(matrix: &ConfusionMatrix) -> Self {
+        let values = matrix
+            .counts
+            .iter()
+            .map(|row| row.iter().map(|&c| c as f64).collect())
+            .collect();
+        HeatmapData::new(values).with_values(true)
+    }
+
+    // Correlations on a diverging scale from -1 to 1
+    pub fn from_correlation(matrix: &CorrelationMatrix) -> Self {
+        HeatmapData {
+            row_labels: matrix.columns.clone(),
+            column_labels: matrix.columns.clone(),
+            values: matrix.values.clone(),
+            colormap: Colormap::RedBlue,
+            domain: Some((-1.0, 1.0)),
+            show_values: true,
+        }
+    }
+
+    // Distance between every pair of points
+    pub fn from_distances<M: Metric>(points: &[Point], metric: &M) -> Self {
+        let values = points
+            .iter()
+            .map(|a| points.iter().map(|b| metric.distance(a, b)).collect())
+            .collect();
+        HeatmapData::new(values)
+    }
+
+    pub fn with_labels(mut self, row_labels: Vec<String>, column_labels: Vec<String>) -> Self {
+        self.row_labels = row_labels;
+        self.column_labels = column_labels;
+        self
+    }
+
+    pub fn with_colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    pub fn with_domain(mut self, low: f64, high: f64) -> Self {
+        self.domain = Some((low, high));
+        self
+    }
+
+    pub fn with_values(mut self, show_values: bool) -> Self {
+        self.show_values = show_values;
+        self
+    }
+
+    // Values at the two ends of the colormap
+    pub fn value_range(&self) -> (f64, f64) {
+        self.domain.unwrap_or_else(|| {
+            let values = self.values.iter().flatten().copied();
+            let low = values.clone().fold(f64::INFINITY, f64::min);
+            let high = values.fold(f64::NEG_INFINITY, f64::max);
+            if low.is_finite() {
+                (low, high)
+            } else {
+                (0.0, 1.0)
+            }
+        })
+    }
+
+    // Position of value on the colormap, in [0, 1]
+    fn scale(&self, value: f64) -> f64 {
+        let (low, high) = self.value_range();
+        if high > low {
+            (value - low) / (high - low)
+        } else {
+            0.5
+        }
+    }
+}
+
+impl Drawable for HeatmapData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let n_rows = self.values.len();
+        let n_columns = self.values.first().map_or(0, |row| row.len());
+        if n_rows == 0 || n_columns == 0 {
+            return;
+        }
+
+        let left = MARGIN + LABEL_WIDTH;
+        let top = MARGIN;
+        let grid_width = width - left - MARGIN - COLOR_BAR_GAP - COLOR_BAR_WIDTH;
+        let grid_height = height - top - MARGIN;
+        let cell_width = grid_width / n_columns as f64;
+        let cell_height = grid_height / n_rows as f64;
+
+        context.set_font(&font(config.tick_font_size, config));
+        context.set_text_baseline("middle");
+        for (i, row) in self.values.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                if value.is_nan() {
+                    continue;
+                }
+                let x = left + j as f64 * cell_width;
+                let y = top + i as f64 * cell_height;
+                let color = self.colormap.map(self.scale(value));
+                context.set_fill_style_str(&color.to_string());
+                context.fill_rect(x, y, cell_width, cell_height);
+
+                if self.show_values {
+                    // Light text on dark cells
+                    context.set_fill_style_str(color.contrasting_text());
+                    context.set_text_align("center");
+                    context
+                        .fill_text(
+                            &format_value(value),
+                            x + cell_width / 2.0,
+                            y + cell_height / 2.0,
+                        )
+                        .unwrap_or(());
+                }
+            }
+        }
+
+        // Row labels on the left, column labels underneath
+        context.set_fill_style_str(&config.text_color);
+        context.set_text_align("right");
+        for (i, label) in self.row_labels.iter().enumerate().take(n_rows) {
+            let y = top + (i as f64 + 0.5) * cell_height;
+            context.fill_text(label, left - 6.0, y).unwrap_or(());
+        }
+        context.set_text_align("center");
+        for (j, label) in self.column_labels.iter().enumerate().take(n_columns) {
+            let x = left + (j as f64 + 0.5) * cell_width;
+            context
+                .fill_text(label, x, top + grid_height + 14.0)
+                .unwrap_or(());
+        }
+
+        // Color bar, low values at the bottom
+        let bar_left = left + grid_width + COLOR_BAR_GAP;
+        let steps = 50;
+        let step_height = grid_height / steps as f64;
+        for s in 0..steps {
+            let t = 1.0 - (s as f64 + 0.5) / steps as f64;
+            context.set_fill_style_str(&self.colormap.map(t).to_string());
+            context.fill_rect(
+                bar_left,
+                top + s as f64 * step_height,
+                COLOR_BAR_WIDTH,
+                step_height + 0.5,
+            );
+        }
+        let (low, high) = self.value_range();
+        context.set_fill_style_str(&config.text_color);
+        context
+            .fill_text(
+                &format_value(high),
+                bar_left + COLOR_BAR_WIDTH / 2.0,
+                top - 8.0,
+            )
+            .unwrap_or(());
+        context
+            .fill_text(
+                &format_value(low),
+                bar_left + COLOR_BAR_WIDTH / 2.0,
+                top + grid_height + 8.0,
+            )
+            .unwrap_or(());
+        context.set_text_align("start");
+        context.set_text_baseline("alphabetic");
+        draw_x_label(context, width, height, config);
+        draw_y_label(context, height, config);
+        draw_title(context, width, config);
+    }
+}
+
+// Heatmap on a canvas, redrawn whenever data changes
+#[component]
+pub fn Heatmap(
+    data: Signal<HeatmapData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
+
+// Integers as such, other values to two decimals
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e9 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
@@ -0,0 +1,280 @@
+use super::canvas::{clear_canvas, context_2d, draw_axes, draw_title, MARGIN};
+use super::color::Palette;
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scale::AxisRange;
+use super::scatter::Drawable;
+use super::theme::{themed, use_theme};
+use super::vega::spec::{BinDef, Channel, FieldDef, FieldType, Mark, MarkDef, VegaSpec};
+use crate::data::frame::DataFrame;
+use crate::ml::utils::quantile;
+use dioxus::prelude::*;
+use serde::Serialize;
+use web_sys::CanvasRenderingContext2d;
+
+// Caps automatic binning on long-tailed data
+const MAX_BINS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bins {
+    // This many equal-width bins between the smallest and largest value
+    Count(usize),
+    // Freedman and Diaconis (1981): width 2 * IQR / n^(1/3), robust to
+    // outliers. Falls back to Sturges' log2(n) + 1 bins when the IQR is 0.
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistogramMode {
+    Count,
+    // Count / (n * bin width), so the bars integrate to 1 and compare
+    // across sample sizes
+    Density,
+}
+
+// One bar: values in [start, end), the last bar also holding the maximum
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+    pub density: f64,
+}
+
+// Distribution of one variable. NaNs are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramData {
+    pub values: Vec<f64>,
+    pub bins: Bins,
+    pub mode: HistogramMode,
+    // CSS color of the bars
+    pub color: String,
+}
+
+impl Default for HistogramData {
+    fn default() -> Self {
+        HistogramData::new(vec![])
+    }
+}
+
+impl HistogramData {
+    pub fn new(values: Vec<f64>) -> Self {
+        HistogramData {
+            values,
+            bins: Bins::Auto,
+            mode: HistogramMode::Count,
+            color: Palette::default().color(0).to_string(),
+        }
+    }
+
+    // Non-missing values of a numeric column, None for unknown and string
+    // columns
+    pub fn from_column(frame: &DataFrame, name: &str) -> Option<Self> {
+        Some(HistogramData::new(frame.numeric_values(name)?))
+    }
+
+    pub fn with_bins(mut self, bins: Bins) -> Self {
+        self.bins = bins;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: HistogramMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // Bars from the smallest to the largest value, empty without values
+    pub fn compute_bins(&self) -> Vec<Bin> {
+        let values: Vec<f64> = self
+            .values
+            .iter()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .collect();
+        let Some(min) = values.iter().copied().reduce(f64::min) else {
+            return vec![];
+        };
+        let max = values.iter().copied().fold(min, f64::max);
+
+        // A single distinct value gets one bar of unit width
+        let (n_bins, width) = if max > min {
+            let n_bins = match self.bins {
+                Bins::Count(n) => n.max(1),
+                Bins::Auto => auto_bin_count(&values, max - min),
+            };
+            (n_bins, (max - min) / n_bins as f64)
+        } else {
+            (1, 1.0)
+        };
+        let mut counts = vec![0; n_bins];
+        for v in &values {
+            let i = ((v - min) / width) as usize;
+            counts[i.min(n_bins - 1)] += 1;
+        }
+
+        let n = values.len() as f64;
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| Bin {
+                start: min + i as f64 * width,
+                end: min + (i + 1) as f64 * width,
+                count,
+                density: count as f64 / (n * width),
+            })
+            .collect()
+    }
+
+    // Vega-Lite bar chart of the bins computed here, so both backends show
+    // the same bars
+    pub fn vega_spec(&self, title: &str, x_label: &str) -> VegaSpec {
+        let (field, y_title) = self.mode_field();
+        let bins = self
+            .compute_bins()
+            .iter()
+            .filter_map(|bin| serde_json::to_value(bin).ok())
+            .collect();
+        let tooltip =
+            |field: &str| FieldDef::new(field, FieldType::Quantitative).with_format(".3~g");
+        VegaSpec::new()
+            .title(title)
+            .data(bins)
+            .mark_def(MarkDef {
+                color: Some(self.color.clone()),
+                bin_spacing: Some(1.0),
+                ..MarkDef::new(Mark::Bar)
+            })
+            .encode(
+                Channel::X,
+                FieldDef::new("start", FieldType::Quantitative)
+                    .with_bin(BinDef {
+                        binned: Some(true),
+                        ..BinDef::default()
+                    })
+                    .with_title(x_label),
+            )
+            .x2("end")
+            .encode(
+                Channel::Y,
+                FieldDef::new(field, FieldType::Quantitative).with_title(y_title),
+            )
+            .tooltip(tooltip("start"))
+            .tooltip(tooltip("end"))
+            .tooltip(tooltip(field))
+    }
+
+    fn mode_field(&self) -> (&'static str, &'static str) {
+        match self.mode {
+            HistogramMode::Count => ("count", "Count"),
+            HistogramMode::Density => ("density", "Density"),
+        }
+    }
+}
+
+impl Drawable for HistogramData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let margin = MARGIN;
+        let bins = self.compute_bins();
+        let heights: Vec<f64> = bins
+            .iter()
+            .map(|bin| match self.mode {
+                HistogramMode::Count => bin.count as f64,
+                HistogramMode::Density => bin.density,
+            })
+            .collect();
+
+        // Axes from the first edge to the last and from 0 to the tallest bar
+        let x_min = bins.first().map_or(0.0, |bin| bin.start);
+        let x_max = bins.last().map_or(1.0, |bin| bin.end);
+        let y_max = heights
+            .iter()
+            .copied()
+            .fold(0.0, f64::max)
+            .max(f64::EPSILON);
+        draw_axes(
+            context,
+            width,
+            height,
+            AxisRange::new(x_min, x_max),
+            AxisRange::new(0.0, y_max),
+            config,
+        );
+
+        let x_scale = (width - 2.0 * margin) / (x_max - x_min);
+        let y_scale = (height - 2.0 * margin) / y_max;
+        context.set_fill_style_str(&self.color);
+        // Gaps between bars in the background color
+        context.set_stroke_style_str(config.background.as_deref().unwrap_or("white"));
+        context.set_line_width(1.0);
+        for (bin, h) in bins.iter().zip(heights) {
+            let left = margin + (bin.start - x_min) * x_scale;
+            let bar_width = (bin.end - bin.start) * x_scale;
+            let bar_height = h * y_scale;
+            let top = height - margin - bar_height;
+            context.fill_rect(left, top, bar_width, bar_height);
+            context.stroke_rect(left, top, bar_width, bar_height);
+        }
+        draw_title(context, width, config);
+    }
+}
+
+// Histogram on a canvas, redrawn whenever data changes
+#[component]
+pub fn Histogram(
+    data: Signal<HistogramData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
+
+fn auto_bin_count(values: &[f64], range: f64) -> usize {
+    let n = values.len() as f64;
+    let iqr = quantile(values, 0.75) - quantile(values, 0.25);
+    let count = if iqr > 0.0 {
+        (range / (2.0 * iqr / n.cbrt())).ceil()
+    } else {
+        n.log2().ceil() + 1.0
+    };
+    (count as usize).clamp(1, MAX_BINS)
+}
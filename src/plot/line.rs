@@ -0,0 +1,303 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_axes, draw_band, draw_error_bar, draw_legend, draw_title,
+    LegendEntry, Swatch, MARGIN,
+};
+use super::color::Palette;
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::responsive::use_chart_size;
+use super::scale::AxisRange;
+use super::scatter::Drawable;
+use super::theme::{themed, use_theme};
+use dioxus::prelude::*;
+use std::f64;
+use web_sys::CanvasRenderingContext2d;
+
+const LINE_WIDTH: f64 = 2.0;
+const MARKER_RADIUS: f64 = 3.0;
+const ERROR_BAR_WIDTH: f64 = 1.0;
+const ERROR_CAP_WIDTH: f64 = 6.0;
+// Opacity of the series color shading its band
+const BAND_OPACITY: f64 = 0.2;
+
+// Uncertainty around every y of a series. NaN bounds leave it out at that
+// point.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Uncertainty {
+    // Bars from y - error to y + error, e.g. the std of a score across folds
+    ErrorBars(Vec<f64>),
+    // Area shaded between lower and upper bounds, e.g. a prediction interval
+    Band { lower: Vec<f64>, upper: Vec<f64> },
+}
+
+// One line of a line plot. NaN values leave a gap in the line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    // CSS color, the next palette color when None
+    pub color: Option<String>,
+    // Draw a dot at every point
+    pub markers: bool,
+    pub uncertainty: Option<Uncertainty>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LinePlotData {
+    pub series: Vec<Series>,
+}
+
+impl Series {
+    pub fn new(name: &str, x: Vec<f64>, y: Vec<f64>) -> Self {
+        Series {
+            name: name.to_string(),
+            x,
+            y,
+            color: None,
+            markers: false,
+            uncertainty: None,
+        }
+    }
+
+    // Mean of several runs of the same curve at every x, e.g. the scores of
+    // each cross-validation fold, shaded one standard deviation either side.
+    // Points where no run has a value are NaN.
+    pub fn from_runs(name: &str, x: Vec<f64>, runs: &[Vec<f64>]) -> Self {
+        let (mean, std): (Vec<f64>, Vec<f64>) = (0..x.len())
+            .map(|i| {
+                let values: Vec<f64> = runs
+                    .iter()
+                    .filter_map(|run| run.get(i).copied())
+                    .filter(|v| !v.is_nan())
+                    .collect();
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                (mean, variance.sqrt())
+            })
+            .unzip();
+        Series::new(name, x, mean).with_std_band(std)
+    }
+
+    // y against its index, e.g. a loss curve against the iteration
+    pub fn from_values(name: &str, y: Vec<f64>) -> Self {
+        let x = (0..y.len()).map(|i| i as f64).collect();
+        Series::new(name, x, y)
+    }
+
+    pub fn with_color(mut self, color: &str) -> Self {
+        self.color = Some(color.to_string());
+        self
+    }
+
+    pub fn with_markers(mut self, markers: bool) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    // Error bars of half-length errors[i] at every point
+    pub fn with_error_bars(mut self, errors: Vec<f64>) -> Self {
+        self.uncertainty = Some(Uncertainty::ErrorBars(errors));
+        self
+    }
+
+    pub fn with_band(mut self, lower: Vec<f64>, upper: Vec<f64>) -> Self {
+        self.uncertainty = Some(Uncertainty::Band { lower, upper });
+        self
+    }
+
+    // Band from y - std[i] to y + std[i]
+    pub fn with_std_band(self, std: Vec<f64>) -> Self {
+        let lower = self.y.iter().zip(&std).map(|(y, s)| y - s).collect();
+        let upper = self.y.iter().zip(&std).map(|(y, s)| y + s).collect();
+        self.with_band(lower, upper)
+    }
+
+    // Lower and upper ends of the uncertainty at every point, none without
+    // one
+    fn bounds(&self) -> Vec<(f64, f64)> {
+        match &self.uncertainty {
+            Some(Uncertainty::ErrorBars(errors)) => self
+                .y
+                .iter()
+                .zip(errors)
+                .map(|(y, e)| (y - e.abs(), y + e.abs()))
+                .collect(),
+            Some(Uncertainty::Band { lower, upper }) => {
+                lower.iter().copied().zip(upper.iter().copied()).collect()
+            }
+            None => vec![],
+        }
+    }
+}
+
+impl LinePlotData {
+    pub fn new(series: Vec<Series>) -> Self {
+        LinePlotData { series }
+    }
+
+    // Color every series is drawn with
+    fn colors(&self, palette: Palette) -> Vec<&str> {
+        self.series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.color.as_deref().unwrap_or(palette.color(i)))
+            .collect()
+    }
+}
+
+impl Drawable for LinePlotData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let margin = MARGIN;
+
+        // Data ranges over every series, on the configured scales
+        let x_range = AxisRange::from_values(
+            self.series.iter().flat_map(|s| s.x.iter().copied()),
+            config.x_scale,
+        );
+        let y_range = AxisRange::from_values(
+            self.series.iter().flat_map(|s| {
+                let bounds = s.bounds().into_iter().flat_map(|(low, high)| [low, high]);
+                s.y.iter().copied().chain(bounds)
+            }),
+            config.y_scale,
+        );
+        draw_axes(context, width, height, x_range, y_range, config);
+
+        // NaN for values without a position, such as 0 on a log scale
+        let to_canvas = |x: f64, y: f64| {
+            (
+                margin + x_range.fraction(x) * (width - 2.0 * margin),
+                height - margin - y_range.fraction(y) * (height - 2.0 * margin),
+            )
+        };
+
+        // Bands under every line
+        for (series, color) in self.series.iter().zip(self.colors(config.palette)) {
+            if let Some(Uncertainty::Band { .. }) = series.uncertainty {
+                let (upper, lower): (Vec<_>, Vec<_>) = series
+                    .x
+                    .iter()
+                    .zip(series.bounds())
+                    .map(|(&x, (low, high))| (to_canvas(x, high), to_canvas(x, low)))
+                    .unzip();
+                context.set_fill_style_str(color);
+                draw_band(context, &upper, &lower, BAND_OPACITY);
+            }
+        }
+
+        for (series, color) in self.series.iter().zip(self.colors(config.palette)) {
+            context.set_stroke_style_str(color);
+            context.set_fill_style_str(color);
+
+            if let Some(Uncertainty::ErrorBars(_)) = series.uncertainty {
+                context.set_line_width(ERROR_BAR_WIDTH);
+                for (&x, (low, high)) in series.x.iter().zip(series.bounds()) {
+                    let (x_pos, low_pos) = to_canvas(x, low);
+                    let (_, high_pos) = to_canvas(x, high);
+                    if x_pos.is_nan() || low_pos.is_nan() || high_pos.is_nan() {
+                        continue;
+                    }
+                    draw_error_bar(context, x_pos, low_pos, high_pos, ERROR_CAP_WIDTH);
+                }
+            }
+
+            context.set_line_width(LINE_WIDTH);
+
+            context.begin_path();
+            let mut pen_down = false;
+            for (&x, &y) in series.x.iter().zip(&series.y) {
+                let (x_pos, y_pos) = to_canvas(x, y);
+                if x_pos.is_nan() || y_pos.is_nan() {
+                    pen_down = false;
+                    continue;
+                }
+                if pen_down {
+                    context.line_to(x_pos, y_pos);
+                } else {
+                    context.move_to(x_pos, y_pos);
+                    pen_down = true;
+                }
+            }
+            context.stroke();
+
+            if series.markers {
+                for (&x, &y) in series.x.iter().zip(&series.y) {
+                    let (x_pos, y_pos) = to_canvas(x, y);
+                    if x_pos.is_nan() || y_pos.is_nan() {
+                        continue;
+                    }
+                    context.begin_path();
+                    context
+                        .arc(x_pos, y_pos, MARKER_RADIUS, 0.0, 2.0 * f64::consts::PI)
+                        .unwrap_or(());
+                    context.fill();
+                }
+            }
+        }
+
+        let entries: Vec<LegendEntry> = self
+            .series
+            .iter()
+            .zip(self.colors(config.palette))
+            .map(|(series, color)| LegendEntry {
+                label: series.name.clone(),
+                color: color.to_string(),
+                swatch: Swatch::Line,
+            })
+            .collect();
+        draw_legend(context, width, height, &entries, config);
+        draw_title(context, width, config);
+    }
+}
+
+// Line chart of one or more series, e.g. training loss curves or time
+// series. Redraws whenever data changes.
+#[component]
+pub fn LinePlot(
+    data: Signal<LinePlotData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        clear_canvas(&context, size, &config);
+        data.read().draw(&context, size.width, size.height, &config);
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use dioxus::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+// Rows brushed in each dataset, shared by every chart linked to it. Charts
+// name the dataset their marks are rows of, see ScatterPlot's linked prop.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LinkedSelection {
+    // Datasets without an entry have nothing selected
+    rows: HashMap<String, BTreeSet<usize>>,
+}
+
+impl LinkedSelection {
+    // Rows selected in dataset, None when nothing is
+    pub fn rows(&self, dataset: &str) -> Option<&BTreeSet<usize>> {
+        self.rows.get(dataset)
+    }
+
+    pub fn is_selected(&self, dataset: &str, row: usize) -> bool {
+        self.rows(dataset).is_some_and(|rows| rows.contains(&row))
+    }
+
+    // Replaces the rows selected in dataset, clearing it when rows is empty
+    pub fn select(&mut self, dataset: &str, rows: BTreeSet<usize>) {
+        if rows.is_empty() {
+            self.clear(dataset);
+        } else {
+            self.rows.insert(dataset.to_string(), rows);
+        }
+    }
+
+    pub fn clear(&mut self, dataset: &str) {
+        self.rows.remove(dataset);
+    }
+}
+
+// Shares selection between the charts inside, so that brushing rows in one
+// highlights them in every other linked to the same dataset
+#[component]
+pub fn LinkedSelectionProvider(selection: Signal<LinkedSelection>, children: Element) -> Element {
+    use_context_provider(|| selection);
+    rsx! {
+        {children}
+    }
+}
+
+// Selection of the nearest LinkedSelectionProvider, None outside one
+pub fn use_linked_selection() -> Option<Signal<LinkedSelection>> {
+    try_use_context::<Signal<LinkedSelection>>()
+}
+
+// Rows selected in dataset, None when the chart isn't linked or nothing is
+// selected. Charts call this in their effects, so that they redraw when
+// another chart brushes.
+pub(crate) fn linked_rows(
+    selection: Option<Signal<LinkedSelection>>,
+    dataset: Option<&str>,
+) -> Option<BTreeSet<usize>> {
+    selection?.read().rows(dataset?).cloned()
+}
+
+// Sets the rows selected in dataset, doing nothing when the chart isn't
+// linked
+pub(crate) fn select_rows(
+    selection: Option<Signal<LinkedSelection>>,
+    dataset: Option<&str>,
+    rows: BTreeSet<usize>,
+) {
+    if let (Some(mut selection), Some(dataset)) = (selection, dataset) {
+        selection.write().select(dataset, rows);
+    }
+}
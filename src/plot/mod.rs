@@ -1,2 +1,23 @@
+pub mod animation;
+pub mod boxplot;
+mod canvas;
+pub mod color;
+pub mod comparison;
+pub mod config;
+pub mod decision_boundary;
+pub mod dendrogram;
+pub mod export;
+pub mod heatmap;
+pub mod histogram;
+pub mod line;
+pub mod linked;
+pub mod pairplot;
+pub mod params;
+pub mod responsive;
+pub mod scale;
 pub mod scatter;
-
+pub mod scatter3d;
+pub mod theme;
+pub mod vega;
+pub mod view;
+pub mod webgl;
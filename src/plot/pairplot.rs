@@ -0,0 +1,353 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_legend, draw_marker, draw_title, font, LegendEntry, Swatch,
+    MARGIN,
+};
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::linked::{linked_rows, use_linked_selection};
+use super::responsive::use_chart_size;
+use super::scale::{AxisRange, Scale};
+use super::scatter::{Drawable, MarkerShape};
+use super::theme::{themed, use_theme};
+use crate::data::frame::{DataFrame, FrameError};
+use dioxus::prelude::*;
+use std::collections::BTreeSet;
+use std::f64;
+use web_sys::CanvasRenderingContext2d;
+
+// Pixels between neighboring cells
+const CELL_GAP: f64 = 6.0;
+const MARKER_RADIUS: f64 = 2.0;
+const MARKER_OPACITY: f64 = 0.7;
+// Bars of the histograms on the diagonal, over each column's whole range
+const HISTOGRAM_BINS: usize = 20;
+// Classes' bars overlap, each showing through the others
+const BAR_OPACITY: f64 = 0.5;
+// More columns than this make cells too small to read
+pub const MAX_COLUMNS: usize = 8;
+// Rows outside a linked selection, behind the selected ones
+const DIMMED_COLOR: &str = "#bbbbbb";
+const DIMMED_OPACITY: f64 = 0.3;
+
+// Scatter plot matrix: every column plotted against every other, with the
+// distribution of each on the diagonal
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PairPlotData {
+    pub columns: Vec<String>,
+    // values[c][i] is column c of row i, NaN when missing
+    pub values: Vec<Vec<f64>>,
+    // Class of every row, coloring its markers and bars. One color when
+    // None.
+    pub classes: Option<Vec<String>>,
+}
+
+impl PairPlotData {
+    pub fn new(columns: Vec<String>, values: Vec<Vec<f64>>) -> Self {
+        PairPlotData {
+            columns,
+            values,
+            classes: None,
+        }
+    }
+
+    // The named numeric columns of frame, with rows colored by the values
+    // of class_column when given
+    pub fn from_frame(
+        frame: &DataFrame,
+        names: &[&str],
+        class_column: Option<&str>,
+    ) -> Result<Self, FrameError> {
+        let values = names
+            .iter()
+            .map(|&name| {
+                frame
+                    .column(name)
+                    .ok_or_else(|| FrameError::UnknownColumn(name.to_string()))?
+                    .as_f64()
+                    .ok_or_else(|| FrameError::NotNumeric(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = PairPlotData::new(names.iter().map(|n| n.to_string()).collect(), values);
+        let Some(class_column) = class_column else {
+            return Ok(data);
+        };
+        let column = frame
+            .column(class_column)
+            .ok_or_else(|| FrameError::UnknownColumn(class_column.to_string()))?;
+        let classes = (0..frame.n_rows())
+            .map(|i| column.get(i).map(|v| v.to_string()).unwrap_or_default())
+            .collect();
+        Ok(data.with_classes(classes))
+    }
+
+    pub fn with_classes(mut self, classes: Vec<String>) -> Self {
+        self.classes = Some(classes);
+        self
+    }
+
+    fn n_rows(&self) -> usize {
+        self.values.iter().map(Vec::len).min().unwrap_or(0)
+    }
+
+    // Distinct classes in order of first appearance, and the index of every
+    // row's class among them
+    fn class_indices(&self) -> (Vec<&str>, Vec<usize>) {
+        let Some(classes) = &self.classes else {
+            return (vec![], vec![0; self.n_rows()]);
+        };
+        let mut names: Vec<&str> = vec![];
+        let indices = classes
+            .iter()
+            .map(|class| match names.iter().position(|&n| n == class) {
+                Some(i) => i,
+                None => {
+                    names.push(class);
+                    names.len() - 1
+                }
+            })
+            .collect();
+        (names, indices)
+    }
+
+    // The matrix with the rows outside highlighted dimmed, and the
+    // histograms counting only highlighted rows over the dimmed counts of
+    // all of them. Every row is drawn alike when highlighted is None.
+    pub fn draw_highlighted(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+        highlighted: Option<&BTreeSet<usize>>,
+    ) {
+        let margin = MARGIN;
+        let n = self.columns.len().min(self.values.len());
+        if n == 0 {
+            draw_title(context, width, config);
+            return;
+        }
+        let n_rows = self.n_rows();
+        let (class_names, class_of) = self.class_indices();
+        let colors: Vec<&str> = (0..class_names.len().max(1))
+            .map(|i| config.palette.color(i))
+            .collect();
+        let ranges: Vec<AxisRange> = self.values[..n]
+            .iter()
+            .map(|values| AxisRange::from_values(values.iter().copied(), Scale::Linear))
+            .collect();
+        let cell_width = (width - 2.0 * margin - CELL_GAP * (n - 1) as f64) / n as f64;
+        let cell_height = (height - 2.0 * margin - CELL_GAP * (n - 1) as f64) / n as f64;
+
+        for row in 0..n {
+            for column in 0..n {
+                let left = margin + column as f64 * (cell_width + CELL_GAP);
+                let top = margin + row as f64 * (cell_height + CELL_GAP);
+                context.set_stroke_style_str(&config.axis_color);
+                context.set_line_width(1.0);
+                context.stroke_rect(left, top, cell_width, cell_height);
+
+                context.save();
+                if row == column {
+                    let to_x = |v: f64| left + ranges[column].fraction(v) * cell_width;
+                    let values = &self.values[column][..n_rows];
+                    let bin_width =
+                        (ranges[column].max - ranges[column].min) / HISTOGRAM_BINS as f64;
+                    let draw_bars = |counts: &[usize], tallest: usize| {
+                        for (bin, &count) in counts.iter().enumerate() {
+                            let start = ranges[column].min + bin as f64 * bin_width;
+                            let bar_height = count as f64 / tallest as f64 * cell_height;
+                            context.fill_rect(
+                                to_x(start),
+                                top + cell_height - bar_height,
+                                to_x(start + bin_width) - to_x(start),
+                                bar_height,
+                            );
+                        }
+                    };
+                    let (counts, tallest) = match highlighted {
+                        Some(rows) => {
+                            let all = class_histograms(values, &[], 1, ranges[column]);
+                            let tallest = all[0].iter().copied().max().unwrap_or(0).max(1);
+                            context.set_global_alpha(DIMMED_OPACITY);
+                            context.set_fill_style_str(DIMMED_COLOR);
+                            draw_bars(&all[0], tallest);
+                            // Highlighted rows keep their class, the others
+                            // fall outside every class
+                            let class_of: Vec<usize> = (0..n_rows)
+                                .map(|i| {
+                                    if rows.contains(&i) {
+                                        class_of.get(i).copied().unwrap_or(0)
+                                    } else {
+                                        usize::MAX
+                                    }
+                                })
+                                .collect();
+                            let counts =
+                                class_histograms(values, &class_of, colors.len(), ranges[column]);
+                            (counts, tallest)
+                        }
+                        None => {
+                            let counts =
+                                class_histograms(values, &class_of, colors.len(), ranges[column]);
+                            let tallest =
+                                counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+                            (counts, tallest)
+                        }
+                    };
+                    context.set_global_alpha(BAR_OPACITY);
+                    for (class_counts, color) in counts.iter().zip(&colors) {
+                        context.set_fill_style_str(color);
+                        draw_bars(class_counts, tallest);
+                    }
+                } else {
+                    let (x_range, y_range) = (ranges[column], ranges[row]);
+                    // Dimmed rows first, under the highlighted ones
+                    let dimmed = |i: usize| highlighted.is_some_and(|rows| !rows.contains(&i));
+                    let order = (0..n_rows)
+                        .filter(|&i| dimmed(i))
+                        .chain((0..n_rows).filter(|&i| !dimmed(i)));
+                    for i in order {
+                        let (x, y) = (self.values[column][i], self.values[row][i]);
+                        if x.is_nan() || y.is_nan() {
+                            continue;
+                        }
+                        if dimmed(i) {
+                            context.set_global_alpha(DIMMED_OPACITY);
+                            context.set_fill_style_str(DIMMED_COLOR);
+                        } else {
+                            let class = class_of.get(i).copied().unwrap_or(0);
+                            context.set_global_alpha(MARKER_OPACITY);
+                            context.set_fill_style_str(colors[class % colors.len()]);
+                        }
+                        draw_marker(
+                            context,
+                            MarkerShape::Circle,
+                            left + x_range.fraction(x) * cell_width,
+                            top + cell_height - y_range.fraction(y) * cell_height,
+                            MARKER_RADIUS,
+                        );
+                    }
+                }
+                context.restore();
+            }
+        }
+
+        // Column names under the bottom row and left of the first column
+        context.set_fill_style_str(&config.text_color);
+        context.set_font(&font(config.label_font_size, config));
+        context.set_text_align("center");
+        for (i, name) in self.columns[..n].iter().enumerate() {
+            let center_x = margin + (i as f64 + 0.5) * cell_width + i as f64 * CELL_GAP;
+            let center_y = margin + (i as f64 + 0.5) * cell_height + i as f64 * CELL_GAP;
+            context
+                .fill_text(name, center_x, height - margin + 20.0)
+                .unwrap_or(());
+            context.save();
+            context.translate(margin - 10.0, center_y).unwrap_or(());
+            context.rotate(-f64::consts::FRAC_PI_2).unwrap_or(());
+            context.fill_text(name, 0.0, 0.0).unwrap_or(());
+            context.restore();
+        }
+        context.set_text_align("start");
+
+        let entries: Vec<LegendEntry> = class_names
+            .iter()
+            .zip(&colors)
+            .map(|(name, color)| LegendEntry {
+                label: name.to_string(),
+                color: color.to_string(),
+                swatch: Swatch::Marker(MarkerShape::Circle),
+            })
+            .collect();
+        draw_legend(context, width, height, &entries, config);
+        draw_title(context, width, config);
+    }
+}
+
+impl Drawable for PairPlotData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        self.draw_highlighted(context, width, height, config, None);
+    }
+}
+
+// Counts of values per class in HISTOGRAM_BINS equal bins over range,
+// counts[class][bin]. Values past the end of class_of count for class 0,
+// and values of classes from n_classes on for none.
+fn class_histograms(
+    values: &[f64],
+    class_of: &[usize],
+    n_classes: usize,
+    range: AxisRange,
+) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; HISTOGRAM_BINS]; n_classes];
+    let span = range.max - range.min;
+    for (i, &v) in values.iter().enumerate() {
+        let class = class_of.get(i).copied().unwrap_or(0);
+        if v.is_nan() || span <= 0.0 || class >= n_classes {
+            continue;
+        }
+        let bin = ((v - range.min) / span * HISTOGRAM_BINS as f64) as usize;
+        counts[class][bin.min(HISTOGRAM_BINS - 1)] += 1;
+    }
+    counts
+}
+
+// Scatter plot matrix of a few columns, e.g. the first look at an uploaded
+// dataset. Redraws whenever data changes. Linked to a dataset whose rows
+// its rows are, rows brushed in other charts are highlighted.
+#[component]
+pub fn PairPlot(
+    data: Signal<PairPlotData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+    #[props(default)] linked: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+    let linked_selection = use_linked_selection();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        let highlighted = linked_rows(linked_selection, linked.as_deref());
+        clear_canvas(&context, size, &config);
+        data.read().draw_highlighted(
+            &context,
+            size.width,
+            size.height,
+            &config,
+            highlighted.as_ref(),
+        );
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black;"
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
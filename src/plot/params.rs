@@ -0,0 +1,64 @@
+use crate::ml::params::{ParamKind, ParamSpec, Params};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+// An input per spec, numbers bounded by the spec and checkboxes for Bool.
+// Committed values are clamped to their spec before they reach values,
+// and text that isn't a number is flagged beside its input and left out.
+// Any estimator's form is ParamForm { specs: Estimator::param_specs(), .. }.
+#[component]
+pub fn ParamForm(specs: Vec<ParamSpec>, values: Signal<Params>) -> Element {
+    // Why the text last typed into each input was rejected
+    let mut errors = use_signal(HashMap::<&'static str, String>::new);
+
+    rsx! {
+        for spec in specs {
+            {
+                let current = values.read().value(&spec);
+                let error = errors.read().get(spec.name).cloned();
+                let name = spec.name;
+                rsx! {
+                    label {
+                        key: "{name}",
+                        "{name}: "
+                        if spec.kind == ParamKind::Bool {
+                            input {
+                                r#type: "checkbox",
+                                name,
+                                checked: current != 0.0,
+                                onchange: move |event| {
+                                    values.write().set(name, if event.checked() { 1.0 } else { 0.0 });
+                                },
+                            }
+                        } else {
+                            input {
+                                r#type: "number",
+                                name,
+                                placeholder: name,
+                                value: "{current}",
+                                min: spec.min.map(|min| min.to_string()),
+                                max: spec.max.map(|max| max.to_string()),
+                                step: spec.step.map_or("any".to_string(), |step| step.to_string()),
+                                onchange: {
+                                    let spec = spec.clone();
+                                    move |event: Event<FormData>| match spec.parse(&event.value()) {
+                                        Ok(value) => {
+                                            errors.write().remove(spec.name);
+                                            values.write().set(spec.name, value);
+                                        }
+                                        Err(err) => {
+                                            errors.write().insert(spec.name, err.to_string());
+                                        }
+                                    }
+                                },
+                            }
+                        }
+                        if let Some(error) = error {
+                            span { class: "text-red-600 text-sm", " {error}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
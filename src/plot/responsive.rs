@@ -0,0 +1,104 @@
+use dioxus::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::js_sys::Array;
+use web_sys::{ResizeObserver, ResizeObserverEntry};
+
+type ResizeCallback = Closure<dyn FnMut(Array)>;
+
+// Size a chart is drawn at in CSS pixels, and the device pixels per CSS
+// pixel of the screen (2 on most retina displays)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartSize {
+    pub width: f64,
+    pub height: f64,
+    pub pixel_ratio: f64,
+}
+
+impl ChartSize {
+    pub fn new(width: f64, height: f64) -> Self {
+        ChartSize {
+            width,
+            height,
+            pixel_ratio: device_pixel_ratio(),
+        }
+    }
+
+    // Resolution of a canvas that is sharp at this size
+    pub fn device_width(&self) -> u32 {
+        (self.width * self.pixel_ratio).round() as u32
+    }
+
+    pub fn device_height(&self) -> u32 {
+        (self.height * self.pixel_ratio).round() as u32
+    }
+
+    // width x height scaled down to fit within available CSS pixels of width,
+    // keeping the aspect ratio. Never scaled up.
+    fn fit(width: f64, height: f64, available: f64) -> Self {
+        let scale = if available > 0.0 && width > 0.0 {
+            (available / width).min(1.0)
+        } else {
+            1.0
+        };
+        ChartSize::new(width * scale, height * scale)
+    }
+}
+
+// Device pixels per CSS pixel, 1 outside a browser
+pub fn device_pixel_ratio() -> f64 {
+    web_sys::window()
+        .map(|window| window.device_pixel_ratio())
+        .filter(|ratio| *ratio > 0.0)
+        .unwrap_or(1.0)
+}
+
+// Size of a chart that is width x height when its container leaves room,
+// and shrinks with the container otherwise. Updated whenever the element
+// with container_id is resized.
+pub fn use_chart_size(container_id: String, width: f64, height: f64) -> Signal<ChartSize> {
+    let mut size = use_signal(|| ChartSize::new(width, height));
+    // The observer and its callback live as long as the component
+    let observer = use_hook(|| Rc::new(RefCell::new(None::<(ResizeObserver, ResizeCallback)>)));
+
+    use_effect({
+        let observer = observer.clone();
+        move || {
+            if observer.borrow().is_some() {
+                return;
+            }
+            let Some(element) = web_sys::window()
+                .and_then(|window| window.document())
+                .and_then(|document| document.get_element_by_id(&container_id))
+            else {
+                return;
+            };
+            let callback = Closure::<dyn FnMut(Array)>::new(move |entries: Array| {
+                let Ok(entry) = entries.get(0).dyn_into::<ResizeObserverEntry>() else {
+                    return;
+                };
+                // Less the 1px border around chart canvases
+                let available = entry.content_rect().width() - 2.0;
+                let fitted = ChartSize::fit(width, height, available);
+                if *size.peek() != fitted {
+                    size.set(fitted);
+                }
+            });
+            let Ok(resize_observer) = ResizeObserver::new(callback.as_ref().unchecked_ref()) else {
+                return;
+            };
+            resize_observer.observe(&element);
+            *observer.borrow_mut() = Some((resize_observer, callback));
+        }
+    });
+
+    use_drop(move || {
+        if let Some((resize_observer, _)) = observer.borrow_mut().take() {
+            resize_observer.disconnect();
+        }
+    });
+
+    size
+}
@@ -0,0 +1,290 @@
+use std::f64;
+
+// Prefixes for SI formatting, from 10^-12 to 10^12 in steps of 1000
+const SI_PREFIXES: [&str; 9] = ["p", "n", "µ", "m", "", "k", "M", "G", "T"];
+// Auto formatting switches to scientific notation outside this range of
+// magnitudes
+const SCIENTIFIC_BELOW: f64 = 1e-3;
+const SCIENTIFIC_FROM: f64 = 1e6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scale {
+    #[default]
+    Linear,
+    // Base 10. Zero and negative values have no position and are not drawn.
+    Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    // Fixed decimals for everyday magnitudes, scientific otherwise
+    #[default]
+    Auto,
+    // This many decimals
+    Fixed(usize),
+    // 1.5e6
+    Scientific,
+    // 1.5M, 20µ
+    Si,
+}
+
+// Values an axis spans and how they map onto it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisRange {
+    pub min: f64,
+    pub max: f64,
+    pub scale: Scale,
+}
+
+impl Default for AxisRange {
+    fn default() -> Self {
+        AxisRange::new(0.0, 1.0)
+    }
+}
+
+impl AxisRange {
+    pub fn new(min: f64, max: f64) -> Self {
+        AxisRange {
+            min,
+            max,
+            scale: Scale::Linear,
+        }
+    }
+
+    // Smallest to largest of the values that have a position on the scale.
+    // A single distinct value is widened to a range around it, and no values
+    // give the default range.
+    pub fn from_values(values: impl IntoIterator<Item = f64>, scale: Scale) -> Self {
+        let (min, max) = values
+            .into_iter()
+            .filter(|&v| scale.contains(v))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+        let (min, max) = if !min.is_finite() {
+            match scale {
+                Scale::Linear => (0.0, 1.0),
+                Scale::Log => (1.0, 10.0),
+            }
+        } else if max > min {
+            (min, max)
+        } else {
+            match scale {
+                Scale::Linear => (min - 0.5, max + 0.5),
+                Scale::Log => (min / 2.0, max * 2.0),
+            }
+        };
+        AxisRange { min, max, scale }
+    }
+
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    // Position of value along the axis, 0 at min and 1 at max. NaN for
+    // values without a position on the scale.
+    pub fn fraction(&self, value: f64) -> f64 {
+        match self.scale {
+            Scale::Linear => (value - self.min) / (self.max - self.min),
+            Scale::Log if value > 0.0 => {
+                (value.log10() - self.min.log10()) / (self.max.log10() - self.min.log10())
+            }
+            Scale::Log => f64::NAN,
+        }
+    }
+
+    // Value at a position along the axis, the inverse of fraction
+    pub fn value_at(&self, fraction: f64) -> f64 {
+        match self.scale {
+            Scale::Linear => self.min + fraction * (self.max - self.min),
+            Scale::Log => {
+                let (low, high) = (self.min.log10(), self.max.log10());
+                10f64.powf(low + fraction * (high - low))
+            }
+        }
+    }
+
+    // factor times narrower (wider for factors below 1) around the value at
+    // anchor, a position along the axis, which stays where it is
+    pub fn zoomed(&self, factor: f64, anchor: f64) -> Self {
+        if factor <= 0.0 || !factor.is_finite() {
+            return *self;
+        }
+        AxisRange {
+            min: self.value_at(anchor - anchor / factor),
+            max: self.value_at(anchor + (1.0 - anchor) / factor),
+            scale: self.scale,
+        }
+    }
+
+    // Moved by shift axis lengths, towards larger values when positive
+    pub fn panned(&self, shift: f64) -> Self {
+        AxisRange {
+            min: self.value_at(shift),
+            max: self.value_at(1.0 + shift),
+            scale: self.scale,
+        }
+    }
+
+    // Tick values within the range, at most about max_count of them
+    pub fn ticks(&self, max_count: usize) -> Vec<f64> {
+        match self.scale {
+            Scale::Linear => nice_ticks(self.min, self.max, max_count),
+            Scale::Log => log_ticks(self.min, self.max, max_count),
+        }
+    }
+
+    // Labels for ticks, with as many decimals as their spacing needs
+    pub fn tick_labels(&self, ticks: &[f64], format: NumberFormat) -> Vec<String> {
+        let step = match ticks {
+            [first, second, ..] => (second - first).abs(),
+            _ => (self.max - self.min).abs(),
+        };
+        // Log ticks differ in magnitude, so every tick is its own step,
+        // unless a narrow range fell back to evenly spaced ticks
+        let evenly_spaced = ticks
+            .windows(2)
+            .all(|pair| ((pair[1] - pair[0]) - step).abs() <= step * 1e-6);
+        ticks
+            .iter()
+            .map(|&tick| match self.scale {
+                Scale::Log if !evenly_spaced => format_number(tick, tick, format),
+                _ => format_number(tick, step, format),
+            })
+            .collect()
+    }
+}
+
+impl Scale {
+    // Whether value has a position on the scale
+    pub fn contains(self, value: f64) -> bool {
+        match self {
+            Scale::Linear => value.is_finite(),
+            Scale::Log => value.is_finite() && value > 0.0,
+        }
+    }
+}
+
+// Multiples of a 1, 2 or 5 x 10^n step between min and max (Heckbert,
+// "Nice numbers for graph labels", 1990), at most max_count of them
+pub fn nice_ticks(min: f64, max: f64, max_count: usize) -> Vec<f64> {
+    if !min.is_finite() || !max.is_finite() {
+        return vec![];
+    }
+    if max <= min {
+        return vec![min];
+    }
+    let step = nice_step((max - min) / max_count.saturating_sub(1).max(1) as f64);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+// Smallest 1, 2 or 5 x 10^n at least as large as step
+pub fn nice_step(step: f64) -> f64 {
+    let magnitude = 10f64.powf(step.log10().floor());
+    let fraction = step / magnitude;
+    let nice = if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+// Powers of ten between min and max, thinned to every few decades over
+// wide ranges. Under two decades the 2 and 5 multiples are added, and
+// within a single decade the ticks are linear.
+pub fn log_ticks(min: f64, max: f64, max_count: usize) -> Vec<f64> {
+    if !(min.is_finite() && max.is_finite() && min > 0.0 && max > min) {
+        return nice_ticks(min, max, max_count);
+    }
+    let low = min.log10().ceil() as i32;
+    let high = max.log10().floor() as i32;
+    let decades = (high - low + 1).max(0) as usize;
+    let stride = decades.div_ceil(max_count.max(1)).max(1);
+    let powers: Vec<f64> = (low..=high)
+        .step_by(stride)
+        .map(|e| 10f64.powi(e))
+        .collect();
+    if powers.len() >= 3 {
+        return powers;
+    }
+
+    let multiples: Vec<f64> = (low - 1..=high)
+        .flat_map(|e| [1.0, 2.0, 5.0].map(|m| m * 10f64.powi(e)))
+        .filter(|&v| v >= min && v <= max)
+        .collect();
+    if multiples.len() >= 2 {
+        multiples
+    } else {
+        nice_ticks(min, max, max_count)
+    }
+}
+
+// value written with enough precision to tell apart numbers step apart
+pub fn format_number(value: f64, step: f64, format: NumberFormat) -> String {
+    // Snap rounding noise such as 0.30000000000000004 - 0.3 to zero
+    let value = if value.abs() < step.abs() * 1e-9 {
+        0.0
+    } else {
+        value
+    };
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    match format {
+        NumberFormat::Fixed(decimals) => format!("{:.*}", decimals, value),
+        NumberFormat::Scientific => format_scientific(value, step),
+        NumberFormat::Si => format_si(value, step),
+        NumberFormat::Auto => {
+            let magnitude = value.abs();
+            if magnitude != 0.0 && !(SCIENTIFIC_BELOW..SCIENTIFIC_FROM).contains(&magnitude) {
+                format_scientific(value, step)
+            } else {
+                format!("{:.*}", decimals_for(step), value)
+            }
+        }
+    }
+}
+
+// Decimals needed to show multiples of step
+fn decimals_for(step: f64) -> usize {
+    if step > 0.0 && step.is_finite() {
+        (-step.log10().floor()).clamp(0.0, 12.0) as usize
+    } else {
+        0
+    }
+}
+
+fn format_scientific(value: f64, step: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let exponent = value.abs().log10().floor();
+    let digits = if step > 0.0 && step.is_finite() {
+        (exponent - step.log10().floor()).clamp(0.0, 12.0) as usize
+    } else {
+        0
+    };
+    format!("{:.*e}", digits, value)
+}
+
+fn format_si(value: f64, step: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let group = ((value.abs().log10() / 3.0).floor() as i32).clamp(-4, 4);
+    let scale = 1000f64.powi(group);
+    format!(
+        "{:.*}{}",
+        decimals_for(step / scale),
+        value / scale,
+        SI_PREFIXES[(group + 4) as usize]
+    )
+}
@@ -1,180 +1,898 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_axes, draw_legend, draw_marker, draw_title, LegendEntry, Swatch,
+    MARGIN,
+};
+use super::color::Palette;
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::linked::{linked_rows, select_rows, use_linked_selection};
+use super::responsive::{use_chart_size, ChartSize};
+use super::scale::{format_number, AxisRange, Scale};
+use super::theme::{themed, use_theme};
+use super::view::Viewport;
+use super::webgl::{webgl2_available, PointRenderer, Renderer};
+use crate::ml::geometry::{alpha_shape, convex_hull, voronoi_cells};
+use dioxus::html::geometry::WheelDelta;
 use dioxus::prelude::*;
+use log::error;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::f64;
-use wasm_bindgen::JsCast;
+use std::rc::Rc;
+use web_sys::js_sys::Array;
 use web_sys::CanvasRenderingContext2d;
 
+const POINT_SIZE: f64 = 5.0;
+const CENTROID_SIZE: f64 = 9.0;
+const LINE_WIDTH: f64 = 2.0;
+const REGION_ALPHA: f64 = 0.2;
+const REGION_BORDER: f64 = 1.0;
+const OUTLINE_WIDTH: f64 = 1.5;
+// Pixels per line for wheels that scroll by lines
+const LINE_HEIGHT: f64 = 16.0;
+// Pixels beyond a marker's edge that still count as hovering it
+const HIT_TOLERANCE: f64 = 3.0;
+const SELECTED_COLOR: &str = "black";
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
 pub trait Drawable {
-    fn draw(&self, context: &CanvasRenderingContext2d, width: f64, height: f64);
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    );
 }
 
-#[derive(Clone)]
-pub struct ScatterPlotData {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+    // A marker at every point
+    Points(MarkerShape),
+    // The points joined in order, NaN values leaving a gap
+    Line,
+    // The points as the corners of a filled polygon
+    Region,
+}
+
+// How dragging with Shift held selects points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    // Points within the rectangle spanned by the drag
+    #[default]
+    Box,
+    // Points within the freehand outline traced by the drag
+    Lasso,
+}
+
+// Shape drawn around each cluster by ScatterPlotData::with_outlines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outline {
+    ConvexHull,
+    // Boundary of the Delaunay triangles with circumradius at most this, in
+    // data units, following concave clusters. See geometry::alpha_shape.
+    AlphaShape(f64),
+}
+
+// A point of a point layer: layers[layer].x[index], layers[layer].y[index]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointRef {
+    pub layer: usize,
+    pub index: usize,
+}
+
+// One set of points drawn the same way
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    // Shown in the legend, unless empty
+    pub name: String,
+    pub kind: LayerKind,
     pub x: Vec<f64>,
     pub y: Vec<f64>,
+    // CSS color, the palette color at palette_index when None
+    pub color: Option<String>,
+    // Palette color the layer is drawn with without a color, the one at its
+    // position among the layers when None
+    pub palette_index: Option<usize>,
+    // Marker radius or line width in pixels
+    pub size: f64,
+    // Row of the linked dataset every point is, the point's index when None
+    pub rows: Option<Vec<usize>>,
 }
 
-impl Drawable for ScatterPlotData {
-    fn draw(&self, context: &CanvasRenderingContext2d, width: f64, height: f64) {
-        let margin = 50.0;
+// Layers over shared axes, drawn in order so later layers sit on top
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScatterPlotData {
+    pub layers: Vec<Layer>,
+}
 
-        // Determine data ranges
-        let x_min = self.x.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-        let x_max = self.x.iter().cloned().reduce(f64::max).unwrap_or(1.0);
-        let y_min = self.y.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-        let y_max = self.y.iter().cloned().reduce(f64::max).unwrap_or(1.0);
+impl Layer {
+    pub fn points(name: &str, x: Vec<f64>, y: Vec<f64>) -> Self {
+        Layer {
+            name: name.to_string(),
+            kind: LayerKind::Points(MarkerShape::Circle),
+            x,
+            y,
+            color: None,
+            palette_index: None,
+            size: POINT_SIZE,
+            rows: None,
+        }
+    }
 
-        // Draw axes
-        draw_axes(context, width, height, self.x.clone(), self.y.clone());
+    pub fn line(name: &str, x: Vec<f64>, y: Vec<f64>) -> Self {
+        Layer {
+            kind: LayerKind::Line,
+            size: LINE_WIDTH,
+            ..Layer::points(name, x, y)
+        }
+    }
 
-        // Draw data points
-        let x_scale = (width - 2.0 * margin) / (x_max - x_min);
-        let y_scale = (height - 2.0 * margin) / (y_max - y_min);
+    pub fn region(name: &str, x: Vec<f64>, y: Vec<f64>) -> Self {
+        Layer {
+            kind: LayerKind::Region,
+            size: LINE_WIDTH,
+            ..Layer::points(name, x, y)
+        }
+    }
 
-        for i in 0..self.x.len() {
-            let x_pos = margin + (self.x[i] - x_min) * x_scale;
-            let y_pos = height - margin - (self.y[i] - y_min) * y_scale;
+    // First two coordinates of every point
+    pub fn from_points(name: &str, points: &[Point]) -> Self {
+        let (x, y) = points
+            .iter()
+            .filter_map(|p| Some((*p.first()?, *p.get(1)?)))
+            .unzip();
+        Layer::points(name, x, y)
+    }
 
-            context.begin_path();
-            context
-                .arc(x_pos, y_pos, 5.0, 0.0, 2.0 * f64::consts::PI)
-                .unwrap_or_else(|_| ());
-            context.fill();
+    pub fn with_color(mut self, color: &str) -> Self {
+        self.color = Some(color.to_string());
+        self
+    }
+
+    // Drawn in the palette's i-th color wherever the layer sits, e.g. a
+    // cluster's region matching its points
+    pub fn with_palette_index(mut self, i: usize) -> Self {
+        self.palette_index = Some(i);
+        self
+    }
+
+    // Only changes point layers
+    pub fn with_shape(mut self, shape: MarkerShape) -> Self {
+        if let LayerKind::Points(current) = &mut self.kind {
+            *current = shape;
+        }
+        self
+    }
+
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+
+    // Points past the end of rows aren't rows of the dataset, e.g. none of
+    // a layer of centroids when rows is empty
+    pub fn with_rows(mut self, rows: Vec<usize>) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    // Row of the linked dataset the point at index is, if any
+    pub fn row(&self, index: usize) -> Option<usize> {
+        match &self.rows {
+            Some(rows) => rows.get(index).copied(),
+            None => Some(index),
         }
     }
 }
 
-#[component]
-pub fn ScatterPlot(x: Signal<Vec<f64>>, y: Signal<Vec<f64>>, width: f64, height: f64) -> Element {
-    let canvas_id = "scatterplot_canvas";
+impl ScatterPlotData {
+    // A single unnamed layer of points
+    pub fn new(x: Vec<f64>, y: Vec<f64>) -> Self {
+        ScatterPlotData {
+            layers: vec![Layer::points("", x, y)],
+        }
+    }
 
-    use_effect(move || {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let canvas = document.get_element_by_id(canvas_id).unwrap();
-        let canvas: web_sys::HtmlCanvasElement = canvas
-            .dyn_into::<web_sys::HtmlCanvasElement>()
-            .map_err(|_| ())
-            .unwrap();
-        let context = canvas
-            .get_context("2d")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<CanvasRenderingContext2d>()
-            .unwrap();
-
-        // Clear the canvas
-        clear_canvas(&context, width, height);
-
-        // Draw axes
-        draw_axes(&context, width, height, x.read().clone(), y.read().clone());
-
-        // Plot points
-        draw_points(&context, &x.read(), &y.read(), width, height);
-    });
+    // One layer of points per cluster, cluster i in the palette's i-th color,
+    // with the centroids marked by black crosses on top, e.g. the result of
+    // KMeans
+    pub fn from_clusters(points: &[Point], labels: &[usize], centroids: &[Point]) -> Self {
+        let n_clusters = labels
+            .iter()
+            .map(|&label| label + 1)
+            .max()
+            .unwrap_or(0)
+            .max(centroids.len());
+        let mut layers: Vec<Layer> = (0..n_clusters)
+            .map(|cluster| {
+                let (rows, members): (Vec<usize>, Vec<Point>) = points
+                    .iter()
+                    .zip(labels)
+                    .enumerate()
+                    .filter(|(_, (_, &label))| label == cluster)
+                    .map(|(row, (point, _))| (row, point.clone()))
+                    .unzip();
+                Layer::from_points(&format!("Cluster {}", cluster), &members)
+                    .with_rows(rows)
+                    .with_palette_index(cluster)
+            })
+            .collect();
+        if !centroids.is_empty() {
+            layers.push(
+                Layer::from_points("Centroids", centroids)
+                    .with_shape(MarkerShape::Cross)
+                    .with_color("black")
+                    .with_size(CENTROID_SIZE)
+                    .with_rows(vec![]),
+            );
+        }
+        ScatterPlotData { layers }
+    }
 
-    rsx! {
-        div {
-            canvas {
-                id: "{canvas_id}",
-                width: "{width}",
-                height: "{height}",
-                style: "border: 1px solid black;"
+    pub fn with_layer(mut self, layer: Layer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    // Shades the Voronoi cell of every centroid under the other layers,
+    // cell i in the palette's i-th color like cluster i of from_clusters.
+    // The cells span the data's extent, so that they don't change the view.
+    pub fn with_voronoi(mut self, centroids: &[Point]) -> Self {
+        let x = extent(self.layers.iter().flat_map(|l| l.x.iter().copied()));
+        let y = extent(self.layers.iter().flat_map(|l| l.y.iter().copied()));
+        let (Some(x), Some(y)) = (x, y) else {
+            return self;
+        };
+        let cells = voronoi_cells(centroids, x, y)
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let (x, y) = cell.into_iter().unzip();
+                Layer::region("", x, y)
+                    .with_palette_index(i)
+                    .with_size(REGION_BORDER)
+            });
+        self.layers.splice(0..0, cells);
+        self
+    }
+
+    // Outlines every cluster of points on top of the other layers, cluster
+    // i in the palette's i-th color like in from_clusters, so that clusters
+    // stay apart where their points overlap
+    pub fn with_outlines(mut self, points: &[Point], labels: &[usize], outline: Outline) -> Self {
+        let n_clusters = labels.iter().map(|&label| label + 1).max().unwrap_or(0);
+        for cluster in 0..n_clusters {
+            let members: Vec<Point> = points
+                .iter()
+                .zip(labels)
+                .filter(|(_, &label)| label == cluster)
+                .map(|(point, _)| point.clone())
+                .collect();
+            // Closed polygons, or edges apart from each other by a NaN gap
+            let path: Vec<(f64, f64)> = match outline {
+                Outline::ConvexHull => {
+                    let hull = convex_hull(&members);
+                    hull.iter().chain(hull.first()).copied().collect()
+                }
+                Outline::AlphaShape(radius) => alpha_shape(&members, radius)
+                    .into_iter()
+                    .flat_map(|(a, b)| [a, b, (f64::NAN, f64::NAN)])
+                    .collect(),
+            };
+            let (x, y) = path.into_iter().unzip();
+            self.layers.push(
+                Layer::line("", x, y)
+                    .with_palette_index(cluster)
+                    .with_size(OUTLINE_WIDTH),
+            );
+        }
+        self
+    }
+
+    // Color every layer is drawn with
+    pub(crate) fn colors(&self, palette: Palette) -> Vec<&str> {
+        self.layers
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                l.color
+                    .as_deref()
+                    .unwrap_or(palette.color(l.palette_index.unwrap_or(i)))
+            })
+            .collect()
+    }
+
+    fn legend_entries(&self, palette: Palette) -> Vec<LegendEntry> {
+        self.layers
+            .iter()
+            .zip(self.colors(palette))
+            .filter(|(layer, _)| !layer.name.is_empty())
+            .map(|(layer, color)| LegendEntry {
+                label: layer.name.clone(),
+                color: color.to_string(),
+                swatch: match layer.kind {
+                    LayerKind::Points(shape) => Swatch::Marker(shape),
+                    LayerKind::Line => Swatch::Line,
+                    LayerKind::Region => Swatch::Fill,
+                },
+            })
+            .collect()
+    }
+}
+
+impl Drawable for ScatterPlotData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        let view = self.viewport(config.x_scale, config.y_scale);
+        self.draw_viewport(context, width, height, config, &view);
+    }
+}
+
+impl ScatterPlotData {
+    // Range of the data over every layer, on the given scales
+    pub fn viewport(&self, x_scale: Scale, y_scale: Scale) -> Viewport {
+        Viewport::new(
+            AxisRange::from_values(
+                self.layers.iter().flat_map(|l| l.x.iter().copied()),
+                x_scale,
+            ),
+            AxisRange::from_values(
+                self.layers.iter().flat_map(|l| l.y.iter().copied()),
+                y_scale,
+            ),
+        )
+    }
+
+    // Draws the part of the data within view, e.g. after zooming in
+    pub fn draw_viewport(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+        view: &Viewport,
+    ) {
+        self.draw_layers(context, width, height, config, view, true);
+    }
+
+    // Markers in point layers, which the WebGL renderer draws instead of
+    // the 2D canvas for large plots
+    fn n_markers(&self) -> usize {
+        self.layers
+            .iter()
+            .filter(|layer| matches!(layer.kind, LayerKind::Points(_)))
+            .map(|layer| layer.x.len().min(layer.y.len()))
+            .sum()
+    }
+
+    // draw_viewport, leaving out the markers of point layers unless
+    // with_markers is set
+    fn draw_layers(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+        view: &Viewport,
+        with_markers: bool,
+    ) {
+        let margin = MARGIN;
+        draw_axes(context, width, height, view.x, view.y, config);
+
+        // NaN for values without a position, such as 0 on a log scale
+        let to_canvas = |x: f64, y: f64| view.to_canvas(x, y, width, height);
+
+        // Nothing outside the plot area, where zoomed out data would cover
+        // the axes
+        context.save();
+        context.begin_path();
+        context.rect(margin, margin, width - 2.0 * margin, height - 2.0 * margin);
+        context.clip();
+        for (layer, color) in self.layers.iter().zip(self.colors(config.palette)) {
+            context.set_stroke_style_str(color);
+            context.set_fill_style_str(color);
+            let positions = layer
+                .x
+                .iter()
+                .zip(&layer.y)
+                .map(|(&x, &y)| to_canvas(x, y))
+                .map(|(x_pos, y_pos)| {
+                    (!x_pos.is_nan() && !y_pos.is_nan()).then_some((x_pos, y_pos))
+                });
+
+            match layer.kind {
+                LayerKind::Points(_) if !with_markers => {}
+                LayerKind::Points(shape) => {
+                    context.set_line_width(2.0);
+                    for (x_pos, y_pos) in positions.flatten() {
+                        draw_marker(context, shape, x_pos, y_pos, layer.size);
+                    }
+                }
+                LayerKind::Line => {
+                    context.set_line_width(layer.size);
+                    context.begin_path();
+                    let mut pen_down = false;
+                    for position in positions {
+                        match position {
+                            Some((x_pos, y_pos)) if pen_down => context.line_to(x_pos, y_pos),
+                            Some((x_pos, y_pos)) => {
+                                context.move_to(x_pos, y_pos);
+                                pen_down = true;
+                            }
+                            None => pen_down = false,
+                        }
+                    }
+                    context.stroke();
+                }
+                LayerKind::Region => {
+                    context.set_line_width(layer.size);
+                    context.begin_path();
+                    for (x_pos, y_pos) in positions.flatten() {
+                        context.line_to(x_pos, y_pos);
+                    }
+                    context.close_path();
+                    context.set_global_alpha(REGION_ALPHA);
+                    context.fill();
+                    context.set_global_alpha(1.0);
+                    context.stroke();
+                }
             }
         }
+        context.restore();
+
+        draw_legend(
+            context,
+            width,
+            height,
+            &self.legend_entries(config.palette),
+            config,
+        );
+        draw_title(context, width, config);
     }
 }
 
-fn clear_canvas(context: &CanvasRenderingContext2d, width: f64, height: f64) {
-    context.clear_rect(0.0, 0.0, width, height);
+impl ScatterPlotData {
+    // Coordinates of a point, None when it doesn't exist
+    pub fn point(&self, point: PointRef) -> Option<(f64, f64)> {
+        let layer = self.layers.get(point.layer)?;
+        Some((*layer.x.get(point.index)?, *layer.y.get(point.index)?))
+    }
+
+    // Point whose marker covers the canvas pixel (px, py), the nearest one
+    // when several do
+    pub fn hit_test(
+        &self,
+        view: &Viewport,
+        width: f64,
+        height: f64,
+        px: f64,
+        py: f64,
+    ) -> Option<PointRef> {
+        self.point_positions(view, width, height)
+            .filter_map(|(point, (x, y), size)| {
+                let distance = (x - px).hypot(y - py);
+                (distance <= size + HIT_TOLERANCE).then_some((point, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(point, _)| point)
+    }
+
+    // Points of point layers within a polygon of canvas pixels
+    pub fn select(
+        &self,
+        view: &Viewport,
+        width: f64,
+        height: f64,
+        polygon: &[(f64, f64)],
+    ) -> Vec<PointRef> {
+        self.point_positions(view, width, height)
+            .filter(|&(_, position, _)| polygon_contains(polygon, position))
+            .map(|(point, _, _)| point)
+            .collect()
+    }
+
+    // Rows of the linked dataset the points are
+    pub fn rows_of(&self, points: &[PointRef]) -> BTreeSet<usize> {
+        points
+            .iter()
+            .filter_map(|point| self.layers.get(point.layer)?.row(point.index))
+            .collect()
+    }
+
+    // Points of point layers that are any of rows
+    pub fn points_of_rows(&self, rows: &BTreeSet<usize>) -> Vec<PointRef> {
+        self.layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| matches!(layer.kind, LayerKind::Points(_)))
+            .flat_map(|(l, layer)| {
+                (0..layer.x.len().min(layer.y.len()))
+                    .filter(|&index| layer.row(index).is_some_and(|row| rows.contains(&row)))
+                    .map(move |index| PointRef { layer: l, index })
+            })
+            .collect()
+    }
+
+    // Every drawn point of the point layers with its canvas pixel and marker
+    // size
+    fn point_positions<'a>(
+        &'a self,
+        view: &'a Viewport,
+        width: f64,
+        height: f64,
+    ) -> impl Iterator<Item = (PointRef, (f64, f64), f64)> + 'a {
+        self.layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| matches!(layer.kind, LayerKind::Points(_)))
+            .flat_map(move |(l, layer)| {
+                layer
+                    .x
+                    .iter()
+                    .zip(&layer.y)
+                    .enumerate()
+                    .map(move |(index, (&x, &y))| {
+                        let position = view.to_canvas(x, y, width, height);
+                        (PointRef { layer: l, index }, position, layer.size)
+                    })
+            })
+            .filter(|(_, (x, y), _)| !x.is_nan() && !y.is_nan())
+    }
+
+    // Rings around the selected points
+    fn draw_selected(
+        &self,
+        context: &CanvasRenderingContext2d,
+        view: &Viewport,
+        width: f64,
+        height: f64,
+        selected: &[PointRef],
+    ) {
+        context.set_stroke_style_str(SELECTED_COLOR);
+        context.set_line_width(1.5);
+        for &point in selected {
+            let (Some((x, y)), Some(layer)) = (self.point(point), self.layers.get(point.layer))
+            else {
+                continue;
+            };
+            let (px, py) = view.to_canvas(x, y, width, height);
+            context.begin_path();
+            context
+                .arc(px, py, layer.size + 2.0, 0.0, 2.0 * f64::consts::PI)
+                .unwrap_or(());
+            context.stroke();
+        }
+    }
 }
-fn draw_axes(
-    context: &CanvasRenderingContext2d,
+
+// Scatter plot of one or more layers, redrawn whenever data changes. The
+// mouse wheel zooms around the pointer, dragging pans and a double click
+// shows all of the data again. Hovering a point shows its layer and
+// coordinates, and dragging with Shift held selects the points in a box or
+// lasso, passing them to on_select. A Shift click clears the selection.
+// Linked to a dataset, the selection is shared with the other charts linked
+// to it inside a LinkedSelectionProvider. Markers of large plots are drawn with WebGL, see Renderer.
+#[component]
+pub fn ScatterPlot(
+    data: Signal<ScatterPlotData>,
     width: f64,
     height: f64,
-    x: Vec<f64>,
-    y: Vec<f64>,
-) {
-    let margin = 50.0;
-
-    // Determine data ranges
-    let x_min = x.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-    let x_max = x.iter().cloned().reduce(f64::max).unwrap_or(1.0);
-    let y_min = y.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-    let y_max = y.iter().cloned().reduce(f64::max).unwrap_or(1.0);
-
-    // Draw x-axis
-    context.set_line_width(2.0);
-    context.begin_path();
-    context.move_to(margin, height - margin);
-    context.line_to(width - margin, height - margin);
-    context.stroke();
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+    #[props(default)] selection: SelectionMode,
+    #[props(default)] on_select: EventHandler<Vec<PointRef>>,
+    #[props(default)] renderer: Renderer,
+    // Dataset the points are rows of, see Layer::rows
+    #[props(default)] linked: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let points_id = format!("{}-points", id);
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+    let (x_scale, y_scale) = (config.x_scale, config.y_scale);
+    let number_format = config.number_format;
+    // Visible region, all of the data while None
+    let mut view = use_signal(|| None::<Viewport>);
+    // Last pointer position while dragging
+    let mut drag_from = use_signal(|| None::<(f64, f64)>);
+    // Pointer path while selecting, the corners of the box or the lasso
+    let mut selecting = use_signal(|| None::<Vec<(f64, f64)>>);
+    let mut selected = use_signal(Vec::<PointRef>::new);
+    let linked_selection = use_linked_selection();
+    let dataset = linked.clone();
+    // Point under the pointer with the pointer's position
+    let mut hovered = use_signal(|| None::<(PointRef, f64, f64)>);
+    // Markers of large plots are drawn with WebGL on a canvas under the one
+    // with the axes, and uploaded to it whenever the data changes
+    let webgl_available = use_hook(webgl2_available);
+    let uses_webgl =
+        move |data: &ScatterPlotData| webgl_available && renderer.uses_webgl(data.n_markers());
+    let point_renderer = use_hook(|| Rc::new(RefCell::new(None::<PointRenderer>)));
+    let mut uploads = use_signal(|| 0_usize);
+    let palette = config.palette;
+    let points_background = themed(&config, theme)
+        .background
+        .clone()
+        .unwrap_or_else(|| "transparent".to_string());
 
-    // Draw y-axis
-    context.begin_path();
-    context.move_to(margin, margin);
-    context.line_to(margin, height - margin);
-    context.stroke();
+    // Drawn size, smaller than asked for in narrow containers
+    let ChartSize { width, height, .. } = size();
+    let plot_width = width - 2.0 * MARGIN;
+    let plot_height = height - 2.0 * MARGIN;
+    let current_view = move || view().unwrap_or_else(|| data.read().viewport(x_scale, y_scale));
 
-    // Add x-axis ticks
-    let tick_count = 10;
-    let x_range = x_max - x_min;
-    let y_range = y_max - y_min;
+    // Selected indices mean nothing once the data changes
+    use_effect(move || {
+        data.read();
+        selected.set(vec![]);
+    });
 
-    for i in 0..=tick_count {
-        let t = i as f64 / tick_count as f64;
-        let x_val = x_min + t * x_range;
-        let x_pos = margin + t * (width - 2.0 * margin);
+    use_effect({
+        let point_renderer = point_renderer.clone();
+        let points_id = points_id.clone();
+        move || {
+            let data = data.read();
+            if !uses_webgl(&data) {
+                return;
+            }
+            let mut point_renderer = point_renderer.borrow_mut();
+            if point_renderer.is_none() {
+                match PointRenderer::new(&points_id) {
+                    Ok(created) => *point_renderer = Some(created),
+                    Err(err) => {
+                        error!("Error creating the WebGL renderer: {}", err);
+                        return;
+                    }
+                }
+            }
+            if let Some(point_renderer) = point_renderer.as_mut() {
+                let palette = theme.map_or(palette, |theme| theme.read().palette);
+                point_renderer.upload(&data, palette, x_scale, y_scale);
+            }
+            *uploads.write() += 1;
+        }
+    });
 
-        context.begin_path();
-        context.move_to(x_pos, height - margin);
-        context.line_to(x_pos, height - margin + 5.0);
-        context.stroke();
-
-        context.set_font("10px sans-serif");
-        context
-            .fill_text(
-                &format!("{:.1}", x_val),
-                x_pos - 10.0,
-                height - margin + 20.0,
-            )
-            .unwrap_or_else(|_| ());
-    }
-
-    // Add y-axis ticks
-    for i in 0..=tick_count {
-        let t = i as f64 / tick_count as f64;
-        let y_val = y_min + t * y_range;
-        let y_pos = height - margin - t * (height - 2.0 * margin);
+    use_effect({
+        let point_renderer = point_renderer.clone();
+        move || {
+            let Some(context) = context_2d(&canvas_id) else {
+                return;
+            };
+            let size = size();
+            let (width, height) = (size.width, size.height);
+            let view = current_view();
+            let data = data.read();
+            let config = themed(&config, theme);
 
-        context.begin_path();
-        context.move_to(margin - 5.0, y_pos);
-        context.line_to(margin, y_pos);
-        context.stroke();
+            // Plot layers in the current view, then the selection on top.
+            // Redrawn once the points are uploaded, the 2D canvas drawing
+            // the markers until then.
+            uploads.read();
+            let point_renderer = point_renderer.borrow();
+            if let (true, Some(point_renderer)) = (uses_webgl(&data), point_renderer.as_ref()) {
+                point_renderer.draw(&view, size);
+                // The background is behind the markers, on the WebGL canvas
+                let overlay = PlotConfig {
+                    background: None,
+                    ..config.clone()
+                };
+                clear_canvas(&context, size, &overlay);
+                data.draw_layers(&context, width, height, &overlay, &view, false);
+            } else {
+                clear_canvas(&context, size, &config);
+                data.draw_viewport(&context, width, height, &config, &view);
+            }
+            let selected = match linked_rows(linked_selection, dataset.as_deref()) {
+                Some(rows) => data.points_of_rows(&rows),
+                None => selected.read().clone(),
+            };
+            data.draw_selected(&context, &view, width, height, &selected);
+            if let Some(path) = selecting.read().as_deref() {
+                draw_selection_path(&context, path);
+            }
+        }
+    });
+
+    let tooltip = hovered().and_then(|(point, px, py)| {
+        let data = data.read();
+        let (x, y) = data.point(point)?;
+        let view = current_view();
+        let name = &data.layers.get(point.layer)?.name;
+        // Enough digits to tell apart points a pixel apart
+        let x_step = (view.x.max - view.x.min).abs() / plot_width;
+        let y_step = (view.y.max - view.y.min).abs() / plot_height;
+        let text = format!(
+            "{}{}x: {}\ny: {}",
+            name,
+            if name.is_empty() { "" } else { "\n" },
+            format_number(x, x_step, number_format),
+            format_number(y, y_step, number_format)
+        );
+        Some((text, px + 12.0, py + 12.0))
+    });
+
+    // Inside the border of the canvas on top
+    let points_style = if uses_webgl(&data.read()) {
+        format!(
+            "position: absolute; left: 1px; top: 1px; width: {}px; height: {}px; background: {};",
+            width, height, points_background
+        )
+    } else {
+        "display: none;".to_string()
+    };
 
-        context
-            .fill_text(&format!("{:.1}", y_val), margin - 30.0, y_pos + 3.0)
-            .unwrap_or_else(|_| ());
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            div {
+                style: "position: relative; display: inline-block;",
+                canvas {
+                    id: "{points_id}",
+                    width: "{size().device_width()}",
+                    height: "{size().device_height()}",
+                    style: "{points_style}",
+                }
+                canvas {
+                    id: "{id}",
+                    width: "{size().device_width()}",
+                    height: "{size().device_height()}",
+                    style: "position: relative; width: {width}px; height: {height}px; border: 1px solid black; cursor: grab; touch-action: none;",
+                    onwheel: move |event| {
+                        event.prevent_default();
+                        let delta = match event.delta() {
+                            WheelDelta::Pixels(delta) => delta.y,
+                            WheelDelta::Lines(delta) => delta.y * LINE_HEIGHT,
+                            WheelDelta::Pages(delta) => delta.y * plot_height,
+                        };
+                        let point = event.element_coordinates();
+                        let (fx, fy) = Viewport::plot_position(point.x, point.y, width, height);
+                        let zoomed = current_view().wheel_zoomed(delta, fx.clamp(0.0, 1.0), fy.clamp(0.0, 1.0));
+                        view.set(Some(zoomed));
+                    },
+                    onmousedown: move |event| {
+                        let point = event.element_coordinates();
+                        hovered.set(None);
+                        if event.modifiers().shift() {
+                            selecting.set(Some(vec![(point.x, point.y)]));
+                        } else {
+                            drag_from.set(Some((point.x, point.y)));
+                        }
+                    },
+                    onmousemove: move |event| {
+                        let point = event.element_coordinates();
+                        if let Some(path) = selecting.write().as_mut() {
+                            match selection {
+                                SelectionMode::Box => {
+                                    path.truncate(1);
+                                    path.push((point.x, point.y));
+                                }
+                                SelectionMode::Lasso => path.push((point.x, point.y)),
+                            }
+                            return;
+                        }
+                        let Some((x, y)) = drag_from() else {
+                            let hit = data
+                                .read()
+                                .hit_test(&current_view(), width, height, point.x, point.y)
+                                .map(|hit| (hit, point.x, point.y));
+                            if *hovered.peek() != hit {
+                                hovered.set(hit);
+                            }
+                            return;
+                        };
+                        drag_from.set(Some((point.x, point.y)));
+                        // The data follows the pointer, so the view moves the
+                        // other way
+                        let dx = (x - point.x) / plot_width;
+                        let dy = (point.y - y) / plot_height;
+                        view.set(Some(current_view().panned(dx, dy)));
+                    },
+                    onmouseup: move |_| {
+                        drag_from.set(None);
+                        let Some(path) = selecting.take() else {
+                            return;
+                        };
+                        let polygon = match (selection, path.as_slice()) {
+                            (SelectionMode::Box, &[(x0, y0), (x1, y1)]) => {
+                                vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+                            }
+                            _ => path,
+                        };
+                        let points = data.read().select(&current_view(), width, height, &polygon);
+                        let rows = data.read().rows_of(&points);
+                        select_rows(linked_selection, linked.as_deref(), rows);
+                        selected.set(points.clone());
+                        on_select.call(points);
+                    },
+                    onmouseleave: move |_| {
+                        drag_from.set(None);
+                        selecting.set(None);
+                        hovered.set(None);
+                    },
+                    ondoubleclick: move |_| view.set(None),
+                }
+                if let Some((text, left, top)) = tooltip {
+                    div {
+                        style: "position: absolute; left: {left}px; top: {top}px; pointer-events: none; white-space: pre; background: rgba(255, 255, 255, 0.9); border: 1px solid #999; border-radius: 3px; padding: 2px 6px; font: 12px sans-serif;",
+                        "{text}"
+                    }
+                }
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
     }
 }
-fn draw_points(context: &CanvasRenderingContext2d, x: &[f64], y: &[f64], width: f64, height: f64) {
-    let margin = 50.0;
-
-    // Determine data ranges
-    let x_min = x.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-    let x_max = x.iter().cloned().reduce(f64::max).unwrap_or(1.0);
-    let y_min = y.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-    let y_max = y.iter().cloned().reduce(f64::max).unwrap_or(1.0);
 
-    let x_scale = (width - 2.0 * margin) / (x_max - x_min);
-    let y_scale = (height - 2.0 * margin) / (y_max - y_min);
+// Smallest and largest finite value, None without any
+fn extent(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    values
+        .filter(|v| v.is_finite())
+        .fold(None, |range, v| match range {
+            Some((min, max)) => Some((v.min(min), v.max(max))),
+            None => Some((v, v)),
+        })
+}
 
-    for i in 0..x.len() {
-        let x_pos = margin + (x[i] - x_min) * x_scale;
-        let y_pos = height - margin - (y[i] - y_min) * y_scale;
+// Outline of the box or lasso being dragged
+fn draw_selection_path(context: &CanvasRenderingContext2d, path: &[(f64, f64)]) {
+    context.set_stroke_style_str(SELECTED_COLOR);
+    context.set_fill_style_str("rgba(0, 0, 0, 0.08)");
+    context.set_line_width(1.0);
+    context
+        .set_line_dash(&Array::of2(&4.0.into(), &3.0.into()))
+        .unwrap_or(());
+    context.begin_path();
+    match path {
+        [(x0, y0), (x1, y1)] => context.rect(*x0, *y0, x1 - x0, y1 - y0),
+        _ => {
+            for &(x, y) in path {
+                context.line_to(x, y);
+            }
+            context.close_path();
+        }
+    }
+    context.fill();
+    context.stroke();
+    context.set_line_dash(&Array::new()).unwrap_or(());
+}
 
-        context.begin_path();
-        context
-            .arc(x_pos, y_pos, 5.0, 0.0, 2.0 * f64::consts::PI)
-            .unwrap_or_else(|_| ());
-        context.fill();
+// Even-odd ray casting: whether a horizontal ray from the point crosses the
+// polygon's edges an odd number of times
+fn polygon_contains(polygon: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &(xi, yi) in polygon {
+        let (xj, yj) = previous;
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        previous = (xi, yi);
     }
+    inside
 }
@@ -0,0 +1,383 @@
+use super::canvas::{
+    clear_canvas, context_2d, draw_legend, draw_marker, draw_title, font, LegendEntry, Swatch,
+};
+use super::config::PlotConfig;
+use super::export::ExportToolbar;
+use super::linked::{linked_rows, use_linked_selection};
+use super::responsive::use_chart_size;
+use super::scale::{AxisRange, Scale};
+use super::scatter::{Drawable, MarkerShape};
+use super::theme::{themed, use_theme};
+use dioxus::html::geometry::WheelDelta;
+use dioxus::prelude::*;
+use std::collections::BTreeSet;
+use std::f64;
+use web_sys::CanvasRenderingContext2d;
+
+// Point in n-dimensional space
+type Point = Vec<f64>;
+
+const MARKER_RADIUS: f64 = 3.0;
+const MARKER_OPACITY: f64 = 0.8;
+// Distance of the eye from the center of the data cube, in half cube
+// widths. Nearer points are drawn larger.
+const EYE_DISTANCE: f64 = 4.0;
+// Share of the smaller canvas side the cube spans at zoom 1
+const CUBE_SCALE: f64 = 0.32;
+// Radians turned per pixel dragged
+const ROTATE_SPEED: f64 = 0.01;
+const WHEEL_ZOOM: f64 = 1.1;
+const WHEEL_NOTCH: f64 = 100.0;
+const LINE_HEIGHT: f64 = 16.0;
+// Points outside a linked selection
+const DIMMED_COLOR: &str = "#bbbbbb";
+const DIMMED_OPACITY: f64 = 0.25;
+
+// Direction the data is viewed from, turned by dragging and zoomed by
+// scrolling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    // Turn around the vertical z axis, in radians
+    pub yaw: f64,
+    // Tilt down towards the top of the cube, in radians
+    pub pitch: f64,
+    pub zoom: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            yaw: 0.6,
+            pitch: 0.4,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    // Turned by a drag of (dx, dy) pixels. The tilt stops short of looking
+    // straight down or up, where the view would flip.
+    pub fn rotated(&self, dx: f64, dy: f64) -> Self {
+        let limit = f64::consts::FRAC_PI_2 - 0.01;
+        Camera {
+            yaw: self.yaw + dx * ROTATE_SPEED,
+            pitch: (self.pitch + dy * ROTATE_SPEED).clamp(-limit, limit),
+            ..*self
+        }
+    }
+
+    // Zoomed in by factor, out for factors below 1
+    pub fn zoomed(&self, factor: f64) -> Self {
+        Camera {
+            zoom: (self.zoom * factor).clamp(0.2, 20.0),
+            ..*self
+        }
+    }
+
+    // Horizontal and vertical position in half cube widths, and depth (away
+    // from the eye), of a point of the cube [-1, 1]^3
+    fn project(&self, [x, y, z]: [f64; 3]) -> (f64, f64, f64) {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let x1 = x * cos_yaw - y * sin_yaw;
+        let y1 = x * sin_yaw + y * cos_yaw;
+        let up = z * cos_pitch - y1 * sin_pitch;
+        let depth = y1 * cos_pitch + z * sin_pitch;
+        (x1, up, depth)
+    }
+}
+
+// Points in three dimensions, e.g. an embedding into three components,
+// each axis scaled to its range of values. NaN coordinates are skipped.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scatter3dData {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+    // Drawn at the ends of the axes
+    pub axis_names: [String; 3],
+    // Class of every point, coloring it. One color when None.
+    pub classes: Option<Vec<String>>,
+}
+
+impl Scatter3dData {
+    pub fn new(x: Vec<f64>, y: Vec<f64>, z: Vec<f64>) -> Self {
+        Scatter3dData {
+            x,
+            y,
+            z,
+            axis_names: ["x".to_string(), "y".to_string(), "z".to_string()],
+            classes: None,
+        }
+    }
+
+    // The first three coordinates of every point, missing ones NaN
+    pub fn from_points(points: &[Point]) -> Self {
+        let coordinate = |i: usize| -> Vec<f64> {
+            points
+                .iter()
+                .map(|p| p.get(i).copied().unwrap_or(f64::NAN))
+                .collect()
+        };
+        Scatter3dData::new(coordinate(0), coordinate(1), coordinate(2))
+    }
+
+    pub fn with_axis_names(mut self, x: &str, y: &str, z: &str) -> Self {
+        self.axis_names = [x.to_string(), y.to_string(), z.to_string()];
+        self
+    }
+
+    pub fn with_classes(mut self, classes: Vec<String>) -> Self {
+        self.classes = Some(classes);
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.x.len().min(self.y.len()).min(self.z.len())
+    }
+
+    // Distinct classes in order of first appearance, and the index of every
+    // point's class among them
+    fn class_indices(&self) -> (Vec<&str>, Vec<usize>) {
+        let Some(classes) = &self.classes else {
+            return (vec![], vec![0; self.len()]);
+        };
+        let mut names: Vec<&str> = vec![];
+        let indices = classes
+            .iter()
+            .map(|class| match names.iter().position(|&n| n == class) {
+                Some(i) => i,
+                None => {
+                    names.push(class);
+                    names.len() - 1
+                }
+            })
+            .collect();
+        (names, indices)
+    }
+
+    // The points seen by camera, farthest first so that nearer ones cover
+    // them. Points whose index isn't in highlighted are dimmed, none when
+    // it is None.
+    pub fn draw_view(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+        camera: Camera,
+        highlighted: Option<&BTreeSet<usize>>,
+    ) {
+        let scale = width.min(height) * CUBE_SCALE * camera.zoom;
+        let (center_x, center_y) = (width / 2.0, height / 2.0);
+        let to_canvas = |corner: [f64; 3]| {
+            let (x, up, depth) = camera.project(corner);
+            let perspective = EYE_DISTANCE / (EYE_DISTANCE + depth);
+            (
+                center_x + x * perspective * scale,
+                center_y - up * perspective * scale,
+                depth,
+                perspective,
+            )
+        };
+
+        // Cube edges in the grid color, then the three axes from the corner
+        // where their smallest values meet
+        let edge_color = config.grid_color.as_deref().unwrap_or("#dddddd");
+        context.set_stroke_style_str(edge_color);
+        context.set_line_width(1.0);
+        context.begin_path();
+        for axis in 0..3 {
+            let others: Vec<usize> = (0..3).filter(|&i| i != axis).collect();
+            for (a, b) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+                let (mut from, mut to) = ([0.0; 3], [0.0; 3]);
+                (from[others[0]], from[others[1]]) = (a, b);
+                (to[others[0]], to[others[1]]) = (a, b);
+                (from[axis], to[axis]) = (-1.0, 1.0);
+                let (x0, y0, ..) = to_canvas(from);
+                let (x1, y1, ..) = to_canvas(to);
+                context.move_to(x0, y0);
+                context.line_to(x1, y1);
+            }
+        }
+        context.stroke();
+
+        let ranges = [&self.x, &self.y, &self.z]
+            .map(|values| AxisRange::from_values(values.iter().copied(), Scale::Linear));
+        context.set_stroke_style_str(&config.axis_color);
+        context.set_fill_style_str(&config.text_color);
+        context.set_line_width(1.5);
+        context.set_font(&font(config.label_font_size, config));
+        context.set_text_align("center");
+        let origin = to_canvas([-1.0; 3]);
+        for (axis, name) in self.axis_names.iter().enumerate() {
+            let mut end = [-1.0; 3];
+            end[axis] = 1.0;
+            let end = to_canvas(end);
+            context.begin_path();
+            context.move_to(origin.0, origin.1);
+            context.line_to(end.0, end.1);
+            context.stroke();
+            // Past the end of the axis, away from the origin
+            let (dx, dy) = (end.0 - origin.0, end.1 - origin.1);
+            let length = dx.hypot(dy).max(1.0);
+            context
+                .fill_text(
+                    name,
+                    end.0 + dx / length * 14.0,
+                    end.1 + dy / length * 14.0 + 4.0,
+                )
+                .unwrap_or(());
+        }
+        context.set_text_align("start");
+
+        let (class_names, class_of) = self.class_indices();
+        let mut projected: Vec<(f64, f64, f64, f64, usize, bool)> = (0..self.len())
+            .filter_map(|i| {
+                let point = [self.x[i], self.y[i], self.z[i]];
+                if point.iter().any(|v| v.is_nan()) {
+                    return None;
+                }
+                let mut corner = [0.0; 3];
+                for axis in 0..3 {
+                    corner[axis] = ranges[axis].fraction(point[axis]) * 2.0 - 1.0;
+                }
+                let (x, y, depth, perspective) = to_canvas(corner);
+                Some((
+                    x,
+                    y,
+                    depth,
+                    perspective,
+                    class_of.get(i).copied().unwrap_or(0),
+                    highlighted.is_some_and(|rows| !rows.contains(&i)),
+                ))
+            })
+            .collect();
+        projected.sort_by(|a, b| b.2.total_cmp(&a.2));
+        context.save();
+        for (x, y, _, perspective, class, dimmed) in projected {
+            if dimmed {
+                context.set_global_alpha(DIMMED_OPACITY);
+                context.set_fill_style_str(DIMMED_COLOR);
+            } else {
+                context.set_global_alpha(MARKER_OPACITY);
+                context.set_fill_style_str(config.palette.color(class));
+            }
+            draw_marker(
+                context,
+                MarkerShape::Circle,
+                x,
+                y,
+                MARKER_RADIUS * perspective,
+            );
+        }
+        context.restore();
+
+        let entries: Vec<LegendEntry> = class_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| LegendEntry {
+                label: name.to_string(),
+                color: config.palette.color(i).to_string(),
+                swatch: Swatch::Marker(MarkerShape::Circle),
+            })
+            .collect();
+        draw_legend(context, width, height, &entries, config);
+        draw_title(context, width, config);
+    }
+}
+
+impl Drawable for Scatter3dData {
+    fn draw(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        config: &PlotConfig,
+    ) {
+        self.draw_view(context, width, height, config, Camera::default(), None);
+    }
+}
+
+// 3D scatter plot, turned by dragging and zoomed with the mouse wheel.
+// Redraws whenever data changes. Linked to a dataset whose rows its points
+// are, rows brushed in other charts are highlighted.
+#[component]
+pub fn Scatter3d(
+    data: Signal<Scatter3dData>,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] config: PlotConfig,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+    #[props(default)] linked: Option<String>,
+) -> Element {
+    let canvas_id = id.clone();
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let theme = use_theme();
+    let mut camera = use_signal(Camera::default);
+    // Last pointer position while dragging
+    let mut drag_from = use_signal(|| None::<(f64, f64)>);
+    let linked_selection = use_linked_selection();
+
+    use_effect(move || {
+        let Some(context) = context_2d(&canvas_id) else {
+            return;
+        };
+        let size = size();
+        let config = themed(&config, theme);
+        let highlighted = linked_rows(linked_selection, linked.as_deref());
+        clear_canvas(&context, size, &config);
+        data.read().draw_view(
+            &context,
+            size.width,
+            size.height,
+            &config,
+            camera(),
+            highlighted.as_ref(),
+        );
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            canvas {
+                id: "{id}",
+                width: "{size().device_width()}",
+                height: "{size().device_height()}",
+                style: "width: {size().width}px; height: {size().height}px; border: 1px solid black; cursor: grab; touch-action: none;",
+                onwheel: move |event| {
+                    event.prevent_default();
+                    let delta = match event.delta() {
+                        WheelDelta::Pixels(delta) => delta.y,
+                        WheelDelta::Lines(delta) => delta.y * LINE_HEIGHT,
+                        WheelDelta::Pages(delta) => delta.y * size().height,
+                    };
+                    let factor = WHEEL_ZOOM.powf(-delta / WHEEL_NOTCH);
+                    camera.set(camera().zoomed(factor));
+                },
+                onmousedown: move |event| {
+                    let point = event.element_coordinates();
+                    drag_from.set(Some((point.x, point.y)));
+                },
+                onmousemove: move |event| {
+                    let Some((x, y)) = drag_from() else {
+                        return;
+                    };
+                    let point = event.element_coordinates();
+                    camera.set(camera().rotated(point.x - x, point.y - y));
+                    drag_from.set(Some((point.x, point.y)));
+                },
+                onmouseup: move |_| drag_from.set(None),
+                onmouseleave: move |_| drag_from.set(None),
+                ondoubleclick: move |_| camera.set(Camera::default()),
+            }
+            if let Some(filename) = export {
+                ExportToolbar { target: container_id.clone(), filename }
+            }
+        }
+    }
+}
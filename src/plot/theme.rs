@@ -0,0 +1,141 @@
+use super::color::Palette;
+use super::config::PlotConfig;
+use super::vega::spec::{AxisConfig, Config, LegendConfig, RangeConfig, TitleConfig};
+use dioxus::prelude::*;
+
+// Colors and fonts of every chart under a ThemeProvider, whether drawn on a
+// canvas or by Vega-Lite
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    // CSS colors
+    pub background: String,
+    pub axis_color: String,
+    pub text_color: String,
+    // Lines across the plot area at the ticks, none when None
+    pub grid_color: Option<String>,
+    pub legend_background: String,
+    pub palette: Palette,
+    pub font_family: String,
+    // Font sizes in pixels
+    pub title_font_size: f64,
+    pub label_font_size: f64,
+    pub tick_font_size: f64,
+    pub legend_font_size: f64,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
+
+impl Theme {
+    // Dark text on white
+    pub fn light() -> Self {
+        let config = PlotConfig::default();
+        Theme {
+            background: "white".to_string(),
+            axis_color: "#333333".to_string(),
+            text_color: "#222222".to_string(),
+            grid_color: Some("#e6e6e6".to_string()),
+            legend_background: "rgba(255, 255, 255, 0.8)".to_string(),
+            palette: Palette::Tab10,
+            font_family: config.font_family,
+            title_font_size: config.title_font_size,
+            label_font_size: config.label_font_size,
+            tick_font_size: config.tick_font_size,
+            legend_font_size: config.legend_font_size,
+        }
+    }
+
+    // Light text on near black, with the softer Set2 colors
+    pub fn dark() -> Self {
+        Theme {
+            background: "#1e1e1e".to_string(),
+            axis_color: "#bbbbbb".to_string(),
+            text_color: "#e0e0e0".to_string(),
+            grid_color: Some("#3a3a3a".to_string()),
+            legend_background: "rgba(30, 30, 30, 0.8)".to_string(),
+            palette: Palette::Set2,
+            ..Theme::light()
+        }
+    }
+
+    // config with the theme's colors and fonts, keeping its titles, legend
+    // position and scales
+    pub fn apply(&self, config: &PlotConfig) -> PlotConfig {
+        PlotConfig {
+            palette: self.palette,
+            font_family: self.font_family.clone(),
+            title_font_size: self.title_font_size,
+            label_font_size: self.label_font_size,
+            tick_font_size: self.tick_font_size,
+            legend_font_size: self.legend_font_size,
+            axis_color: self.axis_color.clone(),
+            text_color: self.text_color.clone(),
+            grid_color: self.grid_color.clone(),
+            legend_background: self.legend_background.clone(),
+            background: Some(self.background.clone()),
+            ..config.clone()
+        }
+    }
+
+    // Vega-Lite config drawing charts the way canvas charts are drawn
+    pub fn vega_config(&self) -> Config {
+        Config {
+            background: Some(self.background.clone()),
+            font: Some(self.font_family.clone()),
+            axis: Some(AxisConfig {
+                domain_color: Some(self.axis_color.clone()),
+                tick_color: Some(self.axis_color.clone()),
+                label_color: Some(self.text_color.clone()),
+                title_color: Some(self.text_color.clone()),
+                label_font_size: Some(self.tick_font_size),
+                title_font_size: Some(self.label_font_size),
+                grid: Some(self.grid_color.is_some()),
+                grid_color: self.grid_color.clone(),
+            }),
+            legend: Some(LegendConfig {
+                label_color: Some(self.text_color.clone()),
+                title_color: Some(self.text_color.clone()),
+                label_font_size: Some(self.legend_font_size),
+            }),
+            title: Some(TitleConfig {
+                color: Some(self.text_color.clone()),
+                font_size: Some(self.title_font_size),
+            }),
+            range: Some(RangeConfig {
+                category: self
+                    .palette
+                    .colors()
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect(),
+            }),
+            view: None,
+        }
+    }
+}
+
+// Theme of the charts inside, switched at runtime by setting the signal
+#[component]
+pub fn ThemeProvider(theme: Signal<Theme>, children: Element) -> Element {
+    use_context_provider(|| theme);
+    rsx! {
+        {children}
+    }
+}
+
+// Theme of the nearest ThemeProvider, None outside one
+pub fn use_theme() -> Option<Signal<Theme>> {
+    try_use_context::<Signal<Theme>>()
+}
+
+// config restyled by theme when there is one. Charts call this in their
+// effects, so that they redraw when the theme switches.
+pub(crate) fn themed(config: &PlotConfig, theme: Option<Signal<Theme>>) -> PlotConfig {
+    match theme {
+        Some(theme) => theme.read().apply(config),
+        None => config.clone(),
+    }
+}
@@ -0,0 +1,167 @@
+use super::embed::{embed, SignalListener, VegaView};
+use super::selection::{Param, Selection};
+use super::spec::{Facet, VegaSpec};
+use crate::plot::export::{ExportFormat, ExportToolbar};
+use crate::plot::responsive::use_chart_size;
+use crate::plot::theme::use_theme;
+use dioxus::prelude::*;
+use log::{error, info};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Name of the chart's data set in the running view
+const DATA_NAME: &str = "table";
+// Vega-Lite's default pixels between facet cells
+const FACET_SPACING: f64 = 20.0;
+// Smallest facet cell drawn, in pixels, however many there are
+const MIN_CELL_SIZE: f64 = 40.0;
+
+// Embedded view with the listener on its selection
+type Embedded = (VegaView, Option<SignalListener>);
+
+// Vega-Lite chart of data drawn as spec describes. spec needs no data or
+// size of its own: data is changed in place in the running view whenever
+// it changes, and the chart is embedded again only when spec, selection or
+// its size change. Faceted specs split the size among their cells, embedded
+// again when data changes how many there are. Rendered as SVG, so that
+// export offers SVG downloads. Styled by the theme of a ThemeProvider
+// around it, embedded again when the theme switches.
+#[component]
+pub fn VegaLiteChart(
+    data: Signal<Vec<Value>>,
+    spec: VegaSpec,
+    width: f64,
+    height: f64,
+    id: String,
+    // Selection the viewer can make, passed to on_select as it changes
+    #[props(default)] selection: Option<Param>,
+    #[props(default)] on_select: EventHandler<Selection>,
+    // Filename a toolbar under the chart downloads it as, no toolbar when None
+    #[props(default)] export: Option<String>,
+) -> Element {
+    let container_id = format!("{}-container", id);
+    let size = use_chart_size(container_id.clone(), width, height);
+    let embedded = use_hook(|| Rc::new(RefCell::new(None::<Embedded>)));
+    let theme = use_theme();
+    let shape = use_memo(use_reactive(&spec, move |spec| spec.facet_shape(&data())));
+
+    use_effect(use_reactive((&spec, &selection, &id), {
+        let embedded = embedded.clone();
+        move |(spec, selection, id)| {
+            let size = size();
+            let (rows, columns) = shape();
+            let cell = |total: f64, n: usize| {
+                ((total - FACET_SPACING * (n - 1) as f64) / n as f64).max(MIN_CELL_SIZE) as u32
+            };
+            let values = data.peek().clone();
+            let spec = match theme {
+                Some(theme) => spec.config(theme.read().vega_config()),
+                None => spec,
+            };
+            let spec = match &selection {
+                Some(param) => spec.param(param.clone()),
+                None => spec,
+            }
+            .size(cell(size.width, columns), cell(size.height, rows))
+            .named_data(DATA_NAME, values.clone())
+            .build();
+            let spec = match spec {
+                Ok(spec) => spec,
+                Err(err) => {
+                    error!("Invalid Vega-Lite spec: {}", err);
+                    return;
+                }
+            };
+
+            let embedded = embedded.clone();
+            spawn(async move {
+                let options = json!({"renderer": "svg"});
+                let view = match embed(&format!("#{}", id), &spec, &options).await {
+                    Ok(view) => view,
+                    Err(err) => {
+                        error!("Error embedding Vega-Lite chart: {}", err);
+                        return;
+                    }
+                };
+                info!("Vega-Lite chart {} embedded", id);
+                let listener =
+                    selection.map(|param| view.on_selection(&param, move |s| on_select.call(s)));
+                // Data that changed while embedding
+                if *data.peek() != values {
+                    if let Err(err) = view.set_data(DATA_NAME, &data.peek()).await {
+                        error!("Error updating Vega-Lite chart: {}", err);
+                    }
+                }
+                if let Some((previous, _)) = embedded.borrow_mut().replace((view, listener)) {
+                    previous.finalize();
+                }
+            });
+        }
+    }));
+
+    use_effect({
+        let embedded = embedded.clone();
+        move || {
+            let values = data();
+            let Some(view) = embedded.borrow().as_ref().map(|(view, _)| view.clone()) else {
+                return;
+            };
+            spawn(async move {
+                if let Err(err) = view.set_data(DATA_NAME, &values).await {
+                    error!("Error updating Vega-Lite chart: {}", err);
+                }
+            });
+        }
+    });
+
+    use_drop(move || {
+        if let Some((view, listener)) = embedded.borrow_mut().take() {
+            drop(listener);
+            view.finalize();
+        }
+    });
+
+    rsx! {
+        div {
+            id: "{container_id}",
+            style: "width: 100%;",
+            div { id: "{id}" }
+            if let Some(filename) = export {
+                ExportToolbar {
+                    target: container_id.clone(),
+                    filename,
+                    formats: vec![ExportFormat::Svg],
+                }
+            }
+        }
+    }
+}
+
+// Small multiples of spec, drawn once per value of facet's fields, e.g.
+// clustering results for several k side by side with Facet::columns("k", ..)
+#[component]
+pub fn FacetChart(
+    data: Signal<Vec<Value>>,
+    spec: VegaSpec,
+    facet: Facet,
+    width: f64,
+    height: f64,
+    id: String,
+    #[props(default)] selection: Option<Param>,
+    #[props(default)] on_select: EventHandler<Selection>,
+    #[props(default)] export: Option<String>,
+) -> Element {
+    rsx! {
+        VegaLiteChart {
+            data,
+            spec: spec.facet(facet),
+            width,
+            height,
+            id,
+            selection,
+            on_select,
+            export,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::selection::{Param, Selection};
+use crate::error::{MoonlightError, Result};
+use gloo_utils::format::JsValueSerdeExt;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Promise;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = window, js_name = vegaEmbed)]
+    fn vega_embed(selector: &str, spec: &JsValue, options: &JsValue) -> Promise;
+
+    // What vega-embed resolves to
+    type EmbedResult;
+
+    #[wasm_bindgen(method, getter)]
+    fn view(this: &EmbedResult) -> VegaView;
+
+    // Running Vega view of an embedded chart
+    #[derive(Debug, Clone)]
+    pub type VegaView;
+
+    #[wasm_bindgen(method, js_name = addSignalListener)]
+    fn add_signal_listener(this: &VegaView, name: &str, handler: &JsValue);
+
+    #[wasm_bindgen(method, js_name = removeSignalListener)]
+    fn remove_signal_listener(this: &VegaView, name: &str, handler: &JsValue);
+
+    // Stops the view's timers and event listeners
+    #[wasm_bindgen(method)]
+    pub fn finalize(this: &VegaView);
+
+    #[wasm_bindgen(method, js_name = data)]
+    fn replace_data(this: &VegaView, name: &str, values: &JsValue) -> VegaView;
+
+    #[wasm_bindgen(method, js_name = runAsync)]
+    fn run_async(this: &VegaView) -> Promise;
+}
+
+// Listens to a signal of a view until dropped
+pub struct SignalListener {
+    view: VegaView,
+    name: String,
+    handler: Closure<dyn FnMut(JsValue, JsValue)>,
+}
+
+impl Drop for SignalListener {
+    fn drop(&mut self) {
+        self.view
+            .remove_signal_listener(&self.name, self.handler.as_ref());
+    }
+}
+
+// Renders spec into the element matching selector with vega-embed (loaded
+// by the page), replacing what it showed before
+pub async fn embed(selector: &str, spec: &Value, options: &Value) -> Result<VegaView> {
+    let spec = JsValue::from_serde(spec).map_err(to_js_error)?;
+    let options = JsValue::from_serde(options).map_err(to_js_error)?;
+    let result = JsFuture::from(vega_embed(selector, &spec, &options)).await?;
+    Ok(result.unchecked_into::<EmbedResult>().view())
+}
+
+impl VegaView {
+    // Replaces the values of the named data set and re-renders, keeping the
+    // view with its selections and signals
+    pub async fn set_data(&self, name: &str, values: &[Value]) -> Result<()> {
+        let values = JsValue::from_serde(values).map_err(to_js_error)?;
+        let promise = self.replace_data(name, &values).run_async();
+        JsFuture::from(promise).await?;
+        Ok(())
+    }
+
+    // Calls on_change with the selection of param whenever the viewer
+    // changes it, e.g. on every move of a brush, until the listener is
+    // dropped
+    pub fn on_selection(
+        &self,
+        param: &Param,
+        mut on_change: impl FnMut(Selection) + 'static,
+    ) -> SignalListener {
+        let (name, kind) = (param.name.clone(), param.select.kind);
+        let handler =
+            Closure::<dyn FnMut(JsValue, JsValue)>::new(move |_: JsValue, value: JsValue| {
+                let value = value.into_serde::<Value>().unwrap_or(Value::Null);
+                on_change(Selection::from_signal(&name, kind, &value));
+            });
+        self.add_signal_listener(&param.name, handler.as_ref());
+        SignalListener {
+            view: self.clone(),
+            name: param.name.clone(),
+            handler,
+        }
+    }
+}
+
+// Values that can't cross into JavaScript
+fn to_js_error(err: serde_json::Error) -> MoonlightError {
+    MoonlightError::Js(err.to_string())
+}
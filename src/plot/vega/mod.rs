@@ -0,0 +1,4 @@
+pub mod chart;
+pub mod embed;
+pub mod selection;
+pub mod spec;
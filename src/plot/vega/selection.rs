@@ -0,0 +1,132 @@
+use super::spec::Channel;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionKind {
+    // A brushed rectangle, or band when only one encoding is given
+    Interval,
+    // Clicked marks, shift-click adding to them
+    Point,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelectionDef {
+    #[serde(rename = "type")]
+    pub kind: SelectionKind,
+    // Channels the selection spans, e.g. only x for a horizontal band
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub encodings: Vec<Channel>,
+    // Fields a point selection matches marks on, e.g. selecting a cluster
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+// Named selection parameter of a unit spec. Vega keeps its current value in
+// a signal of the same name, see VegaView::on_selection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Param {
+    pub name: String,
+    pub select: SelectionDef,
+}
+
+impl Param {
+    // Rectangle brushed over x and y
+    pub fn interval(name: &str) -> Self {
+        Param::interval_over(name, vec![Channel::X, Channel::Y])
+    }
+
+    pub fn interval_over(name: &str, encodings: Vec<Channel>) -> Self {
+        Param {
+            name: name.to_string(),
+            select: SelectionDef {
+                kind: SelectionKind::Interval,
+                encodings,
+                fields: vec![],
+            },
+        }
+    }
+
+    // Clicked marks, selecting every mark with the same values of fields
+    pub fn point(name: &str, fields: &[&str]) -> Self {
+        Param {
+            name: name.to_string(),
+            select: SelectionDef {
+                kind: SelectionKind::Point,
+                encodings: vec![],
+                fields: fields.iter().map(|f| f.to_string()).collect(),
+            },
+        }
+    }
+}
+
+// Current value of a selection parameter. Empty when nothing is selected.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Selection {
+    pub param: String,
+    // Interval selections: field, min and max
+    pub ranges: Vec<(String, f64, f64)>,
+    // Point selections: field and the values selected
+    pub values: Vec<(String, Vec<Value>)>,
+}
+
+impl Selection {
+    // Selection from the value of its signal: an object with an array per
+    // field, [min, max] for intervals and the selected values for points
+    pub fn from_signal(param: &str, kind: SelectionKind, value: &Value) -> Self {
+        let mut selection = Selection {
+            param: param.to_string(),
+            ..Selection::default()
+        };
+        let Some(fields) = value.as_object() else {
+            return selection;
+        };
+        for (field, values) in fields {
+            let Some(values) = values.as_array() else {
+                continue;
+            };
+            match kind {
+                SelectionKind::Interval => {
+                    let bounds: Vec<f64> = values.iter().filter_map(Value::as_f64).collect();
+                    if let [a, b] = bounds[..] {
+                        selection.ranges.push((field.clone(), a.min(b), a.max(b)));
+                    }
+                }
+                SelectionKind::Point => selection.values.push((field.clone(), values.clone())),
+            }
+        }
+        selection
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty() && self.values.is_empty()
+    }
+
+    // Whether a data row falls within every range and matches every field's
+    // values. Nothing is contained in an empty selection.
+    pub fn contains(&self, row: &Value) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let in_ranges = self.ranges.iter().all(|(field, min, max)| {
+            row.get(field)
+                .and_then(Value::as_f64)
+                .is_some_and(|v| v >= *min && v <= *max)
+        });
+        let in_values = self
+            .values
+            .iter()
+            .all(|(field, values)| row.get(field).is_some_and(|v| values.contains(v)));
+        in_ranges && in_values
+    }
+
+    // Indices of the rows within the selection
+    pub fn filter(&self, rows: &[Value]) -> Vec<usize> {
+        rows.iter()
+            .enumerate()
+            .filter(|(_, row)| self.contains(row))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
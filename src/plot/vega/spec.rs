@@ -0,0 +1,809 @@
+use super::selection::Param;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+const SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Quantitative,
+    Nominal,
+    Ordinal,
+    Temporal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mark {
+    Point,
+    Circle,
+    Square,
+    Line,
+    Area,
+    Bar,
+    Rect,
+    Rule,
+    Tick,
+    Text,
+    // Extent of y at each x, aggregated from the rows or between y and y2
+    ErrorBar,
+    // Shaded extent of y along x, as for ErrorBar
+    ErrorBand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleType {
+    Linear,
+    Log,
+    Sqrt,
+}
+
+// Encoding channels a field can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    X,
+    Y,
+    // Ends of ranged marks such as binned bars
+    X2,
+    Y2,
+    Color,
+    Size,
+    Shape,
+    Opacity,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VegaError {
+    // A spec needs either a mark or layers
+    MissingMark,
+    MarkAndLayers,
+    // Only the top level spec holds data
+    MissingData,
+    // A mark drawn without a channel it needs, e.g. a line without y
+    MissingChannel { mark: Mark, channel: Channel },
+    EmptyField { channel: Channel },
+    // Parameters share one namespace across the spec and its layers
+    EmptyParamName,
+    DuplicateParam(String),
+    InLayer { index: usize, error: Box<VegaError> },
+    // Only the top level spec can be faceted
+    FacetInLayer,
+    EmptyFacetField,
+    ZeroFacetColumns,
+}
+
+impl fmt::Display for VegaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VegaError::MissingMark => write!(f, "spec has neither a mark nor layers"),
+            VegaError::MarkAndLayers => write!(f, "spec has both a mark and layers"),
+            VegaError::MissingData => write!(f, "spec has no data"),
+            VegaError::MissingChannel { mark, channel } => {
+                write!(f, "{:?} mark needs {:?} encoded", mark, channel)
+            }
+            VegaError::EmptyField { channel } => {
+                write!(f, "{:?} encoding has an empty field name", channel)
+            }
+            VegaError::EmptyParamName => write!(f, "parameter has an empty name"),
+            VegaError::DuplicateParam(name) => write!(f, "parameter {} is defined twice", name),
+            VegaError::InLayer { index, error } => write!(f, "layer {}: {}", index, error),
+            VegaError::FacetInLayer => write!(f, "layer is faceted, only the top level can be"),
+            VegaError::EmptyFacetField => write!(f, "facet has an empty field name"),
+            VegaError::ZeroFacetColumns => write!(f, "wrapped facet has zero columns"),
+        }
+    }
+}
+
+impl Error for VegaError {}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct ScaleDef {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub scale_type: Option<ScaleType>,
+    // Whether a quantitative scale includes zero
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<Vec<f64>>,
+    // Colors (or sizes, shapes) the domain maps to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Vec<String>>,
+    // Named color scheme, e.g. "viridis"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct BinDef {
+    // The data already holds bin starts and ends, in the field and x2 or y2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binned: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxbins: Option<usize>,
+}
+
+// Data field shown through an encoding channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDef {
+    pub field: String,
+    // Required except for secondary channels such as x2
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub field_type: Option<FieldType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    // d3-format string, e.g. ".2f"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<ScaleDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bin: Option<BinDef>,
+}
+
+impl FieldDef {
+    pub fn new(field: &str, field_type: FieldType) -> Self {
+        FieldDef {
+            field_type: Some(field_type),
+            ..FieldDef::secondary(field)
+        }
+    }
+
+    // Field without a type, for x2 and y2
+    pub fn secondary(field: &str) -> Self {
+        FieldDef {
+            field: field.to_string(),
+            field_type: None,
+            title: None,
+            format: None,
+            scale: None,
+            bin: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    pub fn with_scale(mut self, scale: ScaleDef) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn with_bin(mut self, bin: BinDef) -> Self {
+        self.bin = Some(bin);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkDef {
+    #[serde(rename = "type")]
+    pub mark: Mark,
+    // CSS color of every mark, when color isn't encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filled: Option<bool>,
+    // Pixels between bars
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bin_spacing: Option<f64>,
+}
+
+impl MarkDef {
+    pub fn new(mark: Mark) -> Self {
+        MarkDef {
+            mark,
+            color: None,
+            size: None,
+            opacity: None,
+            filled: None,
+            bin_spacing: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Encoding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x2: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y2: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shape: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<FieldDef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tooltip: Vec<FieldDef>,
+}
+
+impl Encoding {
+    fn channel(&self, channel: Channel) -> Option<&FieldDef> {
+        match channel {
+            Channel::X => self.x.as_ref(),
+            Channel::Y => self.y.as_ref(),
+            Channel::X2 => self.x2.as_ref(),
+            Channel::Y2 => self.y2.as_ref(),
+            Channel::Color => self.color.as_ref(),
+            Channel::Size => self.size.as_ref(),
+            Channel::Shape => self.shape.as_ref(),
+            Channel::Opacity => self.opacity.as_ref(),
+        }
+    }
+
+    fn channel_mut(&mut self, channel: Channel) -> &mut Option<FieldDef> {
+        match channel {
+            Channel::X => &mut self.x,
+            Channel::Y => &mut self.y,
+            Channel::X2 => &mut self.x2,
+            Channel::Y2 => &mut self.y2,
+            Channel::Color => &mut self.color,
+            Channel::Size => &mut self.size,
+            Channel::Shape => &mut self.shape,
+            Channel::Opacity => &mut self.opacity,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Encoding::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolveMode {
+    Shared,
+    Independent,
+}
+
+// Whether the cells of a faceted chart share scales, per channel
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Resolve {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub scale: BTreeMap<Channel, ResolveMode>,
+}
+
+impl Resolve {
+    fn is_empty(&self) -> bool {
+        self.scale.is_empty()
+    }
+}
+
+// Small multiples: the spec drawn once per value of a field, on the subset of
+// rows with that value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Facet {
+    // A row of cells per value of one field and a column per value of another
+    Grid {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        row: Option<FieldDef>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        column: Option<FieldDef>,
+    },
+    // A cell per value of field, wrapped after columns cells
+    Wrap {
+        #[serde(flatten)]
+        field: FieldDef,
+        #[serde(skip)]
+        columns: usize,
+    },
+}
+
+impl Facet {
+    pub fn rows(field: &str, field_type: FieldType) -> Self {
+        Facet::Grid {
+            row: Some(FieldDef::new(field, field_type)),
+            column: None,
+        }
+    }
+
+    pub fn columns(field: &str, field_type: FieldType) -> Self {
+        Facet::Grid {
+            row: None,
+            column: Some(FieldDef::new(field, field_type)),
+        }
+    }
+
+    pub fn grid(row: FieldDef, column: FieldDef) -> Self {
+        Facet::Grid {
+            row: Some(row),
+            column: Some(column),
+        }
+    }
+
+    pub fn wrap(field: &str, field_type: FieldType, columns: usize) -> Self {
+        Facet::Wrap {
+            field: FieldDef::new(field, field_type),
+            columns,
+        }
+    }
+
+    fn fields(&self) -> Vec<&FieldDef> {
+        match self {
+            Facet::Grid { row, column } => row.iter().chain(column.iter()).collect(),
+            Facet::Wrap { field, .. } => vec![field],
+        }
+    }
+
+    // Rows and columns of cells drawn for values, as many as the distinct
+    // values of each field
+    pub fn grid_shape(&self, values: &[Value]) -> (usize, usize) {
+        let count = |field: &FieldDef| {
+            let mut distinct: Vec<&Value> = vec![];
+            for value in values.iter().filter_map(|row| row.get(&field.field)) {
+                if !distinct.contains(&value) {
+                    distinct.push(value);
+                }
+            }
+            distinct.len().max(1)
+        };
+        match self {
+            Facet::Grid { row, column } => (
+                row.as_ref().map_or(1, count),
+                column.as_ref().map_or(1, count),
+            ),
+            Facet::Wrap { field, columns } => {
+                let n = count(field);
+                let columns = (*columns).clamp(1, n);
+                (n.div_ceil(columns), columns)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Data {
+    // Name the running view knows the data by, to change it in place
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub values: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ViewConfig {
+    // Border around the plot area, none when None
+    pub stroke: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AxisConfig {
+    // Color of the axis line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_font_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_font_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_color: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegendConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_font_size: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct RangeConfig {
+    // Colors of nominal fields without a scale of their own
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub category: Vec<String>,
+}
+
+// Default styling of the whole chart, see Theme::vega_config
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis: Option<AxisConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legend: Option<LegendConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<TitleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<RangeConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view: Option<ViewConfig>,
+}
+
+impl Config {
+    fn is_empty(&self) -> bool {
+        *self == Config::default()
+    }
+}
+
+// Vega-Lite spec, built up a call at a time and checked by build:
+//
+//     VegaSpec::new()
+//         .data(values)
+//         .mark_point()
+//         .x("x", FieldType::Quantitative)
+//         .y("y", FieldType::Quantitative)
+//         .color("cluster", FieldType::Nominal)
+//         .build()
+//
+// Layers are specs without data of their own. A faceted spec is drawn once
+// per facet value, its size that of each cell.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct VegaSpec {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Data>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facet: Option<Facet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    columns: Option<usize>,
+    #[serde(skip_serializing_if = "Resolve::is_empty")]
+    resolve: Resolve,
+    // What a faceted spec draws in each cell, filled in by build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spec: Option<Box<VegaSpec>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<Param>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mark: Option<MarkDef>,
+    #[serde(skip_serializing_if = "Encoding::is_empty")]
+    encoding: Encoding,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    layer: Vec<VegaSpec>,
+    #[serde(skip_serializing_if = "Config::is_empty")]
+    config: Config,
+}
+
+impl VegaSpec {
+    pub fn new() -> Self {
+        VegaSpec::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    // Plot area size in pixels, not counting axes and legends
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
+
+    // Rows as JSON objects, their keys the fields
+    pub fn data(mut self, values: Vec<Value>) -> Self {
+        self.data = Some(Data { name: None, values });
+        self
+    }
+
+    // Data the view can replace later, see VegaView::set_data
+    pub fn named_data(mut self, name: &str, values: Vec<Value>) -> Self {
+        self.data = Some(Data {
+            name: Some(name.to_string()),
+            values,
+        });
+        self
+    }
+
+    pub fn mark(self, mark: Mark) -> Self {
+        self.mark_def(MarkDef::new(mark))
+    }
+
+    pub fn mark_def(mut self, mark: MarkDef) -> Self {
+        self.mark = Some(mark);
+        self
+    }
+
+    pub fn mark_point(self) -> Self {
+        self.mark(Mark::Point)
+    }
+
+    pub fn mark_line(self) -> Self {
+        self.mark(Mark::Line)
+    }
+
+    pub fn mark_bar(self) -> Self {
+        self.mark(Mark::Bar)
+    }
+
+    pub fn mark_rect(self) -> Self {
+        self.mark(Mark::Rect)
+    }
+
+    // Maps channel to a field, replacing what it showed before
+    pub fn encode(mut self, channel: Channel, field: FieldDef) -> Self {
+        *self.encoding.channel_mut(channel) = Some(field);
+        self
+    }
+
+    pub fn x(self, field: &str, field_type: FieldType) -> Self {
+        self.encode(Channel::X, FieldDef::new(field, field_type))
+    }
+
+    pub fn y(self, field: &str, field_type: FieldType) -> Self {
+        self.encode(Channel::Y, FieldDef::new(field, field_type))
+    }
+
+    pub fn x2(self, field: &str) -> Self {
+        self.encode(Channel::X2, FieldDef::secondary(field))
+    }
+
+    pub fn y2(self, field: &str) -> Self {
+        self.encode(Channel::Y2, FieldDef::secondary(field))
+    }
+
+    pub fn color(self, field: &str, field_type: FieldType) -> Self {
+        self.encode(Channel::Color, FieldDef::new(field, field_type))
+    }
+
+    pub fn size_by(self, field: &str, field_type: FieldType) -> Self {
+        self.encode(Channel::Size, FieldDef::new(field, field_type))
+    }
+
+    pub fn shape(self, field: &str, field_type: FieldType) -> Self {
+        self.encode(Channel::Shape, FieldDef::new(field, field_type))
+    }
+
+    // Adds a field to the tooltip
+    pub fn tooltip(mut self, field: FieldDef) -> Self {
+        self.encoding.tooltip.push(field);
+        self
+    }
+
+    // Selection the viewer can make on this spec's marks. Interval
+    // selections belong on a layer with x and y encoded.
+    pub fn param(mut self, param: Param) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    pub fn layer(mut self, layer: VegaSpec) -> Self {
+        self.layer.push(layer);
+        self
+    }
+
+    // Draws the spec once per facet value, replacing any facet before
+    pub fn facet(mut self, facet: Facet) -> Self {
+        self.facet = Some(facet);
+        self
+    }
+
+    pub fn facet_row(self, field: &str, field_type: FieldType) -> Self {
+        self.facet(Facet::rows(field, field_type))
+    }
+
+    pub fn facet_column(self, field: &str, field_type: FieldType) -> Self {
+        self.facet(Facet::columns(field, field_type))
+    }
+
+    pub fn facet_wrap(self, field: &str, field_type: FieldType, columns: usize) -> Self {
+        self.facet(Facet::wrap(field, field_type, columns))
+    }
+
+    // Rows and columns of cells the spec draws for values, one of each when
+    // it isn't faceted
+    pub fn facet_shape(&self, values: &[Value]) -> (usize, usize) {
+        self.facet
+            .as_ref()
+            .map_or((1, 1), |facet| facet.grid_shape(values))
+    }
+
+    // Gives each facet cell its own scale for channel, e.g. y when comparing
+    // features of different ranges. Cells share scales otherwise.
+    pub fn independent_scale(mut self, channel: Channel) -> Self {
+        self.resolve.scale.insert(channel, ResolveMode::Independent);
+        self
+    }
+
+    // Border around the plot area, none when None
+    pub fn view_stroke(mut self, stroke: Option<&str>) -> Self {
+        self.config.view = Some(ViewConfig {
+            stroke: stroke.map(str::to_string),
+        });
+        self
+    }
+
+    // Styles the chart with config, keeping the view border set by
+    // view_stroke
+    pub fn config(mut self, config: Config) -> Self {
+        let view = self.config.view.take();
+        self.config = Config {
+            view: config.view.or(view),
+            ..config
+        };
+        self
+    }
+
+    // The spec as JSON for vega-embed, after checking that it has data,
+    // a mark or layers, the channels its marks need, uniquely named
+    // parameters and facet fields
+    pub fn build(self) -> Result<Value, VegaError> {
+        if self.data.is_none() {
+            return Err(VegaError::MissingData);
+        }
+        self.validate()?;
+        if let Some(facet) = &self.facet {
+            if facet.fields().iter().any(|field| field.field.is_empty()) {
+                return Err(VegaError::EmptyFacetField);
+            }
+            if let Facet::Wrap { columns: 0, .. } = facet {
+                return Err(VegaError::ZeroFacetColumns);
+            }
+        }
+        let mut names: Vec<&str> = vec![];
+        for param in self
+            .params
+            .iter()
+            .chain(self.layer.iter().flat_map(|l| &l.params))
+        {
+            if param.name.is_empty() {
+                return Err(VegaError::EmptyParamName);
+            }
+            if names.contains(&param.name.as_str()) {
+                return Err(VegaError::DuplicateParam(param.name.clone()));
+            }
+            names.push(&param.name);
+        }
+        for (index, layer) in self.layer.iter().enumerate() {
+            let checked = match layer.facet {
+                Some(_) => Err(VegaError::FacetInLayer),
+                None => layer.validate(),
+            };
+            checked.map_err(|error| VegaError::InLayer {
+                index,
+                error: Box::new(error),
+            })?;
+        }
+        let spec = match self.facet {
+            Some(facet) => VegaSpec {
+                schema: Some(SCHEMA.to_string()),
+                title: self.title,
+                data: self.data,
+                columns: match facet {
+                    Facet::Wrap { columns, .. } => Some(columns),
+                    Facet::Grid { .. } => None,
+                },
+                facet: Some(facet),
+                resolve: self.resolve,
+                spec: Some(Box::new(VegaSpec {
+                    width: self.width,
+                    height: self.height,
+                    params: self.params,
+                    mark: self.mark,
+                    encoding: self.encoding,
+                    layer: self.layer,
+                    ..VegaSpec::default()
+                })),
+                config: self.config,
+                ..VegaSpec::default()
+            },
+            None => VegaSpec {
+                schema: Some(SCHEMA.to_string()),
+                ..self
+            },
+        };
+        Ok(serde_json::to_value(spec).unwrap_or(Value::Null))
+    }
+
+    fn validate(&self) -> Result<(), VegaError> {
+        match (&self.mark, self.layer.is_empty()) {
+            (None, true) => return Err(VegaError::MissingMark),
+            (Some(_), false) => return Err(VegaError::MarkAndLayers),
+            _ => {}
+        }
+        let channels = [
+            Channel::X,
+            Channel::Y,
+            Channel::X2,
+            Channel::Y2,
+            Channel::Color,
+            Channel::Size,
+            Channel::Shape,
+            Channel::Opacity,
+        ];
+        for channel in channels {
+            if let Some(field) = self.encoding.channel(channel) {
+                if field.field.is_empty() {
+                    return Err(VegaError::EmptyField { channel });
+                }
+            }
+        }
+        let Some(mark) = self.mark.as_ref().map(|mark| mark.mark) else {
+            return Ok(());
+        };
+        let (has_x, has_y) = (self.encoding.x.is_some(), self.encoding.y.is_some());
+        match mark {
+            // Positioned on both axes
+            Mark::Point
+            | Mark::Circle
+            | Mark::Square
+            | Mark::Line
+            | Mark::Area
+            | Mark::Rect
+            | Mark::ErrorBar
+            | Mark::ErrorBand => {
+                if !has_x {
+                    return Err(VegaError::MissingChannel {
+                        mark,
+                        channel: Channel::X,
+                    });
+                }
+                if !has_y {
+                    return Err(VegaError::MissingChannel {
+                        mark,
+                        channel: Channel::Y,
+                    });
+                }
+            }
+            // Spanning the other axis when only one is encoded
+            Mark::Bar | Mark::Rule | Mark::Tick => {
+                if !has_x && !has_y {
+                    return Err(VegaError::MissingChannel {
+                        mark,
+                        channel: Channel::X,
+                    });
+                }
+            }
+            Mark::Text => {}
+        }
+        Ok(())
+    }
+}
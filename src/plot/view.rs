@@ -0,0 +1,68 @@
+use super::canvas::MARGIN;
+use super::scale::AxisRange;
+
+// Zoom per notch of a mouse wheel, taken as 100 pixels of scrolling
+const WHEEL_ZOOM: f64 = 1.2;
+const WHEEL_NOTCH: f64 = 100.0;
+
+// Region of data space visible in a chart's plot area. Positions within it
+// are fractions of the plot area from its bottom left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: AxisRange,
+    pub y: AxisRange,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(AxisRange::default(), AxisRange::default())
+    }
+}
+
+impl Viewport {
+    pub fn new(x: AxisRange, y: AxisRange) -> Self {
+        Viewport { x, y }
+    }
+
+    // Zoomed in by factor (out for factors below 1) around the position
+    // (fx, fy), which keeps showing the same data
+    pub fn zoomed(&self, factor: f64, fx: f64, fy: f64) -> Self {
+        Viewport {
+            x: self.x.zoomed(factor, fx),
+            y: self.y.zoomed(factor, fy),
+        }
+    }
+
+    // Moved by fractions of the plot area, e.g. (0.5, 0.0) shows the data
+    // half a plot width further right
+    pub fn panned(&self, dx: f64, dy: f64) -> Self {
+        Viewport {
+            x: self.x.panned(dx),
+            y: self.y.panned(dy),
+        }
+    }
+
+    // Zoomed by a mouse wheel scroll of delta pixels around (fx, fy),
+    // scrolling up zooming in
+    pub fn wheel_zoomed(&self, delta: f64, fx: f64, fy: f64) -> Self {
+        self.zoomed(WHEEL_ZOOM.powf(-delta / WHEEL_NOTCH), fx, fy)
+    }
+
+    // Canvas pixel of the data point (x, y) on a width x height canvas, NaN
+    // for values without a position on the scales
+    pub fn to_canvas(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        (
+            MARGIN + self.x.fraction(x) * (width - 2.0 * MARGIN),
+            height - MARGIN - self.y.fraction(y) * (height - 2.0 * MARGIN),
+        )
+    }
+
+    // Position of a canvas pixel within the plot area, as fractions from its
+    // bottom left corner. Outside [0, 1] for pixels in the margins.
+    pub fn plot_position(px: f64, py: f64, width: f64, height: f64) -> (f64, f64) {
+        (
+            (px - MARGIN) / (width - 2.0 * MARGIN),
+            (height - MARGIN - py) / (height - 2.0 * MARGIN),
+        )
+    }
+}
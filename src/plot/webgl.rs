@@ -0,0 +1,318 @@
+use super::canvas::MARGIN;
+use super::color::{Palette, Rgba};
+use super::responsive::ChartSize;
+use super::scale::{AxisRange, Scale};
+use super::scatter::{LayerKind, MarkerShape, ScatterPlotData};
+use super::view::Viewport;
+use crate::error::{MoonlightError, Result};
+use wasm_bindgen::JsCast;
+use web_sys::js_sys::Float32Array;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlShader,
+    WebGlUniformLocation,
+};
+
+// Points from which Renderer::Auto switches to WebGL, where drawing every
+// marker on a 2D canvas starts to take longer than a frame
+const WEBGL_THRESHOLD: usize = 10_000;
+
+// Positions on the axes (log10 of the value on a log scale) go to clip
+// space as position * scale + offset. Every point is a square gl_PointSize
+// pixels wide, cut to the marker's shape.
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec2 position;
+uniform vec2 scale;
+uniform vec2 offset;
+uniform float size;
+void main() {
+    gl_Position = vec4(position * scale + offset, 0.0, 1.0);
+    gl_PointSize = size;
+}
+"#;
+
+// Shapes as in MarkerShape: 0 circle, 1 square, 2 triangle, 3 cross
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+uniform vec4 color;
+uniform int shape;
+out vec4 fragment;
+void main() {
+    vec2 p = gl_PointCoord * 2.0 - 1.0;
+    if (shape == 0 && dot(p, p) > 1.0) discard;
+    if (shape == 2 && abs(p.x) > (p.y + 1.0) / 2.0) discard;
+    if (shape == 3 && abs(abs(p.x) - abs(p.y)) > 0.3) discard;
+    fragment = vec4(color.rgb * color.a, color.a);
+}
+"#;
+
+// What draws the markers of point layers. Lines, regions, axes and the
+// legend are always drawn on a 2D canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Renderer {
+    // WebGL for large plots, the 2D canvas otherwise
+    #[default]
+    Auto,
+    Canvas,
+    // WebGL2, falling back to the 2D canvas where the browser has none
+    WebGl,
+}
+
+impl Renderer {
+    // Whether a plot with n_points markers is drawn with WebGL
+    pub fn uses_webgl(&self, n_points: usize) -> bool {
+        match self {
+            Renderer::Auto => n_points >= WEBGL_THRESHOLD,
+            Renderer::Canvas => false,
+            Renderer::WebGl => true,
+        }
+    }
+}
+
+// Whether the browser can create a WebGL2 context
+pub fn webgl2_available() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("canvas").ok())
+        .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+        .and_then(|canvas| canvas.get_context("webgl2").ok().flatten())
+        .is_some()
+}
+
+// A point layer's run of vertices in the buffer and how they're drawn
+#[derive(Debug, Clone, PartialEq)]
+struct Batch {
+    first: i32,
+    count: i32,
+    color: Rgba,
+    // Marker radius in CSS pixels
+    size: f64,
+    shape: MarkerShape,
+}
+
+struct Uniforms {
+    scale: Option<WebGlUniformLocation>,
+    offset: Option<WebGlUniformLocation>,
+    size: Option<WebGlUniformLocation>,
+    color: Option<WebGlUniformLocation>,
+    shape: Option<WebGlUniformLocation>,
+}
+
+// Draws the point layers of a scatter plot as gl.POINTS on a WebGL2 canvas.
+// Points are uploaded once per change of the data, zooming and panning only
+// change uniforms.
+pub struct PointRenderer {
+    gl: Gl,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    position: u32,
+    uniforms: Uniforms,
+    batches: Vec<Batch>,
+    // Axis positions are stored relative to this, so that narrowing them to
+    // f32 keeps the precision needed when zoomed in far from zero
+    origin: (f64, f64),
+    scales: (Scale, Scale),
+}
+
+impl PointRenderer {
+    // Renderer for the canvas element with the given id
+    pub fn new(canvas_id: &str) -> Result<Self> {
+        let gl = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id(canvas_id))
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+            .ok_or_else(|| js_error(&format!("no canvas with id {}", canvas_id)))?
+            .get_context("webgl2")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<Gl>().ok())
+            .ok_or_else(|| js_error("WebGL2 is not available"))?;
+
+        let vertex = compile_shader(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment = compile_shader(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+        let program = gl
+            .create_program()
+            .ok_or_else(|| js_error("could not create a program"))?;
+        gl.attach_shader(&program, &vertex);
+        gl.attach_shader(&program, &fragment);
+        gl.link_program(&program);
+        if !gl
+            .get_program_parameter(&program, Gl::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+        {
+            return Err(js_error(
+                &gl.get_program_info_log(&program).unwrap_or_default(),
+            ));
+        }
+
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| js_error("could not create a buffer"))?;
+        let position = u32::try_from(gl.get_attrib_location(&program, "position"))
+            .map_err(|_| js_error("no position attribute"))?;
+        let uniforms = Uniforms {
+            scale: gl.get_uniform_location(&program, "scale"),
+            offset: gl.get_uniform_location(&program, "offset"),
+            size: gl.get_uniform_location(&program, "size"),
+            color: gl.get_uniform_location(&program, "color"),
+            shape: gl.get_uniform_location(&program, "shape"),
+        };
+        Ok(PointRenderer {
+            gl,
+            program,
+            buffer,
+            position,
+            uniforms,
+            batches: vec![],
+            origin: (0.0, 0.0),
+            scales: (Scale::Linear, Scale::Linear),
+        })
+    }
+
+    // Packs the point layers of data into the vertex buffer, leaving out
+    // points without a position on the scales
+    pub fn upload(
+        &mut self,
+        data: &ScatterPlotData,
+        palette: Palette,
+        x_scale: Scale,
+        y_scale: Scale,
+    ) {
+        let view = data.viewport(x_scale, y_scale);
+        self.origin = (
+            axis_position(view.x.min, x_scale),
+            axis_position(view.y.min, y_scale),
+        );
+        self.scales = (x_scale, y_scale);
+
+        let mut vertices: Vec<f32> = vec![];
+        self.batches.clear();
+        for (layer, color) in data.layers.iter().zip(data.colors(palette)) {
+            let LayerKind::Points(shape) = layer.kind else {
+                continue;
+            };
+            let first = vertices.len() / 2;
+            for (&x, &y) in layer.x.iter().zip(&layer.y) {
+                if x_scale.contains(x) && y_scale.contains(y) {
+                    vertices.push((axis_position(x, x_scale) - self.origin.0) as f32);
+                    vertices.push((axis_position(y, y_scale) - self.origin.1) as f32);
+                }
+            }
+            self.batches.push(Batch {
+                first: first as i32,
+                count: (vertices.len() / 2 - first) as i32,
+                color: Rgba::from_css(color).unwrap_or(Rgba::rgb(0, 0, 0)),
+                size: layer.size,
+                shape,
+            });
+        }
+
+        let gl = &self.gl;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(vertices.as_slice()),
+            Gl::STATIC_DRAW,
+        );
+    }
+
+    // Clears the canvas and draws the uploaded points within view, clipped
+    // to the plot area of a chart of the given size
+    pub fn draw(&self, view: &Viewport, size: ChartSize) {
+        let gl = &self.gl;
+        let ratio = size.pixel_ratio;
+        gl.viewport(
+            0,
+            0,
+            size.device_width() as i32,
+            size.device_height() as i32,
+        );
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(Gl::COLOR_BUFFER_BIT);
+
+        // The plot area, in device pixels from the bottom left
+        let margin = (MARGIN * ratio).round() as i32;
+        let plot_width = ((size.width - 2.0 * MARGIN) * ratio).round() as i32;
+        let plot_height = ((size.height - 2.0 * MARGIN) * ratio).round() as i32;
+        if plot_width <= 0 || plot_height <= 0 {
+            return;
+        }
+        gl.viewport(margin, margin, plot_width, plot_height);
+        gl.enable(Gl::SCISSOR_TEST);
+        gl.scissor(margin, margin, plot_width, plot_height);
+        gl.enable(Gl::BLEND);
+        gl.blend_func(Gl::ONE, Gl::ONE_MINUS_SRC_ALPHA);
+
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        gl.enable_vertex_attrib_array(self.position);
+        gl.vertex_attrib_pointer_with_i32(self.position, 2, Gl::FLOAT, false, 0, 0);
+
+        let (x_scale, x_offset) = clip_transform(&view.x, self.scales.0, self.origin.0);
+        let (y_scale, y_offset) = clip_transform(&view.y, self.scales.1, self.origin.1);
+        let uniforms = &self.uniforms;
+        gl.uniform2f(uniforms.scale.as_ref(), x_scale, y_scale);
+        gl.uniform2f(uniforms.offset.as_ref(), x_offset, y_offset);
+        for batch in self.batches.iter().filter(|batch| batch.count > 0) {
+            let Rgba { r, g, b, a } = batch.color;
+            gl.uniform4f(
+                uniforms.color.as_ref(),
+                r as f32 / 255.0,
+                g as f32 / 255.0,
+                b as f32 / 255.0,
+                a as f32,
+            );
+            gl.uniform1f(uniforms.size.as_ref(), (2.0 * batch.size * ratio) as f32);
+            let shape = match batch.shape {
+                MarkerShape::Circle => 0,
+                MarkerShape::Square => 1,
+                MarkerShape::Triangle => 2,
+                MarkerShape::Cross => 3,
+            };
+            gl.uniform1i(uniforms.shape.as_ref(), shape);
+            gl.draw_arrays(Gl::POINTS, batch.first, batch.count);
+        }
+        gl.disable(Gl::SCISSOR_TEST);
+    }
+}
+
+fn compile_shader(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| js_error("could not create a shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(js_error(
+            &gl.get_shader_info_log(&shader).unwrap_or_default(),
+        ))
+    }
+}
+
+fn js_error(message: &str) -> MoonlightError {
+    MoonlightError::Js(message.to_string())
+}
+
+// Where value sits along an axis: itself on a linear scale, its log10 on a
+// log scale
+fn axis_position(value: f64, scale: Scale) -> f64 {
+    match scale {
+        Scale::Linear => value,
+        Scale::Log => value.log10(),
+    }
+}
+
+// Scale and offset taking positions stored relative to origin into clip
+// space, -1 at the range's min and 1 at its max
+fn clip_transform(range: &AxisRange, scale: Scale, origin: f64) -> (f32, f32) {
+    let min = axis_position(range.min, scale) - origin;
+    let max = axis_position(range.max, scale) - origin;
+    let factor = 2.0 / (max - min);
+    (factor as f32, (-1.0 - min * factor) as f32)
+}
@@ -0,0 +1,197 @@
+use crate::pages::kmeans::KMeansComponent;
+use crate::pages::linear::LinearRegressionPage;
+use crate::pages::tsne::TsnePage;
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+// Where the app is served from, base_path in Dioxus.toml
+const BASE_PATH: &str = "/moonlight";
+
+type PopStateCallback = Closure<dyn FnMut(web_sys::Event)>;
+
+// Pages of the playground, one per algorithm. Adding a page takes a
+// variant here and a component in pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    KMeans,
+    LinearRegression,
+    Tsne,
+}
+
+impl Route {
+    pub const ALL: [Route; 3] = [Route::KMeans, Route::LinearRegression, Route::Tsne];
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            Route::KMeans => "/clustering/kmeans",
+            Route::LinearRegression => "/regression/linear",
+            Route::Tsne => "/manifold/tsne",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Route::KMeans => "KMeans",
+            Route::LinearRegression => "Linear regression",
+            Route::Tsne => "t-SNE",
+        }
+    }
+
+    // Heading the page is listed under in the sidebar
+    pub fn section(&self) -> &'static str {
+        match self {
+            Route::KMeans => "Clustering",
+            Route::LinearRegression => "Regression",
+            Route::Tsne => "Manifold learning",
+        }
+    }
+
+    // Route of a path with or without BASE_PATH, the first page for the
+    // root and None for any other path
+    pub fn from_path(path: &str) -> Option<Route> {
+        let path = path.strip_prefix(BASE_PATH).unwrap_or(path);
+        let path = path.trim_end_matches('/');
+        if path.is_empty() {
+            return Some(Route::ALL[0]);
+        }
+        Route::ALL.into_iter().find(|route| route.path() == path)
+    }
+
+    fn href(&self) -> String {
+        format!("{}{}", BASE_PATH, self.path())
+    }
+}
+
+fn location_path() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().pathname().ok())
+        .unwrap_or_default()
+}
+
+// Route of the page shown, None for a path that matches no page
+pub fn use_route() -> Signal<Option<Route>> {
+    use_context::<Signal<Option<Route>>>()
+}
+
+// Shows route, adding it to the browser's history
+pub fn navigate(mut current: Signal<Option<Route>>, route: Route) {
+    let history = web_sys::window().and_then(|window| window.history().ok());
+    if let Some(history) = history {
+        if let Err(err) = history.push_state_with_url(&JsValue::NULL, "", Some(&route.href())) {
+            error!("Error navigating to {}: {:?}", route.path(), err);
+        }
+    }
+    current.set(Some(route));
+}
+
+// Sidebar and the page of the browser's path, following the back and
+// forward buttons. Only the page shown is mounted, so the others cost
+// nothing until visited.
+#[component]
+pub fn Router() -> Element {
+    let mut route = use_context_provider(|| Signal::new(Route::from_path(&location_path())));
+    // The listener lives as long as the router
+    let listener = use_hook(|| Rc::new(RefCell::new(None::<PopStateCallback>)));
+
+    use_effect({
+        let listener = listener.clone();
+        move || {
+            if listener.borrow().is_some() {
+                return;
+            }
+            let Some(window) = web_sys::window() else {
+                return;
+            };
+            let callback = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+                route.set(Route::from_path(&location_path()));
+            });
+            match window
+                .add_event_listener_with_callback("popstate", callback.as_ref().unchecked_ref())
+            {
+                Ok(()) => *listener.borrow_mut() = Some(callback),
+                Err(err) => error!("Error listening for navigation: {:?}", err),
+            }
+        }
+    });
+
+    use_drop(move || {
+        if let (Some(callback), Some(window)) = (listener.borrow_mut().take(), web_sys::window()) {
+            if let Err(err) = window
+                .remove_event_listener_with_callback("popstate", callback.as_ref().unchecked_ref())
+            {
+                error!("Error removing navigation listener: {:?}", err);
+            }
+        }
+    });
+
+    rsx! {
+        div {
+            class: "flex",
+            Sidebar {}
+            div {
+                class: "flex-1 min-w-0 p-2",
+                match route() {
+                    Some(Route::KMeans) => rsx! {
+                        KMeansComponent { k: 5, max_iter: 100, tolerance: 1e-4 }
+                    },
+                    Some(Route::LinearRegression) => rsx! { LinearRegressionPage {} },
+                    Some(Route::Tsne) => rsx! { TsnePage {} },
+                    None => rsx! {
+                        p { "No page at {location_path()}." }
+                    },
+                }
+            }
+        }
+    }
+}
+
+// The pages by section, the one shown highlighted
+#[component]
+fn Sidebar() -> Element {
+    let mut sections: Vec<&'static str> = Route::ALL.iter().map(Route::section).collect();
+    sections.dedup();
+
+    rsx! {
+        nav {
+            class: "w-48 shrink-0 p-2 border-r text-sm",
+            for section in sections {
+                h3 { key: "{section}", class: "mt-2 font-semibold", "{section}" }
+                for route in Route::ALL.into_iter().filter(|route| route.section() == section) {
+                    Link { key: "{route.path()}", to: route, "{route.title()}" }
+                }
+            }
+        }
+    }
+}
+
+// Link to a page that switches to it without reloading the app. Clicks
+// with a modifier held are left to the browser, e.g. to open a new tab.
+#[component]
+pub fn Link(to: Route, children: Element) -> Element {
+    let route = use_route();
+    let class = if route() == Some(to) {
+        "block px-2 rounded bg-blue-100 font-semibold"
+    } else {
+        "block px-2 rounded hover:underline"
+    };
+
+    rsx! {
+        a {
+            href: to.href(),
+            class,
+            onclick: move |event: MouseEvent| {
+                let modifiers = event.modifiers();
+                if modifiers.ctrl() || modifiers.meta() || modifiers.shift() {
+                    return;
+                }
+                event.prevent_default();
+                navigate(route, to);
+            },
+            {children}
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Values kept in the browser across page reloads: datasets, parameters and
+// fitted models, as JSON under a key. They go to IndexedDB, or to
+// localStorage where IndexedDB can't be opened, e.g. in some private
+// windows, as long as they are small enough for it.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Array, Promise};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+const DATABASE: &str = "moonlight";
+const DATABASE_VERSION: u32 = 1;
+const STORE: &str = "values";
+// Prefix of the app's keys in localStorage, which is shared by the origin
+const LOCAL_PREFIX: &str = "moonlight/";
+// Largest value, in bytes of JSON, kept in localStorage, whose quota is a
+// few MB for the whole origin
+const LOCAL_STORAGE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    // Neither IndexedDB nor localStorage, e.g. outside a browser
+    Unavailable,
+    // Over LOCAL_STORAGE_LIMIT without IndexedDB
+    TooLarge(usize),
+    Encode(String),
+    Decode(String),
+    // A failed IndexedDB or localStorage call
+    Browser(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "no browser storage available"),
+            StorageError::TooLarge(size) => write!(
+                f,
+                "{} bytes is too large for localStorage, the limit is {}",
+                size, LOCAL_STORAGE_LIMIT
+            ),
+            StorageError::Encode(message) => write!(f, "cannot encode value: {}", message),
+            StorageError::Decode(message) => write!(f, "cannot decode value: {}", message),
+            StorageError::Browser(message) => write!(f, "storage failed: {}", message),
+        }
+    }
+}
+
+impl Error for StorageError {}
+
+fn js_error(err: JsValue) -> StorageError {
+    StorageError::Browser(err.as_string().unwrap_or_else(|| format!("{:?}", err)))
+}
+
+pub async fn save<T: Serialize + ?Sized>(key: &str, value: &T) -> Result<(), StorageError> {
+    let json = serde_json::to_string(value).map_err(|err| StorageError::Encode(err.to_string()))?;
+    match backend().await? {
+        Backend::IndexedDb(db) => {
+            let store = store(&db, IdbTransactionMode::Readwrite)?;
+            let request = store
+                .put_with_key(&JsValue::from_str(&json), &JsValue::from_str(key))
+                .map_err(js_error)?;
+            finish(&request).await.map(|_| ())
+        }
+        Backend::Local(storage) => {
+            if json.len() > LOCAL_STORAGE_LIMIT {
+                return Err(StorageError::TooLarge(json.len()));
+            }
+            storage
+                .set_item(&format!("{}{}", LOCAL_PREFIX, key), &json)
+                .map_err(js_error)
+        }
+    }
+}
+
+// The value saved under key, None if there is none
+pub async fn load<T: DeserializeOwned>(key: &str) -> Result<Option<T>, StorageError> {
+    let json = match backend().await? {
+        Backend::IndexedDb(db) => {
+            let store = store(&db, IdbTransactionMode::Readonly)?;
+            let request = store.get(&JsValue::from_str(key)).map_err(js_error)?;
+            finish(&request).await?.as_string()
+        }
+        Backend::Local(storage) => storage
+            .get_item(&format!("{}{}", LOCAL_PREFIX, key))
+            .map_err(js_error)?,
+    };
+    json.map(|json| {
+        serde_json::from_str(&json).map_err(|err| StorageError::Decode(err.to_string()))
+    })
+    .transpose()
+}
+
+// Keys of every saved value, sorted
+pub async fn list() -> Result<Vec<String>, StorageError> {
+    let mut keys: Vec<String> = match backend().await? {
+        Backend::IndexedDb(db) => {
+            let store = store(&db, IdbTransactionMode::Readonly)?;
+            let request = store.get_all_keys().map_err(js_error)?;
+            Array::from(&finish(&request).await?)
+                .iter()
+                .filter_map(|key| key.as_string())
+                .collect()
+        }
+        Backend::Local(storage) => {
+            let length = storage.length().map_err(js_error)?;
+            (0..length)
+                .filter_map(|i| storage.key(i).ok().flatten())
+                .filter_map(|key| key.strip_prefix(LOCAL_PREFIX).map(str::to_string))
+                .collect()
+        }
+    };
+    keys.sort();
+    Ok(keys)
+}
+
+pub async fn remove(key: &str) -> Result<(), StorageError> {
+    match backend().await? {
+        Backend::IndexedDb(db) => {
+            let store = store(&db, IdbTransactionMode::Readwrite)?;
+            let request = store.delete(&JsValue::from_str(key)).map_err(js_error)?;
+            finish(&request).await.map(|_| ())
+        }
+        Backend::Local(storage) => storage
+            .remove_item(&format!("{}{}", LOCAL_PREFIX, key))
+            .map_err(js_error),
+    }
+}
+
+enum Backend {
+    IndexedDb(IdbDatabase),
+    Local(web_sys::Storage),
+}
+
+async fn backend() -> Result<Backend, StorageError> {
+    if !cfg!(target_arch = "wasm32") {
+        return Err(StorageError::Unavailable);
+    }
+    match open().await {
+        Ok(db) => Ok(Backend::IndexedDb(db)),
+        Err(err) => web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .map(Backend::Local)
+            .ok_or(err),
+    }
+}
+
+// The app's database, created with its one store on the first visit
+async fn open() -> Result<IdbDatabase, StorageError> {
+    let factory = web_sys::window()
+        .ok_or(StorageError::Unavailable)?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or(StorageError::Unavailable)?;
+    let request = factory
+        .open_with_u32(DATABASE, DATABASE_VERSION)
+        .map_err(js_error)?;
+    let upgrading = request.clone();
+    let on_upgrade = Closure::once_into_js(move || {
+        if let Ok(db) = upgrading.result() {
+            db.unchecked_into::<IdbDatabase>()
+                .create_object_store(STORE)
+                .ok();
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(finish(&request).await?.unchecked_into())
+}
+
+fn store(db: &IdbDatabase, mode: IdbTransactionMode) -> Result<IdbObjectStore, StorageError> {
+    db.transaction_with_str_and_mode(STORE, mode)
+        .and_then(|transaction| transaction.object_store(STORE))
+        .map_err(js_error)
+}
+
+// The result of an IndexedDB request once it has succeeded
+async fn finish(request: &IdbRequest) -> Result<JsValue, StorageError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = succeeded.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::NULL, &result).ok();
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let message = failed
+                .error()
+                .ok()
+                .flatten()
+                .map_or_else(|| "request failed".to_string(), |err| err.message());
+            reject
+                .call1(&JsValue::NULL, &JsValue::from_str(&message))
+                .ok();
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map_err(js_error)
+}
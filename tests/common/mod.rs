@@ -0,0 +1,80 @@
+// Property checks without a framework: a property runs on CASES inputs drawn
+// from rngs seeded 0, 1, 2, ..., and a failure names the seed reproducing
+// it. MOONLIGHT_SEED runs that one seed alone.
+#![allow(dead_code)]
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// Point in n-dimensional space
+pub type Point = Vec<f64>;
+
+pub const CASES: u64 = 32;
+
+pub fn check(name: &str, mut property: impl FnMut(&mut StdRng)) {
+    let seeds = match std::env::var("MOONLIGHT_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+    {
+        Some(seed) => seed..seed + 1,
+        None => 0..CASES,
+    };
+    for seed in seeds {
+        let mut rng = StdRng::seed_from_u64(seed);
+        if catch_unwind(AssertUnwindSafe(|| property(&mut rng))).is_err() {
+            panic!("{} failed for MOONLIGHT_SEED={}", name, seed);
+        }
+    }
+}
+
+// Between min and max points of between 1 and max_features features, the
+// same for every point, uniform in [-10, 10)
+pub fn points(rng: &mut StdRng, min: usize, max: usize, max_features: usize) -> Vec<Point> {
+    let n = rng.gen_range(min..=max);
+    let n_features = rng.gen_range(1..=max_features);
+    (0..n)
+        .map(|_| {
+            (0..n_features)
+                .map(|_| rng.gen_range(-10.0..10.0))
+                .collect()
+        })
+        .collect()
+}
+
+// A label in 0..n_classes per point, with every class present when there
+// are enough points
+pub fn labels(rng: &mut StdRng, n: usize, n_classes: usize) -> Vec<usize> {
+    (0..n)
+        .map(|i| {
+            if i < n_classes {
+                i
+            } else {
+                rng.gen_range(0..n_classes)
+            }
+        })
+        .collect()
+}
+
+pub fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+pub fn assert_all_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{:?} vs {:?}",
+        actual,
+        expected
+    );
+    for (&a, &e) in actual.iter().zip(expected) {
+        assert_close(a, e, tolerance);
+    }
+}
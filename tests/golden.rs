@@ -0,0 +1,319 @@
+// Fits on small datasets whose exact result is known, worked out by hand
+// or matching scikit-learn's for the same data and parameters
+mod common;
+
+use common::{assert_all_close, assert_close, Point};
+use moonlight::ml::base::{Estimator, Predictor};
+use moonlight::ml::classification::logistic::LogisticRegression;
+use moonlight::ml::classification::naive_bayes::GaussianNB;
+use moonlight::ml::classification::Classifier;
+use moonlight::ml::clustering::dbscan::Dbscan;
+use moonlight::ml::clustering::gmm::GaussianMixture;
+use moonlight::ml::clustering::hierarchical::{AgglomerativeClustering, Linkage};
+use moonlight::ml::clustering::kmeans::{Init, KMeans};
+use moonlight::ml::decomposition::pca::Pca;
+use moonlight::ml::ensemble::gbm::GradientBoostingRegressor;
+use moonlight::ml::ensemble::random_forest::RandomForestClassifier;
+use moonlight::ml::metrics::classification::accuracy_score;
+use moonlight::ml::metrics::regression::{mean_squared_error, r2_score};
+use moonlight::ml::neighbors::knn::KNeighborsClassifier;
+use moonlight::ml::preprocessing::encoders::OneHotEncoder;
+use moonlight::ml::preprocessing::imputers::{SimpleImputer, Strategy};
+use moonlight::ml::preprocessing::polynomial::PolynomialFeatures;
+use moonlight::ml::preprocessing::scalers::{MinMaxScaler, RobustScaler, StandardScaler};
+use moonlight::ml::regression::linear::LinearRegression;
+use moonlight::ml::regression::regularized::{Lasso, Ridge};
+use moonlight::ml::regression::LinearModel;
+use moonlight::ml::tree::decision_tree::{DecisionTreeClassifier, DecisionTreeRegressor};
+use moonlight::ml::tree::TreeNode;
+
+fn column(values: &[f64]) -> Vec<Point> {
+    values.iter().map(|&v| vec![v]).collect()
+}
+
+fn sorted(mut rows: Vec<Point>) -> Vec<Point> {
+    rows.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    rows
+}
+
+#[test]
+fn linear_regression_recovers_a_line() {
+    let x = column(&[0.0, 1.0, 2.0, 3.0, 4.0]);
+    let y = [1.0, 3.0, 5.0, 7.0, 9.0];
+    let mut model = LinearRegression::new();
+    model.fit(&x, &y);
+    assert_all_close(model.coefficients(), &[2.0], 1e-9);
+    assert_close(model.intercept(), 1.0, 1e-9);
+    assert_all_close(&model.predict(&column(&[10.0])), &[21.0], 1e-9);
+}
+
+// x = [1, 2, 3, 4] and y = 2x centered: sum(x²) = 5, sum(xy) = 10, and the
+// intercept isn't penalized
+#[test]
+fn ridge_shrinks_the_slope() {
+    let x = column(&[1.0, 2.0, 3.0, 4.0]);
+    let y = [2.0, 4.0, 6.0, 8.0];
+    let mut model = Ridge::new(1.0);
+    model.fit(&x, &y);
+    let slope = 10.0 / 6.0;
+    assert_all_close(model.coefficients(), &[slope], 1e-9);
+    assert_close(model.intercept(), 5.0 - slope * 2.5, 1e-9);
+}
+
+// Soft-thresholding sum(xy) / n = 2.5 by alpha, over sum(x²) / n = 1.25
+#[test]
+fn lasso_soft_thresholds_the_slope() {
+    let x = column(&[1.0, 2.0, 3.0, 4.0]);
+    let y = [2.0, 4.0, 6.0, 8.0];
+    let mut model = Lasso::new(1.0);
+    model.fit(&x, &y);
+    assert_all_close(model.coefficients(), &[1.2], 1e-6);
+    assert_close(model.intercept(), 2.0, 1e-6);
+
+    let mut model = Lasso::new(10.0);
+    model.fit(&x, &y);
+    assert_all_close(model.coefficients(), &[0.0], 1e-12);
+    assert_close(model.intercept(), 5.0, 1e-12);
+}
+
+#[test]
+fn logistic_regression_is_even_on_symmetric_data() {
+    let x = column(&[-2.0, -1.0, 1.0, 2.0]);
+    let y = [0, 0, 1, 1];
+    let mut model = LogisticRegression::new();
+    model.fit(&x, &y);
+    let probabilities = model.predict_proba(&column(&[0.0, -1.5, 1.5]));
+    assert_all_close(&probabilities[0], &[0.5, 0.5], 1e-6);
+    assert_close(probabilities[1][0], probabilities[2][1], 1e-6);
+    assert_eq!(model.predict(&x), y);
+}
+
+#[test]
+fn gaussian_nb_estimates_per_class_moments() {
+    let x = column(&[0.0, 2.0, 10.0, 14.0]);
+    let y = [0, 0, 1, 1];
+    let mut model = GaussianNB::new();
+    model.fit(&x, &y);
+    assert_eq!(model.means(), [vec![1.0], vec![12.0]]);
+    // Plus var_smoothing times the largest feature variance, 32.75
+    let epsilon = model.var_smoothing * 32.75;
+    assert_all_close(&model.variances()[0], &[1.0 + epsilon], 1e-12);
+    assert_all_close(&model.variances()[1], &[4.0 + epsilon], 1e-12);
+    // The wider class 1 takes over well before the midpoint between the means
+    assert_eq!(
+        model.predict(&column(&[-1.0, 3.0, 5.0, 20.0])),
+        [0, 0, 1, 1]
+    );
+}
+
+#[test]
+fn kmeans_finds_the_cluster_means() {
+    let x = vec![
+        vec![0.0, 0.0],
+        vec![0.0, 2.0],
+        vec![10.0, 10.0],
+        vec![10.0, 12.0],
+    ];
+    let mut model = KMeans::new(2).with_random_state(0);
+    model.fit(&x);
+    assert_eq!(
+        sorted(model.centroids().to_vec()),
+        [vec![0.0, 1.0], vec![10.0, 11.0]]
+    );
+    assert_close(model.inertia(), 4.0, 1e-12);
+}
+
+#[test]
+fn gaussian_mixture_finds_the_component_means() {
+    let x = column(&[-11.0, -10.0, -9.0, 9.0, 10.0, 11.0]);
+    let mut model = GaussianMixture::new(2).with_random_state(0);
+    model.fit(&x);
+    let means = sorted(model.means().to_vec());
+    assert_all_close(&means[0], &[-10.0], 1e-6);
+    assert_all_close(&means[1], &[10.0], 1e-6);
+    assert_all_close(model.weights(), &[0.5, 0.5], 1e-6);
+}
+
+#[test]
+fn dbscan_chains_neighbors_and_leaves_noise() {
+    let x = column(&[0.0, 1.0, 2.0, 10.0, 11.0, 50.0]);
+    let mut model = Dbscan::new(1.5, 2);
+    model.fit(&x);
+    assert_eq!(
+        model.labels(),
+        [Some(0), Some(0), Some(0), Some(1), Some(1), None]
+    );
+    assert_eq!(model.core_samples(), [0, 1, 2, 3, 4]);
+    assert_eq!(model.n_clusters(), 2);
+}
+
+#[test]
+fn single_linkage_merges_at_the_gaps() {
+    let x = column(&[0.0, 1.0, 3.0, 7.0]);
+    let mut model = AgglomerativeClustering::new(Linkage::Single);
+    model.fit(&x);
+    let heights: Vec<f64> = model.merges().iter().map(|m| m.distance).collect();
+    assert_eq!(heights, [1.0, 2.0, 4.0]);
+    assert_eq!(model.cut(2), [0, 0, 0, 1]);
+}
+
+#[test]
+fn pca_finds_the_direction_of_a_line() {
+    let x: Vec<Point> = [1.0, 2.0, 3.0, 4.0]
+        .iter()
+        .map(|&v| vec![v, 2.0 * v])
+        .collect();
+    let mut model = Pca::new(2);
+    model.fit(&x);
+    let direction = [1.0 / 5f64.sqrt(), 2.0 / 5f64.sqrt()];
+    let sign = model.components()[0][0].signum();
+    assert_all_close(
+        &model.components()[0],
+        &[sign * direction[0], sign * direction[1]],
+        1e-9,
+    );
+    assert_all_close(model.explained_variance_ratio(), &[1.0, 0.0], 1e-9);
+    assert_all_close(model.mean(), &[2.5, 5.0], 1e-12);
+}
+
+#[test]
+fn scalers_learn_their_statistics() {
+    let x = column(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let mut standard = StandardScaler::new();
+    let scaled = standard.fit_transform(&x);
+    assert_eq!(standard.mean(), [3.0]);
+    assert_all_close(standard.scale(), &[2f64.sqrt()], 1e-12);
+    assert_all_close(&scaled[0], &[-2.0 / 2f64.sqrt()], 1e-12);
+
+    let mut min_max = MinMaxScaler::new();
+    let scaled = min_max.fit_transform(&x);
+    assert_eq!(scaled, column(&[0.0, 0.25, 0.5, 0.75, 1.0]));
+
+    let mut robust = RobustScaler::new();
+    let scaled = robust.fit_transform(&x);
+    assert_eq!((robust.center(), robust.scale()), (&[3.0][..], &[2.0][..]));
+    assert_eq!(scaled, column(&[-1.0, -0.5, 0.0, 0.5, 1.0]));
+}
+
+#[test]
+fn encoders_and_imputers_fill_in_known_values() {
+    let mut encoder = OneHotEncoder::new();
+    let encoded = encoder
+        .fit_transform(&[vec!["b", "x"], vec!["a", "y"], vec!["b", "y"]])
+        .expect("known categories");
+    assert_eq!(
+        encoded,
+        [
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0, 1.0],
+            vec![0.0, 1.0, 0.0, 1.0]
+        ]
+    );
+
+    let mut imputer = SimpleImputer::new(Strategy::Mean);
+    let imputed =
+        imputer.fit_transform(&[vec![1.0, f64::NAN], vec![3.0, 4.0], vec![f64::NAN, 8.0]]);
+    assert_eq!(imputer.statistics(), [2.0, 6.0]);
+    assert_eq!(imputed, [vec![1.0, 6.0], vec![3.0, 4.0], vec![2.0, 8.0]]);
+}
+
+#[test]
+fn polynomial_features_follow_sklearn_order() {
+    let mut model = PolynomialFeatures::new(2);
+    assert_eq!(
+        model.fit_transform(&[vec![2.0, 3.0]]),
+        [vec![1.0, 2.0, 3.0, 4.0, 6.0, 9.0]]
+    );
+    assert_eq!(
+        model.feature_names_out(&["a", "b"]),
+        ["1", "a", "b", "a^2", "a b", "b^2"]
+    );
+}
+
+#[test]
+fn nearest_neighbor_copies_the_closest_label() {
+    let x = column(&[0.0, 1.0, 5.0, 6.0]);
+    let y = [0, 0, 1, 1];
+    let mut model = KNeighborsClassifier::new(1);
+    model.fit(&x, &y);
+    assert_eq!(model.predict(&column(&[-3.0, 2.9, 3.1, 9.0])), [0, 0, 1, 1]);
+}
+
+#[test]
+fn trees_split_halfway_between_classes() {
+    let x = column(&[0.0, 1.0, 2.0, 3.0]);
+    let mut classifier = DecisionTreeClassifier::new();
+    classifier.fit(&x, &[0, 0, 1, 1]);
+    match classifier.tree() {
+        Some(TreeNode::Split {
+            feature,
+            threshold,
+            left,
+            right,
+            ..
+        }) => {
+            assert_eq!((*feature, *threshold), (0, 1.5));
+            assert_eq!(left.leaf_value(&[0.0]), [1.0, 0.0]);
+            assert_eq!(right.leaf_value(&[3.0]), [0.0, 1.0]);
+        }
+        tree => panic!("expected a single split, got {:?}", tree),
+    }
+
+    let mut regressor = DecisionTreeRegressor::new().with_max_depth(1);
+    regressor.fit(&x, &[1.0, 3.0, 10.0, 20.0]);
+    // Isolating the outlier leaves less squared error than halving the data
+    assert_all_close(
+        &regressor.predict(&column(&[0.5, 2.5, 3.0])),
+        &[14.0 / 3.0, 14.0 / 3.0, 20.0],
+        1e-12,
+    );
+}
+
+#[test]
+fn ensembles_fit_separable_data() {
+    let x = column(&[0.0, 1.0, 2.0, 3.0, 10.0, 11.0, 12.0, 13.0]);
+    let y = [0, 0, 0, 0, 1, 1, 1, 1];
+    let mut forest = RandomForestClassifier::new(10).with_random_state(0);
+    forest.fit(&x, &y);
+    assert_eq!(
+        forest.predict(&column(&[-5.0, 1.5, 11.5, 20.0])),
+        [0, 0, 1, 1]
+    );
+
+    // One full-depth tree at learning rate 1 fits the residuals exactly
+    let targets = [0.0, 0.0, 0.0, 0.0, 10.0, 10.0, 10.0, 10.0];
+    let mut boosting = GradientBoostingRegressor::new(1).with_learning_rate(1.0);
+    boosting.fit(&x, &targets);
+    assert_all_close(&boosting.predict(&x), &targets, 1e-12);
+}
+
+#[test]
+fn metrics_score_known_predictions() {
+    assert_eq!(r2_score(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 1.0);
+    assert_eq!(r2_score(&[1.0, 2.0, 3.0], &[2.0, 2.0, 2.0]), 0.0);
+    assert_eq!(mean_squared_error(&[1.0, 2.0], &[2.0, 4.0]), 2.5);
+    assert_eq!(accuracy_score(&[0, 1, 1, 0], &[0, 1, 0, 0]), 0.75);
+}
+
+#[test]
+fn builders_match_constructors() {
+    let built = KMeans::builder()
+        .k(3)
+        .max_iter(10)
+        .init(Init::KMeansPlusPlus)
+        .random_state(7)
+        .build();
+    let mut constructed = KMeans::new(3).with_random_state(7);
+    constructed.max_iter = 10;
+    constructed.init = Init::KMeansPlusPlus;
+    assert_eq!(format!("{:?}", built), format!("{:?}", constructed));
+
+    let built = DecisionTreeClassifier::builder().max_depth(2).build();
+    let constructed = DecisionTreeClassifier::new().with_max_depth(2);
+    assert_eq!(format!("{:?}", built), format!("{:?}", constructed));
+    assert_eq!(
+        format!("{:?}", Pca::builder().n_components(2).build()),
+        format!("{:?}", Pca::new(2))
+    );
+}
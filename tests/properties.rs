@@ -0,0 +1,759 @@
+// Invariants every fit should keep, whatever the data
+mod common;
+
+use common::{assert_all_close, assert_close, check, labels, points, Point};
+use moonlight::data::chunked::ChunkedDataset;
+use moonlight::data::frame::DataFrame;
+use moonlight::data::stream::Batch;
+use moonlight::error::MoonlightError;
+use moonlight::math::matrix::Matrix;
+use moonlight::math::simd;
+use moonlight::ml::base::{Estimator, IterativeEstimator, Predictor, Transformer};
+use moonlight::ml::classification::logistic::LogisticRegression;
+use moonlight::ml::classification::naive_bayes::GaussianNB;
+use moonlight::ml::classification::Classifier;
+use moonlight::ml::clustering::dbscan::Dbscan;
+use moonlight::ml::clustering::hierarchical::{AgglomerativeClustering, Linkage};
+use moonlight::ml::clustering::kmeans::{Algorithm, Init, KMeans};
+use moonlight::ml::decomposition::pca::Pca;
+use moonlight::ml::early_stopping::{EarlyStopping, Monitor};
+use moonlight::ml::ensemble::gbm::GradientBoostingRegressor;
+use moonlight::ml::ensemble::random_forest::RandomForestClassifier;
+use moonlight::ml::neighbors::knn::KNeighborsClassifier;
+use moonlight::ml::nn::mlp::{MlpClassifier, MlpRegressor};
+use moonlight::ml::observer::FitEvent;
+use moonlight::ml::persist::Persist;
+use moonlight::ml::pipeline::Pipeline;
+use moonlight::ml::preprocessing::imputers::{SimpleImputer, Strategy};
+use moonlight::ml::preprocessing::polynomial::PolynomialFeatures;
+use moonlight::ml::preprocessing::scalers::{MinMaxScaler, RobustScaler, StandardScaler};
+use moonlight::ml::regression::regularized::{Lasso, Ridge};
+use moonlight::ml::regression::LinearModel;
+use moonlight::ml::tree::decision_tree::{DecisionTreeClassifier, DecisionTreeRegressor};
+use rand::Rng;
+use std::future::Future;
+use std::task::{Context, Poll, Waker};
+
+fn inertias(model: &KMeans) -> Vec<f64> {
+    model
+        .history()
+        .iter()
+        .map(|snapshot| snapshot.inertia)
+        .collect()
+}
+
+#[test]
+fn kmeans_inertia_never_increases() {
+    check("kmeans_inertia_never_increases", |rng| {
+        let data = points(rng, 10, 200, 4);
+        let k = rng.gen_range(1..=5);
+        for algorithm in [Algorithm::Lloyd, Algorithm::Elkan] {
+            let mut model = KMeans::new(k)
+                .with_history(true)
+                .with_random_state(rng.gen());
+            model.algorithm = algorithm;
+            model.fit(&data);
+            let inertias = inertias(&model);
+            assert!(!inertias.is_empty());
+            for pair in inertias.windows(2) {
+                assert!(pair[1] <= pair[0] * (1.0 + 1e-12), "{:?}", inertias);
+            }
+        }
+    });
+}
+
+#[test]
+fn kmeans_plus_plus_seeds_distinct_points() {
+    check("kmeans_plus_plus_seeds_distinct_points", |rng| {
+        let data = points(rng, 1, 50, 3);
+        // No iterations, so the centroids are the seeds
+        let mut model = KMeans::builder()
+            .k(rng.gen_range(1..=data.len()))
+            .max_iter(0)
+            .init(Init::KMeansPlusPlus)
+            .random_state(rng.gen())
+            .build();
+        model.fit(&data);
+        let seeds = model.centroids();
+        assert_eq!(seeds.len(), model.k);
+        for (i, seed) in seeds.iter().enumerate() {
+            assert!(data.contains(seed));
+            assert!(!seeds[..i].contains(seed), "{:?}", seeds);
+        }
+    });
+}
+
+#[test]
+fn kmeans_assigns_the_nearest_centroid() {
+    check("kmeans_assigns_the_nearest_centroid", |rng| {
+        let data = points(rng, 5, 100, 3);
+        let mut model = KMeans::new(rng.gen_range(1..=4)).with_random_state(rng.gen());
+        model.fit(&data);
+        for (point, label) in data.iter().zip(model.predict(&data)) {
+            let distance = squared_euclidean(point, &model.centroids()[label]);
+            for centroid in model.centroids() {
+                assert!(distance <= squared_euclidean(point, centroid) + 1e-9);
+            }
+        }
+    });
+}
+
+#[test]
+fn scalers_round_trip() {
+    check("scalers_round_trip", |rng| {
+        let data = points(rng, 2, 50, 5);
+        let mut standard = StandardScaler::new();
+        let scaled = standard.fit_transform(&data);
+        assert_rows_close(&standard.inverse_transform(&scaled), &data);
+
+        let mut min_max = MinMaxScaler::new().with_feature_range(-1.0, 2.0);
+        let scaled = min_max.fit_transform(&data);
+        assert!(scaled
+            .iter()
+            .flatten()
+            .all(|&v| (-1.0 - 1e-9..=2.0 + 1e-9).contains(&v)));
+        assert_rows_close(&min_max.inverse_transform(&scaled), &data);
+
+        let mut robust = RobustScaler::new();
+        robust.fit(&data);
+        let scaled = robust.transform(&data);
+        assert_rows_close(&robust.inverse_transform(&scaled), &data);
+    });
+}
+
+#[test]
+fn pca_with_every_component_round_trips() {
+    check("pca_with_every_component_round_trips", |rng| {
+        let data = points(rng, 6, 60, 4);
+        let mut model = Pca::new(data[0].len());
+        let projected = model.fit_transform(&data);
+        assert_rows_close(&model.inverse_transform(&projected), &data);
+        let total: f64 = model.explained_variance_ratio().iter().sum();
+        assert_close(total, 1.0, 1e-9);
+        for pair in model.explained_variance().windows(2) {
+            assert!(pair[0] >= pair[1] - 1e-9);
+        }
+    });
+}
+
+#[test]
+fn tree_predictions_are_constant_per_leaf() {
+    check("tree_predictions_are_constant_per_leaf", |rng| {
+        let data = points(rng, 5, 80, 3);
+        let classes = labels(rng, data.len(), 3);
+        let targets: Vec<f64> = data
+            .iter()
+            .map(|p| p[0] * p[0] + rng.gen::<f64>())
+            .collect();
+
+        let mut classifier = DecisionTreeClassifier::new();
+        classifier.max_depth = Some(rng.gen_range(1..6));
+        classifier.fit(&data, &classes);
+        let tree = classifier.tree().expect("fitted");
+        let predictions = classifier.predict(&data);
+        let probabilities = classifier.predict_proba(&data);
+        for (i, point) in data.iter().enumerate() {
+            let leaf = tree.leaf_value(point);
+            assert_eq!(probabilities[i], leaf);
+            for (j, other) in data.iter().enumerate() {
+                if std::ptr::eq(leaf, tree.leaf_value(other)) {
+                    assert_eq!(predictions[i], predictions[j]);
+                }
+            }
+        }
+
+        let mut regressor = DecisionTreeRegressor::new();
+        regressor.max_depth = Some(rng.gen_range(1..6));
+        regressor.fit(&data, &targets);
+        let tree = regressor.tree().expect("fitted");
+        for (point, prediction) in data.iter().zip(regressor.predict(&data)) {
+            assert_eq!(prediction, tree.leaf_value(point)[0]);
+        }
+    });
+}
+
+#[test]
+fn unrestricted_tree_fits_distinct_points_exactly() {
+    check("unrestricted_tree_fits_distinct_points_exactly", |rng| {
+        let data = points(rng, 2, 60, 3);
+        let classes = labels(rng, data.len(), 4);
+        let mut model = DecisionTreeClassifier::new();
+        model.fit(&data, &classes);
+        assert_eq!(model.predict(&data), classes);
+    });
+}
+
+#[test]
+fn class_probabilities_sum_to_one() {
+    check("class_probabilities_sum_to_one", |rng| {
+        let data = points(rng, 6, 60, 3);
+        let classes = labels(rng, data.len(), 3);
+        let queries = points(rng, 1, 10, 3)
+            .into_iter()
+            .map(|p| p.into_iter().cycle().take(data[0].len()).collect())
+            .collect::<Vec<Point>>();
+
+        let mut logistic = LogisticRegression::new();
+        logistic.max_iter = 50;
+        logistic.fit(&data, &classes);
+        let mut bayes = GaussianNB::new();
+        bayes.fit(&data, &classes);
+        let mut forest = RandomForestClassifier::new(5).with_random_state(rng.gen());
+        forest.fit(&data, &classes);
+        let mut knn = KNeighborsClassifier::new(3);
+        knn.fit(&data, &classes);
+
+        for probabilities in [
+            logistic.predict_proba(&queries),
+            bayes.predict_proba(&queries),
+            Classifier::predict_proba(&forest, &queries),
+            knn.predict_proba(&queries),
+        ] {
+            for row in probabilities {
+                assert!(row.iter().all(|&p| (0.0..=1.0 + 1e-12).contains(&p)));
+                assert_close(row.iter().sum(), 1.0, 1e-9);
+            }
+        }
+    });
+}
+
+#[test]
+fn dbscan_core_samples_have_enough_neighbors() {
+    check("dbscan_core_samples_have_enough_neighbors", |rng| {
+        let data = points(rng, 1, 80, 2);
+        let eps = rng.gen_range(0.5..4.0);
+        let min_samples = rng.gen_range(1..6);
+        let mut model = Dbscan::new(eps, min_samples);
+        model.fit(&data);
+        let neighbors = |i: usize| {
+            data.iter()
+                .filter(|other| squared_euclidean(&data[i], other).sqrt() <= eps)
+                .count()
+        };
+        for i in 0..data.len() {
+            let is_core = model.core_samples().contains(&i);
+            assert_eq!(is_core, neighbors(i) >= min_samples);
+            if is_core {
+                assert!(model.labels()[i].is_some());
+            }
+        }
+    });
+}
+
+#[test]
+fn single_linkage_merges_at_increasing_heights() {
+    check("single_linkage_merges_at_increasing_heights", |rng| {
+        let data = points(rng, 2, 40, 3);
+        for linkage in [
+            Linkage::Single,
+            Linkage::Complete,
+            Linkage::Average,
+            Linkage::Ward,
+        ] {
+            let mut model = AgglomerativeClustering::new(linkage);
+            model.fit(&data);
+            assert_eq!(model.merges().len(), data.len() - 1);
+            assert_eq!(model.merges().last().map(|m| m.size), Some(data.len()));
+            for pair in model.merges().windows(2) {
+                assert!(pair[1].distance >= pair[0].distance - 1e-9, "{:?}", linkage);
+            }
+        }
+    });
+}
+
+#[test]
+fn saved_models_predict_the_same() {
+    check("saved_models_predict_the_same", |rng| {
+        let data = points(rng, 6, 40, 3);
+        let classes = labels(rng, data.len(), 2);
+        let mut kmeans = KMeans::new(2).with_random_state(rng.gen());
+        kmeans.fit(&data);
+        let json = kmeans.save_json().expect("saved");
+        let loaded: KMeans = KMeans::load_json(&json).expect("loaded");
+        assert_eq!(loaded.predict(&data), kmeans.predict(&data));
+
+        let mut forest = RandomForestClassifier::new(3).with_random_state(rng.gen());
+        forest.fit(&data, &classes);
+        let bytes = forest.save_binary().expect("saved");
+        let loaded = RandomForestClassifier::load_binary(&bytes).expect("loaded");
+        assert_eq!(loaded.predict(&data), forest.predict(&data));
+    });
+}
+
+#[test]
+fn polynomial_features_match_their_count() {
+    check("polynomial_features_match_their_count", |rng| {
+        let data = points(rng, 1, 10, 4);
+        let mut model = PolynomialFeatures::new(rng.gen_range(1..4))
+            .with_interaction_only(rng.gen())
+            .with_include_bias(rng.gen());
+        let transformed = model.fit_transform(&data);
+        assert!(transformed
+            .iter()
+            .all(|row| row.len() == model.n_output_features()));
+    });
+}
+
+#[test]
+fn matrix_rows_and_kernels_agree_with_points() {
+    check("matrix_rows_and_kernels_agree_with_points", |rng| {
+        let data = points(rng, 0, 30, 40);
+        let matrix = Matrix::from_rows(&data).unwrap();
+        assert_eq!(matrix.to_points(), data);
+        if !data.is_empty() {
+            let mut ragged = data.clone();
+            ragged.push(vec![0.0; rng.gen_range(41..45)]);
+            assert_eq!(Matrix::from_rows(&ragged), None);
+        }
+        if let [a, b, ..] = data.as_slice() {
+            let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            assert_close(simd::dot(a, b), dot, 1e-12);
+            assert_close(
+                simd::squared_euclidean(a, b),
+                squared_euclidean(a, b),
+                1e-12,
+            );
+            let (a32, b32) = (to_f32(a), to_f32(b));
+            assert_close(simd::dot(&a32, &b32) as f64, dot, 1e-2);
+            assert_close(
+                simd::squared_euclidean(&a32, &b32) as f64,
+                squared_euclidean(a, b),
+                1e-2,
+            );
+        }
+    });
+}
+
+#[test]
+fn frame_matrix_matches_points() {
+    check("frame_matrix_matches_points", |rng| {
+        let data = points(rng, 0, 30, 3);
+        let names = ["a", "b", "c"];
+        let names = &names[..data.first().map_or(3, Vec::len)];
+        let frame = DataFrame::from_points(&data, names).unwrap();
+        let matrix = frame.to_matrix(names).unwrap();
+        assert_eq!(matrix.to_points(), frame.to_points(names).unwrap());
+        assert_eq!(matrix.n_cols(), names.len());
+    });
+}
+
+#[test]
+fn f32_kmeans_clusters_like_f64() {
+    check("f32_kmeans_clusters_like_f64", |rng| {
+        // Blobs far apart, so that rounding can't change an assignment
+        let data: Vec<Point> = (0..rng.gen_range(3..40))
+            .map(|i| {
+                let center = 100.0 * (i % 3) as f64;
+                vec![center + rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)]
+            })
+            .collect();
+        let mut model = KMeans::new(3).with_random_state(rng.gen());
+        let mut model32 = model.clone().cast::<f32>();
+        model.fit(&data);
+        model32.fit_matrix(&Matrix::from_rows(&data).unwrap().cast());
+        assert_eq!(model32.labels(), model.labels());
+        assert_close(model32.inertia(), model.inertia(), 1e-3);
+        for (centroid, centroid32) in model.centroids().iter().zip(model32.centroids()) {
+            let centroid32: Point = centroid32.iter().map(|&v| v as f64).collect();
+            assert_all_close(&centroid32, centroid, 1e-4);
+        }
+    });
+}
+
+#[test]
+fn matrix_fits_match_point_fits() {
+    check("matrix_fits_match_point_fits", |rng| {
+        let data = points(rng, 5, 60, 4);
+        let matrix = Matrix::from_rows(&data).unwrap();
+        let classes = labels(rng, data.len(), 3);
+        let values: Vec<f64> = data.iter().map(|point| point[0] * 2.0 + 1.0).collect();
+
+        let mut logistic = LogisticRegression::new();
+        logistic.fit(&data, &classes);
+        let mut logistic_matrix = LogisticRegression::new();
+        Estimator::fit_matrix(&mut logistic_matrix, &matrix, &classes);
+        assert_eq!(logistic_matrix.coefficients(), logistic.coefficients());
+        assert_eq!(
+            Classifier::predict_proba_matrix(&logistic, &matrix),
+            logistic.predict_proba(&data)
+        );
+        let mut lasso = Lasso::new(0.1);
+        lasso.fit(&data, &values);
+        assert_all_close(&lasso.predict_matrix(&matrix), &lasso.predict(&data), 1e-12);
+
+        let mut tree = DecisionTreeClassifier::new();
+        tree.fit_matrix(&matrix, &classes);
+        assert_eq!(tree.predict_matrix(&matrix), tree.predict(&data));
+        let mut scaler = StandardScaler::new();
+        let scaled = scaler.fit_transform_matrix(&matrix, &());
+        assert_eq!(scaled.to_points(), scaler.transform(&data));
+    });
+}
+
+#[test]
+fn f32_matrices_predict_like_f64() {
+    check("f32_matrices_predict_like_f64", |rng| {
+        // Blobs far apart, so that rounding can't change a neighbour
+        let n = rng.gen_range(6..40);
+        let data: Vec<Point> = (0..n)
+            .map(|i| {
+                let center = 10.0 * (i % 2) as f64;
+                vec![center + rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)]
+            })
+            .collect();
+        let y: Vec<usize> = (0..n).map(|i| i % 2).collect();
+        let x32 = Matrix::from_rows(&data).unwrap().cast::<f32>();
+
+        let mut knn = KNeighborsClassifier::new(3);
+        let mut knn32 = knn.clone().cast::<f32>();
+        knn.fit(&data, &y);
+        knn32.fit_matrix(&x32, &y);
+        assert_eq!(knn32.predict_matrix(&x32), knn.predict(&data));
+
+        let mut logistic = LogisticRegression::new();
+        let mut logistic32 = logistic.clone();
+        logistic.fit(&data, &y);
+        logistic32.fit_matrix(&x32, &y);
+        for (row, row32) in logistic
+            .predict_proba(&data)
+            .iter()
+            .zip(logistic32.predict_proba_matrix(&x32))
+        {
+            assert_all_close(&row32, row, 1e-3);
+        }
+
+        let values: Vec<f64> = data.iter().map(|p| 2.0 * p[0] - p[1] + 1.0).collect();
+        let mut ridge = Ridge::new(0.1);
+        let mut ridge32 = ridge.clone();
+        ridge.fit(&data, &values);
+        ridge32.fit_matrix(&x32, &values);
+        assert_all_close(ridge32.coefficients(), ridge.coefficients(), 1e-3);
+
+        let mut pca = Pca::new(2);
+        let mut pca32 = pca.clone();
+        pca.fit(&data);
+        pca32.fit_matrix(&x32);
+        assert_all_close(pca32.explained_variance(), pca.explained_variance(), 1e-3);
+    });
+}
+
+#[test]
+fn kmeans_restarts_keep_the_lowest_inertia() {
+    check("kmeans_restarts_keep_the_lowest_inertia", |rng| {
+        let data = points(rng, 10, 200, 3);
+        let k = rng.gen_range(1..=5);
+        let seed: u64 = rng.gen();
+        let n_init = rng.gen_range(2..=5);
+        let mut restarted = KMeans::new(k).with_n_init(n_init).with_random_state(seed);
+        restarted.fit(&data);
+        // Every restart is the single run from its seed
+        let lowest = (0..n_init as u64)
+            .map(|restart| {
+                let mut single = KMeans::new(k).with_random_state(seed.wrapping_add(restart));
+                single.fit(&data);
+                single.inertia()
+            })
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(restarted.inertia(), lowest);
+    });
+}
+
+#[test]
+fn observed_kmeans_reports_every_iteration() {
+    check("observed_kmeans_reports_every_iteration", |rng| {
+        let data = points(rng, 10, 200, 3);
+        let k = rng.gen_range(1..=4);
+        let seed = rng.gen();
+        let mut plain = KMeans::new(k).with_random_state(seed);
+        plain.fit(&data);
+        let mut observed = KMeans::new(k).with_random_state(seed);
+        let mut events = vec![];
+        observed.fit_observed(&data, &(), &mut |event: &FitEvent| {
+            events.push(event.clone())
+        });
+
+        assert_eq!(observed.centroids(), plain.centroids());
+        let inertias: Vec<f64> = events
+            .iter()
+            .filter_map(|event| match event {
+                FitEvent::Iteration(metrics) => metrics.get("inertia"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(inertias.len(), observed.n_iter());
+        for pair in inertias.windows(2) {
+            assert!(pair[1] <= pair[0] + 1e-9 * pair[0].max(1.0));
+        }
+        let converged = matches!(events.last(), Some(FitEvent::Converged(_)));
+        assert_eq!(converged, observed.converged());
+    });
+}
+
+#[test]
+fn observed_lasso_reports_every_sweep() {
+    check("observed_lasso_reports_every_sweep", |rng| {
+        let data = points(rng, 10, 100, 3);
+        let y: Vec<f64> = data.iter().map(|point| 2.0 * point[0] - 1.0).collect();
+        let alpha = rng.gen_range(0.01..1.0);
+        let mut plain = Lasso::new(alpha);
+        plain.fit(&data, &y);
+        let mut observed = Lasso::new(alpha);
+        let mut events = vec![];
+        observed.fit_observed(&data, &y, &mut |event: &FitEvent| {
+            events.push(event.clone())
+        });
+
+        assert_eq!(observed.coefficients(), plain.coefficients());
+        let sweeps = events
+            .iter()
+            .filter(|event| matches!(event, FitEvent::Iteration(_)))
+            .count();
+        assert_eq!(sweeps, observed.n_iter());
+        let converged = matches!(events.last(), Some(FitEvent::Converged(_)));
+        assert_eq!(converged, observed.n_iter() < observed.max_iter);
+    });
+}
+
+#[test]
+fn early_stopping_records_the_best_stage() {
+    check("early_stopping_records_the_best_stage", |rng| {
+        let data = points(rng, 10, 100, 3);
+        let y: Vec<f64> = data
+            .iter()
+            .map(|point| point[0] + rng.gen_range(-1.0..1.0))
+            .collect();
+        let patience = rng.gen_range(1..=5);
+        let early_stopping = EarlyStopping::new(patience)
+            .with_min_delta(0.0)
+            .with_monitor(Monitor::ValidationLoss(0.2));
+        let mut model = GradientBoostingRegressor::new(50)
+            .with_early_stopping(early_stopping)
+            .with_random_state(rng.gen());
+        model.fit(&data, &y);
+
+        let stages = model.trees().len();
+        let losses = model.validation_loss();
+        assert_eq!(losses.len(), stages);
+        let best = model.best_iteration().unwrap();
+        let lowest = losses.iter().cloned().fold(f64::INFINITY, f64::min);
+        assert_eq!(losses[best - 1], lowest);
+        assert!(losses[..best - 1].iter().all(|&loss| loss > lowest));
+        if stages < 50 {
+            assert_eq!(stages - best, patience);
+        }
+    });
+}
+
+#[test]
+fn chunked_dataset_regroups_chunks_into_batches() {
+    check("chunked_dataset_regroups_chunks_into_batches", |rng| {
+        let data = points(rng, 1, 200, 3);
+        let batch_size = rng.gen_range(1..=50);
+        let mut chunks = vec![];
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at(rng.gen_range(1..=rest.len().min(70)));
+            chunks.push(Batch {
+                points: chunk.to_vec(),
+                targets: vec![(); chunk.len()],
+            });
+            rest = tail;
+        }
+        let mut dataset = ChunkedDataset::new(chunks.into_iter()).with_batch_size(batch_size);
+
+        let mut batches: Vec<Vec<Point>> = vec![];
+        let n_batches =
+            block_on(dataset.for_each_batch(|batch| batches.push(batch.points.clone()))).unwrap();
+        assert_eq!(n_batches, data.len().div_ceil(batch_size));
+        assert_eq!(dataset.n_seen(), data.len());
+        assert!(batches[..n_batches - 1]
+            .iter()
+            .all(|batch| batch.len() == batch_size));
+        assert_eq!(batches.concat(), data);
+
+        // Fitting through the dataset is fitting batch by batch
+        let seed = rng.gen();
+        let mut chunked = KMeans::new(3).with_random_state(seed);
+        let mut dataset = ChunkedDataset::new(
+            vec![Batch {
+                points: data.clone(),
+                targets: vec![(); data.len()],
+            }]
+            .into_iter(),
+        )
+        .with_batch_size(batch_size);
+        block_on(dataset.partial_fit(&mut chunked)).unwrap();
+        let mut direct = KMeans::new(3).with_random_state(seed);
+        for batch in &batches {
+            direct.partial_fit(batch);
+        }
+        assert_eq!(chunked.centroids(), direct.centroids());
+    });
+}
+
+#[test]
+fn try_fit_refuses_unusable_data() {
+    check("try_fit_refuses_unusable_data", |rng| {
+        let mut data = points(rng, 2, 30, 4);
+        let n_features = data[0].len();
+        let mut model = KMeans::new(data.len() + 1);
+        assert_eq!(
+            model.try_fit(&data, &()),
+            Err(MoonlightError::TooFewSamples {
+                required: data.len() + 1,
+                n_samples: data.len()
+            })
+        );
+        model.k = 1;
+        assert_eq!(model.try_fit(&data, &()), Ok(()));
+
+        let wider = vec![vec![0.0; n_features + 1]];
+        assert_eq!(
+            model.try_predict(&wider),
+            Err(MoonlightError::DimensionMismatch {
+                expected: n_features,
+                found: n_features + 1
+            })
+        );
+
+        let (row, column) = (rng.gen_range(0..data.len()), rng.gen_range(0..n_features));
+        data[row][column] = f64::NAN;
+        assert!(matches!(
+            model.try_fit(&data, &()),
+            Err(MoonlightError::NonFinite { row: r, column: c, .. }) if (r, c) == (row, column)
+        ));
+        assert_eq!(model.try_fit(&[], &()), Err(MoonlightError::EmptyDataset));
+
+        // One target per point
+        data[row][column] = 0.0;
+        let classes = labels(rng, data.len() - 1, 2);
+        let mismatch = Err(MoonlightError::DimensionMismatch {
+            expected: data.len(),
+            found: data.len() - 1,
+        });
+        let mut tree = DecisionTreeClassifier::new();
+        assert_eq!(tree.try_fit(&data, &classes), mismatch);
+        let matrix = Matrix::from_rows(&data).unwrap();
+        assert_eq!(tree.try_fit_matrix(&matrix, &classes), mismatch);
+    });
+}
+
+// Data no model can learn from still fits to something rather than panics
+#[test]
+fn degenerate_data_fits_without_panicking() {
+    let mut agglomerative = AgglomerativeClustering::new(Linkage::Ward);
+    assert!(agglomerative.fit_predict(&[], 2).is_empty());
+    assert!(AgglomerativeClustering::new(Linkage::Single)
+        .cut(3)
+        .is_empty());
+
+    // Points without features, where trees can only be a leaf
+    let featureless: Vec<Point> = vec![vec![]; 6];
+    let (classes, values) = ([0, 1, 1, 0, 1, 1], [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let mut tree = DecisionTreeClassifier::default();
+    tree.fit(&featureless, &classes);
+    assert_eq!(tree.predict(&featureless), vec![1; 6]);
+    let mut forest = RandomForestClassifier::default();
+    forest.fit(&featureless, &classes);
+    assert_eq!(forest.predict(&featureless), vec![1; 6]);
+    let mut regressor = DecisionTreeRegressor::default();
+    regressor.fit(&featureless, &values);
+    assert_all_close(&regressor.predict(&featureless), &[3.5; 6], 1e-12);
+    let mut boosting = GradientBoostingRegressor::new(5);
+    boosting.fit(&featureless, &values);
+    assert_all_close(&boosting.predict(&featureless), &[3.5; 6], 1e-12);
+
+    // Networks without inputs, which can only learn their biases
+    let mut mlp = MlpClassifier::new(vec![4]).with_random_state(0);
+    mlp.fit(&featureless, &classes);
+    assert_eq!(mlp.predict(&featureless), vec![1; 6]);
+    let mut mlp = MlpRegressor::new(vec![4]).with_random_state(0);
+    mlp.fit(&featureless, &values);
+    assert_eq!(mlp.predict(&featureless).len(), 6);
+}
+
+#[test]
+fn only_imputers_take_missing_values() {
+    check("only_imputers_take_missing_values", |rng| {
+        let mut data = points(rng, 2, 30, 4);
+        let n_features = data[0].len();
+        let (row, column) = (rng.gen_range(0..data.len()), rng.gen_range(0..n_features));
+        data[row][column] = f64::NAN;
+
+        let mut scaler = StandardScaler::new();
+        assert!(matches!(
+            scaler.try_fit(&data, &()),
+            Err(MoonlightError::NonFinite { .. })
+        ));
+        let mut imputer = SimpleImputer::new(Strategy::Mean);
+        let imputed = imputer
+            .try_fit_transform(&data, &())
+            .expect("missing values");
+        assert!(imputed.iter().flatten().all(|v| v.is_finite()));
+        assert!(matches!(
+            imputer.try_transform(&[vec![f64::INFINITY; n_features]]),
+            Err(MoonlightError::NonFinite { .. })
+        ));
+
+        scaler.try_fit(&imputed, &()).expect("finite values");
+        assert_eq!(
+            scaler.try_transform(&[vec![0.0; n_features + 1]]),
+            Err(MoonlightError::DimensionMismatch {
+                expected: n_features,
+                found: n_features + 1
+            })
+        );
+        assert_eq!(scaler.try_transform(&[]), Ok(vec![]));
+
+        // Pipelines take what their first step takes
+        let mut scaled = Pipeline::new()
+            .with_step("scaler", StandardScaler::new())
+            .with_estimator("kmeans", KMeans::new(1));
+        assert!(matches!(
+            scaled.try_fit(&data, &()),
+            Err(MoonlightError::NonFinite { .. })
+        ));
+        let mut imputed = Pipeline::new()
+            .with_step("imputer", SimpleImputer::new(Strategy::Mean))
+            .with_step("scaler", StandardScaler::new())
+            .with_estimator("kmeans", KMeans::new(1));
+        imputed.try_fit(&data, &()).expect("missing values");
+        assert_eq!(
+            imputed.try_predict(&[vec![0.0; n_features + 1]]),
+            Err(MoonlightError::DimensionMismatch {
+                expected: n_features,
+                found: n_features + 1
+            })
+        );
+        let mut bare =
+            Pipeline::new().with_estimator("imputer", SimpleImputer::new(Strategy::Mean));
+        bare.try_fit(&data, &()).expect("missing values");
+    });
+}
+
+// Runs a future that never waits, e.g. reading chunks from memory
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("future waited"),
+    }
+}
+
+fn to_f32(point: &[f64]) -> Vec<f32> {
+    point.iter().map(|&v| v as f32).collect()
+}
+
+fn squared_euclidean(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn assert_rows_close(actual: &[Point], expected: &[Point]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert_all_close(a, e, 1e-9);
+    }
+}